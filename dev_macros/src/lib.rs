@@ -1,124 +1,350 @@
-
-
-
-// In the macro_crate/src/lib.rs
-
-// extern crate proc_macro;
-// use proc_macro::TokenStream;
-// use quote::quote;
-// use syn::*;
-
-// #[proc_macro]
-// pub fn extract_app_data(input: TokenStream) -> TokenStream {
-//     let args = parse_macro_input!(input as syn::ExprArray);
-
-//     let output = quote! {
-//         {
-//             let mut data = std::collections::HashMap::new();
-//             let content = std::fs::read_to_string("Cargo.toml").unwrap();
-//             for line in content.lines() {
-//                 #(
-//                     if line.contains(#args) {
-//                         let parts: Vec<&str> = line.split("=").map(|part| part.trim().trim_matches('"')).collect();
-//                         if parts.len() == 2 {
-//                             data.insert(#args, parts[1]);
-//                         }
-//                     }
-//                 )*
-//             }
-//             data
-//         }
-//     };
-
-//     output.into()
-// }
-
-
-
-
-
-
-
-
-
-
-
-
-// macro_rules! extract_app_data {
-//     ($path:expr, $($key:expr),*) => {{
-//         use std::collections::HashMap;
-//         use std::fs;
-
-//         let mut data = HashMap::new();
-//         let content = fs::read_to_string($path).expect("Failed to read file");
-
-//         for line in content.lines() {
-//             $(
-//                 if line.contains($key) {
-//                     let parts: Vec<&str> = line.split('=').map(|part| part.trim_matches('"').trim()).collect();
-//                     if parts.len() > 1 {
-//                         data.insert($key, parts[1]);
-//                     }
-//                 }
-//             )*
-//         }
-//         data
-//     }};
-// }
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-// pub fn add(left: usize, right: usize) -> usize {
-//     left + right
-// }
-
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-
-//     #[test]
-//     fn it_works() {
-//         let result = add(2, 2);
-//         assert_eq!(result, 4);
-//     }
-// }
+//! Procedural macros for [dev_utils](https://docs.rs/dev_utils).
+//!
+//! - [extract_app_data!]: embeds data of the Cargo.toml of the calling crate at compile time
+//! - [#\[timed\]](macro@timed): logs how long every call of a function takes
+//! - [#\[derive(EnumStr)\]](derive@EnumStr): converts the variants of an enum to and from strings
+//!
+//! These macros are re-exported by `dev_utils`, which is the intended way to use them.
+extern crate proc_macro;
+
+mod manifest;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, parse_macro_input, Data, DeriveInput, Expr, Fields, Ident, ItemFn, LitStr, Token};
+
+/// The keys selected from a section: a list of names or `*` for all of them.
+enum Keys {
+    List(Vec<LitStr>),
+    All,
+}
+
+/// The arguments of `extract_app_data!`: `"section" => [keys]` pairs.
+///
+/// `option = value` pairs are accepted and ignored, so that `app_dt!` can forward its arguments.
+struct Sections(Vec<(LitStr, Keys)>);
+
+impl Parse for Sections {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut sections = Vec::new();
+        while !input.is_empty() {
+            if input.peek(Ident) && input.peek2(Token![=]) && !input.peek2(Token![=>]) {
+                input.parse::<Ident>()?;
+                input.parse::<Token![=]>()?;
+                input.parse::<Expr>()?;
+            } else {
+                let section: LitStr = input.parse()?;
+                input.parse::<Token![=>]>()?;
+                let keys = match input.parse::<Option<Token![*]>>()? {
+                    Some(_) => Keys::All,
+                    None => {
+                        let content;
+                        bracketed!(content in input);
+                        Keys::List(Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?.into_iter().collect())
+                    },
+                };
+                sections.push((section, keys));
+            }
+            if !input.is_empty() {input.parse::<Token![,]>()?;}
+        }
+        Ok(Sections(sections))
+    }
+}
+
+/// Embeds the selected keys of the Cargo.toml of the calling crate at compile time.
+///
+/// The manifest is read when the macro is expanded (from `CARGO_MANIFEST_DIR`), so the data
+/// is available to installed binaries, and the crate is rebuilt when the manifest changes.
+///
+/// Sections are selected with `"section" => ["key", ...]` pairs (nested tables like
+/// `"package.metadata"` are supported), or `"section" => *` for all of their keys. Missing
+/// sections and keys are left out; values are rendered for display: strings without quotes,
+/// arrays as comma-separated lists and inline tables as TOML.
+///
+/// # Returns
+///
+/// Returns a `&'static [(&'static str, &'static [(&'static str, &'static str)])]` of the
+/// sections and their `(key, value)` pairs, in the order they were requested.
+///
+/// # Examples
+///
+/// ```
+/// use dev_macros::extract_app_data;
+///
+/// let data = extract_app_data!("package" => ["name", "license", "authors"], "dependencies" => *);
+/// let (section, package) = data[0];
+/// assert_eq!(section, "package");
+/// assert_eq!(package[0], ("name", env!("CARGO_PKG_NAME")));
+/// assert_eq!(package[2], ("authors", env!("CARGO_PKG_AUTHORS")));
+/// assert!(data[1].1.iter().any(|(name, _)| *name == "syn"));
+/// ```
+#[proc_macro]
+pub fn extract_app_data(input: TokenStream) -> TokenStream {
+    let Sections(sections) = parse_macro_input!(input as Sections);
+    match embed_sections(&sections) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn embed_sections(sections: &[(LitStr, Keys)]) -> syn::Result<proc_macro2::TokenStream> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(Span::call_site(), "CARGO_MANIFEST_DIR is not set (the crate must be built by cargo)"))?;
+    let path = Path::new(&manifest_dir).join("Cargo.toml");
+    let content = fs::read_to_string(&path)
+        .map_err(|err| syn::Error::new(Span::call_site(), format!("cannot read {}: {}", path.display(), err)))?;
+    let manifest = manifest::parse(&content)
+        .map_err(|err| syn::Error::new(Span::call_site(), format!("cannot parse {}: {}", path.display(), err)))?;
+
+    let sections = sections.iter().filter_map(|(section, keys)| {
+        let entries = manifest::table(&manifest, &section.value())?;
+        let pairs: Vec<(String, String)> = match keys {
+            Keys::All => entries.iter().map(|(key, value)| (key.clone(), value.display())).collect(),
+            Keys::List(keys) => keys.iter().filter_map(|key| {
+                let key = key.value();
+                entries.iter().find(|(k, _)| *k == key).map(|(_, value)| (key, value.display()))
+            }).collect(),
+        };
+        let (keys, values): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+        Some(quote! { (#section, &[#((#keys, #values)),*]) })
+    });
+
+    let path = path.to_string_lossy();
+    Ok(quote! {{
+        // makes cargo rebuild the crate when the manifest changes
+        const _: &[u8] = include_bytes!(#path);
+        const DATA: &[(&str, &[(&str, &str)])] = &[#(#sections),*];
+        DATA
+    }})
+}
+
+/// Logs how long every call of a function takes, through a
+/// [ScopedTimer](https://docs.rs/dev_utils/latest/dev_utils/performance/struct.ScopedTimer.html)
+/// labeled with the name of the function.
+///
+/// The time is logged at the `debug` level, or at the one given with
+/// `#[timed(level = "...")]` (`trace`, `debug`, `info`, `warn` or `error`). Calls of timed
+/// functions made by another timed function are indented under it.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::{dlog::*, performance::timed};
+///
+/// #[timed]
+/// fn parse(input: &str) -> Vec<u32> {
+///     input.split(',').filter_map(|n| n.parse().ok()).collect()
+/// }
+///
+/// #[timed(level = "info")]
+/// fn load() -> u32 {
+///     parse("1,2,3").iter().sum()
+/// }
+///
+/// set_max_level(Level::Debug);
+/// assert_eq!(load(), 6);
+/// // DEBUG   parse took 2.10µs
+/// // INFO  load took 10.30µs
+/// ```
+#[proc_macro_attribute]
+pub fn timed(args: TokenStream, input: TokenStream) -> TokenStream {
+    let level = match parse_level(args.into()) {
+        Ok(level) => level,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let ItemFn { attrs, vis, sig, block } = parse_macro_input!(input as ItemFn);
+    if let Some(constness) = &sig.constness {
+        return syn::Error::new_spanned(constness, "`#[timed]` cannot be used on a `const fn`").to_compile_error().into();
+    }
+    let name = sig.ident.to_string();
+    quote! {
+        #(#attrs)* #vis #sig {
+            let __timed_timer = ::dev_utils::performance::ScopedTimer::with_level(#name, ::dev_utils::dlog::Level::#level);
+            #block
+        }
+    }.into()
+}
+
+// Parses the arguments of `#[timed]`: nothing or `level = "..."`
+fn parse_level(args: proc_macro2::TokenStream) -> syn::Result<Ident> {
+    let mut level = Ident::new("Debug", Span::call_site());
+    let parser = syn::meta::parser(|meta| match meta.path.is_ident("level") {
+        true => {
+            let value: LitStr = meta.value()?.parse()?;
+            let name = match value.value().to_lowercase().as_str() {
+                "trace" => "Trace",
+                "debug" => "Debug",
+                "info" => "Info",
+                "warn" => "Warn",
+                "error" => "Error",
+                _ => return Err(syn::Error::new_spanned(&value, "expected one of: trace, debug, info, warn, error")),
+            };
+            level = Ident::new(name, value.span());
+            Ok(())
+        },
+        false => Err(meta.error("unsupported `#[timed]` argument (expected `level = \"...\"`)")),
+    });
+    syn::parse::Parser::parse2(parser, args)?;
+    Ok(level)
+}
+
+/// Derives string conversions for an enum of unit variants:
+/// - `as_str(&self) -> &'static str`, also used by a `Display` implementation
+/// - `FromStr`, with a `String` error naming the enum and the unknown value
+/// - `iter()`, an iterator over all the variants in declaration order
+///
+/// Variants are named as written, unless renamed with `#[enum_str(rename = "...")]`, or for
+/// the whole enum with `#[enum_str(rename_all = "...")]`: `lowercase`, `UPPERCASE`,
+/// `snake_case`, `SCREAMING_SNAKE_CASE` or `kebab-case`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_macros::EnumStr;
+///
+/// #[derive(Debug, PartialEq, EnumStr)]
+/// #[enum_str(rename_all = "UPPERCASE")]
+/// enum Method { Get, Post, #[enum_str(rename = "DEL")] Delete }
+///
+/// assert_eq!(Method::Get.as_str(), "GET");
+/// assert_eq!("DEL".parse::<Method>(), Ok(Method::Delete));
+/// assert_eq!("get".parse::<Method>(), Err("unknown Method: get".to_string()));
+/// assert_eq!(Method::iter().map(|m| m.to_string()).collect::<Vec<_>>(), ["GET", "POST", "DEL"]);
+/// ```
+#[proc_macro_derive(EnumStr, attributes(enum_str))]
+pub fn derive_enum_str(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match enum_str(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn enum_str(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input.ident, "`EnumStr` can only be derived for enums"));
+    };
+    let rename_all = enum_str_attr(&input.attrs, "rename_all")?;
+    if let Some(rule) = &rename_all {
+        if rename(&input.ident, &rule.value()).is_none() {
+            return Err(syn::Error::new_spanned(rule, "expected one of: lowercase, UPPERCASE, snake_case, SCREAMING_SNAKE_CASE, kebab-case"));
+        }
+    }
+
+    let mut variants = Vec::new();
+    let mut names = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(variant, "`EnumStr` only supports unit variants"));
+        }
+        let name = match (enum_str_attr(&variant.attrs, "rename")?, &rename_all) {
+            (Some(name), _) => name.value(),
+            (None, Some(rule)) => rename(&variant.ident, &rule.value()).unwrap_or_default(),
+            (None, None) => variant.ident.to_string(),
+        };
+        variants.push(&variant.ident);
+        names.push(name);
+    }
+
+    let (ident, ident_str) = (&input.ident, input.ident.to_string());
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Returns the name of the variant.
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    #(Self::#variants => #names,)*
+                }
+            }
+
+            /// Returns an iterator over all the variants, in declaration order.
+            pub fn iter() -> impl Iterator<Item = Self> {
+                [#(Self::#variants),*].into_iter()
+            }
+        }
+
+        // only `core` and `alloc` paths, so that `no_std` crates can derive it too
+        const _: () = {
+            extern crate alloc;
+
+            impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+
+            impl #impl_generics ::core::str::FromStr for #ident #ty_generics #where_clause {
+                type Err = alloc::string::String;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    match s {
+                        #(#names => ::core::result::Result::Ok(Self::#variants),)*
+                        _ => ::core::result::Result::Err(alloc::format!("unknown {}: {}", #ident_str, s)),
+                    }
+                }
+            }
+        };
+    })
+}
+
+// Returns the value of `#[enum_str(key = "...")]`, if any
+fn enum_str_attr(attrs: &[syn::Attribute], key: &str) -> syn::Result<Option<LitStr>> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("enum_str")) {
+        attr.parse_nested_meta(|meta| match meta.path.is_ident(key) {
+            true => {
+                value = Some(meta.value()?.parse()?);
+                Ok(())
+            },
+            false => Err(meta.error(format!("unsupported `enum_str` argument (expected `{} = \"...\"`)", key))),
+        })?;
+    }
+    Ok(value)
+}
+
+// Renames a variant (`HttpVersion`) with a `rename_all` rule
+fn rename(ident: &Ident, rule: &str) -> Option<String> {
+    let name = ident.to_string();
+    // the words of a PascalCase name
+    let mut words: Vec<String> = Vec::new();
+    for c in name.chars() {
+        match words.last_mut() {
+            Some(word) if !c.is_uppercase() => word.push(c),
+            _ => words.push(c.to_string()),
+        }
+    }
+    let join = |separator: &str, upper: bool| words.iter()
+        .map(|word| if upper {word.to_uppercase()} else {word.to_lowercase()})
+        .collect::<Vec<_>>().join(separator);
+    match rule {
+        "lowercase" => Some(name.to_lowercase()),
+        "UPPERCASE" => Some(name.to_uppercase()),
+        "snake_case" => Some(join("_", false)),
+        "SCREAMING_SNAKE_CASE" => Some(join("_", true)),
+        "kebab-case" => Some(join("-", false)),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename() {
+        let ident = Ident::new("HttpVersion", Span::call_site());
+        assert_eq!(rename(&ident, "lowercase").unwrap(), "httpversion");
+        assert_eq!(rename(&ident, "UPPERCASE").unwrap(), "HTTPVERSION");
+        assert_eq!(rename(&ident, "snake_case").unwrap(), "http_version");
+        assert_eq!(rename(&ident, "SCREAMING_SNAKE_CASE").unwrap(), "HTTP_VERSION");
+        assert_eq!(rename(&ident, "kebab-case").unwrap(), "http-version");
+        assert_eq!(rename(&ident, "camelCase"), None);
+    }
+}
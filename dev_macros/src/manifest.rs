@@ -0,0 +1,309 @@
+//! A small reader for the subset of TOML used by Cargo manifests.
+//!
+//! The proc macros can't depend on `dev_utils` (which depends on them), so this is a
+//! minimal stand-in for its `file::toml` parser: it keeps the tables, keys and values of a
+//! manifest and renders values for display, without converting numbers or dates.
+
+/// A parsed value. Only strings, arrays and inline tables are structured; everything
+/// else (numbers, booleans, dates) is kept as written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+    Other(String),
+}
+
+impl Value {
+    /// Renders the value for display: strings without quotes, arrays as comma-separated lists
+    /// and inline tables as TOML (`{ version = "1.0", optional = true }`).
+    pub fn display(&self) -> String {
+        match self {
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items.iter().map(Value::display).collect::<Vec<_>>().join(", "),
+            Value::Table(_) | Value::Other(_) => self.to_toml(),
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        match self {
+            Value::String(s) => format!("{:?}", s),
+            Value::Array(items) => format!("[{}]", items.iter().map(Value::to_toml).collect::<Vec<_>>().join(", ")),
+            Value::Table(entries) => match entries.is_empty() {
+                true => "{}".to_string(),
+                false => format!("{{ {} }}", entries.iter().map(|(k, v)| format!("{} = {}", k, v.to_toml())).collect::<Vec<_>>().join(", ")),
+            },
+            Value::Other(raw) => raw.clone(),
+        }
+    }
+}
+
+/// The tables of a manifest, in order of appearance (keys of the root table are in the `""` table).
+pub type Manifest = Vec<(String, Vec<(String, Value)>)>;
+
+/// Returns the entries of a table (`"package"`, `"package.metadata"`, ...).
+pub fn table<'a>(manifest: &'a Manifest, name: &str) -> Option<&'a [(String, Value)]> {
+    manifest.iter().find(|(table, _)| table == name).map(|(_, entries)| entries.as_slice())
+}
+
+/// Parses a manifest.
+///
+/// # Returns
+///
+/// Returns the [Manifest], or a message with the line of the first syntax error.
+pub fn parse(input: &str) -> Result<Manifest, String> {
+    let mut parser = Parser { chars: input.chars().collect(), pos: 0 };
+    let mut manifest: Manifest = vec![(String::new(), Vec::new())];
+    let mut current = 0;
+    loop {
+        parser.skip_blank(true);
+        match parser.peek() {
+            None => return Ok(manifest),
+            Some('[') => {
+                parser.pos += 1;
+                let array = parser.eat('[');
+                let name = parser.key()?.join(".");
+                if !parser.eat(']') || (array && !parser.eat(']')) {return Err(parser.error("expected `]`"));}
+                // entries of a repeated table (`[[bin]]`) are merged, which is enough for display
+                current = match manifest.iter().position(|(table, _)| *table == name) {
+                    Some(index) => index,
+                    None => {manifest.push((name, Vec::new())); manifest.len() - 1},
+                };
+            },
+            Some(_) => {
+                let (key, value) = parser.entry()?;
+                insert(&mut manifest[current].1, &key, value);
+            },
+        }
+        parser.skip_blank(false);
+        match parser.peek() {
+            None | Some('\n') => {},
+            Some(_) => return Err(parser.error("expected a new line")),
+        }
+    }
+}
+
+// Inserts a value at a dotted key (`version.workspace = true`), creating the inner tables
+fn insert(entries: &mut Vec<(String, Value)>, key: &[String], value: Value) {
+    let (first, rest) = key.split_first().expect("keys are never empty");
+    if rest.is_empty() {return entries.push((first.clone(), value));}
+    let index = match entries.iter().position(|(k, v)| k == first && matches!(v, Value::Table(_))) {
+        Some(index) => index,
+        None => {entries.push((first.clone(), Value::Table(Vec::new()))); entries.len() - 1},
+    };
+    if let Value::Table(inner) = &mut entries[index].1 {insert(inner, rest, value);}
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {self.chars.get(self.pos).copied()}
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {self.pos += 1;}
+        found
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars().enumerate().all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.chars[..self.pos.min(self.chars.len())].iter().filter(|&&c| c == '\n').count() + 1;
+        format!("line {}: {}", line, message)
+    }
+
+    // Skips spaces and comments (and new lines, if `newlines`)
+    fn skip_blank(&mut self, newlines: bool) {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => self.pos += 1,
+                '\n' if newlines => self.pos += 1,
+                '#' => while self.peek().is_some_and(|c| c != '\n') {self.pos += 1},
+                _ => break,
+            }
+        }
+    }
+
+    fn entry(&mut self) -> Result<(Vec<String>, Value), String> {
+        let key = self.key()?;
+        self.skip_blank(false);
+        if !self.eat('=') {return Err(self.error("expected `=`"));}
+        self.skip_blank(false);
+        Ok((key, self.value()?))
+    }
+
+    // A dotted key of bare or quoted parts
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_blank(false);
+            let part = match self.peek() {
+                Some(quote @ ('"' | '\'')) => self.string(quote)?,
+                _ => {
+                    let start = self.pos;
+                    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {self.pos += 1;}
+                    if start == self.pos {return Err(self.error("expected a key"));}
+                    self.chars[start..self.pos].iter().collect()
+                },
+            };
+            parts.push(part);
+            self.skip_blank(false);
+            if !self.eat('.') {return Ok(parts);}
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.string(quote).map(Value::String),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_blank(true);
+                    if self.eat(']') {return Ok(Value::Array(items));}
+                    items.push(self.value()?);
+                    self.skip_blank(true);
+                    if !self.eat(',') {
+                        self.skip_blank(true);
+                        return match self.eat(']') {
+                            true => Ok(Value::Array(items)),
+                            false => Err(self.error("expected `,` or `]`")),
+                        };
+                    }
+                }
+            },
+            Some('{') => {
+                self.pos += 1;
+                let mut entries = Vec::new();
+                self.skip_blank(false);
+                if self.eat('}') {return Ok(Value::Table(entries));}
+                loop {
+                    let (key, value) = self.entry()?;
+                    insert(&mut entries, &key, value);
+                    self.skip_blank(false);
+                    if self.eat('}') {return Ok(Value::Table(entries));}
+                    if !self.eat(',') {return Err(self.error("expected `,` or `}`"));}
+                }
+            },
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| !matches!(c, ',' | ']' | '}' | '#') && !c.is_whitespace()) {self.pos += 1;}
+                match self.pos - start {
+                    0 => Err(self.error("expected a value")),
+                    _ => Ok(Value::Other(self.chars[start..self.pos].iter().collect())),
+                }
+            },
+        }
+    }
+
+    // A basic (`"`) or literal (`'`) string, single or multi-line
+    fn string(&mut self, quote: char) -> Result<String, String> {
+        let delimiter: String = [quote; 3].iter().collect();
+        let multiline = self.starts_with(&delimiter);
+        self.pos += if multiline {3} else {1};
+        if multiline {
+            self.eat('\r');
+            self.eat('\n');  // a new line right after the delimiter is trimmed
+        }
+
+        let mut out = String::new();
+        loop {
+            if multiline && self.starts_with(&delimiter) {
+                self.pos += 3;
+                return Ok(out);
+            }
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('\n') if !multiline => return Err(self.error("unterminated string")),
+                Some(c) if c == quote && !multiline => {
+                    self.pos += 1;
+                    return Ok(out);
+                },
+                Some('\\') if quote == '"' => {
+                    self.pos += 1;
+                    let escaped = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match escaped {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        '"' | '\\' => out.push(escaped),
+                        'u' | 'U' => {
+                            let len = if escaped == 'u' {4} else {8};
+                            let hex: String = self.chars.iter().skip(self.pos).take(len).collect();
+                            let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+                            out.push(c);
+                            self.pos += len;
+                        },
+                        '\n' | ' ' | '\t' | '\r' if multiline => self.skip_blank(true),  // line ending backslash
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                },
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                },
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let manifest = parse(r#"
+            [package]
+            name = "app"  # comment
+            authors = [
+                "Ann <ann@example.com>",
+                'Bob',
+            ]
+            version.workspace = true
+
+            [dependencies]
+            log = { version = "0.4", features = ["std"] }
+            local = { path = "../local" }
+            serde.workspace = true
+
+            [[bin]]
+            name = "app"
+        "#).unwrap();
+
+        let package = table(&manifest, "package").unwrap();
+        assert_eq!(package[0], ("name".to_string(), Value::String("app".to_string())));
+        assert_eq!(package[1].1.display(), "Ann <ann@example.com>, Bob");
+        assert_eq!(package[2].1.display(), "{ workspace = true }");
+
+        let dependencies = table(&manifest, "dependencies").unwrap();
+        assert_eq!(dependencies[0].1.display(), r#"{ version = "0.4", features = ["std"] }"#);
+        assert_eq!(dependencies[2].0, "serde");
+        assert!(table(&manifest, "bin").is_some());
+    }
+
+    #[test]
+    fn test_strings() {
+        let manifest = parse("a = \"tab\\there \\u00e9\"\nb = '''\nraw \\n'''\nc = \"\"\"\none \\\n  two\"\"\"").unwrap();
+        let root = table(&manifest, "").unwrap();
+        assert_eq!(root[0].1.display(), "tab\there é");
+        assert_eq!(root[1].1.display(), "raw \\n");
+        assert_eq!(root[2].1.display(), "one two");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("name = \"unterminated").unwrap_err().starts_with("line 1"));
+        assert!(parse("[package]\nname").unwrap_err().starts_with("line 2"));
+        assert!(parse("a = [1, 2").is_err());
+        assert!(parse("a = 1 b = 2").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use dev_utils::{app_dt, base_change::convert_base, format::*};
+use dev_utils::{app_dt, base_change::convert_base, format::*, performance::bench};
 
 fn main() {
     app_dt!(file!());
@@ -87,16 +87,8 @@ fn showcase_error_handling() {
 fn showcase_performance() {
     println!("\n{}", "Performance Test:".style(Style::Bold).style(Style::Italic));
 
-    let iterations = 100_000;
-    let start = std::time::Instant::now();
-
-    for _ in 0..iterations {let _ = convert_base("123456789", 10, 16);}
-
-    let duration = start.elapsed();
-    println!("Time to perform {} op: {:?}", iterations, duration);
-    println!("Average time per op: {:?}", duration / iterations as u32);
-    println!("Operations per sec: {:.2}", iterations as f64 / duration.as_secs_f64());
-
+    let result = bench("convert_base(\"123456789\", 10, 16)", 100_000, || convert_base("123456789", 10, 16));
+    println!("Operations per sec: {:.2}", result.ops_per_sec());
 }
 
 // You can add this function if you want to demonstrate the precision of the conversions
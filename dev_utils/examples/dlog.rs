@@ -14,7 +14,7 @@ fn main() {
     showcase_log_use_cases();  // * gen some delay's to simulate real-world scenarios
     showcase_log_formatting();
     showcase_datetime_features();  // Not very awesome... .__. 
-    // showcase_log_performance();  // = 352.6482ms / 10000 logs (average of 10 runs)
+    // showcase_log_performance();  // = 352.6482ms / 10000 logs (sync, average of 10 runs)
 }
 
 fn showcase_log_levels() {
@@ -109,19 +109,29 @@ fn showcase_log_formatting() {
 }
 
 
-// = Time to log 10000 messages: 352.6482ms
-// = Average time per log: 35.264µs
+// = Time to log 10000 messages: 352.6482ms (sync)
+// = Average time per log: 35.264µs (sync)
 fn showcase_log_performance() {
     println!("\n{}", "Log Performance:".style(Style::Bold).style(Style::Italic));
-    
-    let iterations = 10000;
-    let start = std::time::Instant::now();
 
-    (0..iterations).for_each(|i| trace!("Performance test log {}", i));
-    
-    let duration = start.elapsed();
-    println!("Time to log {} messages: {:?}", iterations, duration);
-    println!("Average time per log: {:?}", duration / iterations as u32);
+    let iterations = 10000;
+    let bench = || {
+        let start = std::time::Instant::now();
+        (0..iterations).for_each(|i| trace!("Performance test log {}", i));
+        start.elapsed()
+    };
+
+    let sync_duration = bench();
+    // * every log from now on is written by a worker thread (the channel absorbs the whole burst)
+    let _log = enable_async(16 * 1024);
+    let async_duration = bench();
+    flush();
+
+    for (mode, duration) in [("sync", sync_duration), ("async", async_duration)] {
+        println!("Time to log {} messages ({}): {:?}", iterations, mode, duration);
+        println!("Average time per log ({}): {:?}", mode, duration / iterations as u32);
+    }
+    println!("Speedup: {:.1}x", sync_duration.as_secs_f64() / async_duration.as_secs_f64());
 }
 
 fn showcase_log_use_cases() {
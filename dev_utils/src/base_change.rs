@@ -16,7 +16,10 @@
 //! assert_eq!(convert_base("1010", 2, 10).unwrap(), "10");
 //! assert_eq!(convert_base("FF", 16, 10).unwrap(), "255");
 //! ```
-use std::fmt;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt;
 
 /// A custom arbitrary-precision unsigned integer implementation.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -200,13 +203,39 @@ impl FixedDecimal {
 }
 
 /// Represents errors that can occur during base conversion.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BaseConversionError {
+    /// A base outside of `2..=62`.
     InvalidBase,
+    /// A character that is not a digit of the source base.
     InvalidDigit,
+    /// A malformed number (e.g. more than one `.`).
     InvalidInput,
 }
 
+impl fmt::Display for BaseConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBase => write!(f, "Invalid base: must be between 2 and 62"),
+            Self::InvalidDigit => write!(f, "Invalid digit for the source base"),
+            Self::InvalidInput => write!(f, "Invalid number"),
+        }
+    }
+}
+
+impl core::error::Error for BaseConversionError {}
+
+#[cfg(feature = "std")]
+impl crate::error::ErrorCode for BaseConversionError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidBase => "base.invalid_base",
+            Self::InvalidDigit => "base.invalid_digit",
+            Self::InvalidInput => "base.invalid_input",
+        }
+    }
+}
+
 /// Converts a digit character to its numeric value.
 ///
 /// # Arguments
@@ -291,7 +320,6 @@ pub fn convert_base(number: &str, from_base: u32, to_base: u32) -> Result<String
 
 #[cfg(test)]
 mod tests {
-    use crate::format::{Style, Stylize};
     use super::*;
 
     #[test]
@@ -474,4 +502,14 @@ mod tests {
             assert_eq!(convert_base(src, *src_base, *new_base).unwrap(), *result)
         });
     }
+
+    #[test]
+    fn test_errors() {
+        fn parse(number: &str, base: u32) -> Result<String, alloc::boxed::Box<dyn core::error::Error>> {
+            Ok(convert_base(number, base, 10)?)
+        }
+        assert_eq!(parse("19", 8).unwrap_err().to_string(), "Invalid digit for the source base");
+        assert_eq!(convert_base("1", 1, 10), Err(BaseConversionError::InvalidBase));
+        assert_eq!(convert_base("1.0.1", 2, 10), Err(BaseConversionError::InvalidInput));
+    }
 }
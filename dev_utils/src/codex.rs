@@ -0,0 +1,19 @@
+//! Encoding and decoding of data into alternative representations.
+//!
+//! # Modules
+//! - [base64] - Base64 encoding in the standard and URL-safe alphabets
+//! - [checksum] - CRC-32, CRC-32C and Adler-32 checksums, one-shot and streaming
+//! - [gzip] - Compression and decompression of gzip and raw DEFLATE data
+//! - [mnemonic] - Encode bytes as a sequence of human-friendly words (PGP word list)
+//!
+//! # Examples
+//! ```
+//! use dev_utils::codex::mnemonic;
+//!
+//! let words = mnemonic::encode(&[0xCA, 0xFE]);
+//! assert_eq!(mnemonic::decode(&words).unwrap(), vec![0xCA, 0xFE]);
+//! ```
+pub mod base64;
+pub mod checksum;
+pub mod gzip;
+pub mod mnemonic;
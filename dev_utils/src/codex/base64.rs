@@ -0,0 +1,118 @@
+//! Base64 encoding (RFC 4648), in the standard and the URL-safe alphabets.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::codex::base64;
+//!
+//! assert_eq!(base64::encode(b"hello"), "aGVsbG8=");
+//! assert_eq!(base64::decode("aGVsbG8=").unwrap(), b"hello");
+//! assert_eq!(base64::encode_url(&[0xFB, 0xFF]), "-_8");
+//! ```
+use std::fmt;
+use std::error::Error;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+
+/// Represents errors that can occur when decoding base64 text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Base64Error {
+    /// A character that is not part of the alphabet (or misplaced padding).
+    InvalidChar { ch: char, index: usize },
+    /// The text ends in the middle of a byte.
+    InvalidLength(usize),
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidChar { ch, index } => write!(f, "Invalid base64 character {:?} at position {}", ch, index),
+            Self::InvalidLength(len) => write!(f, "Invalid base64 length: {}", len),
+        }
+    }
+}
+
+impl Error for Base64Error {}
+
+/// Encodes bytes with the standard alphabet (`+`, `/`), padded with `=`.
+pub fn encode(bytes: &[u8]) -> String {encode_with(bytes, STANDARD, true)}
+
+/// Encodes bytes with the URL-safe alphabet (`-`, `_`), without padding (as in JWTs).
+pub fn encode_url(bytes: &[u8]) -> String {encode_with(bytes, URL_SAFE, false)}
+
+/// Decodes text in the standard alphabet. Padding is optional.
+///
+/// # Returns
+/// A `Result` containing either the decoded bytes or a [Base64Error].
+pub fn decode(text: &str) -> Result<Vec<u8>, Base64Error> {decode_with(text, STANDARD)}
+
+/// Decodes text in the URL-safe alphabet. Padding is optional.
+///
+/// # Returns
+/// A `Result` containing either the decoded bytes or a [Base64Error].
+pub fn decode_url(text: &str) -> Result<Vec<u8>, Base64Error> {decode_with(text, URL_SAFE)}
+
+fn encode_with(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        // 1, 2 or 3 bytes are written as 2, 3 or 4 characters
+        for i in 0..=chunk.len() {
+            out.push(alphabet[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {out.push('=');}
+        }
+    }
+    out
+}
+
+fn decode_with(text: &str, alphabet: &[u8; 64]) -> Result<Vec<u8>, Base64Error> {
+    let data = text.trim_end_matches('=');
+    if text.len() - data.len() > 2 {
+        return Err(Base64Error::InvalidChar { ch: '=', index: data.len() });
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for (index, ch) in data.chars().enumerate() {
+        let value = alphabet.iter().position(|&c| c as char == ch).ok_or(Base64Error::InvalidChar { ch, index })?;
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    // a single leftover character can't hold a whole byte
+    match bits {
+        6 => Err(Base64Error::InvalidLength(data.len())),
+        _ => Ok(out),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc_vectors() {
+        let vectors = [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foob", "Zm9vYg=="), ("fooba", "Zm9vYmE="), ("foobar", "Zm9vYmFy")];
+        for (plain, encoded) in vectors {
+            assert_eq!(encode(plain.as_bytes()), encoded);
+            assert_eq!(decode(encoded).unwrap(), plain.as_bytes());
+            assert_eq!(decode_url(encoded.trim_end_matches('=')).unwrap(), plain.as_bytes());
+        }
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode_url(&encode_url(&bytes)).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(decode("Zm9v!"), Err(Base64Error::InvalidChar { ch: '!', index: 4 }));
+        assert_eq!(decode("Zm9vY"), Err(Base64Error::InvalidLength(5)));
+        assert_eq!(decode("Zg==="), Err(Base64Error::InvalidChar { ch: '=', index: 2 }));
+        assert_eq!(decode_url("+/"), Err(Base64Error::InvalidChar { ch: '+', index: 0 }));
+    }
+}
@@ -0,0 +1,216 @@
+//! Non-cryptographic checksums: CRC-32 (gzip, zip, PNG), CRC-32C (iSCSI, ext4) and Adler-32 (zlib).
+//!
+//! Each checksum has a one-shot function and a streaming type, which also implements
+//! [std::hash::Hasher]. The CRCs use slicing-by-8 tables built at compile time.
+//! They detect accidental corruption; use [crate::crypto::hash] against deliberate changes.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::codex::checksum::{crc32, Crc32};
+//!
+//! let mut crc = Crc32::new();
+//! crc.update(b"1234");
+//! crc.update(b"56789");
+//! assert_eq!(crc.finalize(), 0xCBF4_3926);
+//! assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+//! ```
+use std::hash::Hasher;
+
+// The 8 tables of slicing-by-8 for a reflected polynomial: `tables[k][b]` is the CRC of
+// byte `b` followed by `k` zero bytes
+const fn crc_tables(polynomial: u32) -> [[u32; 256]; 8] {
+    let mut tables = [[0; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {(crc >> 1) ^ polynomial} else {crc >> 1};
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+    let mut k = 1;
+    while k < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let previous = tables[k - 1][i];
+            tables[k][i] = (previous >> 8) ^ tables[0][(previous & 0xff) as usize];
+            i += 1;
+        }
+        k += 1;
+    }
+    tables
+}
+
+static CRC32_TABLES: [[u32; 256]; 8] = crc_tables(0xEDB8_8320);
+static CRC32C_TABLES: [[u32; 256]; 8] = crc_tables(0x82F6_3B78);
+
+// Feeds `data` to a (non-inverted) CRC register, 8 bytes at a time then byte by byte
+fn crc_update(tables: &[[u32; 256]; 8], mut crc: u32, data: &[u8]) -> u32 {
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let low = crc ^ u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let high = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        let lookup = |k: usize, word: u32, shift: u32| tables[k][((word >> shift) & 0xff) as usize];
+        crc = lookup(7, low, 0) ^ lookup(6, low, 8) ^ lookup(5, low, 16) ^ lookup(4, low, 24)
+            ^ lookup(3, high, 0) ^ lookup(2, high, 8) ^ lookup(1, high, 16) ^ lookup(0, high, 24);
+    }
+    chunks.remainder().iter().fold(crc, |crc, &b| (crc >> 8) ^ tables[0][((crc ^ b as u32) & 0xff) as usize])
+}
+
+// A streaming CRC type and its one-shot function
+macro_rules! define_crc {
+    ($(#[$meta:meta])* $name:ident, $function:ident, $tables:ident, $label:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name {
+            crc: u32,
+        }
+
+        impl $name {
+            /// Starts a checksum of no data.
+            pub fn new() -> Self {$name { crc: !0 }}
+
+            /// Feeds data to the checksum.
+            pub fn update(&mut self, data: &[u8]) {self.crc = crc_update(&$tables, self.crc, data);}
+
+            /// Returns the checksum of the data fed so far (more data can still be fed).
+            pub fn finalize(&self) -> u32 {!self.crc}
+        }
+
+        impl Default for $name {
+            fn default() -> Self {Self::new()}
+        }
+
+        impl Hasher for $name {
+            fn write(&mut self, bytes: &[u8]) {self.update(bytes);}
+            fn finish(&self) -> u64 {self.finalize() as u64}
+        }
+
+        #[doc = concat!("Returns the ", $label, " of `data`.")]
+        pub fn $function(data: &[u8]) -> u32 {
+            let mut crc = $name::new();
+            crc.update(data);
+            crc.finalize()
+        }
+    };
+}
+
+define_crc! {
+    /// A streaming CRC-32 (IEEE 802.3 polynomial, as in gzip, zip and PNG).
+    Crc32, crc32, CRC32_TABLES, "CRC-32"
+}
+
+define_crc! {
+    /// A streaming CRC-32C (Castagnoli polynomial, as in iSCSI, ext4 and SSE 4.2's `crc32` instruction).
+    Crc32c, crc32c, CRC32C_TABLES, "CRC-32C"
+}
+
+const ADLER_MOD: u32 = 65521;
+// The most bytes that can be summed before `b` may overflow a u32
+const ADLER_NMAX: usize = 5552;
+
+/// A streaming Adler-32 (the checksum of zlib streams).
+///
+/// # Examples
+/// ```
+/// use dev_utils::codex::checksum::{adler32, Adler32};
+///
+/// let mut adler = Adler32::new();
+/// adler.update(b"Wiki");
+/// adler.update(b"pedia");
+/// assert_eq!(adler.finalize(), 0x11E6_0398);
+/// assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    /// Starts a checksum of no data.
+    pub fn new() -> Self {Adler32 { a: 1, b: 0 }}
+
+    /// Feeds data to the checksum.
+    pub fn update(&mut self, data: &[u8]) {
+        // the sums are only reduced once per block
+        for block in data.chunks(ADLER_NMAX) {
+            for &byte in block {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= ADLER_MOD;
+            self.b %= ADLER_MOD;
+        }
+    }
+
+    /// Returns the checksum of the data fed so far (more data can still be fed).
+    pub fn finalize(&self) -> u32 {(self.b << 16) | self.a}
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {Self::new()}
+}
+
+impl Hasher for Adler32 {
+    fn write(&mut self, bytes: &[u8]) {self.update(bytes);}
+    fn finish(&self) -> u64 {self.finalize() as u64}
+}
+
+/// Returns the Adler-32 of `data`.
+pub fn adler32(data: &[u8]) -> u32 {
+    let mut adler = Adler32::new();
+    adler.update(data);
+    adler.finalize()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The bit by bit definitions, to check the tables and the blocks against
+    fn crc_reference(polynomial: u32, data: &[u8]) -> u32 {
+        !data.iter().fold(!0u32, |crc, &b| {
+            (0..8).fold(crc ^ b as u32, |crc, _| if crc & 1 == 1 {(crc >> 1) ^ polynomial} else {crc >> 1})
+        })
+    }
+
+    fn adler_reference(data: &[u8]) -> u32 {
+        let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &x| {
+            let a = (a + x as u32) % ADLER_MOD;
+            (a, (b + a) % ADLER_MOD)
+        });
+        (b << 16) | a
+    }
+
+    #[test]
+    fn test_check_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+        assert_eq!(crc32(b"The quick brown fox jumps over the lazy dog"), 0x414F_A339);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!((crc32(b""), crc32c(b""), adler32(b"")), (0, 0, 1));
+    }
+
+    #[test]
+    fn test_against_reference() {
+        // long enough for several Adler-32 blocks, with uneven chunks
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8 | 0xe0).collect();
+        assert_eq!(crc32(&data), crc_reference(0xEDB8_8320, &data));
+        assert_eq!(crc32c(&data), crc_reference(0x82F6_3B78, &data));
+        assert_eq!(adler32(&data), adler_reference(&data));
+        assert_eq!(adler32(&[0xff; 100_000]), adler_reference(&[0xff; 100_000]));
+
+        let (mut crc, mut adler) = (Crc32c::new(), Adler32::default());
+        for chunk in data.chunks(777) {
+            crc.write(chunk);
+            adler.write(chunk);
+        }
+        assert_eq!(crc.finish(), crc32c(&data) as u64);
+        assert_eq!(adler.finish(), adler32(&data) as u64);
+    }
+}
@@ -0,0 +1,465 @@
+//! Compression and decompression of gzip (RFC 1952) and raw DEFLATE (RFC 1951) data.
+//!
+//! The compressor finds LZ77 matches with hash chains and encodes them with the fixed
+//! Huffman codes: simple and fast enough, if a little larger than `gzip -6`.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::codex::gzip;
+//!
+//! // `gzip -n` of "gzip"
+//! let data = [31, 139, 8, 0, 0, 0, 0, 0, 0, 3, 75, 175, 202, 44, 0, 0, 242, 92, 233, 58, 4, 0, 0, 0];
+//! assert_eq!(gzip::decompress(&data).unwrap(), b"gzip");
+//!
+//! let text = b"to be or not to be, that is the question".repeat(10);
+//! let compressed = gzip::compress(&text);
+//! assert!(compressed.len() < text.len() / 4);
+//! assert_eq!(gzip::decompress(&compressed).unwrap(), text);
+//! ```
+use std::fmt;
+use std::error::Error;
+use super::checksum::crc32;
+
+/// Represents errors that can occur when decompressing data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GzipError {
+    /// The data doesn't start with a gzip header (or uses another compression method).
+    InvalidHeader,
+    /// The compressed data is corrupt.
+    InvalidData(String),
+    /// The data ends before the end of the stream.
+    UnexpectedEof,
+    /// The CRC-32 of the decompressed data doesn't match the one of the trailer.
+    ChecksumMismatch { expected: u32, found: u32 },
+}
+
+impl fmt::Display for GzipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "Invalid gzip header"),
+            Self::InvalidData(message) => write!(f, "Invalid compressed data: {}", message),
+            Self::UnexpectedEof => write!(f, "Unexpected end of compressed data"),
+            Self::ChecksumMismatch { expected, found } => write!(f, "Checksum mismatch: expected {:08x}, found {:08x}", expected, found),
+        }
+    }
+}
+
+impl Error for GzipError {}
+
+fn invalid(message: &str) -> GzipError {GzipError::InvalidData(message.to_string())}
+
+// Header flags
+const FHCRC: u8 = 0x02;
+const FEXTRA: u8 = 0x04;
+const FNAME: u8 = 0x08;
+const FCOMMENT: u8 = 0x10;
+
+/// Decompresses gzip data. Concatenated members (as written by `cat a.gz b.gz`) are
+/// decompressed one after the other.
+///
+/// # Returns
+/// A `Result` containing either the decompressed bytes or a [GzipError].
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, GzipError> {
+    if data.is_empty() {return Err(GzipError::InvalidHeader);}
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        pos = decompress_member(data, pos, &mut out)?;
+    }
+    Ok(out)
+}
+
+// Decompresses the member that starts at `start` into `out`, returning where it ends
+fn decompress_member(data: &[u8], start: usize, out: &mut Vec<u8>) -> Result<usize, GzipError> {
+    let byte = |i: usize| data.get(i).copied().ok_or(GzipError::UnexpectedEof);
+    if data.get(start..start + 3) != Some(&[0x1f, 0x8b, 8][..]) {return Err(GzipError::InvalidHeader);}
+    let flags = byte(start + 3)?;
+    let mut pos = start + 10;
+    if flags & FEXTRA != 0 {
+        pos += 2 + (byte(pos)? as usize | (byte(pos + 1)? as usize) << 8);
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            // a zero-terminated string
+            while byte(pos)? != 0 {pos += 1;}
+            pos += 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        let expected = byte(pos)? as u32 | (byte(pos + 1)? as u32) << 8;
+        let found = crc32(data.get(start..pos).ok_or(GzipError::UnexpectedEof)?) & 0xffff;
+        if expected != found {return Err(GzipError::ChecksumMismatch { expected, found });}
+        pos += 2;
+    }
+
+    let begin = out.len();
+    let mut reader = BitReader { data, pos: pos.min(data.len()), bits: 0, count: 0 };
+    inflate_into(&mut reader, out)?;
+    let pos = reader.pos;
+    let trailer = data.get(pos..pos + 8).ok_or(GzipError::UnexpectedEof)?;
+    let word = |i: usize| u32::from_le_bytes([trailer[i], trailer[i + 1], trailer[i + 2], trailer[i + 3]]);
+    let (expected, found) = (word(0), crc32(&out[begin..]));
+    if expected != found {return Err(GzipError::ChecksumMismatch { expected, found });}
+    if word(4) != (out.len() - begin) as u32 {return Err(invalid("size mismatch"));}
+    Ok(pos + 8)
+}
+
+/// Decompresses raw DEFLATE data (without the gzip or zlib wrappers).
+///
+/// # Returns
+/// A `Result` containing either the decompressed bytes or a [GzipError].
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, GzipError> {
+    let mut out = Vec::new();
+    inflate_into(&mut BitReader { data, pos: 0, bits: 0, count: 0 }, &mut out)?;
+    Ok(out)
+}
+
+/// Compresses data into a gzip member (without file name or time), readable by `gzip -d`
+/// and [decompress].
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];  // 0xff: unknown OS
+    out.extend(deflate(data));
+    out.extend(crc32(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+/// Compresses data into raw DEFLATE (without the gzip or zlib wrappers), as in zip archives.
+///
+/// Data that doesn't compress is written in stored blocks, so the output is never more
+/// than 5 bytes per 64 KiB larger than the input.
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let compressed = fixed_block(data);
+    let stored_len = data.len() + 5 * data.len().div_ceil(0xffff).max(1);
+    if compressed.len() <= stored_len {return compressed;}
+
+    let mut out = Vec::with_capacity(stored_len);
+    let chunks = data.chunks(0xffff).count();
+    for (i, chunk) in data.chunks(0xffff).enumerate() {
+        out.push((i + 1 == chunks) as u8);  // BFINAL, then the stored type (00) and padding
+        out.extend((chunk.len() as u16).to_le_bytes());
+        out.extend((!(chunk.len() as u16)).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out
+}
+
+// Writes the bits of a DEFLATE stream, least significant first
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, n: u32) {
+        self.bits |= (value as u64) << self.count;
+        self.count += n;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are packed starting with their most significant bit
+    fn code(&mut self, code: u32, len: u32) {self.bits(code.reverse_bits() >> (32 - len), len);}
+
+    // A symbol of the fixed literal/length code (RFC 1951, 3.2.6)
+    fn fixed_symbol(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {self.out.push(self.bits as u8);}
+        self.out
+    }
+}
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const MAX_MATCH: usize = 258;
+// How many earlier positions with the same hash are tried for each match
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+// One final block with the fixed codes, with greedy LZ77 matching
+fn fixed_block(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    writer.bits(1, 1);  // BFINAL
+    writer.bits(1, 2);  // fixed Huffman codes
+
+    // the last position of each hash of 3 bytes, and the previous one with the same hash
+    let hash = |i: usize| ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize) & ((1 << HASH_BITS) - 1);
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; data.len()];
+    let mut pos = 0;
+    while pos < data.len() {
+        let (mut best_len, mut best_distance) = (0, 0);
+        if pos + 3 <= data.len() {
+            let max_len = (data.len() - pos).min(MAX_MATCH);
+            let mut candidate = head[hash(pos)];
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || pos - candidate > WINDOW_SIZE {break;}
+                let len = data[candidate..].iter().zip(&data[pos..pos + max_len]).take_while(|(a, b)| a == b).count();
+                if len > best_len {
+                    (best_len, best_distance) = (len, pos - candidate);
+                    if len == max_len {break;}
+                }
+                candidate = previous[candidate];
+            }
+        }
+
+        let advance = match best_len >= 3 {
+            true => {
+                let i = LENGTH_BASE.iter().rposition(|&base| base as usize <= best_len).unwrap_or(0);
+                writer.fixed_symbol(257 + i as u32);
+                writer.bits((best_len - LENGTH_BASE[i] as usize) as u32, LENGTH_EXTRA[i] as u32);
+                let d = DIST_BASE.iter().rposition(|&base| base as usize <= best_distance).unwrap_or(0);
+                writer.code(d as u32, 5);
+                writer.bits((best_distance - DIST_BASE[d] as usize) as u32, DIST_EXTRA[d] as u32);
+                best_len
+            },
+            false => {
+                writer.fixed_symbol(data[pos] as u32);
+                1
+            },
+        };
+        let end = (pos + advance).min(data.len().saturating_sub(2));
+        for (i, link) in previous.iter_mut().enumerate().take(end).skip(pos) {
+            let h = hash(i);
+            *link = head[h];
+            head[h] = i;
+        }
+        pos += advance;
+    }
+    writer.fixed_symbol(256);  // end of block
+    writer.finish()
+}
+
+// Reads the bits of a DEFLATE stream, least significant first
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> Result<u32, GzipError> {
+        while self.count < n {
+            let byte = *self.data.get(self.pos).ok_or(GzipError::UnexpectedEof)?;
+            self.bits |= (byte as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+        let value = self.bits & ((1u64 << n) - 1) as u32;
+        self.bits >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    // Drops the bits left in the current byte (stored blocks start at a byte boundary)
+    fn align(&mut self) {
+        self.bits = 0;
+        self.count = 0;
+    }
+}
+
+// A canonical Huffman code: the number of codes of each length, and the symbols sorted by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, GzipError> {
+        let mut counts = [0u16; 16];
+        lengths.iter().for_each(|&len| counts[len as usize] += 1);
+        counts[0] = 0;
+        // over-subscribed codes are invalid, incomplete ones are allowed (RFC 1951, 3.2.7)
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {return Err(invalid("over-subscribed Huffman code"));}
+        }
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {offsets[len + 1] = offsets[len] + counts[len];}
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate().filter(|(_, &len)| len != 0) {
+            symbols[offsets[len as usize] as usize] = symbol as u16;
+            offsets[len as usize] += 1;
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, GzipError> {
+        // codes are packed starting with their most significant bit
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {return Ok(self.symbols[(index + code - first) as usize]);}
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("invalid Huffman code"))
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// The order in which the lengths of the code length code are sent
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn inflate_into(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), GzipError> {
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored_block(reader, out)?,
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                huffman_block(reader, out, &Huffman::new(&lengths)?, &Huffman::new(&[5; 30])?)?;
+            },
+            2 => {
+                let (literals, distances) = dynamic_codes(reader)?;
+                huffman_block(reader, out, &literals, &distances)?;
+            },
+            _ => return Err(invalid("invalid block type")),
+        }
+        if last {return Ok(());}
+    }
+}
+
+fn stored_block(reader: &mut BitReader, out: &mut Vec<u8>) -> Result<(), GzipError> {
+    reader.align();
+    let header = reader.data.get(reader.pos..reader.pos + 4).ok_or(GzipError::UnexpectedEof)?;
+    let (len, nlen) = (u16::from_le_bytes([header[0], header[1]]), u16::from_le_bytes([header[2], header[3]]));
+    if len != !nlen {return Err(invalid("stored block length mismatch"));}
+    let start = reader.pos + 4;
+    out.extend_from_slice(reader.data.get(start..start + len as usize).ok_or(GzipError::UnexpectedEof)?);
+    reader.pos = start + len as usize;
+    Ok(())
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), GzipError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_length_count] {code_lengths[i] = reader.bits(3)? as u8;}
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    // the literal/length and distance code lengths are sent as one sequence
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(reader)? {
+            len @ 0..=15 => (len as u8, 1),
+            16 => (*lengths.last().ok_or_else(|| invalid("repeated length without a previous one"))?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {return Err(invalid("too many code lengths"));}
+    if lengths[256] == 0 {return Err(invalid("missing end-of-block code"));}
+    Ok((Huffman::new(&lengths[..literal_count])?, Huffman::new(&lengths[literal_count..])?))
+}
+
+fn huffman_block(reader: &mut BitReader, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<(), GzipError> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = symbol - 257;
+                if i >= LENGTH_BASE.len() {return Err(invalid("invalid length symbol"));}
+                let len = LENGTH_BASE[i] as usize + reader.bits(LENGTH_EXTRA[i] as u32)? as usize;
+                let d = distances.decode(reader)? as usize;
+                if d >= DIST_BASE.len() {return Err(invalid("invalid distance symbol"));}
+                let distance = DIST_BASE[d] as usize + reader.bits(DIST_EXTRA[d] as u32)? as usize;
+                if distance > out.len() {return Err(invalid("distance too far back"));}
+                // the copy may overlap what it writes (a run)
+                let start = out.len() - distance;
+                for i in 0..len {out.push(out[start + i]);}
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inflate() {
+        // zlib level 0 (a stored block), level 9 (fixed and dynamic Huffman codes)
+        assert_eq!(inflate(&[1, 6, 0, 249, 255, 115, 116, 111, 114, 101, 100]).unwrap(), b"stored");
+        let fixed = [75, 76, 74, 77, 73, 73, 75, 73, 78, 73, 76, 75, 75, 74, 75, 77, 79, 78, 76, 74, 73, 76, 28, 122, 130, 0];
+        let expected: Vec<u8> = (0..200).map(|i: u32| b'a' + ((i * i + i / 3) % 7) as u8).collect();
+        assert_eq!(inflate(&fixed).unwrap(), expected);
+        let dynamic = [125, 139, 177, 9, 0, 48, 12, 195, 110, 245, 96, 72, 150, 26, 26, 253, 79, 115, 65, 23, 129, 64, 162, 203,
+            178, 8, 109, 208, 29, 167, 60, 82, 106, 172, 210, 241, 138, 185, 21, 232, 1, 50, 54, 91, 230, 44, 190, 239, 3];
+        let text = b"tiheaeatotiettarseohesaaohseahaneesaetrhottistttoseetettonet";
+        assert_eq!(inflate(&dynamic).unwrap(), [&text[..], &text[..30]].concat());
+
+        assert_eq!(inflate(&fixed[..10]), Err(GzipError::UnexpectedEof));
+        assert_eq!(inflate(&[7]), Err(invalid("invalid block type")));
+        assert_eq!(inflate(&[1, 6, 0, 0, 0]), Err(invalid("stored block length mismatch")));
+    }
+
+    #[test]
+    fn test_decompress() {
+        let hello = [31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 203, 72, 205, 201, 201, 87, 200, 64, 39, 1, 227, 81, 61, 141, 23, 0, 0, 0];
+        assert_eq!(decompress(&hello).unwrap(), b"hello hello hello hello");
+        assert_eq!(decompress(&[&hello[..], &hello[..]].concat()).unwrap().len(), 46);
+        // with extra field, file name, comment and header CRC
+        let flagged = [31, 139, 8, 30, 0, 0, 0, 0, 0, 3, 3, 0, 120, 121, 122, 97, 46, 116, 120, 116, 0, 104, 105, 0, 18, 157,
+            75, 175, 202, 44, 0, 0, 242, 92, 233, 58, 4, 0, 0, 0];
+        assert_eq!(decompress(&flagged).unwrap(), b"gzip");
+
+        let mut corrupt = hello;
+        corrupt[12] ^= 1;
+        assert!(decompress(&corrupt).is_err());
+        let mut bad_crc = hello;
+        bad_crc[21] ^= 0xff;
+        assert!(matches!(decompress(&bad_crc), Err(GzipError::ChecksumMismatch { .. })));
+        assert_eq!(decompress(b"plain"), Err(GzipError::InvalidHeader));
+        assert_eq!(decompress(&hello[..hello.len() - 2]), Err(GzipError::UnexpectedEof));
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_compress() {
+        let text = b"hello hello hello hello".repeat(100);
+        let runs: Vec<u8> = (0..100_000u32).map(|i| (i / 1000) as u8).collect();
+        // a pseudo-random sequence, that doesn't compress
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let noise: Vec<u8> = (0..70_000).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 32) as u8
+        }).collect();
+        for data in [&b""[..], b"a", b"abcabcabc", &text, &runs, &noise] {
+            assert_eq!(inflate(&deflate(data)).unwrap(), data);
+            assert_eq!(decompress(&compress(data)).unwrap(), data);
+        }
+        assert_eq!(deflate(b""), [3, 0]);
+        assert!(deflate(&text).len() < 50);
+        assert!(deflate(&runs).len() < 2_000);
+        assert_eq!(deflate(&noise).len(), noise.len() + 10);  // 2 stored blocks
+    }
+}
@@ -0,0 +1,196 @@
+//! Wordlist-based mnemonic encoding of binary data.
+//!
+//! Bytes are mapped to words of the [PGP word list](https://en.wikipedia.org/wiki/PGP_word_list),
+//! which uses two different lists of 256 words: one for bytes at even positions
+//! (two-syllable words) and one for bytes at odd positions (three-syllable words).
+//! This makes checksums and fingerprints easy to read aloud, and lets the decoder
+//! detect swapped, repeated or missing words.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::codex::mnemonic::{encode, decode};
+//!
+//! let words = encode(&[0xE5, 0x82, 0x94, 0xF2]);
+//! assert_eq!(words, "topmost Istanbul Pluto vagabond");
+//! assert_eq!(decode(&words).unwrap(), vec![0xE5, 0x82, 0x94, 0xF2]);
+//! ```
+use std::fmt;
+use std::error::Error;
+
+
+/// Represents errors that can occur when decoding a mnemonic phrase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MnemonicError {
+    /// The word is not part of any of the word lists.
+    UnknownWord(String),
+    /// The word exists but belongs to the list of the other parity (likely a swapped or missing word).
+    WrongPosition { word: String, index: usize },
+}
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownWord(word) => write!(f, "Unknown word: {}", word),
+            Self::WrongPosition { word, index } => write!(f, "Word '{}' is not valid at position {}", word, index),
+        }
+    }
+}
+
+impl Error for MnemonicError {}
+
+/// Returns the word that encodes `byte` at the given position of a sequence.
+///
+/// # Examples
+/// ```
+/// use dev_utils::codex::mnemonic::word_for;
+///
+/// assert_eq!(word_for(0x00, 0), "aardvark");
+/// assert_eq!(word_for(0x00, 1), "adroitness");
+/// ```
+pub fn word_for(byte: u8, position: usize) -> &'static str {
+    match position % 2 {
+        0 => EVEN_WORDS[byte as usize],
+        _ => ODD_WORDS[byte as usize],
+    }
+}
+
+/// Encodes a byte slice into a space-separated sequence of words.
+///
+/// # Arguments
+/// * `bytes` - The data to encode
+///
+/// # Returns
+/// A `String` with one word per byte.
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().enumerate()
+        .map(|(i, &b)| word_for(b, i))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes a sequence of words (separated by whitespace, `-` or `,`) back into bytes.
+///
+/// Matching is case-insensitive.
+///
+/// # Arguments
+/// * `phrase` - The words to decode
+///
+/// # Returns
+/// A `Result` containing either the decoded bytes or a [MnemonicError].
+pub fn decode(phrase: &str) -> Result<Vec<u8>, MnemonicError> {
+    let find = |list: &[&str; 256], word: &str| list.iter().position(|w| w.eq_ignore_ascii_case(word));
+
+    phrase.split(|c: char| c.is_whitespace() || c == '-' || c == ',')
+        .filter(|w| !w.is_empty())
+        .enumerate()
+        .map(|(i, word)| {
+            let (expected, other) = match i % 2 {
+                0 => (&EVEN_WORDS, &ODD_WORDS),
+                _ => (&ODD_WORDS, &EVEN_WORDS),
+            };
+            match (find(expected, word), find(other, word)) {
+                (Some(byte), _) => Ok(byte as u8),
+                (None, Some(_)) => Err(MnemonicError::WrongPosition { word: word.to_string(), index: i }),
+                (None, None) => Err(MnemonicError::UnknownWord(word.to_string())),
+            }
+        })
+        .collect()
+}
+
+// Two-syllable words, used for bytes at even positions.
+const EVEN_WORDS: [&str; 256] = [
+    "aardvark", "absurd", "accrue", "acme", "adrift", "adult", "afflict", "ahead",
+    "aimless", "Algol", "allow", "alone", "ammo", "ancient", "apple", "artist",
+    "assume", "Athens", "atlas", "Aztec", "baboon", "backfield", "backward", "banjo",
+    "beaming", "bedlamp", "beehive", "beeswax", "befriend", "Belfast", "berserk", "billiard",
+    "bison", "blackjack", "blockade", "blowtorch", "bluebird", "bombast", "bookshelf", "brackish",
+    "breadline", "breakup", "brickyard", "briefcase", "Burbank", "button", "buzzard", "cement",
+    "chairlift", "chatter", "checkup", "chisel", "choking", "chopper", "Christmas", "clamshell",
+    "classic", "classroom", "cleanup", "clockwork", "cobra", "commence", "concert", "cowbell",
+    "crackdown", "cranky", "crowfoot", "crucial", "crumpled", "crusade", "cubic", "dashboard",
+    "deadbolt", "deckhand", "dogsled", "dragnet", "drainage", "dreadful", "drifter", "dropper",
+    "drumbeat", "drunken", "Dupont", "dwelling", "eating", "edict", "egghead", "eightball",
+    "endorse", "endow", "enlist", "erase", "escape", "exceed", "eyeglass", "eyetooth",
+    "facial", "fallout", "flagpole", "flatfoot", "flytrap", "fracture", "framework", "freedom",
+    "frighten", "gazelle", "Geiger", "glitter", "glucose", "goggles", "goldfish", "gremlin",
+    "guidance", "hamlet", "highchair", "hockey", "indoors", "indulge", "inverse", "involve",
+    "island", "jawbone", "keyboard", "kickoff", "kiwi", "klaxon", "locale", "lockup",
+    "merit", "minnow", "miser", "Mohawk", "mural", "music", "necklace", "Neptune",
+    "newborn", "nightbird", "Oakland", "obtuse", "offload", "optic", "orca", "payday",
+    "peachy", "pheasant", "physique", "playhouse", "Pluto", "preclude", "prefer", "preshrunk",
+    "printer", "prowler", "pupil", "puppy", "python", "quadrant", "quiver", "quota",
+    "ragtime", "ratchet", "rebirth", "reform", "regain", "reindeer", "rematch", "repay",
+    "retouch", "revenge", "reward", "rhythm", "ribcage", "ringbolt", "robust", "rocker",
+    "ruffled", "sailboat", "sawdust", "scallion", "scenic", "scorecard", "Scotland", "seabird",
+    "select", "sentence", "shadow", "shamrock", "showgirl", "skullcap", "skydive", "slingshot",
+    "slowdown", "snapline", "snapshot", "snowcap", "snowslide", "solo", "southward", "soybean",
+    "spaniel", "spearhead", "spellbind", "spheroid", "spigot", "spindle", "spyglass", "stagehand",
+    "stagnate", "stairway", "standard", "stapler", "steamship", "sterling", "stockman", "stopwatch",
+    "stormy", "sugar", "surmount", "suspense", "sweatband", "swelter", "tactics", "talon",
+    "tapeworm", "tempest", "tiger", "tissue", "tonic", "topmost", "tracker", "transit",
+    "trauma", "treadmill", "Trojan", "trouble", "tumor", "tunnel", "tycoon", "uncut",
+    "unearth", "unwind", "uproot", "upset", "upshot", "vapor", "village", "virus",
+    "Vulcan", "waffle", "wallet", "watchword", "wayside", "willow", "woodlark", "Zulu",
+];
+
+// Three-syllable words, used for bytes at odd positions.
+const ODD_WORDS: [&str; 256] = [
+    "adroitness", "adviser", "aftermath", "aggregate", "alkali", "almighty", "amulet", "amusement",
+    "antenna", "applicant", "Apollo", "armistice", "article", "asteroid", "Atlantic", "atmosphere",
+    "autopsy", "Babylon", "backwater", "barbecue", "belowground", "bifocals", "bodyguard", "bookseller",
+    "borderline", "bottomless", "Bradbury", "bravado", "Brazilian", "breakaway", "Burlington", "businessman",
+    "butterfat", "Camelot", "candidate", "cannonball", "Capricorn", "caravan", "caretaker", "celebrate",
+    "cellulose", "certify", "chambermaid", "Cherokee", "Chicago", "clergyman", "coherence", "combustion",
+    "commando", "company", "component", "concurrent", "confidence", "conformist", "congregate", "consensus",
+    "consulting", "corporate", "corrosion", "councilman", "crossover", "crucifix", "cumbersome", "customer",
+    "Dakota", "decadence", "December", "decimal", "designing", "detector", "detergent", "determine",
+    "dictator", "dinosaur", "direction", "disable", "disbelief", "disruptive", "distortion", "document",
+    "embezzle", "enchanting", "enrollment", "enterprise", "equation", "equipment", "escapade", "Eskimo",
+    "everyday", "examine", "existence", "exodus", "fascinate", "filament", "finicky", "forever",
+    "fortitude", "frequency", "gadgetry", "Galveston", "getaway", "glossary", "gossamer", "graduate",
+    "gravity", "guitarist", "hamburger", "Hamilton", "handiwork", "hazardous", "headwaters", "hemisphere",
+    "hesitate", "hideaway", "holiness", "hurricane", "hydraulic", "impartial", "impetus", "inception",
+    "indigo", "inertia", "infancy", "inferno", "informant", "insincere", "insurgent", "integrate",
+    "intention", "inventive", "Istanbul", "Jamaica", "Jupiter", "leprosy", "letterhead", "liberty",
+    "maritime", "matchmaker", "maverick", "Medusa", "megaton", "microscope", "microwave", "midsummer",
+    "millionaire", "miracle", "misnomer", "molasses", "molecule", "Montana", "monument", "mosquito",
+    "narrative", "nebula", "newsletter", "Norwegian", "October", "Ohio", "onlooker", "opulent",
+    "Orlando", "outfielder", "Pacific", "pandemic", "Pandora", "paperweight", "paragon", "paragraph",
+    "paramount", "passenger", "pedigree", "Pegasus", "penetrate", "perceptive", "performance", "pharmacy",
+    "phonetic", "photograph", "pioneering", "picnic", "politeness", "positive", "potato", "processor",
+    "provincial", "proximate", "puberty", "publisher", "pyramid", "quantity", "racketeer", "rebellion",
+    "recipe", "recover", "repellent", "replica", "reproduce", "resistor", "responsive", "retraction",
+    "retrieval", "retrospect", "revenue", "revival", "revolver", "sandalwood", "sardonic", "Saturday",
+    "savagery", "scavenger", "sensation", "sociable", "souvenir", "specialist", "speculate", "stethoscope",
+    "stupendous", "supportive", "surrender", "suspicious", "sympathy", "tambourine", "telephone", "therapist",
+    "tobacco", "tolerance", "tomorrow", "torpedo", "tradition", "travesty", "trombonist", "truncated",
+    "typewriter", "ultimate", "undaunted", "underfoot", "unicorn", "unify", "universe", "unravel",
+    "upcoming", "vacancy", "vagabond", "vertigo", "Virginia", "visitor", "vocalist", "voyager",
+    "warranty", "Waterloo", "whimsical", "Wichita", "Wilmington", "Wyoming", "yesteryear", "Yucatan",
+];
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(&encode(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn test_known_fingerprint() {
+        let bytes = [0xE5, 0x82, 0x94, 0xF2, 0xE9, 0xA2];
+        assert_eq!(encode(&bytes), "topmost Istanbul Pluto vagabond treadmill Pacific");
+        assert_eq!(decode("TOPMOST-istanbul, pluto vagabond").unwrap(), vec![0xE5, 0x82, 0x94, 0xF2]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode("Istanbul topmost"), Err(MnemonicError::WrongPosition { word: "Istanbul".to_string(), index: 0 }));
+        assert_eq!(decode("topmost banana"), Err(MnemonicError::UnknownWord("banana".to_string())));
+        assert_eq!(decode("").unwrap(), Vec::<u8>::new());
+    }
+}
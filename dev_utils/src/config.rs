@@ -0,0 +1,325 @@
+//! Configuration file utilities for tools built on dev_utils.
+//!
+//! # Features
+//! - [Config] - Loads TOML, YAML and JSON files, merges them with environment variables and
+//!   overrides, and reads values with typed getters
+//!
+//! # Modules
+//! - [migrations] - Versioned migrations of a config file schema, with backups and logging
+//!
+//! # Examples
+//! ```
+//! use dev_utils::config::migrations::Migrations;
+//!
+//! let migrations = Migrations::new()
+//!     .add(1, "rename `color` to `theme`", |cfg| Ok(cfg.replace("color =", "theme =")));
+//!
+//! let (migrated, report) = migrations.migrate_str("color = \"dark\"\n").unwrap();
+//! assert_eq!(migrated, "config_version = 1\ntheme = \"dark\"\n");
+//! assert_eq!((report.from, report.to), (0, 1));
+//! ```
+pub mod migrations;
+
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use crate::file::{self, FileError};
+use crate::file::toml::{self, TomlValue};
+use crate::file::yaml::{self, Mapping, YamlValue};
+use crate::units::parse_duration;
+
+/// Represents errors that can occur when loading or reading a [Config].
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A config file could not be read.
+    File(FileError),
+    /// A config file could not be parsed.
+    Parse { path: PathBuf, message: String },
+    /// The format of a file could not be detected from its extension.
+    UnknownFormat(PathBuf),
+    /// None of the files matching the pattern exist.
+    NotFound(String),
+    /// The key is not set.
+    Missing(String),
+    /// The value of the key could not be converted to the requested type.
+    Invalid { key: String, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::File(err) => write!(f, "{}", err),
+            ConfigError::Parse { path, message } => write!(f, "Failed to parse {}: {}", path.display(), message),
+            ConfigError::UnknownFormat(path) => write!(f, "Unknown config format: {}", path.display()),
+            ConfigError::NotFound(pattern) => write!(f, "No config file found matching {}", pattern),
+            ConfigError::Missing(key) => write!(f, "Missing config key: {}", key),
+            ConfigError::Invalid { key, value } => write!(f, "Invalid value for config key {}: {}", key, value),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<FileError> for ConfigError {
+    fn from(err: FileError) -> Self {ConfigError::File(err)}
+}
+
+/// The format of a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Format {
+    /// Detects the format from the extension of a path (`.toml`, `.yaml`/`.yml`, `.json`).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Format> {
+        let ext = path.as_ref().extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// Parses `content` into a tree of values.
+    ///
+    /// JSON is read with the YAML parser (JSON documents are valid YAML flow collections).
+    pub fn parse(self, content: &str) -> Result<YamlValue, String> {
+        match self {
+            Format::Toml => toml::parse(content).map(|t| YamlValue::from(TomlValue::Table(t))).map_err(|e| e.to_string()),
+            Format::Yaml | Format::Json => yaml::parse(content).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Layered application configuration.
+///
+/// Sources are merged in the order they are added, later sources overriding earlier ones:
+/// the usual order is files, then environment variables, then explicit overrides.
+/// Nested tables are merged key by key; any other value is replaced.
+///
+/// # Examples
+/// ```
+/// use dev_utils::config::Config;
+/// use dev_utils::file::{create, TempDir};
+///
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join("app.toml"), "[server]\nhost = \"0.0.0.0\"\nport = 8080").unwrap();
+/// create(dir.join("app.yaml"), "server:\n  port: 9090").unwrap();
+///
+/// let config = Config::load(dir.join("app.{toml,yaml,json}").to_str().unwrap()).unwrap()
+///     .with_override("server.host", "localhost");
+///
+/// assert_eq!(config.get::<u16>("server.port").unwrap(), 9090);
+/// assert_eq!(config.get::<String>("server.host").unwrap(), "localhost");
+/// assert_eq!(config.get_or("server.workers", 4), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    root: YamlValue,
+}
+
+impl Default for Config {
+    fn default() -> Self {Self::new()}
+}
+
+impl Config {
+    /// Creates an empty config.
+    pub fn new() -> Self {Config { root: YamlValue::Mapping(Mapping::new()) }}
+
+    /// Loads and merges every existing file matching `pattern`.
+    ///
+    /// The pattern may list alternatives in braces (`"app.{toml,yaml,json}"`); the files are
+    /// merged in the order of the alternatives. The format is detected from each extension.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the [Config], or a [ConfigError] if no file exists
+    /// or one of them cannot be parsed.
+    pub fn load(pattern: &str) -> Result<Self, ConfigError> {
+        let paths = file::expand_braces(pattern)?.into_iter()
+            .map(PathBuf::from)
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        if paths.is_empty() {return Err(ConfigError::NotFound(pattern.to_string()));}
+        paths.iter().try_fold(Config::new(), |config, path| config.with_file(path))
+    }
+
+    /// Merges a config file on top of the current values.
+    pub fn with_file<P: AsRef<Path>>(self, path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let format = Format::from_path(path).ok_or_else(|| ConfigError::UnknownFormat(path.to_path_buf()))?;
+        self.with_str(&file::read(path)?, format)
+            .map_err(|err| match err {
+                ConfigError::Parse { message, .. } => ConfigError::Parse { path: path.to_path_buf(), message },
+                err => err,
+            })
+    }
+
+    /// Merges config data in the given [Format] on top of the current values.
+    pub fn with_str(mut self, content: &str, format: Format) -> Result<Self, ConfigError> {
+        let value = format.parse(content).map_err(|message| ConfigError::Parse { path: PathBuf::new(), message })?;
+        merge(&mut self.root, value);
+        Ok(self)
+    }
+
+    /// Merges the environment variables starting with `prefix` followed by `_`.
+    ///
+    /// The rest of the name is lowercased and `__` separates nested keys, so with the
+    /// prefix `APP`, `APP_SERVER__PORT=9000` sets `server.port` and `APP_LOG_LEVEL` sets `log_level`.
+    pub fn with_env(self, prefix: &str) -> Self {
+        let prefix = format!("{}_", prefix);
+        let mut vars = env::vars()
+            .filter_map(|(name, value)| Some((name.strip_prefix(&prefix)?.to_lowercase().replace("__", "."), value)))
+            .collect::<Vec<_>>();
+        vars.sort();
+        vars.into_iter().fold(self, |config, (key, value)| config.with_override(&key, &value))
+    }
+
+    /// Sets a value, overriding anything loaded before.
+    pub fn with_override(mut self, key: &str, value: &str) -> Self {
+        self.set(key, YamlValue::String(value.to_string()));
+        self
+    }
+
+    /// Sets the value at a dotted `key`, creating the intermediate tables.
+    pub fn set(&mut self, key: &str, value: YamlValue) {
+        let mut patch = value;
+        for part in key.rsplit('.') {
+            patch = YamlValue::Mapping(Mapping::from([(part.to_string(), patch)]));
+        }
+        merge(&mut self.root, patch);
+    }
+
+    /// Returns the raw value at a path like `"server.port"` or `"users[0].name"`.
+    pub fn get_value(&self, key: &str) -> Option<&YamlValue> {
+        self.root.get_path(key).filter(|value| !value.is_null())
+    }
+
+    /// Returns `true` if the key is set.
+    pub fn contains(&self, key: &str) -> bool {self.get_value(key).is_some()}
+
+    /// Reads the value at `key` converted to `T`.
+    ///
+    /// Scalars are converted through their text, so values set from environment
+    /// variables (always strings) are read the same way as typed file values.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the value, or [ConfigError::Missing] / [ConfigError::Invalid].
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, ConfigError> {
+        let value = self.get_value(key).ok_or_else(|| ConfigError::Missing(key.to_string()))?;
+        let text = match value {
+            YamlValue::String(s) => s.clone(),
+            YamlValue::Sequence(_) | YamlValue::Mapping(_) => String::new(),
+            scalar => scalar.to_string(),
+        };
+        text.parse().ok()
+            .filter(|_| !matches!(value, YamlValue::Sequence(_) | YamlValue::Mapping(_)))
+            .ok_or_else(|| ConfigError::Invalid { key: key.to_string(), value: value.to_string() })
+    }
+
+    /// Reads the value at `key`, falling back to `default` if it is missing or invalid.
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> T {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Reads the duration at `key`, written like `"30s"` or `"1h30m"` (see [parse_duration]).
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the duration, or [ConfigError::Missing] / [ConfigError::Invalid].
+    pub fn get_duration(&self, key: &str) -> Result<Duration, ConfigError> {
+        let text: String = self.get(key)?;
+        parse_duration(&text).map_err(|_| ConfigError::Invalid { key: key.to_string(), value: text })
+    }
+
+    /// Returns the merged tree of values.
+    pub fn values(&self) -> &YamlValue {&self.root}
+}
+
+// Merges `patch` into `base`: mappings are merged recursively, anything else is replaced
+fn merge(base: &mut YamlValue, patch: YamlValue) {
+    match (base, patch) {
+        (YamlValue::Mapping(base), YamlValue::Mapping(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {base.insert(key, value);},
+                }
+            }
+        },
+        (base, patch) => *base = patch,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::with_env_vars;
+
+    #[test]
+    fn test_merge_layers() {
+        let config = Config::new()
+            .with_str("[db]\nurl = \"postgres://x\"\npool = 5\ntags = [\"a\"]", Format::Toml).unwrap()
+            .with_str("{\"db\": {\"pool\": 10, \"tags\": [\"b\", \"c\"]}}", Format::Json).unwrap()
+            .with_override("db.timeout", "30");
+        assert_eq!(config.get::<String>("db.url").unwrap(), "postgres://x");
+        assert_eq!(config.get::<u32>("db.pool").unwrap(), 10);
+        assert_eq!(config.get::<String>("db.tags[1]").unwrap(), "c");
+        assert_eq!(config.get::<u64>("db.timeout").unwrap(), 30);
+    }
+
+    #[test]
+    fn test_units() {
+        let config = Config::new().with_str("timeout: 1m30s
+retry: 250
+cache: 64 MiB", Format::Yaml).unwrap();
+        assert_eq!(config.get_duration("timeout").unwrap(), Duration::from_secs(90));
+        assert!(matches!(config.get_duration("retry"), Err(ConfigError::Invalid { .. })));
+        assert_eq!(config.get::<crate::units::Bytes>("cache").unwrap().as_bytes(), 64 << 20);
+    }
+
+    #[test]
+    fn test_env_vars() {
+        let vars = [("DEVUTILS_CFG_TEST_SERVER__PORT", "7000"), ("DEVUTILS_CFG_TEST_DEBUG", "true")];
+        let config = with_env_vars(&vars, || Config::new()
+            .with_str("server:\n  port: 80\n  host: example.com", Format::Yaml).unwrap()
+            .with_env("DEVUTILS_CFG_TEST"));
+        assert_eq!(config.get::<u16>("server.port").unwrap(), 7000);
+        assert_eq!(config.get::<String>("server.host").unwrap(), "example.com");
+        assert!(config.get::<bool>("debug").unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        let config = Config::new().with_str("port: high\nlist: [1]", Format::Yaml).unwrap();
+        assert!(matches!(config.get::<u16>("port"), Err(ConfigError::Invalid { .. })));
+        assert!(matches!(config.get::<u16>("list"), Err(ConfigError::Invalid { .. })));
+        assert!(matches!(config.get::<u16>("nope"), Err(ConfigError::Missing(_))));
+        assert!(matches!(Config::load("missing_cfg.{toml,yaml}"), Err(ConfigError::NotFound(_))));
+        assert!(matches!(Config::new().with_str("a = ", Format::Toml), Err(ConfigError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_load_files() {
+        let dir = file::TempDir::new().unwrap();
+        file::create(dir.join("app.toml"), "name = \"toml\"\nlevel = 1").unwrap();
+        file::create(dir.join("app.json"), "{\"name\": \"json\"}").unwrap();
+        file::create(dir.join("broken.yml"), "a: [").unwrap();
+
+        let config = Config::load(dir.join("app.{toml,json}").to_str().unwrap()).unwrap();
+        assert_eq!(config.get::<String>("name").unwrap(), "json");
+        assert_eq!(config.get::<i32>("level").unwrap(), 1);
+        match Config::new().with_file(dir.join("broken.yml")) {
+            Err(ConfigError::Parse { path, .. }) => assert!(path.ends_with("broken.yml")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Versioned migrations of a config file schema.
+//!
+//! Tool authors register one migration function per schema version. When a config file
+//! is loaded, its stored version (an integer under the version key, `config_version` by
+//! default) is detected, the pending migrations are applied in order, the old file is
+//! backed up next to it, and the changes are logged through [crate::dlog].
+//!
+//! Migrations work on the raw text of the file, so they can be used with any format
+//! where the version is written as `key = N`, `key: N` or `"key": N`. A file without a version
+//! gets one in its own syntax: TOML, YAML or (for a file that is a JSON object) JSON.
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::file::{self, FileError};
+use crate::info;
+
+/// Represents errors that can occur when migrating a config file.
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The config file could not be read, backed up or written.
+    File(FileError),
+    /// A migration function failed.
+    Failed { version: u32, message: String },
+    /// The stored version is newer than the latest registered migration.
+    NewerVersion { found: u32, latest: u32 },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::File(err) => write!(f, "{}", err),
+            MigrationError::Failed { version, message } => write!(f, "Migration to version {} failed: {}", version, message),
+            MigrationError::NewerVersion { found, latest } => write!(f,
+                "Config version {} is newer than the latest supported version {}", found, latest
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<FileError> for MigrationError {
+    fn from(err: FileError) -> Self {MigrationError::File(err)}
+}
+
+/// What [Migrations::migrate_str] and [Migrations::load] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The version stored in the config before migrating (0 if there was none).
+    pub from: u32,
+    /// The version after migrating.
+    pub to: u32,
+    /// The descriptions of the applied migrations, in order.
+    pub applied: Vec<String>,
+    /// The backup of the original file, if it was migrated by [Migrations::load].
+    pub backup: Option<PathBuf>,
+}
+
+impl MigrationReport {
+    /// Returns `true` if at least one migration was applied.
+    pub fn changed(&self) -> bool {!self.applied.is_empty()}
+}
+
+type MigrationFn = Box<dyn Fn(&str) -> Result<String, String>>;
+
+struct Migration {
+    version: u32,
+    description: String,
+    apply: MigrationFn,
+}
+
+/// The registered migrations of a config schema.
+pub struct Migrations {
+    version_key: String,
+    migrations: Vec<Migration>,
+}
+
+impl Default for Migrations {
+    fn default() -> Self {Self::new()}
+}
+
+impl Migrations {
+    /// Creates an empty set of migrations, using the `config_version` key.
+    pub fn new() -> Self {
+        Migrations { version_key: "config_version".to_string(), migrations: Vec::new() }
+    }
+
+    /// Sets the key holding the schema version in the config file.
+    pub fn version_key(mut self, key: &str) -> Self {
+        self.version_key = key.to_string();
+        self
+    }
+
+    /// Registers the migration that upgrades a config to `version`.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version produced by the migration
+    /// * `description` - A short description, used in the logs and the report
+    /// * `migration` - The function transforming the previous version's content
+    pub fn add<F>(mut self, version: u32, description: &str, migration: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, String> + 'static,
+    {
+        self.migrations.push(Migration { version, description: description.to_string(), apply: Box::new(migration) });
+        self.migrations.sort_by_key(|m| m.version);
+        self
+    }
+
+    /// Returns the latest registered version (0 if there are no migrations).
+    pub fn latest(&self) -> u32 {self.migrations.last().map_or(0, |m| m.version)}
+
+    /// Returns the version stored in `content` (0 if the version key is missing).
+    pub fn stored_version(&self, content: &str) -> u32 {
+        content.lines().find_map(|line| self.parse_version_line(line)).unwrap_or(0)
+    }
+
+    // Parses `key = N`, `key: N` or `"key": N,` lines of the version key
+    fn parse_version_line(&self, line: &str) -> Option<u32> {
+        let (key, value) = line.split_once(['=', ':'])?;
+        let key = key.trim().trim_matches(['"', '\'']);
+        let value = value.trim().trim_end_matches(',').trim_matches(['"', '\'']);
+        (key == self.version_key).then(|| value.parse().ok()).flatten()
+    }
+
+    // Writes `version` in the version line, or adds one (see `insert_version`) if there is none
+    fn set_version(&self, content: &str, version: u32) -> String {
+        let mut found = false;
+        let lines = content.split_inclusive('\n').map(|line| match !found && self.parse_version_line(line).is_some() {
+            true => {
+                found = true;
+                let (key, value) = line.split_at(line.find(['=', ':']).unwrap_or(0) + 1);
+                let old = self.parse_version_line(line).unwrap_or(0).to_string();
+                format!("{}{}", key, value.replacen(&old, &version.to_string(), 1))
+            },
+            false => line.to_string(),
+        }).collect::<String>();
+        match found {
+            true => lines,
+            false => self.insert_version(&lines, version),
+        }
+    }
+
+    // Adds the version line in the syntax of the content, guessed from its first meaningful
+    // line: a JSON object gets a first member, YAML a `key: N` line and TOML a `key = N` line
+    fn insert_version(&self, content: &str, version: u32) -> String {
+        let first = content.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with('#')).unwrap_or("");
+        if first.starts_with('{') {
+            let (head, rest) = content.split_at(content.find('{').unwrap_or(0) + 1);
+            let entry = format!("\"{}\": {}", self.version_key, version);
+            return match rest.trim_start_matches([' ', '\t', '\r']).starts_with('\n') {
+                // a multi-line object: on its own line, indented like the next member
+                true => {
+                    let indent = rest.lines().skip(1).find(|line| !line.trim().is_empty())
+                        .map_or("", |line| &line[..line.len() - line.trim_start().len()]);
+                    let separator = if rest.trim_start().starts_with('}') {""} else {","};
+                    format!("{}\n{}{}{}{}", head, indent, entry, separator, rest)
+                },
+                false if rest.trim_start().starts_with('}') => format!("{}{}{}", head, entry, rest.trim_start()),
+                false => format!("{}{}, {}", head, entry, rest.trim_start()),
+            };
+        }
+
+        let is_yaml = first.starts_with("---") || match (first.find(':'), first.find('=')) {
+            (Some(colon), Some(equals)) => colon < equals,
+            (colon, _) => colon.is_some() && !first.starts_with('['),
+        };
+        match is_yaml {
+            // after the document start marker, if there is one
+            true => match content.strip_prefix("---\n") {
+                Some(rest) => format!("---\n{}: {}\n{}", self.version_key, version, rest),
+                None => format!("{}: {}\n{}", self.version_key, version, content),
+            },
+            false => format!("{} = {}\n{}", self.version_key, version, content),
+        }
+    }
+
+    /// Applies the pending migrations to the content of a config file.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the migrated content and the [MigrationReport], or a
+    /// `MigrationError` if a migration failed or the stored version is unknown.
+    pub fn migrate_str(&self, content: &str) -> Result<(String, MigrationReport), MigrationError> {
+        let from = self.stored_version(content);
+        if from > self.latest() {
+            return Err(MigrationError::NewerVersion { found: from, latest: self.latest() });
+        }
+
+        let mut content = content.to_string();
+        let mut report = MigrationReport { from, to: from, applied: Vec::new(), backup: None };
+        for migration in self.migrations.iter().filter(|m| m.version > from) {
+            let migrated = (migration.apply)(&content).map_err(|message| MigrationError::Failed { version: migration.version, message })?;
+            content = self.set_version(&migrated, migration.version);
+            report.to = migration.version;
+            report.applied.push(migration.description.clone());
+        }
+        Ok((content, report))
+    }
+
+    /// Loads a config file, migrating it in place if it is outdated.
+    ///
+    /// Before writing the migrated content, the original file is copied to
+    /// `<file name>.v<old version>.bak` in the same directory, and the new content is written
+    /// atomically (see [file::write_atomic]). Each applied migration is
+    /// logged at the info level.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the config file
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the (migrated) content and the [MigrationReport],
+    /// or a `MigrationError`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::{file, config::migrations::Migrations};
+    ///
+    /// let path = file::create("migrations_example.toml", "config_version = 1\nport = 80\n").unwrap();
+    /// let migrations = Migrations::new()
+    ///     .add(1, "initial schema", |cfg| Ok(cfg.to_string()))
+    ///     .add(2, "add `host`", |cfg| Ok(format!("{}host = \"localhost\"\n", cfg)));
+    ///
+    /// let (config, report) = migrations.load(&path).unwrap();
+    /// assert!(config.starts_with("config_version = 2\n"));
+    /// assert_eq!(report.applied, vec!["add `host`"]);
+    /// assert!(report.backup.as_ref().unwrap().exists());
+    ///
+    /// file::delete(&path).unwrap();
+    /// file::delete(report.backup.unwrap()).unwrap();
+    /// ```
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> Result<(String, MigrationReport), MigrationError> {
+        let path = path.as_ref();
+        let (content, mut report) = self.migrate_str(&file::read(path)?)?;
+        if !report.changed() {return Ok((content, report));}
+
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let backup = path.with_file_name(format!("{}.v{}.bak", name, report.from));
+        file::copy(path, &backup)?;
+        file::write_atomic(path, &content)?;
+
+        let skipped = self.migrations.iter().filter(|m| m.version <= report.from).count();
+        for (migration, description) in self.migrations[skipped..].iter().zip(&report.applied) {
+            info!("Migrated {} to version {}: {}", path.display(), migration.version, description);
+        }
+        info!("Backed up the previous config (version {}) to {}", report.from, backup.display());
+        report.backup = Some(backup);
+        Ok((content, report))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrations() -> Migrations {
+        Migrations::new()
+            .add(2, "split `addr`", |cfg| Ok(cfg.replace("addr = \"localhost:80\"", "host = \"localhost\"\nport = 80")))
+            .add(1, "rename `name`", |cfg| Ok(cfg.replace("name =", "title =")))
+    }
+
+    #[test]
+    fn test_version_detection() {
+        let m = Migrations::new().version_key("schema");
+        assert_eq!(m.stored_version("a = 1\nschema = 3\n"), 3);
+        assert_eq!(m.stored_version("schema: 4\n"), 4);
+        assert_eq!(m.stored_version("{\n  \"schema\": 5,\n}"), 5);
+        assert_eq!(m.stored_version("version = \"1.0\"\n"), 0);
+        assert_eq!(m.set_version("{\n  \"schema\": 5,\n}", 6), "{\n  \"schema\": 6,\n}");
+    }
+
+    #[test]
+    fn test_insert_missing_version() {
+        let m = Migrations::new().add(1, "initial schema", |cfg| Ok(cfg.to_string()));
+        let migrated = |content: &str| m.migrate_str(content).unwrap().0;
+        assert_eq!(migrated("# app\nname = \"app\"\n"), "config_version = 1\n# app\nname = \"app\"\n");
+        assert_eq!(migrated("[server]\nhost = \"a:b\"\n"), "config_version = 1\n[server]\nhost = \"a:b\"\n");
+
+        let yaml = migrated("name: app\nurl: \"http://a=b\"\n");
+        assert_eq!(yaml, "config_version: 1\nname: app\nurl: \"http://a=b\"\n");
+        assert_eq!(m.stored_version(&yaml), 1);
+        assert_eq!(migrated("---\nname: app\n"), "---\nconfig_version: 1\nname: app\n");
+
+        let json = migrated("{\n  \"name\": \"app\"\n}\n");
+        assert_eq!(json, "{\n  \"config_version\": 1,\n  \"name\": \"app\"\n}\n");
+        assert_eq!(m.stored_version(&json), 1);
+        assert_eq!(migrated("{\"name\": \"app\"}"), "{\"config_version\": 1, \"name\": \"app\"}");
+        assert_eq!(migrated("{}"), "{\"config_version\": 1}");
+        assert_eq!(migrated("{\n}"), "{\n\"config_version\": 1\n}");
+    }
+
+    #[test]
+    fn test_migrate_in_order() {
+        let (content, report) = migrations().migrate_str("name = \"app\"\naddr = \"localhost:80\"\n").unwrap();
+        assert_eq!(content, "config_version = 2\ntitle = \"app\"\nhost = \"localhost\"\nport = 80\n");
+        assert_eq!(report.applied, vec!["rename `name`", "split `addr`"]);
+
+        let (_, report) = migrations().migrate_str(&content).unwrap();
+        assert!(!report.changed());
+    }
+
+    #[test]
+    fn test_migration_errors() {
+        let failing = Migrations::new().add(1, "fails", |_| Err("missing field".to_string()));
+        assert!(matches!(failing.migrate_str(""), Err(MigrationError::Failed { version: 1, .. })));
+        assert!(matches!(migrations().migrate_str("config_version = 9"), Err(MigrationError::NewerVersion { found: 9, latest: 2 })));
+    }
+}
@@ -0,0 +1,132 @@
+//! Low-level terminal output primitives.
+//!
+//! This module provides building blocks for custom terminal layouts, such as
+//! a [Region] of reserved rows that can be rewritten in place,
+//! [progress bars](progress::ProgressBar) that fall back to log lines outside terminals,
+//! interactive [prompts](prompt) (menus, confirmations and passwords),
+//! [keyboard events](events) (blocking and polled key reads), and
+//! [terminal control](term) (cursor movement, clearing, alternate screen, raw mode, size).
+//!
+//! # Examples
+//! ```no_run
+//! use dev_utils::console::Region;
+//!
+//! let mut region = Region::new(2).unwrap();
+//! for i in 0..=100 {
+//!     region.write_line(0, &format!("Downloading... {i}%")).unwrap();
+//!     region.write_line(1, &format!("{}", "#".repeat(i / 5))).unwrap();
+//! }
+//! // the cursor is restored below the region when it is dropped
+//! ```
+use std::io::{self, Stdout, Write};
+
+pub mod events;
+pub mod progress;
+pub mod prompt;
+pub mod term;
+
+use term::Command;
+
+
+/// A block of reserved terminal rows that can be written independently.
+///
+/// On creation the rows are reserved below the cursor (scrolling the terminal if needed)
+/// and the cursor position is saved. [Region::write_line] rewrites a single row in place,
+/// and on drop the cursor is moved right below the region.
+pub struct Region<W: Write = Stdout> {
+    writer: W,
+    rows: usize,
+}
+
+impl Region<Stdout> {
+    /// Reserves `rows` lines on stdout.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The number of rows to reserve
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the new `Region`, or an `io::Error`.
+    pub fn new(rows: usize) -> io::Result<Self> {Self::with_writer(io::stdout(), rows)}
+}
+
+impl<W: Write> Region<W> {
+    /// Reserves `rows` lines on the given writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The output to draw on (usually a terminal)
+    /// * `rows` - The number of rows to reserve
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the new `Region`, or an `io::Error`.
+    pub fn with_writer(mut writer: W, rows: usize) -> io::Result<Self> {
+        // make room for the region (scrolling if needed), then go back to its first row
+        write!(writer, "{}{}\r{}", "\n".repeat(rows), Command::MoveUp(rows as u16), Command::SaveCursor)?;
+        writer.flush()?;
+        Ok(Region { writer, rows })
+    }
+
+    /// Returns the number of rows of the region.
+    pub fn rows(&self) -> usize {self.rows}
+
+    /// Replaces the content of row `index` (0-indexed) with `text`.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The row to write to
+    /// * `text` - The content of the row (should not contain newlines)
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing `()` if successful, or an `io::Error` of kind
+    /// `InvalidInput` if `index` is out of bounds.
+    pub fn write_line(&mut self, index: usize, text: &str) -> io::Result<()> {
+        if index >= self.rows {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("row {} is out of bounds for a region of {} rows", index, self.rows)
+            ));
+        }
+        write!(self.writer, "{}{}\r{}{}", Command::RestoreCursor, Command::MoveDown(index as u16), Command::ClearLine, text)?;
+        self.writer.flush()
+    }
+
+    /// Clears every row of the region.
+    pub fn clear(&mut self) -> io::Result<()> {
+        (0..self.rows).try_for_each(|i| self.write_line(i, ""))
+    }
+}
+
+impl<W: Write> Drop for Region<W> {
+    fn drop(&mut self) {
+        // restore the cursor and move it below the region
+        let _ = write!(self.writer, "{}{}\r", Command::RestoreCursor, Command::MoveDown(self.rows as u16));
+        let _ = self.writer.flush();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_output() {
+        let mut buffer = Vec::new();
+        {
+            let mut region = Region::with_writer(&mut buffer, 2).unwrap();
+            region.write_line(1, "second").unwrap();
+        }
+        let output = String::from_utf8(buffer).unwrap();
+        assert_eq!(output, "\n\n\x1b[2A\r\x1b7\x1b8\x1b[1B\r\x1b[2Ksecond\x1b8\x1b[2B\r");
+    }
+
+    #[test]
+    fn test_region_bounds() {
+        let mut region = Region::with_writer(Vec::new(), 1).unwrap();
+        assert!(region.write_line(0, "ok").is_ok());
+        assert_eq!(region.write_line(1, "oops").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
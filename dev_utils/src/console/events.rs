@@ -0,0 +1,298 @@
+//! Keyboard input: blocking and polled key reads without extra dependencies.
+//!
+//! The terminal is put in [raw mode](super::term::RawMode) while a key is read, so keys are
+//! available as soon as they are pressed (no need for `Enter`) and are not echoed.
+//! Escape sequences (arrows, function keys, `Home`, ...) are decoded into a [Key].
+//!
+//! Keep a [RawMode](super::term::RawMode) guard alive around a read loop to avoid
+//! switching the terminal mode on every key (and missing the keys typed in between).
+//!
+//! # Examples
+//! ```no_run
+//! use std::time::Duration;
+//! use dev_utils::console::{events::{poll_key, Key}, term::RawMode};
+//!
+//! let _raw = RawMode::enable().unwrap();
+//! loop {
+//!     match poll_key(Duration::from_millis(100)).unwrap() {
+//!         Some(Key::Esc) | Some(Key::Ctrl('c')) => break,
+//!         Some(key) => print!("{:?}\r\n", key),
+//!         None => {},  // time to redraw, animate, ...
+//!     }
+//! }
+//! ```
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use super::term::{self, RawMode};
+
+/// A key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A printable character (including space).
+    Char(char),
+    Enter,
+    Tab,
+    /// `Shift+Tab`.
+    BackTab,
+    Backspace,
+    Delete,
+    Esc,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    /// A function key, from `F(1)` to `F(12)`.
+    F(u8),
+    /// A letter pressed with `Ctrl` (lowercase), e.g. `Ctrl('c')`.
+    Ctrl(char),
+    /// A character pressed with `Alt`.
+    Alt(char),
+    /// A sequence that could not be decoded.
+    Unknown,
+}
+
+// The names of the keys without a payload, as written by `Display` and read by `FromStr`
+const KEY_NAMES: [(Key, &str); 16] = [
+    (Key::Enter, "Enter"), (Key::Tab, "Tab"), (Key::BackTab, "BackTab"), (Key::Backspace, "Backspace"),
+    (Key::Delete, "Delete"), (Key::Esc, "Esc"), (Key::Up, "Up"), (Key::Down, "Down"), (Key::Left, "Left"),
+    (Key::Right, "Right"), (Key::Home, "Home"), (Key::End, "End"), (Key::PageUp, "PageUp"),
+    (Key::PageDown, "PageDown"), (Key::Insert, "Insert"), (Key::Unknown, "Unknown"),
+];
+
+/// Formats the key as the character itself (`a`, ` `) or its name (`Enter`, `F5`,
+/// `Ctrl+c`, `Alt+x`), which [Key::from_str] reads back.
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Key::Char(c) => write!(f, "{}", c),
+            Key::F(n) => write!(f, "F{}", n),
+            Key::Ctrl(c) => write!(f, "Ctrl+{}", c),
+            Key::Alt(c) => write!(f, "Alt+{}", c),
+            key => f.write_str(KEY_NAMES.iter().find(|(k, _)| k == key).map_or("Unknown", |(_, name)| name)),
+        }
+    }
+}
+
+/// Error returned when a string is not a key written by [Key]'s `Display`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError(pub String);
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {write!(f, "invalid key: {:?}", self.0)}
+}
+
+impl Error for ParseKeyError {}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let single = |s: &str| {
+            let mut chars = s.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        };
+        if let Some(c) = single(s) {return Ok(Key::Char(c));}
+        if let Some((key, _)) = KEY_NAMES.iter().find(|(_, name)| *name == s) {return Ok(*key);}
+        let key = match s {
+            s if s.starts_with("Ctrl+") => single(&s[5..]).map(Key::Ctrl),
+            s if s.starts_with("Alt+") => single(&s[4..]).map(Key::Alt),
+            s if s.starts_with('F') => s[1..].parse::<u8>().ok().filter(|n| (1..=12).contains(n)).map(Key::F),
+            _ => None,
+        };
+        key.ok_or_else(|| ParseKeyError(s.to_string()))
+    }
+}
+
+// Keys decoded from a read but not returned yet (a single read can hold several keys)
+static PENDING: Mutex<VecDeque<Key>> = Mutex::new(VecDeque::new());
+
+/// Blocks until a key is pressed and returns it.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the key, or an `io::Error` (of kind `UnexpectedEof`
+/// if stdin is closed).
+pub fn read_key() -> io::Result<Key> {
+    if let Some(key) = next_pending() {return Ok(key);}
+    let _raw = RawMode::enable()?;
+    read_keys()
+}
+
+/// Waits up to `timeout` for a key press.
+///
+/// A zero timeout checks for a key without blocking.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the key, `None` if no key was pressed in time, or an `io::Error`.
+pub fn poll_key(timeout: Duration) -> io::Result<Option<Key>> {
+    if let Some(key) = next_pending() {return Ok(Some(key));}
+    let _raw = RawMode::enable()?;
+    match term::wait_for_input(timeout)? {
+        true => read_keys().map(Some),
+        false => Ok(None),
+    }
+}
+
+/// Queues keys to be returned by the next [read_key] and [poll_key] calls, before any
+/// real input. This drives interactive code (menus, prompts) from tests or recorded sessions.
+///
+/// # Examples
+/// ```
+/// use dev_utils::console::events::{inject_keys, read_key, Key};
+///
+/// inject_keys([Key::Down, Key::Enter]);
+/// assert_eq!(read_key().unwrap(), Key::Down);
+/// assert_eq!(read_key().unwrap(), Key::Enter);
+/// ```
+pub fn inject_keys<I: IntoIterator<Item = Key>>(keys: I) {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).extend(keys);
+}
+
+fn next_pending() -> Option<Key> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).pop_front()
+}
+
+// Reads the available input, returning the first key and queueing the others
+fn read_keys() -> io::Result<Key> {
+    // at least as big as the buffer of stdin, so the read bypasses it
+    // (otherwise buffered bytes would be invisible to `wait_for_input`)
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = io::stdin().lock().read(&mut buf)?;
+        if n == 0 {return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stdin was closed"));}
+        let mut keys = decode_keys(&buf[..n]).into_iter();
+        if let Some(first) = keys.next() {
+            PENDING.lock().unwrap_or_else(|e| e.into_inner()).extend(keys);
+            return Ok(first);
+        }
+    }
+}
+
+/// Decodes raw terminal input into keys.
+pub(crate) fn decode_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let (key, len) = decode_key(&bytes[i..]);
+        keys.push(key);
+        i += len;
+    }
+    keys
+}
+
+// Decodes the key at the start of `bytes`, returning it with the number of bytes used
+fn decode_key(bytes: &[u8]) -> (Key, usize) {
+    match bytes {
+        [0x1b, b'[', rest @ ..] => decode_csi(rest).map_or((Key::Unknown, 2), |(key, len)| (key, len + 2)),
+        [0x1b, b'O', code, ..] => (match code {
+            b'A' => Key::Up,
+            b'B' => Key::Down,
+            b'C' => Key::Right,
+            b'D' => Key::Left,
+            b'H' => Key::Home,
+            b'F' => Key::End,
+            b'P'..=b'S' => Key::F(code - b'P' + 1),
+            _ => Key::Unknown,
+        }, 3),
+        [0x1b] | [0x1b, 0x1b, ..] => (Key::Esc, 1),
+        [0x1b, rest @ ..] => match decode_key(rest) {
+            (Key::Char(c), len) => (Key::Alt(c), len + 1),
+            _ => (Key::Esc, 1),
+        },
+        [b'\r' | b'\n', ..] => (Key::Enter, 1),
+        [b'\t', ..] => (Key::Tab, 1),
+        [0x7f | 0x08, ..] => (Key::Backspace, 1),
+        [b @ 0x01..=0x1a, ..] => (Key::Ctrl((b'a' + b - 1) as char), 1),
+        [b, ..] if *b < 0x20 => (Key::Unknown, 1),
+        _ => {
+            // a (possibly multi-byte) UTF-8 character
+            let len = match bytes[0] {0xf0.. => 4, 0xe0.. => 3, 0xc0.. => 2, _ => 1}.min(bytes.len());
+            let key = std::str::from_utf8(&bytes[..len]).ok()
+                .and_then(|s| s.chars().next())
+                .map_or(Key::Unknown, Key::Char);
+            (key, len)
+        },
+    }
+}
+
+// Decodes a CSI sequence (after `ESC [`): parameters followed by a final byte
+fn decode_csi(bytes: &[u8]) -> Option<(Key, usize)> {
+    let end = bytes.iter().position(|b| (0x40..=0x7e).contains(b))?;
+    let params = std::str::from_utf8(&bytes[..end]).ok()?;
+    // modifiers (`1;5A` is Ctrl+Up) are ignored
+    let code = params.split(';').next().unwrap_or("");
+    let key = match (bytes[end], code) {
+        (b'A', _) => Key::Up,
+        (b'B', _) => Key::Down,
+        (b'C', _) => Key::Right,
+        (b'D', _) => Key::Left,
+        (b'H', _) => Key::Home,
+        (b'F', _) => Key::End,
+        (b'Z', _) => Key::BackTab,
+        (b'~', "1" | "7") => Key::Home,
+        (b'~', "2") => Key::Insert,
+        (b'~', "3") => Key::Delete,
+        (b'~', "4" | "8") => Key::End,
+        (b'~', "5") => Key::PageUp,
+        (b'~', "6") => Key::PageDown,
+        (b'~', "11") => Key::F(1),
+        (b'~', "12") => Key::F(2),
+        (b'~', "13") => Key::F(3),
+        (b'~', "14") => Key::F(4),
+        (b'~', "15") => Key::F(5),
+        (b'~', "17") => Key::F(6),
+        (b'~', "18") => Key::F(7),
+        (b'~', "19") => Key::F(8),
+        (b'~', "20") => Key::F(9),
+        (b'~', "21") => Key::F(10),
+        (b'~', "23") => Key::F(11),
+        (b'~', "24") => Key::F(12),
+        _ => Key::Unknown,
+    };
+    Some((key, end + 1))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_keys() {
+        assert_eq!(decode_keys(b"\x1b[A\x1b[Bq\r"), vec![Key::Up, Key::Down, Key::Char('q'), Key::Enter]);
+        assert_eq!(decode_keys(b"\x1b"), vec![Key::Esc]);
+        assert_eq!(decode_keys("é\x7f\x03".as_bytes()), vec![Key::Char('é'), Key::Backspace, Key::Ctrl('c')]);
+    }
+
+    #[test]
+    fn test_decode_sequences() {
+        assert_eq!(decode_keys(b"\x1b[3~\x1b[5~\x1b[H\x1b[4~"), vec![Key::Delete, Key::PageUp, Key::Home, Key::End]);
+        assert_eq!(decode_keys(b"\x1bOP\x1b[15~\x1b[24~"), vec![Key::F(1), Key::F(5), Key::F(12)]);
+        assert_eq!(decode_keys(b"\x1b[1;5C\x1b[Z"), vec![Key::Right, Key::BackTab]);
+        assert_eq!(decode_keys(b"\x1bx\x1b\x1b"), vec![Key::Alt('x'), Key::Esc, Key::Esc]);
+        assert_eq!(decode_keys(b"\x1b[99"), vec![Key::Unknown, Key::Char('9'), Key::Char('9')]);
+    }
+
+    #[test]
+    fn test_key_names() {
+        let keys = [Key::Char('a'), Key::Char(' '), Key::Char('+'), Key::Enter, Key::PageDown, Key::F(12), Key::Ctrl('c'), Key::Alt('+'), Key::Unknown];
+        for key in keys {
+            assert_eq!(key.to_string().parse::<Key>(), Ok(key), "{}", key);
+        }
+        assert_eq!(Key::Ctrl('w').to_string(), "Ctrl+w");
+        assert_eq!("F13".parse::<Key>(), Err(ParseKeyError("F13".to_string())));
+        assert!("Ctrl+ab".parse::<Key>().is_err());
+        assert!("".parse::<Key>().is_err());
+    }
+}
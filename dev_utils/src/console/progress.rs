@@ -0,0 +1,270 @@
+//! Progress bars that degrade to periodic log lines when the output is not a terminal.
+//!
+//! On a terminal the bar is redrawn in place; in CI logs (or any piped output) a line such
+//! as `45% (450/1000), 2.1 MB/s, ETA 12s` is logged through [crate::dlog] at a
+//! configurable interval instead, so the output stays readable.
+//!
+//! # Examples
+//! ```
+//! use std::time::Duration;
+//! use dev_utils::console::progress::ProgressBar;
+//!
+//! let mut bar = ProgressBar::new(1000).label("Downloading").bytes().log_interval(Duration::from_secs(10));
+//! for _ in 0..10 {
+//!     bar.inc(100);
+//! }
+//! bar.finish();
+//! ```
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+use crate::dlog::{self, Level};
+use crate::format::term;
+use super::term::Command;
+
+// Minimum time between two redraws of a live bar
+const REDRAW_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How a [ProgressBar] reports its progress.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProgressMode {
+    /// A live bar redrawn in place (for terminals).
+    Bar,
+    /// An info log line written through [crate::dlog] at every log interval (for CI logs and files).
+    Log,
+}
+
+impl ProgressMode {
+    /// Returns [ProgressMode::Bar] when stdout is a terminal, [ProgressMode::Log] otherwise.
+    pub fn detect() -> Self {
+        match term::is_stdout_tty() {
+            true => ProgressMode::Bar,
+            false => ProgressMode::Log,
+        }
+    }
+}
+
+/// A progress indicator for a task with a known amount of work.
+///
+/// The [ProgressMode] is detected on creation and can be overridden with [ProgressBar::mode].
+pub struct ProgressBar<W: Write = Stdout> {
+    writer: W,
+    total: u64,
+    position: u64,
+    label: String,
+    width: usize,
+    bytes: bool,
+    mode: ProgressMode,
+    log_interval: Duration,
+    start: Instant,
+    last_report: Instant,
+    drawn: bool,
+    finished: bool,
+}
+
+impl ProgressBar<Stdout> {
+    /// Creates a progress bar for `total` units of work, drawn on stdout.
+    pub fn new(total: u64) -> Self {Self::with_writer(io::stdout(), total)}
+}
+
+impl<W: Write> ProgressBar<W> {
+    /// Creates a progress bar for `total` units of work, drawn on the given writer.
+    ///
+    /// The writer is only used by [ProgressMode::Bar]; log lines always go through [crate::dlog].
+    pub fn with_writer(writer: W, total: u64) -> Self {
+        let now = Instant::now();
+        ProgressBar {
+            writer, total, position: 0,
+            label: String::new(),
+            width: 30,
+            bytes: false,
+            mode: ProgressMode::detect(),
+            log_interval: Duration::from_secs(5),
+            start: now,
+            last_report: now,
+            drawn: false,
+            finished: false,
+        }
+    }
+
+    /// Sets the text shown before the bar (or at the start of each log line).
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Sets the width of the bar in characters (30 by default).
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Reports the rate in bytes per second (e.g. `2.1 MB/s`) instead of units per second.
+    pub fn bytes(mut self) -> Self {
+        self.bytes = true;
+        self
+    }
+
+    /// Overrides the detected [ProgressMode].
+    pub fn mode(mut self, mode: ProgressMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the time between two log lines in [ProgressMode::Log] (5 seconds by default).
+    pub fn log_interval(mut self, interval: Duration) -> Self {
+        self.log_interval = interval;
+        self
+    }
+
+    /// Returns the current position.
+    pub fn position(&self) -> u64 {self.position}
+
+    /// Advances the position by `delta`.
+    pub fn inc(&mut self, delta: u64) {self.set(self.position.saturating_add(delta))}
+
+    /// Moves to the given position (capped to the total).
+    pub fn set(&mut self, position: u64) {
+        self.position = position.min(self.total);
+        self.report(Instant::now(), false);
+    }
+
+    /// Marks the task as completed, reporting the final state once.
+    pub fn finish(&mut self) {
+        if self.finished {return;}
+        self.finished = true;
+        self.report(Instant::now(), true);
+    }
+
+    /// Returns the current status, e.g. `45% (450/1000), 2.1 MB/s, ETA 12s`.
+    pub fn status(&self) -> String {self.status_at(Instant::now())}
+
+    fn status_at(&self, now: Instant) -> String {
+        let elapsed = now.duration_since(self.start).as_secs_f64();
+        let rate = match elapsed > 0.0 {
+            true => self.position as f64 / elapsed,
+            false => 0.0,
+        };
+        let percent = match self.total {
+            0 => 100,
+            total => self.position * 100 / total,
+        };
+        let time = match (self.finished, rate > 0.0) {
+            (true, _) => format!("done in {}", format_duration(elapsed)),
+            (false, true) => format!("ETA {}", format_duration((self.total - self.position) as f64 / rate)),
+            (false, false) => "ETA ?".to_string(),
+        };
+        format!("{}% ({}/{}), {}, {}", percent, self.position, self.total, format_rate(rate, self.bytes), time)
+    }
+
+    fn report(&mut self, now: Instant, force: bool) {
+        let since_last = now.duration_since(self.last_report);
+        match self.mode {
+            ProgressMode::Bar if force || !self.drawn || since_last >= REDRAW_INTERVAL => {
+                let _ = self.draw(now);
+            },
+            ProgressMode::Log if force || since_last >= self.log_interval => {
+                let prefix = match self.label.is_empty() {
+                    true => String::new(),
+                    false => format!("{}: ", self.label),
+                };
+                dlog::emit(Level::Info, format_args!("{}{}", prefix, self.status_at(now)));
+            },
+            _ => return,
+        }
+        self.last_report = now;
+        self.drawn = true;
+    }
+
+    fn draw(&mut self, now: Instant) -> io::Result<()> {
+        let filled = match self.total {
+            0 => self.width,
+            total => (self.position as f64 / total as f64 * self.width as f64) as usize,
+        };
+        let label = match self.label.is_empty() {
+            true => String::new(),
+            false => format!("{} ", self.label),
+        };
+        write!(self.writer, "\r{}{}[{}{}] {}",
+            Command::ClearLine, label, "█".repeat(filled), "░".repeat(self.width - filled), self.status_at(now)
+        )?;
+        if self.finished {writeln!(self.writer)?;}
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Drop for ProgressBar<W> {
+    fn drop(&mut self) {
+        // leave the line of an unfinished live bar
+        if self.mode == ProgressMode::Bar && self.drawn && !self.finished {
+            let _ = writeln!(self.writer);
+        }
+    }
+}
+
+// Formats a rate with decimal units (kB/s, MB/s, ...) for bytes, or as units per second
+fn format_rate(rate: f64, bytes: bool) -> String {
+    if !bytes {return format!("{:.1}/s", rate);}
+    let units = ["B", "kB", "MB", "GB", "TB"];
+    let (mut value, mut unit) = (rate, 0);
+    while value >= 1000.0 && unit < units.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}/s", value, units[unit])
+}
+
+// Formats seconds as `12s`, `3m 05s` or `1h 02m`
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let mut bar = ProgressBar::with_writer(Vec::new(), 1000).bytes().mode(ProgressMode::Log).log_interval(Duration::MAX);
+        bar.start -= Duration::from_secs(10);
+        bar.position = 450;
+        let now = bar.start + Duration::from_secs(10);
+        assert_eq!(bar.status_at(now), "45% (450/1000), 45.0 B/s, ETA 12s");
+        bar.finished = true;
+        assert_eq!(bar.status_at(now), "45% (450/1000), 45.0 B/s, done in 10s");
+    }
+
+    #[test]
+    fn test_formatting() {
+        assert_eq!(format_rate(2_100_000.0, true), "2.1 MB/s");
+        assert_eq!(format_rate(12.25, false), "12.2/s");
+        assert_eq!(format_duration(185.0), "3m 05s");
+        assert_eq!(format_duration(3720.0), "1h 02m");
+    }
+
+    #[test]
+    fn test_live_bar() {
+        let mut output = Vec::new();
+        {
+            let mut bar = ProgressBar::with_writer(&mut output, 4).width(4).mode(ProgressMode::Bar);
+            bar.inc(2);
+            bar.finish();
+        }
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("\r\x1b[2K[██░░] 50% (2/4)"));
+        assert!(output.contains("done in") && output.ends_with("\n"));
+        assert_eq!(output.matches('\n').count(), 1);
+    }
+
+    #[test]
+    fn test_log_mode_does_not_draw() {
+        let mut output = Vec::new();
+        ProgressBar::with_writer(&mut output, 10).mode(ProgressMode::Log).inc(5);
+        assert!(output.is_empty());
+    }
+}
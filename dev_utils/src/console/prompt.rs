@@ -0,0 +1,274 @@
+//! Interactive prompts: confirmations, single and multiple choice menus, and passwords.
+//!
+//! Menus are navigated with the arrow keys (or `j`/`k`) when stdin is a terminal; the
+//! terminal is put in [raw mode](super::term::RawMode) and restored afterwards. When stdin
+//! is not a terminal (piped input), menus fall back to reading the number of the chosen
+//! item from a line.
+//!
+//! Every prompt returns an `io::Error` of kind `Interrupted` when the user presses
+//! `Ctrl+C` or `Esc`.
+//!
+//! # Examples
+//! ```no_run
+//! use dev_utils::console::prompt::{confirm, multi_select, password, select};
+//!
+//! let env = select("Choose env", &["dev", "staging", "prod"]).unwrap();
+//! let features = multi_select("Enable features", &["logs", "metrics", "tracing"]).unwrap();
+//! let secret = password("Token").unwrap();
+//! if confirm("Deploy now?").unwrap() {
+//!     println!("deploying to {} with {:?} ({} chars token)", env, features, secret.len());
+//! }
+//! ```
+use std::io::{self, BufRead, IsTerminal, Write};
+use crate::format::{Style, Stylize, CYAN, GREEN};
+use super::events::{read_key, Key};
+use super::term::{self, Command, RawMode};
+
+
+/// Asks a yes/no question, returning `false` when the answer is empty.
+///
+/// Accepts `y`, `yes`, `n` and `no` (case insensitive); any other answer asks again.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the answer, or an `io::Error` (of kind `UnexpectedEof`
+/// if stdin is closed).
+pub fn confirm(prompt: &str) -> io::Result<bool> {
+    loop {
+        print!("{} {} {} ", "?".color(GREEN), prompt.style(Style::Bold), "(y/N)".style(Style::Dim));
+        io::stdout().flush()?;
+        match parse_confirm(&read_line()?) {
+            Some(answer) => return Ok(answer),
+            None => println!("{}", "Please answer y or n".style(Style::Dim)),
+        }
+    }
+}
+
+fn parse_confirm(answer: &str) -> Option<bool> {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(true),
+        "" | "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn read_line() -> io::Result<String> {
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line)? {
+        0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stdin was closed")),
+        _ => Ok(line.trim_end_matches(['\n', '\r']).to_string()),
+    }
+}
+
+/// Shows a menu and returns the index of the chosen item.
+///
+/// Use the arrow keys (or `j`/`k`) to move and `Enter` to choose.
+///
+/// # Arguments
+///
+/// * `prompt` - The question shown above the menu
+/// * `items` - The choices (must not be empty)
+///
+/// # Returns
+///
+/// Returns a `Result` containing the index of the chosen item, or an `io::Error`.
+pub fn select<T: AsRef<str>>(prompt: &str, items: &[T]) -> io::Result<usize> {
+    let items = items.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    let chosen = run_menu(prompt, &items, false)?;
+    Ok(chosen[0])
+}
+
+/// Shows a menu where several items can be chosen, returning their indices (sorted).
+///
+/// Use the arrow keys (or `j`/`k`) to move, `Space` to toggle an item, `a` to toggle
+/// every item and `Enter` to confirm.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the indices of the chosen items, or an `io::Error`.
+pub fn multi_select<T: AsRef<str>>(prompt: &str, items: &[T]) -> io::Result<Vec<usize>> {
+    let items = items.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    run_menu(prompt, &items, true)
+}
+
+/// Reads a password without echoing it.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the password, or an `io::Error`.
+pub fn password(prompt: &str) -> io::Result<String> {
+    print!("{} {} ", "?".color(GREEN), prompt.style(Style::Bold));
+    io::stdout().flush()?;
+    if !io::stdin().is_terminal() {return read_line();}
+
+    let _raw = RawMode::enable()?;
+    let mut password = String::new();
+    let result = loop {
+        match read_key()? {
+            Key::Enter => break Ok(()),
+            Key::Ctrl('c') | Key::Esc => break Err(interrupted()),
+            Key::Backspace => {password.pop();},
+            Key::Char(c) => password.push(c),
+            _ => {},
+        }
+    };
+    print!("\r\n");
+    io::stdout().flush()?;
+    result.map(|_| password)
+}
+
+fn interrupted() -> io::Error {io::Error::new(io::ErrorKind::Interrupted, "prompt cancelled")}
+
+
+// The state of a (multi) selection menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Menu {
+    cursor: usize,
+    checked: Vec<bool>,
+    multiple: bool,
+}
+
+impl Menu {
+    fn new(len: usize, multiple: bool) -> Self {Menu { cursor: 0, checked: vec![false; len], multiple }}
+
+    // Applies a key, returning the chosen indices once the menu is confirmed
+    fn handle(&mut self, key: &Key) -> Option<io::Result<Vec<usize>>> {
+        let len = self.checked.len();
+        match key {
+            Key::Up | Key::Char('k') => self.cursor = (self.cursor + len - 1) % len,
+            Key::Down | Key::Char('j') | Key::Tab => self.cursor = (self.cursor + 1) % len,
+            Key::Char(' ') if self.multiple => self.checked[self.cursor] ^= true,
+            Key::Char('a') if self.multiple => {
+                let all = self.checked.iter().all(|&c| c);
+                self.checked.iter_mut().for_each(|c| *c = !all);
+            },
+            Key::Enter if self.multiple => return Some(Ok((0..len).filter(|&i| self.checked[i]).collect())),
+            Key::Enter => return Some(Ok(vec![self.cursor])),
+            Key::Ctrl('c') | Key::Esc => return Some(Err(interrupted())),
+            _ => {},
+        }
+        None
+    }
+
+    fn render(&self, items: &[&str]) -> Vec<String> {
+        items.iter().enumerate().map(|(i, item)| {
+            let pointer = if i == self.cursor {"❯".color(CYAN)} else {" ".to_string()};
+            let check = match (self.multiple, self.checked[i]) {
+                (false, _) => String::new(),
+                (true, true) => format!("{} ", "◉".color(GREEN)),
+                (true, false) => "◯ ".to_string(),
+            };
+            let item = if i == self.cursor {item.color(CYAN)} else {item.to_string()};
+            format!("{} {}{}", pointer, check, item)
+        }).collect()
+    }
+}
+
+fn run_menu(prompt: &str, items: &[&str], multiple: bool) -> io::Result<Vec<usize>> {
+    if items.is_empty() {return Err(io::Error::new(io::ErrorKind::InvalidInput, "a menu needs at least one item"));}
+    let hint = if multiple {"(space to toggle, enter to confirm)"} else {"(use arrow keys)"};
+    println!("{} {} {}", "?".color(GREEN), prompt.style(Style::Bold), hint.style(Style::Dim));
+    if !io::stdin().is_terminal() {return read_menu_line(items, multiple);}
+
+    let mut stdout = io::stdout();
+    let mut menu = Menu::new(items.len(), multiple);
+    let _raw = RawMode::enable()?;
+    let _hidden = HiddenCursor::hide()?;
+    draw(&mut stdout, &menu.render(items), None)?;
+
+    let result = loop {
+        if let Some(result) = menu.handle(&read_key()?) {break result;}
+        draw(&mut stdout, &menu.render(items), Some(items.len()))?;
+    };
+
+    // replace the menu (and the prompt line) with a one-line summary
+    write!(stdout, "{}\r{}", Command::MoveUp(items.len() as u16 + 1), Command::ClearToEnd)?;
+    let summary = match &result {
+        Ok(chosen) => chosen.iter().map(|&i| items[i]).collect::<Vec<_>>().join(", ").color(CYAN),
+        Err(_) => "cancelled".style(Style::Dim),
+    };
+    write!(stdout, "{} {} {}\r\n", "?".color(GREEN), prompt.style(Style::Bold), summary)?;
+    stdout.flush()?;
+    result
+}
+
+// Hides the cursor until dropped, so that it comes back on every return (like a failed `read_key`)
+struct HiddenCursor;
+
+impl HiddenCursor {
+    fn hide() -> io::Result<Self> {
+        term::hide_cursor()?;
+        Ok(HiddenCursor)
+    }
+}
+
+impl Drop for HiddenCursor {
+    fn drop(&mut self) {let _ = term::show_cursor();}
+}
+
+// Draws the lines of a menu, first moving up over the `previous` frame
+fn draw<W: Write>(out: &mut W, lines: &[String], previous: Option<usize>) -> io::Result<()> {
+    if let Some(rows) = previous {write!(out, "{}", Command::MoveUp(rows as u16))?;}
+    for line in lines {write!(out, "\r{}{}\r\n", Command::ClearLine, line)?;}
+    out.flush()
+}
+
+// Fallback for piped input: the items are listed with numbers and a line is read
+fn read_menu_line(items: &[&str], multiple: bool) -> io::Result<Vec<usize>> {
+    items.iter().enumerate().for_each(|(i, item)| println!("  {}) {}", i + 1, item));
+    loop {
+        print!("{} ", if multiple {"Numbers (comma separated):"} else {"Number:"}.style(Style::Dim));
+        io::stdout().flush()?;
+        let line = read_line()?;
+        let chosen = line.split(',')
+            .map(|n| n.trim().parse::<usize>().ok().filter(|n| (1..=items.len()).contains(n)).map(|n| n - 1))
+            .collect::<Option<Vec<_>>>();
+        match chosen {
+            Some(mut chosen) if multiple || chosen.len() == 1 => {
+                chosen.sort();
+                chosen.dedup();
+                return Ok(chosen);
+            },
+            _ => println!("{}", format!("Please enter a number between 1 and {}", items.len()).style(Style::Dim)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_navigation() {
+        let mut menu = Menu::new(3, false);
+        assert!(menu.handle(&Key::Up).is_none());
+        assert_eq!(menu.cursor, 2);  // wraps around
+        menu.handle(&Key::Down);
+        menu.handle(&Key::Char('j'));
+        assert_eq!(menu.handle(&Key::Enter).unwrap().unwrap(), vec![1]);
+        assert_eq!(menu.handle(&Key::Esc).unwrap().unwrap_err().kind(), io::ErrorKind::Interrupted);
+    }
+
+    #[test]
+    fn test_multi_select() {
+        let mut menu = Menu::new(3, true);
+        menu.handle(&Key::Char(' '));
+        menu.handle(&Key::Down);
+        menu.handle(&Key::Down);
+        menu.handle(&Key::Char(' '));
+        assert_eq!(menu.handle(&Key::Enter).unwrap().unwrap(), vec![0, 2]);
+        menu.handle(&Key::Char('a'));
+        assert_eq!(menu.checked, vec![true; 3]);
+
+        let lines = menu.render(&["a", "b", "c"]).iter().map(|l| crate::format::strip_ansi_codes(l)).collect::<Vec<_>>();
+        assert_eq!(lines, vec!["  ◉ a", "  ◉ b", "❯ ◉ c"]);
+    }
+
+    #[test]
+    fn test_parse_confirm() {
+        assert_eq!(parse_confirm(" Yes\n"), Some(true));
+        assert_eq!(parse_confirm(""), Some(false));
+        assert_eq!(parse_confirm("maybe"), None);
+    }
+}
@@ -0,0 +1,470 @@
+//! Terminal control: cursor movement, clearing, the alternate screen, raw mode and the terminal size.
+//!
+//! Every control sequence is a [Command], which can be written to any writer with `write!`
+//! (so it composes with other output), or sent directly to stdout with [execute] and the
+//! shorthand functions ([move_cursor], [clear_screen], [hide_cursor], ...).
+//!
+//! # Examples
+//! ```no_run
+//! use dev_utils::console::term::{self, AlternateScreen, Command};
+//!
+//! let (cols, rows) = term::terminal_size().unwrap_or((80, 24));
+//! let screen = AlternateScreen::enter().unwrap();  // left when dropped
+//! term::hide_cursor().unwrap();
+//! term::move_cursor(cols / 2 - 3, rows / 2).unwrap();
+//! print!("hello!{}", Command::MoveTo(0, rows - 1));
+//! term::show_cursor().unwrap();
+//! drop(screen);
+//! ```
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A terminal control sequence (ANSI/VT100).
+///
+/// Coordinates are 0-based: `(0, 0)` is the top-left cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Moves the cursor to column `x` and row `y`.
+    MoveTo(u16, u16),
+    /// Moves the cursor up by a number of rows.
+    MoveUp(u16),
+    /// Moves the cursor down by a number of rows.
+    MoveDown(u16),
+    /// Moves the cursor right by a number of columns.
+    MoveRight(u16),
+    /// Moves the cursor left by a number of columns.
+    MoveLeft(u16),
+    /// Moves the cursor to the first column of the current row.
+    MoveToLineStart,
+    SaveCursor,
+    RestoreCursor,
+    HideCursor,
+    ShowCursor,
+    /// Clears the whole current line (the cursor doesn't move).
+    ClearLine,
+    /// Clears from the cursor to the end of the screen.
+    ClearToEnd,
+    /// Clears the whole screen and moves the cursor to the top-left cell.
+    ClearScreen,
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::MoveTo(x, y) => write!(f, "\x1b[{};{}H", *y as u32 + 1, *x as u32 + 1),
+            Command::MoveUp(0) | Command::MoveDown(0) | Command::MoveRight(0) | Command::MoveLeft(0) => Ok(()),
+            Command::MoveUp(n) => write!(f, "\x1b[{}A", n),
+            Command::MoveDown(n) => write!(f, "\x1b[{}B", n),
+            Command::MoveRight(n) => write!(f, "\x1b[{}C", n),
+            Command::MoveLeft(n) => write!(f, "\x1b[{}D", n),
+            Command::MoveToLineStart => write!(f, "\r"),
+            Command::SaveCursor => write!(f, "\x1b7"),
+            Command::RestoreCursor => write!(f, "\x1b8"),
+            Command::HideCursor => write!(f, "\x1b[?25l"),
+            Command::ShowCursor => write!(f, "\x1b[?25h"),
+            Command::ClearLine => write!(f, "\x1b[2K"),
+            Command::ClearToEnd => write!(f, "\x1b[J"),
+            Command::ClearScreen => write!(f, "\x1b[2J\x1b[1;1H"),
+            Command::EnterAlternateScreen => write!(f, "\x1b[?1049h"),
+            Command::LeaveAlternateScreen => write!(f, "\x1b[?1049l"),
+        }
+    }
+}
+
+/// Writes a [Command] to stdout and flushes it.
+pub fn execute(command: Command) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "{}", command)?;
+    stdout.flush()
+}
+
+/// Moves the cursor to column `x` and row `y` (0-based).
+pub fn move_cursor(x: u16, y: u16) -> io::Result<()> {execute(Command::MoveTo(x, y))}
+
+/// Clears the current line and moves the cursor to its start.
+pub fn clear_line() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "{}{}", Command::ClearLine, Command::MoveToLineStart)?;
+    stdout.flush()
+}
+
+/// Clears the screen and moves the cursor to the top-left cell.
+pub fn clear_screen() -> io::Result<()> {execute(Command::ClearScreen)}
+
+/// Hides the cursor (see [show_cursor]).
+pub fn hide_cursor() -> io::Result<()> {execute(Command::HideCursor)}
+
+/// Shows the cursor again.
+pub fn show_cursor() -> io::Result<()> {execute(Command::ShowCursor)}
+
+/// Switches to the alternate screen buffer, keeping the normal screen intact.
+///
+/// Prefer [AlternateScreen], which leaves the alternate screen even on early returns.
+pub fn enter_alternate_screen() -> io::Result<()> {execute(Command::EnterAlternateScreen)}
+
+/// Switches back from the alternate screen buffer.
+pub fn leave_alternate_screen() -> io::Result<()> {execute(Command::LeaveAlternateScreen)}
+
+/// Keeps the terminal on the alternate screen buffer until dropped.
+///
+/// The cursor is shown again when leaving, in case it was hidden.
+#[must_use = "the alternate screen is left when the guard is dropped"]
+pub struct AlternateScreen {
+    _private: (),
+}
+
+impl AlternateScreen {
+    /// Switches to the alternate screen buffer.
+    pub fn enter() -> io::Result<Self> {
+        enter_alternate_screen()?;
+        Ok(AlternateScreen { _private: () })
+    }
+}
+
+impl Drop for AlternateScreen {
+    fn drop(&mut self) {
+        let _ = execute(Command::LeaveAlternateScreen);
+        let _ = execute(Command::ShowCursor);
+    }
+}
+
+/// Returns the size of the terminal as `(columns, rows)`.
+///
+/// The size of the terminal attached to stdout is queried from the OS; if stdout is not
+/// a terminal, the `COLUMNS` and `LINES` environment variables are used instead.
+///
+/// # Returns
+///
+/// Returns `None` if the size cannot be determined.
+pub fn terminal_size() -> Option<(u16, u16)> {
+    sys::terminal_size().or_else(|| {
+        let var = |name| std::env::var(name).ok()?.trim().parse::<u16>().ok().filter(|&n| n > 0);
+        Some((var("COLUMNS")?, var("LINES")?))
+    })
+}
+
+/// Puts the terminal in raw mode until dropped.
+///
+/// In raw mode, input is not echoed, not line-buffered, and `Ctrl+C` is read as a key
+/// instead of sending a signal. The previous mode is restored on drop, so guards can be nested.
+///
+/// # Examples
+/// ```no_run
+/// use dev_utils::console::{events, term::RawMode};
+///
+/// let raw = RawMode::enable().unwrap();
+/// let key = events::read_key().unwrap();
+/// drop(raw);
+/// println!("you pressed {:?}", key);
+/// ```
+#[must_use = "raw mode is disabled when the guard is dropped"]
+pub struct RawMode {
+    saved: sys::Saved,
+    outermost: bool,
+}
+
+// The mode saved by the outermost RawMode guard, for [reset]
+static ORIGINAL_MODE: Mutex<Option<sys::Saved>> = Mutex::new(None);
+
+impl RawMode {
+    /// Enables raw mode on the terminal attached to stdin.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the guard, or an `io::Error` if stdin is not a terminal.
+    pub fn enable() -> io::Result<Self> {
+        let saved = sys::enable_raw()?;
+        let mut original = ORIGINAL_MODE.lock().unwrap_or_else(|e| e.into_inner());
+        let outermost = original.is_none();
+        if outermost {*original = Some(saved.clone());}
+        Ok(RawMode { saved, outermost })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        sys::restore(&self.saved);
+        if self.outermost {ORIGINAL_MODE.lock().unwrap_or_else(|e| e.into_inner()).take();}
+    }
+}
+
+/// Puts the terminal back in a usable state: raw mode off, alternate screen left, cursor shown.
+///
+/// The guards ([RawMode], [AlternateScreen]) do this when dropped; call this where they
+/// won't be, like before exiting from a [signal handler](crate::signals) or a panic hook.
+/// Escape sequences are only written when stdout is a terminal.
+pub fn reset() {
+    if let Some(saved) = ORIGINAL_MODE.lock().unwrap_or_else(|e| e.into_inner()).take() {sys::restore(&saved);}
+    let mut stdout = io::stdout();
+    if stdout.is_terminal() {
+        let _ = write!(stdout, "{}{}", Command::LeaveAlternateScreen, Command::ShowCursor);
+        let _ = stdout.flush();
+    }
+}
+
+// Waits until stdin has input to read, or the timeout expires
+pub(crate) fn wait_for_input(timeout: Duration) -> io::Result<bool> {
+    sys::wait_for_input(timeout.as_millis().min(i32::MAX as u128) as u32)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))]
+mod sys {
+    use std::ffi::{c_int, c_ulong, c_short};
+    use std::io;
+
+    // The layout of `struct termios` and the flag values differ between platforms
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    mod platform {
+        pub type Flag = u32;
+        pub type NFds = std::ffi::c_ulong;
+        pub const TIOCGWINSZ: std::ffi::c_ulong = 0x5413;
+        pub const ECHO: Flag = 0o10;
+        pub const ICANON: Flag = 0o2;
+        pub const ISIG: Flag = 0o1;
+        pub const IEXTEN: Flag = 0o100000;
+        pub const VTIME: usize = 5;
+        pub const VMIN: usize = 6;
+
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        pub struct Termios {
+            pub iflag: Flag,
+            pub oflag: Flag,
+            pub cflag: Flag,
+            pub lflag: Flag,
+            pub line: u8,
+            pub cc: [u8; 32],
+            pub ispeed: Flag,
+            pub ospeed: Flag,
+        }
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    mod platform {
+        pub type Flag = std::ffi::c_ulong;
+        pub type NFds = std::ffi::c_uint;
+        pub const TIOCGWINSZ: std::ffi::c_ulong = 0x4008_7468;
+        pub const ECHO: Flag = 0x8;
+        pub const ICANON: Flag = 0x100;
+        pub const ISIG: Flag = 0x80;
+        pub const IEXTEN: Flag = 0x400;
+        pub const VMIN: usize = 16;
+        pub const VTIME: usize = 17;
+
+        #[repr(C)]
+        #[derive(Clone, Copy)]
+        pub struct Termios {
+            pub iflag: Flag,
+            pub oflag: Flag,
+            pub cflag: Flag,
+            pub lflag: Flag,
+            pub cc: [u8; 20],
+            pub ispeed: Flag,
+            pub ospeed: Flag,
+        }
+    }
+
+    use platform::*;
+
+    const STDIN: c_int = 0;
+    const STDOUT: c_int = 1;
+    const TCSANOW: c_int = 0;
+    const POLLIN: c_short = 1;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Winsize {
+        rows: u16,
+        cols: u16,
+        x_pixels: u16,
+        y_pixels: u16,
+    }
+
+    #[repr(C)]
+    struct PollFd {
+        fd: c_int,
+        events: c_short,
+        revents: c_short,
+    }
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+        fn tcgetattr(fd: c_int, termios: *mut Termios) -> c_int;
+        fn tcsetattr(fd: c_int, action: c_int, termios: *const Termios) -> c_int;
+        fn poll(fds: *mut PollFd, nfds: NFds, timeout: c_int) -> c_int;
+    }
+
+    pub fn terminal_size() -> Option<(u16, u16)> {
+        let mut size = Winsize::default();
+        // SAFETY: TIOCGWINSZ writes a `struct winsize` through the pointer
+        let result = unsafe {ioctl(STDOUT, TIOCGWINSZ, &mut size as *mut Winsize)};
+        (result == 0 && size.cols > 0 && size.rows > 0).then_some((size.cols, size.rows))
+    }
+
+    #[derive(Clone)]
+    pub struct Saved(Termios);
+
+    pub fn enable_raw() -> io::Result<Saved> {
+        let mut termios = std::mem::MaybeUninit::<Termios>::uninit();
+        // SAFETY: tcgetattr fully initializes the struct when it succeeds
+        let original = unsafe {
+            if tcgetattr(STDIN, termios.as_mut_ptr()) != 0 {return Err(io::Error::last_os_error());}
+            termios.assume_init()
+        };
+        let mut raw = original;
+        raw.lflag &= !(ECHO | ICANON | ISIG | IEXTEN);
+        raw.cc[VMIN] = 1;
+        raw.cc[VTIME] = 0;
+        // SAFETY: `raw` is a valid termios obtained from tcgetattr
+        if unsafe {tcsetattr(STDIN, TCSANOW, &raw)} != 0 {return Err(io::Error::last_os_error());}
+        Ok(Saved(original))
+    }
+
+    pub fn restore(saved: &Saved) {
+        // SAFETY: the saved termios was obtained from tcgetattr
+        unsafe {tcsetattr(STDIN, TCSANOW, &saved.0);}
+    }
+
+    pub fn wait_for_input(timeout_ms: u32) -> io::Result<bool> {
+        let mut fd = PollFd { fd: STDIN, events: POLLIN, revents: 0 };
+        // SAFETY: `fd` is a single valid pollfd
+        match unsafe {poll(&mut fd, 1, timeout_ms.min(c_int::MAX as u32) as c_int)} {
+            -1 => Err(io::Error::last_os_error()),
+            n => Ok(n > 0),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+    use std::io;
+
+    const STD_INPUT_HANDLE: u32 = -10i32 as u32;
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const ENABLE_PROCESSED_INPUT: u32 = 0x1;
+    const ENABLE_LINE_INPUT: u32 = 0x2;
+    const ENABLE_ECHO_INPUT: u32 = 0x4;
+    const ENABLE_VIRTUAL_TERMINAL_INPUT: u32 = 0x200;
+    const WAIT_OBJECT_0: u32 = 0;
+    const WAIT_FAILED: u32 = 0xFFFF_FFFF;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Coord {
+        x: i16,
+        y: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct SmallRect {
+        left: i16,
+        top: i16,
+        right: i16,
+        bottom: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ScreenBufferInfo {
+        size: Coord,
+        cursor_position: Coord,
+        attributes: u16,
+        window: SmallRect,
+        maximum_window_size: Coord,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(id: u32) -> *mut c_void;
+        fn GetConsoleScreenBufferInfo(handle: *mut c_void, info: *mut ScreenBufferInfo) -> i32;
+        fn GetConsoleMode(handle: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(handle: *mut c_void, mode: u32) -> i32;
+        fn WaitForSingleObject(handle: *mut c_void, milliseconds: u32) -> u32;
+    }
+
+    pub fn terminal_size() -> Option<(u16, u16)> {
+        let mut info = ScreenBufferInfo::default();
+        // SAFETY: plain console API calls on the standard output handle
+        let ok = unsafe {GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info)} != 0;
+        let (cols, rows) = (info.window.right - info.window.left + 1, info.window.bottom - info.window.top + 1);
+        (ok && cols > 0 && rows > 0).then_some((cols as u16, rows as u16))
+    }
+
+    #[derive(Clone)]
+    pub struct Saved(u32);
+
+    pub fn enable_raw() -> io::Result<Saved> {
+        let mut mode = 0;
+        // SAFETY: plain console API calls on the standard input handle
+        unsafe {
+            let handle = GetStdHandle(STD_INPUT_HANDLE);
+            if GetConsoleMode(handle, &mut mode) == 0 {return Err(io::Error::last_os_error());}
+            // virtual terminal input makes the special keys arrive as the same escape sequences as on unix
+            let raw = (mode & !(ENABLE_PROCESSED_INPUT | ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT)) | ENABLE_VIRTUAL_TERMINAL_INPUT;
+            if SetConsoleMode(handle, raw) == 0 {return Err(io::Error::last_os_error());}
+        }
+        Ok(Saved(mode))
+    }
+
+    pub fn restore(saved: &Saved) {
+        // SAFETY: restores the mode obtained from GetConsoleMode
+        unsafe {SetConsoleMode(GetStdHandle(STD_INPUT_HANDLE), saved.0);}
+    }
+
+    pub fn wait_for_input(timeout_ms: u32) -> io::Result<bool> {
+        // SAFETY: waits on the standard input handle
+        match unsafe {WaitForSingleObject(GetStdHandle(STD_INPUT_HANDLE), timeout_ms)} {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_FAILED => Err(io::Error::last_os_error()),
+            _ => Ok(false),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", windows)))]
+mod sys {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "raw terminal mode is not supported on this platform")
+    }
+
+    pub fn terminal_size() -> Option<(u16, u16)> {None}
+
+    #[derive(Clone)]
+    pub struct Saved;
+
+    pub fn enable_raw() -> io::Result<Saved> {Err(unsupported())}
+
+    pub fn restore(_: &Saved) {}
+
+    pub fn wait_for_input(_: u32) -> io::Result<bool> {Err(unsupported())}
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(Command::MoveTo(0, 0).to_string(), "\x1b[1;1H");
+        assert_eq!(Command::MoveTo(9, 4).to_string(), "\x1b[5;10H");
+        assert_eq!(Command::MoveUp(3).to_string(), "\x1b[3A");
+        assert_eq!(Command::MoveDown(0).to_string(), "");
+        assert_eq!(format!("{}{}", Command::ClearLine, Command::MoveToLineStart), "\x1b[2K\r");
+    }
+
+    #[test]
+    fn test_terminal_size_is_positive() {
+        if let Some((cols, rows)) = terminal_size() {
+            assert!(cols > 0 && rows > 0);
+        }
+    }
+}
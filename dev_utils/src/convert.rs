@@ -0,0 +1,19 @@
+//! Conversions between numbers and their textual representations.
+//!
+//! Arbitrary bases (2 to 62) are handled by [base_change](crate::base_change).
+//!
+//! # Modules
+//! - [roman] - Roman numerals (`1994` ↔ `MCMXCIV`)
+//! - [column] - Spreadsheet column letters (`28` ↔ `AB`)
+//!
+//! # Examples
+//! ```
+//! use dev_utils::convert::{column, roman};
+//!
+//! assert_eq!(roman::to_roman(2024).unwrap(), "MMXXIV");
+//! assert_eq!(roman::from_roman("xlii").unwrap(), 42);
+//! assert_eq!(column::to_column(28).unwrap(), "AB");
+//! assert_eq!(column::from_column("AB").unwrap(), 28);
+//! ```
+pub mod column;
+pub mod roman;
@@ -0,0 +1,110 @@
+//! Spreadsheet column letters: `A` to `Z`, then `AA`, `AB`, ... (bijective base 26).
+//!
+//! # Examples
+//! ```
+//! use dev_utils::convert::column::{from_column, to_column};
+//!
+//! assert_eq!(to_column(1).unwrap(), "A");
+//! assert_eq!(to_column(703).unwrap(), "AAA");
+//! assert_eq!(from_column("xfd").unwrap(), 16384);  // the last column of a spreadsheet
+//! ```
+use std::fmt;
+use std::error::Error;
+
+/// Represents errors that can occur when converting column letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnError {
+    /// Columns are numbered from 1.
+    Zero,
+    /// The string is empty or contains characters other than letters.
+    InvalidColumn(String),
+    /// The column number doesn't fit in a `u32`.
+    Overflow(String),
+}
+
+impl fmt::Display for ColumnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnError::Zero => write!(f, "columns are numbered from 1"),
+            ColumnError::InvalidColumn(s) => write!(f, "invalid column: {:?}", s),
+            ColumnError::Overflow(s) => write!(f, "column {:?} is too large", s),
+        }
+    }
+}
+
+impl Error for ColumnError {}
+
+/// Returns the letters of a column (`1` is `A`, `27` is `AA`).
+///
+/// # Returns
+///
+/// A `Result` containing the uppercase letters, or [ColumnError::Zero].
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::convert::column::to_column;
+///
+/// assert_eq!(to_column(26).unwrap(), "Z");
+/// assert_eq!(to_column(28).unwrap(), "AB");
+/// ```
+pub fn to_column(n: u32) -> Result<String, ColumnError> {
+    if n == 0 {return Err(ColumnError::Zero);}
+    let mut letters = Vec::new();
+    let mut rest = n;
+    while rest > 0 {
+        rest -= 1;
+        letters.push((b'A' + (rest % 26) as u8) as char);
+        rest /= 26;
+    }
+    Ok(letters.into_iter().rev().collect())
+}
+
+/// Returns the number of a column from its letters (in any case).
+///
+/// # Returns
+///
+/// A `Result` containing the number (from 1), or a [ColumnError].
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::convert::column::from_column;
+///
+/// assert_eq!(from_column("A").unwrap(), 1);
+/// assert_eq!(from_column("ab").unwrap(), 28);
+/// assert!(from_column("A1").is_err());
+/// ```
+pub fn from_column(s: &str) -> Result<u32, ColumnError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err(ColumnError::InvalidColumn(s.to_string()));
+    }
+    s.bytes().try_fold(0u32, |n, b| {
+        n.checked_mul(26)
+            .and_then(|n| n.checked_add((b.to_ascii_uppercase() - b'A') as u32 + 1))
+            .ok_or_else(|| ColumnError::Overflow(s.to_string()))
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for n in (1..20_000).chain([u32::MAX - 1, u32::MAX]) {
+            assert_eq!(from_column(&to_column(n).unwrap()), Ok(n));
+        }
+        assert_eq!(to_column(52).unwrap(), "AZ");
+        assert_eq!(to_column(53).unwrap(), "BA");
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(to_column(0), Err(ColumnError::Zero));
+        assert_eq!(from_column(""), Err(ColumnError::InvalidColumn(String::new())));
+        assert_eq!(from_column("É"), Err(ColumnError::InvalidColumn("É".to_string())));
+        assert_eq!(from_column("ZZZZZZZZ"), Err(ColumnError::Overflow("ZZZZZZZZ".to_string())));
+    }
+}
@@ -0,0 +1,130 @@
+//! Roman numerals, in their standard (subtractive) form, from 1 (`I`) to 3999 (`MMMCMXCIX`).
+//!
+//! # Examples
+//! ```
+//! use dev_utils::convert::roman::{from_roman, to_roman};
+//!
+//! assert_eq!(to_roman(1994).unwrap(), "MCMXCIV");
+//! assert_eq!(from_roman("MCMXCIV").unwrap(), 1994);
+//! assert!(from_roman("IIII").is_err());  // not in standard form
+//! ```
+use std::fmt;
+use std::error::Error;
+
+/// The largest number that can be written with standard Roman numerals.
+pub const MAX: u32 = 3999;
+
+// The values of the numerals, including the subtractive pairs, in decreasing order
+const NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"), (100, "C"), (90, "XC"),
+    (50, "L"), (40, "XL"), (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I"),
+];
+
+/// Represents errors that can occur when converting Roman numerals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RomanError {
+    /// The number is 0 or greater than [MAX].
+    OutOfRange(u32),
+    /// The string is not a Roman numeral in standard form.
+    InvalidNumeral(String),
+}
+
+impl fmt::Display for RomanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomanError::OutOfRange(n) => write!(f, "{} cannot be written in Roman numerals (1 to {})", n, MAX),
+            RomanError::InvalidNumeral(s) => write!(f, "invalid Roman numeral: {:?}", s),
+        }
+    }
+}
+
+impl Error for RomanError {}
+
+/// Writes a number in Roman numerals.
+///
+/// # Arguments
+///
+/// * `n` - The number, from 1 to [MAX]
+///
+/// # Returns
+///
+/// A `Result` containing the uppercase numeral, or [RomanError::OutOfRange].
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::convert::roman::to_roman;
+///
+/// assert_eq!(to_roman(4).unwrap(), "IV");
+/// assert_eq!(to_roman(3999).unwrap(), "MMMCMXCIX");
+/// assert!(to_roman(0).is_err());
+/// ```
+pub fn to_roman(n: u32) -> Result<String, RomanError> {
+    if !(1..=MAX).contains(&n) {return Err(RomanError::OutOfRange(n));}
+    let mut rest = n;
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while rest >= value {
+            out.push_str(numeral);
+            rest -= value;
+        }
+    }
+    Ok(out)
+}
+
+/// Reads a Roman numeral (in any case).
+///
+/// Only the standard form is accepted: the one [to_roman] produces, so that every number
+/// has a single representation (`IV`, but not `IIII` or `IIV`).
+///
+/// # Returns
+///
+/// A `Result` containing the number, or [RomanError::InvalidNumeral].
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::convert::roman::from_roman;
+///
+/// assert_eq!(from_roman("XLII").unwrap(), 42);
+/// assert_eq!(from_roman("mmxxiv").unwrap(), 2024);
+/// assert!(from_roman("VX").is_err());
+/// ```
+pub fn from_roman(s: &str) -> Result<u32, RomanError> {
+    let invalid = || RomanError::InvalidNumeral(s.to_string());
+    let numeral = s.trim().to_uppercase();
+    let mut rest = numeral.as_str();
+    let mut n = 0;
+    for (value, symbol) in NUMERALS {
+        while let Some(tail) = rest.strip_prefix(symbol) {
+            n += value;
+            rest = tail;
+        }
+    }
+    // the greedy reading accepts some non-standard forms (`IIII`), which don't round-trip
+    match rest.is_empty() && n > 0 && n <= MAX && to_roman(n).as_deref() == Ok(numeral.as_str()) {
+        true => Ok(n),
+        false => Err(invalid()),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for n in 1..=MAX {
+            assert_eq!(from_roman(&to_roman(n).unwrap()), Ok(n));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        for s in ["", "IIII", "VV", "IC", "MMMM", "XM", "ABC", "I I"] {
+            assert_eq!(from_roman(s), Err(RomanError::InvalidNumeral(s.to_string())), "{:?}", s);
+        }
+        assert_eq!(to_roman(4000), Err(RomanError::OutOfRange(4000)));
+    }
+}
@@ -0,0 +1,24 @@
+//! Cryptographic primitives implemented with the standard library only.
+//!
+//! These implementations are meant for development tooling (checksums, test fixtures,
+//! signing dev tokens), not as a replacement for audited cryptography crates.
+//!
+//! # Modules
+//! - [aes] - AES-128/256 with the CTR and GCM modes
+//! - [hash] - Hash functions (SHA-256, SHA-1) with streaming and one-shot APIs
+//! - [hmac] - HMAC-SHA256 message authentication and constant-time comparison
+//! - [jwt] - JSON Web Tokens: HS256 signing and verification, and claims inspection (requires the `file` feature)
+//! - [kdf] - PBKDF2-SHA256 key derivation and password hashing
+//!
+//! # Examples
+//! ```
+//! use dev_utils::crypto::hash::{sha256, to_hex};
+//!
+//! assert_eq!(to_hex(&sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+//! ```
+pub mod aes;
+pub mod hash;
+pub mod hmac;
+#[cfg(feature = "file")]
+pub mod jwt;
+pub mod kdf;
@@ -0,0 +1,420 @@
+//! The AES block cipher (FIPS 197) with the CTR and GCM modes (NIST SP 800-38A and 800-38D),
+//! for 128, 192 and 256 bits keys (requires the `crypto` feature).
+//!
+//! [encrypt] and [decrypt] use GCM, which also authenticates the data: a changed ciphertext,
+//! tag or associated data makes [decrypt] fail. [ctr] only encrypts.
+//!
+//! The implementation is constant-time-ish: the S-box is computed (an inversion in GF(2^8))
+//! instead of looked up in a table, and GHASH multiplies with masks instead of branches, so
+//! no memory access or branch depends on the key or the data. That makes it slower than
+//! table-based AES: fine for secrets in configs and test fixtures, not for bulk data.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::crypto::aes;
+//!
+//! let key = [7u8; 32];
+//! let nonce = [1u8; aes::NONCE_SIZE];  // never reuse a nonce with the same key
+//! let sealed = aes::encrypt(&key, &nonce, b"header", b"secret data").unwrap();
+//! assert_eq!(sealed.len(), b"secret data".len() + aes::TAG_SIZE);
+//!
+//! assert_eq!(aes::decrypt(&key, &nonce, b"header", &sealed).unwrap(), b"secret data");
+//! assert!(aes::decrypt(&key, &nonce, b"other header", &sealed).is_err());
+//! ```
+use std::error::Error;
+use std::fmt;
+use super::hmac::constant_time_eq;
+
+/// The size of an AES block, in bytes.
+pub const BLOCK_SIZE: usize = 16;
+/// The size of the GCM tag appended by [encrypt], in bytes.
+pub const TAG_SIZE: usize = 16;
+/// The recommended GCM nonce size, in bytes (other sizes are hashed into one).
+pub const NONCE_SIZE: usize = 12;
+
+/// An AES key schedule, to encrypt single blocks.
+///
+/// # Examples
+/// ```
+/// use dev_utils::crypto::aes::Aes;
+/// use dev_utils::crypto::hash::to_hex;
+///
+/// // FIPS 197, appendix C.1
+/// let key: Vec<u8> = (0..16).collect();
+/// let mut block = *b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xaa\xbb\xcc\xdd\xee\xff";
+/// Aes::new(&key).unwrap().encrypt_block(&mut block);
+/// assert_eq!(to_hex(&block), "69c4e0d86a7b0430d8cdb78070b4c55a");
+/// ```
+#[derive(Clone)]
+pub struct Aes {
+    round_keys: Vec<[u8; BLOCK_SIZE]>,
+}
+
+impl Aes {
+    /// Expands a 16, 24 or 32 bytes key (AES-128, AES-192 or AES-256).
+    pub fn new(key: &[u8]) -> Result<Self, AesError> {
+        if ![16, 24, 32].contains(&key.len()) {return Err(AesError::InvalidKeyLength(key.len()));}
+        let nk = key.len() / 4;
+        let rounds = nk + 6;
+        let mut words: Vec<[u8; 4]> = key.chunks_exact(4).map(|w| [w[0], w[1], w[2], w[3]]).collect();
+        let mut rcon = 1u8;
+        for i in nk..4 * (rounds + 1) {
+            let mut word = words[i - 1];
+            if i % nk == 0 {
+                word.rotate_left(1);
+                word = sub_word(word);
+                word[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                word = sub_word(word);
+            }
+            let previous = words[i - nk];
+            words.push([0, 1, 2, 3].map(|j| word[j] ^ previous[j]));
+        }
+        let round_keys = words.chunks_exact(4)
+            .map(|w| {
+                let mut key = [0; BLOCK_SIZE];
+                key.iter_mut().zip(w.concat()).for_each(|(k, b)| *k = b);
+                key
+            })
+            .collect();
+        Ok(Aes { round_keys })
+    }
+
+    /// Returns the key size in bits (128, 192 or 256).
+    pub fn key_bits(&self) -> usize {(self.round_keys.len() - 7) * 32}
+
+    /// Encrypts one block in place.
+    pub fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let (last, middle) = (self.round_keys.len() - 1, &self.round_keys[1..self.round_keys.len() - 1]);
+        add_round_key(block, &self.round_keys[0]);
+        for round_key in middle {
+            sub_bytes(block);
+            shift_rows(block);
+            mix_columns(block);
+            add_round_key(block, round_key);
+        }
+        sub_bytes(block);
+        shift_rows(block);
+        add_round_key(block, &self.round_keys[last]);
+    }
+
+    // XORs `data` with the encrypted counter blocks, starting at `counter`
+    fn apply_keystream(&self, counter: &mut [u8; BLOCK_SIZE], data: &mut [u8], increment: fn(&mut [u8; BLOCK_SIZE])) {
+        for chunk in data.chunks_mut(BLOCK_SIZE) {
+            let mut keystream = *counter;
+            self.encrypt_block(&mut keystream);
+            chunk.iter_mut().zip(keystream).for_each(|(b, k)| *b ^= k);
+            increment(counter);
+        }
+    }
+}
+
+impl fmt::Debug for Aes {
+    // the round keys give the key away
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {write!(f, "Aes{}", self.key_bits())}
+}
+
+/// Encrypts or decrypts `data` in CTR mode: the same call does both.
+///
+/// The whole 16 bytes `counter` is incremented (big-endian) after each block, as in
+/// SP 800-38A. CTR doesn't authenticate the data; prefer [encrypt] unless a format requires CTR.
+///
+/// # Examples
+/// ```
+/// use dev_utils::crypto::aes;
+///
+/// let (key, counter) = ([3u8; 16], [0u8; 16]);
+/// let encrypted = aes::ctr(&key, &counter, b"any length").unwrap();
+/// assert_eq!(aes::ctr(&key, &counter, &encrypted).unwrap(), b"any length");
+/// ```
+pub fn ctr(key: &[u8], counter: &[u8; BLOCK_SIZE], data: &[u8]) -> Result<Vec<u8>, AesError> {
+    let (mut output, mut counter) = (data.to_vec(), *counter);
+    Aes::new(key)?.apply_keystream(&mut counter, &mut output, |counter| {
+        let next = u128::from_be_bytes(*counter).wrapping_add(1);
+        *counter = next.to_be_bytes();
+    });
+    Ok(output)
+}
+
+/// Encrypts and authenticates `data` with AES-GCM.
+///
+/// The `aad` (associated data) is authenticated but not encrypted: a header or an id that
+/// must travel in clear with the ciphertext. The nonce must be unique for each message with
+/// the same key; [NONCE_SIZE] bytes are recommended.
+///
+/// # Returns
+/// A `Result` containing either the ciphertext followed by its [TAG_SIZE] bytes tag, or an
+/// [AesError] if the key or the nonce has an invalid length.
+pub fn encrypt(key: &[u8], nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>, AesError> {
+    let gcm = Gcm::new(key, nonce)?;
+    let mut output = data.to_vec();
+    gcm.aes.apply_keystream(&mut gcm.first_counter(), &mut output, inc32);
+    let tag = gcm.tag(aad, &output);
+    output.extend_from_slice(&tag);
+    Ok(output)
+}
+
+/// Checks and decrypts data sealed by [encrypt] (the ciphertext followed by its tag).
+///
+/// # Returns
+/// A `Result` containing either the plaintext, or an [AesError]: nothing is decrypted
+/// unless the tag matches the key, the nonce, the `aad` and the ciphertext.
+pub fn decrypt(key: &[u8], nonce: &[u8], aad: &[u8], data: &[u8]) -> Result<Vec<u8>, AesError> {
+    let gcm = Gcm::new(key, nonce)?;
+    let Some(split) = data.len().checked_sub(TAG_SIZE) else {return Err(AesError::AuthenticationFailed);};
+    let (ciphertext, tag) = data.split_at(split);
+    if !constant_time_eq(&gcm.tag(aad, ciphertext), tag) {return Err(AesError::AuthenticationFailed);}
+    let mut output = ciphertext.to_vec();
+    gcm.aes.apply_keystream(&mut gcm.first_counter(), &mut output, inc32);
+    Ok(output)
+}
+
+// The state of one GCM message: the cipher, the hash key and the pre-counter block (J0)
+struct Gcm {
+    aes: Aes,
+    hash_key: u128,
+    j0: [u8; BLOCK_SIZE],
+}
+
+impl Gcm {
+    fn new(key: &[u8], nonce: &[u8]) -> Result<Self, AesError> {
+        if nonce.is_empty() {return Err(AesError::InvalidNonceLength(0));}
+        let aes = Aes::new(key)?;
+        let mut h = [0; BLOCK_SIZE];
+        aes.encrypt_block(&mut h);
+        let hash_key = u128::from_be_bytes(h);
+        let j0 = match nonce.len() {
+            NONCE_SIZE => {
+                let mut j0 = [0; BLOCK_SIZE];
+                j0[..NONCE_SIZE].copy_from_slice(nonce);
+                j0[BLOCK_SIZE - 1] = 1;
+                j0
+            },
+            _ => ghash(hash_key, &[], nonce).to_be_bytes(),
+        };
+        Ok(Gcm { aes, hash_key, j0 })
+    }
+
+    fn first_counter(&self) -> [u8; BLOCK_SIZE] {
+        let mut counter = self.j0;
+        inc32(&mut counter);
+        counter
+    }
+
+    fn tag(&self, aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_SIZE] {
+        let mut mask = self.j0;
+        self.aes.encrypt_block(&mut mask);
+        (ghash(self.hash_key, aad, ciphertext) ^ u128::from_be_bytes(mask)).to_be_bytes()
+    }
+}
+
+// Increments the last 32 bits of a GCM counter block (wrapping)
+fn inc32(counter: &mut [u8; BLOCK_SIZE]) {
+    let low = u32::from_be_bytes([counter[12], counter[13], counter[14], counter[15]]).wrapping_add(1);
+    counter[12..].copy_from_slice(&low.to_be_bytes());
+}
+
+// GHASH of `aad` and `ciphertext`, each zero-padded to whole blocks, then their lengths in bits
+fn ghash(hash_key: u128, aad: &[u8], ciphertext: &[u8]) -> u128 {
+    let mut y = 0;
+    for data in [aad, ciphertext] {
+        for chunk in data.chunks(BLOCK_SIZE) {
+            let mut block = [0; BLOCK_SIZE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            y = gf128_mul(y ^ u128::from_be_bytes(block), hash_key);
+        }
+    }
+    let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+    gf128_mul(y ^ lengths, hash_key)
+}
+
+// Multiplication in GF(2^128) with GCM's bit order (the first bit is the lowest degree),
+// selecting with masks rather than branching on the bits
+fn gf128_mul(x: u128, y: u128) -> u128 {
+    const R: u128 = 0xe1 << 120;
+    let (mut z, mut v) = (0, y);
+    for i in (0..128).rev() {
+        z ^= v & 0u128.wrapping_sub((x >> i) & 1);
+        v = (v >> 1) ^ (R & 0u128.wrapping_sub(v & 1));
+    }
+    z
+}
+
+// Multiplication by x in GF(2^8), without branching on the high bit
+fn xtime(b: u8) -> u8 {(b << 1) ^ (0x1b & 0u8.wrapping_sub(b >> 7))}
+
+// The S-box of the 16 bytes of `lanes` at once (SWAR): the inverse in GF(2^8)
+// (x^254, 0 for 0) followed by the affine transformation
+fn sbox_lanes(lanes: u128) -> u128 {
+    const ONES: u128 = u128::from_ne_bytes([0x01; 16]);
+    let lane = |b: u8| ONES * b as u128;
+    let mul = |mut a: u128, mut b: u128| {
+        let mut p = 0;
+        for _ in 0..8 {
+            p ^= a & ((b & ONES) * 0xff);
+            a = ((a & lane(0x7f)) << 1) ^ (((a >> 7) & ONES) * 0x1b);
+            b >>= 1;
+        }
+        p
+    };
+    let square = |a: u128| mul(a, a);
+    let x2 = square(lanes);
+    let x3 = mul(x2, lanes);
+    let x12 = square(square(x3));
+    let x15 = mul(x12, x3);
+    let x240 = square(square(square(square(x15))));
+    let inverse = mul(mul(x240, x12), x2);  // x^254
+    let rotl = |a: u128, k: u32| ((a << k) & lane(0xff << k)) | ((a >> (8 - k)) & lane(0xff >> (8 - k)));
+    inverse ^ rotl(inverse, 1) ^ rotl(inverse, 2) ^ rotl(inverse, 3) ^ rotl(inverse, 4) ^ lane(0x63)
+}
+
+fn sub_bytes(state: &mut [u8; BLOCK_SIZE]) {*state = sbox_lanes(u128::from_le_bytes(*state)).to_le_bytes();}
+
+fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    let mut lanes = [0; BLOCK_SIZE];
+    lanes[..4].copy_from_slice(&word);
+    sub_bytes(&mut lanes);
+    [lanes[0], lanes[1], lanes[2], lanes[3]]
+}
+
+// The state is column-major: byte `r + 4c` is row `r` of column `c`
+fn shift_rows(state: &mut [u8; BLOCK_SIZE]) {
+    let old = *state;
+    for (i, byte) in state.iter_mut().enumerate() {
+        let (row, column) = (i % 4, i / 4);
+        *byte = old[row + 4 * ((column + row) % 4)];
+    }
+}
+
+fn mix_columns(state: &mut [u8; BLOCK_SIZE]) {
+    for column in state.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        column[0] ^= all ^ xtime(a0 ^ a1);
+        column[1] ^= all ^ xtime(a1 ^ a2);
+        column[2] ^= all ^ xtime(a2 ^ a3);
+        column[3] ^= all ^ xtime(a3 ^ a0);
+    }
+}
+
+fn add_round_key(state: &mut [u8; BLOCK_SIZE], round_key: &[u8; BLOCK_SIZE]) {
+    state.iter_mut().zip(round_key).for_each(|(b, k)| *b ^= k);
+}
+
+/// Represents errors that can occur when encrypting or decrypting with AES.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AesError {
+    /// The key is not 16, 24 or 32 bytes long.
+    InvalidKeyLength(usize),
+    /// The GCM nonce is empty.
+    InvalidNonceLength(usize),
+    /// The tag doesn't match: wrong key, nonce or associated data, or altered data.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for AesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AesError::InvalidKeyLength(len) => write!(f, "Invalid AES key length: {} bytes (expected 16, 24 or 32)", len),
+            AesError::InvalidNonceLength(len) => write!(f, "Invalid GCM nonce length: {} bytes", len),
+            AesError::AuthenticationFailed => write!(f, "Authentication failed: the data or its tag was altered"),
+        }
+    }
+}
+
+impl Error for AesError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash::to_hex;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    const PLAINTEXT: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+    const AAD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+    const KEY: &str = "feffe9928665731c6d6a8f9467308308";
+
+    #[test]
+    fn test_fips197_blocks() {
+        let block = hex("00112233445566778899aabbccddeeff");
+        for (key_len, expected) in [
+            (16, "69c4e0d86a7b0430d8cdb78070b4c55a"),
+            (24, "dda97ca4864cdfe06eaf70a0ec0d7191"),
+            (32, "8ea2b7ca516745bfeafc49904b496089"),
+        ] {
+            let aes = Aes::new(&(0..key_len).collect::<Vec<u8>>()).unwrap();
+            let mut output = [0; BLOCK_SIZE];
+            output.copy_from_slice(&block);
+            aes.encrypt_block(&mut output);
+            assert_eq!(to_hex(&output), expected);
+            assert_eq!(format!("{:?}", aes), format!("Aes{}", key_len as usize * 8));
+        }
+        assert_eq!(Aes::new(&[0; 20]).unwrap_err(), AesError::InvalidKeyLength(20));
+    }
+
+    #[test]
+    fn test_ctr_vectors() {
+        // SP 800-38A, F.5.1 and F.5.5 (first two blocks)
+        let counter: [u8; 16] = hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").try_into().unwrap();
+        let plaintext = hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        let aes128 = ctr(&hex("2b7e151628aed2a6abf7158809cf4f3c"), &counter, &plaintext).unwrap();
+        assert_eq!(to_hex(&aes128), "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff");
+        let aes256 = ctr(&hex("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"), &counter, &plaintext).unwrap();
+        assert_eq!(to_hex(&aes256), "601ec313775789a5b7a7f504bbf3d228f443e3ca4d62b59aca84e990cacaf5c5");
+        assert_eq!(ctr(&hex("2b7e151628aed2a6abf7158809cf4f3c"), &counter, &aes128[..20]).unwrap(), &plaintext[..20]);
+
+        // the counter wraps around
+        let wrapped = ctr(&[0; 16], &[0xff; 16], &[0; 32]).unwrap();
+        assert_eq!(to_hex(&wrapped), "3f5b8cc9ea855a0afa7347d23e8d664e66e94bd4ef8a2c3b884cfa59ca342b2e");
+    }
+
+    #[test]
+    fn test_gcm_vectors() {
+        // the test cases of the GCM specification (McGrew and Viega), also in NIST's CAVP
+        let cases = [
+            ("00000000000000000000000000000000", "000000000000000000000000", "", "", "", "58e2fccefa7e3061367f1d57a4e7455a"),
+            ("00000000000000000000000000000000", "000000000000000000000000", "", "00000000000000000000000000000000",
+                "0388dace60b6a392f328c2b971b2fe78", "ab6e47d42cec13bdf53a67b21257bddf"),
+            (KEY, "cafebabefacedbaddecaf888", AAD, PLAINTEXT,
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+                "5bc94fbc3221a5db94fae95ae7121a47"),
+            // 8 and 60 bytes nonces
+            (KEY, "cafebabefacedbad", AAD, PLAINTEXT,
+                "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c742373806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+                "3612d2e79e3b0785561be14aaca2fccb"),
+            (KEY, "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b", AAD, PLAINTEXT,
+                "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca701e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5",
+                "619cc5aefffe0bfa462af43c1699d050"),
+            ("0000000000000000000000000000000000000000000000000000000000000000", "000000000000000000000000", "", "", "", "530f8afbc74536b9a963b4f1c4cb738b"),
+            ("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308", "cafebabefacedbaddecaf888", AAD, PLAINTEXT,
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+                "76fc6ece0f4e1768cddf8853bb2d551b"),
+        ];
+        for (key, nonce, aad, plaintext, ciphertext, tag) in cases {
+            let (key, nonce, aad) = (hex(key), hex(nonce), hex(aad));
+            let sealed = encrypt(&key, &nonce, &aad, &hex(plaintext)).unwrap();
+            assert_eq!(to_hex(&sealed), format!("{}{}", ciphertext, tag));
+            assert_eq!(decrypt(&key, &nonce, &aad, &sealed).unwrap(), hex(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_gcm_authentication() {
+        let (key, nonce) = ([9u8; 16], [4u8; NONCE_SIZE]);
+        let sealed = encrypt(&key, &nonce, b"id=1", b"payload").unwrap();
+        for i in 0..sealed.len() {
+            let mut altered = sealed.clone();
+            altered[i] ^= 0x80;
+            assert_eq!(decrypt(&key, &nonce, b"id=1", &altered), Err(AesError::AuthenticationFailed));
+        }
+        assert_eq!(decrypt(&key, &nonce, b"id=2", &sealed), Err(AesError::AuthenticationFailed));
+        assert_eq!(decrypt(&key, &[5; NONCE_SIZE], b"id=1", &sealed), Err(AesError::AuthenticationFailed));
+        assert_eq!(decrypt(&key, &nonce, b"", &sealed[..TAG_SIZE - 1]), Err(AesError::AuthenticationFailed));
+        assert_eq!(encrypt(&key, &[], b"", b"").unwrap_err().to_string(), "Invalid GCM nonce length: 0 bytes");
+    }
+}
@@ -0,0 +1,348 @@
+//! Hash functions with streaming ([Sha256], [Sha1]) and one-shot ([sha256], [sha1]) APIs.
+//!
+//! SHA-1 is broken for signatures; it is only here for protocols that still require it
+//! (like the WebSocket handshake).
+use std::fmt;
+use std::io::{self, Read};
+use crate::codex::checksum::{self, Adler32, Crc32, Crc32c};
+
+/// The hash algorithms supported by [Algorithm::digest_reader] (and `file::hash`).
+///
+/// The checksums ([crate::codex::checksum]) are much faster, for quick integrity checks;
+/// their digest is the big-endian value (`cbf43926` for the CRC-32 of `123456789`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// SHA-256 (32 bytes digest).
+    Sha256,
+    /// SHA-1 (20 bytes digest).
+    Sha1,
+    /// CRC-32 checksum (4 bytes, not cryptographic).
+    Crc32,
+    /// CRC-32C checksum (4 bytes, not cryptographic).
+    Crc32c,
+    /// Adler-32 checksum (4 bytes, not cryptographic).
+    Adler32,
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Algorithm::Sha256 => write!(f, "SHA-256"),
+            Algorithm::Sha1 => write!(f, "SHA-1"),
+            Algorithm::Crc32 => write!(f, "CRC-32"),
+            Algorithm::Crc32c => write!(f, "CRC-32C"),
+            Algorithm::Adler32 => write!(f, "Adler-32"),
+        }
+    }
+}
+
+impl Algorithm {
+    /// Hashes everything read from `reader`, in blocks (the input is never fully loaded).
+    ///
+    /// # Returns
+    ///
+    /// Returns an `io::Result` containing the digest bytes.
+    pub fn digest_reader<R: Read>(self, mut reader: R) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0; 64 * 1024];
+        let mut read = |update: &mut dyn FnMut(&[u8])| -> io::Result<()> {
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) => return Ok(()),
+                    Ok(n) => update(&buffer[..n]),
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                }
+            }
+        };
+        match self {
+            Algorithm::Sha256 => {
+                let mut hasher = Sha256::new();
+                read(&mut |data| hasher.update(data))?;
+                Ok(hasher.finalize().to_vec())
+            },
+            Algorithm::Sha1 => {
+                let mut hasher = Sha1::new();
+                read(&mut |data| hasher.update(data))?;
+                Ok(hasher.finalize().to_vec())
+            },
+            Algorithm::Crc32 => {
+                let mut crc = Crc32::new();
+                read(&mut |data| crc.update(data))?;
+                Ok(crc.finalize().to_be_bytes().to_vec())
+            },
+            Algorithm::Crc32c => {
+                let mut crc = Crc32c::new();
+                read(&mut |data| crc.update(data))?;
+                Ok(crc.finalize().to_be_bytes().to_vec())
+            },
+            Algorithm::Adler32 => {
+                let mut adler = Adler32::new();
+                read(&mut |data| adler.update(data))?;
+                Ok(adler.finalize().to_be_bytes().to_vec())
+            },
+        }
+    }
+
+    /// Hashes a byte slice.
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::Sha256 => sha256(data).to_vec(),
+            Algorithm::Sha1 => sha1(data).to_vec(),
+            Algorithm::Crc32 => checksum::crc32(data).to_be_bytes().to_vec(),
+            Algorithm::Crc32c => checksum::crc32c(data).to_be_bytes().to_vec(),
+            Algorithm::Adler32 => checksum::adler32(data).to_be_bytes().to_vec(),
+        }
+    }
+}
+
+/// Formats bytes as a lowercase hexadecimal string.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const H0: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+/// A streaming SHA-256 hasher.
+///
+/// # Examples
+/// ```
+/// use dev_utils::crypto::hash::{sha256, Sha256};
+///
+/// let mut hasher = Sha256::new();
+/// hasher.update(b"hello ");
+/// hasher.update(b"world");
+/// assert_eq!(hasher.finalize(), sha256(b"hello world"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_len: usize,
+    total_len: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {Self::new()}
+}
+
+impl Sha256 {
+    /// The size of a SHA-256 block in bytes (used by HMAC).
+    pub const BLOCK_SIZE: usize = 64;
+
+    /// Creates a new hasher.
+    pub fn new() -> Self {
+        Sha256 { state: H0, block: [0; 64], block_len: 0, total_len: 0 }
+    }
+
+    /// Feeds data to the hasher.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        while !data.is_empty() {
+            let n = (64 - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[..n]);
+            self.block_len += n;
+            data = &data[n..];
+            if self.block_len == 64 {
+                compress(&mut self.state, &self.block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    /// Returns the digest of the data fed so far.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bit_len = self.total_len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.block_len != 56 {self.update(&[0]);}
+        self.update(&bit_len.to_be_bytes());
+
+        let mut digest = [0; 32];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+    let mut w = [0u32; 64];
+    for (i, chunk) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// Returns the SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// A streaming SHA-1 hasher, with the same API as [Sha256].
+///
+/// # Examples
+/// ```
+/// use dev_utils::crypto::hash::{sha1, to_hex, Sha1};
+///
+/// let mut hasher = Sha1::new();
+/// hasher.update(b"hello ");
+/// hasher.update(b"world");
+/// assert_eq!(hasher.finalize(), sha1(b"hello world"));
+/// assert_eq!(to_hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sha1 {
+    state: [u32; 5],
+    block: [u8; 64],
+    block_len: usize,
+    total_len: u64,
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {Self::new()}
+}
+
+impl Sha1 {
+    /// The size of a SHA-1 block in bytes.
+    pub const BLOCK_SIZE: usize = 64;
+
+    /// Creates a new hasher.
+    pub fn new() -> Self {
+        Sha1 { state: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0], block: [0; 64], block_len: 0, total_len: 0 }
+    }
+
+    /// Feeds data to the hasher.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        while !data.is_empty() {
+            let n = (64 - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[..n]);
+            self.block_len += n;
+            data = &data[n..];
+            if self.block_len == 64 {
+                compress_sha1(&mut self.state, &self.block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    /// Returns the digest of the data fed so far.
+    pub fn finalize(mut self) -> [u8; 20] {
+        let bit_len = self.total_len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.block_len != 56 {self.update(&[0]);}
+        self.update(&bit_len.to_be_bytes());
+
+        let mut digest = [0; 20];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+fn compress_sha1(state: &mut [u32; 5], block: &[u8; 64]) {
+    let mut w = [0u32; 80];
+    for (i, chunk) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, &word) in w.iter().enumerate() {
+        let (f, k) = match i / 20 {
+            0 => ((b & c) | (!b & d), 0x5a827999),
+            1 => (b ^ c ^ d, 0x6ed9eba1),
+            2 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            _ => (b ^ c ^ d, 0xca62c1d6),
+        };
+        let t = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+        (e, d, c, b, a) = (d, c, b.rotate_left(30), a, t);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// Returns the SHA-1 digest of `data`.
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_vectors() {
+        assert_eq!(to_hex(&sha256(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(
+            to_hex(&sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            to_hex(&sha256(&vec![b'a'; 1_000_000])),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn test_streaming() {
+        let data = (0..1000u32).map(|i| i as u8).collect::<Vec<_>>();
+        let mut hasher = Sha256::new();
+        data.chunks(7).for_each(|chunk| hasher.update(chunk));
+        assert_eq!(hasher.finalize(), sha256(&data));
+        assert_eq!(Algorithm::Sha256.digest_reader(&data[..]).unwrap(), Algorithm::Sha256.digest(&data));
+        assert_eq!(Algorithm::Sha1.digest_reader(&data[..]).unwrap(), Algorithm::Sha1.digest(&data));
+        for algorithm in [Algorithm::Crc32, Algorithm::Crc32c, Algorithm::Adler32] {
+            assert_eq!(algorithm.digest_reader(&data[..]).unwrap(), algorithm.digest(&data));
+        }
+        assert_eq!(to_hex(&Algorithm::Crc32.digest(b"123456789")), "cbf43926");
+    }
+
+    #[test]
+    fn test_sha1_vectors() {
+        assert_eq!(to_hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            to_hex(&sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(to_hex(&sha1(&vec![b'a'; 1_000_000])), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+}
@@ -0,0 +1,97 @@
+//! HMAC-SHA256 message authentication (RFC 2104), with streaming ([HmacSha256]) and
+//! one-shot ([hmac_sha256]) APIs.
+use super::hash::Sha256;
+
+/// A streaming HMAC-SHA256 computation.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::crypto::hash::to_hex;
+/// use dev_utils::crypto::hmac::{hmac_sha256, HmacSha256};
+///
+/// let mut mac = HmacSha256::new(b"key");
+/// mac.update(b"The quick brown fox ");
+/// mac.update(b"jumps over the lazy dog");
+/// let tag = mac.finalize();
+/// assert_eq!(to_hex(&tag), "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8");
+/// assert_eq!(tag, hmac_sha256(b"key", b"The quick brown fox jumps over the lazy dog"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct HmacSha256 {
+    inner: Sha256,
+    outer_key: [u8; Sha256::BLOCK_SIZE],
+}
+
+impl HmacSha256 {
+    /// Creates a computation with a key of any length (keys longer than a block are hashed).
+    pub fn new(key: &[u8]) -> Self {
+        let mut block = [0u8; Sha256::BLOCK_SIZE];
+        match key.len() > Sha256::BLOCK_SIZE {
+            true => block[..32].copy_from_slice(&super::hash::sha256(key)),
+            false => block[..key.len()].copy_from_slice(key),
+        }
+        let mut inner = Sha256::new();
+        inner.update(&block.map(|b| b ^ 0x36));
+        HmacSha256 { inner, outer_key: block.map(|b| b ^ 0x5c) }
+    }
+
+    /// Feeds data to the computation.
+    pub fn update(&mut self, data: &[u8]) {self.inner.update(data);}
+
+    /// Returns the authentication tag of the data fed so far.
+    pub fn finalize(self) -> [u8; 32] {
+        let mut outer = Sha256::new();
+        outer.update(&self.outer_key);
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+
+    /// Returns `true` if `tag` is the tag of the data fed so far, comparing in constant time.
+    pub fn verify(self, tag: &[u8]) -> bool {constant_time_eq(&self.finalize(), tag)}
+}
+
+/// Returns the HMAC-SHA256 tag of `data` with `key`.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = HmacSha256::new(key);
+    mac.update(data);
+    mac.finalize()
+}
+
+/// Compares two byte strings in a time that doesn't depend on where they differ
+/// (only on their lengths), to check secrets and tags without leaking them through timing.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash::to_hex;
+
+    #[test]
+    fn test_rfc4231_vectors() {
+        // test cases 1, 2 and 6 (a key longer than a block)
+        assert_eq!(to_hex(&hmac_sha256(&[0x0b; 20], b"Hi There")), "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+        assert_eq!(to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        assert_eq!(
+            to_hex(&hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let tag = hmac_sha256(b"secret", b"data");
+        let mac = || {
+            let mut mac = HmacSha256::new(b"secret");
+            mac.update(b"data");
+            mac
+        };
+        assert!(mac().verify(&tag));
+        assert!(!mac().verify(&tag[..31]));
+        assert!(!mac().verify(&hmac_sha256(b"secreT", b"data")));
+        assert!(constant_time_eq(b"", b"") && !constant_time_eq(b"a", b"b"));
+    }
+}
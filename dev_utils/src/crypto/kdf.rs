@@ -0,0 +1,151 @@
+//! Key derivation and password hashing with PBKDF2-HMAC-SHA256 (RFC 8018).
+//!
+//! [hash_password] stores everything needed to check a password in one string:
+//! `$pbkdf2-sha256$i=<iterations>$<salt>$<hash>`, the salt and hash in unpadded base64url.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::crypto::kdf::{hash_password_with, verify_password};
+//!
+//! let stored = hash_password_with("hunter2", 1_000);  // fewer iterations than the default, for the example
+//! assert!(stored.starts_with("$pbkdf2-sha256$i=1000$"));
+//! assert_eq!(verify_password("hunter2", &stored), Ok(true));
+//! assert_eq!(verify_password("hunter3", &stored), Ok(false));
+//! ```
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::codex::base64;
+use super::hmac::{constant_time_eq, HmacSha256};
+
+/// The iterations of [hash_password]: slow enough to resist brute force on dev data,
+/// fast enough for seeding scripts that hash many passwords.
+pub const DEFAULT_ITERATIONS: u32 = 100_000;
+
+const PREFIX: &str = "$pbkdf2-sha256$";
+
+/// Derives a 32 bytes key from a password and a salt, with PBKDF2-HMAC-SHA256.
+///
+/// # Examples
+/// ```
+/// use dev_utils::crypto::hash::to_hex;
+/// use dev_utils::crypto::kdf::pbkdf2_sha256;
+///
+/// let key = pbkdf2_sha256(b"password", b"salt", 1);
+/// assert_eq!(to_hex(&key), "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b");
+/// ```
+pub fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    // the keyed state is computed once and cloned for each iteration
+    let keyed = HmacSha256::new(password);
+    let mut mac = keyed.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());  // the index of the (only) block
+    let mut u = mac.finalize();
+    let mut key = u;
+    for _ in 1..iterations {
+        let mut mac = keyed.clone();
+        mac.update(&u);
+        u = mac.finalize();
+        key.iter_mut().zip(u).for_each(|(k, u)| *k ^= u);
+    }
+    key
+}
+
+/// Hashes a password with a random salt and [DEFAULT_ITERATIONS], returning the encoded
+/// string to store.
+pub fn hash_password(password: &str) -> String {hash_password_with(password, DEFAULT_ITERATIONS)}
+
+/// Like [hash_password], with the given number of iterations.
+pub fn hash_password_with(password: &str, iterations: u32) -> String {
+    let salt = random_salt();
+    let hash = pbkdf2_sha256(password.as_bytes(), &salt, iterations.max(1));
+    format!("{}i={}${}${}", PREFIX, iterations.max(1), base64::encode_url(&salt), base64::encode_url(&hash))
+}
+
+/// Checks a password against a string from [hash_password], in constant time.
+///
+/// # Returns
+/// A `Result` containing whether the password matches, or a [KdfError] if the string is not
+/// a PBKDF2-SHA256 hash.
+pub fn verify_password(password: &str, encoded: &str) -> Result<bool, KdfError> {
+    let invalid = |message: &str| KdfError::InvalidFormat(message.to_string());
+    let Some(rest) = encoded.strip_prefix(PREFIX) else {
+        let algorithm = encoded.trim_start_matches('$').split('$').next().unwrap_or("");
+        return Err(KdfError::UnsupportedAlgorithm(algorithm.to_string()));
+    };
+    let parts: Vec<&str> = rest.split('$').collect();
+    let [params, salt, hash] = parts[..] else {return Err(invalid("expected parameters, salt and hash"));};
+    let iterations: u32 = params.strip_prefix("i=")
+        .and_then(|i| i.parse().ok())
+        .filter(|&i| i > 0)
+        .ok_or_else(|| invalid("invalid iterations"))?;
+    let salt = base64::decode_url(salt).map_err(|_| invalid("invalid salt"))?;
+    let hash = base64::decode_url(hash).map_err(|_| invalid("invalid hash"))?;
+    Ok(constant_time_eq(&pbkdf2_sha256(password.as_bytes(), &salt, iterations), &hash))
+}
+
+// A unique salt: salts don't have to be secret, only different for each password
+fn random_salt() -> [u8; 16] {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    let mut salt = [0; 16];
+    for (i, chunk) in salt.chunks_exact_mut(8).enumerate() {
+        // each `RandomState` has its own random keys
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_usize(i);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    salt
+}
+
+/// Represents errors that can occur when reading an encoded password hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KdfError {
+    /// The hash uses another algorithm (like `argon2id` or `bcrypt`).
+    UnsupportedAlgorithm(String),
+    /// The hash is not in the `$pbkdf2-sha256$i=<iterations>$<salt>$<hash>` format.
+    InvalidFormat(String),
+}
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KdfError::UnsupportedAlgorithm(algorithm) => write!(f, "Unsupported password hash algorithm: {:?}", algorithm),
+            KdfError::InvalidFormat(message) => write!(f, "Invalid password hash: {}", message),
+        }
+    }
+}
+
+impl Error for KdfError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash::to_hex;
+
+    #[test]
+    fn test_pbkdf2_vectors() {
+        assert_eq!(to_hex(&pbkdf2_sha256(b"password", b"salt", 4096)), "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a");
+        // RFC 7914, section 11 (first 32 bytes)
+        assert_eq!(to_hex(&pbkdf2_sha256(b"passwd", b"salt", 1)), "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc");
+    }
+
+    #[test]
+    fn test_verify_password() {
+        // as written by Python's hashlib.pbkdf2_hmac
+        let stored = "$pbkdf2-sha256$i=1000$MDEyMzQ1Njc4OWFiY2RlZg$pj4T35D2v4tYmC1sTJ1y5tcMADOdtnQGvuHmyYDQh2g";
+        assert_eq!(verify_password("hunter2", stored), Ok(true));
+        assert_eq!(verify_password("Hunter2", stored), Ok(false));
+
+        let (a, b) = (hash_password_with("same", 10), hash_password_with("same", 10));
+        assert_ne!(a, b);  // different salts
+        assert!(verify_password("same", &a).unwrap() && verify_password("same", &b).unwrap());
+
+        assert_eq!(verify_password("x", "$2b$12$abc"), Err(KdfError::UnsupportedAlgorithm("2b".to_string())));
+        assert_eq!(verify_password("x", "$pbkdf2-sha256$i=0$AA$AA").unwrap_err().to_string(), "Invalid password hash: invalid iterations");
+        assert_eq!(verify_password("x", "$pbkdf2-sha256$i=1$AA"), Err(KdfError::InvalidFormat("expected parameters, salt and hash".to_string())));
+    }
+}
@@ -7,8 +7,11 @@
 //! # Features
 //! - [Date], [Time], and [DateTime] structs for representing date and time components
 //! - Methods for creating and validating date and time objects
-//! - Conversion between timestamps and [DateTime] objects
+//! - Conversion between timestamps (in seconds, milliseconds or nanoseconds) and [DateTime] objects
 //! - Parsing of datetime strings
+//! - Calendar queries: [Weekday], ordinal day and ISO week of a [Date]
+//! - Date arithmetic (days, months and years, with an [OverflowPolicy] for month ends)
+//! - Human-readable relative times ("3 minutes ago", "in 2 days")
 //! - Error handling for invalid dates, times, and parsing errors
 //!
 //! # Examples
@@ -25,34 +28,72 @@
 //! let parsed_dt = DateTime::from_str("2023-05-01 12:34:56").unwrap();
 //! assert_eq!(parsed_dt, dt);
 //! ```
-use std::path::Display;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use std::fmt::{self};
-use std::str::FromStr;
-use std::error::Error;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use core::time::Duration;
+use core::fmt;
+use core::str::FromStr;
+use core::error::Error;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(feature = "std")]
+use std::sync::{PoisonError, RwLock};
+use dev_macros::EnumStr;
 
 
 /// Represents a date with year, month, and day.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Date { year: i32, month: u8, day: u8, }
 
-// Represents a time with hour, minute, and second.
+// Represents a time with hour, minute, second and nanosecond.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Time { hour: u8, minute: u8, second: u8, }
+pub struct Time { hour: u8, minute: u8, second: u8, nanosecond: u32, }
 
 /// Represents a combination of date and time.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DateTime { pub date: Date, pub time: Time, }
 
+/// A day of the week.
+///
+/// Converts to and from its English name (`"Monday"`); [Weekday::iter] lists the days from Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumStr)]
+pub enum Weekday { Monday, Tuesday, Wednesday, Thursday, Friday, Saturday, Sunday, }
+
+impl Weekday {
+    /// Returns the ISO 8601 number of the day, from 1 (Monday) to 7 (Sunday).
+    pub const fn number_from_monday(&self) -> u8 {*self as u8 + 1}
+
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday, Weekday::Tuesday, Weekday::Wednesday, Weekday::Thursday,
+        Weekday::Friday, Weekday::Saturday, Weekday::Sunday,
+    ];
+}
+
+/// What to do when adding months or years lands on a day that doesn't exist
+/// (e.g. January 31st + 1 month, or February 29th + 1 year).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Use the last day of the month instead (January 31st + 1 month = February 28th).
+    #[default]
+    Clamp,
+    /// Carry the extra days into the next month (January 31st + 1 month = March 3rd).
+    Wrap,
+    /// Return a [DateTimeError::InvalidDate].
+    Error,
+}
+
 /// Represents errors that can occur when working with dates and times.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DateTimeError {
     InvalidYear(i32),
     InvalidMonth(u8),
     InvalidDay(u8),
+    InvalidOrdinal(u16),
     InvalidHour(u8),
     InvalidMinute(u8),
     InvalidSecond(u8),
+    InvalidNanosecond(u32),
     InvalidDate { year: i32, month: u8, day: u8 },
     InvalidTime { hour: u8, minute: u8, second: u8 },
     ParseError(String),
@@ -64,9 +105,11 @@ impl fmt::Display for DateTimeError {
             Self::InvalidYear(year) => write!(f, "Invalid year: {}", year),
             Self::InvalidMonth(month) => write!(f, "Invalid month: {}", month),
             Self::InvalidDay(day) => write!(f, "Invalid day: {}", day),
+            Self::InvalidOrdinal(day) => write!(f, "Invalid day of the year: {}", day),
             Self::InvalidHour(hour) => write!(f, "Invalid hour: {}", hour),
             Self::InvalidMinute(minute) => write!(f, "Invalid minute: {}", minute),
             Self::InvalidSecond(second) => write!(f, "Invalid second: {}", second),
+            Self::InvalidNanosecond(nanosecond) => write!(f, "Invalid nanosecond: {}", nanosecond),
             Self::InvalidDate { year, month, day } => write!(f, "Invalid date: {}-{}-{}", year, month, day),
             Self::InvalidTime { hour, minute, second } => write!(f, "Invalid time: {}:{}:{}", hour, minute, second),
             Self::ParseError(msg) => write!(f, "Parse error: {}", msg),
@@ -76,6 +119,25 @@ impl fmt::Display for DateTimeError {
 
 impl Error for DateTimeError {}
 
+#[cfg(feature = "std")]
+impl crate::error::ErrorCode for DateTimeError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidYear(_) => "datetime.invalid_year",
+            Self::InvalidMonth(_) => "datetime.invalid_month",
+            Self::InvalidDay(_) => "datetime.invalid_day",
+            Self::InvalidOrdinal(_) => "datetime.invalid_ordinal",
+            Self::InvalidHour(_) => "datetime.invalid_hour",
+            Self::InvalidMinute(_) => "datetime.invalid_minute",
+            Self::InvalidSecond(_) => "datetime.invalid_second",
+            Self::InvalidNanosecond(_) => "datetime.invalid_nanosecond",
+            Self::InvalidDate { .. } => "datetime.invalid_date",
+            Self::InvalidTime { .. } => "datetime.invalid_time",
+            Self::ParseError(_) => "datetime.parse_error",
+        }
+    }
+}
+
 impl Date {
     /// Creates a new [Date] instance.
     ///
@@ -105,6 +167,13 @@ impl Date {
         }
     }
 
+    /// Returns the year.
+    pub const fn year(&self) -> i32 {self.year}
+    /// Returns the month (1-12).
+    pub const fn month(&self) -> u8 {self.month}
+    /// Returns the day of the month (1-31).
+    pub const fn day(&self) -> u8 {self.day}
+
     /// Calculates the number of days in a given month of a specific year.
     ///
     /// # Arguments
@@ -144,6 +213,203 @@ impl Date {
     pub const fn is_leap_year(year: i32) -> bool {
         year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
     }
+
+    /// Creates a [Date] from a year and the day of that year.
+    ///
+    /// # Arguments
+    /// * `year` - The year
+    /// * `day` - The day of the year (1-365, or 1-366 in leap years)
+    ///
+    /// # Returns
+    /// A `Result` containing either the valid [Date] or a [DateTimeError::InvalidOrdinal].
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::{Date, DateTimeError};
+    ///
+    /// assert_eq!(Date::from_ordinal(2024, 60).unwrap(), Date::new(2024, 2, 29).unwrap());
+    /// assert_eq!(Date::from_ordinal(2023, 366), Err(DateTimeError::InvalidOrdinal(366)));
+    /// ```
+    pub fn from_ordinal(year: i32, day: u16) -> Result<Self, DateTimeError> {
+        let days_in_year = 365 + Self::is_leap_year(year) as u16;
+        if day < 1 || day > days_in_year {return Err(DateTimeError::InvalidOrdinal(day));}
+
+        let (mut month, mut day) = (1, day);
+        while day > Self::days_in_month(year, month) as u16 {
+            day -= Self::days_in_month(year, month) as u16;
+            month += 1;
+        }
+        Self::new(year, month, day as u8)
+    }
+
+    /// Returns the day of the year, from 1 (January 1st) to 366.
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::Date;
+    ///
+    /// assert_eq!(Date::new(2023, 12, 31).unwrap().ordinal(), 365);
+    /// ```
+    pub fn ordinal(&self) -> u16 {
+        (1..self.month).map(|m| Self::days_in_month(self.year, m) as u16).sum::<u16>() + self.day as u16
+    }
+
+    /// Returns the day of the week.
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::{Date, Weekday};
+    ///
+    /// assert_eq!(Date::new(2024, 2, 29).unwrap().weekday(), Weekday::Thursday);
+    /// ```
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a Thursday
+        Weekday::ALL[(self.days_since_epoch() + 3).rem_euclid(7) as usize]
+    }
+
+    /// Returns the ISO 8601 week date as `(year, week)`.
+    ///
+    /// Weeks start on Monday and week 1 is the one containing the first Thursday of the year,
+    /// so the first days of January can belong to the last week of the previous year
+    /// (and the last days of December to week 1 of the next one).
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::Date;
+    ///
+    /// assert_eq!(Date::new(2024, 5, 1).unwrap().iso_week(), (2024, 18));
+    /// assert_eq!(Date::new(2021, 1, 3).unwrap().iso_week(), (2020, 53));
+    /// assert_eq!(Date::new(2024, 12, 30).unwrap().iso_week(), (2025, 1));
+    /// ```
+    pub fn iso_week(&self) -> (i32, u8) {
+        let week = (self.ordinal() as i32 - self.weekday().number_from_monday() as i32 + 10) / 7;
+        match week {
+            0 => (self.year - 1, Self::iso_weeks_in_year(self.year - 1)),
+            w if w > Self::iso_weeks_in_year(self.year) as i32 => (self.year + 1, 1),
+            w => (self.year, w as u8),
+        }
+    }
+
+    // Years starting on a Thursday (or a Wednesday, for leap years) have 53 ISO weeks
+    fn iso_weeks_in_year(year: i32) -> u8 {
+        match (Date { year, month: 1, day: 1 }.weekday(), Self::is_leap_year(year)) {
+            (Weekday::Thursday, _) | (Weekday::Wednesday, true) => 53,
+            _ => 52,
+        }
+    }
+
+    /// Returns the date a number of days later (or earlier, if negative).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the year of the resulting date doesn't fit an `i32` (see [Date::checked_add_days]).
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::Date;
+    ///
+    /// let date = Date::new(2024, 2, 28).unwrap();
+    /// assert_eq!(date.add_days(2), Date::new(2024, 3, 1).unwrap());
+    /// assert_eq!(date.add_days(-59), Date::new(2023, 12, 31).unwrap());
+    /// ```
+    pub fn add_days(&self, days: i64) -> Self {
+        self.checked_add_days(days).expect("date out of range after adding days")
+    }
+
+    /// Returns the date a number of days later (or earlier, if negative), or `None` if its
+    /// year doesn't fit an `i32`.
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::Date;
+    ///
+    /// let date = Date::new(2024, 2, 28).unwrap();
+    /// assert_eq!(date.checked_add_days(2), Some(Date::new(2024, 3, 1).unwrap()));
+    /// assert_eq!(date.checked_add_days(i64::MAX), None);
+    /// ```
+    pub fn checked_add_days(&self, days: i64) -> Option<Self> {
+        const FIRST: Date = Date { year: i32::MIN, month: 1, day: 1 };
+        const LAST: Date = Date { year: i32::MAX, month: 12, day: 31 };
+        let days = self.days_since_epoch().checked_add(days)?;
+        (FIRST.days_since_epoch()..=LAST.days_since_epoch()).contains(&days)
+            .then(|| Self::from_days_since_epoch(days))
+    }
+
+    /// Returns the date a number of months later (or earlier, if negative).
+    ///
+    /// # Arguments
+    /// * `months` - The number of months to add
+    /// * `policy` - What to do if the day doesn't exist in the resulting month
+    ///
+    /// # Returns
+    /// A `Result` containing the new [Date], or a [DateTimeError::InvalidDate] with [OverflowPolicy::Error].
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::{Date, OverflowPolicy};
+    ///
+    /// let date = Date::new(2023, 1, 31).unwrap();
+    /// assert_eq!(date.add_months(1, OverflowPolicy::Clamp).unwrap(), Date::new(2023, 2, 28).unwrap());
+    /// assert_eq!(date.add_months(1, OverflowPolicy::Wrap).unwrap(), Date::new(2023, 3, 3).unwrap());
+    /// assert!(date.add_months(1, OverflowPolicy::Error).is_err());
+    /// ```
+    pub fn add_months(&self, months: i32, policy: OverflowPolicy) -> Result<Self, DateTimeError> {
+        let total = self.year as i64 * 12 + (self.month as i64 - 1) + months as i64;
+        let (year, month) = (total.div_euclid(12) as i32, total.rem_euclid(12) as u8 + 1);
+        let last_day = Self::days_in_month(year, month);
+        match (self.day <= last_day, policy) {
+            (true, _) => Self::new(year, month, self.day),
+            (false, OverflowPolicy::Clamp) => Self::new(year, month, last_day),
+            (false, OverflowPolicy::Wrap) => Ok(Self { year, month, day: last_day }.add_days((self.day - last_day) as i64)),
+            (false, OverflowPolicy::Error) => Err(DateTimeError::InvalidDate { year, month, day: self.day }),
+        }
+    }
+
+    /// Returns the date a number of years later (or earlier, if negative).
+    ///
+    /// Only February 29th can overflow, which `policy` resolves like in [Date::add_months].
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::{Date, OverflowPolicy};
+    ///
+    /// let leap_day = Date::new(2024, 2, 29).unwrap();
+    /// assert_eq!(leap_day.add_years(1, OverflowPolicy::Clamp).unwrap(), Date::new(2025, 2, 28).unwrap());
+    /// assert_eq!(leap_day.add_years(4, OverflowPolicy::Error).unwrap(), Date::new(2028, 2, 29).unwrap());
+    /// ```
+    pub fn add_years(&self, years: i32, policy: OverflowPolicy) -> Result<Self, DateTimeError> {
+        self.add_months(years.saturating_mul(12), policy)
+    }
+
+    // Inverse of `days_since_epoch`; the year wraps around if it doesn't fit an `i32`
+    fn from_days_since_epoch(days: i64) -> Self {
+        let (year, month, day) = Self::civil_from_days(days);
+        Self { year: year as i32, month, day }
+    }
+
+    // The year, month and day of a number of days since 1970-01-01
+    fn civil_from_days(days: i64) -> (i64, u8, u8) {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;  // 0 = March
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+        let month = if shifted_month < 10 {shifted_month + 3} else {shifted_month - 9} as u8;
+        (year_of_era + era * 400 + (month <= 2) as i64, month, day)
+    }
+
+    /// Returns the number of days since 1970-01-01 (negative for earlier dates).
+    fn days_since_epoch(&self) -> i64 {
+        // days from civil: years start in March, so the leap day is the last day of the year
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((self.month as i64 + 9) % 12) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
 }
 
 impl fmt::Display for Date {
@@ -172,24 +438,108 @@ impl Time {
     /// ```
     pub const fn new(hour: u8, minute: u8, second: u8) -> Result<Self, DateTimeError> {
         match (hour, minute, second) {
-            (h, m, s) if h < 24 && m < 60 && s < 60 => Ok(Self { hour: h, minute: m, second: s }),
+            (h, m, s) if h < 24 && m < 60 && s < 60 => Ok(Self { hour: h, minute: m, second: s, nanosecond: 0 }),
             (h, _, _) if h >= 24 => Err(DateTimeError::InvalidHour(h)),
             (_, m, _) if m >= 60 => Err(DateTimeError::InvalidMinute(m)),
             (_, _, s) if s >= 60 => Err(DateTimeError::InvalidSecond(s)),
             _ => unreachable!() // * This case should never happen due to the nature of u8
         }
     }
+
+    /// Returns the same time with a sub-second part.
+    ///
+    /// # Arguments
+    /// * `nanosecond` - The nanoseconds past the second (0-999999999)
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::Time;
+    ///
+    /// let time = Time::new(12, 34, 56).unwrap().with_nanosecond(250_000_000).unwrap();
+    /// assert_eq!(time.millisecond(), 250);
+    /// assert_eq!(time.to_string(), "12:34:56.250");
+    /// ```
+    pub const fn with_nanosecond(self, nanosecond: u32) -> Result<Self, DateTimeError> {
+        match nanosecond {
+            0..=999_999_999 => Ok(Self { nanosecond, ..self }),
+            _ => Err(DateTimeError::InvalidNanosecond(nanosecond)),
+        }
+    }
+
+    /// Returns the hour (0-23).
+    pub const fn hour(&self) -> u8 {self.hour}
+    /// Returns the minute (0-59).
+    pub const fn minute(&self) -> u8 {self.minute}
+    /// Returns the second (0-59).
+    pub const fn second(&self) -> u8 {self.second}
+    /// Returns the milliseconds past the second (0-999).
+    pub const fn millisecond(&self) -> u16 {(self.nanosecond / 1_000_000) as u16}
+    /// Returns the nanoseconds past the second (0-999999999).
+    pub const fn nanosecond(&self) -> u32 {self.nanosecond}
 }
 
 impl fmt::Display for Time {
+    /// Formats as `HH:MM:SS`, followed by the milli, micro or nanoseconds when there are any
+    /// (`12:34:56.250`, `12:34:56.000001`).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        match self.nanosecond {
+            0 => Ok(()),
+            ns if ns % 1_000_000 == 0 => write!(f, ".{:03}", ns / 1_000_000),
+            ns if ns % 1_000 == 0 => write!(f, ".{:06}", ns / 1_000),
+            ns => write!(f, ".{:09}", ns),
+        }
+    }
+}
+
+/// A source of the current time: returns the time elapsed since the UNIX epoch.
+pub type Clock = fn() -> Duration;
+
+#[cfg(feature = "std")]
+static CLOCK: RwLock<Option<Clock>> = RwLock::new(None);
+
+/// Replaces the system clock as the source of [DateTime::now] and of the `dlog` timestamps.
+///
+/// On `wasm32-unknown-unknown` there is no system clock (`SystemTime::now` panics): without
+/// a clock set here, the time is stuck at the UNIX epoch. In a browser, pass a function
+/// returning `Date.now()` (through your JS bindings). It can also freeze the time in tests.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::datetime::{self, DateTime};
+///
+/// datetime::set_clock(|| Duration::from_secs(1_700_000_000));
+/// assert_eq!(DateTime::now().to_string(), "2023-11-14 22:13:20");
+/// datetime::reset_clock();
+/// ```
+#[cfg(feature = "std")]
+pub fn set_clock(clock: Clock) {*CLOCK.write().unwrap_or_else(PoisonError::into_inner) = Some(clock);}
+
+/// Goes back to the system clock (see [set_clock]).
+#[cfg(feature = "std")]
+pub fn reset_clock() {*CLOCK.write().unwrap_or_else(PoisonError::into_inner) = None;}
+
+// The time since the UNIX epoch, from the clock set with `set_clock` or the system clock
+#[cfg(feature = "std")]
+pub(crate) fn clock_now() -> Duration {
+    match *CLOCK.read().unwrap_or_else(PoisonError::into_inner) {
+        Some(clock) => clock(),
+        None => system_now(),
     }
 }
 
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+fn system_now() -> Duration {SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()}
+
+#[cfg(all(feature = "std", target_arch = "wasm32", target_os = "unknown"))]
+fn system_now() -> Duration {Duration::ZERO}  // no clock: `SystemTime::now` would panic
+
 impl DateTime {
     /// Returns a [DateTime] instance representing the current date and time.
     ///
+    /// The time comes from the system clock, or from the [Clock] set with [set_clock].
+    ///
     /// # Examples
     /// ```
     /// use dev_utils::datetime::DateTime;
@@ -197,67 +547,188 @@ impl DateTime {
     /// let now = DateTime::now();
     /// println!("Current date and time: {}", now);
     /// ```
-    pub fn now() -> Self {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        Self::from_timestamp(now.as_secs() as i64).unwrap()
-    }
+    #[cfg(feature = "std")]
+    pub fn now() -> Self {Self::from_unix_nanos(clock_now().as_nanos() as i128)}
 
     /// Creates a [DateTime] instance from a Unix timestamp.
     ///
     /// # Arguments
-    /// * `timestamp` - The Unix timestamp (seconds since 1970-01-01 00:00:00 UTC)
+    /// * `timestamp` - The Unix timestamp (seconds since 1970-01-01 00:00:00 UTC, negative for earlier dates)
     ///
     /// # Returns
-    /// A `Result` containing either the valid `DateTime` or a `DateTimeError`.
+    /// A `Result` containing either the valid `DateTime` or a [DateTimeError::InvalidYear] if
+    /// the year doesn't fit an `i32` (the error holds the nearest `i32` bound).
     ///
     /// # Examples
     /// ```
     /// use dev_utils::datetime::DateTime;
     /// 
     /// let dt = DateTime::from_timestamp(1682899200).unwrap();
-    /// assert_eq!(dt.to_string(), "2023-05-02 00:00:00");
+    /// assert_eq!(dt.to_string(), "2023-05-01 00:00:00");
+    ///
+    /// let dt = DateTime::from_timestamp(-1).unwrap();
+    /// assert_eq!(dt.to_string(), "1969-12-31 23:59:59");
+    ///
+    /// assert!(DateTime::from_timestamp(i64::MAX).is_err());
     /// ```
     pub fn from_timestamp(timestamp: i64) -> Result<Self, DateTimeError> {
-        let (days, seconds) = (timestamp / 86400, timestamp % 86400);
-        let (year, month, day) = Self::calculate_ymd(days);
-        let (hour, minute, second) = (seconds / 3600, (seconds % 3600) / 60, seconds % 60);
+        let (year, _, _) = Date::civil_from_days(timestamp.div_euclid(86_400));
+        i32::try_from(year).map_err(|_| DateTimeError::InvalidYear(if year < 0 {i32::MIN} else {i32::MAX}))?;
+        Ok(Self::from_unix_nanos(timestamp as i128 * 1_000_000_000))
+    }
 
-        Ok(Self {
-            date: Date::new(year, month, day + 1)?,
-            time: Time::new(hour as u8, minute as u8, second as u8)?,
-        })
+    /// Returns the Unix timestamp: the seconds since 1970-01-01 00:00:00 UTC (negative for
+    /// earlier dates). Sub-second precision is rounded down.
+    ///
+    /// This is the inverse of [DateTime::from_timestamp].
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::DateTime;
+    ///
+    /// let dt: DateTime = "2023-05-01 00:00:00".parse().unwrap();
+    /// assert_eq!(dt.timestamp(), 1682899200);
+    ///
+    /// let dt: DateTime = "1969-07-20 20:17:40".parse().unwrap();
+    /// assert_eq!(dt.timestamp(), -14182940);
+    /// ```
+    pub fn timestamp(&self) -> i64 {
+        self.date.days_since_epoch() * 86400 + self.time.hour as i64 * 3600 + self.time.minute as i64 * 60 + self.time.second as i64
     }
 
-    /// Calculates the year, month, and day from the number of days since 1970-01-01.
+    /// Creates a [DateTime] instance from a Unix timestamp in milliseconds (negative before 1970).
     ///
-    /// # Arguments
-    /// * `days` - The number of days since 1970-01-01
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::DateTime;
     ///
-    /// # Returns
-    /// A tuple containing the calculated (year, month, day).
-    fn calculate_ymd(mut days: i64) -> (i32, u8, u8) {
-        let mut year = 1970;
-        let mut month = 1;
-
-        while days >= 365 + Date::is_leap_year(year) as i64 {
-            days -= 365 + Date::is_leap_year(year) as i64;
-            year += 1;
+    /// let dt = DateTime::from_timestamp_millis(1682899200123);
+    /// assert_eq!(dt.to_string(), "2023-05-01 00:00:00.123");
+    /// assert_eq!(dt.timestamp_millis(), 1682899200123);
+    /// ```
+    pub fn from_timestamp_millis(millis: i64) -> Self {Self::from_unix_nanos(millis as i128 * 1_000_000)}
+
+    /// Creates a [DateTime] instance from a Unix timestamp in nanoseconds (negative before 1970).
+    ///
+    /// # Examples
+    /// ```
+    /// use dev_utils::datetime::DateTime;
+    ///
+    /// let dt = DateTime::from_timestamp_nanos(1682899200000000001);
+    /// assert_eq!(dt.to_string(), "2023-05-01 00:00:00.000000001");
+    /// ```
+    pub fn from_timestamp_nanos(nanos: i64) -> Self {Self::from_unix_nanos(nanos as i128)}
+
+    /// Returns the milliseconds since 1970-01-01 00:00:00 UTC (negative for earlier dates).
+    pub fn timestamp_millis(&self) -> i64 {self.unix_nanos().div_euclid(1_000_000) as i64}
+
+    /// Formats a duration with its largest unit, rounded down ("45 seconds", "3 minutes", "2 days").
+    ///
+    /// Months are counted as 30 days and years as 365 days.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use dev_utils::datetime::DateTime;
+    ///
+    /// assert_eq!(DateTime::humanize_duration(Duration::from_secs(1)), "1 second");
+    /// assert_eq!(DateTime::humanize_duration(Duration::from_secs(150)), "2 minutes");
+    /// assert_eq!(DateTime::humanize_duration(Duration::from_secs(90 * 86400)), "3 months");
+    /// ```
+    pub fn humanize_duration(duration: Duration) -> String {
+        const UNITS: [(u64, &str); 6] = [
+            (365 * 86400, "year"), (30 * 86400, "month"), (86400, "day"), (3600, "hour"), (60, "minute"), (1, "second"),
+        ];
+        let seconds = duration.as_secs();
+        let (size, unit) = UNITS.iter().find(|(size, _)| seconds >= *size).unwrap_or(&UNITS[5]);
+        match seconds / size {
+            1 => format!("1 {}", unit),
+            n => format!("{} {}s", n, unit),
         }
+    }
 
-        while days >= Date::days_in_month(year, month) as i64 {
-            days -= Date::days_in_month(year, month) as i64;
-            month += 1;
+    /// Returns the date and time a [Duration] later.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use dev_utils::datetime::DateTime;
+    ///
+    /// let dt: DateTime = "2023-12-31 23:30:00".parse().unwrap();
+    /// assert_eq!(dt.add_duration(Duration::from_secs(3600)).to_string(), "2024-01-01 00:30:00");
+    /// ```
+    pub fn add_duration(&self, duration: Duration) -> Self {
+        Self::from_unix_nanos(self.unix_nanos().saturating_add(duration.as_nanos() as i128))
+    }
+
+    /// Returns the date and time a [Duration] earlier.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use dev_utils::datetime::DateTime;
+    ///
+    /// let dt: DateTime = "2024-01-01 00:00:00.250".parse().unwrap();
+    /// assert_eq!(dt.sub_duration(Duration::from_millis(500)).to_string(), "2023-12-31 23:59:59.750");
+    /// ```
+    pub fn sub_duration(&self, duration: Duration) -> Self {
+        Self::from_unix_nanos(self.unix_nanos().saturating_sub(duration.as_nanos() as i128))
+    }
+
+    // Inverse of `unix_nanos`, for any (also negative) number of nanoseconds
+    fn from_unix_nanos(nanos: i128) -> Self {
+        const NANOS_PER_DAY: i128 = 86_400_000_000_000;
+        let (days, nanos) = (nanos.div_euclid(NANOS_PER_DAY), nanos.rem_euclid(NANOS_PER_DAY));
+        let (seconds, nanosecond) = ((nanos / 1_000_000_000) as u32, (nanos % 1_000_000_000) as u32);
+        DateTime {
+            date: Date::from_days_since_epoch(days as i64),
+            time: Time { hour: (seconds / 3600) as u8, minute: (seconds % 3600 / 60) as u8, second: (seconds % 60) as u8, nanosecond },
         }
+    }
+
+    // Nanoseconds since 1970-01-01 00:00:00
+    fn unix_nanos(&self) -> i128 {
+        self.timestamp() as i128 * 1_000_000_000 + self.time.nanosecond as i128
+    }
+}
 
-        (year, month, days as u8 + 1)
+/// Describes a [DateTime] relative to now: "3 minutes ago", "in 2 days" or "just now".
+///
+/// # Examples
+/// ```
+/// use dev_utils::datetime::{relative, DateTime};
+///
+/// let file_modified = DateTime::from_timestamp(1682899200).unwrap();
+/// println!("modified {}", relative(file_modified));  // modified 2 years ago
+/// ```
+#[cfg(feature = "std")]
+pub fn relative(dt: DateTime) -> String {relative_to(dt, DateTime::now())}
+
+/// Describes a [DateTime] relative to another one (see [relative]).
+///
+/// # Examples
+/// ```
+/// use dev_utils::datetime::{relative_to, DateTime};
+///
+/// let now: DateTime = "2024-03-10 12:00:00".parse().unwrap();
+/// assert_eq!(relative_to("2024-03-10 11:57:00".parse().unwrap(), now), "3 minutes ago");
+/// assert_eq!(relative_to("2024-03-12 12:00:00".parse().unwrap(), now), "in 2 days");
+/// ```
+pub fn relative_to(dt: DateTime, now: DateTime) -> String {
+    let seconds = dt.timestamp() - now.timestamp();
+    let duration = DateTime::humanize_duration(Duration::from_secs(seconds.unsigned_abs()));
+    match seconds {
+        0 => "just now".to_string(),
+        ..0 => format!("{} ago", duration),
+        _ => format!("in {}", duration),
     }
 }
 
 impl fmt::Display for DateTime {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",  // 2023-05-01 12:34:56
+        write!(f, "{:04}-{:02}-{:02} {}",  // 2023-05-01 12:34:56[.789]
             self.date.year, self.date.month, self.date.day,  // date
-            self.time.hour, self.time.minute, self.time.second  // time
+            self.time  // time
         )
     }
 }
@@ -267,7 +738,8 @@ impl FromStr for DateTime {
 
     /// Parses a string into a [DateTime] instance.
     ///
-    /// The expected format is "YYYY-MM-DD HH:MM:SS".
+    /// The expected format is "YYYY-MM-DD HH:MM:SS", optionally followed by a fraction
+    /// of a second with up to 9 digits ("YYYY-MM-DD HH:MM:SS.mmm").
     ///
     /// # Arguments
     /// * `s` - The string to parse
@@ -282,6 +754,9 @@ impl FromStr for DateTime {
     /// 
     /// let dt = DateTime::from_str("2023-05-01 12:34:56").unwrap();
     /// assert_eq!(dt.to_string(), "2023-05-01 12:34:56");
+    ///
+    /// let dt = DateTime::from_str("2023-05-01 12:34:56.78").unwrap();
+    /// assert_eq!(dt.time.millisecond(), 780);
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<&str> = s.split_whitespace().collect();
@@ -305,11 +780,17 @@ impl FromStr for DateTime {
         let day:    u8 = parse_part(date_parts[2], "day")?;
         let hour:   u8 = parse_part(time_parts[0], "hour")?;
         let minute: u8 = parse_part(time_parts[1], "minute")?;
-        let second: u8 = parse_part(time_parts[2], "second")?;
+        let (second, fraction) = time_parts[2].split_once('.').unwrap_or((time_parts[2], ""));
+        let second: u8 = parse_part(second, "second")?;
+        let nanosecond = match fraction.len() {
+            0 if !time_parts[2].ends_with('.') => 0,
+            1..=9 if fraction.bytes().all(|b| b.is_ascii_digit()) => parse_part::<u32>(fraction, "fraction")? * 10u32.pow(9 - fraction.len() as u32),
+            _ => return Err(DateTimeError::ParseError("Invalid fraction".to_string())),
+        };
 
         Ok(Self {
             date: Date::new(year, month, day)?, 
-            time: Time::new(hour, minute, second)? }
+            time: Time::new(hour, minute, second)?.with_nanosecond(nanosecond)? }
         )
     }
 }
@@ -335,6 +816,10 @@ mod tests {
     fn test_datetime_from_timestamp() {
         let dt = DateTime::from_timestamp(1682899200).unwrap();
         assert_eq!(dt.to_string(), "2023-05-01 00:00:00");
+
+        assert!(DateTime::from_timestamp(3_000_000_000_000_000).unwrap().date.year() > 95_000_000);
+        assert_eq!(DateTime::from_timestamp(i64::MAX), Err(DateTimeError::InvalidYear(i32::MAX)));
+        assert_eq!(DateTime::from_timestamp(i64::MIN), Err(DateTimeError::InvalidYear(i32::MIN)));
     }
 
     #[test]
@@ -343,6 +828,148 @@ mod tests {
         assert_eq!(dt.to_string(), "2023-05-01 12:34:56");
     }
 
+    #[test]
+    fn test_calendar() {
+        let date = |y, m, d| Date::new(y, m, d).unwrap();
+        assert_eq!(date(1970, 1, 1).weekday(), Weekday::Thursday);
+        assert_eq!(date(1969, 12, 31).weekday(), Weekday::Wednesday);
+        assert_eq!(date(2000, 1, 1).weekday(), Weekday::Saturday);
+        assert_eq!(Weekday::Sunday.number_from_monday(), 7);
+        assert_eq!(Weekday::Friday.to_string(), "Friday");
+        assert_eq!("Sunday".parse(), Ok(Weekday::Sunday));
+        assert!(Weekday::iter().eq(Weekday::ALL));
+
+        assert_eq!(date(2024, 12, 31).ordinal(), 366);
+        for (y, day) in [(2023, 1), (2023, 365), (2024, 60), (2024, 366), (1900, 59)] {
+            assert_eq!(Date::from_ordinal(y, day).unwrap().ordinal(), day);
+        }
+        assert_eq!(Date::from_ordinal(2024, 0), Err(DateTimeError::InvalidOrdinal(0)));
+        assert_eq!(Date::from_ordinal(2024, 400), Err(DateTimeError::InvalidOrdinal(400)));
+        assert_eq!(DateTimeError::InvalidOrdinal(400).to_string(), "Invalid day of the year: 400");
+        #[cfg(feature = "std")]
+        assert_eq!(crate::error::ErrorCode::code(&DateTimeError::InvalidOrdinal(400)), "datetime.invalid_ordinal");
+
+        assert_eq!(date(2026, 1, 1).iso_week(), (2026, 1));
+        assert_eq!(date(2027, 1, 1).iso_week(), (2026, 53));
+        assert_eq!(date(2008, 12, 29).iso_week(), (2009, 1));
+        assert_eq!(date(2010, 1, 3).iso_week(), (2009, 53));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let date = |y, m, d| Date::new(y, m, d).unwrap();
+        for days in [-800_000, -1, 0, 59, 365, 11_016, 2_932_896] {
+            assert_eq!(Date::from_days_since_epoch(days).days_since_epoch(), days);
+        }
+        assert_eq!(date(2000, 3, 1).add_days(-1), date(2000, 2, 29));
+        assert_eq!(date(1900, 3, 1).add_days(-1), date(1900, 2, 28));
+
+        // the edges of the range
+        let (first, last) = (date(i32::MIN, 1, 1), date(i32::MAX, 12, 31));
+        assert_eq!(last.checked_add_days(0), Some(last));
+        assert_eq!(last.checked_add_days(1), None);
+        assert_eq!(first.checked_add_days(-1), None);
+        assert_eq!(first.checked_add_days(last.days_since_epoch() - first.days_since_epoch()), Some(last));
+        assert_eq!(date(2024, 1, 1).checked_add_days(i64::MAX), None);
+        assert_eq!(date(2024, 1, 1).checked_add_days(i64::MIN), None);
+        assert!(std::panic::catch_unwind(|| last.add_days(1)).is_err());
+
+        // Jan 31 + 1 month
+        assert_eq!(date(2024, 1, 31).add_months(1, OverflowPolicy::Clamp), Ok(date(2024, 2, 29)));
+        assert_eq!(date(2024, 1, 31).add_months(1, OverflowPolicy::Wrap), Ok(date(2024, 3, 2)));
+        assert_eq!(date(2024, 3, 31).add_months(-1, OverflowPolicy::Clamp), Ok(date(2024, 2, 29)));
+        assert_eq!(date(2024, 11, 15).add_months(14, OverflowPolicy::Error), Ok(date(2026, 1, 15)));
+        assert_eq!(date(2024, 1, 15).add_months(-13, OverflowPolicy::Error), Ok(date(2022, 12, 15)));
+
+        // Feb 29 + 1 year
+        assert_eq!(date(2024, 2, 29).add_years(1, OverflowPolicy::Clamp), Ok(date(2025, 2, 28)));
+        assert_eq!(date(2024, 2, 29).add_years(1, OverflowPolicy::Wrap), Ok(date(2025, 3, 1)));
+        assert_eq!(date(2024, 2, 29).add_years(-1, OverflowPolicy::Error), Err(DateTimeError::InvalidDate { year: 2023, month: 2, day: 29 }));
+        assert_eq!(date(2024, 2, 29).add_years(-4, OverflowPolicy::Error), Ok(date(2020, 2, 29)));
+
+        let dt: DateTime = "2024-02-28 23:59:59".parse().unwrap();
+        assert_eq!(dt.add_duration(Duration::from_secs(1)).to_string(), "2024-02-29 00:00:00");
+        assert_eq!(dt.add_duration(Duration::from_secs(86400 * 366)).to_string(), "2025-02-28 23:59:59");
+    }
+
+    #[test]
+    fn test_subsecond() {
+        let dt = DateTime::from_timestamp_millis(-1);
+        assert_eq!(dt.to_string(), "1969-12-31 23:59:59.999");
+        assert_eq!(dt.timestamp_millis(), -1);
+        for millis in [-86_400_001, 0, 1, 999, 1_700_000_000_123] {
+            assert_eq!(DateTime::from_timestamp_millis(millis).timestamp_millis(), millis);
+        }
+        assert_eq!(DateTime::from_timestamp_nanos(1_500).to_string(), "1970-01-01 00:00:00.000001500");
+        assert_eq!(DateTime::from_timestamp_nanos(-1).time.nanosecond(), 999_999_999);
+
+        for s in ["2024-02-29 23:59:59.5", "2024-02-29 23:59:59.000250", "2024-02-29 23:59:59.123456789"] {
+            let dt: DateTime = s.parse().unwrap();
+            assert_eq!(dt.to_string().trim_end_matches('0'), s.trim_end_matches('0'));
+        }
+        assert_eq!("2024-02-29 23:59:59.5".parse::<DateTime>().unwrap().time.millisecond(), 500);
+        for s in ["2024-02-29 23:59:59.", "2024-02-29 23:59:59.1234567890", "2024-02-29 23:59:59.-1", "2024-02-29 23:59:59.+1"] {
+            assert!(s.parse::<DateTime>().is_err(), "{}", s);
+        }
+        assert_eq!(Time::new(0, 0, 0).unwrap().with_nanosecond(1_000_000_000), Err(DateTimeError::InvalidNanosecond(1_000_000_000)));
+
+        let dt: DateTime = "2024-12-31 23:59:59.750".parse().unwrap();
+        assert_eq!(dt.add_duration(Duration::from_millis(250)).to_string(), "2025-01-01 00:00:00");
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        // ~ 10000 timestamps spread (with a changing step) from year -5000 to year 9000
+        let (start, end) = (-219_900_000_000i64, 221_900_000_000i64);
+        let mut timestamp = start;
+        let mut step = 1i64;
+        while timestamp < end {
+            let dt = DateTime::from_timestamp(timestamp).unwrap();
+            assert_eq!(dt.timestamp(), timestamp, "{}", dt);
+            if dt.date.year >= 0 {assert_eq!(dt.to_string().parse::<DateTime>().unwrap(), dt);}
+            step = (step * 6_364_136_223 + 1_442_695) % 88_006_977;
+            timestamp += step + 1;
+        }
+        // around the epoch and day boundaries
+        for timestamp in (-200_000..200_000).step_by(997).chain([-86_401, -86_400, -86_399, -1, 0, 1, 86_399, 86_400]) {
+            assert_eq!(DateTime::from_timestamp(timestamp).unwrap().timestamp(), timestamp);
+        }
+        assert_eq!(DateTime::from_timestamp(-86_400).unwrap().to_string(), "1969-12-31 00:00:00");
+        assert_eq!(DateTime::from_timestamp(-2_208_988_800).unwrap().to_string(), "1900-01-01 00:00:00");
+        assert_eq!(DateTime::from_timestamp(951_782_400).unwrap().to_string(), "2000-02-29 00:00:00");
+    }
+
+    #[test]
+    fn test_relative() {
+        let now: DateTime = "2024-02-28 23:59:30".parse().unwrap();
+        let at = |s: &str| relative_to(s.parse().unwrap(), now);
+        assert_eq!(at("2024-02-28 23:59:30"), "just now");
+        assert_eq!(at("2024-02-28 23:59:29"), "1 second ago");
+        assert_eq!(at("2024-02-29 00:01:00"), "in 1 minute");
+        assert_eq!(at("2024-03-01 00:00:00"), "in 1 day");
+        assert_eq!(at("2023-02-28 23:59:30"), "1 year ago");
+        assert_eq!(at("1969-12-31 00:00:00"), "54 years ago");
+        assert_eq!(DateTime::humanize_duration(Duration::ZERO), "0 seconds");
+        assert_eq!(DateTime::humanize_duration(Duration::from_secs(7 * 3600 + 59)), "7 hours");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_clock() {
+        // the system time (less than a microsecond off, so that the tests running at the
+        // same time are not affected), marked with 777 nanoseconds
+        fn marked_clock() -> Duration {
+            let now = system_now();
+            now - Duration::from_nanos(now.subsec_nanos() as u64 % 1000) + Duration::from_nanos(777)
+        }
+
+        set_clock(marked_clock);
+        let now = DateTime::now();
+        reset_clock();
+        assert_eq!(now.time.nanosecond() % 1000, 777);
+        assert!(now.date.year() >= 2024);
+    }
+
     #[test]
     fn test_error_display() {
         let err = DateTimeError::InvalidYear(2023);
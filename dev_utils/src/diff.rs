@@ -0,0 +1,335 @@
+//! Line-based diffs of strings and files, rendered as unified diffs.
+//!
+//! The changes are computed with Myers' algorithm, so the diff has the fewest possible
+//! added and removed lines. A [Diff] renders as a unified diff (`diff -u`), either plain
+//! with [Diff::unified] or colored with `Display` (green additions, red removals).
+//!
+//! # Examples
+//! ```
+//! use dev_utils::diff::Diff;
+//!
+//! let diff = Diff::new("a\nb\nc\n", "a\nB\nc\n").labels("expected", "actual");
+//! assert_eq!(diff.unified(), "\
+//! --- expected
+//! +++ actual
+//! @@ -1,3 +1,3 @@
+//!  a
+//! -b
+//! +B
+//!  c
+//! ");
+//! println!("{}", diff);  // the same, colored
+//! ```
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::format::{Stylize, Style, CYAN, GREEN, RED};
+
+/// A line of a [Diff], including its line ending (if any).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<'a> {
+    /// A line present in both texts.
+    Equal(&'a str),
+    /// A line only present in the new text.
+    Insert(&'a str),
+    /// A line only present in the old text.
+    Delete(&'a str),
+}
+
+impl<'a> Change<'a> {
+    /// Returns the line, including its line ending (if any).
+    pub fn line(&self) -> &'a str {
+        match self {
+            Change::Equal(line) | Change::Insert(line) | Change::Delete(line) => line,
+        }
+    }
+
+    // The prefix of the line in a unified diff
+    fn prefix(&self) -> char {
+        match self {
+            Change::Equal(_) => ' ',
+            Change::Insert(_) => '+',
+            Change::Delete(_) => '-',
+        }
+    }
+}
+
+/// The line changes between two texts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diff<'a> {
+    changes: Vec<Change<'a>>,
+    labels: (String, String),
+    context: usize,
+}
+
+impl<'a> Diff<'a> {
+    /// Computes the changes from `old` to `new`, with 3 lines of context and the labels `a` and `b`.
+    pub fn new(old: &'a str, new: &'a str) -> Self {
+        let (old, new) = (old.split_inclusive('\n').collect::<Vec<_>>(), new.split_inclusive('\n').collect::<Vec<_>>());
+        Diff { changes: myers(&old, &new), labels: ("a".to_string(), "b".to_string()), context: 3 }
+    }
+
+    /// Sets the names of the old and new texts, shown in the `---` and `+++` header lines.
+    pub fn labels(mut self, old: &str, new: &str) -> Self {
+        self.labels = (old.to_string(), new.to_string());
+        self
+    }
+
+    /// Sets the number of unchanged lines shown around every change.
+    pub fn context(mut self, lines: usize) -> Self {
+        self.context = lines;
+        self
+    }
+
+    /// Returns all the lines of both texts, in order.
+    pub fn changes(&self) -> &[Change<'a>] {&self.changes}
+
+    /// Returns whether both texts are identical.
+    pub fn is_identical(&self) -> bool {self.changes.iter().all(|change| matches!(change, Change::Equal(_)))}
+
+    /// Returns the number of inserted and deleted lines.
+    pub fn stats(&self) -> (usize, usize) {
+        self.changes.iter().fold((0, 0), |(inserted, deleted), change| match change {
+            Change::Equal(_) => (inserted, deleted),
+            Change::Insert(_) => (inserted + 1, deleted),
+            Change::Delete(_) => (inserted, deleted + 1),
+        })
+    }
+
+    /// Renders the unified diff without colors (empty if the texts are identical).
+    pub fn unified(&self) -> String {self.render(false)}
+
+    fn render(&self, colored: bool) -> String {
+        if self.is_identical() {return String::new();}
+        let paint = |text: String, color| match colored {
+            true => text.color(color),
+            false => text,
+        };
+        let mut out = String::new();
+        for line in [format!("--- {}", self.labels.0), format!("+++ {}", self.labels.1)] {
+            out += &match colored {
+                true => line.style(Style::Bold),
+                false => line,
+            };
+            out.push('\n');
+        }
+        for hunk in self.hunks() {
+            out += &paint(hunk.header(), CYAN);
+            out.push('\n');
+            for change in &self.changes[hunk.range.clone()] {
+                let line = change.line();
+                let text = format!("{}{}", change.prefix(), line.strip_suffix('\n').unwrap_or(line));
+                out += &match change {
+                    Change::Equal(_) => text,
+                    Change::Insert(_) => paint(text, GREEN),
+                    Change::Delete(_) => paint(text, RED),
+                };
+                out.push('\n');
+                if !line.ends_with('\n') {out += "\\ No newline at end of file\n";}
+            }
+        }
+        out
+    }
+
+    // Groups the changes that are close enough to share their context lines
+    fn hunks(&self) -> Vec<Hunk> {
+        let changed: Vec<usize> = (0..self.changes.len()).filter(|&i| !matches!(self.changes[i], Change::Equal(_))).collect();
+        let mut groups: Vec<(usize, usize)> = Vec::new();
+        for &i in &changed {
+            match groups.last_mut() {
+                Some((_, last)) if i - *last <= 2 * self.context + 1 => *last = i,
+                _ => groups.push((i, i)),
+            }
+        }
+
+        // the line numbers where every change starts
+        let mut positions = Vec::with_capacity(self.changes.len() + 1);
+        let (mut old_line, mut new_line) = (0, 0);
+        for change in &self.changes {
+            positions.push((old_line, new_line));
+            match change {
+                Change::Equal(_) => {old_line += 1; new_line += 1;},
+                Change::Insert(_) => new_line += 1,
+                Change::Delete(_) => old_line += 1,
+            }
+        }
+        positions.push((old_line, new_line));
+
+        groups.into_iter().map(|(first, last)| {
+            let range = first.saturating_sub(self.context)..(last + self.context + 1).min(self.changes.len());
+            let (start, end) = (positions[range.start], positions[range.end]);
+            Hunk { range, old: (start.0, end.0 - start.0), new: (start.1, end.1 - start.1) }
+        }).collect()
+    }
+}
+
+impl fmt::Display for Diff<'_> {
+    /// Renders the unified diff with colors: green additions, red removals and cyan hunk headers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(true))
+    }
+}
+
+// A group of changes with their context, and the (0-based start, length) of its old and new lines
+struct Hunk {
+    range: std::ops::Range<usize>,
+    old: (usize, usize),
+    new: (usize, usize),
+}
+
+impl Hunk {
+    // `@@ -1,3 +1,4 @@`: 1-based starts (the previous line for empty ranges), lengths of 1 omitted
+    fn header(&self) -> String {
+        let range = |(start, len): (usize, usize)| match len {
+            0 => format!("{},0", start),
+            1 => format!("{}", start + 1),
+            _ => format!("{},{}", start + 1, len),
+        };
+        format!("@@ -{} +{} @@", range(self.old), range(self.new))
+    }
+}
+
+// Myers' O(ND) diff of two lists of lines
+fn myers<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Change<'a>> {
+    // the common prefix and suffix don't need the search
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (a, b) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let index = |k: isize| (k + max as isize + 1) as usize;
+    let mut v = vec![0isize; 2 * max + 3];
+    // the furthest reaching x of every diagonal k, before each round d
+    let mut trace = Vec::new();
+    'search: for d in 0..=max as isize {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = match k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                true => v[index(k + 1)],  // down: insertion
+                false => v[index(k - 1)] + 1,  // right: deletion
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {x += 1; y += 1;}
+            v[index(k)] = x;
+            if x >= n && y >= m {break 'search;}
+        }
+    }
+
+    let mut changes = Vec::with_capacity(old.len().max(new.len()));
+    changes.extend(old[old.len() - suffix..].iter().rev().map(|line| Change::Equal(line)));
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let (d, k) = (d as isize, x - y);
+        let prev_k = match k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+            true => k + 1,
+            false => k - 1,
+        };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            changes.push(Change::Equal(a[x as usize - 1]));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            match x == prev_x {
+                true => changes.push(Change::Insert(b[y as usize - 1])),
+                false => changes.push(Change::Delete(a[x as usize - 1])),
+            }
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    changes.extend(old[..prefix].iter().rev().map(|line| Change::Equal(line)));
+    changes.reverse();
+    changes
+}
+
+/// Renders the colored unified diff of two files, labeled with their paths
+/// (empty if the files are identical).
+///
+/// # Arguments
+///
+/// * `a` - The path of the old file
+/// * `b` - The path of the new file
+///
+/// # Returns
+///
+/// Returns a `Result` containing the diff (see [Diff]), or an `io::Error` if a file can't be read.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dev_utils::diff::diff_files;
+///
+/// let diff = diff_files("tests/golden/output.txt", "target/output.txt").unwrap();
+/// assert!(diff.is_empty(), "output changed:\n{}", diff);
+/// ```
+pub fn diff_files<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> io::Result<String> {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    let (old, new) = (fs::read_to_string(a)?, fs::read_to_string(b)?);
+    Ok(Diff::new(&old, &new).labels(&a.display().to_string(), &b.display().to_string()).to_string())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::TempDir;
+
+    #[test]
+    fn test_changes() {
+        let diff = Diff::new("a\nb\nc\na\nb\nb\na\n", "c\nb\na\nb\na\nc\n");
+        assert_eq!(diff.stats(), (2, 3));  // the shortest edit script has 5 steps
+        let old: String = diff.changes().iter().filter(|c| !matches!(c, Change::Insert(_))).map(Change::line).collect();
+        let new: String = diff.changes().iter().filter(|c| !matches!(c, Change::Delete(_))).map(Change::line).collect();
+        assert_eq!((old.as_str(), new.as_str()), ("a\nb\nc\na\nb\nb\na\n", "c\nb\na\nb\na\nc\n"));
+
+        assert!(Diff::new("same\n", "same\n").is_identical());
+        assert_eq!(Diff::new("same\n", "same\n").unified(), "");
+        assert_eq!(Diff::new("", "new\n").stats(), (1, 0));
+    }
+
+    #[test]
+    fn test_unified() {
+        let old: String = (1..=20).map(|n| format!("{}\n", n)).collect();
+        let new = old.replacen("2\n", "two\n", 1).replace("17\n", "").replace("20\n", "20");
+        assert_eq!(Diff::new(&old, &new).unified(), "\
+--- a
++++ b
+@@ -1,5 +1,5 @@
+ 1
+-2
++two
+ 3
+ 4
+ 5
+@@ -14,7 +14,6 @@
+ 14
+ 15
+ 16
+-17
+ 18
+ 19
+-20
++20
+\\ No newline at end of file
+");
+        assert_eq!(Diff::new("", "x\n").unified(), "--- a\n+++ b\n@@ -0,0 +1 @@\n+x\n");
+        assert_eq!(Diff::new("a\nb\n", "a\n").context(0).unified(), "--- a\n+++ b\n@@ -2 +1,0 @@\n-b\n");
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let dir = TempDir::new().unwrap();
+        let (a, b) = (dir.join("a.txt"), dir.join("b.txt"));
+        fs::write(&a, "one\ntwo\n").unwrap();
+        fs::write(&b, "one\n2\n").unwrap();
+        let diff = crate::format::strip_ansi_codes(&diff_files(&a, &b).unwrap());
+        assert!(diff.starts_with(&format!("--- {}\n+++ {}\n", a.display(), b.display())));
+        assert!(diff.ends_with("@@ -1,2 +1,2 @@\n one\n-two\n+2\n"));
+        assert_eq!(diff_files(&a, &a).unwrap(), "");
+        assert!(diff_files(&a, dir.join("missing.txt")).is_err());
+    }
+}
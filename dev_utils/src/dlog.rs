@@ -7,9 +7,23 @@
 //! # Features
 //! - Five log levels: Trace, Debug, Info, Warn, and Error
 //! - Colored output for easy visual distinction between log levels
+//! - Long messages wrapped to the terminal width, aligned under the first line
 //! - Customizable log formatting through the `DlogStyle` trait
 //! - Atomic log level setting for thread-safe operation
 //! - Macros for easy logging at different levels
+//! - Log-once and rate-limited macros keyed by callsite ([info_once!], [warn_every!], ...)
+//! - Optional collapsing of repeated messages ("message repeated 128 times", see [set_dedup])
+//! - Scoped key-value fields attached to every record ([with_fields!])
+//! - Pretty (default) and JSON output formats ([set_format])
+//! - Records counted per level ([record_counts]), e.g. for `http::dashboard`
+//! - Pluggable output ([Writer], [set_writer]): stdout by default, the browser console with the
+//!   `wasm` feature on `wasm32` ([ConsoleWriter]), timestamps from [crate::datetime::set_clock]
+//! - Optional asynchronous backend formatting and writing on a worker thread ([enable_async])
+//! - Idempotent, thread-safe initialization ([try_init]) that reports double initialization
+//! - Adapter for the `log` crate facade ([init_as_log_logger], requires the `log-compat` feature)
+//! - In-memory capture of the records for unit tests ([test_capture])
+//! - A panic hook printing panics as a styled error block and restoring the terminal ([install_panic_hook])
+//! - Honors the global color mode from [crate::format::term] (e.g. `NO_COLOR`, piped output)
 //!
 //! # Examples
 //! ```
@@ -26,11 +40,21 @@
 //! trace!("This is a trace message"); // This won't be printed due to log level
 //! ```
 use std::fmt;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
-use crate::format::{Color, Style, Stylize};
+use std::thread;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use crate::datetime::{self, DateTime};
+use crate::console::term::terminal_size;
+use crate::format::{term, strip_ansi_codes, wrap, Color, Style, Stylize};
 
-pub use crate::{__dlog_internal, error, warn, info, debug, trace};
+pub use crate::{__dlog_internal, error, warn, info, debug, trace, with_fields};
+pub use crate::{__dlog_once, error_once, warn_once, info_once, debug_once, trace_once};
+pub use crate::{__dlog_every, error_every, warn_every, info_every, debug_every, trace_every};
 
 macro_rules! define_levels {
     ($($level:ident => $value:expr, $color:expr),+ $(,)?) => {
@@ -137,13 +161,156 @@ fn strip_ansi_escapes(src_str: &str) -> String {
 }
 
 
+thread_local! {
+    // Stack of the key-value fields pushed by the active `with_fields!` scopes of this thread
+    static FIELDS: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Guard returned by [with_fields!] (and [push_fields]).
+///
+/// The fields it pushed are removed from the current thread's scope when it is dropped.
+#[must_use = "the fields are removed as soon as the guard is dropped"]
+pub struct FieldsGuard { count: usize }
+
+impl Drop for FieldsGuard {
+    fn drop(&mut self) {
+        FIELDS.with(|fields| {
+            let mut fields = fields.borrow_mut();
+            let len = fields.len().saturating_sub(self.count);
+            fields.truncate(len);
+        });
+    }
+}
+
+/// Pushes key-value fields that are attached to every log record of the current thread
+/// until the returned guard is dropped.
+///
+/// Prefer the [with_fields!] macro.
+pub fn push_fields(new_fields: Vec<(String, String)>) -> FieldsGuard {
+    let count = new_fields.len();
+    FIELDS.with(|fields| fields.borrow_mut().extend(new_fields));
+    FieldsGuard { count }
+}
+
+/// Returns the fields of the active scopes of the current thread (outermost first).
+pub fn current_fields() -> Vec<(String, String)> {
+    FIELDS.with(|fields| fields.borrow().clone())
+}
+
 const LEVEL_WIDTH: usize = 0x05;  // * Just an unsigned integer w/ a fancy declaration
 
+/// A single log record, captured where the log macro was invoked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The `Level` of the record.
+    pub level: Level,
+    /// The formatted message.
+    pub message: String,
+    /// The scoped fields active when the record was created (see [with_fields!]).
+    pub fields: Vec<(String, String)>,
+    /// The time elapsed since the UNIX epoch when the record was created
+    /// (from the [datetime clock](crate::datetime::set_clock)).
+    pub timestamp: Duration,
+}
+
+impl Record {
+    /// Captures a record on the current thread (message, fields and timestamp).
+    pub fn capture(level: Level, args: fmt::Arguments) -> Self {
+        Record {
+            level,
+            message: args.to_string(),
+            fields: current_fields(),
+            timestamp: datetime::clock_now(),
+        }
+    }
+}
+
+thread_local! {
+    // The buffer of the active `test_capture` guard of this thread, if any
+    static CAPTURE: RefCell<Option<Rc<RefCell<Vec<Record>>>>> = const { RefCell::new(None) };
+}
+
+/// Guard returned by [test_capture] that holds the captured records.
+///
+/// The previous output (stdout or an outer capture) is restored when it is dropped.
+#[must_use = "the capture stops as soon as the guard is dropped"]
+pub struct LogCapture {
+    records: Rc<RefCell<Vec<Record>>>,
+    previous: Option<Rc<RefCell<Vec<Record>>>>,
+}
+
+/// Redirects the records logged on the current thread to an in-memory buffer.
+///
+/// Every level is captured regardless of [set_max_level], so assertions don't depend on the
+/// global configuration (or on other tests running in parallel).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::dlog::*;
+///
+/// let logs = test_capture();
+/// warn!("Disk usage at {}%", 93);
+/// logs.assert_logged(Level::Warn, "Disk usage at 93%");
+/// assert!(!logs.contains(Level::Error, "Disk"));
+/// ```
+pub fn test_capture() -> LogCapture {
+    let records = Rc::new(RefCell::new(Vec::new()));
+    let previous = CAPTURE.with(|capture| capture.borrow_mut().replace(Rc::clone(&records)));
+    LogCapture { records, previous }
+}
+
+impl LogCapture {
+    /// Returns a copy of the records captured so far.
+    pub fn records(&self) -> Vec<Record> {self.records.borrow().clone()}
+
+    /// Returns whether a record with the given level contains `needle` in its message.
+    pub fn contains(&self, level: Level, needle: &str) -> bool {
+        self.records.borrow().iter().any(|r| r.level == level && r.message.contains(needle))
+    }
+
+    /// Removes the captured records.
+    pub fn clear(&self) {self.records.borrow_mut().clear()}
+
+    /// Panics (listing the captured records) if no record matches [LogCapture::contains].
+    #[track_caller]
+    pub fn assert_logged(&self, level: Level, needle: &str) {
+        if !self.contains(level, needle) {
+            panic!("no {} record containing {:?} was logged. Captured records:\n{}", level, needle, self.listing());
+        }
+    }
+
+    /// Panics (listing the captured records) if a record matches [LogCapture::contains].
+    #[track_caller]
+    pub fn assert_not_logged(&self, level: Level, needle: &str) {
+        if self.contains(level, needle) {
+            panic!("a {} record containing {:?} was logged. Captured records:\n{}", level, needle, self.listing());
+        }
+    }
+
+    fn listing(&self) -> String {
+        match self.records.borrow().is_empty() {
+            true => "    (none)".to_string(),
+            false => self.records.borrow().iter()
+                .map(|r| format!("    {:<LEVEL_WIDTH$} {}", r.level, r.message))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CAPTURE.with(|capture| *capture.borrow_mut() = previous);
+    }
+}
+
 /// Trait for customizing log message formatting.
 pub trait DlogStyle {
     /// Formats a log message.
     ///
-    /// This method can be overridden to customize the appearance of log messages.
+    /// By default this captures a [Record] and delegates to [DlogStyle::format_record].
     ///
     /// # Arguments
     ///
@@ -154,17 +321,26 @@ pub trait DlogStyle {
     ///
     /// A `String` containing the formatted log message
     fn format_log(&self, level: &Level, args: fmt::Arguments) -> String {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        let secs = now.as_secs();
-        let ms = now.subsec_millis();
-
-        let (hr, min, sec) = (
-            (secs / 3600) % 24,
-            (secs / 60) % 60,
-            secs % 60
-        );
+        self.format_record(&Record::capture(*level, args))
+    }
 
-        let timestamp = format!("\x1b[90m[{hr:02}:{min:02}:{sec:02}.{ms:03}]\x1b[0m");
+    /// Formats a captured [Record].
+    ///
+    /// This method can be overridden to customize the appearance of log messages.
+    /// It is also the method used by the asynchronous backend (see [enable_async]),
+    /// where records are formatted on a worker thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record to format
+    ///
+    /// # Returns
+    ///
+    /// A `String` containing the formatted log message
+    fn format_record(&self, record: &Record) -> String {
+        let level = &record.level;
+        let time = DateTime::from_timestamp_nanos(record.timestamp.as_nanos() as i64).time;
+        let timestamp = format!("\x1b[90m[{:02}:{:02}:{:02}.{:03}]\x1b[0m", time.hour(), time.minute(), time.second(), time.millisecond());
         
         let level_str = level.to_string();
         let level_str = self.level_color(level, 
@@ -175,14 +351,26 @@ pub trait DlogStyle {
         let prefix = format!("{} {} ", timestamp, level_str);
         let content_start = strip_ansi_escapes(&prefix).len();
 
-        let binding = args.to_string();
-        let (lines, overall_style) = parse_styled_lines(&binding);
+        let (lines, overall_style) = parse_styled_lines(&record.message);
+        let lines = match message_width(content_start) {
+            Some(width) => lines.iter().flat_map(|line| wrap(line, width).split('\n').map(String::from).collect::<Vec<_>>()).collect(),
+            None => lines,
+        };
         let line_count = lines.len();
 
+        let fields = &record.fields;
+        let fields_suffix = match fields.is_empty() {
+            true => String::new(),
+            false => format!("{}{}",
+                if overall_style.is_empty() {""} else {"\x1b[0m"},
+                fields.iter().map(|(k, v)| format!(" {}{v}", format!("{k}=").style(Style::Dim))).collect::<String>()
+            ),
+        };
+
         let mut output = String::new();
         for (i, line) in lines.into_iter().enumerate() {
             let formatted_line = if i == 0 {
-                format!("{}{}{}", prefix, overall_style, line)
+                format!("{}{}{}{}", prefix, overall_style, line, fields_suffix)
             } else {
                 let line_prefix = if i == line_count - 1 { "└" } else { "│" };
                 format!("\n{}{} {}{}", 
@@ -214,6 +402,15 @@ pub trait DlogStyle {
     }
 }
 
+/// Returns the width available for the message on a line of the terminal, if it is known
+/// and wide enough for wrapping to be useful.
+fn message_width(content_start: usize) -> Option<usize> {
+    terminal_size()
+        .map(|(columns, _)| columns as usize)
+        .filter(|&columns| columns >= content_start + 20)
+        .map(|columns| columns - content_start)
+}
+
 /// Parses a string into lines, extracting any overall style.
 ///
 /// This function is used internally to handle multi-line log messages and preserve styling.
@@ -252,6 +449,677 @@ impl DlogStyle for DefaultDlogStyle {
     }
 }
 
+/// A [DlogStyle] that renders each record as a single-line JSON object.
+///
+/// ANSI codes are stripped from the message, and the scoped fields (see [with_fields!])
+/// are included in a `fields` object.
+///
+/// ```text
+/// {"timestamp":1700000000123,"level":"Info","message":"started","fields":{"request_id":"42"}}
+/// ```
+pub struct JsonDlogStyle;
+
+impl DlogStyle for JsonDlogStyle {
+    fn format_record(&self, record: &Record) -> String {
+        let fields = record.fields.iter()
+            .map(|(k, v)| format!("\"{}\":\"{}\"", json_escape(k), json_escape(v)))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{\"timestamp\":{},\"level\":\"{}\",\"message\":\"{}\",\"fields\":{{{}}}}}",
+            record.timestamp.as_millis(), record.level, json_escape(&strip_ansi_codes(&record.message)), fields
+        )
+    }
+}
+
+/// Escapes a string to be embedded in a JSON string literal.
+pub(crate) fn json_escape(s: &str) -> String {
+    s.chars().map(|c| match c {
+        '"' => "\\\"".to_string(),
+        '\\' => "\\\\".to_string(),
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        '\t' => "\\t".to_string(),
+        c if c.is_control() => format!("\\u{:04x}", c as u32),
+        c => c.to_string(),
+    }).collect()
+}
+
+/// The output format used by the logging macros.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LogFormat {
+    /// Colored, human-readable output ([DefaultDlogStyle]).
+    #[default]
+    Pretty = 0,
+    /// One JSON object per line ([JsonDlogStyle]).
+    Json = 1,
+}
+
+static LOG_FORMAT: AtomicUsize = AtomicUsize::new(LogFormat::Pretty as usize);
+
+/// Sets the output format used by the logging macros.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::dlog::{set_format, LogFormat};
+///
+/// set_format(LogFormat::Json);
+/// ```
+pub fn set_format(format: LogFormat) {
+    LOG_FORMAT.store(format as usize, Ordering::SeqCst);
+}
+
+fn current_format() -> LogFormat {
+    match LOG_FORMAT.load(Ordering::Relaxed) {
+        1 => LogFormat::Json,
+        _ => LogFormat::Pretty,
+    }
+}
+
+fn format_with(format: LogFormat, record: &Record) -> String {
+    match format {
+        LogFormat::Json => JsonDlogStyle.format_record(record),
+        LogFormat::Pretty => DefaultDlogStyle.format_record(record),
+    }
+}
+
+/// Logs a message with the given level, using the format selected with [set_format].
+///
+/// This function is called by the logging macros. When the asynchronous backend is
+/// enabled (see [enable_async]) the record is handed to the worker thread.
+pub fn emit(level: Level, args: fmt::Arguments) {
+    let captured = CAPTURE.with(|capture| match capture.borrow().as_ref() {
+        Some(records) => {records.borrow_mut().push(Record::capture(level, args)); true},
+        None => false,
+    });
+    if captured || !enabled(level) {return;}
+    RECORD_COUNTS[level as usize - 1].fetch_add(1, Ordering::Relaxed);
+    let record = Record::capture(level, args);
+    if DEDUP_ENABLED.load(Ordering::Relaxed) {
+        // keep the lock while dispatching so the summary is written before the new record
+        let mut dedup = DEDUP.lock().unwrap_or_else(PoisonError::into_inner);
+        let (is_new, summary) = dedup.check(&record);
+        summary.into_iter().for_each(dispatch);
+        if is_new {dispatch(record);}
+        return;
+    }
+    dispatch(record);
+}
+
+// Records emitted per level (indexed by `level as usize - 1`), for `record_counts`
+static RECORD_COUNTS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+
+/// Returns the number of records emitted by the logging macros at each level, from
+/// [Level::Error] to [Level::Trace] (disabled levels and [test_capture]d records aside).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::dlog::*;
+///
+/// set_max_level(Level::Info);
+/// let before = record_counts()[0].1;
+/// error!("counted");
+/// assert_eq!(record_counts()[0], (Level::Error, before + 1));
+/// ```
+pub fn record_counts() -> Vec<(Level, u64)> {
+    [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace].into_iter()
+        .map(|level| (level, RECORD_COUNTS[level as usize - 1].load(Ordering::Relaxed)))
+        .collect()
+}
+
+// Sends a record to the asynchronous backend if enabled, or writes it right away
+fn dispatch(record: Record) {
+    match async_sender() {
+        Some(sender) => {let _ = sender.send(AsyncMessage::Record(record, current_format()));},
+        None => write_output(record.level, &format_with(current_format(), &record)),
+    }
+}
+
+// Collapses consecutive records with the same level and message
+struct Dedup {
+    last: Option<(Level, String)>,
+    repeated: usize,
+}
+
+impl Dedup {
+    const fn new() -> Self {Dedup { last: None, repeated: 0 }}
+
+    // Returns whether the record must be written, and the summary of the previous repetitions (if any)
+    fn check(&mut self, record: &Record) -> (bool, Option<Record>) {
+        match &self.last {
+            Some((level, message)) if *level == record.level && *message == record.message => {
+                self.repeated += 1;
+                (false, None)
+            },
+            _ => {
+                let summary = self.take_summary();
+                self.last = Some((record.level, record.message.clone()));
+                (true, summary)
+            },
+        }
+    }
+
+    fn take_summary(&mut self) -> Option<Record> {
+        let (level, _) = self.last.as_ref()?;
+        match std::mem::take(&mut self.repeated) {
+            0 => None,
+            n => Some(Record::capture(*level, format_args!("message repeated {} times", n))),
+        }
+    }
+}
+
+static DEDUP_ENABLED: AtomicBool = AtomicBool::new(false);
+static DEDUP: Mutex<Dedup> = Mutex::new(Dedup::new());
+
+/// Enables or disables the collapsing of repeated messages.
+///
+/// When enabled, consecutive records with the same level and message are written once,
+/// followed by a `message repeated N times` record when a different message arrives
+/// (or on [flush]).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::dlog::*;
+///
+/// set_max_level(Level::Info);
+/// set_dedup(true);
+/// (0..128).for_each(|_| warn!("disk almost full"));  // printed once
+/// info!("done");  // preceded by "message repeated 127 times"
+/// set_dedup(false);
+/// ```
+pub fn set_dedup(enabled: bool) {
+    DEDUP_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Per-callsite state of the log-once ([info_once!], ...) and rate-limited ([warn_every!], ...) macros.
+///
+/// Each macro invocation owns a `static` callsite, so the state is shared by every thread.
+pub struct Callsite {
+    last: AtomicU64,  // `elapsed_nanos()` of the last emission
+    suppressed: AtomicU64,
+}
+
+const NEVER: u64 = u64::MAX;
+
+// Nanoseconds on a monotonic clock, for the rate-limited macros
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn elapsed_nanos() -> u64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+// `Instant::now` panics without a system clock: the `datetime` clock is used instead
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn elapsed_nanos() -> u64 {datetime::clock_now().as_nanos() as u64}
+
+impl Callsite {
+    /// Creates a callsite that has never emitted.
+    pub const fn new() -> Self {Callsite { last: AtomicU64::new(NEVER), suppressed: AtomicU64::new(0) }}
+
+    /// Returns `true` the first time it is called, and `false` afterwards.
+    pub fn first(&self) -> bool {
+        self.last.swap(0, Ordering::Relaxed) == NEVER
+    }
+
+    /// Returns whether the callsite may emit again, `interval` after its last emission.
+    ///
+    /// # Returns
+    ///
+    /// `Some(n)` if it may emit, where `n` is the number of calls suppressed since the
+    /// last emission, or `None` if the call must be suppressed.
+    pub fn every(&self, interval: Duration) -> Option<u64> {
+        let now = elapsed_nanos();
+        let last = self.last.load(Ordering::Relaxed);
+        let due = last == NEVER || now.saturating_sub(last) >= interval.as_nanos() as u64;
+        match due && self.last.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed).is_ok() {
+            true => Some(self.suppressed.swap(0, Ordering::Relaxed)),
+            false => {
+                self.suppressed.fetch_add(1, Ordering::Relaxed);
+                None
+            },
+        }
+    }
+}
+
+impl Default for Callsite {
+    fn default() -> Self {Self::new()}
+}
+
+/// A destination for the formatted records (stdout by default), set with [set_writer].
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Mutex;
+/// use dev_utils::dlog::*;
+///
+/// struct Collect(Mutex<Vec<String>>);
+///
+/// impl Writer for Collect {
+///     fn write(&self, _level: Level, line: &str) {self.0.lock().unwrap().push(line.to_string());}
+/// }
+///
+/// set_writer(Collect(Mutex::new(Vec::new())));
+/// set_max_level(Level::Info);
+/// info!("collected, not printed");
+/// reset_writer();
+/// ```
+pub trait Writer: Send + Sync {
+    /// Writes a formatted record, without its trailing newline.
+    ///
+    /// The line has no ANSI codes when colors are disabled (see [crate::format::term]).
+    fn write(&self, level: Level, line: &str);
+
+    /// Writes out any buffered output (called by [flush]).
+    fn flush(&self) {}
+}
+
+/// The default [Writer]: one line per record on stdout.
+pub struct StdoutWriter;
+
+impl Writer for StdoutWriter {
+    fn write(&self, _level: Level, line: &str) {println!("{}", line);}
+
+    fn flush(&self) {let _ = io::stdout().flush();}
+}
+
+/// A [Writer] to the browser console (`console.error`, `console.warn`, `console.info` or
+/// `console.debug`, by level), the default writer with the `wasm` feature on `wasm32`.
+///
+/// It calls the `console_log(level, ptr, len)` function imported from the `dev_utils`
+/// module, which the JS host provides when instantiating the module, e.g.:
+///
+/// ```js
+/// const { instance } = await WebAssembly.instantiateStreaming(fetch("tool.wasm"), {
+///     dev_utils: {
+///         console_log(level, ptr, len) {
+///             const line = new TextDecoder().decode(new Uint8Array(instance.exports.memory.buffer, ptr, len));
+///             [console.error, console.warn, console.info, console.debug, console.debug][level - 1](line);
+///         },
+///     },
+/// });
+/// ```
+///
+/// With `wasm-bindgen`, implement a [Writer] over `web_sys::console` instead and [set_writer] it.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub struct ConsoleWriter;
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+impl Writer for ConsoleWriter {
+    fn write(&self, level: Level, line: &str) {
+        #[link(wasm_import_module = "dev_utils")]
+        extern "C" {
+            fn console_log(level: u32, ptr: *const u8, len: usize);
+        }
+        // SAFETY: the host only reads `len` bytes of the line from the module memory
+        unsafe {console_log(level as u32, line.as_ptr(), line.len());}
+    }
+}
+
+static WRITER: RwLock<Option<Arc<dyn Writer>>> = RwLock::new(None);
+
+/// Sends the records to `writer` instead of the default one ([StdoutWriter], or
+/// [ConsoleWriter] with the `wasm` feature on `wasm32`).
+///
+/// The writer is also used by the asynchronous backend (see [enable_async]).
+pub fn set_writer<W: Writer + 'static>(writer: W) {
+    *WRITER.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(writer));
+}
+
+/// Goes back to the default writer (see [set_writer]).
+pub fn reset_writer() {*WRITER.write().unwrap_or_else(PoisonError::into_inner) = None;}
+
+// The writer set with `set_writer`, if any (cloned, so that writers can log)
+fn custom_writer() -> Option<Arc<dyn Writer>> {WRITER.read().unwrap_or_else(PoisonError::into_inner).clone()}
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+fn default_writer() -> &'static dyn Writer {&StdoutWriter}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+fn default_writer() -> &'static dyn Writer {&ConsoleWriter}
+
+// Writes a formatted message to the current writer, honoring the global color mode
+fn write_output(level: Level, message: &str) {
+    let stripped;
+    let line = match term::colors_enabled() {
+        true => message,
+        false => {
+            stripped = strip_ansi_codes(message);
+            &stripped
+        },
+    };
+    match custom_writer() {
+        Some(writer) => writer.write(level, line),
+        None => default_writer().write(level, line),
+    }
+}
+
+enum AsyncMessage {
+    Record(Record, LogFormat),
+    Flush(mpsc::Sender<()>),
+}
+
+// `None` once enabling failed because no thread could be spawned (logging stays synchronous)
+static ASYNC_SENDER: OnceLock<Option<SyncSender<AsyncMessage>>> = OnceLock::new();
+
+fn async_sender() -> Option<&'static SyncSender<AsyncMessage>> {ASYNC_SENDER.get()?.as_ref()}
+
+/// Guard returned by [enable_async] that flushes pending log records when dropped.
+#[must_use = "pending records are flushed when the guard is dropped"]
+pub struct AsyncGuard;
+
+impl Drop for AsyncGuard {
+    fn drop(&mut self) {flush();}
+}
+
+/// Enables the asynchronous logging backend.
+///
+/// The logging macros then only capture the message into a bounded channel of
+/// `capacity` records; a background thread formats the records and writes them through a
+/// buffered stdout (or the [Writer] set with [set_writer]). When the channel is full, the
+/// callers block until there is room. It needs a thread: where none can be spawned (like on
+/// `wasm32-unknown-unknown`), the records keep being written synchronously
+/// ([try_enable_async] reports it with [InitError::AsyncUnavailable]).
+///
+/// Records still in flight are lost if the process exits without calling [flush] (or
+/// dropping the returned [AsyncGuard]). Calling this function again has no effect.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::dlog::*;
+///
+/// let _log = enable_async(1024);  // flushes on drop
+/// set_max_level(Level::Info);
+/// (0..3).for_each(|i| info!("fast log {}", i));
+/// flush();
+/// ```
+pub fn enable_async(capacity: usize) -> AsyncGuard {
+    ASYNC_SENDER.get_or_init(|| spawn_async_worker(capacity));
+    AsyncGuard
+}
+
+// Starts the writer thread, or returns `None` if threads are not supported
+fn spawn_async_worker(capacity: usize) -> Option<SyncSender<AsyncMessage>> {
+    let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+    thread::Builder::new()
+        .name("dlog-writer".to_string())
+        .spawn(move || async_worker(receiver))
+        .ok()?;
+    Some(sender)
+}
+
+/// Like [enable_async], but returns an error if the asynchronous backend was already enabled
+/// or if its writer thread could not be spawned.
+pub fn try_enable_async(capacity: usize) -> Result<AsyncGuard, InitError> {
+    match ASYNC_SENDER.get() {
+        Some(Some(_)) => Err(InitError::AsyncAlreadyEnabled),
+        Some(None) => Err(InitError::AsyncUnavailable),
+        None => {
+            let mut installed = false;
+            let sender = ASYNC_SENDER.get_or_init(|| {
+                installed = true;
+                spawn_async_worker(capacity)
+            });
+            match (installed, sender) {
+                (_, None) => Err(InitError::AsyncUnavailable),
+                (true, Some(_)) => Ok(AsyncGuard),
+                (false, Some(_)) => Err(InitError::AsyncAlreadyEnabled),  // lost a race with another thread
+            }
+        },
+    }
+}
+
+/// Blocks until every record sent to the asynchronous backend has been written.
+///
+/// Also writes the pending `message repeated N times` summary (see [set_dedup]).
+pub fn flush() {
+    if let Some(summary) = DEDUP.lock().unwrap_or_else(PoisonError::into_inner).take_summary() {dispatch(summary);}
+    if let Some(sender) = async_sender() {
+        let (ack, done) = mpsc::channel();
+        if sender.send(AsyncMessage::Flush(ack)).is_ok() {let _ = done.recv();}
+    }
+    match custom_writer() {
+        Some(writer) => writer.flush(),
+        None => default_writer().flush(),
+    }
+}
+
+fn async_worker(receiver: Receiver<AsyncMessage>) {
+    let mut out = io::BufWriter::with_capacity(64 * 1024, io::stdout());
+    let mut next = receiver.recv().ok();
+    while let Some(message) = next {
+        match message {
+            AsyncMessage::Record(record, format) => {
+                let line = format_with(format, &record);
+                match custom_writer() {
+                    // a custom writer gets the line right away, stdout goes through the buffer
+                    Some(_) => write_output(record.level, &line),
+                    None => {
+                        let _ = match term::colors_enabled() {
+                            true => writeln!(out, "{}", line),
+                            false => writeln!(out, "{}", strip_ansi_codes(&line)),
+                        };
+                    },
+                }
+            },
+            AsyncMessage::Flush(ack) => {
+                let _ = out.flush();
+                let _ = ack.send(());
+            },
+        }
+        // write the buffer out whenever the channel runs dry
+        next = match receiver.try_recv() {
+            Ok(message) => Some(message),
+            Err(_) => {
+                let _ = out.flush();
+                receiver.recv().ok()
+            },
+        };
+    }
+}
+
+/// The configuration installed by [try_init] / [init].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DlogConfig {
+    /// The maximum level to log (see [set_max_level]).
+    pub level: Level,
+    /// The output format (see [set_format]).
+    pub format: LogFormat,
+    /// The channel capacity of the asynchronous backend, if it should be enabled (see [enable_async]).
+    pub async_capacity: Option<usize>,
+}
+
+impl Default for DlogConfig {
+    fn default() -> Self {
+        DlogConfig { level: Level::Info, format: LogFormat::Pretty, async_capacity: None }
+    }
+}
+
+/// Represents errors that can occur when initializing the logger.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InitError {
+    /// A configuration was already installed (the installed one is returned).
+    AlreadyInitialized(DlogConfig),
+    /// The asynchronous backend was already enabled.
+    AsyncAlreadyEnabled,
+    /// The asynchronous backend could not spawn its writer thread, so logging stays synchronous.
+    AsyncUnavailable,
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::AlreadyInitialized(config) => write!(f, "dlog is already initialized ({:?})", config),
+            InitError::AsyncAlreadyEnabled => write!(f, "the dlog asynchronous backend is already enabled"),
+            InitError::AsyncUnavailable => write!(f, "the dlog asynchronous backend needs threads, which are not available"),
+        }
+    }
+}
+
+impl std::error::Error for InitError {}
+
+static INIT_CONFIG: OnceLock<DlogConfig> = OnceLock::new();
+
+/// Installs a logger configuration, once per process.
+///
+/// This is safe to call concurrently and from both libraries and binaries: only the first
+/// call installs its configuration, every other call returns [InitError::AlreadyInitialized]
+/// with the configuration that won. The returned guard flushes pending records on drop
+/// (see [enable_async]).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::dlog::*;
+///
+/// let _log = try_init(DlogConfig { level: Level::Debug, ..Default::default() }).unwrap();
+/// assert!(matches!(try_init(DlogConfig::default()), Err(InitError::AlreadyInitialized(_))));
+/// assert!(enabled(Level::Debug));
+/// ```
+pub fn try_init(config: DlogConfig) -> Result<AsyncGuard, InitError> {
+    let mut installed = false;
+    let current = INIT_CONFIG.get_or_init(|| {
+        installed = true;
+        config
+    });
+    if !installed {return Err(InitError::AlreadyInitialized(*current));}
+
+    set_max_level(config.level);
+    set_format(config.format);
+    if let Some(capacity) = config.async_capacity {
+        let _ = try_enable_async(capacity);
+    }
+    Ok(AsyncGuard)
+}
+
+/// Installs a logger configuration, ignoring the call if one was already installed.
+///
+/// See [try_init] to know whether the configuration was applied.
+pub fn init(config: DlogConfig) -> AsyncGuard {
+    let _ = try_init(config);
+    AsyncGuard
+}
+
+/// Returns the configuration installed by [try_init] / [init], if any.
+pub fn installed_config() -> Option<DlogConfig> {INIT_CONFIG.get().copied()}
+
+/// Replaces the default panic message with a red error block.
+///
+/// The block shows the thread, the location and the message of the panic, followed by the
+/// backtrace when one is captured (`RUST_BACKTRACE=1`, see [std::backtrace::Backtrace::capture]).
+/// Before printing, pending records are [flush]ed so they appear first, and the terminal
+/// is [reset](crate::console::term::reset) (raw mode, hidden cursor, alternate screen), so a
+/// panic in an interactive tool doesn't leave the terminal broken. The block goes to stderr.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dev_utils::dlog;
+///
+/// dlog::install_panic_hook();
+/// let config: Option<&str> = None;
+/// config.expect("no config file found");
+/// //  PANIC  thread 'main' panicked at src/main.rs:5:12
+/// //  │ no config file found
+/// ```
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        // the writer thread can't wait for itself
+        if thread::current().name() != Some("dlog-writer") {flush();}
+        crate::console::term::reset();
+
+        let message = match (info.payload().downcast_ref::<&str>(), info.payload().downcast_ref::<String>()) {
+            (Some(message), _) => message.to_string(),
+            (None, Some(message)) => message.clone(),
+            (None, None) => "Box<dyn Any>".to_string(),
+        };
+        let location = info.location().map_or("an unknown location".to_string(), |l| l.to_string());
+        let backtrace = std::backtrace::Backtrace::capture();
+        let backtrace = (backtrace.status() == std::backtrace::BacktraceStatus::Captured).then(|| backtrace.to_string());
+        let block = panic_block(thread::current().name().unwrap_or("<unnamed>"), &location, &message, backtrace.as_deref());
+        let _ = match term::colors_enabled() {
+            true => writeln!(io::stderr(), "{}", block),
+            false => writeln!(io::stderr(), "{}", strip_ansi_codes(&block)),
+        };
+    }));
+}
+
+// The block printed by the panic hook, with the message (and backtrace) in a gutter
+fn panic_block(thread: &str, location: &str, message: &str, backtrace: Option<&str>) -> String {
+    let red = Level::Error.color();
+    let mut block = format!("{} {}",
+        " PANIC ".on_color(red).style(Style::Bold),
+        format!("thread '{}' panicked at {}", thread, location).color(red),
+    );
+    let mut lines: Vec<String> = message.lines().map(|line| line.style(Style::Bold)).collect();
+    if let Some(backtrace) = backtrace {
+        lines.extend([String::new(), "stack backtrace:".style(Style::Dim)]);
+        lines.extend(backtrace.lines().map(|line| line.style(Style::Dim)));
+    }
+    for line in lines {
+        block.push_str(&format!("\n {} {}", "│".color(red), line));
+    }
+    block.push_str("\x1b[0m");
+    block
+}
+
+#[cfg(feature = "log-compat")]
+impl From<log::Level> for Level {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => Level::Error,
+            log::Level::Warn => Level::Warn,
+            log::Level::Info => Level::Info,
+            log::Level::Debug => Level::Debug,
+            log::Level::Trace => Level::Trace,
+        }
+    }
+}
+
+// Forwards the records of the `log` crate macros to `emit`
+#[cfg(feature = "log-compat")]
+struct LogAdapter;
+
+#[cfg(feature = "log-compat")]
+impl log::Log for LogAdapter {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {enabled(metadata.level().into())}
+
+    fn log(&self, record: &log::Record) {emit(record.level().into(), *record.args())}
+
+    fn flush(&self) {flush()}
+}
+
+/// Installs dlog as the logger of the `log` crate facade.
+///
+/// Libraries using the standard `log` macros (`log::info!`, ...) are then printed with
+/// dlog's styles and filtered by [set_max_level]. Requires the `log-compat` feature.
+///
+/// # Returns
+///
+/// Returns a `Result` containing `()`, or a `log::SetLoggerError` if another logger was
+/// already installed.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "log-compat")] {
+/// use dev_utils::dlog::{init_as_log_logger, set_max_level, Level};
+///
+/// init_as_log_logger().unwrap();
+/// set_max_level(Level::Debug);
+/// log::debug!("printed by dlog");
+/// # }
+/// ```
+#[cfg(feature = "log-compat")]
+pub fn init_as_log_logger() -> Result<(), log::SetLoggerError> {
+    log::set_logger(&LogAdapter)?;
+    log::set_max_level(log::LevelFilter::Trace);  // the filtering is done by `enabled`
+    Ok(())
+}
+
 /// Logs a message with the given style and level.
 ///
 /// This function is the core of the logging system and is typically called through the logging macros.
@@ -263,15 +1131,36 @@ impl DlogStyle for DefaultDlogStyle {
 /// * `args` - The message content as `fmt::Arguments`
 pub fn log(style: &impl DlogStyle, level: Level, args: fmt::Arguments) {
     if enabled(level) {
-        let log_message = style.format_log(&level, args);
-        println!("{}", log_message);
+        write_output(level, &style.format_log(&level, args));
     }
 }
 
 #[macro_export]
 macro_rules! __dlog_internal {
     ($level:expr, $($arg:tt)+) => {
-        $crate::dlog::log(&$crate::dlog::DefaultDlogStyle, $level, format_args!($($arg)+))
+        $crate::dlog::emit($level, format_args!($($arg)+))
+    };
+}
+
+/// Attaches key-value fields to every log record emitted on the current thread while the
+/// returned [FieldsGuard](crate::dlog::FieldsGuard) is alive.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::dlog::*;
+///
+/// set_max_level(Level::Info);
+/// {
+///     let _fields = with_fields!("request_id" => 42, "user" => "alice");
+///     info!("Handling request");  // ... request_id=42 user=alice
+/// }
+/// info!("No fields here");
+/// ```
+#[macro_export]
+macro_rules! with_fields {
+    ($($key:expr => $value:expr),* $(,)?) => {
+        $crate::dlog::push_fields(vec![$(($key.to_string(), $value.to_string())),*])
     };
 }
 
@@ -282,6 +1171,181 @@ macro_rules! __dlog_internal {
 #[macro_export] macro_rules! trace { ($($arg:tt)+) => { $crate::__dlog_internal!($crate::dlog::Level::Trace, $($arg)+) }; }
 
 
+#[macro_export]
+macro_rules! __dlog_once {
+    ($level:expr, $($arg:tt)+) => {{
+        static CALLSITE: $crate::dlog::Callsite = $crate::dlog::Callsite::new();
+        if $crate::dlog::enabled($level) && CALLSITE.first() {
+            $crate::dlog::emit($level, format_args!($($arg)+));
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! __dlog_every {
+    ($level:expr, $interval:expr, $($arg:tt)+) => {{
+        static CALLSITE: $crate::dlog::Callsite = $crate::dlog::Callsite::new();
+        if $crate::dlog::enabled($level) {
+            match CALLSITE.every($interval) {
+                Some(0) => $crate::dlog::emit($level, format_args!($($arg)+)),
+                Some(n) => $crate::dlog::emit($level, format_args!("{} (suppressed {} similar messages)", format_args!($($arg)+), n)),
+                None => (),
+            }
+        }
+    }};
+}
+
+// Log only the first time the callsite is reached, e.g. `info_once!("Using {} threads", n)`
+#[macro_export] macro_rules! error_once { ($($arg:tt)+) => { $crate::__dlog_once!($crate::dlog::Level::Error, $($arg)+) }; }
+#[macro_export] macro_rules! warn_once  { ($($arg:tt)+) => { $crate::__dlog_once!($crate::dlog::Level::Warn,  $($arg)+) }; }
+#[macro_export] macro_rules! info_once  { ($($arg:tt)+) => { $crate::__dlog_once!($crate::dlog::Level::Info,  $($arg)+) }; }
+#[macro_export] macro_rules! debug_once { ($($arg:tt)+) => { $crate::__dlog_once!($crate::dlog::Level::Debug, $($arg)+) }; }
+#[macro_export] macro_rules! trace_once { ($($arg:tt)+) => { $crate::__dlog_once!($crate::dlog::Level::Trace, $($arg)+) }; }
+
+// Log at most once per interval, e.g. `warn_every!(Duration::from_secs(5), "Queue is full")`
+#[macro_export] macro_rules! error_every { ($interval:expr, $($arg:tt)+) => { $crate::__dlog_every!($crate::dlog::Level::Error, $interval, $($arg)+) }; }
+#[macro_export] macro_rules! warn_every  { ($interval:expr, $($arg:tt)+) => { $crate::__dlog_every!($crate::dlog::Level::Warn,  $interval, $($arg)+) }; }
+#[macro_export] macro_rules! info_every  { ($interval:expr, $($arg:tt)+) => { $crate::__dlog_every!($crate::dlog::Level::Info,  $interval, $($arg)+) }; }
+#[macro_export] macro_rules! debug_every { ($interval:expr, $($arg:tt)+) => { $crate::__dlog_every!($crate::dlog::Level::Debug, $interval, $($arg)+) }; }
+#[macro_export] macro_rules! trace_every { ($interval:expr, $($arg:tt)+) => { $crate::__dlog_every!($crate::dlog::Level::Trace, $interval, $($arg)+) }; }
+
+
 // todo: Improve this code by implemeneting some PROC MACRO
 // todo: that will generate the following macros.
 // todo: Because the code below is repetitive, so it can be generated.
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_scope() {
+        assert!(current_fields().is_empty());
+        {
+            let _outer = with_fields!("request_id" => 42);
+            {
+                let _inner = with_fields!("user" => "alice", "role" => "admin");
+                assert_eq!(current_fields().len(), 3);
+                let line = strip_ansi_codes(&DefaultDlogStyle.format_log(&Level::Info, format_args!("hi")));
+                assert!(line.ends_with("hi request_id=42 user=alice role=admin"));
+            }
+            assert_eq!(current_fields(), vec![("request_id".to_string(), "42".to_string())]);
+        }
+        assert!(current_fields().is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_init() {
+        let config = DlogConfig { level: Level::Trace, ..Default::default() };
+        let results = (0..8)
+            .map(|_| thread::spawn(move || try_init(config).is_ok()))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(results.iter().filter(|&&ok| ok).count(), 1);
+        assert_eq!(installed_config(), Some(config));
+        assert_eq!(try_init(config).err(), Some(InitError::AlreadyInitialized(config)));
+    }
+
+    #[test]
+    fn test_json_style() {
+        let _fields = with_fields!("k" => "v\"q");
+        let line = JsonDlogStyle.format_log(&Level::Warn, format_args!("a\nb"));
+        assert!(line.starts_with("{\"timestamp\":"));
+        assert!(line.ends_with(",\"level\":\"Warn\",\"message\":\"a\\nb\",\"fields\":{\"k\":\"v\\\"q\"}}"));
+    }
+
+    #[test]
+    #[cfg(feature = "log-compat")]
+    fn test_log_level_mapping() {
+        assert_eq!(Level::from(log::Level::Warn), Level::Warn);
+        assert_eq!(Level::from(log::Level::Trace), Level::Trace);
+        assert!(Level::from(log::Level::Error) < Level::from(log::Level::Info));
+    }
+
+    #[test]
+    fn test_callsite() {
+        let once = Callsite::new();
+        assert!(once.first());
+        assert!(!once.first());
+
+        let every = Callsite::new();
+        assert_eq!(every.every(Duration::from_secs(60)), Some(0));
+        assert_eq!(every.every(Duration::from_secs(60)), None);
+        assert_eq!(every.every(Duration::from_secs(60)), None);
+        assert_eq!(every.every(Duration::ZERO), Some(2));
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut dedup = Dedup::new();
+        let record = |message: &str| Record::capture(Level::Warn, format_args!("{}", message));
+        assert_eq!(dedup.check(&record("full")), (true, None));
+        assert_eq!(dedup.check(&record("full")), (false, None));
+        assert_eq!(dedup.check(&record("full")), (false, None));
+        let (is_new, summary) = dedup.check(&record("done"));
+        assert!(is_new);
+        assert_eq!(summary.unwrap().message, "message repeated 2 times");
+        assert!(dedup.take_summary().is_none());
+    }
+
+    #[test]
+    fn test_log_capture() {
+        let logs = test_capture();
+        warn!("cache miss for key {}", 7);
+        {
+            let inner = test_capture();
+            error!("inner only");
+            inner.assert_logged(Level::Error, "inner");
+        }
+        debug!("captured even when disabled");
+        logs.assert_logged(Level::Warn, "key 7");
+        logs.assert_not_logged(Level::Error, "inner");
+        assert_eq!(logs.records().len(), 2);
+        logs.clear();
+        assert!(logs.records().is_empty());
+    }
+
+    #[test]
+    fn test_writer() {
+        #[derive(Clone, Default)]
+        struct Collect(Arc<Mutex<Vec<(Level, String)>>>, Arc<AtomicUsize>);
+
+        impl Writer for Collect {
+            fn write(&self, level: Level, line: &str) {self.0.lock().unwrap().push((level, strip_ansi_codes(line)));}
+            fn flush(&self) {self.1.fetch_add(1, Ordering::SeqCst);}
+        }
+
+        let writer = Collect::default();
+        set_writer(writer.clone());
+        write_output(Level::Warn, &"disk almost full".style(Style::Bold));
+        flush();
+        reset_writer();
+        write_output(Level::Warn, "back to stdout");
+
+        // other tests may log at the same time
+        assert!(writer.0.lock().unwrap().contains(&(Level::Warn, "disk almost full".to_string())));
+        assert!(!writer.0.lock().unwrap().iter().any(|(_, line)| line == "back to stdout"));
+        assert!(writer.1.load(Ordering::SeqCst) >= 1);
+    }
+
+    #[test]
+    fn test_panic_block() {
+        let block = panic_block("main", "src/main.rs:3:5", "first line\nsecond line", None);
+        assert_eq!(strip_ansi_codes(&block), " PANIC  thread 'main' panicked at src/main.rs:3:5\n │ first line\n │ second line");
+        assert!(block.ends_with("\x1b[0m"));
+
+        let block = strip_ansi_codes(&panic_block("worker", "a.rs:1:1", "oops", Some("   0: main\n   1: start")));
+        assert_eq!(block.lines().skip(1).collect::<Vec<_>>(), [" │ oops", " │ ", " │ stack backtrace:", " │    0: main", " │    1: start"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no Warn record containing \"missing\"")]
+    fn test_assert_logged_fails() {
+        let logs = test_capture();
+        info!("something else");
+        logs.assert_logged(Level::Warn, "missing");
+    }
+}
@@ -0,0 +1,288 @@
+//! Loading of `.env` files into the process environment.
+//!
+//! # Features
+//! - `KEY=VALUE` lines, with an optional `export ` prefix
+//! - Comments (full-line, and `#` after whitespace in unquoted values)
+//! - Single quoted (literal), double quoted (escapes, multi-line) and unquoted values
+//! - `${VAR}`, `$VAR` and `${VAR:-default}` expansion from earlier keys or the environment
+//!
+//! # Examples
+//! ```
+//! use dev_utils::env::parse_dotenv;
+//!
+//! let values = parse_dotenv("
+//! ## database settings
+//! export DB_HOST=localhost
+//! DB_URL=\"postgres://${DB_HOST}:${DB_PORT:-5432}/app\"
+//! GREETING='hello $USER'
+//! ").unwrap();
+//!
+//! assert_eq!(values["DB_URL"], "postgres://localhost:5432/app");
+//! assert_eq!(values["GREETING"], "hello $USER");
+//! ```
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::path::Path;
+use crate::file::{self, FileError};
+
+/// Represents errors that can occur when reading a `.env` file.
+#[derive(Debug)]
+pub enum DotenvError {
+    /// The file could not be read.
+    File(FileError),
+    /// A line is not a valid `KEY=VALUE` assignment.
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for DotenvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DotenvError::File(err) => write!(f, "{}", err),
+            DotenvError::Parse { line, message } => write!(f, "Invalid .env line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for DotenvError {}
+
+impl From<FileError> for DotenvError {
+    fn from(err: FileError) -> Self {DotenvError::File(err)}
+}
+
+/// Parses the content of a `.env` file without touching the environment.
+///
+/// # Arguments
+///
+/// * `content` - The text of the file
+///
+/// # Returns
+///
+/// Returns a `Result` containing the variables (expanded), or a [DotenvError::Parse].
+pub fn parse_dotenv(content: &str) -> Result<BTreeMap<String, String>, DotenvError> {
+    let mut values = BTreeMap::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((i, line)) = lines.next() {
+        let error = |message: &str| DotenvError::Parse { line: i + 1, message: message.to_string() };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {continue;}
+
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+        let (key, raw) = line.split_once('=').ok_or_else(|| error("expected KEY=VALUE"))?;
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+            return Err(error(&format!("invalid key `{}`", key)));
+        }
+
+        let raw = raw.trim_start();
+        let value = match raw.chars().next() {
+            Some('\'') => match raw[1..].split_once('\'') {
+                Some((literal, rest)) if is_comment_or_empty(rest) => literal.to_string(),
+                Some(_) => return Err(error("unexpected text after the closing quote")),
+                None => return Err(error("unterminated single quoted value")),
+            },
+            Some('"') => {
+                // double quoted values may span several lines
+                let mut text = raw[1..].to_string();
+                let close = loop {
+                    if let Some(close) = closing_quote(&text) {break close;}
+                    let (_, next) = lines.next().ok_or_else(|| error("unterminated double quoted value"))?;
+                    text.push('\n');
+                    text.push_str(next);
+                };
+                if !is_comment_or_empty(&text[close + 1..]) {return Err(error("unexpected text after the closing quote"));}
+                expand(&unescape(&text[..close]), &values)
+            },
+            _ => {
+                let unquoted = raw.find(" #").map_or(raw, |i| &raw[..i]).trim_end();
+                expand(unquoted, &values)
+            },
+        };
+        values.insert(key.to_string(), value);
+    }
+    Ok(values)
+}
+
+fn is_comment_or_empty(s: &str) -> bool {
+    let s = s.trim();
+    s.is_empty() || s.starts_with('#')
+}
+
+// Finds the first unescaped `"`
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' if !escaped => return Some(i),
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+// Resolves the escapes of a double quoted value (`\$` is kept for `expand`)
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some('$') => out.push_str("\\$"),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
+
+// Expands `$VAR`, `${VAR}` and `${VAR:-default}` using the values parsed so far, then the environment
+fn expand(text: &str, values: &BTreeMap<String, String>) -> String {
+    let lookup = |name: &str| values.get(name).cloned().or_else(|| env::var(name).ok());
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(['$', '\\']) {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        if rest[i..].starts_with("\\$") {
+            out.push('$');
+            rest = &rest[i + 2..];
+        } else if rest[i..].starts_with('\\') {
+            out.push('\\');
+            rest = after;
+        } else if let Some(braced) = after.strip_prefix('{').and_then(|b| b.split_once('}')) {
+            let (inner, tail) = braced;
+            out.push_str(&match inner.split_once(":-") {
+                Some((name, default)) => lookup(name).filter(|v| !v.is_empty()).unwrap_or_else(|| default.to_string()),
+                None => lookup(inner).unwrap_or_default(),
+            });
+            rest = tail;
+        } else {
+            let len = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+            match len {
+                0 => out.push('$'),
+                _ => out.push_str(&lookup(&after[..len]).unwrap_or_default()),
+            }
+            rest = &after[len..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Reads and parses a `.env` file without touching the environment (see [parse_dotenv]).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::env::dotenv_values;
+/// use dev_utils::file::{create, TempDir};
+///
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join(".env"), "PORT=8080 # http").unwrap();
+/// assert_eq!(dotenv_values(dir.join(".env")).unwrap()["PORT"], "8080");
+/// ```
+pub fn dotenv_values<P: AsRef<Path>>(path: P) -> Result<BTreeMap<String, String>, DotenvError> {
+    parse_dotenv(&file::read(path)?)
+}
+
+/// Loads a `.env` file into the process environment.
+///
+/// Variables that are already set are left untouched, so the real environment
+/// always takes precedence over the file.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the names of the variables that were set, or a [DotenvError].
+pub fn load_dotenv<P: AsRef<Path>>(path: P) -> Result<Vec<String>, DotenvError> {
+    let mut set = Vec::new();
+    for (key, value) in dotenv_values(path)? {
+        if env::var_os(&key).is_none() {
+            env::set_var(&key, value);
+            set.push(key);
+        }
+    }
+    Ok(set)
+}
+
+/// Loads a `.env` file into the process environment, overriding variables that are already set.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the names of the variables that were set, or a [DotenvError].
+pub fn load_dotenv_override<P: AsRef<Path>>(path: P) -> Result<Vec<String>, DotenvError> {
+    let values = dotenv_values(path)?;
+    values.iter().for_each(|(key, value)| env::set_var(key, value));
+    Ok(values.into_keys().collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::with_env_vars;
+
+    #[test]
+    fn test_parse_values() {
+        let values = parse_dotenv(r#"
+            A=plain value # comment
+            B = "line1\nline2 \"quoted\""
+            C='literal ${A} \n'
+            D="multi
+line"
+            URL=http://host/#anchor
+            E=
+        "#).unwrap();
+        assert_eq!(values["A"], "plain value");
+        assert_eq!(values["B"], "line1\nline2 \"quoted\"");
+        assert_eq!(values["C"], "literal ${A} \\n");
+        assert_eq!(values["D"], "multi\nline");
+        assert_eq!(values["URL"], "http://host/#anchor");
+        assert_eq!(values["E"], "");
+    }
+
+    #[test]
+    fn test_expansion() {
+        let values = with_env_vars(&[("DEVUTILS_DOTENV_TEST_HOME", "/home/test")], || parse_dotenv("
+            DIR=$DEVUTILS_DOTENV_TEST_HOME/app
+            LOG=${DIR}/log
+            LEVEL=${DEVUTILS_DOTENV_MISSING:-info}
+            PRICE=\"\\$5 and $ alone\"
+        ")).unwrap();
+        assert_eq!(values["DIR"], "/home/test/app");
+        assert_eq!(values["LOG"], "/home/test/app/log");
+        assert_eq!(values["LEVEL"], "info");
+        assert_eq!(values["PRICE"], "$5 and $ alone");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse_dotenv("A=1\nno equals"), Err(DotenvError::Parse { line: 2, .. })));
+        assert!(parse_dotenv("A=\"open").is_err());
+        assert!(parse_dotenv("BAD KEY=1").is_err());
+        assert!(parse_dotenv("A='x' trailing").is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = file::TempDir::new().unwrap();
+        file::create(dir.join(".env"), "DEVUTILS_DOTENV_NEW=1\nDEVUTILS_DOTENV_SET=file").unwrap();
+        with_env_vars(&[("DEVUTILS_DOTENV_SET", "env")], || {
+            assert_eq!(load_dotenv(dir.join(".env")).unwrap(), vec!["DEVUTILS_DOTENV_NEW".to_string()]);
+            assert_eq!(env::var("DEVUTILS_DOTENV_NEW").unwrap(), "1");
+            assert_eq!(env::var("DEVUTILS_DOTENV_SET").unwrap(), "env");
+
+            load_dotenv_override(dir.join(".env")).unwrap();
+            assert_eq!(env::var("DEVUTILS_DOTENV_SET").unwrap(), "file");
+        });
+        assert!(env::var("DEVUTILS_DOTENV_SET").is_err());
+    }
+}
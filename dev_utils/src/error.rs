@@ -0,0 +1,283 @@
+//! Error reports with context, for applications and scripts.
+//!
+//! A [Report] wraps any error of the crate (or any `std::error::Error`), and collects
+//! context messages as it goes up the call stack ([Context::context] on results and
+//! options, [Report::context] on reports). It is displayed as the chain of messages, from
+//! the outermost context to the root cause:
+//!
+//! ```text
+//! while starting the app
+//!  ├─ while reading config.toml
+//!  └─ IO error: No such file or directory (os error 2)
+//! ```
+//!
+//! The alternate form (`{:#}`) puts the chain on a single line, separated by `: `, for logs.
+//! Colors follow the global color mode of [crate::format::term].
+//! The errors of the crate also have an [ErrorCode], found in a report with [Report::code].
+//!
+//! # Examples
+//! ```
+//! use dev_utils::error::{Context, Report};
+//! use dev_utils::file::{self, FileError};
+//!
+//! fn read_config(path: &str) -> Result<String, Report> {
+//!     let text = file::read(path).with_context(|| format!("while reading {}", path))?;
+//!     Ok(text)
+//! }
+//!
+//! let report = read_config("no_such_config.toml").context("while starting the app").unwrap_err();
+//! assert!(format!("{:#}", report).starts_with("while starting the app: while reading no_such_config.toml: IO error"));
+//! assert!(report.downcast_ref::<FileError>().is_some());
+//! ```
+use std::error::Error;
+use std::fmt;
+use crate::base_change::BaseConversionError;
+#[cfg(feature = "datetime")]
+use crate::datetime::DateTimeError;
+#[cfg(feature = "file")]
+use crate::file::FileError;
+#[cfg(feature = "format")]
+use crate::format::{Stylize, Style, RED};
+
+/// A [std::result::Result] whose error defaults to [Report].
+pub type Result<T, E = Report> = std::result::Result<T, E>;
+
+/// A stable identifier of an error kind, like `"file.io"` or `"datetime.invalid_day"`,
+/// for programs that match on errors or report them (exit codes, JSON output, ...).
+///
+/// # Examples
+/// ```
+/// use dev_utils::base_change::convert_base;
+/// use dev_utils::error::ErrorCode;
+///
+/// assert_eq!(convert_base("12", 2, 10).unwrap_err().code(), "base.invalid_digit");
+/// ```
+pub trait ErrorCode {
+    /// Returns the code of the error, as `<module>.<kind>`.
+    fn code(&self) -> &'static str;
+}
+
+// The code of an error, if it is one of the crate's errors with one
+fn error_code(error: &(dyn Error + 'static)) -> Option<&'static str> {
+    #[cfg(feature = "file")]
+    if let Some(error) = error.downcast_ref::<FileError>() {return Some(error.code());}
+    #[cfg(feature = "datetime")]
+    if let Some(error) = error.downcast_ref::<DateTimeError>() {return Some(error.code());}
+    error.downcast_ref::<BaseConversionError>().map(ErrorCode::code)
+}
+
+// The first message of a report, and the tree glyphs (plain without the `format` feature)
+#[cfg(feature = "format")]
+fn headline(message: &str) -> String {message.color(RED).style(Style::Bold)}
+#[cfg(feature = "format")]
+fn dim(glyph: &str) -> String {glyph.style(Style::Dim)}
+#[cfg(not(feature = "format"))]
+fn headline(message: &str) -> String {message.to_string()}
+#[cfg(not(feature = "format"))]
+fn dim(glyph: &str) -> String {glyph.to_string()}
+
+/// An error with the context it happened in.
+///
+/// Any error type implementing `std::error::Error` (and `Send + Sync`) converts into a
+/// report, so `?` works on the results of every module of the crate. A report doesn't
+/// implement `std::error::Error` itself (that would conflict with this conversion), but it
+/// converts into a `Box<dyn Error + Send + Sync>`.
+///
+/// # Examples
+/// ```
+/// use dev_utils::error::Report;
+/// use dev_utils::datetime::Date;
+///
+/// let report = Report::from(Date::new(2024, 2, 30).unwrap_err()).context("invalid due date");
+/// assert_eq!(report.to_string().lines().count(), 2);
+/// assert_eq!(format!("{:#}", report), "invalid due date: Invalid day: 30");
+/// assert_eq!(report.chain().count(), 2);
+/// assert_eq!(report.root_cause().to_string(), "Invalid day: 30");
+/// ```
+pub struct Report {
+    inner: Box<dyn Error + Send + Sync + 'static>,
+}
+
+// A context message, over the error it explains
+struct ContextError {
+    message: String,
+    source: Box<dyn Error + Send + Sync + 'static>,
+}
+
+impl fmt::Debug for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context").field("message", &self.message).field("source", &self.source).finish()
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {write!(f, "{}", self.message)}
+}
+
+impl Error for ContextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {Some(&*self.source)}
+}
+
+// The error of a report created from a message alone
+#[derive(Debug)]
+struct MessageError(String);
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {write!(f, "{}", self.0)}
+}
+
+impl Error for MessageError {}
+
+impl Report {
+    /// Creates a report of an error.
+    pub fn new<E: Error + Send + Sync + 'static>(error: E) -> Self {
+        Report { inner: Box::new(error) }
+    }
+
+    /// Creates a report from a message, without an underlying error.
+    pub fn msg<M: fmt::Display>(message: M) -> Self {
+        Report::new(MessageError(message.to_string()))
+    }
+
+    /// Adds a context message, shown before the current ones.
+    pub fn context<C: fmt::Display>(self, context: C) -> Self {
+        Report::new(ContextError { message: context.to_string(), source: self.inner })
+    }
+
+    /// Returns the errors of the report, from the outermost context to the root cause
+    /// (following [Error::source]).
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn Error + 'static)> {
+        let mut next = Some(&*self.inner as &(dyn Error + 'static));
+        std::iter::from_fn(move || {
+            let current = next?;
+            next = current.source();
+            Some(current)
+        })
+    }
+
+    /// Returns the innermost error: the one that started it all.
+    pub fn root_cause(&self) -> &(dyn Error + 'static) {
+        self.chain().last().expect("a report has at least one error")
+    }
+
+    /// Returns the [ErrorCode] of the first error of the chain that has one, if any.
+    pub fn code(&self) -> Option<&'static str> {self.chain().find_map(error_code)}
+
+    /// Returns the first error of the chain that is an `E`, if any.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.chain().find_map(|error| error.downcast_ref::<E>())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.chain().map(|error| error.to_string()).collect();
+        if f.alternate() {return write!(f, "{}", messages.join(": "));}
+
+        write!(f, "{}", headline(&messages[0]))?;
+        for (i, message) in messages.iter().enumerate().skip(1) {
+            let (branch, indent) = match i == messages.len() - 1 {
+                true => ("└─", "  "),
+                false => ("├─", "│ "),
+            };
+            let mut lines = message.lines();
+            write!(f, "\n {} {}", dim(branch), lines.next().unwrap_or(""))?;
+            for line in lines {write!(f, "\n {} {}", dim(indent), line)?;}
+        }
+        Ok(())
+    }
+}
+
+// Same as Display, so that `fn main() -> Result<(), Report>` prints the whole chain
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {fmt::Display::fmt(self, f)}
+}
+
+impl<E: Error + Send + Sync + 'static> From<E> for Report {
+    fn from(error: E) -> Self {Report::new(error)}
+}
+
+impl From<Report> for Box<dyn Error + Send + Sync + 'static> {
+    fn from(report: Report) -> Self {report.inner}
+}
+
+impl From<Report> for Box<dyn Error + 'static> {
+    fn from(report: Report) -> Self {report.inner}
+}
+
+/// Adds context to the error of a `Result`, or to the `None` of an `Option`,
+/// turning it into a [Report].
+pub trait Context<T> {
+    /// Wraps the error in a [Report] with a context message.
+    fn context<C: fmt::Display>(self, context: C) -> Result<T>;
+
+    /// Like [Context::context], but only builds the message if there is an error.
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, context: F) -> Result<T>;
+}
+
+impl<T, E: Into<Report>> Context<T> for std::result::Result<T, E> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T> {
+        self.map_err(|error| error.into().context(context))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, context: F) -> Result<T> {
+        self.map_err(|error| error.into().context(context()))
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T> {
+        self.ok_or_else(|| Report::msg(context))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, context: F) -> Result<T> {
+        self.ok_or_else(|| Report::msg(context()))
+    }
+}
+
+
+#[cfg(all(test, feature = "file"))]
+mod tests {
+    use super::*;
+    use crate::file::{self, FileError};
+    use crate::format::strip_ansi_codes;
+
+    fn read_settings() -> Result<String> {
+        let text = file::read("no/such/settings.toml").context("while reading settings.toml")?;
+        Ok(text)
+    }
+
+    #[test]
+    fn test_chain() {
+        let report = read_settings().context("while starting").unwrap_err();
+        let messages: Vec<String> = report.chain().map(|e| e.to_string()).collect();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[..2], ["while starting", "while reading settings.toml"]);
+        assert!(matches!(report.downcast_ref::<FileError>(), Some(FileError::Io(_))));
+        assert_eq!(report.code(), Some("file.io"));
+        assert_eq!(report.root_cause().to_string(), messages[2]);
+
+        let display = strip_ansi_codes(&report.to_string());
+        assert_eq!(display, format!("while starting\n ├─ while reading settings.toml\n └─ {}", messages[2]));
+        assert_eq!(format!("{:#}", report), messages.join(": "));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let multiline = Report::msg("first\nsecond").context("outer");
+        assert_eq!(strip_ansi_codes(&format!("{:?}", multiline)), "outer\n └─ first\n    second");
+
+        let missing: Option<u8> = None;
+        assert_eq!(format!("{:#}", missing.with_context(|| format!("no {}", "port")).unwrap_err()), "no port");
+        assert_eq!(Some(1).context("unused").unwrap(), 1);
+
+        let base = crate::base_change::convert_base("12", 2, 10).context("converting").unwrap_err();
+        assert_eq!(format!("{:#}", base), "converting: Invalid digit for the source base");
+        assert_eq!(base.code(), Some("base.invalid_digit"));
+        assert_eq!(Report::msg("no code").context("outer").code(), None);
+
+        let boxed: Box<dyn Error + Send + Sync> = Report::msg("boxed").context("outer").into();
+        assert_eq!(boxed.to_string(), "outer");
+        assert_eq!(boxed.source().unwrap().to_string(), "boxed");
+    }
+}
@@ -0,0 +1,431 @@
+//! A small expression evaluator, for interactive consoles and computed config values.
+//!
+//! Expressions support `+ - * / %`, `^` (power), parentheses, variables, the constants `pi`
+//! and `e`, and the functions `sqrt`, `abs`, `ln`, `log`, `exp`, `sin`, `cos`, `tan`, `floor`,
+//! `ceil`, `round`, `min`, `max` and `pow`.
+//!
+//! Number literals can be written in other bases: `0x1F`, `0o17` and `0b101`, or `<base>#<digits>`
+//! for any base from 2 to 62 (`36#Z`, `62#Hi`). These literals are converted with
+//! [convert_base](crate::base_change::convert_base); an [Evaluator] can also read every plain
+//! literal in another base.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::eval::{evaluate, Evaluator};
+//!
+//! assert_eq!(evaluate("2 * (3 + 4) / 0.5").unwrap(), 28.0);
+//! assert_eq!(evaluate("2 ^ 3 ^ 2").unwrap(), 512.0);
+//! assert_eq!(evaluate("0xFF + 0b1").unwrap(), 256.0);
+//!
+//! let mut calc = Evaluator::new().with_variable("width", 80.0);
+//! assert_eq!(calc.evaluate("width / 4 - 2").unwrap(), 18.0);
+//! calc.execute("half = width / 2").unwrap();
+//! assert_eq!(calc.evaluate("max(half, 30)").unwrap(), 40.0);
+//! ```
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use crate::base_change::convert_base;
+
+/// Represents errors that can occur when evaluating an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    /// The expression is malformed at the given character position.
+    Syntax { position: usize, message: String },
+    /// A number literal is not valid in its base.
+    InvalidLiteral(String),
+    /// A variable is not defined.
+    UnknownVariable(String),
+    /// A function is not defined, or called with the wrong number of arguments.
+    UnknownFunction(String),
+    /// A division or remainder by zero.
+    DivisionByZero,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Syntax { position, message } => write!(f, "syntax error at {}: {}", position, message),
+            EvalError::InvalidLiteral(s) => write!(f, "invalid number literal: {}", s),
+            EvalError::UnknownVariable(s) => write!(f, "unknown variable: {}", s),
+            EvalError::UnknownFunction(s) => write!(f, "unknown function: {}", s),
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl Error for EvalError {}
+
+type Result<T> = std::result::Result<T, EvalError>;
+
+/// Evaluates an expression with the default [Evaluator] (no variables besides `pi` and `e`).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::eval::{evaluate, EvalError};
+///
+/// assert_eq!(evaluate("-2 ^ 2 + 10 % 3").unwrap(), -3.0);
+/// assert_eq!(evaluate("sqrt(16) * 2#101").unwrap(), 20.0);
+/// assert_eq!(evaluate("1 / 0"), Err(EvalError::DivisionByZero));
+/// assert!(matches!(evaluate("(1 + 2"), Err(EvalError::Syntax { .. })));
+/// ```
+pub fn evaluate(expr: &str) -> Result<f64> {Evaluator::new().evaluate(expr)}
+
+/// An expression evaluator holding variables and the base of plain number literals.
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    variables: HashMap<String, f64>,
+    base: u32,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {Self::new()}
+}
+
+impl Evaluator {
+    /// Creates an evaluator with the constants `pi` and `e`, reading literals in base 10.
+    pub fn new() -> Self {
+        let variables = HashMap::from([("pi".to_string(), std::f64::consts::PI), ("e".to_string(), std::f64::consts::E)]);
+        Evaluator { variables, base: 10 }
+    }
+
+    /// Adds a variable.
+    pub fn with_variable(mut self, name: &str, value: f64) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Reads plain literals (those without a `0x` / `<base>#` prefix) in `base`.
+    ///
+    /// Literals must start with a digit, so in base 16 `0FF` is a number and `FF` a variable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the base is not between 2 and 62.
+    pub fn with_base(mut self, base: u32) -> Self {
+        assert!((2..=62).contains(&base), "base must be between 2 and 62, got {}", base);
+        self.base = base;
+        self
+    }
+
+    /// Sets a variable.
+    pub fn set(&mut self, name: &str, value: f64) {self.variables.insert(name.to_string(), value);}
+
+    /// Returns the value of a variable.
+    pub fn get(&self, name: &str) -> Option<f64> {self.variables.get(name).copied()}
+
+    /// Evaluates an expression.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value, or an [EvalError].
+    pub fn evaluate(&self, expr: &str) -> Result<f64> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { evaluator: self, tokens: &tokens, pos: 0, len: expr.chars().count(), depth: 0 };
+        let value = parser.expression()?;
+        match parser.peek() {
+            None => Ok(value),
+            Some((token, position)) => Err(syntax(*position, format!("unexpected {}", token))),
+        }
+    }
+
+    /// Evaluates a line that is either an expression or an assignment (`name = expression`),
+    /// as typed in an interactive console. Assigned values are stored as variables.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the value of the expression, or an [EvalError].
+    pub fn execute(&mut self, line: &str) -> Result<f64> {
+        if let Some((name, expr)) = line.split_once('=') {
+            let name = name.trim();
+            if is_identifier(name) && !expr.starts_with('=') {
+                let value = self.evaluate(expr)?;
+                self.set(name, value);
+                return Ok(value);
+            }
+        }
+        self.evaluate(line)
+    }
+
+    // Reads a number literal in the evaluator's base, or in the base of its prefix
+    fn literal(&self, text: &str) -> Result<f64> {
+        let invalid = || EvalError::InvalidLiteral(text.to_string());
+        let (base, digits) = match text.split_once('#') {
+            Some((base, digits)) => (base.parse::<u32>().ok().filter(|b| (2..=62).contains(b)).ok_or_else(invalid)?, digits),
+            None if self.base == 10 => match text.get(..2) {
+                Some("0x" | "0X") => (16, &text[2..]),
+                Some("0o" | "0O") => (8, &text[2..]),
+                Some("0b" | "0B") => (2, &text[2..]),
+                _ => return text.parse::<f64>().map_err(|_| invalid()),
+            },
+            None => (self.base, text),
+        };
+        // bases up to 36 are case-insensitive, as for Rust's `from_str_radix`
+        let digits = if base <= 36 {digits.to_uppercase()} else {digits.to_string()};
+        let (int, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+        if int.is_empty() && fraction.is_empty() {return Err(invalid());}
+        // the fraction is read as part of the integer, then scaled back down
+        let decimal = convert_base(&format!("{}{}", int, fraction), base, 10).map_err(|_| invalid())?;
+        let value = decimal.parse::<f64>().map_err(|_| invalid())?;
+        Ok(value / (base as f64).powi(fraction.len() as i32))
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_alphabetic() || c == '_') && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn syntax(position: usize, message: impl Into<String>) -> EvalError {
+    EvalError::Syntax { position, message: message.into() }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Ident(String),
+    Op(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(s) | Token::Ident(s) => write!(f, "'{}'", s),
+            Token::Op(c) => write!(f, "'{}'", c),
+        }
+    }
+}
+
+// Splits an expression into tokens, with their character positions
+fn tokenize(expr: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => {i += 1; continue;},
+            '0'..='9' | '.' => {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || chars[i] == '#') {
+                    // the sign of a decimal exponent (`1e-3`)
+                    let exponent = matches!(chars[i], 'e' | 'E') && chars[start..i].iter().all(|c| c.is_ascii_digit() || *c == '.');
+                    i += 1;
+                    if exponent && matches!(chars.get(i), Some('+' | '-')) && chars.get(i + 1).is_some_and(char::is_ascii_digit) {i += 1;}
+                }
+                tokens.push((Token::Number(chars[start..i].iter().collect()), start));
+            },
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {i += 1;}
+                tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+            },
+            '+' | '-' | '*' | '/' | '%' | '^' | '(' | ')' | ',' => {
+                tokens.push((Token::Op(c), start));
+                i += 1;
+            },
+            c => return Err(syntax(start, format!("unexpected character '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+// A recursive descent parser evaluating the expression as it goes:
+//   expression = term (('+' | '-') term)*
+//   term       = unary (('*' | '/' | '%') unary)*
+//   unary      = '-' unary | '+' unary | power
+//   power      = atom ('^' unary)?
+//   atom       = number | ident | ident '(' args ')' | '(' expression ')'
+// Every nesting (parentheses, call arguments, signs, exponents) goes through `unary`, which
+// counts the depth so that a hostile input can't overflow the stack.
+struct Parser<'a> {
+    evaluator: &'a Evaluator,
+    tokens: &'a [(Token, usize)],
+    pos: usize,
+    len: usize,
+    depth: usize,
+}
+
+// How deeply an expression may nest
+const MAX_DEPTH: usize = 256;
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&(Token, usize)> {self.tokens.get(self.pos)}
+
+    fn eat(&mut self, op: char) -> bool {
+        let found = matches!(self.peek(), Some((Token::Op(c), _)) if *c == op);
+        if found {self.pos += 1;}
+        found
+    }
+
+    fn expect(&mut self, op: char) -> Result<()> {
+        match self.eat(op) {
+            true => Ok(()),
+            false => Err(self.unexpected(&format!("'{}'", op))),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> EvalError {
+        match self.peek() {
+            Some((token, position)) => syntax(*position, format!("expected {}, found {}", expected, token)),
+            None => syntax(self.len, format!("expected {}, found end of input", expected)),
+        }
+    }
+
+    fn expression(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {value += self.term()?;}
+            else if self.eat('-') {value -= self.term()?;}
+            else {return Ok(value);}
+        }
+    }
+
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {value *= self.unary()?;}
+            else if self.eat('/') || self.eat('%') {
+                let is_div = matches!(self.tokens[self.pos - 1].0, Token::Op('/'));
+                let rhs = self.unary()?;
+                if rhs == 0.0 {return Err(EvalError::DivisionByZero);}
+                value = if is_div {value / rhs} else {value % rhs};
+            }
+            else {return Ok(value);}
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64> {
+        if self.depth == MAX_DEPTH {
+            let position = self.peek().map_or(self.len, |(_, position)| *position);
+            return Err(syntax(position, format!("expression nested deeper than {} levels", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let value = if self.eat('-') {self.unary().map(|value| -value)}
+            else if self.eat('+') {self.unary()}
+            else {self.power()};
+        self.depth -= 1;
+        value
+    }
+
+    fn power(&mut self) -> Result<f64> {
+        let base = self.atom()?;
+        match self.eat('^') {
+            true => Ok(base.powf(self.unary()?)),  // right-associative, and `2^-1` is allowed
+            false => Ok(base),
+        }
+    }
+
+    fn atom(&mut self) -> Result<f64> {
+        let Some((token, _)) = self.peek().cloned() else {return Err(self.unexpected("a value"));};
+        match token {
+            Token::Number(text) => {
+                self.pos += 1;
+                self.evaluator.literal(&text)
+            },
+            Token::Ident(name) => {
+                self.pos += 1;
+                if !self.eat('(') {
+                    return self.evaluator.get(&name).ok_or(EvalError::UnknownVariable(name));
+                }
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.expression()?);
+                        if self.eat(')') {break;}
+                        self.expect(',')?;
+                    }
+                }
+                call(&name, &args)
+            },
+            Token::Op('(') => {
+                self.pos += 1;
+                let value = self.expression()?;
+                self.expect(')')?;
+                Ok(value)
+            },
+            Token::Op(_) => Err(self.unexpected("a value")),
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64> {
+    let unary: Option<fn(f64) -> f64> = match name {
+        "sqrt" => Some(f64::sqrt),
+        "abs" => Some(f64::abs),
+        "ln" => Some(f64::ln),
+        "log" => Some(f64::log10),
+        "exp" => Some(f64::exp),
+        "sin" => Some(f64::sin),
+        "cos" => Some(f64::cos),
+        "tan" => Some(f64::tan),
+        "floor" => Some(f64::floor),
+        "ceil" => Some(f64::ceil),
+        "round" => Some(f64::round),
+        _ => None,
+    };
+    match (name, unary, args) {
+        (_, Some(f), [x]) => Ok(f(*x)),
+        ("pow", _, [x, y]) => Ok(x.powf(*y)),
+        ("min", _, [_, ..]) => Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+        ("max", _, [_, ..]) => Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        _ => Err(EvalError::UnknownFunction(format!("{}/{}", name, args.len()))),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence() {
+        let cases = [
+            ("1 + 2 * 3", 7.0), ("(1 + 2) * 3", 9.0), ("10 - 4 - 3", 3.0), ("2 * 3 ^ 2", 18.0),
+            ("-2 ^ 2", -4.0), ("2 ^ -1", 0.5), ("--3", 3.0), ("7 % 4 * 2", 6.0), ("1.5e3 + 2E-1", 1500.2),
+            (".5 + 1", 1.5), ("min(3, 1, 2) + pow(2, 10)", 1025.0), ("floor(e * 10)", 27.0),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(evaluate(expr).unwrap(), expected, "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_bases() {
+        assert_eq!(evaluate("0x1f").unwrap(), 31.0);
+        assert_eq!(evaluate("0o17 + 0B11").unwrap(), 18.0);
+        assert_eq!(evaluate("36#z").unwrap(), 35.0);
+        assert_eq!(evaluate("62#z").unwrap(), 61.0);
+        assert_eq!(evaluate("2#0.1").unwrap(), 0.5);
+        assert_eq!(Evaluator::new().with_base(16).evaluate("0FF + 10").unwrap(), 271.0);
+        assert_eq!(Evaluator::new().with_base(2).evaluate("101 * 10#3").unwrap(), 15.0);
+        assert_eq!(evaluate("0x"), Err(EvalError::InvalidLiteral("0x".to_string())));
+        assert_eq!(evaluate("2#102"), Err(EvalError::InvalidLiteral("2#102".to_string())));
+        assert_eq!(evaluate("99#1"), Err(EvalError::InvalidLiteral("99#1".to_string())));
+        assert_eq!(evaluate("12abc"), Err(EvalError::InvalidLiteral("12abc".to_string())));
+    }
+
+    #[test]
+    fn test_variables_and_errors() {
+        let mut calc = Evaluator::new();
+        assert_eq!(calc.execute("x = 2 + 3").unwrap(), 5.0);
+        assert_eq!(calc.execute("x_2 = x * x").unwrap(), 25.0);
+        assert_eq!(calc.get("x_2"), Some(25.0));
+        assert_eq!(calc.evaluate("y"), Err(EvalError::UnknownVariable("y".to_string())));
+        assert_eq!(calc.evaluate("nope(1)"), Err(EvalError::UnknownFunction("nope/1".to_string())));
+        assert_eq!(calc.evaluate("sqrt(1, 2)"), Err(EvalError::UnknownFunction("sqrt/2".to_string())));
+        assert_eq!(calc.evaluate("5 % 0"), Err(EvalError::DivisionByZero));
+        assert_eq!(calc.evaluate("1 +"), Err(syntax(3, "expected a value, found end of input")));
+        assert_eq!(calc.evaluate("2 3"), Err(syntax(2, "unexpected '3'")));
+        assert_eq!(calc.evaluate("1 $ 2"), Err(syntax(2, "unexpected character '$'")));
+        assert_eq!(calc.evaluate("max(1,"), Err(syntax(6, "expected a value, found end of input")));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        assert_eq!(evaluate(&format!("{}1{}", "(".repeat(200), ")".repeat(200))).unwrap(), 1.0);
+        assert_eq!(evaluate(&format!("{}1", "-".repeat(200))).unwrap(), 1.0);
+        for deep in [format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000)), format!("{}1", "-".repeat(100_000)), "2^".repeat(100_000) + "1"] {
+            assert!(matches!(evaluate(&deep), Err(EvalError::Syntax { ref message, .. }) if message.contains("nested deeper")));
+        }
+    }
+}
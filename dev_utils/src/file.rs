@@ -5,8 +5,22 @@
 //!
 //! # Features
 //! - CRUD operations on files
+//! - Binary-safe reads and writes, and chunked reading of large files ([read_bytes], [read_chunks])
+//! - Atomic writes and `.bak` backups ([write_atomic], [backup])
 //! - Listing directory contents
 //! - Copying, moving, and renaming files
+//! - Recursive delete, move and merge of directories with dry runs and progress ([merge_dirs])
+//! - Batch renaming with wildcard captures, templates, dry-run plans and undo
+//! - Line count and code statistics per extension ([loc])
+//! - Checksums, streaming comparison and duplicate detection ([hash], [compare], [dedupe]; `crypto` feature)
+//! - Metadata with dates and permissions ([info], [list_info])
+//! - Temporary files and directories removed on drop ([TempDir], [TempFile])
+//! - Glob patterns (`*`, `?`, `**`, `{a,b}`) to match and find paths ([glob], [GlobSet])
+//! - Colored directory trees with depth limits, ignore patterns and sizes ([tree])
+//! - Following growing files with truncation and rotation detection ([watch], [watch_until], [tail])
+//! - Dependency-free TOML and YAML parsers with typed value models ([toml], [yaml])
+//! - Parsing and serialization of classic INI files ([ini])
+//! - Zip archives: packing directories, listing and safe extraction ([zip]; `codex` feature)
 //! - Error handling with custom error types
 //! - All operations use only the Rust standard library
 //! 
@@ -37,6 +51,7 @@ use std::path::{Path, PathBuf};
 use std::fs::{self, File, OpenOptions, DirEntry};
 use std::io::{self, Read, Write, Error};
 use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Custom error type for file operations.
 #[derive(Debug)]
@@ -58,6 +73,15 @@ impl fmt::Display for FileError {
 
 impl std::error::Error for FileError {}
 
+impl crate::error::ErrorCode for FileError {
+    fn code(&self) -> &'static str {
+        match self {
+            FileError::Io(_) => "file.io",
+            FileError::PathError(_) => "file.path",
+        }
+    }
+}
+
 impl From<io::Error> for FileError {
     fn from(err: io::Error) -> Self {FileError::Io(err)}
 }
@@ -65,6 +89,33 @@ impl From<io::Error> for FileError {
 /// Custom Result type for file operations.
 type Result<T> = std::result::Result<T, FileError>;
 
+mod rename;
+mod loc;
+mod watch;
+mod glob;
+mod tree;
+#[cfg(feature = "crypto")]
+mod compare;
+mod temp;
+mod info;
+mod dirs;
+pub mod ini;
+pub mod toml;
+pub mod yaml;
+#[cfg(feature = "codex")]
+pub mod zip;
+pub use rename::{batch_rename, plan_rename, undo_rename, RenamePlan, RenameConflict, RENAME_MANIFEST};
+pub use loc::{loc, LocReport, LocStats};
+#[cfg(feature = "crypto")]
+pub use compare::{compare, dedupe, hash};
+pub use dirs::{merge_dirs, recursive_delete, recursive_move, ConflictStrategy, DirOperation, DirOptions};
+pub use info::{info, list_info, FileInfo};
+pub use glob::{glob, Glob, GlobSet};
+pub(crate) use glob::expand_braces;
+pub use temp::{TempDir, TempFile};
+pub use tree::{tree, TreeOptions};
+pub use watch::{tail, watch, watch_until, Watcher, WatchEvent, WATCH_INTERVAL};
+
 /// Creates a new file with the given content.
 ///
 /// If the file already exists, it will be overwritten.
@@ -81,9 +132,10 @@ type Result<T> = std::result::Result<T, FileError>;
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::create;
+/// use dev_utils::file::{create, TempDir};
 ///
-/// let file_path = create("example.txt", "Hello, World!").unwrap();
+/// let dir = TempDir::new().unwrap();
+/// let file_path = create(dir.join("example.txt"), "Hello, World!").unwrap();
 /// assert!(file_path.exists());
 /// ```
 pub fn create<P: AsRef<Path>>(path: P, content: &str) -> Result<PathBuf> {
@@ -109,9 +161,10 @@ pub fn create<P: AsRef<Path>>(path: P, content: &str) -> Result<PathBuf> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, read};
+/// use dev_utils::file::{create, read, TempDir};
 ///
-/// let file_path = create("example.txt", "Hello, World!").unwrap();
+/// let dir = TempDir::new().unwrap();
+/// let file_path = create(dir.join("example.txt"), "Hello, World!").unwrap();
 /// let content = read(&file_path).unwrap();
 /// assert_eq!(content, "Hello, World!");
 /// ```
@@ -138,9 +191,10 @@ pub fn read<P: AsRef<Path>>(path: P) -> Result<String> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, update, read};
+/// use dev_utils::file::{create, update, read, TempDir};
 ///
-/// let file_path = create("example.txt", "Hello").unwrap();
+/// let dir = TempDir::new().unwrap();
+/// let file_path = create(dir.join("example.txt"), "Hello").unwrap();
 /// update(&file_path, "Updated content").unwrap();
 /// assert_eq!(read(&file_path).unwrap(), "Updated content");
 /// ```
@@ -154,6 +208,91 @@ pub fn update<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
     Ok(())
 }
 
+/// Replaces the contents of a file atomically.
+///
+/// The content is written to a temporary file in the same directory, flushed to disk,
+/// and then renamed over the target. Readers (and a crash in the middle of the write)
+/// see either the old or the new content, never a truncated file. The permissions of an
+/// existing target are preserved.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to write.
+/// * `content` - The new content of the file.
+///
+/// # Returns
+///
+/// Returns a `Result` containing `()` if successful, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{write_atomic, read, delete};
+///
+/// write_atomic("settings.json", "{\"theme\": \"dark\"}").unwrap();
+/// assert_eq!(read("settings.json").unwrap(), "{\"theme\": \"dark\"}");
+/// delete("settings.json").unwrap();
+/// ```
+pub fn write_atomic<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let path = path.as_ref();
+    let name = path.file_name().ok_or_else(|| FileError::PathError(format!("Not a file path: {}", path.display())))?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let tmp = dir.join(format!(".{}.tmp{}_{}",
+        name.to_string_lossy(), std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let write = || -> io::Result<()> {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        if let Ok(metadata) = fs::metadata(path) {file.set_permissions(metadata.permissions())?;}
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    };
+    if let Err(err) = write() {
+        let _ = fs::remove_file(&tmp);
+        return Err(err.into());
+    }
+    // persist the rename itself (directories can't be opened for syncing on every platform)
+    if let Ok(dir) = File::open(dir) {let _ = dir.sync_all();}
+    Ok(())
+}
+
+/// Creates a `.bak` copy of a file next to it (e.g. `config.toml.bak`).
+///
+/// An existing backup is overwritten.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to back up.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the path of the backup, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, backup, read, delete};
+///
+/// let path = create("backup_example.txt", "v1").unwrap();
+/// let bak = backup(&path).unwrap();
+/// assert!(bak.ends_with("backup_example.txt.bak"));
+/// assert_eq!(read(&bak).unwrap(), "v1");
+/// delete(&path).unwrap();
+/// delete(&bak).unwrap();
+/// ```
+pub fn backup<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let name = path.file_name().ok_or_else(|| FileError::PathError(format!("Not a file path: {}", path.display())))?;
+    let backup = path.with_file_name(format!("{}.bak", name.to_string_lossy()));
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
 /// Appends content to the end of a file.
 ///
 /// If the file doesn't exist, it will be created.
@@ -170,9 +309,10 @@ pub fn update<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, append, read};
+/// use dev_utils::file::{create, append, read, TempDir};
 ///
-/// let file_path = create("example.txt", "Hello").unwrap();
+/// let dir = TempDir::new().unwrap();
+/// let file_path = create(dir.join("example.txt"), "Hello").unwrap();
 /// append(&file_path, ", World!").unwrap();
 /// assert_eq!(read(&file_path).unwrap(), "Hello, World!");
 /// ```
@@ -185,6 +325,140 @@ pub fn append<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
     Ok(())
 }
 
+/// Reads the contents of a file as raw bytes.
+///
+/// Unlike [read], this works with any data (images, archives, ...).
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to read.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the bytes of the file, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{write_bytes, read_bytes, append_bytes, delete};
+///
+/// write_bytes("bytes_example.bin", &[0xFF, 0x00]).unwrap();
+/// append_bytes("bytes_example.bin", &[0xD8]).unwrap();
+/// assert_eq!(read_bytes("bytes_example.bin").unwrap(), vec![0xFF, 0x00, 0xD8]);
+/// delete("bytes_example.bin").unwrap();
+/// ```
+pub fn read_bytes<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    Ok(fs::read(path)?)
+}
+
+/// Writes raw bytes to a file, replacing its contents.
+///
+/// If the file doesn't exist, it will be created (as well as its parent directories).
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to write.
+/// * `content` - The bytes to write.
+///
+/// # Returns
+///
+/// Returns a `Result` containing `()` if successful, or a `FileError`.
+pub fn write_bytes<P: AsRef<Path>>(path: P, content: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {fs::create_dir_all(parent)?;}
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Appends raw bytes to the end of a file.
+///
+/// If the file doesn't exist, it will be created.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to append to.
+/// * `content` - The bytes to append.
+///
+/// # Returns
+///
+/// Returns a `Result` containing `()` if successful, or a `FileError`.
+pub fn append_bytes<P: AsRef<Path>>(path: P, content: &[u8]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)?;
+    file.write_all(content)?;
+    Ok(())
+}
+
+// Reads until `buffer` is full or the end of the input is reached, returning the bytes read
+pub(crate) fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+/// Iterator over the chunks of a file, returned by [read_chunks].
+#[derive(Debug)]
+pub struct Chunks {
+    file: File,
+    chunk_size: usize,
+    done: bool,
+}
+
+impl Iterator for Chunks {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {return None;}
+        let mut chunk = vec![0; self.chunk_size];
+        match read_full(&mut self.file, &mut chunk) {
+            Ok(0) => {self.done = true; None},
+            Ok(n) => {
+                self.done = n < self.chunk_size;
+                chunk.truncate(n);
+                Some(Ok(chunk))
+            },
+            Err(err) => {self.done = true; Some(Err(err.into()))},
+        }
+    }
+}
+
+/// Reads a file in chunks of `chunk_size` bytes, without loading it entirely.
+///
+/// Every chunk has exactly `chunk_size` bytes, except the last one.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to read.
+/// * `chunk_size` - The size of the chunks in bytes (must not be 0).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the [Chunks] iterator, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{write_bytes, read_chunks, delete};
+///
+/// write_bytes("chunks_example.bin", &[1, 2, 3, 4, 5]).unwrap();
+/// let chunks = read_chunks("chunks_example.bin", 2).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(chunks, vec![vec![1, 2], vec![3, 4], vec![5]]);
+/// delete("chunks_example.bin").unwrap();
+/// ```
+pub fn read_chunks<P: AsRef<Path>>(path: P, chunk_size: usize) -> Result<Chunks> {
+    if chunk_size == 0 {return Err(FileError::PathError("The chunk size must be greater than 0".to_string()));}
+    Ok(Chunks { file: File::open(path)?, chunk_size, done: false })
+}
+
 /// Deletes a file.
 ///
 /// # Arguments
@@ -198,12 +472,12 @@ pub fn append<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, delete};
-/// use std::path::Path;
+/// use dev_utils::file::{create, delete, TempDir};
 ///
-/// let file_path = create("example.txt", "").unwrap();
+/// let dir = TempDir::new().unwrap();
+/// let file_path = create(dir.join("example.txt"), "").unwrap();
 /// delete(&file_path).unwrap();
-/// assert!(!Path::new("example.txt").exists());
+/// assert!(!file_path.exists());
 /// ```
 pub fn delete<P: AsRef<Path>>(path: P) -> Result<()> {
     fs::remove_file(path)?;
@@ -223,12 +497,12 @@ pub fn delete<P: AsRef<Path>>(path: P) -> Result<()> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, list};
-/// use std::path::Path;
+/// use dev_utils::file::{create, list, TempDir};
 ///
-/// create("dir/file1.txt", "").unwrap();
-/// create("dir/file2.txt", "").unwrap();
-/// let contents = list("dir").unwrap();
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join("file1.txt"), "").unwrap();
+/// create(dir.join("file2.txt"), "").unwrap();
+/// let contents = list(&dir).unwrap();
 /// assert_eq!(contents.len(), 2);
 /// ```
 pub fn list<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
@@ -253,11 +527,12 @@ pub fn list<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, copy, read};
+/// use dev_utils::file::{create, copy, read, TempDir};
 ///
-/// let original = create("original.txt", "Hello").unwrap();
-/// copy(&original, "copy.txt").unwrap();
-/// assert_eq!(read("copy.txt").unwrap(), "Hello");
+/// let dir = TempDir::new().unwrap();
+/// let original = create(dir.join("original.txt"), "Hello").unwrap();
+/// copy(&original, dir.join("copy.txt")).unwrap();
+/// assert_eq!(read(dir.join("copy.txt")).unwrap(), "Hello");
 /// ```
 pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
     fs::copy(from, to)?;
@@ -278,13 +553,13 @@ pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, mv, read};
-/// use std::path::Path;
+/// use dev_utils::file::{create, mv, read, TempDir};
 ///
-/// let original = create("original.txt", "Hello").unwrap();
-/// mv(&original, "moved.txt").unwrap();
-/// assert!(!Path::new("original.txt").exists());
-/// assert_eq!(read("moved.txt").unwrap(), "Hello");
+/// let dir = TempDir::new().unwrap();
+/// let original = create(dir.join("original.txt"), "Hello").unwrap();
+/// mv(&original, dir.join("moved.txt")).unwrap();
+/// assert!(!original.exists());
+/// assert_eq!(read(dir.join("moved.txt")).unwrap(), "Hello");
 /// ```
 pub fn mv<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
     fs::rename(from, to)?;
@@ -308,13 +583,13 @@ pub fn mv<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, rename, read};
-/// use std::path::Path;
+/// use dev_utils::file::{create, rename, read, TempDir};
 ///
-/// let original = create("original.txt", "Hello").unwrap();
-/// rename(&original, "renamed.txt").unwrap();
-/// assert!(!Path::new("original.txt").exists());
-/// assert_eq!(read("renamed.txt").unwrap(), "Hello");
+/// let dir = TempDir::new().unwrap();
+/// let original = create(dir.join("original.txt"), "Hello").unwrap();
+/// rename(&original, dir.join("renamed.txt")).unwrap();
+/// assert!(!original.exists());
+/// assert_eq!(read(dir.join("renamed.txt")).unwrap(), "Hello");
 /// ```
 pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
     fs::rename(from, to)?;
@@ -336,16 +611,16 @@ pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, recursive_copy, read};
-/// use std::path::Path;
-///
-/// create("dir/file1.txt", "Hello").unwrap();
-/// create("dir/subdir/file2.txt", "World").unwrap();
-/// recursive_copy("dir", "copy_dir").unwrap();
-/// assert!(Path::new("copy_dir/file1.txt").exists());
-/// assert!(Path::new("copy_dir/subdir/file2.txt").exists());
-/// assert_eq!(read("copy_dir/file1.txt").unwrap(), "Hello");
-/// assert_eq!(read("copy_dir/subdir/file2.txt").unwrap(), "World");
+/// use dev_utils::file::{create, recursive_copy, read, TempDir};
+///
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join("src/file1.txt"), "Hello").unwrap();
+/// create(dir.join("src/subdir/file2.txt"), "World").unwrap();
+/// recursive_copy(dir.join("src"), dir.join("copy_dir")).unwrap();
+/// assert!(dir.join("copy_dir/file1.txt").exists());
+/// assert!(dir.join("copy_dir/subdir/file2.txt").exists());
+/// assert_eq!(read(dir.join("copy_dir/file1.txt")).unwrap(), "Hello");
+/// assert_eq!(read(dir.join("copy_dir/subdir/file2.txt")).unwrap(), "World");
 /// ```
 pub fn recursive_copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
     let from = from.as_ref();
@@ -392,12 +667,13 @@ pub fn recursive_copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<
 /// # Examples
 ///
 /// ```
-/// use dev_utils::file::{create, find};
+/// use dev_utils::file::{create, find, TempDir};
 ///
-/// create("dir/file1.txt", "").unwrap();
-/// create("dir/file2.dat", "").unwrap();
-/// create("dir/subdir/file3.txt", "").unwrap();
-/// let txt_files = find("dir", |entry| {
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join("file1.txt"), "").unwrap();
+/// create(dir.join("file2.dat"), "").unwrap();
+/// create(dir.join("subdir/file3.txt"), "").unwrap();
+/// let txt_files = find(&dir, |entry| {
 ///     entry.path().extension().map_or(false, |ext| ext == "txt")
 /// }).unwrap();
 /// assert_eq!(txt_files.len(), 2);
@@ -431,30 +707,45 @@ where
     Ok(())
 }
 
-// todo: Check why the test module is not working as expected...
-// todo:     The individual tests are working fine but when running the module test, it's not working
-// todo:     -> (cargo test --lib file)
+/// A step of a value path like `"a.b[2].c"`, used by the parsers of structured files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PathStep<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+// Splits a value path into keys and `[index]` steps; `None` if an index is malformed
+pub(crate) fn path_steps(path: &str) -> Option<Vec<PathStep<'_>>> {
+    let mut steps = Vec::new();
+    for segment in path.split('.') {
+        let (key, mut indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !key.is_empty() {steps.push(PathStep::Key(key));}
+        while let Some(rest) = indices.strip_prefix('[') {
+            let (index, rest) = rest.split_once(']')?;
+            steps.push(PathStep::Index(index.trim().parse().ok()?));
+            indices = rest;
+        }
+        if !indices.is_empty() {return None;}
+    }
+    Some(steps)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
 
-    const TEST_DIR: &str = "test_file_ops";
     const TEST_FILE: &str = "test_file.txt";
     const TEST_FILE_COPY: &str = "test_file_copy.txt";
     const TEST_FILE_MOVE: &str = "test_file_move.txt";
     const TEST_FILE_RENAME: &str = "test_file_rename.txt";
 
-    fn setup() {fs::create_dir(TEST_DIR);}
-    fn cleanup() {fs::remove_dir_all(TEST_DIR);}
-    fn get_test_path(filename: &str) -> PathBuf {Path::new(TEST_DIR).join(filename)}
-
     #[test]
     fn test_crud_operations() {
-        setup();
+        let dir = TempDir::new().unwrap();
 
         // Create
-        let file_path = get_test_path(TEST_FILE);
+        let file_path = dir.join(TEST_FILE);
         let content = "Hello, World!";
         let created_path = create(&file_path, content).unwrap();
         assert_eq!(created_path, file_path);
@@ -479,40 +770,36 @@ mod tests {
         // Delete
         delete(&file_path).unwrap();
         assert!(!file_path.exists());
-
-        cleanup();
     }
 
     #[test]
     fn test_list_and_find() {
-        setup();
+        let dir = TempDir::new().unwrap();
 
-        let file_path = get_test_path(TEST_FILE);
+        let file_path = dir.join(TEST_FILE);
         create(&file_path, "Content").unwrap();
-        create(&get_test_path("file2.txt"), "Content").unwrap();
-        create(&get_test_path("file3.dat"), "Content").unwrap();
+        create(dir.join("file2.txt"), "Content").unwrap();
+        create(dir.join("file3.dat"), "Content").unwrap();
 
         // List
-        let entries = list(TEST_DIR).unwrap();
+        let entries = list(&dir).unwrap();
         assert_eq!(entries.len(), 3);
 
         // Find
-        let txt_files = find(TEST_DIR, |entry| {
-            entry.path().extension().map_or(false, |ext| ext == "txt")
+        let txt_files = find(&dir, |entry| {
+            entry.path().extension().is_some_and(|ext| ext == "txt")
         }).unwrap();
         assert_eq!(txt_files.len(), 2);
-
-        cleanup();
     }
 
     #[test]
     fn test_copy_move_rename() {
-        setup();
+        let dir = TempDir::new().unwrap();
 
-        let original_path = get_test_path(TEST_FILE);
-        let copy_path = get_test_path(TEST_FILE_COPY);
-        let move_path = get_test_path(TEST_FILE_MOVE);
-        let rename_path = get_test_path(TEST_FILE_RENAME);
+        let original_path = dir.join(TEST_FILE);
+        let copy_path = dir.join(TEST_FILE_COPY);
+        let move_path = dir.join(TEST_FILE_MOVE);
+        let rename_path = dir.join(TEST_FILE_RENAME);
 
         // Create original file
         create(&original_path, "Original content").unwrap();
@@ -531,21 +818,19 @@ mod tests {
         rename(&move_path, &rename_path).unwrap();
         assert!(!move_path.exists());
         assert!(rename_path.exists());
-
-        cleanup();
     }
 
     #[test]
     fn test_recursive_copy() {
-        setup();
+        let dir = TempDir::new().unwrap();
 
-        let sub_dir = Path::new(TEST_DIR).join("sub_dir");
+        let sub_dir = dir.join("sub_dir");
         fs::create_dir(&sub_dir).unwrap();
 
-        create(&sub_dir.join("file1.txt"), "Content 1").unwrap();
-        create(&sub_dir.join("file2.txt"), "Content 2").unwrap();
+        create(sub_dir.join("file1.txt"), "Content 1").unwrap();
+        create(sub_dir.join("file2.txt"), "Content 2").unwrap();
 
-        let copy_dir = Path::new(TEST_DIR).join("copy_dir");
+        let copy_dir = dir.join("copy_dir");
 
         recursive_copy(&sub_dir, &copy_dir).unwrap();
 
@@ -553,10 +838,40 @@ mod tests {
         assert!(copy_dir.join("file1.txt").exists());
         assert!(copy_dir.join("file2.txt").exists());
 
-        assert_eq!(read(&copy_dir.join("file1.txt")).unwrap(), "Content 1");
-        assert_eq!(read(&copy_dir.join("file2.txt")).unwrap(), "Content 2");
+        assert_eq!(read(copy_dir.join("file1.txt")).unwrap(), "Content 1");
+        assert_eq!(read(copy_dir.join("file2.txt")).unwrap(), "Content 2");
+    }
 
-        cleanup();
+    #[test]
+    fn test_write_atomic_and_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.join("state.json");
+
+        write_atomic(&path, "{}").unwrap();
+        let bak = backup(&path).unwrap();
+        write_atomic(&path, "{\"saved\": true}").unwrap();
+        assert_eq!(read(&path).unwrap(), "{\"saved\": true}");
+        assert_eq!(read(&bak).unwrap(), "{}");
+        assert_eq!(list(&dir).unwrap().len(), 2);  // no temporary file left behind
+
+        assert!(write_atomic(dir.join("missing/state.json"), "").is_err());
+        assert_eq!(list(&dir).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_binary_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.join("nested/data.bin");
+        let data = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        write_bytes(&path, &data[..600]).unwrap();
+        append_bytes(&path, &data[600..]).unwrap();
+        assert_eq!(read_bytes(&path).unwrap(), data);
+        assert!(read(&path).is_err());  // not valid UTF-8
+
+        let chunks = read_chunks(&path, 256).unwrap().map(|chunk| chunk.unwrap().len()).collect::<Vec<_>>();
+        assert_eq!(chunks, vec![256, 256, 256, 232]);
+        assert_eq!(read_chunks(&path, 1000).unwrap().count(), 1);
+        assert!(read_chunks(&path, 0).is_err());
     }
 
     #[test]
@@ -0,0 +1,141 @@
+// Checksums, streaming comparison and duplicate detection of files.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::fs::{self, File};
+use super::{find, read_full, Result};
+use crate::crypto::hash::{to_hex, Algorithm};
+
+/// Returns the hexadecimal digest of a file, reading it in blocks.
+///
+/// For quick integrity checks where tampering isn't a concern, the checksums
+/// ([Algorithm::Crc32], [Algorithm::Adler32]...) are much faster than the hashes.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to hash.
+/// * `algorithm` - The hash [Algorithm] to use.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the lowercase hexadecimal digest, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, hash, delete};
+/// use dev_utils::crypto::hash::Algorithm;
+///
+/// let path = create("hash_example.txt", "abc").unwrap();
+/// assert_eq!(hash(&path, Algorithm::Sha256).unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+/// assert_eq!(hash(&path, Algorithm::Crc32).unwrap(), "352441c2");
+/// delete(&path).unwrap();
+/// ```
+pub fn hash<P: AsRef<Path>>(path: P, algorithm: Algorithm) -> Result<String> {
+    Ok(to_hex(&algorithm.digest_reader(File::open(path)?)?))
+}
+
+/// Returns whether two files have the same content.
+///
+/// The sizes are compared first; the contents are then compared block by block, stopping
+/// at the first difference, so the files are never loaded entirely.
+///
+/// # Arguments
+///
+/// * `a` - The path of the first file.
+/// * `b` - The path of the second file.
+///
+/// # Returns
+///
+/// Returns a `Result` containing `true` if the contents are identical, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, compare, delete};
+///
+/// let a = create("compare_a.txt", "same").unwrap();
+/// let b = create("compare_b.txt", "same").unwrap();
+/// assert!(compare(&a, &b).unwrap());
+/// delete(&a).unwrap();
+/// delete(&b).unwrap();
+/// ```
+pub fn compare<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> Result<bool> {
+    let (mut a, mut b) = (File::open(a)?, File::open(b)?);
+    if a.metadata()?.len() != b.metadata()?.len() {return Ok(false);}
+
+    let (mut buf_a, mut buf_b) = (vec![0; 64 * 1024], vec![0; 64 * 1024]);
+    loop {
+        let (n, m) = (read_full(&mut a, &mut buf_a)?, read_full(&mut b, &mut buf_b)?);
+        if n != m || buf_a[..n] != buf_b[..m] {return Ok(false);}
+        if n == 0 {return Ok(true);}
+    }
+}
+
+/// Finds the groups of identical files under a directory (recursively).
+///
+/// Files are first grouped by size, and only files sharing a size are hashed (SHA-256).
+/// Empty files are ignored.
+///
+/// # Arguments
+///
+/// * `dir` - The directory to scan.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the groups (of two or more sorted paths, the groups
+/// sorted by their first path), or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, dedupe, TempDir};
+///
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join("a.txt"), "same").unwrap();
+/// create(dir.join("sub/b.txt"), "same").unwrap();
+/// create(dir.join("c.txt"), "other").unwrap();
+/// let groups = dedupe(&dir).unwrap();
+/// assert_eq!(groups.len(), 1);
+/// assert_eq!(groups[0].len(), 2);
+/// ```
+pub fn dedupe<P: AsRef<Path>>(dir: P) -> Result<Vec<Vec<PathBuf>>> {
+    let mut by_size = BTreeMap::<u64, Vec<PathBuf>>::new();
+    for path in find(dir, |_| true)? {
+        let size = fs::metadata(&path)?.len();
+        if size > 0 {by_size.entry(size).or_default().push(path);}
+    }
+
+    let mut groups = Vec::new();
+    for candidates in by_size.into_values().filter(|paths| paths.len() > 1) {
+        let mut by_hash = BTreeMap::<String, Vec<PathBuf>>::new();
+        for path in candidates {
+            by_hash.entry(hash(&path, Algorithm::Sha256)?).or_default().push(path);
+        }
+        groups.extend(by_hash.into_values().filter(|paths| paths.len() > 1));
+    }
+    groups.iter_mut().for_each(|group| group.sort());
+    groups.sort();
+    Ok(groups)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{create, TempDir};
+
+    #[test]
+    fn test_compare_and_dedupe() {
+        let dir = TempDir::new().unwrap();
+        let big = "x".repeat(200_000);
+        let a = create(dir.join("a.bin"), &big).unwrap();
+        let b = create(dir.join("sub/b.bin"), &big).unwrap();
+        let c = create(dir.join("c.bin"), &format!("{}y", &big[1..])).unwrap();
+        create(dir.join("empty1"), "").unwrap();
+        create(dir.join("empty2"), "").unwrap();
+
+        assert!(compare(&a, &b).unwrap());
+        assert!(!compare(&a, &c).unwrap());  // same size, last byte differs
+        assert_eq!(dedupe(&dir).unwrap(), vec![vec![a, b]]);
+    }
+}
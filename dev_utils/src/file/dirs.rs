@@ -0,0 +1,298 @@
+// Recursive delete, move and merge of directories, with dry runs and progress callbacks.
+use std::path::{Path, PathBuf};
+use std::fs;
+use super::{FileError, Result};
+
+/// A single step of a directory operation, reported to the progress callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirOperation {
+    /// A directory is created.
+    CreateDir(PathBuf),
+    /// A file is copied (overwriting the target if it exists).
+    Copy { from: PathBuf, to: PathBuf },
+    /// A file or directory is moved.
+    Move { from: PathBuf, to: PathBuf },
+    /// A file (or symbolic link) is deleted.
+    Delete(PathBuf),
+    /// An (empty) directory is removed.
+    RemoveDir(PathBuf),
+    /// A conflicting file is left untouched.
+    Skip(PathBuf),
+}
+
+/// How [merge_dirs] handles files that exist in both directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Replace the destination file.
+    Overwrite,
+    /// Keep the destination file.
+    Skip,
+    /// Keep the most recently modified of both files.
+    KeepNewer,
+    /// Copy the source file next to it, with a ` (n)` suffix (e.g. `notes (1).txt`).
+    Rename,
+    /// Stop with an error at the first conflict.
+    Fail,
+}
+
+type ProgressFn<'a> = Box<dyn FnMut(&DirOperation) + 'a>;
+
+/// Options of [recursive_delete], [recursive_move] and [merge_dirs].
+#[derive(Default)]
+pub struct DirOptions<'a> {
+    dry_run: bool,
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> DirOptions<'a> {
+    /// Creates the default options (operations are applied, no progress callback).
+    pub fn new() -> Self {Self::default()}
+
+    /// Only plans the operations: nothing is changed on disk.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Sets a callback called with every operation, before it is applied.
+    pub fn on_progress<F: FnMut(&DirOperation) + 'a>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    // Reports the operation, applies it (unless in dry-run mode) and records it
+    fn run(&mut self, operation: DirOperation, done: &mut Vec<DirOperation>) -> Result<()> {
+        if let Some(progress) = self.progress.as_mut() {progress(&operation);}
+        if !self.dry_run {
+            match &operation {
+                DirOperation::CreateDir(dir) => fs::create_dir_all(dir)?,
+                DirOperation::Copy { from, to } => {fs::copy(from, to)?;},
+                DirOperation::Move { from, to } => fs::rename(from, to)?,
+                DirOperation::Delete(path) => fs::remove_file(path)?,
+                DirOperation::RemoveDir(dir) => fs::remove_dir(dir)?,
+                DirOperation::Skip(_) => (),
+            }
+        }
+        done.push(operation);
+        Ok(())
+    }
+}
+
+// Returns the entries of a directory, sorted by name
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?.map(|entry| entry.map(|e| e.path())).collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+// Whether `path` is a real directory (symbolic links to directories are treated as files)
+fn is_real_dir(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.is_dir())
+}
+
+/// Deletes a directory and all its contents (or a single file).
+///
+/// Files are deleted before their directories; symbolic links are deleted, never followed.
+///
+/// # Arguments
+///
+/// * `path` - The directory to delete.
+/// * `options` - The [DirOptions] (dry run, progress callback).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the applied (or planned) operations, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, recursive_delete, DirOptions, TempDir};
+///
+/// let tmp = TempDir::new().unwrap();
+/// create(tmp.join("build/a.o"), "").unwrap();
+/// create(tmp.join("build/sub/b.o"), "").unwrap();
+///
+/// let planned = recursive_delete(tmp.join("build"), DirOptions::new().dry_run()).unwrap();
+/// assert_eq!(planned.len(), 4);  // 2 files + 2 directories
+/// assert!(tmp.join("build").exists());
+///
+/// let mut deleted = 0;
+/// recursive_delete(tmp.join("build"), DirOptions::new().on_progress(|_| deleted += 1)).unwrap();
+/// assert!(!tmp.join("build").exists());
+/// assert_eq!(deleted, 4);
+/// ```
+pub fn recursive_delete<P: AsRef<Path>>(path: P, mut options: DirOptions) -> Result<Vec<DirOperation>> {
+    let mut done = Vec::new();
+    delete_internal(path.as_ref(), &mut options, &mut done)?;
+    Ok(done)
+}
+
+fn delete_internal(path: &Path, options: &mut DirOptions, done: &mut Vec<DirOperation>) -> Result<()> {
+    match is_real_dir(path) {
+        true => {
+            for entry in sorted_entries(path)? {delete_internal(&entry, options, done)?;}
+            options.run(DirOperation::RemoveDir(path.to_path_buf()), done)
+        },
+        false => options.run(DirOperation::Delete(path.to_path_buf()), done),
+    }
+}
+
+/// Moves a directory (or file) to a new location.
+///
+/// The move is a single rename when possible. When the destination is on another file
+/// system, the tree is copied file by file and the source is deleted afterwards.
+/// In dry-run mode a single [DirOperation::Move] is planned.
+///
+/// # Arguments
+///
+/// * `from` - The directory to move.
+/// * `to` - The new path (must not exist).
+/// * `options` - The [DirOptions] (dry run, progress callback).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the applied (or planned) operations, or a `FileError`
+/// if the destination already exists.
+pub fn recursive_move<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q, mut options: DirOptions) -> Result<Vec<DirOperation>> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    if to.exists() {return Err(FileError::PathError(format!("Destination already exists: {}", to.display())));}
+    fs::symlink_metadata(from)?;
+
+    let mut done = Vec::new();
+    let operation = DirOperation::Move { from: from.to_path_buf(), to: to.to_path_buf() };
+    if options.dry_run || fs::rename(from, to).is_ok() {
+        if let Some(progress) = options.progress.as_mut() {progress(&operation);}
+        done.push(operation);
+        return Ok(done);
+    }
+    // probably a cross-device move: copy everything, then delete the source
+    merge_internal(from, to, ConflictStrategy::Fail, &mut options, &mut done)?;
+    delete_internal(from, &mut options, &mut done)?;
+    Ok(done)
+}
+
+/// Merges the contents of `src` into `dst` (recursively), leaving `src` untouched.
+///
+/// Missing directories are created, and files that exist in both directories are handled
+/// according to the [ConflictStrategy].
+///
+/// # Arguments
+///
+/// * `src` - The directory to merge from.
+/// * `dst` - The directory to merge into (created if missing).
+/// * `strategy` - How to handle conflicting files.
+/// * `options` - The [DirOptions] (dry run, progress callback).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the applied (or planned) operations, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, read, merge_dirs, ConflictStrategy, DirOptions, TempDir};
+///
+/// let tmp = TempDir::new().unwrap();
+/// create(tmp.join("new/a.txt"), "new a").unwrap();
+/// create(tmp.join("new/b.txt"), "new b").unwrap();
+/// create(tmp.join("old/a.txt"), "old a").unwrap();
+///
+/// merge_dirs(tmp.join("new"), tmp.join("old"), ConflictStrategy::Skip, DirOptions::new()).unwrap();
+/// assert_eq!(read(tmp.join("old/a.txt")).unwrap(), "old a");
+/// assert_eq!(read(tmp.join("old/b.txt")).unwrap(), "new b");
+/// ```
+pub fn merge_dirs<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P, dst: Q, strategy: ConflictStrategy, mut options: DirOptions,
+) -> Result<Vec<DirOperation>> {
+    let mut done = Vec::new();
+    merge_internal(src.as_ref(), dst.as_ref(), strategy, &mut options, &mut done)?;
+    Ok(done)
+}
+
+fn merge_internal(src: &Path, dst: &Path, strategy: ConflictStrategy, options: &mut DirOptions, done: &mut Vec<DirOperation>) -> Result<()> {
+    if !is_real_dir(src) {return Err(FileError::PathError(format!("Not a directory: {}", src.display())));}
+    if !dst.exists() {options.run(DirOperation::CreateDir(dst.to_path_buf()), done)?;}
+
+    for entry in sorted_entries(src)? {
+        let target = dst.join(entry.file_name().unwrap_or_default());
+        if is_real_dir(&entry) {
+            merge_internal(&entry, &target, strategy, options, done)?;
+            continue;
+        }
+        let copy = |to: PathBuf| DirOperation::Copy { from: entry.clone(), to };
+        let operation = match (target.exists(), strategy) {
+            (false, _) | (true, ConflictStrategy::Overwrite) => copy(target),
+            (true, ConflictStrategy::Skip) => DirOperation::Skip(entry.clone()),
+            (true, ConflictStrategy::KeepNewer) => {
+                let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+                match modified(&entry) > modified(&target) {
+                    true => copy(target),
+                    false => DirOperation::Skip(entry.clone()),
+                }
+            },
+            (true, ConflictStrategy::Rename) => copy(free_name(&target)),
+            (true, ConflictStrategy::Fail) => return Err(FileError::PathError(format!("File already exists: {}", target.display()))),
+        };
+        options.run(operation, done)?;
+    }
+    Ok(())
+}
+
+// Returns the first `name (n).ext` path that doesn't exist
+fn free_name(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..).map(|n| path.with_file_name(format!("{} ({}){}", stem, n, ext))).find(|p| !p.exists()).unwrap()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{create, read, TempDir};
+
+    #[test]
+    fn test_recursive_move() {
+        let tmp = TempDir::new().unwrap();
+        create(tmp.join("src/a.txt"), "a").unwrap();
+        create(tmp.join("src/sub/b.txt"), "b").unwrap();
+
+        let planned = recursive_move(tmp.join("src"), tmp.join("dst"), DirOptions::new().dry_run()).unwrap();
+        assert_eq!(planned, vec![DirOperation::Move { from: tmp.join("src"), to: tmp.join("dst") }]);
+        assert!(tmp.join("src").exists());
+
+        recursive_move(tmp.join("src"), tmp.join("dst"), DirOptions::new()).unwrap();
+        assert_eq!(read(tmp.join("dst/sub/b.txt")).unwrap(), "b");
+        assert!(!tmp.join("src").exists());
+        assert!(recursive_move(tmp.join("dst"), tmp.join("dst"), DirOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let tmp = TempDir::new().unwrap();
+        create(tmp.join("src/a.txt"), "new").unwrap();
+        create(tmp.join("src/deep/c.txt"), "c").unwrap();
+        create(tmp.join("dst/a.txt"), "old").unwrap();
+
+        let err = merge_dirs(tmp.join("src"), tmp.join("dst"), ConflictStrategy::Fail, DirOptions::new());
+        assert!(err.is_err());
+
+        let ops = merge_dirs(tmp.join("src"), tmp.join("dst"), ConflictStrategy::Rename, DirOptions::new()).unwrap();
+        assert_eq!(read(tmp.join("dst/a (1).txt")).unwrap(), "new");
+        assert_eq!(read(tmp.join("dst/a.txt")).unwrap(), "old");
+        assert!(ops.contains(&DirOperation::CreateDir(tmp.join("dst/deep"))));
+        assert_eq!(read(tmp.join("dst/deep/c.txt")).unwrap(), "c");
+
+        merge_dirs(tmp.join("src"), tmp.join("dst"), ConflictStrategy::Overwrite, DirOptions::new()).unwrap();
+        assert_eq!(read(tmp.join("dst/a.txt")).unwrap(), "new");
+        assert_eq!(read(tmp.join("src/a.txt")).unwrap(), "new");  // the source is untouched
+    }
+
+    #[test]
+    fn test_delete_single_file() {
+        let tmp = TempDir::new().unwrap();
+        let file = create(tmp.join("only.txt"), "").unwrap();
+        assert_eq!(recursive_delete(&file, DirOptions::new()).unwrap(), vec![DirOperation::Delete(file.clone())]);
+        assert!(!file.exists());
+    }
+}
@@ -0,0 +1,337 @@
+// Glob patterns (`*`, `?`, `**` and `{a,b}`) to match and find paths.
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use std::fmt;
+use std::fs;
+use super::{FileError, Result};
+use super::rename::wildcard_captures;
+
+/// A compiled glob pattern.
+///
+/// - `*` matches any sequence of characters within a path segment
+/// - `?` matches a single character
+/// - `**` (as a whole segment) matches any number of directories, including none
+/// - `{a,b}` matches any of the comma-separated alternatives (which can be nested)
+///
+/// Wildcards don't match names starting with a `.` (hidden files), unless the pattern
+/// segment starts with a `.` too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    alternatives: Vec<Alternative>,
+}
+
+// A pattern without braces, split into path segments
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Alternative {
+    absolute: bool,
+    segments: Vec<String>,
+    suffix: String,  // literal end of the last segment, used to reject paths quickly
+}
+
+impl Glob {
+    /// Compiles a glob pattern.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the `Glob`, or a `FileError::PathError` if the braces
+    /// of the pattern are unbalanced.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let alternatives = expand_braces(pattern)?.iter().map(|alt| {
+            let mut segments = Vec::<String>::new();
+            for segment in alt.split(['/', '\\']).filter(|s| !s.is_empty() && *s != ".") {
+                if !(segment == "**" && segments.last().is_some_and(|last| last == "**")) {
+                    segments.push(segment.to_string());
+                }
+            }
+            let last = segments.last().map(String::as_str).unwrap_or_default();
+            let suffix = last.rsplit(['*', '?']).next().unwrap_or_default().to_string();
+            Alternative { absolute: alt.starts_with('/'), segments, suffix }
+        }).collect();
+        Ok(Glob { pattern: pattern.to_string(), alternatives })
+    }
+
+    /// Returns the original pattern.
+    pub fn as_str(&self) -> &str {&self.pattern}
+
+    /// Returns whether `path` matches the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::file::Glob;
+    ///
+    /// let glob = Glob::new("src/**/*.{rs,toml}").unwrap();
+    /// assert!(glob.is_match("src/file/glob.rs"));
+    /// assert!(glob.is_match("src/lib.rs"));
+    /// assert!(!glob.is_match("examples/dlog.rs"));
+    /// ```
+    pub fn is_match<P: AsRef<Path>>(&self, path: P) -> bool {
+        let (absolute, names) = split_path(path.as_ref());
+        self.matches_split(absolute, &names)
+    }
+
+    fn matches_split(&self, absolute: bool, names: &[String]) -> bool {
+        self.alternatives.iter().any(|alt| alt.absolute == absolute
+            && (alt.suffix.is_empty() || names.last().is_some_and(|name| name.ends_with(&alt.suffix)))
+            && match_segments(&alt.segments, names)
+        )
+    }
+
+    /// Returns the existing paths (files and directories) matching the pattern, sorted.
+    ///
+    /// Directories that cannot be read are skipped, and `**` doesn't descend into symlinked
+    /// directories (which could form a cycle).
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut results = BTreeSet::new();
+        for alt in &self.alternatives {
+            let root = match alt.absolute {
+                true => PathBuf::from("/"),
+                false => PathBuf::new(),
+            };
+            if !alt.segments.is_empty() {walk(&root, &alt.segments, &mut results);}
+        }
+        results.into_iter().collect()
+    }
+}
+
+impl fmt::Display for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// A set of glob patterns matched together.
+///
+/// The path is split once for all the patterns, and each pattern rejects paths that don't
+/// end with its literal suffix (e.g. `.rs` for `**/*.rs`) before any wildcard matching.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::GlobSet;
+///
+/// let ignored = GlobSet::new(["target/**", "**/*.{tmp,bak}", "**/.git"]).unwrap();
+/// assert!(ignored.is_match("target/debug/app"));
+/// assert_eq!(ignored.matches("src/old.bak"), vec![1]);
+/// assert!(!ignored.is_match("src/main.rs"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlobSet {
+    globs: Vec<Glob>,
+}
+
+impl GlobSet {
+    /// Compiles every pattern (see [Glob::new]).
+    pub fn new<I, S>(patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let globs = patterns.into_iter().map(|p| Glob::new(p.as_ref())).collect::<Result<Vec<_>>>()?;
+        Ok(GlobSet { globs })
+    }
+
+    /// Returns the number of patterns.
+    pub fn len(&self) -> usize {self.globs.len()}
+
+    /// Returns `true` if the set has no patterns.
+    pub fn is_empty(&self) -> bool {self.globs.is_empty()}
+
+    /// Returns whether `path` matches any of the patterns.
+    pub fn is_match<P: AsRef<Path>>(&self, path: P) -> bool {
+        let (absolute, names) = split_path(path.as_ref());
+        self.globs.iter().any(|glob| glob.matches_split(absolute, &names))
+    }
+
+    /// Returns the indices of the patterns matching `path`.
+    pub fn matches<P: AsRef<Path>>(&self, path: P) -> Vec<usize> {
+        let (absolute, names) = split_path(path.as_ref());
+        (0..self.globs.len()).filter(|&i| self.globs[i].matches_split(absolute, &names)).collect()
+    }
+}
+
+/// Returns the existing paths matching a glob pattern, sorted.
+///
+/// See [Glob] for the supported syntax.
+///
+/// # Arguments
+///
+/// * `pattern` - The glob pattern (e.g. `src/**/*.rs`)
+///
+/// # Returns
+///
+/// Returns a `Result` containing the matching paths, or a `FileError` if the pattern is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::glob;
+///
+/// let sources = glob("src/**/*.rs").unwrap();
+/// assert!(sources.contains(&"src/lib.rs".into()));
+/// assert!(sources.windows(2).all(|w| w[0] < w[1]));
+/// ```
+pub fn glob(pattern: &str) -> Result<Vec<PathBuf>> {
+    Ok(Glob::new(pattern)?.paths())
+}
+
+// Expands the `{a,b}` groups of a pattern into every alternative
+pub(crate) fn expand_braces(pattern: &str) -> Result<Vec<String>> {
+    let unbalanced = || FileError::PathError(format!("Unbalanced braces in glob pattern: {}", pattern));
+    let Some(open) = pattern.find('{') else {
+        return match pattern.contains('}') {
+            true => Err(unbalanced()),
+            false => Ok(vec![pattern.to_string()]),
+        };
+    };
+
+    let (mut depth, mut close, mut options, mut start) = (0, None, Vec::new(), open + 1);
+    for (i, c) in pattern.char_indices().skip_while(|&(i, _)| i <= open) {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            '}' => {close = Some(i); options.push(&pattern[start..i]); break;},
+            ',' if depth == 0 => {options.push(&pattern[start..i]); start = i + 1;},
+            _ => (),
+        }
+    }
+    let close = close.ok_or_else(unbalanced)?;
+    if pattern[..open].contains('}') {return Err(unbalanced());}
+
+    let mut expanded = Vec::new();
+    for option in options {
+        expanded.extend(expand_braces(&format!("{}{}{}", &pattern[..open], option, &pattern[close + 1..]))?);
+    }
+    Ok(expanded)
+}
+
+// Splits a path into (is absolute, normal segments)
+fn split_path(path: &Path) -> (bool, Vec<String>) {
+    let mut absolute = false;
+    let mut names = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => absolute = true,
+            Component::CurDir => (),
+            Component::ParentDir => names.push("..".to_string()),
+            Component::Normal(name) => names.push(name.to_string_lossy().into_owned()),
+        }
+    }
+    (absolute, names)
+}
+
+fn has_wildcard(segment: &str) -> bool {segment.contains(['*', '?'])}
+
+fn segment_matches(segment: &str, name: &str) -> bool {
+    match has_wildcard(segment) {
+        true => (!name.starts_with('.') || segment.starts_with('.')) && wildcard_captures(segment, name).is_some(),
+        false => segment == name,
+    }
+}
+
+fn match_segments(segments: &[String], names: &[String]) -> bool {
+    match segments.first().map(String::as_str) {
+        None => names.is_empty(),
+        Some("**") => (0..=names.len())
+            .take_while(|&n| n == 0 || !names[n - 1].starts_with('.'))
+            .any(|n| match_segments(&segments[1..], &names[n..])),
+        Some(segment) => names.first().is_some_and(|name| segment_matches(segment, name))
+            && match_segments(&segments[1..], &names[1..]),
+    }
+}
+
+// Collects the paths under `dir` matching the remaining segments
+fn walk(dir: &Path, segments: &[String], results: &mut BTreeSet<PathBuf>) {
+    let Some(segment) = segments.first() else {
+        results.insert(dir.to_path_buf());
+        return;
+    };
+    let read_dir = || fs::read_dir(if dir.as_os_str().is_empty() {Path::new(".")} else {dir});
+
+    match segment.as_str() {
+        "**" => {
+            walk(dir, &segments[1..], results);
+            for entry in read_dir().into_iter().flatten().flatten() {
+                let hidden = entry.file_name().to_string_lossy().starts_with('.');
+                // the type of the entry itself: a symlink to a directory is not followed
+                let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+                if !hidden && is_dir {walk(&dir.join(entry.file_name()), segments, results);}
+            }
+        },
+        literal if !has_wildcard(literal) => {
+            let path = dir.join(literal);
+            if path.symlink_metadata().is_ok() {walk(&path, &segments[1..], results);}
+        },
+        pattern => {
+            for entry in read_dir().into_iter().flatten().flatten() {
+                if segment_matches(pattern, &entry.file_name().to_string_lossy()) {
+                    walk(&dir.join(entry.file_name()), &segments[1..], results);
+                }
+            }
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("*.{rs,toml}").unwrap(), vec!["*.rs", "*.toml"]);
+        assert_eq!(expand_braces("{a,b{c,d}}x").unwrap(), vec!["ax", "bcx", "bdx"]);
+        assert!(expand_braces("{a,b").is_err());
+        assert!(expand_braces("a}").is_err());
+    }
+
+    #[test]
+    fn test_is_match() {
+        let glob = Glob::new("src/**/*.rs").unwrap();
+        assert!(glob.is_match("src/main.rs"));
+        assert!(glob.is_match("./src/a/b/c.rs"));
+        assert!(!glob.is_match("src/main.rsx"));
+        assert!(!glob.is_match("src/.hidden/x.rs"));
+        assert!(Glob::new("file?.txt").unwrap().is_match("file1.txt"));
+        assert!(!Glob::new("*").unwrap().is_match(".env"));
+        assert!(Glob::new(".*").unwrap().is_match(".env"));
+        assert!(Glob::new("/tmp/*").unwrap().is_match("/tmp/x"));
+        assert!(!Glob::new("/tmp/*").unwrap().is_match("tmp/x"));
+    }
+
+    #[test]
+    fn test_glob_set() {
+        let set = GlobSet::new(["**/*.rs", "Cargo.{toml,lock}", "docs/**"]).unwrap();
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.matches("Cargo.lock"), vec![1]);
+        assert_eq!(set.matches("docs/guide.rs"), vec![0, 2]);
+        assert!(!set.is_match("README.md"));
+    }
+
+    #[test]
+    fn test_glob_paths() {
+        let dir = super::super::TempDir::new().unwrap();
+        for file in ["a.rs", "b.txt", "sub/c.rs", "sub/deep/d.rs", ".git/e.rs"] {
+            super::super::create(dir.join(file), "").unwrap();
+        }
+        let pattern = format!("{}/**/*.{{rs,txt}}", dir.path().display());
+        let found = glob(&pattern).unwrap().into_iter()
+            .map(|p| p.strip_prefix(dir.path()).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec!["a.rs", "b.txt", "sub/c.rs", "sub/deep/d.rs"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_glob_symlink_cycle() {
+        let dir = super::super::TempDir::new().unwrap();
+        super::super::create(dir.join("sub/a.rs"), "").unwrap();
+        std::os::unix::fs::symlink(dir.path(), dir.join("sub/loop")).unwrap();
+
+        let found = glob(&format!("{}/**/*.rs", dir.path().display())).unwrap();
+        assert_eq!(found, vec![dir.join("sub/a.rs")]);
+        // still reachable when named explicitly
+        assert_eq!(glob(&format!("{}/sub/loop/sub/*.rs", dir.path().display())).unwrap(), vec![dir.join("sub/loop/sub/a.rs")]);
+    }
+}
@@ -0,0 +1,175 @@
+// File metadata with dates as `DateTime` and readable sizes.
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::fmt;
+use std::fs::{self, Metadata};
+use super::Result;
+use crate::datetime::DateTime;
+use crate::format::human_bytes;
+
+/// Metadata of a file system entry, returned by [info] and [list_info].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    /// The path of the entry.
+    pub path: PathBuf,
+    /// The size in bytes (of the target, for symbolic links).
+    pub size: u64,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+    /// Whether the entry itself is a symbolic link.
+    pub is_symlink: bool,
+    /// Whether the entry is read-only.
+    pub readonly: bool,
+    /// The Unix permission bits (e.g. `0o644`), `None` on other platforms.
+    pub mode: Option<u32>,
+    /// The creation time (UTC), if supported by the platform and file system.
+    pub created: Option<DateTime>,
+    /// The last modification time (UTC).
+    pub modified: Option<DateTime>,
+}
+
+impl FileInfo {
+    /// Returns the permissions as an `ls` style string (`rwxr-xr-x`).
+    ///
+    /// Without Unix permission bits, this is `r--r--r--` or `rw-rw-rw-` depending on [FileInfo::readonly].
+    pub fn permissions(&self) -> String {
+        let mode = self.mode.unwrap_or(if self.readonly {0o444} else {0o666});
+        (0..9).map(|i| match mode & (0o400 >> i) != 0 {
+            true => ['r', 'w', 'x'][i % 3],
+            false => '-',
+        }).collect()
+    }
+}
+
+impl fmt::Display for FileInfo {
+    /// Formats the entry as an `ls -l` style line: type, permissions, size, modification time and path.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match (self.is_symlink, self.is_dir) {
+            (true, _) => 'l',
+            (false, true) => 'd',
+            (false, false) => '-',
+        };
+        let modified = self.modified.map(|m| m.to_string()).unwrap_or_else(|| "-".repeat(19));
+        write!(f, "{}{} {:>10} {} {}", kind, self.permissions(), human_bytes(self.size), modified, self.path.display())
+    }
+}
+
+fn to_datetime(time: std::io::Result<SystemTime>) -> Option<DateTime> {
+    let seconds = time.ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    DateTime::from_timestamp(seconds as i64).ok()
+}
+
+#[cfg(unix)]
+fn mode(metadata: &Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &Metadata) -> Option<u32> {None}
+
+/// Returns the metadata of a file, directory or symbolic link.
+///
+/// # Arguments
+///
+/// * `path` - The path of the entry.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the [FileInfo], or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, info, TempDir};
+///
+/// let dir = TempDir::new().unwrap();
+/// let path = create(dir.join("notes.txt"), "Hello").unwrap();
+/// let info = info(&path).unwrap();
+/// assert_eq!(info.size, 5);
+/// assert!(!info.is_dir && !info.is_symlink);
+/// println!("{}", info);  // -rw-r--r--        5 B 2024-05-01 12:34:56 /tmp/.../notes.txt
+/// ```
+pub fn info<P: AsRef<Path>>(path: P) -> Result<FileInfo> {
+    let path = path.as_ref();
+    let link = fs::symlink_metadata(path)?;
+    let metadata = fs::metadata(path).unwrap_or_else(|_| link.clone());  // a broken link describes itself
+    Ok(FileInfo {
+        path: path.to_path_buf(),
+        size: metadata.len(),
+        is_dir: metadata.is_dir(),
+        is_symlink: link.file_type().is_symlink(),
+        readonly: metadata.permissions().readonly(),
+        mode: mode(&metadata),
+        created: to_datetime(metadata.created()),
+        modified: to_datetime(metadata.modified()),
+    })
+}
+
+/// Lists the contents of a directory with their metadata (see [super::list]).
+///
+/// # Arguments
+///
+/// * `path` - The path of the directory to list.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the [FileInfo] of every entry, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, list_info, TempDir};
+///
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join("a.bin"), &"x".repeat(1536)).unwrap();
+/// for entry in list_info(&dir).unwrap() {
+///     println!("{}", entry);  // -rw-r--r--    1.5 KiB ...
+/// }
+/// ```
+pub fn list_info<P: AsRef<Path>>(path: P) -> Result<Vec<FileInfo>> {
+    fs::read_dir(path)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| info(entry.path()))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{create, TempDir};
+
+    #[test]
+    fn test_info() {
+        let dir = TempDir::new().unwrap();
+        let path = create(dir.join("data.txt"), &"x".repeat(2048)).unwrap();
+        let info = info(&path).unwrap();
+        assert_eq!((info.size, info.is_dir, info.is_symlink), (2048, false, false));
+        assert!(info.modified.is_some());
+        assert!(info.to_string().starts_with('-') && info.to_string().contains("2.0 KiB"));
+
+        let dir_info = super::info(dir.path()).unwrap();
+        assert!(dir_info.is_dir && dir_info.to_string().starts_with('d'));
+        assert_eq!(list_info(&dir).unwrap(), vec![info]);
+    }
+
+    #[test]
+    fn test_permissions() {
+        let mut info = FileInfo {
+            path: PathBuf::new(), size: 0, is_dir: false, is_symlink: false,
+            readonly: false, mode: Some(0o754), created: None, modified: None,
+        };
+        assert_eq!(info.permissions(), "rwxr-xr--");
+        (info.mode, info.readonly) = (None, true);
+        assert_eq!(info.permissions(), "r--r--r--");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink() {
+        let dir = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(dir.join("missing"), dir.join("link")).unwrap();
+        assert!(info(dir.join("link")).unwrap().is_symlink);
+    }
+}
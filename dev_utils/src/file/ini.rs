@@ -0,0 +1,282 @@
+//! Parsing and serialization of classic INI files.
+//!
+//! Files are read into a section → key → value map. Keys written before the first
+//! `[section]` header belong to the global section, stored under the empty name `""`.
+//! Both `;` and `#` start comments, `=` and `:` separate keys from values, and values
+//! may be quoted to keep leading/trailing spaces or comment characters. Inside a quoted
+//! value, the quote character is written twice (`"say ""hi"""`), and line breaks are kept:
+//! a quoted value continues on the next lines until its closing quote.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::file::ini;
+//!
+//! let config = ini::parse("
+//! ; global settings
+//! name = demo
+//!
+//! [server]
+//! host = localhost  # inline comment
+//! port: 8080
+//! ").unwrap();
+//!
+//! assert_eq!(config[""]["name"], "demo");
+//! assert_eq!(config["server"]["port"], "8080");
+//! assert_eq!(ini::serialize(&config), "name = demo\n\n[server]\nhost = localhost\nport = 8080\n");
+//! ```
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// The keys and values of a section.
+pub type Section = BTreeMap<String, String>;
+
+/// A parsed INI file: sections (the global one is `""`) mapped to their keys.
+pub type Ini = BTreeMap<String, Section>;
+
+/// What to do when a key appears more than once in the same section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// The last value wins.
+    #[default]
+    KeepLast,
+    /// The first value wins.
+    KeepFirst,
+    /// The values are joined with newlines.
+    Append,
+    /// Parsing fails with an [IniError].
+    Error,
+}
+
+/// Error returned when an INI file cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniError {
+    /// The line (1-based) where the error was found.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for IniError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "INI error at line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for IniError {}
+
+/// Parses an INI file, keeping the last value of duplicated keys.
+///
+/// See [parse_with] to choose another [DuplicateKeys] policy.
+pub fn parse(input: &str) -> Result<Ini, IniError> {parse_with(input, DuplicateKeys::default())}
+
+/// Parses an INI file with the given policy for duplicated keys.
+///
+/// Sections that appear more than once are merged.
+///
+/// # Arguments
+///
+/// * `input` - The INI source
+/// * `duplicates` - How duplicated keys in a section are handled
+///
+/// # Returns
+///
+/// Returns a `Result` containing the sections, or an [IniError].
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::ini::{parse_with, DuplicateKeys};
+///
+/// let input = "[paths]\ndir = /usr\ndir = /opt";
+/// assert_eq!(parse_with(input, DuplicateKeys::Append).unwrap()["paths"]["dir"], "/usr\n/opt");
+/// assert!(parse_with(input, DuplicateKeys::Error).is_err());
+/// ```
+pub fn parse_with(input: &str, duplicates: DuplicateKeys) -> Result<Ini, IniError> {
+    let mut ini = Ini::new();
+    let mut section = String::new();
+
+    let mut lines = input.lines().enumerate();
+    while let Some((i, full_line)) = lines.next() {
+        let error = |message: String| IniError { line: i + 1, message };
+        let line = full_line.trim();
+        if line.is_empty() || line.starts_with([';', '#']) {continue;}
+
+        if let Some(header) = line.strip_prefix('[') {
+            let (name, rest) = header.split_once(']').ok_or_else(|| error("unterminated section header".into()))?;
+            if !is_comment_or_empty(rest) {return Err(error(format!("unexpected text after section `{}`", name)));}
+            section = name.trim().to_string();
+            ini.entry(section.clone()).or_default();
+            continue;
+        }
+
+        let separator = line.find(['=', ':']).ok_or_else(|| error(format!("expected `key = value`, found `{}`", line)))?;
+        let key = line[..separator].trim();
+        if key.is_empty() {return Err(error("empty key".into()));}
+        let mut raw = line[separator + 1..].trim().to_string();
+        if parse_value(&raw) == Err(UNTERMINATED) {
+            // a multi-line quoted value: keep the line ends (and the spaces before them)
+            raw = full_line.trim_start()[separator + 1..].trim_start().to_string();
+            for (_, next) in lines.by_ref() {
+                raw.push('\n');
+                raw.push_str(next);
+                if parse_value(&raw) != Err(UNTERMINATED) {break;}
+            }
+        }
+        let value = parse_value(&raw).map_err(|message| error(message.into()))?;
+
+        let keys = ini.entry(section.clone()).or_default();
+        match (keys.get_mut(key), duplicates) {
+            (None, _) | (Some(_), DuplicateKeys::KeepLast) => {keys.insert(key.to_string(), value);},
+            (Some(_), DuplicateKeys::KeepFirst) => {},
+            (Some(existing), DuplicateKeys::Append) => {existing.push('\n'); existing.push_str(&value);},
+            (Some(_), DuplicateKeys::Error) => return Err(error(format!("duplicate key `{}` in section `{}`", key, section))),
+        }
+    }
+    Ok(ini)
+}
+
+fn is_comment_or_empty(s: &str) -> bool {
+    let s = s.trim();
+    s.is_empty() || s.starts_with([';', '#'])
+}
+
+const UNTERMINATED: &str = "unterminated quoted value";
+
+// Unquotes a value or removes its inline comment
+fn parse_value(raw: &str) -> Result<String, &'static str> {
+    match raw.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let mut value = String::new();
+            let mut rest = &raw[1..];
+            loop {
+                let (part, after) = rest.split_once(quote).ok_or(UNTERMINATED)?;
+                value.push_str(part);
+                match after.strip_prefix(quote) {
+                    Some(after) => {value.push(quote); rest = after;},  // a doubled quote
+                    None => {rest = after; break;},
+                }
+            }
+            match is_comment_or_empty(rest) {
+                true => Ok(value),
+                false => Err("unexpected text after the closing quote"),
+            }
+        },
+        _ => {
+            let end = raw.char_indices()
+                .find(|&(i, c)| matches!(c, ';' | '#') && raw[..i].ends_with([' ', '\t']))
+                .map_or(raw.len(), |(i, _)| i);
+            Ok(raw[..end].trim_end().to_string())
+        },
+    }
+}
+
+/// Serializes sections back into INI text.
+///
+/// The global section is written first without a header; values that would not
+/// survive a round trip unquoted (leading/trailing spaces, comment characters, quotes, line
+/// breaks) are quoted, so the text parses back to the same values.
+pub fn serialize(ini: &Ini) -> String {
+    let mut out = String::new();
+    let sections = ini.get_key_value("").into_iter()
+        .chain(ini.iter().filter(|(name, _)| !name.is_empty()));
+
+    for (name, keys) in sections {
+        if !name.is_empty() {
+            if !out.is_empty() {out.push('\n');}
+            out.push_str(&format!("[{}]\n", name));
+        }
+        for (key, value) in keys {
+            out.push_str(&format!("{} = {}\n", key, format_value(value)));
+        }
+    }
+    out
+}
+
+fn format_value(value: &str) -> String {
+    let needs_quotes = value.trim() != value || value.contains([';', '#', '"', '\'', '\n']);
+    match (needs_quotes, value.contains('"'), value.contains('\'')) {
+        (false, _, _) => value.to_string(),
+        (true, false, _) => format!("\"{}\"", value),
+        (true, true, false) => format!("'{}'", value),
+        (true, true, true) => format!("\"{}\"", value.replace('"', "\"\"")),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let ini = parse("
+            top = 1
+            [db]
+            url = \"postgres://h;x\" ; comment
+            name = 'spaced '
+            empty =
+            path = C:\\dir#1
+            [ db ]
+            extra = yes
+        ").unwrap();
+        assert_eq!(ini[""]["top"], "1");
+        assert_eq!(ini["db"]["url"], "postgres://h;x");
+        assert_eq!(ini["db"]["name"], "spaced ");
+        assert_eq!(ini["db"]["empty"], "");
+        assert_eq!(ini["db"]["path"], "C:\\dir#1");
+        assert_eq!(ini["db"]["extra"], "yes");
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let input = "a = 1\na = 2";
+        assert_eq!(parse(input).unwrap()[""]["a"], "2");
+        assert_eq!(parse_with(input, DuplicateKeys::KeepFirst).unwrap()[""]["a"], "1");
+        assert_eq!(parse_with(input, DuplicateKeys::Error).unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("[open").is_err());
+        assert!(parse("[s] junk").is_err());
+        assert!(parse("no separator").is_err());
+        assert!(parse("= value").is_err());
+        assert!(parse("a = \"open").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "g = 1\n\n[a]\nk = \" padded \"\nlist = x\nlist = y\n\n[b]\nq = 'say \"hi\" ;'\n";
+        let ini = parse_with(input, DuplicateKeys::Append).unwrap();
+        // the appended values come back as one multi-line value
+        let text = "g = 1\n\n[a]\nk = \" padded \"\nlist = \"x\ny\"\n\n[b]\nq = 'say \"hi\" ;'\n";
+        assert_eq!(serialize(&ini), text);
+        assert_eq!(parse(text).unwrap(), ini);
+    }
+
+    #[test]
+    fn test_both_quotes_round_trip() {
+        let ini = Ini::from([("".to_string(), Section::from([
+            ("both".to_string(), "it's \"x\" ;".to_string()),
+            ("edges".to_string(), "\"'".to_string()),
+        ]))]);
+        let text = serialize(&ini);
+        assert_eq!(text, "both = \"it's \"\"x\"\" ;\"\nedges = \"\"\"'\"\n");
+        assert_eq!(parse(&text).unwrap(), ini);
+        assert_eq!(parse("a = 'it''s' ; note").unwrap()[""]["a"], "it's");
+    }
+
+    #[test]
+    fn test_multi_line_round_trip() {
+        let ini = Ini::from([("s".to_string(), Section::from([
+            ("text".to_string(), "line 1  \n\n  line \"3\"\n".to_string()),
+            ("after".to_string(), "x".to_string()),
+        ]))]);
+        let text = serialize(&ini);
+        assert_eq!(text, "[s]\nafter = x\ntext = 'line 1  \n\n  line \"3\"\n'\n");
+        assert_eq!(parse(&text).unwrap(), ini);
+
+        assert_eq!(parse("a = 'one\ntwo' ; note\nb = 2").unwrap()[""]["a"], "one\ntwo");
+        assert_eq!(parse("x = 1\na = \"open\nb = 2").unwrap_err().line, 2);
+    }
+}
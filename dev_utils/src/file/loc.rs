@@ -0,0 +1,189 @@
+// Line count and code statistics (a tiny `tokei`).
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::fmt;
+use std::fs;
+use super::Result;
+use crate::format::table::{Alignment, BorderStyle, Table};
+use crate::format::{Style, Stylize};
+
+/// Line statistics of a group of files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LocStats {
+    pub files: usize,
+    pub lines: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blank: usize,
+}
+
+impl LocStats {
+    fn merge(&mut self, other: &LocStats) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.code += other.code;
+        self.comments += other.comments;
+        self.blank += other.blank;
+    }
+}
+
+/// The result of [loc]: statistics grouped by file extension.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocReport {
+    /// Statistics per (lowercase) extension; files without extension use an empty key.
+    pub by_extension: BTreeMap<String, LocStats>,
+}
+
+impl LocReport {
+    /// Returns the sum of the statistics of every extension.
+    pub fn total(&self) -> LocStats {
+        let mut total = LocStats::default();
+        self.by_extension.values().for_each(|stats| total.merge(stats));
+        total
+    }
+
+    /// Builds a [Table] with one row per extension (sorted by lines of code) and a total row.
+    pub fn to_table(&self) -> Table {
+        let mut rows = self.by_extension.iter().collect::<Vec<_>>();
+        rows.sort_by(|a, b| b.1.code.cmp(&a.1.code).then(a.0.cmp(b.0)));
+
+        let headers = ["Extension", "Files", "Lines", "Code", "Comments", "Blank"];
+        let mut table = (1..headers.len()).fold(
+            Table::new(headers.map(|h| h.style(Style::Bold))).border(BorderStyle::Line),
+            |table, col| table.align(col, Alignment::Right),
+        );
+        let mut add_row = |name: String, s: &LocStats| {
+            table.add_row([name, s.files.to_string(), s.lines.to_string(), s.code.to_string(), s.comments.to_string(), s.blank.to_string()]);
+        };
+        rows.iter().for_each(|(ext, stats)| add_row(match ext.is_empty() {
+            true => "(none)".to_string(),
+            false => ext.to_string(),
+        }, stats));
+        add_row("Total".style(Style::Bold), &self.total());
+        table
+    }
+}
+
+impl fmt::Display for LocReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_table())
+    }
+}
+
+// (line comment prefixes, block comment delimiters)
+type CommentRules = (&'static [&'static str], Option<(&'static str, &'static str)>);
+
+fn comment_rules(ext: &str) -> CommentRules {
+    const C_BLOCK: Option<(&str, &str)> = Some(("/*", "*/"));
+    match ext {
+        "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "js" | "jsx" | "ts" | "tsx" | "java" | "go"
+        | "cs" | "swift" | "kt" | "scala" | "dart" | "zig" | "scss" => (&["//"], C_BLOCK),
+        "css" => (&[], C_BLOCK),
+        "py" | "sh" | "bash" | "zsh" | "toml" | "yaml" | "yml" | "rb" | "pl" | "r" | "env" => (&["#"], None),
+        "ini" | "cfg" => (&[";", "#"], None),
+        "sql" | "lua" | "hs" => (&["--"], None),
+        "html" | "htm" | "xml" | "svg" | "md" | "vue" => (&[], Some(("<!--", "-->"))),
+        _ => (&[], None),
+    }
+}
+
+/// Counts code, comment and blank lines of the content of a single file.
+fn count_lines(content: &str, (line_prefixes, block): CommentRules) -> LocStats {
+    let mut stats = LocStats { files: 1, ..Default::default() };
+    let mut in_block = false;
+
+    for line in content.lines().map(str::trim) {
+        stats.lines += 1;
+        match (line, block) {
+            ("", _) => stats.blank += 1,
+            (_, Some((_, end))) if in_block => {
+                stats.comments += 1;
+                in_block = !line.contains(end);
+            },
+            (_, Some((start, end))) if line.starts_with(start) => {
+                stats.comments += 1;
+                in_block = !line[start.len()..].contains(end);
+            },
+            _ if line_prefixes.iter().any(|p| line.starts_with(p)) => stats.comments += 1,
+            _ => stats.code += 1,
+        }
+    }
+    stats
+}
+
+/// Counts files and lines (code, comments, blank) under `root`, grouped by extension.
+///
+/// Hidden entries (starting with `.`) and `target` directories are skipped, as well as
+/// files that are not valid UTF-8 (binary files).
+///
+/// # Arguments
+///
+/// * `root` - The directory (or single file) to analyze
+///
+/// # Returns
+///
+/// Returns a `Result` containing the [LocReport], or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::loc;
+///
+/// let report = loc("src").unwrap();
+/// assert!(report.by_extension["rs"].code > 0);
+/// println!("{}", report);
+/// ```
+pub fn loc<P: AsRef<Path>>(root: P) -> Result<LocReport> {
+    let mut report = LocReport::default();
+    loc_internal(root.as_ref(), &mut report)?;
+    Ok(report)
+}
+
+fn loc_internal(path: &Path, report: &mut LocReport) -> Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') || (name == "target" && entry.path().is_dir()) {continue;}
+            loc_internal(&entry.path(), report)?;
+        }
+    } else if let Ok(content) = String::from_utf8(fs::read(path)?) {
+        let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let stats = count_lines(&content, comment_rules(&ext));
+        report.by_extension.entry(ext).or_default().merge(&stats);
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_rust_lines() {
+        let src = "// comment\nfn main() {\n\n    /* block\n    still */\n    let x = 1; // trailing\n}\n";
+        let stats = count_lines(src, comment_rules("rs"));
+        assert_eq!(stats, LocStats { files: 1, lines: 7, code: 3, comments: 3, blank: 1 });
+    }
+
+    #[test]
+    fn test_count_other_languages() {
+        let stats = count_lines("# title\nkey = 1\n", comment_rules("toml"));
+        assert_eq!((stats.code, stats.comments), (1, 1));
+        let stats = count_lines("<!-- a -->\n<p>\n", comment_rules("html"));
+        assert_eq!((stats.code, stats.comments), (1, 1));
+        let stats = count_lines("# not a comment\n", comment_rules("unknown"));
+        assert_eq!(stats.code, 1);
+    }
+
+    #[test]
+    fn test_report_total() {
+        let mut report = LocReport::default();
+        report.by_extension.insert("rs".into(), LocStats { files: 2, lines: 10, code: 6, comments: 2, blank: 2 });
+        report.by_extension.insert("py".into(), LocStats { files: 1, lines: 5, code: 5, comments: 0, blank: 0 });
+        assert_eq!(report.total(), LocStats { files: 3, lines: 15, code: 11, comments: 2, blank: 2 });
+        assert_eq!(report.to_table().render().lines().count(), 7);
+    }
+}
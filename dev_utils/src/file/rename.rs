@@ -0,0 +1,327 @@
+// Batch renaming of files with pattern captures and templates.
+use std::path::{Path, PathBuf};
+use std::fs;
+use super::{create, read, FileError, Result};
+
+/// Name of the undo manifest written next to the renamed files.
+pub const RENAME_MANIFEST: &str = ".rename_undo";
+
+/// A planned rename operation, computed before touching the file system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamePlan {
+    /// The directory containing the files.
+    pub dir: PathBuf,
+    /// The `(from, to)` pairs of every matched file, in sorted order.
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    /// Problems that would prevent the plan from being applied safely.
+    pub conflicts: Vec<RenameConflict>,
+}
+
+/// A problem detected while planning a batch rename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameConflict {
+    /// The target path already exists and is not being renamed itself.
+    TargetExists(PathBuf),
+    /// Two or more files would be renamed to the same path.
+    DuplicateTarget(PathBuf),
+    /// The template produced an empty or invalid file name.
+    InvalidName(String),
+}
+
+impl RenamePlan {
+    /// Returns `true` if the plan has no conflicts.
+    pub fn is_valid(&self) -> bool {self.conflicts.is_empty()}
+
+    /// Applies the plan, writing an undo manifest (see [undo_rename]) into the directory.
+    ///
+    /// Files are first moved to temporary names, so chains and swaps (`a -> b`, `b -> a`) are safe.
+    /// If a rename fails, the completed ones are undone (as far as possible) before returning the error.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the path of the undo manifest, or a `FileError`
+    /// if the plan has conflicts or a rename fails.
+    pub fn apply(&self) -> Result<PathBuf> {
+        if !self.is_valid() {
+            return Err(FileError::PathError(format!("rename plan has conflicts: {:?}", self.conflicts)));
+        }
+        let renames: Vec<_> = self.renames.iter().filter(|(from, to)| from != to).collect();
+
+        let mut staged = Vec::with_capacity(renames.len());
+        for (i, (from, _)) in renames.iter().enumerate() {
+            let tmp = self.dir.join(format!(".rename_tmp_{}_{}", std::process::id(), i));
+            if let Err(err) = fs::rename(from, &tmp) {
+                roll_back(&renames, &staged, 0);
+                return Err(err.into());
+            }
+            staged.push(tmp);
+        }
+        for (done, (tmp, (_, to))) in staged.iter().zip(&renames).enumerate() {
+            if let Err(err) = fs::rename(tmp, to) {
+                roll_back(&renames, &staged, done);
+                return Err(err.into());
+            }
+        }
+
+        let manifest = renames.iter()
+            .map(|(from, to)| format!("{}\t{}", file_name(to), file_name(from)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        create(self.dir.join(RENAME_MANIFEST), &manifest)
+    }
+}
+
+// Undoes a partially applied plan: the first `moved` files go back from their target to their
+// temporary name, then every staged file to its original name. Errors are ignored, since the
+// rename error that caused the rollback is the one reported.
+fn roll_back(renames: &[&(PathBuf, PathBuf)], staged: &[PathBuf], moved: usize) {
+    for (tmp, (_, to)) in staged.iter().zip(renames).take(moved).rev() {
+        let _ = fs::rename(to, tmp);
+    }
+    for (tmp, (from, _)) in staged.iter().zip(renames).rev() {
+        let _ = fs::rename(tmp, from);
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Plans a batch rename of the files in `dir` whose name matches `pattern`, without renaming anything.
+///
+/// The `pattern` is matched against file names (not paths): `*` matches any run of characters
+/// and `?` a single character; each wildcard is captured. The `template` supports:
+/// - `{0}`, `{1}`, ... - the wildcard captures, in order
+/// - `{num}` - a counter (starting at 1, following the sorted file names), `{num:03}` zero-padded
+/// - `{name}` - the original file stem, `{ext}` - the original extension
+///
+/// # Arguments
+///
+/// * `dir` - The directory containing the files (not searched recursively)
+/// * `pattern` - The wildcard pattern selecting the files
+/// * `template` - The template used to build the new names
+///
+/// # Returns
+///
+/// Returns a `Result` containing the [RenamePlan], or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, plan_rename};
+///
+/// create("rename_doc/IMG_001.jpg", "").unwrap();
+/// create("rename_doc/IMG_002.jpg", "").unwrap();
+/// let plan = plan_rename("rename_doc", "IMG_*.*", "photo_{num:03}.{1}").unwrap();
+/// assert!(plan.is_valid());
+/// assert!(plan.renames[1].1.ends_with("photo_002.jpg"));
+/// # std::fs::remove_dir_all("rename_doc").unwrap();
+/// ```
+pub fn plan_rename<P: AsRef<Path>>(dir: P, pattern: &str, template: &str) -> Result<RenamePlan> {
+    let dir = dir.as_ref();
+    let mut names = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name != RENAME_MANIFEST)
+        .collect::<Vec<_>>();
+    names.sort();
+
+    let mut renames = Vec::new();
+    let mut conflicts = Vec::new();
+    for name in names {
+        let Some(captures) = wildcard_captures(pattern, &name) else {continue};
+        let new_name = expand_template(template, &name, &captures, renames.len() + 1)?;
+        if new_name.is_empty() || new_name.contains(['/', '\\']) {
+            conflicts.push(RenameConflict::InvalidName(new_name));
+            continue;
+        }
+        renames.push((dir.join(&name), dir.join(new_name)));
+    }
+
+    for (i, (_, to)) in renames.iter().enumerate() {
+        if renames[..i].iter().any(|(_, other)| other == to) {
+            conflicts.push(RenameConflict::DuplicateTarget(to.clone()));
+        } else if to.exists() && !renames.iter().any(|(from, _)| from == to) {
+            conflicts.push(RenameConflict::TargetExists(to.clone()));
+        }
+    }
+    Ok(RenamePlan { dir: dir.to_path_buf(), renames, conflicts })
+}
+
+/// Renames the files in `dir` matching `pattern` according to `template` (see [plan_rename]).
+///
+/// Nothing is renamed if the plan has conflicts.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the applied [RenamePlan], or a `FileError`.
+pub fn batch_rename<P: AsRef<Path>>(dir: P, pattern: &str, template: &str) -> Result<RenamePlan> {
+    let plan = plan_rename(dir, pattern, template)?;
+    plan.apply()?;
+    Ok(plan)
+}
+
+/// Reverts a batch rename using the undo manifest written by [RenamePlan::apply].
+///
+/// The manifest is deleted once every file has been restored.
+///
+/// # Arguments
+///
+/// * `dir` - The directory where the rename was applied
+pub fn undo_rename<P: AsRef<Path>>(dir: P) -> Result<()> {
+    let dir = dir.as_ref();
+    let manifest_path = dir.join(RENAME_MANIFEST);
+    let manifest = read(&manifest_path)?;
+
+    let renames = manifest.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(new, old)| (dir.join(new), dir.join(old)))
+        .collect::<Vec<_>>();
+    RenamePlan { dir: dir.to_path_buf(), renames, conflicts: Vec::new() }.apply()?;
+
+    fs::remove_file(manifest_path)?;
+    Ok(())
+}
+
+/// Matches `text` against a wildcard `pattern` (`*` and `?`), returning the captured parts.
+pub(crate) fn wildcard_captures(pattern: &str, text: &str) -> Option<Vec<String>> {
+    fn go(p: &[char], t: &[char], caps: &mut Vec<String>) -> bool {
+        match p.first() {
+            None => t.is_empty(),
+            Some('*') => (0..=t.len()).rev().any(|n| {
+                caps.push(t[..n].iter().collect());
+                go(&p[1..], &t[n..], caps) || {caps.pop(); false}
+            }),
+            Some('?') => !t.is_empty() && {
+                caps.push(t[0].to_string());
+                go(&p[1..], &t[1..], caps) || {caps.pop(); false}
+            },
+            Some(c) => t.first() == Some(c) && go(&p[1..], &t[1..], caps),
+        }
+    }
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let mut caps = Vec::new();
+    go(&p, &t, &mut caps).then_some(caps)
+}
+
+// Expands the `{...}` placeholders of a rename template
+fn expand_template(template: &str, name: &str, captures: &[String], num: usize) -> Result<String> {
+    let path = Path::new(name);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = path.extension().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let invalid = |p: &str| FileError::PathError(format!("invalid template placeholder: {{{}}}", p));
+
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| invalid(&rest[start + 1..]))? + start;
+        let placeholder = &rest[start + 1..end];
+        let (key, width) = match placeholder.split_once(':') {
+            Some((key, width)) => (key, Some(width.parse::<usize>().map_err(|_| invalid(placeholder))?)),
+            None => (placeholder, None),
+        };
+        let value = match key {
+            "num" => num.to_string(),
+            "name" => stem.clone(),
+            "ext" => ext.clone(),
+            index => index.parse::<usize>().ok()
+                .and_then(|i| captures.get(i).cloned())
+                .ok_or_else(|| invalid(placeholder))?,
+        };
+        match width {
+            Some(w) => result.push_str(&format!("{:0>w$}", value)),
+            None => result.push_str(&value),
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::TempDir;
+
+    #[test]
+    fn test_wildcard_captures() {
+        assert_eq!(wildcard_captures("IMG_*.*", "IMG_01.a.jpg"), Some(vec!["01.a".into(), "jpg".into()]));
+        assert_eq!(wildcard_captures("f?le", "file"), Some(vec!["i".into()]));
+        assert_eq!(wildcard_captures("*.rs", "main.txt"), None);
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let caps = vec!["7".to_string()];
+        assert_eq!(expand_template("photo_{num:03}.{ext}", "a.jpg", &caps, 4).unwrap(), "photo_004.jpg");
+        assert_eq!(expand_template("{name}-{0}", "a.jpg", &caps, 1).unwrap(), "a-7");
+        assert!(expand_template("{5}", "a.jpg", &caps, 1).is_err());
+    }
+
+    #[test]
+    fn test_rename_and_undo() {
+        let dir = TempDir::new().unwrap();
+        create(dir.join("b.txt"), "B").unwrap();
+        create(dir.join("a.txt"), "A").unwrap();
+        create(dir.join("keep.md"), "K").unwrap();
+
+        let plan = batch_rename(&dir, "*.txt", "{num}_{0}.txt").unwrap();
+        assert_eq!(plan.renames.len(), 2);
+        assert_eq!(read(dir.join("1_a.txt")).unwrap(), "A");
+        assert_eq!(read(dir.join("2_b.txt")).unwrap(), "B");
+
+        undo_rename(&dir).unwrap();
+        assert_eq!(read(dir.join("a.txt")).unwrap(), "A");
+        assert!(!dir.join(RENAME_MANIFEST).exists());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let dir = TempDir::new().unwrap();
+        create(dir.join("a.txt"), "").unwrap();
+        create(dir.join("b.txt"), "").unwrap();
+        create(dir.join("taken.md"), "").unwrap();
+
+        let plan = plan_rename(&dir, "*.txt", "same.txt").unwrap();
+        assert_eq!(plan.conflicts, vec![RenameConflict::DuplicateTarget(dir.join("same.txt"))]);
+        assert!(plan.apply().is_err());
+
+        let plan = plan_rename(&dir, "a.*", "taken.md").unwrap();
+        assert_eq!(plan.conflicts, vec![RenameConflict::TargetExists(dir.join("taken.md"))]);
+    }
+
+    #[test]
+    fn test_rename_chain() {
+        let dir = TempDir::new().unwrap();
+        ["0", "1", "2"].iter().for_each(|n| {create(dir.join(format!("{n}.txt")), n).unwrap();});
+
+        // 0 -> 1, 1 -> 2, 2 -> 3: targets that are also sources are not conflicts
+        let plan = batch_rename(&dir, "*.txt", "{num}.txt").unwrap();
+        assert!(plan.is_valid());
+        assert_eq!(read(dir.join("3.txt")).unwrap(), "2");
+        assert_eq!(read(dir.join("1.txt")).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_failed_apply_rolls_back() {
+        let dir = TempDir::new().unwrap();
+        create(dir.join("a.txt"), "A").unwrap();
+        create(dir.join("b.txt"), "B").unwrap();
+
+        // the second target's directory doesn't exist, so the second move of phase 2 fails
+        let plan = RenamePlan {
+            dir: dir.path().to_path_buf(),
+            renames: vec![(dir.join("a.txt"), dir.join("b.txt")), (dir.join("b.txt"), dir.join("missing/c.txt"))],
+            conflicts: vec![],
+        };
+        assert!(plan.apply().is_err());
+        assert_eq!(read(dir.join("a.txt")).unwrap(), "A");
+        assert_eq!(read(dir.join("b.txt")).unwrap(), "B");
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(names.len(), 2, "leftover files: {:?}", names);
+        assert!(!dir.join(RENAME_MANIFEST).exists());
+    }
+}
@@ -0,0 +1,173 @@
+// Temporary files and directories removed on drop.
+use std::path::{Path, PathBuf};
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::Result;
+
+// Returns a unique path under the system temp dir: `<prefix>_<pid>_<nanos>_<counter><suffix>`
+fn unique_path(prefix: &str, suffix: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or_default();
+    std::env::temp_dir().join(format!("{}_{}_{}_{}{}",
+        prefix, std::process::id(), nanos, COUNTER.fetch_add(1, Ordering::Relaxed), suffix
+    ))
+}
+
+// Retries `create` with new unique paths while they already exist
+fn create_unique(prefix: &str, suffix: &str, create: impl Fn(&Path) -> io::Result<()>) -> Result<PathBuf> {
+    loop {
+        let path = unique_path(prefix, suffix);
+        match create(&path) {
+            Ok(()) => return Ok(path),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// A directory under the system temp dir, removed (with its contents) when dropped.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, list, TempDir};
+///
+/// let dir = TempDir::new().unwrap();
+/// create(dir.join("a.txt"), "hello").unwrap();
+/// assert_eq!(list(&dir).unwrap().len(), 1);
+///
+/// let path = dir.path().to_path_buf();
+/// drop(dir);
+/// assert!(!path.exists());
+/// ```
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempDir {
+    /// Creates a new, empty temporary directory.
+    pub fn new() -> Result<Self> {Self::with_prefix("dev_utils")}
+
+    /// Creates a new, empty temporary directory whose name starts with `prefix`.
+    pub fn with_prefix(prefix: &str) -> Result<Self> {
+        let path = create_unique(prefix, "", |path| fs::create_dir(path))?;
+        Ok(TempDir { path, keep: false })
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {&self.path}
+
+    /// Returns the path of an entry inside the directory.
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {self.path.join(path)}
+
+    /// Disables the cleanup and returns the path of the directory.
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.path.clone()
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {&self.path}
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.keep {let _ = fs::remove_dir_all(&self.path);}
+    }
+}
+
+/// An empty file under the system temp dir, removed when dropped.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{update, read, TempFile};
+///
+/// let file = TempFile::with_suffix(".json").unwrap();
+/// update(&file, "{}").unwrap();
+/// assert_eq!(read(&file).unwrap(), "{}");
+/// assert!(file.path().to_string_lossy().ends_with(".json"));
+/// ```
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempFile {
+    /// Creates a new, empty temporary file.
+    pub fn new() -> Result<Self> {Self::with_suffix("")}
+
+    /// Creates a new, empty temporary file whose name ends with `suffix` (e.g. an extension).
+    pub fn with_suffix(suffix: &str) -> Result<Self> {
+        let path = create_unique("dev_utils", suffix, |path| OpenOptions::new().write(true).create_new(true).open(path).map(drop))?;
+        Ok(TempFile { path, keep: false })
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {&self.path}
+
+    /// Disables the cleanup and returns the path of the file.
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.path.clone()
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {&self.path}
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.keep {let _ = fs::remove_file(&self.path);}
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_dir() {
+        let (a, b) = (TempDir::new().unwrap(), TempDir::with_prefix("custom").unwrap());
+        assert_ne!(a.path(), b.path());
+        assert!(b.path().file_name().unwrap().to_string_lossy().starts_with("custom_"));
+        fs::write(a.join("nested.txt"), "x").unwrap();
+
+        let path = a.path().to_path_buf();
+        drop(a);
+        assert!(!path.exists());
+
+        let kept = b.keep();
+        assert!(kept.exists());
+        fs::remove_dir(kept).unwrap();
+    }
+
+    #[test]
+    fn test_temp_file() {
+        let file = TempFile::new().unwrap();
+        let path = file.path().to_path_buf();
+        assert!(path.is_file());
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_cleanup_on_panic() {
+        let path = std::sync::Mutex::new(PathBuf::new());
+        let result = std::panic::catch_unwind(|| {
+            let dir = TempDir::new().unwrap();
+            *path.lock().unwrap() = dir.path().to_path_buf();
+            panic!("test failure");
+        });
+        assert!(result.is_err());
+        assert!(!path.lock().unwrap().exists());
+    }
+}
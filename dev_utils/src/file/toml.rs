@@ -0,0 +1,724 @@
+//! A dependency-free TOML parser with a typed value model.
+//!
+//! Supports the whole TOML 1.0 syntax used in practice: tables, dotted keys,
+//! arrays of tables (`[[bin]]`), inline tables, multi-line arrays, every string
+//! flavor (basic, literal and their multi-line forms), integers in every base,
+//! floats, booleans and datetimes (kept as written, see [TomlValue::as_datetime]).
+//!
+//! # Examples
+//! ```
+//! use dev_utils::file::toml::{self, TomlValue};
+//!
+//! let doc = toml::parse(r#"
+//! [package]
+//! name = "dev_utils"
+//! keywords = ["utils", "dev"]
+//!
+//! [[bin]]
+//! name = "tool"
+//! "#).unwrap();
+//!
+//! assert_eq!(doc["package"].get("name").and_then(TomlValue::as_str), Some("dev_utils"));
+//! assert_eq!(doc["package"].get_path("keywords[1]").and_then(TomlValue::as_str), Some("dev"));
+//! assert_eq!(doc["bin"].get_path("[0].name").and_then(TomlValue::as_str), Some("tool"));
+//! ```
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use super::{path_steps, PathStep};
+use crate::datetime::DateTime;
+
+/// A TOML table: keys mapped to values, in sorted order.
+pub type Table = BTreeMap<String, TomlValue>;
+
+/// A parsed TOML value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<TomlValue>),
+    Table(Table),
+    /// An offset/local datetime, local date or local time, exactly as written.
+    Datetime(String),
+}
+
+impl TomlValue {
+    /// Returns the string value, if this is a [TomlValue::String].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {TomlValue::String(s) => Some(s), _ => None}
+    }
+
+    /// Returns the integer value, if this is a [TomlValue::Int].
+    pub fn as_int(&self) -> Option<i64> {
+        match self {TomlValue::Int(i) => Some(*i), _ => None}
+    }
+
+    /// Returns the value as a float (integers are converted).
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            TomlValue::Float(f) => Some(*f),
+            TomlValue::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean value, if this is a [TomlValue::Bool].
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {TomlValue::Bool(b) => Some(*b), _ => None}
+    }
+
+    /// Returns the items, if this is a [TomlValue::Array].
+    pub fn as_array(&self) -> Option<&[TomlValue]> {
+        match self {TomlValue::Array(items) => Some(items), _ => None}
+    }
+
+    /// Returns the table, if this is a [TomlValue::Table].
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {TomlValue::Table(table) => Some(table), _ => None}
+    }
+
+    /// Converts a [TomlValue::Datetime] with both a date and a time into a [DateTime].
+    ///
+    /// Fractional seconds and offsets are ignored; local dates and times return `None`.
+    pub fn as_datetime(&self) -> Option<DateTime> {
+        let TomlValue::Datetime(raw) = self else {return None};
+        let (date, time) = raw.split_at_checked(10)?;
+        let time = time.get(1..9)?;
+        DateTime::from_str(&format!("{} {}", date, time)).ok()
+    }
+
+    /// Returns the value stored under `key`, if this is a table.
+    pub fn get(&self, key: &str) -> Option<&TomlValue> {
+        self.as_table()?.get(key)
+    }
+
+    /// Looks up a nested value using a path like `"package.metadata.tags[0]"`.
+    ///
+    /// Keys are separated by `.` and array items are selected with `[index]`.
+    pub fn get_path(&self, path: &str) -> Option<&TomlValue> {
+        path_steps(path)?.into_iter().try_fold(self, |value, step| match step {
+            PathStep::Key(key) => value.get(key),
+            PathStep::Index(i) => value.as_array()?.get(i),
+        })
+    }
+
+    /// Returns the name of the TOML type of the value (used in error messages).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TomlValue::String(_) => "string",
+            TomlValue::Int(_) => "integer",
+            TomlValue::Float(_) => "float",
+            TomlValue::Bool(_) => "boolean",
+            TomlValue::Array(_) => "array",
+            TomlValue::Table(_) => "table",
+            TomlValue::Datetime(_) => "datetime",
+        }
+    }
+}
+
+impl std::ops::Index<&str> for TomlValue {
+    type Output = TomlValue;
+
+    /// Returns the value of `key`, panicking if this is not a table or the key is missing.
+    fn index(&self, key: &str) -> &TomlValue {
+        self.get(key).unwrap_or_else(|| panic!("no key `{}` in TOML {}", key, self.type_name()))
+    }
+}
+
+/// Formats the value as an inline TOML value (tables become inline tables).
+impl fmt::Display for TomlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TomlValue::String(s) => write!(f, "{}", quote(s)),
+            TomlValue::Int(i) => write!(f, "{}", i),
+            TomlValue::Float(x) if x.is_nan() => write!(f, "nan"),
+            TomlValue::Float(x) if x.is_infinite() => write!(f, "{}inf", if *x < 0.0 {"-"} else {""}),
+            TomlValue::Float(x) if x.fract() == 0.0 && x.abs() < 1e16 => write!(f, "{:.1}", x),
+            TomlValue::Float(x) => write!(f, "{}", x),
+            TomlValue::Bool(b) => write!(f, "{}", b),
+            TomlValue::Datetime(raw) => write!(f, "{}", raw),
+            TomlValue::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {write!(f, ", ")?;}
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            TomlValue::Table(table) if table.is_empty() => write!(f, "{{}}"),
+            TomlValue::Table(table) => {
+                write!(f, "{{ ")?;
+                for (i, (key, value)) in table.iter().enumerate() {
+                    if i > 0 {write!(f, ", ")?;}
+                    write!(f, "{} = {}", format_key(key), value)?;
+                }
+                write!(f, " }}")
+            },
+        }
+    }
+}
+
+// Quotes a string as a TOML basic string
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// Returns the key as is if it is a valid bare key, quoted otherwise
+fn format_key(key: &str) -> String {
+    match !key.is_empty() && key.chars().all(is_bare_key_char) {
+        true => key.to_string(),
+        false => quote(key),
+    }
+}
+
+fn is_bare_key_char(c: char) -> bool {c.is_ascii_alphanumeric() || c == '_' || c == '-'}
+
+
+/// Error returned when a TOML document cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlError {
+    /// The line (1-based) where the error was found.
+    pub line: usize,
+    /// The column (1-based) where the error was found.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TOML error at line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for TomlError {}
+
+/// Parses a TOML document into its root [Table].
+///
+/// # Arguments
+///
+/// * `input` - The TOML source
+///
+/// # Returns
+///
+/// Returns a `Result` containing the root table, or a [TomlError] pointing at the
+/// offending line and column.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::toml::{parse, TomlValue};
+///
+/// let doc = parse("server = { host = \"localhost\", port = 8080 }").unwrap();
+/// assert_eq!(doc["server"]["port"], TomlValue::Int(8080));
+/// assert!(parse("key = ").is_err());
+/// ```
+pub fn parse(input: &str) -> Result<Table, TomlError> {
+    Parser { src: input, pos: 0, depth: 0 }.document()
+}
+
+impl FromStr for TomlValue {
+    type Err = TomlError;
+
+    /// Parses a whole document, returning it as a [TomlValue::Table].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {parse(s).map(TomlValue::Table)}
+}
+
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    depth: usize,  // of nested arrays and inline tables
+}
+
+// How deeply arrays and inline tables may nest
+const MAX_DEPTH: usize = 256;
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {self.src[self.pos..].chars().next()}
+
+    fn rest(&self) -> &'a str {&self.src[self.pos..]}
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {self.pos += s.len();}
+        found
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> TomlError {
+        let before = &self.src[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        TomlError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> TomlError {self.error_at(self.pos, message)}
+
+    fn expect(&mut self, s: &str) -> Result<(), TomlError> {
+        match self.eat(s) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected `{}`, found {}", s, self.describe_next()))),
+        }
+    }
+
+    fn describe_next(&self) -> String {
+        match self.peek() {
+            None => "end of input".to_string(),
+            Some('\n') | Some('\r') => "end of line".to_string(),
+            Some(c) => format!("`{}`", c),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {self.pos += 1;}
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            self.pos = self.rest().find('\n').map_or(self.src.len(), |i| self.pos + i);
+        }
+    }
+
+    // Skips whitespace, comments and newlines (between statements and inside arrays)
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            if !(self.eat("\n") || self.eat("\r\n")) {break;}
+        }
+    }
+
+    // Consumes the rest of the line after a statement: only whitespace and a comment are allowed
+    fn end_of_line(&mut self) -> Result<(), TomlError> {
+        self.skip_whitespace();
+        self.skip_comment();
+        match self.peek() {
+            None => Ok(()),
+            _ if self.eat("\n") || self.eat("\r\n") => Ok(()),
+            _ => Err(self.error(format!("expected end of line, found {}", self.describe_next()))),
+        }
+    }
+
+    fn document(mut self) -> Result<Table, TomlError> {
+        let mut root = Table::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut defined: Vec<Vec<String>> = Vec::new();
+
+        loop {
+            self.skip_blank();
+            let start = self.pos;
+            match self.peek() {
+                None => return Ok(root),
+                Some('[') if self.eat("[[") => {
+                    let path = self.key()?;
+                    self.expect("]]")?;
+                    self.end_of_line()?;
+                    let (last, parents) = path.split_last().expect("keys are never empty");
+                    let parent = table_at(&mut root, parents).map_err(|e| self.error_at(start, e))?;
+                    match parent.entry(last.clone()).or_insert_with(|| TomlValue::Array(Vec::new())) {
+                        TomlValue::Array(items) => items.push(TomlValue::Table(Table::new())),
+                        _ => return Err(self.error_at(start, format!("key `{}` is already defined and is not an array of tables", last))),
+                    }
+                    // tables nested in the previous array item can be defined again
+                    defined.retain(|t| !t.starts_with(&path));
+                    current = path;
+                },
+                Some('[') => {
+                    self.pos += 1;
+                    let path = self.key()?;
+                    self.expect("]")?;
+                    self.end_of_line()?;
+                    if defined.contains(&path) {
+                        return Err(self.error_at(start, format!("table `{}` is defined more than once", path.join("."))));
+                    }
+                    table_at(&mut root, &path).map_err(|e| self.error_at(start, e))?;
+                    defined.push(path.clone());
+                    current = path;
+                },
+                Some(_) => {
+                    let (keys, value) = self.key_value()?;
+                    self.end_of_line()?;
+                    let table = table_at(&mut root, &current).map_err(|e| self.error_at(start, e))?;
+                    insert(table, &keys, value).map_err(|e| self.error_at(start, e))?;
+                },
+            }
+        }
+    }
+
+    fn key_value(&mut self) -> Result<(Vec<String>, TomlValue), TomlError> {
+        let keys = self.key()?;
+        self.expect("=")?;
+        self.skip_whitespace();
+        Ok((keys, self.value()?))
+    }
+
+    // Parses a (possibly dotted) key, e.g. `a."b.c".'d'`
+    fn key(&mut self) -> Result<Vec<String>, TomlError> {
+        let mut keys = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"') => {self.pos += 1; self.basic_string()?},
+                Some('\'') => {self.pos += 1; self.literal_string()?},
+                _ => {
+                    let len = self.rest().find(|c| !is_bare_key_char(c)).unwrap_or(self.rest().len());
+                    if len == 0 {return Err(self.error(format!("expected a key, found {}", self.describe_next())));}
+                    self.pos += len;
+                    self.src[self.pos - len..self.pos].to_string()
+                },
+            };
+            keys.push(key);
+            self.skip_whitespace();
+            if !self.eat(".") {return Ok(keys);}
+        }
+    }
+
+    fn value(&mut self) -> Result<TomlValue, TomlError> {
+        match self.peek() {
+            Some('"') if self.eat("\"\"\"") => self.multiline_basic_string().map(TomlValue::String),
+            Some('"') => {self.pos += 1; self.basic_string().map(TomlValue::String)},
+            Some('\'') if self.eat("'''") => self.multiline_literal_string().map(TomlValue::String),
+            Some('\'') => {self.pos += 1; self.literal_string().map(TomlValue::String)},
+            Some('[' | '{') if self.depth == MAX_DEPTH => Err(self.error(format!("value nested deeper than {} levels", MAX_DEPTH))),
+            Some('[') => self.nested(Self::array),
+            Some('{') => self.nested(Self::inline_table),
+            _ if self.eat("true") => Ok(TomlValue::Bool(true)),
+            _ if self.eat("false") => Ok(TomlValue::Bool(false)),
+            Some(c) if c.is_ascii_alphanumeric() || matches!(c, '+' | '-') => self.number_or_datetime(),
+            _ => Err(self.error(format!("expected a value, found {}", self.describe_next()))),
+        }
+    }
+
+    // Parses an array or inline table (with `parse`) after its opening bracket, one level deeper
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<TomlValue, TomlError>) -> Result<TomlValue, TomlError> {
+        self.pos += 1;
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self) -> Result<TomlValue, TomlError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.eat("]") {return Ok(TomlValue::Array(items));}
+            items.push(self.value()?);
+            self.skip_blank();
+            if !self.eat(",") {
+                self.expect("]")?;
+                return Ok(TomlValue::Array(items));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<TomlValue, TomlError> {
+        let mut table = Table::new();
+        self.skip_whitespace();
+        if self.eat("}") {return Ok(TomlValue::Table(table));}
+        loop {
+            let start = self.pos;
+            let (keys, value) = self.key_value()?;
+            insert(&mut table, &keys, value).map_err(|e| self.error_at(start, e))?;
+            self.skip_whitespace();
+            if !self.eat(",") {
+                self.expect("}")?;
+                return Ok(TomlValue::Table(table));
+            }
+        }
+    }
+
+    // Parses the content of a basic string, after the opening quote
+    fn basic_string(&mut self) -> Result<String, TomlError> {
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some('\n') => return Err(self.error_at(self.pos - 1, "unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => out.push(self.escape()?),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn multiline_basic_string(&mut self) -> Result<String, TomlError> {
+        let mut out = String::new();
+        let _ = self.eat("\n") || self.eat("\r\n");  // a newline right after the delimiter is trimmed
+        loop {
+            if self.rest().starts_with("\"\"\"") {
+                // up to two quotes may directly precede the closing delimiter
+                let extra = (leading(self.rest(), '"') - 3).min(2);
+                out.extend(std::iter::repeat_n('"', extra));
+                self.pos += 3 + extra;
+                return Ok(out);
+            }
+            match self.bump() {
+                None => return Err(self.error("unterminated multi-line string")),
+                Some('\\') if self.line_ending_backslash() => {
+                    while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {self.pos += 1;}
+                },
+                Some('\\') => out.push(self.escape()?),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    // Whether the backslash just consumed is followed only by whitespace up to the end of the line
+    fn line_ending_backslash(&self) -> bool {
+        let rest = self.rest().trim_start_matches([' ', '\t']);
+        rest.starts_with('\n') || rest.starts_with("\r\n")
+    }
+
+    fn escape(&mut self) -> Result<char, TomlError> {
+        let start = self.pos - 1;
+        let c = match self.bump() {
+            Some('b') => '\u{8}',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('f') => '\u{c}',
+            Some('r') => '\r',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some(u @ ('u' | 'U')) => {
+                let len = if u == 'u' {4} else {8};
+                let hex = self.rest().get(..len).unwrap_or("");
+                let c = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == len).and_then(char::from_u32);
+                self.pos += hex.len();
+                c.ok_or_else(|| self.error_at(start, format!("invalid unicode escape `\\{}{}`", u, hex)))?
+            },
+            Some(c) => return Err(self.error_at(start, format!("invalid escape sequence `\\{}`", c))),
+            None => return Err(self.error("unterminated string")),
+        };
+        Ok(c)
+    }
+
+    // Parses the content of a literal string, after the opening quote
+    fn literal_string(&mut self) -> Result<String, TomlError> {
+        let len = self.rest().find(['\'', '\n']).ok_or_else(|| self.error("unterminated string"))?;
+        if self.rest()[len..].starts_with('\n') {return Err(self.error_at(self.pos + len, "unterminated string"));}
+        let s = self.rest()[..len].to_string();
+        self.pos += len + 1;
+        Ok(s)
+    }
+
+    fn multiline_literal_string(&mut self) -> Result<String, TomlError> {
+        let _ = self.eat("\n") || self.eat("\r\n");
+        let len = self.rest().find("'''").ok_or_else(|| self.error("unterminated multi-line string"))?;
+        // up to two quotes may directly precede the closing delimiter
+        let len = len + (leading(&self.rest()[len..], '\'') - 3).min(2);
+        let s = self.rest()[..len].to_string();
+        self.pos += len + 3;
+        Ok(s)
+    }
+
+    fn number_or_datetime(&mut self) -> Result<TomlValue, TomlError> {
+        let start = self.pos;
+        let token_len = |s: &str| s.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | ':' | '_'))).unwrap_or(s.len());
+        let mut len = token_len(self.rest());
+        let token = &self.rest()[..len];
+
+        // `1979-05-27 07:32:00`: a date followed by a space and a time
+        if is_date(token) && len == 10 && self.rest()[len..].starts_with(' ') && is_time(&self.rest()[len + 1..]) {
+            len += 1 + token_len(&self.rest()[len + 1..]);
+        }
+        let token = self.rest()[..len].to_string();
+        self.pos += len;
+
+        if is_date(&token) || is_time(&token) {return Ok(TomlValue::Datetime(token));}
+        parse_number(&token).ok_or_else(|| self.error_at(start, format!("invalid value `{}`", token)))
+    }
+}
+
+// Counts the repetitions of `c` at the start of `s`
+fn leading(s: &str, c: char) -> usize {s.len() - s.trim_start_matches(c).len()}
+
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 10 && b[4] == b'-' && b[7] == b'-' && [0, 1, 2, 3, 5, 6, 8, 9].iter().all(|&i| b[i].is_ascii_digit())
+}
+
+fn is_time(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 8 && b[2] == b':' && b[5] == b':' && [0, 1, 3, 4, 6, 7].iter().all(|&i| b[i].is_ascii_digit())
+}
+
+fn parse_number(token: &str) -> Option<TomlValue> {
+    // underscores are only allowed between digits
+    let valid_underscores = token.split('_').count() == 1 || token.as_bytes().windows(3)
+        .filter(|w| w[1] == b'_')
+        .all(|w| w[0].is_ascii_alphanumeric() && w[2].is_ascii_alphanumeric());
+    if !valid_underscores || token.starts_with('_') || token.ends_with('_') {return None;}
+    let clean = token.replace('_', "");
+
+    let (sign, digits) = match clean.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, clean.strip_prefix('+').unwrap_or(&clean)),
+    };
+    match digits {
+        "inf" => return Some(TomlValue::Float(sign as f64 * f64::INFINITY)),
+        "nan" => return Some(TomlValue::Float(f64::NAN)),
+        _ => {},
+    }
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(rest) = clean.strip_prefix(prefix) {
+            return i64::from_str_radix(rest, radix).ok().map(TomlValue::Int);
+        }
+    }
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {return None;}
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && digits.as_bytes()[1].is_ascii_digit();
+    match digits.contains(['.', 'e', 'E']) {
+        true if !leading_zero && !digits.contains(".e") && !digits.ends_with('.') => clean.parse().ok().map(TomlValue::Float),
+        false if !leading_zero => clean.parse().ok().map(TomlValue::Int),
+        _ => None,
+    }
+}
+
+// Returns the table at `path`, creating missing tables (the last item of arrays of tables is used)
+fn table_at<'t>(mut table: &'t mut Table, path: &[String]) -> Result<&'t mut Table, String> {
+    for key in path {
+        table = match table.entry(key.clone()).or_insert_with(|| TomlValue::Table(Table::new())) {
+            TomlValue::Table(inner) => inner,
+            TomlValue::Array(items) => match items.last_mut() {
+                Some(TomlValue::Table(inner)) => inner,
+                _ => return Err(format!("key `{}` is an array, not a table", key)),
+            },
+            other => return Err(format!("key `{}` is a {}, not a table", key, other.type_name())),
+        };
+    }
+    Ok(table)
+}
+
+// Inserts a value under a dotted key, failing on duplicates
+fn insert(table: &mut Table, keys: &[String], value: TomlValue) -> Result<(), String> {
+    let (last, parents) = keys.split_last().expect("keys are never empty");
+    let table = table_at(table, parents)?;
+    match table.contains_key(last) {
+        true => Err(format!("duplicate key `{}`", keys.join("."))),
+        false => {table.insert(last.clone(), value); Ok(())},
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalars() {
+        let doc = parse(r#"
+            str = "tab\there \u00e9"  # comment
+            lit = 'C:\path'
+            int = +1_000
+            hex = 0xff
+            neg = -17
+            float = 6.25e-1
+            inf = -inf
+            yes = true
+            date = 1979-05-27T07:32:00Z
+            spaced = 1979-05-27 07:32:00
+            day = 1979-05-27
+        "#).unwrap();
+        assert_eq!(doc["str"].as_str(), Some("tab\there é"));
+        assert_eq!(doc["lit"].as_str(), Some("C:\\path"));
+        assert_eq!(doc["int"], TomlValue::Int(1000));
+        assert_eq!(doc["hex"], TomlValue::Int(255));
+        assert_eq!(doc["neg"], TomlValue::Int(-17));
+        assert_eq!(doc["float"], TomlValue::Float(0.625));
+        assert_eq!(doc["inf"].as_float(), Some(f64::NEG_INFINITY));
+        assert_eq!(doc["yes"], TomlValue::Bool(true));
+        assert_eq!(doc["date"], TomlValue::Datetime("1979-05-27T07:32:00Z".into()));
+        assert_eq!(doc["spaced"].as_datetime().unwrap().to_string(), "1979-05-27 07:32:00");
+        assert_eq!(doc["day"].as_datetime(), None);
+    }
+
+    #[test]
+    fn test_multiline_strings() {
+        let doc = parse("a = \"\"\"\nline one\nline \\\n    two\"\"\"\nb = '''\nraw \\n '''\n").unwrap();
+        assert_eq!(doc["a"].as_str(), Some("line one\nline two"));
+        assert_eq!(doc["b"].as_str(), Some("raw \\n "));
+    }
+
+    #[test]
+    fn test_tables_and_arrays() {
+        let doc = parse(r#"
+            [package]
+            name = "app"
+            authors = [
+                "a",  # first
+                "b",
+            ]
+            metadata.docs.rs = { all-features = true }
+
+            [[bin]]
+            name = "one"
+
+            [[bin]]
+            name = "two"
+            [bin.extra]
+            flag = false
+
+            [dependencies]
+            log = { version = "0.4", features = ["std"] }
+        "#).unwrap();
+        let doc = TomlValue::Table(doc);
+        assert_eq!(doc.get_path("package.authors[1]").and_then(TomlValue::as_str), Some("b"));
+        assert_eq!(doc.get_path("package.metadata.docs.rs.all-features"), Some(&TomlValue::Bool(true)));
+        assert_eq!(doc["bin"].as_array().unwrap().len(), 2);
+        assert_eq!(doc.get_path("bin[1].extra.flag"), Some(&TomlValue::Bool(false)));
+        assert_eq!(doc.get_path("bin[0].extra"), None);
+        assert_eq!(doc.get_path("dependencies.log.features[0]").and_then(TomlValue::as_str), Some("std"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("a = 1\nb = \"open\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(parse("a = 1\na = 2").unwrap_err().message.contains("duplicate key"));
+        assert!(parse("[t]\n[t]").is_err());
+        assert!(parse("a = 1 b = 2").is_err());
+        assert!(parse("a = 012").is_err());
+        assert!(parse("a = 1\n[a]").is_err());
+        assert!(parse("a = [1, 2").is_err());
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let nested = |open: &str, close: &str, n: usize| format!("a = {}1{}", open.repeat(n), close.repeat(n));
+        assert!(parse(&nested("[", "]", 200)).is_ok());
+        for deep in [nested("[", "]", 100_000), nested("{ b = ", " }", 100_000)] {
+            assert!(parse(&deep).unwrap_err().message.contains("deeper than 256 levels"));
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let doc = parse("t = { s = \"q\\\"\", f = 1.0, a = [1, 2], \"odd key\" = {} }").unwrap();
+        assert_eq!(doc["t"].to_string(), r#"{ a = [1, 2], f = 1.0, "odd key" = {}, s = "q\"" }"#);
+    }
+}
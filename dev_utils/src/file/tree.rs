@@ -0,0 +1,193 @@
+// Directory tree rendering (like the `tree` command).
+use std::path::Path;
+use std::fs;
+use super::{read, GlobSet, Result};
+use crate::format::{human_bytes, Style, Stylize, BLUE};
+
+/// Options of [tree].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// The maximum depth to descend (`None` for no limit).
+    pub max_depth: Option<usize>,
+    /// Entries to skip, as `.gitignore` style patterns (e.g. `target/`, `*.log`, `/docs`).
+    pub ignore: Vec<String>,
+    /// Annotate files with their size.
+    pub sizes: bool,
+    /// Show hidden entries (starting with a `.`).
+    pub hidden: bool,
+    /// Also skip the entries matched by the `.gitignore` file of the root directory.
+    pub gitignore: bool,
+}
+
+impl TreeOptions {
+    /// Creates the default options: no depth limit, no ignore patterns, no sizes.
+    pub fn new() -> Self {Self::default()}
+
+    /// Limits the depth of the tree.
+    pub fn depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Adds a `.gitignore` style pattern of entries to skip.
+    pub fn ignore(mut self, pattern: &str) -> Self {
+        self.ignore.push(pattern.to_string());
+        self
+    }
+
+    /// Annotates files with their size.
+    pub fn sizes(mut self) -> Self {
+        self.sizes = true;
+        self
+    }
+
+    /// Shows hidden entries.
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Skips the entries matched by the root's `.gitignore`.
+    pub fn gitignore(mut self) -> Self {
+        self.gitignore = true;
+        self
+    }
+}
+
+// Ignore patterns compiled into glob sets, split by whether they only apply to directories
+struct IgnoreRules {
+    any: GlobSet,
+    dirs: GlobSet,
+}
+
+impl IgnoreRules {
+    fn new<'a>(patterns: impl Iterator<Item = &'a str>) -> Result<Self> {
+        let (mut any, mut dirs) = (Vec::new(), Vec::new());
+        for pattern in patterns.map(str::trim).filter(|p| !p.is_empty() && !p.starts_with(['#', '!'])) {
+            let (pattern, dir_only) = match pattern.strip_suffix('/') {
+                Some(pattern) => (pattern, true),
+                None => (pattern, false),
+            };
+            // patterns with a `/` are relative to the root, the others match at any depth
+            let glob = match pattern.contains('/') {
+                true => pattern.trim_start_matches('/').to_string(),
+                false => format!("**/{}", pattern),
+            };
+            match dir_only {
+                true => dirs.push(glob),
+                false => any.push(glob),
+            }
+        }
+        Ok(IgnoreRules { any: GlobSet::new(any)?, dirs: GlobSet::new(dirs)? })
+    }
+
+    fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        self.any.is_match(relative) || (is_dir && self.dirs.is_match(relative))
+    }
+}
+
+/// Renders the directory tree under `path`, like the `tree` command.
+///
+/// Entries are sorted by name, directories are highlighted and a summary line with the
+/// number of directories and files is appended.
+///
+/// # Arguments
+///
+/// * `path` - The root directory
+/// * `options` - The [TreeOptions] (depth limit, ignore patterns, sizes, ...)
+///
+/// # Returns
+///
+/// Returns a `Result` containing the rendered tree, or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{tree, TreeOptions};
+///
+/// let rendered = tree("src", &TreeOptions::new().depth(1).ignore("*.md").sizes()).unwrap();
+/// println!("{}", rendered);
+/// // src
+/// // ├── base_change.rs (13.9 KiB)
+/// // ├── config
+/// // ...
+/// ```
+pub fn tree<P: AsRef<Path>>(path: P, options: &TreeOptions) -> Result<String> {
+    let root = path.as_ref();
+    let gitignore = match options.gitignore {
+        true => read(root.join(".gitignore")).unwrap_or_default(),
+        false => String::new(),
+    };
+    let rules = IgnoreRules::new(options.ignore.iter().map(String::as_str).chain(gitignore.lines()))?;
+
+    let mut out = root.display().to_string().style(Style::Bold).color(BLUE);
+    let mut counts = (0, 0);
+    render(root, root, "", 1, options, &rules, &mut out, &mut counts)?;
+    out.push_str(&format!("\n\n{} {}, {} {}",
+        counts.0, if counts.0 == 1 {"directory"} else {"directories"},
+        counts.1, if counts.1 == 1 {"file"} else {"files"},
+    ));
+    Ok(out)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render(
+    root: &Path, dir: &Path, prefix: &str, depth: usize, options: &TreeOptions,
+    rules: &IgnoreRules, out: &mut String, counts: &mut (usize, usize),
+) -> Result<()> {
+    if options.max_depth.is_some_and(|max| depth > max) {return Ok(());}
+
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?.into_iter()
+        .filter(|entry| options.hidden || !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter(|entry| {
+            let path = entry.path();
+            !rules.is_ignored(path.strip_prefix(root).unwrap_or(&path), path.is_dir())
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for (i, entry) in entries.iter().enumerate() {
+        let last = i == entries.len() - 1;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = entry.path();
+        out.push_str(&format!("\n{}{}", prefix, if last {"└── "} else {"├── "}));
+
+        if path.is_dir() {
+            counts.0 += 1;
+            out.push_str(&name.style(Style::Bold).color(BLUE));
+            let child_prefix = format!("{}{}", prefix, if last {"    "} else {"│   "});
+            render(root, &path, &child_prefix, depth + 1, options, rules, out, counts)?;
+        } else {
+            counts.1 += 1;
+            out.push_str(&name);
+            if options.sizes {
+                let size = entry.metadata()?.len();
+                out.push_str(&format!(" ({})", human_bytes(size)).style(Style::Dim));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::strip_ansi_codes;
+
+    #[test]
+    fn test_tree() {
+        let dir = super::super::TempDir::new().unwrap();
+        for (file, content) in [("a.txt", "hello"), ("src/main.rs", ""), ("src/deep/x.rs", ""), ("target/app", ""), ("b.log", ""), (".env", "")] {
+            super::super::create(dir.join(file), content).unwrap();
+        }
+        super::super::create(dir.join(".gitignore"), "# build output\ntarget/\n").unwrap();
+
+        let options = TreeOptions::new().ignore("*.log").gitignore().sizes();
+        let rendered = strip_ansi_codes(&tree(&dir, &options).unwrap());
+        let body = rendered.lines().skip(1).collect::<Vec<_>>().join("\n");
+        assert_eq!(body, "├── a.txt (5 B)\n└── src\n    ├── deep\n    │   └── x.rs (0 B)\n    └── main.rs (0 B)\n\n2 directories, 3 files");
+
+        let rendered = strip_ansi_codes(&tree(&dir, &TreeOptions::new().depth(1).hidden()).unwrap());
+        assert!(rendered.contains(".env") && rendered.contains("target") && !rendered.contains("main.rs"));
+    }
+}
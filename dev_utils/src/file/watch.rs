@@ -0,0 +1,294 @@
+// Following files as they grow (`tail -f`), with truncation and rotation detection.
+use std::path::{Path, PathBuf};
+use std::fs::{self, File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::ControlFlow;
+use std::time::Duration;
+use crate::signals::ShutdownToken;
+use super::Result;
+
+/// Time between two polls of [watch].
+pub const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A change detected by a [Watcher].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// A complete line appended to the file (without the line terminator).
+    Line(String),
+    /// The file got shorter: it is read again from the start.
+    Truncated,
+    /// The path now points to a different file (e.g. log rotation): it is read from the start.
+    Rotated,
+}
+
+/// Polls a file for appended lines, truncation and rotation.
+///
+/// Only the file metadata is checked on each poll; the file is read only when it grows.
+/// Use [watch] for a blocking loop with a callback.
+#[derive(Debug)]
+pub struct Watcher {
+    path: PathBuf,
+    offset: u64,
+    identity: Option<(u64, u64)>,
+    partial: Vec<u8>,  // bytes of the last line until its newline arrives
+}
+
+// (device, inode) of a file, used to detect rotation
+#[cfg(unix)]
+fn identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn identity(_metadata: &Metadata) -> Option<(u64, u64)> {None}  // rotation is seen as a truncation
+
+impl Watcher {
+    /// Starts watching `path` from its current end (like `tail -f`).
+    ///
+    /// If the file does not exist yet, it is read from the start once it is created.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let (offset, identity) = match fs::metadata(&path) {
+            Ok(metadata) => (metadata.len(), identity(&metadata)),
+            Err(_) => (0, None),
+        };
+        Watcher { path, offset, identity, partial: Vec::new() }
+    }
+
+    /// Returns the path being watched.
+    pub fn path(&self) -> &Path {&self.path}
+
+    /// Checks the file once, returning the changes since the previous poll.
+    ///
+    /// A missing file (e.g. in the middle of a rotation) yields no events.
+    pub fn poll(&mut self) -> Result<Vec<WatchEvent>> {
+        let mut events = Vec::new();
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(_) => return Ok(events),
+        };
+
+        let current = identity(&metadata);
+        if self.identity.is_some() && current != self.identity {
+            events.push(WatchEvent::Rotated);
+            self.restart();
+        } else if metadata.len() < self.offset {
+            events.push(WatchEvent::Truncated);
+            self.restart();
+        }
+        self.identity = current;
+
+        if metadata.len() > self.offset {
+            let mut file = File::open(&self.path)?;
+            file.seek(SeekFrom::Start(self.offset))?;
+            let mut buffer = Vec::new();
+            self.offset += file.read_to_end(&mut buffer)? as u64;
+            self.partial.extend(buffer);
+
+            let complete = match self.partial.iter().rposition(|&b| b == b'\n') {
+                Some(last_newline) => self.partial.drain(..=last_newline).collect::<Vec<_>>(),
+                None => Vec::new(),
+            };
+            events.extend(String::from_utf8_lossy(&complete).lines().map(|line| WatchEvent::Line(line.to_string())));
+        }
+        Ok(events)
+    }
+
+    fn restart(&mut self) {
+        self.offset = 0;
+        self.partial.clear();
+    }
+}
+
+/// Follows a file, calling `callback` with every change until it returns `ControlFlow::Break`.
+///
+/// The file is polled every [WATCH_INTERVAL], starting from its current end.
+///
+/// # Arguments
+///
+/// * `path` - The file to follow
+/// * `callback` - Called with every [WatchEvent]
+///
+/// # Returns
+///
+/// Returns a `Result` containing `()` once the callback breaks, or a `FileError`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::ops::ControlFlow;
+/// use dev_utils::file::{watch, WatchEvent};
+///
+/// watch("app.log", |event| {
+///     match event {
+///         WatchEvent::Line(line) if line.contains("FATAL") => return ControlFlow::Break(()),
+///         WatchEvent::Line(line) => println!("{}", line),
+///         other => println!("-- {:?} --", other),
+///     }
+///     ControlFlow::Continue(())
+/// }).unwrap();
+/// ```
+pub fn watch<P, F>(path: P, callback: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(WatchEvent) -> ControlFlow<()>,
+{
+    watch_until(path, &ShutdownToken::new(), callback)
+}
+
+/// Like [watch], but also stops when `token` is triggered (e.g. by `Ctrl+C`, see
+/// [signals::ctrl_c_token](crate::signals::ctrl_c_token)), without waiting for the next poll.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::ops::ControlFlow;
+/// use dev_utils::{file::{watch_until, WatchEvent}, signals};
+///
+/// let shutdown = signals::ctrl_c_token().unwrap();
+/// watch_until("app.log", &shutdown, |event| {
+///     if let WatchEvent::Line(line) = event {println!("{}", line);}
+///     ControlFlow::Continue(())
+/// }).unwrap();
+/// println!("stopped following app.log");
+/// ```
+pub fn watch_until<P, F>(path: P, token: &ShutdownToken, mut callback: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(WatchEvent) -> ControlFlow<()>,
+{
+    let mut watcher = Watcher::new(path);
+    while !token.is_shutdown() {
+        for event in watcher.poll()? {
+            if callback(event).is_break() {return Ok(());}
+        }
+        token.wait_timeout(WATCH_INTERVAL);
+    }
+    Ok(())
+}
+
+/// Returns the last `n` lines of a file.
+///
+/// The file is read backwards in blocks, so only the end of large files is loaded.
+///
+/// # Arguments
+///
+/// * `path` - The file to read
+/// * `n` - The number of lines to return
+///
+/// # Returns
+///
+/// Returns a `Result` containing the lines (oldest first), or a `FileError`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, delete, tail};
+///
+/// let path = create("tail_example.log", "one\ntwo\nthree\n").unwrap();
+/// assert_eq!(tail(&path, 2).unwrap(), vec!["two", "three"]);
+/// delete(&path).unwrap();
+/// ```
+pub fn tail<P: AsRef<Path>>(path: P, n: usize) -> Result<Vec<String>> {
+    const BLOCK: u64 = 8 * 1024;
+    let mut file = File::open(path)?;
+    let mut start = file.metadata()?.len();
+    let mut buffer = Vec::new();
+
+    // read blocks from the end until there are more than `n` newlines (ignoring the final one)
+    while start > 0 && buffer.iter().filter(|&&b| b == b'\n').count() <= n {
+        let size = BLOCK.min(start);
+        start -= size;
+        let mut block = vec![0; size as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        block.extend(buffer);
+        buffer = block;
+    }
+
+    let content = String::from_utf8_lossy(&buffer);
+    let lines = content.lines().collect::<Vec<_>>();
+    Ok(lines[lines.len().saturating_sub(n)..].iter().map(|line| line.to_string()).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::TempDir;
+    use std::io::Write;
+
+    fn append(path: &Path, content: &str) {
+        fs::OpenOptions::new().append(true).create(true).open(path).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+
+    fn line(text: &str) -> WatchEvent {WatchEvent::Line(text.to_string())}
+
+    #[test]
+    fn test_appends_and_truncation() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.join("app.log");
+        fs::write(&path, "old\n").unwrap();
+        let mut watcher = Watcher::new(&path);
+        assert!(watcher.poll().unwrap().is_empty());
+
+        append(&path, "first\nsecond");
+        assert_eq!(watcher.poll().unwrap(), vec![line("first")]);
+        append(&path, " half\r\n");
+        assert_eq!(watcher.poll().unwrap(), vec![line("second half")]);
+
+        fs::write(&path, "new\n").unwrap();
+        assert_eq!(watcher.poll().unwrap(), vec![WatchEvent::Truncated, line("new")]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_rotation() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.join("app.log");
+        fs::write(&path, "before rotation, quite a long line\n").unwrap();
+        let mut watcher = Watcher::new(&path);
+
+        fs::rename(&path, dir.join("app.log.1")).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+        fs::write(&path, "rotated\n").unwrap();
+        assert_eq!(watcher.poll().unwrap(), vec![WatchEvent::Rotated, line("rotated")]);
+    }
+
+    #[test]
+    fn test_watch_until() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.join("app.log");
+        let token = ShutdownToken::new();
+        let stopper = {
+            let (token, path) = (token.clone(), path.clone());
+            std::thread::spawn(move || {
+                std::thread::sleep(WATCH_INTERVAL / 2);
+                append(&path, "one\ntwo\n");
+                std::thread::sleep(WATCH_INTERVAL * 2);
+                token.shutdown();
+            })
+        };
+        let mut events = Vec::new();
+        watch_until(&path, &token, |event| {
+            events.push(event);
+            ControlFlow::Continue(())
+        }).unwrap();
+        stopper.join().unwrap();
+        assert_eq!(events, vec![line("one"), line("two")]);
+    }
+
+    #[test]
+    fn test_tail() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.join("big.log");
+        let content = (0..5000).map(|i| format!("line {}\n", i)).collect::<String>();
+        fs::write(&path, content).unwrap();
+        assert_eq!(tail(&path, 3).unwrap(), vec!["line 4997", "line 4998", "line 4999"]);
+        assert_eq!(tail(&path, 0).unwrap(), Vec::<String>::new());
+
+        fs::write(&path, "a\nb").unwrap();
+        assert_eq!(tail(&path, 5).unwrap(), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,808 @@
+//! A dependency-free parser for the commonly used subset of YAML.
+//!
+//! Supported: block mappings and sequences nested by indentation, flow collections
+//! (`[a, b]`, `{a: 1}`), plain, single and double quoted scalars, literal (`|`) and
+//! folded (`>`) block scalars, comments and a leading `---` document marker.
+//! Anchors, aliases, tags and multiple documents are not supported.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::file::yaml::{self, YamlValue};
+//!
+//! let doc = yaml::parse("
+//! server:
+//!   host: localhost  # comment
+//!   ports: [8080, 8081]
+//! users:
+//!   - name: ana
+//!     admin: true
+//! ").unwrap();
+//!
+//! assert_eq!(doc.get_path("server.host").and_then(YamlValue::as_str), Some("localhost"));
+//! assert_eq!(doc.get_path("server.ports[1]"), Some(&YamlValue::Int(8081)));
+//! assert_eq!(doc.get_path("users[0].admin"), Some(&YamlValue::Bool(true)));
+//! ```
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use super::{path_steps, PathStep};
+use super::toml::TomlValue;
+use crate::dlog::json_escape;
+
+/// A YAML mapping: keys mapped to values, in sorted order.
+pub type Mapping = BTreeMap<String, YamlValue>;
+
+/// A parsed YAML value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum YamlValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Sequence(Vec<YamlValue>),
+    Mapping(Mapping),
+}
+
+impl YamlValue {
+    /// Returns `true` if this is [YamlValue::Null].
+    pub fn is_null(&self) -> bool {matches!(self, YamlValue::Null)}
+
+    /// Returns the string value, if this is a [YamlValue::String].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {YamlValue::String(s) => Some(s), _ => None}
+    }
+
+    /// Returns the integer value, if this is a [YamlValue::Int].
+    pub fn as_int(&self) -> Option<i64> {
+        match self {YamlValue::Int(i) => Some(*i), _ => None}
+    }
+
+    /// Returns the value as a float (integers are converted).
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            YamlValue::Float(f) => Some(*f),
+            YamlValue::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean value, if this is a [YamlValue::Bool].
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {YamlValue::Bool(b) => Some(*b), _ => None}
+    }
+
+    /// Returns the items, if this is a [YamlValue::Sequence].
+    pub fn as_sequence(&self) -> Option<&[YamlValue]> {
+        match self {YamlValue::Sequence(items) => Some(items), _ => None}
+    }
+
+    /// Returns the mapping, if this is a [YamlValue::Mapping].
+    pub fn as_mapping(&self) -> Option<&Mapping> {
+        match self {YamlValue::Mapping(map) => Some(map), _ => None}
+    }
+
+    /// Returns the value stored under `key`, if this is a mapping.
+    pub fn get(&self, key: &str) -> Option<&YamlValue> {
+        self.as_mapping()?.get(key)
+    }
+
+    /// Looks up a nested value using a path like `"a.b[2].c"`.
+    ///
+    /// Keys are separated by `.` and sequence items are selected with `[index]`.
+    pub fn get_path(&self, path: &str) -> Option<&YamlValue> {
+        path_steps(path)?.into_iter().try_fold(self, |value, step| match step {
+            PathStep::Key(key) => value.get(key),
+            PathStep::Index(i) => value.as_sequence()?.get(i),
+        })
+    }
+
+    /// Writes the value as compact JSON. NaN and infinite floats become `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::file::yaml::parse;
+    ///
+    /// let doc = parse("name: \"Ada\"\ntags: [x, 1.5]\nnone: ~").unwrap();
+    /// assert_eq!(doc.to_json(), r#"{"name":"Ada","none":null,"tags":["x",1.5]}"#);
+    /// ```
+    pub fn to_json(&self) -> String {
+        match self {
+            YamlValue::Null => "null".to_string(),
+            YamlValue::Float(x) if !x.is_finite() => "null".to_string(),
+            YamlValue::String(s) => format!("\"{}\"", json_escape(s)),
+            YamlValue::Sequence(items) => format!("[{}]", items.iter().map(YamlValue::to_json).collect::<Vec<_>>().join(",")),
+            YamlValue::Mapping(map) => {
+                let entries: Vec<String> = map.iter().map(|(key, value)| format!("\"{}\":{}", json_escape(key), value.to_json())).collect();
+                format!("{{{}}}", entries.join(","))
+            },
+            scalar => scalar.to_string(),
+        }
+    }
+
+    /// Returns the name of the type of the value (used in error messages).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            YamlValue::Null => "null",
+            YamlValue::Bool(_) => "boolean",
+            YamlValue::Int(_) => "integer",
+            YamlValue::Float(_) => "float",
+            YamlValue::String(_) => "string",
+            YamlValue::Sequence(_) => "sequence",
+            YamlValue::Mapping(_) => "mapping",
+        }
+    }
+}
+
+impl std::ops::Index<&str> for YamlValue {
+    type Output = YamlValue;
+
+    /// Returns the value of `key`, panicking if this is not a mapping or the key is missing.
+    fn index(&self, key: &str) -> &YamlValue {
+        self.get(key).unwrap_or_else(|| panic!("no key `{}` in YAML {}", key, self.type_name()))
+    }
+}
+
+/// Formats the value in flow style (`{a: 1, b: [x, y]}`), quoting strings when needed.
+impl fmt::Display for YamlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YamlValue::Null => write!(f, "null"),
+            YamlValue::Bool(b) => write!(f, "{}", b),
+            YamlValue::Int(i) => write!(f, "{}", i),
+            YamlValue::Float(x) if x.is_nan() => write!(f, ".nan"),
+            YamlValue::Float(x) if x.is_infinite() => write!(f, "{}.inf", if *x < 0.0 {"-"} else {""}),
+            YamlValue::Float(x) if x.fract() == 0.0 && x.abs() < 1e16 => write!(f, "{:.1}", x),
+            YamlValue::Float(x) => write!(f, "{}", x),
+            YamlValue::String(s) => write!(f, "{}", format_string(s)),
+            YamlValue::Sequence(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {write!(f, ", ")?;}
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            YamlValue::Mapping(map) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {write!(f, ", ")?;}
+                    write!(f, "{}: {}", format_string(key), value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+// Writes a string plain when it would be read back as the same string, double quoted otherwise
+fn format_string(s: &str) -> String {
+    let plain = !s.is_empty() && s.trim() == s
+        && !s.starts_with(['-', '?', '!', '&', '*', '|', '>', '%', '@', '`', '"', '\''])
+        && !s.contains([':', '#', ',', '[', ']', '{', '}', '\n', '\t'])
+        && resolve_plain(s) == YamlValue::String(s.to_string());
+    if plain {return s.to_string();}
+
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+
+/// Error returned when a YAML document cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YamlError {
+    /// The line (1-based) where the error was found.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "YAML error at line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for YamlError {}
+
+impl YamlError {
+    fn new(line: usize, message: impl Into<String>) -> Self {YamlError { line, message: message.into() }}
+}
+
+/// Parses a YAML document.
+///
+/// # Arguments
+///
+/// * `input` - The YAML source
+///
+/// # Returns
+///
+/// Returns a `Result` containing the root value ([YamlValue::Null] for an empty document),
+/// or a [YamlError] pointing at the offending line.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::yaml::{parse, YamlValue};
+///
+/// let doc = parse("- a\n- [b, c]\n- {d: 1}").unwrap();
+/// assert_eq!(doc.get_path("[1][0]").and_then(YamlValue::as_str), Some("b"));
+/// assert!(parse("a: 1\n  b: 2").is_err());
+/// ```
+pub fn parse(input: &str) -> Result<YamlValue, YamlError> {
+    let mut lines = Vec::new();
+    for (i, raw) in input.lines().enumerate() {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        let text = raw.trim_start_matches(' ');
+        if text.starts_with('\t') && !text.trim().is_empty() {
+            return Err(YamlError::new(i + 1, "tabs are not allowed for indentation"));
+        }
+        let end_marker = text.len() == raw.len() && matches!(text.trim_end(), "---" | "...");
+        match end_marker && lines.iter().any(|l: &Line| !l.is_blank()) {
+            true => break,
+            false if end_marker => lines.clear(),
+            false => lines.push(Line { number: i + 1, indent: raw.len() - text.len(), text: text.to_string() }),
+        }
+    }
+
+    let mut parser = Parser { lines, pos: 0, depth: 0 };
+    let value = parser.node(0)?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(line) => Err(YamlError::new(line.number, "unexpected indentation")),
+    }
+}
+
+impl FromStr for YamlValue {
+    type Err = YamlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {parse(s)}
+}
+
+/// Converts a TOML value into the equivalent YAML tree (datetimes are kept as strings).
+impl From<TomlValue> for YamlValue {
+    fn from(value: TomlValue) -> Self {
+        match value {
+            TomlValue::String(s) | TomlValue::Datetime(s) => YamlValue::String(s),
+            TomlValue::Int(i) => YamlValue::Int(i),
+            TomlValue::Float(f) => YamlValue::Float(f),
+            TomlValue::Bool(b) => YamlValue::Bool(b),
+            TomlValue::Array(items) => YamlValue::Sequence(items.into_iter().map(YamlValue::from).collect()),
+            TomlValue::Table(table) => YamlValue::Mapping(table.into_iter().map(|(k, v)| (k, v.into())).collect()),
+        }
+    }
+}
+
+macro_rules! impl_from_scalar {
+    ($($t:ty => $variant:ident),+ $(,)?) => {
+        $(impl From<$t> for YamlValue {
+            fn from(value: $t) -> Self {YamlValue::$variant(value.into())}
+        })+
+    };
+}
+
+impl_from_scalar! {
+    bool => Bool,
+    i64 => Int,
+    i32 => Int,
+    f64 => Float,
+    String => String,
+    &str => String,
+}
+
+
+struct Line {
+    number: usize,
+    indent: usize,
+    /// The line without its indentation.
+    text: String,
+}
+
+impl Line {
+    fn is_blank(&self) -> bool {self.text.trim().is_empty() || self.text.starts_with('#')}
+
+    fn content(&self) -> &str {strip_comment(&self.text)}
+
+    fn is_sequence_item(&self) -> bool {
+        let content = self.content();
+        content == "-" || content.starts_with("- ")
+    }
+}
+
+struct Parser {
+    lines: Vec<Line>,
+    pos: usize,
+    depth: usize,  // of nested block nodes
+}
+
+// How deeply block nodes, and flow collections, may nest
+const MAX_DEPTH: usize = 256;
+
+impl Parser {
+    // Returns the next line with content, skipping blank and comment lines
+    fn peek(&mut self) -> Option<&Line> {
+        while self.lines.get(self.pos).is_some_and(Line::is_blank) {self.pos += 1;}
+        self.lines.get(self.pos)
+    }
+
+    // Parses the block node starting at the next line, if it is indented at least `min_indent`
+    fn node(&mut self, min_indent: usize) -> Result<YamlValue, YamlError> {
+        let Some(line) = self.peek() else {return Ok(YamlValue::Null)};
+        if line.indent < min_indent {return Ok(YamlValue::Null);}
+        let (indent, number) = (line.indent, line.number);
+        let (is_sequence, is_mapping) = (line.is_sequence_item(), split_entry(line.content()).is_some());
+        if self.depth == MAX_DEPTH {
+            return Err(YamlError::new(number, format!("nested deeper than {} levels", MAX_DEPTH)));
+        }
+
+        self.depth += 1;
+        let value = if is_sequence {self.sequence(indent)}
+            else if is_mapping {self.mapping(indent)}
+            else {
+                let content = self.lines[self.pos].content().to_string();
+                self.pos += 1;
+                self.inline_value(&content, indent, number)
+            };
+        self.depth -= 1;
+        value
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<YamlValue, YamlError> {
+        let mut items = Vec::new();
+        while let Some(line) = self.peek() {
+            if line.indent != indent || !line.is_sequence_item() {break;}
+            let number = line.number;
+            let after_dash = &line.text[1..];
+            let rest = after_dash.trim_start_matches(' ');
+            let offset = indent + 1 + after_dash.len() - rest.len();
+
+            let item = match strip_comment(rest) {
+                "" => {
+                    self.pos += 1;
+                    self.node(indent + 1)?
+                },
+                // `- key: value` and `- - item` start a nested block at the column of their content
+                content if split_entry(content).is_some() || content == "-" || content.starts_with("- ") => {
+                    self.lines[self.pos] = Line { number, indent: offset, text: rest.to_string() };
+                    self.node(indent + 1)?
+                },
+                content => {
+                    let content = content.to_string();
+                    self.pos += 1;
+                    self.inline_value(&content, indent, number)?
+                },
+            };
+            items.push(item);
+        }
+        Ok(YamlValue::Sequence(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<YamlValue, YamlError> {
+        let mut map = Mapping::new();
+        while let Some(line) = self.peek() {
+            if line.indent != indent || line.is_sequence_item() {break;}
+            let number = line.number;
+            let Some((key, rest)) = split_entry(line.content()) else {
+                return Err(YamlError::new(number, format!("expected `key: value`, found `{}`", line.content())));
+            };
+            let key = match parse_key(key) {
+                Some(key) => key,
+                None => return Err(YamlError::new(number, format!("invalid key `{}`", key))),
+            };
+            let rest = rest.to_string();
+            self.pos += 1;
+
+            let value = match rest.as_str() {
+                // a sequence may be indented at the same level as its key
+                "" if self.peek().is_some_and(|next| next.indent == indent && next.is_sequence_item()) => self.sequence(indent)?,
+                "" => self.node(indent + 1)?,
+                rest => self.inline_value(rest, indent, number)?,
+            };
+            if map.insert(key.clone(), value).is_some() {
+                return Err(YamlError::new(number, format!("duplicate key `{}`", key)));
+            }
+        }
+        Ok(YamlValue::Mapping(map))
+    }
+
+    // Parses a value written on the same line as its key or dash (`content` has no comment)
+    fn inline_value(&mut self, content: &str, indent: usize, number: usize) -> Result<YamlValue, YamlError> {
+        match content.chars().next() {
+            Some('|' | '>') => self.block_scalar(content, indent, number),
+            Some('[' | '{') => {
+                // flow collections may span several lines
+                let mut text = content.to_string();
+                while !is_balanced(&text) {
+                    match self.peek() {
+                        Some(line) if line.indent > indent || line.content().starts_with([']', '}']) => {
+                            text.push(' ');
+                            text.push_str(line.content());
+                            self.pos += 1;
+                        },
+                        _ => return Err(YamlError::new(number, "unterminated flow collection")),
+                    }
+                }
+                let mut flow = Flow { chars: text.chars().collect(), pos: 0, depth: 0 };
+                let value = flow.value().map_err(|message| YamlError::new(number, message))?;
+                flow.skip_spaces();
+                match flow.pos == flow.chars.len() {
+                    true => Ok(value),
+                    false => Err(YamlError::new(number, "unexpected content after flow collection")),
+                }
+            },
+            Some('"' | '\'') => {
+                let mut flow = Flow { chars: content.chars().collect(), pos: 0, depth: 0 };
+                let value = flow.quoted().map_err(|message| YamlError::new(number, message))?;
+                flow.skip_spaces();
+                match flow.pos == flow.chars.len() {
+                    true => Ok(YamlValue::String(value)),
+                    false => Err(YamlError::new(number, "unexpected content after quoted string")),
+                }
+            },
+            _ => Ok(resolve_plain(content)),
+        }
+    }
+
+    // Reads the lines of a `|` (literal) or `>` (folded) block scalar
+    fn block_scalar(&mut self, header: &str, indent: usize, number: usize) -> Result<YamlValue, YamlError> {
+        let (folded, chomping) = (header.starts_with('>'), &header[1..]);
+        if !matches!(chomping, "" | "-" | "+") {
+            return Err(YamlError::new(number, format!("unsupported block scalar header `{}`", header)));
+        }
+
+        let mut raw = Vec::new();
+        while let Some(line) = self.lines.get(self.pos) {
+            if !line.text.trim().is_empty() && line.indent <= indent {break;}
+            raw.push(line);
+            self.pos += 1;
+        }
+        let block_indent = raw.iter().filter(|l| !l.text.trim().is_empty()).map(|l| l.indent).min().unwrap_or(0);
+        let lines = raw.iter()
+            .map(|l| match l.text.trim().is_empty() {
+                true => String::new(),
+                false => format!("{}{}", " ".repeat(l.indent - block_indent), l.text),
+            })
+            .collect::<Vec<_>>();
+
+        let mut text = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            let separator = match (folded, lines.get(i.wrapping_sub(1))) {
+                (_, None) => "",
+                (true, Some(prev)) if !prev.is_empty() && !line.is_empty() && !line.starts_with(' ') => " ",
+                (true, Some(prev)) if prev.is_empty() && i >= 2 && lines[i - 2].is_empty() => "\n",
+                (true, Some(prev)) if prev.is_empty() => "",
+                _ => "\n",
+            };
+            text.push_str(separator);
+            text.push_str(line);
+        }
+        let body = text.trim_end_matches('\n');
+        let text = match chomping {
+            "-" => body.to_string(),
+            "+" => format!("{}{}", body, "\n".repeat(lines.iter().rev().take_while(|l| l.is_empty()).count() + 1)),
+            _ if body.is_empty() => String::new(),
+            _ => format!("{}\n", body),
+        };
+        Ok(YamlValue::String(text))
+    }
+}
+
+// Removes a trailing comment (a `#` preceded by whitespace, outside quotes) and trailing spaces
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if prev == ' ' || "[{,:".contains(prev) || i == 0 => quote = Some(c),
+            (Some(q), c) if c == q && !(q == '"' && prev == '\\') => quote = None,
+            (None, '#') if prev.is_whitespace() => return text[..i].trim_end(),
+            _ => {},
+        }
+        prev = c;
+    }
+    text.trim_end()
+}
+
+// Splits `key: value` at the first `: ` (or trailing `:`) outside quotes and brackets
+fn split_entry(content: &str) -> Option<(&str, &str)> {
+    if content.starts_with(['[', '{']) {return None;}
+    let mut quote = None;
+    let bytes = content.as_bytes();
+    for (i, c) in content.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, ':') if bytes.get(i + 1).is_none_or(|&b| b == b' ') => {
+                return Some((content[..i].trim_end(), content[i + 1..].trim()));
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+fn parse_key(key: &str) -> Option<String> {
+    match key.chars().next() {
+        Some('"' | '\'') => {
+            let mut flow = Flow { chars: key.chars().collect(), pos: 0, depth: 0 };
+            flow.quoted().ok().filter(|_| flow.pos == flow.chars.len())
+        },
+        _ => Some(key.to_string()),
+    }
+}
+
+// Whether every bracket of a flow collection is closed (ignoring quoted text)
+fn is_balanced(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '\\') => {chars.next();},
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth -= 1,
+            _ => {},
+        }
+    }
+    depth <= 0
+}
+
+// Resolves the type of a plain (unquoted) scalar
+fn resolve_plain(s: &str) -> YamlValue {
+    match s {
+        "" | "~" | "null" | "Null" | "NULL" => return YamlValue::Null,
+        "true" | "True" | "TRUE" => return YamlValue::Bool(true),
+        "false" | "False" | "FALSE" => return YamlValue::Bool(false),
+        ".inf" | ".Inf" | ".INF" | "+.inf" => return YamlValue::Float(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => return YamlValue::Float(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => return YamlValue::Float(f64::NAN),
+        _ => {},
+    }
+    let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+    if let Some(hex) = s.strip_prefix("0x") {
+        if let Ok(i) = i64::from_str_radix(hex, 16) {return YamlValue::Int(i);}
+    }
+    if let Some(oct) = s.strip_prefix("0o") {
+        if let Ok(i) = i64::from_str_radix(oct, 8) {return YamlValue::Int(i);}
+    }
+    if unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') && unsigned.chars().any(|c| c.is_ascii_digit()) {
+        if unsigned.chars().all(|c| c.is_ascii_digit()) {
+            if let Ok(i) = s.parse() {return YamlValue::Int(i);}
+        }
+        if unsigned.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+')) {
+            if let Ok(f) = s.parse() {return YamlValue::Float(f);}
+        }
+    }
+    YamlValue::String(s.to_string())
+}
+
+
+// Parser of flow collections (`[a, {b: c}]`) and quoted scalars
+struct Flow {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,  // of nested collections
+}
+
+impl Flow {
+    fn peek(&self) -> Option<char> {self.chars.get(self.pos).copied()}
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {self.pos += 1;}
+    }
+
+    fn value(&mut self) -> Result<YamlValue, String> {
+        self.skip_spaces();
+        match self.peek() {
+            Some('[' | '{') if self.depth == MAX_DEPTH => Err(format!("flow collection nested deeper than {} levels", MAX_DEPTH)),
+            Some('[' | '{') => {
+                self.depth += 1;
+                let collection = self.collection();
+                self.depth -= 1;
+                collection
+            },
+            Some('"' | '\'') => self.quoted().map(YamlValue::String),
+            _ => Ok(resolve_plain(&self.plain(&[',', ']', '}']))),
+        }
+    }
+
+    // Reads the flow sequence or mapping starting at the current character
+    fn collection(&mut self) -> Result<YamlValue, String> {
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_spaces();
+                    if self.peek() == Some(']') {self.pos += 1; return Ok(YamlValue::Sequence(items));}
+                    items.push(self.value()?);
+                    self.skip_spaces();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(']') => {},
+                        _ => return Err("expected `,` or `]` in flow sequence".into()),
+                    }
+                }
+            },
+            Some('{') => {
+                self.pos += 1;
+                let mut map = Mapping::new();
+                loop {
+                    self.skip_spaces();
+                    if self.peek() == Some('}') {self.pos += 1; return Ok(YamlValue::Mapping(map));}
+                    let key = match self.peek() {
+                        Some('"' | '\'') => self.quoted()?,
+                        _ => self.plain(&[':', ',', '}']),
+                    };
+                    self.skip_spaces();
+                    let value = match self.peek() {
+                        Some(':') => {self.pos += 1; self.value()?},
+                        _ => YamlValue::Null,
+                    };
+                    if map.insert(key.clone(), value).is_some() {return Err(format!("duplicate key `{}`", key));}
+                    self.skip_spaces();
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some('}') => {},
+                        _ => return Err("expected `,` or `}` in flow mapping".into()),
+                    }
+                }
+            },
+            _ => Err("expected `[` or `{`".into()),
+        }
+    }
+
+    // Reads a plain scalar up to one of the `stop` characters
+    fn plain(&mut self, stop: &[char]) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !stop.contains(&c)) {self.pos += 1;}
+        self.chars[start..self.pos].iter().collect::<String>().trim().to_string()
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let quote = self.peek().ok_or("expected a quoted string")?;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = self.peek().ok_or("unterminated quoted string")?;
+            self.pos += 1;
+            match (quote, c) {
+                ('\'', '\'') if self.peek() == Some('\'') => {self.pos += 1; out.push('\'');},
+                (q, c) if c == q => return Ok(out),
+                ('"', '\\') => out.push(self.escape()?),
+                (_, c) => out.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, String> {
+        let c = self.peek().ok_or("unterminated quoted string")?;
+        self.pos += 1;
+        let hex_len = match c {
+            'n' => return Ok('\n'),
+            't' => return Ok('\t'),
+            'r' => return Ok('\r'),
+            '0' => return Ok('\0'),
+            '"' | '\\' | '/' | ' ' => return Ok(c),
+            'x' => 2,
+            'u' => 4,
+            'U' => 8,
+            c => return Err(format!("invalid escape sequence `\\{}`", c)),
+        };
+        let hex = self.chars.get(self.pos..self.pos + hex_len).map(|h| h.iter().collect::<String>()).unwrap_or_default();
+        self.pos += hex_len;
+        u32::from_str_radix(&hex, 16).ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("invalid escape sequence `\\{}{}`", c, hex))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalars() {
+        let doc = parse("
+            a: 1
+            b: -2.5
+            c: true
+            d: ~
+            e: plain text # comment
+            f: \"quoted # not a comment\\n\"
+            g: 'it''s'
+            h: http://example.com
+            i: 0x1F
+            j: 1.2.3
+        ").unwrap();
+        assert_eq!(doc["a"], YamlValue::Int(1));
+        assert_eq!(doc["b"], YamlValue::Float(-2.5));
+        assert_eq!(doc["c"], YamlValue::Bool(true));
+        assert!(doc["d"].is_null());
+        assert_eq!(doc["e"].as_str(), Some("plain text"));
+        assert_eq!(doc["f"].as_str(), Some("quoted # not a comment\n"));
+        assert_eq!(doc["g"].as_str(), Some("it's"));
+        assert_eq!(doc["h"].as_str(), Some("http://example.com"));
+        assert_eq!(doc["i"], YamlValue::Int(31));
+        assert_eq!(doc["j"].as_str(), Some("1.2.3"));
+    }
+
+    #[test]
+    fn test_nesting() {
+        let doc = parse("---
+# leading comment
+name: app
+deps:
+- log
+- serde
+matrix:
+  - os: linux
+    versions: [1, 2]
+  - os: mac
+    extra:
+      deep: yes
+  -
+    - nested
+empty:
+").unwrap();
+        assert_eq!(doc.get_path("deps[1]").and_then(YamlValue::as_str), Some("serde"));
+        assert_eq!(doc.get_path("matrix[0].versions[1]"), Some(&YamlValue::Int(2)));
+        assert_eq!(doc.get_path("matrix[1].extra.deep").and_then(YamlValue::as_str), Some("yes"));
+        assert_eq!(doc.get_path("matrix[2][0]").and_then(YamlValue::as_str), Some("nested"));
+        assert_eq!(doc.get_path("empty"), Some(&YamlValue::Null));
+        assert_eq!(doc.get_path("matrix[9]"), None);
+    }
+
+    #[test]
+    fn test_block_scalars() {
+        let doc = parse("literal: |\n  line 1\n    indented\n\n  line 3\nfolded: >-\n  one\n  two\n\n  three\nnext: 1\n").unwrap();
+        assert_eq!(doc["literal"].as_str(), Some("line 1\n  indented\n\nline 3\n"));
+        assert_eq!(doc["folded"].as_str(), Some("one two\nthree"));
+        assert_eq!(doc["next"], YamlValue::Int(1));
+    }
+
+    #[test]
+    fn test_flow_collections() {
+        let doc = parse("m: {a: 1, 'b c': [x, \"y, z\"], d: {}}\nlong: [\n  1,\n  2\n]").unwrap();
+        assert_eq!(doc.get_path("m.b c[1]").and_then(YamlValue::as_str), Some("y, z"));
+        assert_eq!(doc.get_path("m.d"), Some(&YamlValue::Mapping(Mapping::new())));
+        assert_eq!(doc["long"].as_sequence().map(<[_]>::len), Some(2));
+        assert_eq!(doc["m"].to_string(), "{a: 1, b c: [x, \"y, z\"], d: {}}");
+        // escaped quotes don't end a quoted string
+        assert_eq!(parse(r#"q: ["\"]", "]"]"#).unwrap()["q"].as_sequence().map(<[_]>::len), Some(2));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse("a: 1\n  b: 2").unwrap_err().line, 2);
+        assert!(parse("a: 1\na: 2").unwrap_err().message.contains("duplicate key"));
+        assert!(parse("a: [1, 2").is_err());
+        assert!(parse("a:\n\tb: 1").is_err());
+        assert!(parse("a: 1\njust text").is_err());
+        assert_eq!(parse("").unwrap(), YamlValue::Null);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let nested = |open: &str, close: &str, n: usize| format!("a: {}1{}", open.repeat(n), close.repeat(n));
+        assert!(parse(&nested("[", "]", 200)).is_ok());
+        for deep in [nested("[", "]", 100_000), nested("{b: ", "}", 100_000), "- ".repeat(1000) + "x"] {
+            assert!(parse(&deep).unwrap_err().message.contains("deeper than 256 levels"));
+        }
+    }
+}
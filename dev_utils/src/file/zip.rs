@@ -0,0 +1,505 @@
+//! Zip archives: packing a directory, listing and extracting archives.
+//!
+//! Entries are stored or deflated (with [crate::codex::gzip::deflate]). Extraction refuses
+//! entries whose path would land outside the destination (`../`, absolute paths, drive
+//! letters), so untrusted archives can't overwrite other files. Unix permissions are kept
+//! both ways. ZIP64 (archives or files over 4 GiB), encryption and split archives are not supported.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::file::{create, read, zip, TempDir};
+//!
+//! let tmp = TempDir::new().unwrap();
+//! create(tmp.join("dist/app.js"), "console.log('hi')").unwrap();
+//! create(tmp.join("dist/assets/logo.svg"), "<svg/>").unwrap();
+//!
+//! zip::create(tmp.join("dist"), tmp.join("dist.zip"), zip::ZipOptions::new()).unwrap();
+//! let names: Vec<String> = zip::list(tmp.join("dist.zip")).unwrap().into_iter().map(|e| e.name).collect();
+//! assert_eq!(names, ["app.js", "assets/", "assets/logo.svg"]);
+//!
+//! zip::extract(tmp.join("dist.zip"), tmp.join("out"), zip::ZipOptions::new()).unwrap();
+//! assert_eq!(read(tmp.join("out/assets/logo.svg")).unwrap(), "<svg/>");
+//! ```
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use crate::codex::checksum::crc32;
+use crate::codex::gzip;
+use crate::datetime::{Date, DateTime, Time};
+use super::{FileError, Result};
+
+/// How the data of an entry is compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Stored as is (method 0).
+    Stored,
+    /// Compressed with DEFLATE (method 8); files that don't shrink are stored instead.
+    #[default]
+    Deflate,
+}
+
+/// An entry of an archive, as listed in its central directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    /// The path inside the archive, `/`-separated; directories end with `/`.
+    pub name: String,
+    /// The uncompressed size, in bytes.
+    pub size: u64,
+    /// The size of the data in the archive, in bytes.
+    pub compressed_size: u64,
+    pub compression: Compression,
+    pub crc32: u32,
+    /// The modification time (zip times have a 2 seconds precision).
+    pub modified: Option<DateTime>,
+    /// The Unix permissions (`0o755`), if the archive was written on Unix.
+    pub mode: Option<u32>,
+    offset: u64,
+}
+
+impl ZipEntry {
+    /// Returns `true` if the entry is a directory.
+    pub fn is_dir(&self) -> bool {self.name.ends_with('/')}
+}
+
+type ProgressFn<'a> = Box<dyn FnMut(&ZipEntry, usize, usize) + 'a>;
+
+/// Options of [create] and [extract].
+#[derive(Default)]
+pub struct ZipOptions<'a> {
+    compression: Compression,
+    progress: Option<ProgressFn<'a>>,
+}
+
+impl<'a> ZipOptions<'a> {
+    /// Creates the default options (deflated entries, no progress callback).
+    pub fn new() -> Self {Self::default()}
+
+    /// Sets how [create] compresses the files.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Sets a callback called after each entry is written or extracted, with the entry,
+    /// the number of entries done and the total.
+    pub fn on_progress<F: FnMut(&ZipEntry, usize, usize) + 'a>(mut self, callback: F) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    fn report(&mut self, entry: &ZipEntry, done: usize, total: usize) {
+        if let Some(progress) = self.progress.as_mut() {progress(entry, done, total);}
+    }
+}
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+// Version 2.0 (deflate and directories), made on Unix (3) so the permissions are read back
+const VERSION: u16 = 20;
+const MADE_BY_UNIX: u16 = 3 << 8 | VERSION;
+// General purpose flags: bit 0 is encryption, bit 11 UTF-8 names
+const FLAG_ENCRYPTED: u16 = 1;
+const FLAG_UTF8: u16 = 1 << 11;
+
+fn invalid(message: String) -> FileError {FileError::Io(io::Error::new(io::ErrorKind::InvalidData, message))}
+
+/// Packs the contents of a directory (recursively) into a zip archive.
+///
+/// Entries are named relative to `dir` and sorted; symbolic links are followed.
+///
+/// # Arguments
+///
+/// * `dir` - The directory to pack (its own name is not part of the entry names).
+/// * `archive` - The path of the archive to write (overwritten if it exists).
+/// * `options` - The [ZipOptions] (compression, progress callback).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the written entries, or a `FileError`.
+pub fn create<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, archive: Q, mut options: ZipOptions) -> Result<Vec<ZipEntry>> {
+    let dir = dir.as_ref();
+    if !dir.is_dir() {return Err(FileError::PathError(format!("Not a directory: {}", dir.display())));}
+    let mut paths = Vec::new();
+    collect(dir, &mut paths)?;
+    let archive = archive.as_ref();
+    // an older version of the archive may be inside the directory
+    if let Ok(archive_path) = archive.canonicalize() {
+        paths.retain(|path| path.canonicalize().ok().as_ref() != Some(&archive_path));
+    }
+
+    let mut out = BufWriter::new(File::create(archive)?);
+    let (mut entries, mut offset) = (Vec::with_capacity(paths.len()), 0u64);
+    for path in &paths {
+        let metadata = fs::metadata(path)?;
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        let mut name = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        let data = match metadata.is_dir() {
+            true => {
+                name.push('/');
+                Vec::new()
+            },
+            false => fs::read(path)?,
+        };
+        let too_large = |what: &str| FileError::PathError(format!("{} is too large for a zip archive: {}", what, path.display()));
+        if data.len() > u32::MAX as usize {return Err(too_large("File"));}
+
+        let deflated = match options.compression {
+            Compression::Deflate if !data.is_empty() => Some(gzip::deflate(&data)).filter(|d| d.len() < data.len()),
+            _ => None,
+        };
+        let (compression, stored) = match &deflated {
+            Some(deflated) => (Compression::Deflate, &deflated[..]),
+            None => (Compression::Stored, &data[..]),
+        };
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .and_then(|since| DateTime::from_timestamp(since.as_secs() as i64).ok());
+        let (time, date) = dos_time(modified);
+        let entry = ZipEntry {
+            name,
+            size: data.len() as u64,
+            compressed_size: stored.len() as u64,
+            compression,
+            crc32: crc32(&data),
+            modified: from_dos_time(time, date),  // as it will be read back
+            mode: mode(&metadata),
+            offset,
+        };
+        if offset > u32::MAX as u64 {return Err(too_large("Archive"));}
+        let header = local_header(&entry);
+        out.write_all(&header)?;
+        out.write_all(stored)?;
+        offset += (header.len() + stored.len()) as u64;
+        entries.push(entry);
+        options.report(&entries[entries.len() - 1], entries.len(), paths.len());
+    }
+
+    let mut central = Vec::new();
+    entries.iter().for_each(|entry| central_header(entry, &mut central));
+    if entries.len() > u16::MAX as usize || offset > u32::MAX as u64 {
+        return Err(FileError::PathError(format!("Too many or too large files for a zip archive: {}", dir.display())));
+    }
+    let mut end = Vec::with_capacity(22);
+    end.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+    end.extend([0; 4]);  // disk numbers
+    end.extend((entries.len() as u16).to_le_bytes());
+    end.extend((entries.len() as u16).to_le_bytes());
+    end.extend((central.len() as u32).to_le_bytes());
+    end.extend((offset as u32).to_le_bytes());
+    end.extend([0; 2]);  // comment length
+    out.write_all(&central)?;
+    out.write_all(&end)?;
+    out.flush()?;
+    Ok(entries)
+}
+
+// Collects the files and directories under `dir`, sorted (a directory before its contents)
+fn collect(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.map(|entry| entry.map(|e| e.path())).collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        let is_dir = entry.is_dir();
+        paths.push(entry.clone());
+        if is_dir {collect(&entry, paths)?;}
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn mode(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &fs::Metadata) -> Option<u32> {None}
+
+// The MS-DOS date and time fields (local times in theory; dev_utils writes UTC)
+fn dos_time(time: Option<DateTime>) -> (u16, u16) {
+    match time {
+        Some(DateTime { date, time }) if date.year() >= 1980 && date.year() < 2108 => (
+            (time.hour() as u16) << 11 | (time.minute() as u16) << 5 | (time.second() as u16 / 2),
+            ((date.year() - 1980) as u16) << 9 | (date.month() as u16) << 5 | (date.day() as u16),
+        ),
+        _ => (0, 1 << 5 | 1),  // 1980-01-01 00:00:00
+    }
+}
+
+fn from_dos_time(time: u16, date: u16) -> Option<DateTime> {
+    let date = Date::new(1980 + (date >> 9) as i32, (date >> 5 & 0xf) as u8, (date & 0x1f) as u8).ok()?;
+    let time = Time::new((time >> 11) as u8, (time >> 5 & 0x3f) as u8, (time & 0x1f) as u8 * 2).ok()?;
+    Some(DateTime { date, time })
+}
+
+// The fields shared by the local and central headers, from the version needed to the name length
+fn common_fields(entry: &ZipEntry, out: &mut Vec<u8>) {
+    let (time, date) = dos_time(entry.modified);
+    out.extend(VERSION.to_le_bytes());
+    out.extend(FLAG_UTF8.to_le_bytes());
+    out.extend(match entry.compression {Compression::Stored => 0u16, Compression::Deflate => 8}.to_le_bytes());
+    out.extend(time.to_le_bytes());
+    out.extend(date.to_le_bytes());
+    out.extend(entry.crc32.to_le_bytes());
+    out.extend((entry.compressed_size as u32).to_le_bytes());
+    out.extend((entry.size as u32).to_le_bytes());
+    out.extend((entry.name.len() as u16).to_le_bytes());
+    out.extend([0; 2]);  // extra field length
+}
+
+fn local_header(entry: &ZipEntry) -> Vec<u8> {
+    let mut out = Vec::with_capacity(30 + entry.name.len());
+    out.extend(LOCAL_HEADER.to_le_bytes());
+    common_fields(entry, &mut out);
+    out.extend(entry.name.as_bytes());
+    out
+}
+
+fn central_header(entry: &ZipEntry, out: &mut Vec<u8>) {
+    out.extend(CENTRAL_HEADER.to_le_bytes());
+    out.extend(MADE_BY_UNIX.to_le_bytes());
+    common_fields(entry, out);
+    out.extend([0; 6]);  // comment length, disk number and internal attributes
+    // the Unix file type and permissions in the high half, the MS-DOS directory flag in the low one
+    let (kind, dos) = match entry.is_dir() {true => (0o040000, 0x10), false => (0o100000, 0)};
+    let default = if entry.is_dir() {0o755} else {0o644};
+    out.extend(((kind | entry.mode.unwrap_or(default)) << 16 | dos).to_le_bytes());
+    out.extend((entry.offset as u32).to_le_bytes());
+    out.extend(entry.name.as_bytes());
+}
+
+/// Lists the entries of an archive, from its central directory.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the entries in archive order, or a `FileError` if the
+/// file is not a zip archive.
+pub fn list<P: AsRef<Path>>(archive: P) -> Result<Vec<ZipEntry>> {
+    read_entries(&fs::read(archive)?)
+}
+
+// Little-endian fields of a byte slice, checked against its length
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    fn bytes(&self, at: usize, len: usize) -> Result<&[u8]> {
+        self.0.get(at..at + len).ok_or_else(|| invalid("Truncated zip archive".to_string()))
+    }
+    fn u16(&self, at: usize) -> Result<u16> {self.bytes(at, 2).map(|b| u16::from_le_bytes([b[0], b[1]]))}
+    fn u32(&self, at: usize) -> Result<u32> {self.bytes(at, 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))}
+}
+
+fn read_entries(data: &[u8]) -> Result<Vec<ZipEntry>> {
+    let fields = Fields(data);
+    // the end record is the last 22 bytes, unless the archive has a comment (up to 64 KiB)
+    let end = (0..=data.len().saturating_sub(22)).rev()
+        .take(22 + u16::MAX as usize)
+        .find(|&at| fields.u32(at).ok() == Some(END_OF_CENTRAL_DIRECTORY))
+        .ok_or_else(|| invalid("Not a zip archive (no end of central directory)".to_string()))?;
+    let count = fields.u16(end + 10)? as usize;
+    let mut at = fields.u32(end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if fields.u32(at)? != CENTRAL_HEADER {return Err(invalid("Invalid central directory header".to_string()));}
+        let made_by_unix = fields.u16(at + 4)? >> 8 == 3;
+        if fields.u16(at + 8)? & FLAG_ENCRYPTED != 0 {return Err(invalid("Encrypted zip entries are not supported".to_string()));}
+        let compression = match fields.u16(at + 10)? {
+            0 => Compression::Stored,
+            8 => Compression::Deflate,
+            method => return Err(invalid(format!("Unsupported zip compression method: {}", method))),
+        };
+        let (name_len, extra_len, comment_len) = (fields.u16(at + 28)? as usize, fields.u16(at + 30)? as usize, fields.u16(at + 32)? as usize);
+        let attributes = fields.u32(at + 38)?;
+        entries.push(ZipEntry {
+            name: String::from_utf8_lossy(fields.bytes(at + 46, name_len)?).into_owned(),
+            size: fields.u32(at + 24)? as u64,
+            compressed_size: fields.u32(at + 20)? as u64,
+            compression,
+            crc32: fields.u32(at + 16)?,
+            modified: from_dos_time(fields.u16(at + 12)?, fields.u16(at + 14)?),
+            mode: Some(attributes >> 16 & 0o777).filter(|&mode| made_by_unix && mode != 0),
+            offset: fields.u32(at + 42)? as u64,
+        });
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+// Returns the decompressed data of an entry, checking its size and CRC
+fn entry_data(data: &[u8], entry: &ZipEntry) -> Result<Vec<u8>> {
+    let fields = Fields(data);
+    let at = entry.offset as usize;
+    if fields.u32(at)? != LOCAL_HEADER {return Err(invalid(format!("Invalid local header for {}", entry.name)));}
+    let start = at + 30 + fields.u16(at + 26)? as usize + fields.u16(at + 28)? as usize;
+    let raw = fields.bytes(start, entry.compressed_size as usize)?;
+    let content = match entry.compression {
+        Compression::Stored => raw.to_vec(),
+        Compression::Deflate => gzip::inflate(raw).map_err(|err| invalid(format!("{}: {}", entry.name, err)))?,
+    };
+    if content.len() as u64 != entry.size || crc32(&content) != entry.crc32 {
+        return Err(invalid(format!("Corrupt zip entry (size or CRC mismatch): {}", entry.name)));
+    }
+    Ok(content)
+}
+
+// The relative path an entry extracts to, or `None` if it would escape the destination
+fn safe_path(name: &str) -> Option<PathBuf> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') {return None;}
+    let mut path = PathBuf::new();
+    for component in Path::new(&name).components() {
+        match component {
+            Component::Normal(part) if !part.to_string_lossy().contains(':') => path.push(part),
+            Component::CurDir => {},
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+/// Extracts an archive into a directory (created if missing), overwriting existing files.
+///
+/// Every entry is checked before anything is written: an archive with an entry outside
+/// the destination (like `../../.bashrc` or `/etc/passwd`) is refused as a whole.
+///
+/// # Arguments
+///
+/// * `archive` - The path of the zip archive.
+/// * `dest` - The directory to extract into.
+/// * `options` - The [ZipOptions] (progress callback; the compression is ignored).
+///
+/// # Returns
+///
+/// Returns a `Result` containing the extracted paths, or a `FileError` if an entry is
+/// unsafe, corrupt or uses an unsupported feature.
+pub fn extract<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, dest: Q, mut options: ZipOptions) -> Result<Vec<PathBuf>> {
+    let data = fs::read(archive)?;
+    let entries = read_entries(&data)?;
+    let targets = entries.iter()
+        .map(|entry| safe_path(&entry.name)
+            .map(|path| dest.as_ref().join(path))
+            .ok_or_else(|| FileError::PathError(format!("Unsafe path in zip archive: {}", entry.name))))
+        .collect::<Result<Vec<_>>>()?;
+
+    fs::create_dir_all(&dest)?;
+    for (i, (entry, target)) in entries.iter().zip(&targets).enumerate() {
+        match entry.is_dir() {
+            true => fs::create_dir_all(target)?,
+            false => {
+                if let Some(parent) = target.parent() {fs::create_dir_all(parent)?;}
+                fs::write(target, entry_data(&data, entry)?)?;
+            },
+        }
+        #[cfg(unix)]
+        if let Some(mode) = entry.mode {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(target, fs::Permissions::from_mode(mode))?;
+        }
+        options.report(entry, i + 1, entries.len());
+    }
+    Ok(targets)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{create as create_file, read, TempDir};
+
+    // An archive with a single stored entry, written by hand
+    fn single_entry(name: &str, content: &[u8]) -> Vec<u8> {
+        let entry = ZipEntry {
+            name: name.to_string(), size: content.len() as u64, compressed_size: content.len() as u64,
+            compression: Compression::Stored, crc32: crc32(content), modified: None, mode: None, offset: 0,
+        };
+        let mut data = local_header(&entry);
+        data.extend(content);
+        let central_start = data.len();
+        central_header(&entry, &mut data);
+        let central_len = data.len() - central_start;
+        data.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        data.extend([0, 0, 0, 0, 1, 0, 1, 0]);
+        data.extend((central_len as u32).to_le_bytes());
+        data.extend((central_start as u32).to_le_bytes());
+        data.extend([0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let log = "build ok\n".repeat(500);
+        create_file(tmp.join("dist/build.log"), &log).unwrap();
+        create_file(tmp.join("dist/empty.txt"), "").unwrap();
+        create_file(tmp.join("dist/bin/run.sh"), "#!/bin/sh\necho run\n").unwrap();
+        fs::create_dir_all(tmp.join("dist/cache")).unwrap();
+
+        let mut progress = Vec::new();
+        let options = ZipOptions::new().on_progress(|entry, done, total| progress.push((entry.name.clone(), done, total)));
+        let written = create(tmp.join("dist"), tmp.join("dist.zip"), options).unwrap();
+        assert_eq!(progress.last(), Some(&("empty.txt".to_string(), 5, 5)));
+        assert_eq!(list(tmp.join("dist.zip")).unwrap(), written);
+
+        let names: Vec<&str> = written.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["bin/", "bin/run.sh", "build.log", "cache/", "empty.txt"]);
+        let log_entry = &written[2];
+        assert_eq!((log_entry.compression, log_entry.size), (Compression::Deflate, log.len() as u64));
+        assert!(log_entry.compressed_size < 100);
+        assert_eq!(written[4].compression, Compression::Stored);  // nothing to compress
+
+        let extracted = extract(tmp.join("dist.zip"), tmp.join("out"), ZipOptions::new()).unwrap();
+        assert_eq!(extracted.len(), 5);
+        assert_eq!(read(tmp.join("out/build.log")).unwrap(), log);
+        assert_eq!(read(tmp.join("out/bin/run.sh")).unwrap(), "#!/bin/sh\necho run\n");
+        assert!(tmp.join("out/cache").is_dir());
+
+        let stored = create(tmp.join("dist"), tmp.join("stored.zip"), ZipOptions::new().compression(Compression::Stored)).unwrap();
+        assert!(stored.iter().all(|e| e.compression == Compression::Stored && e.size == e.compressed_size));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = TempDir::new().unwrap();
+        create_file(tmp.join("src/run.sh"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(tmp.join("src/run.sh"), fs::Permissions::from_mode(0o750)).unwrap();
+        let entries = create(tmp.join("src"), tmp.join("a.zip"), ZipOptions::new()).unwrap();
+        assert_eq!(entries[0].mode, Some(0o750));
+        extract(tmp.join("a.zip"), tmp.join("out"), ZipOptions::new()).unwrap();
+        assert_eq!(fs::metadata(tmp.join("out/run.sh")).unwrap().permissions().mode() & 0o777, 0o750);
+    }
+
+    #[test]
+    fn test_unsafe_paths() {
+        assert_eq!(safe_path("a/./b.txt"), Some(PathBuf::from("a/b.txt")));
+        assert_eq!(safe_path("dir\\file.txt"), Some(PathBuf::from("dir/file.txt")));
+        for name in ["../evil.txt", "a/../../evil.txt", "/etc/passwd", "\\\\server\\share", "C:/Windows/evil.dll", "..\\evil"] {
+            assert_eq!(safe_path(name), None, "{}", name);
+        }
+
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.join("evil.zip"), single_entry("../evil.txt", b"evil")).unwrap();
+        assert_eq!(list(tmp.join("evil.zip")).unwrap()[0].name, "../evil.txt");
+        let error = extract(tmp.join("evil.zip"), tmp.join("out"), ZipOptions::new()).unwrap_err();
+        assert_eq!(error.to_string(), "Path error: Unsafe path in zip archive: ../evil.txt");
+        assert!(!tmp.path().join("evil.txt").exists() && !tmp.join("out").exists());
+    }
+
+    #[test]
+    fn test_invalid_archives() {
+        let tmp = TempDir::new().unwrap();
+        let mut data = single_entry("ok.txt", b"fine");
+        data[30 + "ok.txt".len()] ^= 1;
+        fs::write(tmp.join("corrupt.zip"), &data).unwrap();
+        assert!(list(tmp.join("corrupt.zip")).is_ok());
+        let error = extract(tmp.join("corrupt.zip"), tmp.join("out"), ZipOptions::new()).unwrap_err();
+        assert_eq!(error.to_string(), "IO error: Corrupt zip entry (size or CRC mismatch): ok.txt");
+
+        assert!(list(tmp.join("missing.zip")).is_err());
+        fs::write(tmp.join("text.zip"), "not a zip").unwrap();
+        assert!(list(tmp.join("text.zip")).unwrap_err().to_string().contains("Not a zip archive"));
+    }
+}
@@ -10,6 +10,14 @@
 //! - Text styling (bold, italic, underline, etc.)
 //! - ANSI escape code handling
 //! - Utilities for stripping ANSI codes and calculating visual string length
+//! - ANSI-aware [wrap] and [indent] of styled text
+//! - [hexdump] and [hexdump_diff] of binary data, highlighting non-printable or differing bytes
+//! - [chart]s (sparklines, bar and line charts) drawn with block characters
+//! - [num] formatting of numbers (digit grouping, SI suffixes, ordinals)
+//! - [table] rendering with ANSI-aware column alignment
+//! - [term] detection (TTY, `NO_COLOR`, `CLICOLOR_FORCE`) and a global color mode
+//! - Gradients ([gradient], [rainbow], [block_gradient]) applied per character
+//! - A [TextStyle] builder that merges colors and styles into a single escape sequence
 //!
 //! # Examples
 //! ```
@@ -19,7 +27,22 @@
 //! let text = "Hello, World!";
 //! println!("{}", text.color(RED).on_color(WHITE).style(Style::Bold));
 //! ```
-use std::fmt;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use float::Float;
+
+pub mod chart;
+pub mod num;
+pub mod table;
+pub mod term;
+mod hexdump;
+#[cfg(any(test, not(feature = "std")))]
+mod float;
+
+pub use hexdump::{hexdump, hexdump_diff};
 
 
 /// Represents an RGB color.
@@ -68,6 +91,58 @@ impl Color {
     pub fn as_bg(&self) -> String {
         format!("\x1b[48;2;{};{};{}m", self.r, self.g, self.b)
     }
+
+    /// Linearly interpolates between this color and `other`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The color to interpolate towards
+    /// * `t` - The interpolation factor (clamped to 0.0..=1.0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::format::{Color, BLACK, WHITE};
+    ///
+    /// assert_eq!(BLACK.lerp(WHITE, 0.5), Color::new(128, 128, 128));
+    /// ```
+    pub fn lerp(&self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b))
+    }
+
+    /// Creates a `Color` from HSV components.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Hue in degrees (wraps around 360)
+    /// * `s` - Saturation (0.0..=1.0)
+    /// * `v` - Value (0.0..=1.0)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::format::{Color, RED, GREEN};
+    ///
+    /// assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), RED);
+    /// assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), GREEN);
+    /// ```
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Color {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let c = v * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let to_u8 = |n: f32| ((n + v - c) * 255.0).round() as u8;
+        Color::new(to_u8(r), to_u8(g), to_u8(b))
+    }
 }
 
 
@@ -109,6 +184,11 @@ macro_rules! create_style_enum {
             pub fn code(&self) -> String {
                 match self {$(Style::$style => format!("\x1b[{}m", $code),)*}
             }
+
+            /// Returns the numeric SGR parameter of the style (e.g. `1` for bold).
+            pub fn sgr(&self) -> u8 {
+                match self {$(Style::$style => $code,)*}
+            }
         }
     };
 }
@@ -138,9 +218,9 @@ pub trait Stylize {
 macro_rules! impl_stylize {
     ($($t:ty)*) => ($(
         impl Stylize for $t {
-            fn color(&self, color: Color) -> String {format!("{}{}\x1b[0m", color.as_fg(), self)}
-            fn on_color(&self, color: Color) -> String {format!("{}{}\x1b[0m", color.as_bg(), self)}
-            fn style(&self, style: Style) -> String {format!("{}{}\x1b[0m", style.code(), self)}
+            fn color(&self, color: Color) -> String {paint(&color.as_fg(), self)}
+            fn on_color(&self, color: Color) -> String {paint(&color.as_bg(), self)}
+            fn style(&self, style: Style) -> String {paint(&style.code(), self)}
         }
     )*)
 }
@@ -148,6 +228,166 @@ macro_rules! impl_stylize {
 // The `impl_stylize!` macro implements the Stylize trait for str and String.
 impl_stylize! { str String }
 
+/// A composable text style that renders as a single ANSI escape sequence.
+///
+/// Unlike chaining [Stylize] calls (which nests one escape sequence and one reset per call),
+/// a `TextStyle` merges every attribute into one sequence. When the styled text itself
+/// contains resets (e.g. an inner colored word), the style is re-applied after each of them,
+/// so the outer style keeps applying to the rest of the text.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::{TextStyle, Stylize, RED, BLACK, BLUE};
+///
+/// let style = TextStyle::new().bold().italic().fg(RED).bg(BLACK);
+/// assert_eq!(style.sequence(), "\x1b[1;3;38;2;255;0;0;48;2;0;0;0m");
+/// println!("{}", style.apply(&format!("outer {} outer", "inner".color(BLUE))));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    styles: Vec<Style>,
+    fg: Option<Color>,
+    bg: Option<Color>,
+}
+
+impl TextStyle {
+    /// Creates an empty `TextStyle`.
+    pub fn new() -> Self {Self::default()}
+
+    /// Adds a [Style] (duplicates are ignored).
+    pub fn style(mut self, style: Style) -> Self {
+        if !self.styles.contains(&style) {self.styles.push(style);}
+        self
+    }
+
+    /// Adds the [Style::Bold] style.
+    pub fn bold(self) -> Self {self.style(Style::Bold)}
+    /// Adds the [Style::Dim] style.
+    pub fn dim(self) -> Self {self.style(Style::Dim)}
+    /// Adds the [Style::Italic] style.
+    pub fn italic(self) -> Self {self.style(Style::Italic)}
+    /// Adds the [Style::Underline] style.
+    pub fn underline(self) -> Self {self.style(Style::Underline)}
+
+    /// Sets the foreground color (replacing any previous one).
+    pub fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Sets the background color (replacing any previous one).
+    pub fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Returns `true` if no attribute has been set.
+    pub fn is_empty(&self) -> bool {self.styles.is_empty() && self.fg.is_none() && self.bg.is_none()}
+
+    /// Returns the merged escape sequence (empty if the style is empty).
+    ///
+    /// The sequence is always returned, regardless of the global color mode.
+    pub fn sequence(&self) -> String {
+        if self.is_empty() {return String::new();}
+        let params = self.styles.iter().map(|s| s.sgr().to_string())
+            .chain(self.fg.map(|Color { r, g, b }| format!("38;2;{r};{g};{b}")))
+            .chain(self.bg.map(|Color { r, g, b }| format!("48;2;{r};{g};{b}")))
+            .collect::<Vec<_>>();
+        format!("\x1b[{}m", params.join(";"))
+    }
+
+    /// Applies the style to the text, honoring the global color mode (see [term]).
+    pub fn apply(&self, text: &str) -> String {
+        match term::colors_enabled() && !self.is_empty() {
+            true => wrap_sequence(&self.sequence(), text),
+            false => text.to_string(),
+        }
+    }
+}
+
+// Wraps the text in the sequence, re-applying it after every inner reset
+fn wrap_sequence(sequence: &str, text: &str) -> String {
+    let body = text.replace("\x1b[0m", &format!("\x1b[0m{}", sequence));
+    format!("{}{}\x1b[0m", sequence, body)
+}
+
+/// Wraps the text with the given escape code and a reset, unless colors are disabled (see [term]).
+fn paint(code: &str, text: &str) -> String {
+    match term::colors_enabled() {
+        true => format!("{}{}\x1b[0m", code, text),
+        false => text.to_string(),
+    }
+}
+
+/// Colors each character of the text with a linear gradient from `start` to `end`.
+///
+/// Whitespace is kept uncolored, but still counts as a step of the gradient.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::{gradient, strip_ansi_codes, RED, BLUE};
+///
+/// let text = gradient("Hello, World!", RED, BLUE);
+/// assert_eq!(strip_ansi_codes(&text), "Hello, World!");
+/// ```
+pub fn gradient(text: &str, start: Color, end: Color) -> String {
+    let steps = text.chars().count().saturating_sub(1).max(1) as f32;
+    paint_chars(text, |i| start.lerp(end, i as f32 / steps))
+}
+
+/// Colors each character of the text with the colors of the rainbow (a full hue sweep).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::{rainbow, visual_length};
+///
+/// assert_eq!(visual_length(&rainbow("Rainbow")), 7);
+/// ```
+pub fn rainbow(text: &str) -> String {
+    let len = text.chars().count().max(1) as f32;
+    paint_chars(text, |i| Color::from_hsv(360.0 * i as f32 / len, 1.0, 1.0))
+}
+
+// Applies a per-character foreground color, leaving whitespace untouched
+fn paint_chars(text: &str, color_at: impl Fn(usize) -> Color) -> String {
+    text.chars().enumerate().map(|(i, c)| match c.is_whitespace() {
+        true => c.to_string(),
+        false => c.to_string().color(color_at(i)),
+    }).collect()
+}
+
+/// Renders a rectangle of `width` x `height` cells filled with a bilinear gradient between four corner colors.
+///
+/// Each cell is drawn as two full blocks (`██`) so it looks roughly square in a terminal.
+/// Rows are separated by `\n` (no trailing newline).
+///
+/// # Arguments
+///
+/// * `width` - The number of cells per row
+/// * `height` - The number of rows
+/// * `corners` - The colors of the (top-left, top-right, bottom-left, bottom-right) corners
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::{block_gradient, RED, GREEN, BLUE, CYAN};
+///
+/// let block = block_gradient(8, 4, (RED, BLUE, GREEN, CYAN));
+/// assert_eq!(block.lines().count(), 4);
+/// ```
+pub fn block_gradient(width: usize, height: usize, corners: (Color, Color, Color, Color)) -> String {
+    let (top_left, top_right, bottom_left, bottom_right) = corners;
+    let factor = |i: usize, n: usize| i as f32 / n.saturating_sub(1).max(1) as f32;
+
+    (0..height).map(|y| {
+        let (left, right) = (top_left.lerp(bottom_left, factor(y, height)), top_right.lerp(bottom_right, factor(y, height)));
+        (0..width).map(|x| "██".color(left.lerp(right, factor(x, width)))).collect::<String>()
+    }).collect::<Vec<_>>().join("\n")
+}
+
 /// Removes ANSI escape codes from a string.
 ///
 /// This function uses a finite state machine to identify and remove ANSI escape sequences,
@@ -230,3 +470,221 @@ pub fn strip_ansi_codes(s: &str) -> String {
 pub fn visual_length(s: &str) -> usize {
     strip_ansi_codes(s).chars().count()
 }
+
+/// Wraps text to lines of at most `width` visible characters, breaking at spaces.
+///
+/// ANSI escape codes don't count towards the width. A style that is still active at the
+/// end of a line is reset there and opened again on the next line, so every line can be
+/// printed (or prefixed) on its own. Existing line breaks and the indentation of each
+/// line are kept, and words longer than `width` are split.
+///
+/// # Arguments
+///
+/// * `text` - The text to wrap, which may contain ANSI escape codes
+/// * `width` - The maximum number of visible characters of a line (at least 1)
+///
+/// # Returns
+///
+/// A `String` with the wrapped lines, separated by `\n`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::wrap;
+///
+/// assert_eq!(wrap("the quick brown fox", 10), "the quick\nbrown fox");
+/// assert_eq!(wrap("\x1b[1mbold words\x1b[0m", 5), "\x1b[1mbold\x1b[0m\n\x1b[1mwords\x1b[0m");
+/// ```
+pub fn wrap(text: &str, width: usize) -> String {
+    let width = width.max(1);
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.split('\n') {
+        let paragraph = paragraph.strip_suffix('\r').unwrap_or(paragraph);
+        let indent = &paragraph[..paragraph.len() - paragraph.trim_start_matches([' ', '\t']).len()];
+        let (mut line, mut line_width) = (indent.to_string(), visual_length(indent));
+        let mut empty = true;
+        for word in paragraph.split([' ', '\t']).filter(|word| !word.is_empty()) {
+            let word_width = visual_length(word);
+            if !empty && line_width + 1 + word_width > width {
+                lines.push(core::mem::take(&mut line));
+                (line_width, empty) = (0, true);
+            }
+            if !empty {
+                line.push(' ');
+                line_width += 1;
+            }
+            // a word that doesn't fit on a line of its own is split
+            for c in split_escapes(word) {
+                let c_width = visual_length(c);
+                if line_width + c_width > width && line_width > 0 {
+                    lines.push(core::mem::take(&mut line));
+                    line_width = 0;
+                }
+                line.push_str(c);
+                line_width += c_width;
+            }
+            empty = false;
+        }
+        lines.push(line);
+    }
+
+    // carries the styles across the line breaks
+    let mut active = String::new();
+    let last = lines.len() - 1;
+    lines.into_iter().enumerate().map(|(i, line)| {
+        let mut out = active.clone() + &line;
+        for sequence in split_escapes(&line).filter(|s| s.starts_with('\x1b')) {
+            match sequence {
+                "\x1b[0m" | "\x1b[m" => active.clear(),
+                _ => active.push_str(sequence),
+            }
+        }
+        if !active.is_empty() && i != last {out.push_str("\x1b[0m");}
+        out
+    }).collect::<Vec<_>>().join("\n")
+}
+
+// Splits a string into its characters and escape sequences (`ESC [ params m`)
+fn split_escapes(s: &str) -> impl Iterator<Item = &str> {
+    let mut rest = s;
+    core::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, first) = chars.next()?;
+        let end = match first == '\x1b' && rest[1..].starts_with('[') {
+            true => rest[2..].find(|c: char| !(c.is_ascii_digit() || c == ';')).map_or(rest.len(), |i| i + 3),
+            false => chars.next().map_or(rest.len(), |(i, _)| i),
+        };
+        let (token, tail) = rest.split_at(end.min(rest.len()));
+        rest = tail;
+        Some(token)
+    })
+}
+
+/// Adds a prefix to every line of a string that has visible characters.
+///
+/// Blank lines (including lines with only ANSI escape codes) are left as they are,
+/// so no trailing whitespace is added.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::indent;
+///
+/// assert_eq!(indent("first\n\nsecond", "  "), "  first\n\n  second");
+/// ```
+pub fn indent(text: &str, prefix: &str) -> String {
+    text.split('\n')
+        .map(|line| match strip_ansi_codes(line).trim().is_empty() {
+            true => line.to_string(),
+            false => format!("{}{}", prefix, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+
+/// Formats a number of bytes with binary units (`B`, `KiB`, `MiB`, ...).
+///
+/// # Arguments
+///
+/// * `bytes` - The number of bytes
+///
+/// # Returns
+///
+/// A `String` with one decimal for sizes of at least 1 KiB.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::human_bytes;
+///
+/// assert_eq!(human_bytes(512), "512 B");
+/// assert_eq!(human_bytes(1_468_006), "1.4 MiB");
+/// ```
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    let (mut value, mut unit) = (bytes as f64, 0);
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} B", bytes),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_lerp() {
+        assert_eq!(RED.lerp(BLUE, 0.0), RED);
+        assert_eq!(RED.lerp(BLUE, 1.0), BLUE);
+        assert_eq!(RED.lerp(BLUE, 2.0), BLUE);
+        assert_eq!(BLACK.lerp(WHITE, 0.25), Color::new(64, 64, 64));
+    }
+
+    #[test]
+    fn test_from_hsv() {
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), BLUE);
+        assert_eq!(Color::from_hsv(420.0, 1.0, 1.0), YELLOW);
+        assert_eq!(Color::from_hsv(0.0, 0.0, 1.0), WHITE);
+    }
+
+    #[test]
+    fn test_text_style_sequence() {
+        assert_eq!(TextStyle::new().sequence(), "");
+        assert_eq!(TextStyle::new().bold().bold().dim().sequence(), "\x1b[1;2m");
+        assert_eq!(TextStyle::new().fg(RED).fg(BLUE).sequence(), "\x1b[38;2;0;0;255m");
+        assert_eq!(TextStyle::new().apply("plain"), "plain");
+    }
+
+    #[test]
+    fn test_nested_resets() {
+        let inner = "\x1b[31mred\x1b[0m";
+        assert_eq!(
+            wrap_sequence("\x1b[1m", &format!("a {inner} b")),
+            "\x1b[1ma \x1b[31mred\x1b[0m\x1b[1m b\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_gradients_keep_text() {
+        assert_eq!(strip_ansi_codes(&gradient("a b", RED, BLUE)), "a b");
+        assert_eq!(strip_ansi_codes(&rainbow("xyz")), "xyz");
+        assert_eq!(gradient("", RED, BLUE), "");
+        assert_eq!(visual_length(&block_gradient(3, 2, (RED, GREEN, BLUE, WHITE))), 3 * 2 * 2 + 1);
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three", 7), "one two\nthree");
+        assert_eq!(wrap("  indented  text\nkept", 20), "  indented text\nkept");
+        assert_eq!(wrap("abcdefgh ij", 3), "abc\ndef\ngh\nij");
+        assert_eq!(wrap("", 5), "");
+
+        let styled = "\x1b[31mred \x1b[1mbold\x1b[0m plain text";
+        let wrapped = wrap(styled, 8);
+        assert_eq!(wrapped, "\x1b[31mred \x1b[1mbold\x1b[0m\nplain\ntext");
+        assert_eq!(wrap("\x1b[32ma b c\x1b[0m", 1), "\x1b[32ma\x1b[0m\n\x1b[32mb\x1b[0m\n\x1b[32mc\x1b[0m");
+        assert!(wrapped.lines().all(|line| visual_length(line) <= 8));
+    }
+
+    #[test]
+    fn test_indent() {
+        assert_eq!(indent("a\n  b\n", "> "), "> a\n>   b\n");
+        assert_eq!(indent("\x1b[1m\x1b[0m\nx", "- "), "\x1b[1m\x1b[0m\n- x");
+    }
+
+    #[test]
+    fn test_human_bytes() {
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(1023), "1023 B");
+        assert_eq!(human_bytes(1024), "1.0 KiB");
+        assert_eq!(human_bytes(5 * 1024 * 1024 * 1024), "5.0 GiB");
+        assert_eq!(human_bytes(u64::MAX), "16.0 EiB");
+    }
+}
@@ -0,0 +1,208 @@
+//! Small charts drawn with Unicode block characters, for visualizing data inline in a terminal.
+//!
+//! - [sparkline] - A one-line trend (`▁▂▄▇█▅▂`)
+//! - [bar_chart] - Labeled horizontal bars, colored by their length
+//! - [line_chart] - A multi-line plot with a labeled y-axis
+//!
+//! # Examples
+//! ```
+//! use dev_utils::format::chart::{bar_chart, sparkline};
+//! use dev_utils::format::strip_ansi_codes;
+//!
+//! assert_eq!(sparkline(&[1.0, 2.0, 4.0, 8.0, 4.0]), "▁▂▄█▄");
+//!
+//! let chart = bar_chart(&["sort", "sort_unstable"], &[120.0, 60.0], 10);
+//! assert_eq!(strip_ansi_codes(&chart), "\
+//! sort          │██████████ 120
+//! sort_unstable │█████ 60");
+//! ```
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use super::{visual_length, Stylize, Color, CYAN, GREEN, RED, YELLOW};
+use super::num::{group_digits, si};
+#[cfg(not(feature = "std"))]
+use super::float::Float;
+
+// The eight heights of a sparkline column
+const SPARK_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+// The eighths of a block, for bars with sub-character precision
+const PARTIAL_BLOCKS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+// The smallest and largest finite values, if any
+fn range(values: &[f64]) -> Option<(f64, f64)> {
+    values.iter().copied().filter(|v| v.is_finite())
+        .fold(None, |range, v| match range {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((f64::min(lo, v), f64::max(hi, v))),
+        })
+}
+
+/// Renders the values as a single line of blocks, from `▁` (the smallest value)
+/// to `█` (the largest). Non-finite values are shown as spaces.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::chart::sparkline;
+///
+/// assert_eq!(sparkline(&[0.0, 0.5, 1.0, f64::NAN, 1.0]), "▁▅█ █");
+/// assert_eq!(sparkline(&[3.0, 3.0]), "▄▄");  // a flat line sits in the middle
+/// assert_eq!(sparkline(&[]), "");
+/// ```
+pub fn sparkline(values: &[f64]) -> String {
+    let Some((min, max)) = range(values) else {return " ".repeat(values.len());};
+    values.iter().map(|&v| match v.is_finite() {
+        false => ' ',
+        true if max == min => SPARK_BLOCKS[3],
+        true => SPARK_BLOCKS[((v - min) / (max - min) * 7.0).round() as usize],
+    }).collect()
+}
+
+/// The color of a bar from its length relative to the longest one: green, yellow, then red.
+pub fn heat_color(fraction: f64) -> Color {
+    let fraction = fraction.clamp(0.0, 1.0) as f32;
+    match fraction < 0.5 {
+        true => GREEN.lerp(YELLOW, fraction * 2.0),
+        false => YELLOW.lerp(RED, (fraction - 0.5) * 2.0),
+    }
+}
+
+/// Renders one horizontal bar per value, the largest one `width` characters wide,
+/// with the labels aligned on the left and the values after the bars.
+///
+/// Bars are colored from green (short) to red (long); negative and non-finite values
+/// are drawn as empty bars.
+///
+/// # Arguments
+///
+/// * `labels` - The label of each bar (labels may contain ANSI styling)
+/// * `values` - The value of each bar; extra labels or values are ignored
+/// * `width` - The width of the longest bar, in characters
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::chart::bar_chart;
+/// use dev_utils::format::strip_ansi_codes;
+///
+/// let chart = bar_chart(&["a", "b"], &[1.0, 0.25], 4);
+/// assert_eq!(strip_ansi_codes(&chart), "a │████ 1\nb │█ 0.25");
+/// ```
+pub fn bar_chart<S: AsRef<str>>(labels: &[S], values: &[f64], width: usize) -> String {
+    let peak = values.iter().copied().filter(|v| v.is_finite()).fold(0.0, f64::max);
+    let label_width = labels.iter().map(|l| visual_length(l.as_ref())).max().unwrap_or(0);
+    labels.iter().zip(values).map(|(label, &value)| {
+        let fraction = if peak > 0.0 && value > 0.0 && value.is_finite() {value / peak} else {0.0};
+        let eighths = (fraction * width as f64 * 8.0).round() as usize;
+        let mut bar = "█".repeat(eighths / 8);
+        if !eighths.is_multiple_of(8) {bar.push(PARTIAL_BLOCKS[eighths % 8]);}
+        let padding = " ".repeat(label_width - visual_length(label.as_ref()));
+        format!("{}{} │{} {}", label.as_ref(), padding, bar.color(heat_color(fraction)), group_digits(value))
+    }).collect::<Vec<_>>().join("\n")
+}
+
+/// Renders the values as a line plot `height` rows tall, with the largest and smallest
+/// values labeled on the y-axis (with SI suffixes, see [si]).
+///
+/// When there are more values than `width`, consecutive values are averaged so the plot
+/// fits in `width` columns; otherwise each value takes one column.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::chart::line_chart;
+/// use dev_utils::format::strip_ansi_codes;
+///
+/// let chart = line_chart(&[1.0, 2.0, 3.0, 2.0, 1.0], 20, 3);
+/// assert_eq!(strip_ansi_codes(&chart), "\
+/// 3 ┤  •
+///   │ • •
+/// 1 ┤•   •");
+/// ```
+pub fn line_chart(values: &[f64], width: usize, height: usize) -> String {
+    let columns = resample(values, width.max(1));
+    let height = height.max(2);
+    let Some((min, max)) = range(&columns) else {return String::new();};
+    let row_of = |v: f64| match max > min {
+        true => ((v - min) / (max - min) * (height - 1) as f64).round() as usize,
+        false => 0,
+    };
+
+    // rows are filled from the bottom (row 0), then printed top to bottom
+    let mut grid = vec![vec![' '; columns.len()]; height];
+    let mut previous: Option<usize> = None;
+    for (col, &value) in columns.iter().enumerate() {
+        if !value.is_finite() {
+            previous = None;
+            continue;
+        }
+        let row = row_of(value);
+        grid[row][col] = '•';
+        // a steep slope is drawn in the rows between the two points
+        if let Some(prev) = previous {
+            let (slope, rows) = match row > prev {
+                true => ('╱', prev + 1..row),
+                false => ('╲', row + 1..prev),
+            };
+            for r in rows {grid[r][col - 1] = slope;}
+        }
+        previous = Some(row);
+    }
+
+    let (top, bottom) = (si(max), si(min));
+    let axis_width = visual_length(&top).max(visual_length(&bottom));
+    (0..height).rev().map(|row| {
+        let (label, tick) = match row {
+            r if r == height - 1 => (top.as_str(), '┤'),
+            0 => (bottom.as_str(), '┤'),
+            _ => ("", '│'),
+        };
+        let line: String = grid[row].iter().collect();
+        format!("{:>w$} {}{}", label, tick, line.trim_end().color(CYAN), w = axis_width)
+    }).collect::<Vec<_>>().join("\n")
+}
+
+// Averages consecutive values so that there are at most `width` of them
+fn resample(values: &[f64], width: usize) -> Vec<f64> {
+    if values.len() <= width {return values.to_vec();}
+    (0..width).map(|i| {
+        let chunk = &values[i * values.len() / width..(i + 1) * values.len() / width];
+        chunk.iter().sum::<f64>() / chunk.len() as f64
+    }).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::strip_ansi_codes;
+
+    #[test]
+    fn test_sparkline() {
+        let values: Vec<f64> = (0..8).map(f64::from).collect();
+        assert_eq!(sparkline(&values), "▁▂▃▄▅▆▇█");
+        assert_eq!(sparkline(&[f64::INFINITY, 1.0]), " ▄");
+        assert_eq!(sparkline(&[f64::NAN]), " ");
+    }
+
+    #[test]
+    fn test_bar_chart() {
+        let chart = strip_ansi_codes(&bar_chart(&["x", "yy", "z"], &[2.0, 1.0, -1.0], 3));
+        assert_eq!(chart, "x  │███ 2\nyy │█▌ 1\nz  │ -1");
+        assert_eq!(bar_chart(&["only zeros"], &[0.0], 5), format!("only zeros │{} 0", "".color(heat_color(0.0))));
+        assert_eq!(heat_color(1.0), RED);
+        assert_eq!(heat_color(0.5), YELLOW);
+    }
+
+    #[test]
+    fn test_line_chart() {
+        let values: Vec<f64> = (0..40).map(|i| (i as f64 / 40.0 * std::f64::consts::TAU).sin()).collect();
+        let chart = strip_ansi_codes(&line_chart(&values, 20, 5));
+        assert_eq!(chart.lines().count(), 5);
+        assert!(chart.lines().all(|line| visual_length(line) <= 20 + 7));
+        assert_eq!(resample(&[1.0, 3.0, 5.0, 7.0], 2), [2.0, 6.0]);
+        assert_eq!(strip_ansi_codes(&line_chart(&[2.0, 2.0], 10, 2)), "2 ┤\n2 ┤••");
+        assert_eq!(line_chart(&[], 10, 3), "");
+    }
+}
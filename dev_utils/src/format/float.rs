@@ -0,0 +1,58 @@
+// The float methods of `std` used by `format`, for `no_std` builds (`core` doesn't have them).
+// Imported only without `std`, so that the inherent methods are used otherwise.
+
+pub(crate) trait Float {
+    fn round(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {$(
+        impl Float for $t {
+            // halfway cases away from zero, like the `std` one
+            fn round(self) -> Self {
+                let magnitude = self.abs();
+                // from 2^52 on (2^23 for f32) every float is whole; NaN and infinities stay as they are
+                if magnitude.is_nan() || magnitude >= 4_503_599_627_370_496.0 {return self;}
+                let whole = magnitude as u64 as $t;
+                let rounded = match magnitude - whole >= 0.5 {
+                    true => whole + 1.0,
+                    false => whole,
+                };
+                match self.is_sign_negative() {
+                    true => -rounded,
+                    false => rounded,
+                }
+            }
+
+            fn rem_euclid(self, rhs: Self) -> Self {
+                let r = self % rhs;
+                match r < 0.0 {
+                    true => r + rhs.abs(),
+                    false => r,
+                }
+            }
+        }
+    )*};
+}
+
+impl_float!(f32, f64);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_std() {
+        for x in [0.0, 0.49999997, 0.5, 1.5, 2.5, -0.5, -2.4, -2.6, 7.0, 1e20, f64::INFINITY, f64::MAX] {
+            assert_eq!(Float::round(x), x.round(), "round({})", x);
+            assert_eq!(Float::round(x as f32), (x as f32).round(), "round({}f32)", x);
+        }
+        assert!(Float::round(f64::NAN).is_nan());
+        for (x, rhs) in [(370.0, 360.0), (-30.0, 360.0), (720.0, 360.0), (-7.5, -2.0), (0.0, 1.0)] {
+            assert_eq!(Float::rem_euclid(x, rhs), x.rem_euclid(rhs), "rem_euclid({}, {})", x, rhs);
+            assert_eq!(Float::rem_euclid(x as f32, rhs as f32), (x as f32).rem_euclid(rhs as f32));
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Hexdumps of binary data, in the classic offset / hex / ASCII layout.
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use super::{Color, Stylize, Style, GREEN, RED, YELLOW};
+
+const BYTES_PER_ROW: usize = 16;
+// side-by-side rows are half as wide, so that two of them fit in a terminal
+const DIFF_BYTES_PER_ROW: usize = 8;
+const CONTEXT_ROWS: usize = 2;
+
+/// Renders a hexdump of a buffer: the offset, 16 bytes in hex and the same bytes as ASCII
+/// on each row, followed by the length of the buffer.
+///
+/// Non-printable bytes are shown as `.` in the ASCII column and highlighted in both columns:
+/// zeros are dimmed and other control or non-ASCII bytes are yellow.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::{hexdump, strip_ansi_codes};
+///
+/// let dump = strip_ansi_codes(&hexdump(b"GET / HTTP/1.1\r\n\0"));
+/// assert_eq!(dump, "\
+/// 00000000  47 45 54 20 2f 20 48 54  54 50 2f 31 2e 31 0d 0a  |GET / HTTP/1.1..|
+/// 00000010  00                                                |.|
+/// 00000011");
+/// ```
+pub fn hexdump(data: &[u8]) -> String {
+    if data.is_empty() {return String::new();}
+    let mut out = String::new();
+    for offset in (0..data.len()).step_by(BYTES_PER_ROW) {
+        let row = hex_row(data, offset, BYTES_PER_ROW, false, |_, byte, s| match byte {
+            0 => s.style(Style::Dim),
+            b if b.is_ascii_graphic() || b == b' ' => s.to_string(),
+            _ => s.color(YELLOW),
+        });
+        out.push_str(&format!("{:08x}  {}\n", offset, row));
+    }
+    out.push_str(&format!("{:08x}", data.len()));
+    out
+}
+
+/// Renders a side-by-side hexdump of two buffers, highlighting the bytes that differ
+/// (red on the left, green on the right).
+///
+/// Only the rows containing differences, plus two rows of context around them, are shown;
+/// skipped rows are marked with `...`.
+///
+/// # Returns
+///
+/// The hexdump with a header row, or an empty string if the buffers are equal.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::{hexdump_diff, strip_ansi_codes};
+///
+/// let diff = strip_ansi_codes(&hexdump_diff(b"Hello, World!", b"Hello, Wor1d!"));
+/// assert!(diff.contains("|Hello, W|") && diff.contains("|or1d!   |"));
+/// assert_eq!(hexdump_diff(b"same", b"same"), "");
+/// ```
+pub fn hexdump_diff(left: &[u8], right: &[u8]) -> String {
+    let differs = |i: usize| left.get(i) != right.get(i);
+    let rows = left.len().max(right.len()).div_ceil(DIFF_BYTES_PER_ROW);
+    let row_differs = |row: usize| (row * DIFF_BYTES_PER_ROW..(row + 1) * DIFF_BYTES_PER_ROW).any(differs);
+    let shown = (0..rows)
+        .filter(|&row| (row.saturating_sub(CONTEXT_ROWS)..=row + CONTEXT_ROWS).any(|r| r < rows && row_differs(r)))
+        .collect::<Vec<_>>();
+    if shown.is_empty() {return String::new();}
+
+    let paint = |color: Color| move |i: usize, _, s: &str| match differs(i) {
+        true => s.color(color),
+        false => s.to_string(),
+    };
+    let mut out = format!("{}  {:<w$}   {}\n", format!("{:>8}", "offset").style(Style::Dim), "left", "right", w = DIFF_BYTES_PER_ROW * 4 + 3);
+    let mut next_row = 0;
+    for row in shown {
+        if row != next_row {out.push_str(&format!("{}\n", format!("{:>8}", "...").style(Style::Dim)));}
+        let offset = row * DIFF_BYTES_PER_ROW;
+        out.push_str(&format!("{}  {}   {}\n",
+            format!("{:08x}", offset).style(Style::Dim),
+            hex_row(left, offset, DIFF_BYTES_PER_ROW, true, paint(RED)),
+            hex_row(right, offset, DIFF_BYTES_PER_ROW, true, paint(GREEN)),
+        ));
+        next_row = row + 1;
+    }
+    out.trim_end().to_string()
+}
+
+// Renders the hex and ASCII columns of the `width` bytes of `data` starting at `offset`
+// (padding the ASCII column of a short row if `pad`). `paint` styles the text of each byte
+// from its index and value, in both columns.
+fn hex_row(data: &[u8], offset: usize, width: usize, pad: bool, paint: impl Fn(usize, u8, &str) -> String) -> String {
+    let (mut hex, mut ascii) = (String::new(), String::new());
+    for i in offset..offset + width {
+        if i - offset == 8 && width > 8 {hex.push(' ');}
+        match data.get(i) {
+            Some(&b) => {
+                let c = if b.is_ascii_graphic() || b == b' ' {b as char} else {'.'};
+                hex.push_str(&paint(i, b, &format!("{:02x}", b)));
+                ascii.push_str(&paint(i, b, &c.to_string()));
+            },
+            None => {
+                hex.push_str("  ");
+                if pad {ascii.push(' ');}
+            },
+        }
+        hex.push(' ');
+    }
+    format!("{} |{}|", hex, ascii)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::strip_ansi_codes;
+
+    #[test]
+    fn test_hexdump() {
+        assert_eq!(hexdump(&[]), "");
+        let data: Vec<u8> = (0..=255).collect();
+        let dump = strip_ansi_codes(&hexdump(&data));
+        assert_eq!(dump.lines().count(), 17);
+        assert_eq!(dump.lines().nth(2).unwrap(), "00000020  20 21 22 23 24 25 26 27  28 29 2a 2b 2c 2d 2e 2f  | !\"#$%&'()*+,-./|");
+        assert!(dump.ends_with("\n00000100"));
+        // the non-printable bytes are highlighted
+        assert!(hexdump(b"\x01").contains(&"01".color(YELLOW)) || !crate::format::term::colors_enabled());
+    }
+
+    #[test]
+    fn test_hexdump_diff() {
+        let left: Vec<u8> = (0..64).collect();
+        let mut right = left.clone();
+        right[40] = 0xFF;
+        let diff = strip_ansi_codes(&hexdump_diff(&left, &right));
+        assert!(diff.starts_with("  offset"));
+        assert!(diff.contains("...\n00000018"));
+        assert!(diff.contains("28 29 2a") && diff.contains("ff 29 2a"));
+        assert_eq!(strip_ansi_codes(&hexdump_diff(b"abc", b"ab")).lines().nth(1).unwrap(),
+            "00000000  61 62 63                 |abc     |   61 62                    |ab      |");
+    }
+}
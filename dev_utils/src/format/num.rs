@@ -0,0 +1,274 @@
+//! Human-readable number formatting: digit grouping, SI suffixes, ordinals and aligned columns.
+//!
+//! The free functions use the global [NumberFormat] (`1,234.5` by default), which can be
+//! changed with [set_number_format]; the same helpers are available as methods of a
+//! [NumberFormat] to format with specific separators.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::format::num::*;
+//!
+//! assert_eq!(group_digits(1234567), "1,234,567");
+//! assert_eq!(si(1_500_000), "1.5M");
+//! assert_eq!(ordinal(3), "3rd");
+//! assert_eq!(NumberFormat::EUROPEAN.group_digits(-9876.25), "-9.876,25");
+//! assert_eq!(align_decimals(&[3.5, 1250.0], 1), ["    3.5", "1,250.0"]);
+//! ```
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+use core::sync::atomic::{AtomicU32, Ordering};
+#[cfg(not(feature = "std"))]
+use super::float::Float;
+
+/// A primitive number that can be formatted by this module.
+pub trait Number: Copy {
+    /// Returns the value as a float.
+    fn to_f64(self) -> f64;
+    /// Returns the value as an integer, if it is one.
+    fn to_i128(self) -> Option<i128>;
+}
+
+macro_rules! impl_number {
+    (int: $($int:ty)*; float: $($float:ty)*) => {
+        $(impl Number for $int {
+            fn to_f64(self) -> f64 {self as f64}
+            fn to_i128(self) -> Option<i128> {Some(self as i128)}
+        })*
+        $(impl Number for $float {
+            fn to_f64(self) -> f64 {self as f64}
+            fn to_i128(self) -> Option<i128> {None}
+        })*
+    };
+}
+
+impl_number!(int: i8 i16 i32 i64 i128 isize u8 u16 u32 u64 usize; float: f32 f64);
+
+/// The separators used to format numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// The separator between groups of three digits (`1,000`).
+    pub thousands: char,
+    /// The decimal separator (`0.5`).
+    pub decimal: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {NumberFormat::ENGLISH}
+}
+
+// The suffixes of the powers of 1000 used by `si`
+const SI_SUFFIXES: [&str; 7] = ["", "k", "M", "G", "T", "P", "E"];
+
+impl NumberFormat {
+    /// `1,234,567.89`
+    pub const ENGLISH: NumberFormat = NumberFormat { thousands: ',', decimal: '.' };
+    /// `1.234.567,89`
+    pub const EUROPEAN: NumberFormat = NumberFormat { thousands: '.', decimal: ',' };
+    /// `1 234 567.89`, with a narrow no-break space as recommended by the SI
+    pub const SI: NumberFormat = NumberFormat { thousands: '\u{202F}', decimal: '.' };
+
+    /// Creates a format from its separators.
+    pub const fn new(thousands: char, decimal: char) -> Self {NumberFormat { thousands, decimal }}
+
+    /// Formats a number with its integer digits grouped by thousands.
+    ///
+    /// Floats keep their shortest representation (`1234.5` is `1,234.5`).
+    pub fn group_digits<N: Number>(&self, n: N) -> String {
+        let raw = match n.to_i128() {
+            Some(i) => i.to_string(),
+            None => n.to_f64().to_string(),
+        };
+        self.localize(&raw)
+    }
+
+    /// Formats a float with `precision` decimals and its integer digits grouped by thousands.
+    pub fn decimal<N: Number>(&self, n: N, precision: usize) -> String {
+        self.localize(&format!("{:.*}", precision, n.to_f64()))
+    }
+
+    /// Formats a number with an SI suffix (`k`, `M`, `G`, ...) and at most one decimal.
+    ///
+    /// Numbers below 1000 are kept as they are (rounded to one decimal).
+    pub fn si<N: Number>(&self, n: N) -> String {
+        let x = n.to_f64();
+        if !x.is_finite() {return x.to_string();}
+        let (mut value, mut unit) = (x.abs(), 0);
+        while value >= 1000.0 && unit < SI_SUFFIXES.len() - 1 {
+            value /= 1000.0;
+            unit += 1;
+        }
+        // 999_950 rounds to 1000.0k, which is 1M
+        if (value * 10.0).round() >= 10_000.0 && unit < SI_SUFFIXES.len() - 1 {
+            value /= 1000.0;
+            unit += 1;
+        }
+        let mut digits = format!("{:.1}", value);
+        if digits.ends_with(".0") {digits.truncate(digits.len() - 2);}
+        let sign = if x < 0.0 && digits != "0" {"-"} else {""};
+        format!("{}{}{}", sign, digits.replace('.', &self.decimal.to_string()), SI_SUFFIXES[unit])
+    }
+
+    /// Formats floats with `precision` decimals, grouped digits, right-aligned to the same width.
+    pub fn align_decimals<N: Number>(&self, values: &[N], precision: usize) -> Vec<String> {
+        let formatted: Vec<String> = values.iter().map(|&n| self.decimal(n, precision)).collect();
+        let width = formatted.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+        formatted.into_iter().map(|s| format!("{:>width$}", s)).collect()
+    }
+
+    // Groups the integer digits of a formatted number and replaces its decimal point
+    fn localize(&self, raw: &str) -> String {
+        let (sign, unsigned) = raw.strip_prefix('-').map_or(("", raw), |rest| ("-", rest));
+        let (int, fraction) = unsigned.split_once('.').map_or((unsigned, None), |(int, fraction)| (int, Some(fraction)));
+        if !int.bytes().all(|b| b.is_ascii_digit()) {return raw.to_string();}  // inf, NaN, exponents
+
+        let mut out = String::from(sign);
+        for (i, digit) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {out.push(self.thousands);}
+            out.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            out.push(self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+}
+
+// One atomic per separator: 64-bit atomics are missing on some `no_std` targets
+static THOUSANDS_SEPARATOR: AtomicU32 = AtomicU32::new(NumberFormat::ENGLISH.thousands as u32);
+static DECIMAL_SEPARATOR: AtomicU32 = AtomicU32::new(NumberFormat::ENGLISH.decimal as u32);
+
+/// Sets the global [NumberFormat] used by the functions of this module.
+pub fn set_number_format(format: NumberFormat) {
+    THOUSANDS_SEPARATOR.store(format.thousands as u32, Ordering::SeqCst);
+    DECIMAL_SEPARATOR.store(format.decimal as u32, Ordering::SeqCst);
+}
+
+/// Returns the global [NumberFormat].
+pub fn number_format() -> NumberFormat {
+    let char_of = |separator: &AtomicU32| char::from_u32(separator.load(Ordering::Relaxed)).unwrap_or(' ');
+    NumberFormat { thousands: char_of(&THOUSANDS_SEPARATOR), decimal: char_of(&DECIMAL_SEPARATOR) }
+}
+
+/// Formats a number with its integer digits grouped by thousands.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::num::group_digits;
+///
+/// assert_eq!(group_digits(1234567), "1,234,567");
+/// assert_eq!(group_digits(-1000i64), "-1,000");
+/// assert_eq!(group_digits(255u8), "255");
+/// assert_eq!(group_digits(1234.5), "1,234.5");
+/// ```
+pub fn group_digits<N: Number>(n: N) -> String {number_format().group_digits(n)}
+
+/// Formats a float with `precision` decimals and its integer digits grouped by thousands.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::num::decimal;
+///
+/// assert_eq!(decimal(1234.567, 2), "1,234.57");
+/// assert_eq!(decimal(42, 1), "42.0");
+/// ```
+pub fn decimal<N: Number>(n: N, precision: usize) -> String {number_format().decimal(n, precision)}
+
+/// Formats a number with an SI suffix (`k`, `M`, `G`, `T`, `P`, `E`) and at most one decimal.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::num::si;
+///
+/// assert_eq!(si(1_500_000), "1.5M");
+/// assert_eq!(si(2_000), "2k");
+/// assert_eq!(si(999), "999");
+/// assert_eq!(si(-12_345.0), "-12.3k");
+/// ```
+pub fn si<N: Number>(n: N) -> String {number_format().si(n)}
+
+/// Formats floats with `precision` decimals and grouped digits, right-aligned to the same width
+/// so they line up in a column.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::num::align_decimals;
+///
+/// assert_eq!(align_decimals(&[1.5, 10.25, 1000.0], 2), ["    1.50", "   10.25", "1,000.00"]);
+/// ```
+pub fn align_decimals<N: Number>(values: &[N], precision: usize) -> Vec<String> {
+    number_format().align_decimals(values, precision)
+}
+
+/// Formats an integer as an English ordinal (`1st`, `2nd`, `3rd`, `4th`, `11th`, `21st`, ...).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::format::num::ordinal;
+///
+/// assert_eq!(ordinal(1), "1st");
+/// assert_eq!(ordinal(12), "12th");
+/// assert_eq!(ordinal(103), "103rd");
+/// ```
+pub fn ordinal(n: i64) -> String {
+    let suffix = match (n.unsigned_abs() % 10, n.unsigned_abs() % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_digits() {
+        let en = NumberFormat::ENGLISH;
+        assert_eq!(en.group_digits(0), "0");
+        assert_eq!(en.group_digits(100), "100");
+        assert_eq!(en.group_digits(1000), "1,000");
+        assert_eq!(en.group_digits(-123456), "-123,456");
+        assert_eq!(en.group_digits(u64::MAX), "18,446,744,073,709,551,615");
+        assert_eq!(en.group_digits(f64::INFINITY), "inf");
+        assert_eq!(NumberFormat::SI.group_digits(12345), "12\u{202F}345");
+        assert_eq!(NumberFormat::new('\'', '.').decimal(1234567.891, 1), "1'234'567.9");
+    }
+
+    #[test]
+    fn test_si() {
+        let en = NumberFormat::ENGLISH;
+        assert_eq!(en.si(0), "0");
+        assert_eq!(en.si(12.34), "12.3");
+        assert_eq!(en.si(1_000), "1k");
+        assert_eq!(en.si(999_950), "1M");
+        assert_eq!(en.si(3_210_000_000u64), "3.2G");
+        assert_eq!(en.si(u64::MAX), "18.4E");
+        assert_eq!(en.si(-0.01), "0");
+        assert_eq!(NumberFormat::EUROPEAN.si(1_500), "1,5k");
+    }
+
+    #[test]
+    fn test_ordinal() {
+        let ordinals: Vec<String> = [0, 1, 2, 3, 4, 11, 12, 13, 21, 22, 111, -1].into_iter().map(ordinal).collect();
+        assert_eq!(ordinals, ["0th", "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "111th", "-1st"]);
+    }
+
+    #[test]
+    fn test_global_format() {
+        assert_eq!(number_format(), NumberFormat::default());
+        set_number_format(NumberFormat::EUROPEAN);
+        assert_eq!(number_format(), NumberFormat::EUROPEAN);
+        set_number_format(NumberFormat::default());
+    }
+}
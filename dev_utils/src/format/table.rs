@@ -0,0 +1,246 @@
+//! A small table renderer for terminal output.
+//!
+//! Column widths are computed with [visual_length], so cells that contain
+//! ANSI styling (colors, bold, etc.) still line up correctly.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::format::table::{Table, Alignment, BorderStyle};
+//!
+//! let mut table = Table::new(["Name", "Size"]).border(BorderStyle::Ascii);
+//! table.add_row(["main.rs", "1.2 KiB"]);
+//! table.add_row(["lib.rs", "14 B"]);
+//! let table = table.align(1, Alignment::Right);
+//!
+//! println!("{}", table);
+//! ```
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use super::visual_length;
+
+
+/// Horizontal alignment of the content of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// The set of characters used to draw the borders of a [Table].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorderStyle {
+    /// No borders at all, columns are only separated by the padding.
+    #[default]
+    None,
+    /// Plain ASCII borders (`+`, `-`, `|`).
+    Ascii,
+    /// Unicode box-drawing borders (`┌`, `─`, `│`, ...).
+    Line,
+    /// Unicode box-drawing borders with rounded corners (`╭`, `╮`, ...).
+    Rounded,
+}
+
+// (top-left, top-mid, top-right, mid-left, mid-mid, mid-right, bot-left, bot-mid, bot-right, horizontal, vertical)
+type BorderChars = [char; 11];
+
+impl BorderStyle {
+    fn chars(&self) -> Option<BorderChars> {
+        match self {
+            BorderStyle::None => None,
+            BorderStyle::Ascii => Some(['+', '+', '+', '+', '+', '+', '+', '+', '+', '-', '|']),
+            BorderStyle::Line => Some(['┌', '┬', '┐', '├', '┼', '┤', '└', '┴', '┘', '─', '│']),
+            BorderStyle::Rounded => Some(['╭', '┬', '╮', '├', '┼', '┤', '╰', '┴', '╯', '─', '│']),
+        }
+    }
+}
+
+/// A table made of a header row and any number of data rows.
+///
+/// Rows shorter than the header are padded with empty cells, and rows longer
+/// than the header extend the table with unnamed columns.
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    alignments: Vec<Alignment>,
+    border: BorderStyle,
+    padding: usize,
+}
+
+impl Table {
+    /// Creates a new [Table] with the given headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The header cells (may contain ANSI styling)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::format::table::Table;
+    ///
+    /// let table = Table::new(["Key", "Value"]);
+    /// assert_eq!(table.column_count(), 2);
+    /// ```
+    pub fn new<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        Table {
+            headers: headers.into_iter().map(|h| h.to_string()).collect(),
+            padding: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Appends a row of cells to the table.
+    pub fn add_row<I, S>(&mut self, row: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.rows.push(row.into_iter().map(|c| c.to_string()).collect());
+        self
+    }
+
+    /// Appends a row of cells to the table (builder style).
+    pub fn row<I, S>(mut self, row: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.add_row(row);
+        self
+    }
+
+    /// Sets the border style of the table.
+    pub fn border(mut self, border: BorderStyle) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Sets the alignment of a single column (0-indexed).
+    pub fn align(mut self, column: usize, alignment: Alignment) -> Self {
+        if self.alignments.len() <= column {
+            self.alignments.resize(column + 1, Alignment::default());
+        }
+        self.alignments[column] = alignment;
+        self
+    }
+
+    /// Sets the number of spaces placed on each side of every cell.
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Returns the number of columns of the table.
+    pub fn column_count(&self) -> usize {
+        self.rows.iter().map(Vec::len).chain(core::iter::once(self.headers.len())).max().unwrap_or(0)
+    }
+
+    /// Returns the visual width of every column (ignoring ANSI codes).
+    pub fn column_widths(&self) -> Vec<usize> {
+        let mut widths = vec![0; self.column_count()];
+        core::iter::once(&self.headers).chain(&self.rows).for_each(|row| {
+            row.iter().enumerate().for_each(|(i, cell)| widths[i] = widths[i].max(visual_length(cell)));
+        });
+        widths
+    }
+
+    /// Renders the table into a `String` (without a trailing newline).
+    pub fn render(&self) -> String {
+        let widths = self.column_widths();
+        let border = self.border.chars();
+        let mut lines = Vec::new();
+
+        if let Some(b) = border {lines.push(self.separator(&widths, b[0], b[1], b[2], b[9]));}
+        if !self.headers.is_empty() {
+            lines.push(self.render_row(&self.headers, &widths));
+            if let Some(b) = border {lines.push(self.separator(&widths, b[3], b[4], b[5], b[9]));}
+        }
+        self.rows.iter().for_each(|row| lines.push(self.render_row(row, &widths)));
+        if let Some(b) = border {lines.push(self.separator(&widths, b[6], b[7], b[8], b[9]));}
+
+        lines.join("\n")
+    }
+
+    fn render_row(&self, row: &[String], widths: &[usize]) -> String {
+        let pad = " ".repeat(self.padding);
+        let cells = widths.iter().enumerate().map(|(i, &width)| {
+            let cell = row.get(i).map(String::as_str).unwrap_or("");
+            let alignment = self.alignments.get(i).copied().unwrap_or_default();
+            format!("{pad}{}{pad}", align_cell(cell, width, alignment))
+        }).collect::<Vec<_>>();
+
+        match self.border.chars() {
+            Some(b) => format!("{v}{}{v}", cells.join(&b[10].to_string()), v = b[10]),
+            None => cells.join("").trim_end().to_string(),
+        }
+    }
+
+    fn separator(&self, widths: &[usize], left: char, mid: char, right: char, h: char) -> String {
+        let segments = widths.iter()
+            .map(|w| h.to_string().repeat(w + 2 * self.padding))
+            .collect::<Vec<_>>();
+        format!("{left}{}{right}", segments.join(&mid.to_string()))
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+/// Pads a (possibly styled) cell to the given visual width.
+fn align_cell(cell: &str, width: usize, alignment: Alignment) -> String {
+    let gap = width.saturating_sub(visual_length(cell));
+    match alignment {
+        Alignment::Left => format!("{cell}{}", " ".repeat(gap)),
+        Alignment::Right => format!("{}{cell}", " ".repeat(gap)),
+        Alignment::Center => format!("{}{cell}{}", " ".repeat(gap / 2), " ".repeat(gap - gap / 2)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{Stylize, RED};
+
+    #[test]
+    fn test_plain_table() {
+        let table = Table::new(["a", "bbb"]).row(["cc", "d"]);
+        assert_eq!(table.render(), " a   bbb\n cc  d");
+    }
+
+    #[test]
+    fn test_ansi_cells_align() {
+        let table = Table::new(["x", "y"])
+            .row(["red".color(RED), "1".to_string()])
+            .row(["blue".to_string(), "2".to_string()])
+            .border(BorderStyle::Ascii);
+        let lines: Vec<_> = table.render().lines().map(visual_length).collect();
+        assert!(lines.windows(2).all(|w| w[0] == w[1]));
+        assert_eq!(table.column_widths(), vec![4, 1]);
+    }
+
+    #[test]
+    fn test_alignment() {
+        assert_eq!(align_cell("ab", 6, Alignment::Right), "    ab");
+        assert_eq!(align_cell("ab", 6, Alignment::Center), "  ab  ");
+        assert_eq!(align_cell("ab", 1, Alignment::Left), "ab");
+    }
+
+    #[test]
+    fn test_border_rendering() {
+        let table = Table::new(["k"]).row(["v"]).border(BorderStyle::Line);
+        assert_eq!(table.render(), "┌───┐\n│ k │\n├───┤\n│ v │\n└───┘");
+    }
+}
@@ -0,0 +1,109 @@
+//! Terminal capability detection and global color mode.
+//!
+//! By default ([ColorMode::Auto]) colors are only emitted when stdout is a terminal.
+//! The common environment conventions are respected:
+//! - `NO_COLOR` (set and non-empty) disables colors
+//! - `CLICOLOR_FORCE` (set and not `0`) forces colors, even when piped
+//!
+//! # Examples
+//! ```
+//! use dev_utils::format::term::{set_color_mode, colors_enabled, ColorMode};
+//! use dev_utils::format::{Stylize, RED};
+//!
+//! set_color_mode(ColorMode::Never);
+//! assert!(!colors_enabled());
+//! assert_eq!("plain".color(RED), "plain");
+//! ```
+use core::sync::atomic::{AtomicU8, Ordering};
+#[cfg(feature = "std")]
+use std::env;
+#[cfg(feature = "std")]
+use std::io::{self, IsTerminal};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+
+/// Controls whether ANSI escape codes are emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// Detect from the environment and whether stdout is a terminal.
+    #[default]
+    Auto = 0,
+    /// Always emit ANSI codes.
+    Always = 1,
+    /// Never emit ANSI codes.
+    Never = 2,
+}
+
+static COLOR_MODE: AtomicU8 = AtomicU8::new(ColorMode::Auto as u8);
+#[cfg(feature = "std")]
+static AUTO_DETECTED: OnceLock<bool> = OnceLock::new();
+
+/// Sets the global color mode used by [Stylize](super::Stylize) and `dlog`.
+pub fn set_color_mode(mode: ColorMode) {
+    COLOR_MODE.store(mode as u8, Ordering::SeqCst);
+}
+
+/// Returns the current global color mode.
+pub fn color_mode() -> ColorMode {
+    match COLOR_MODE.load(Ordering::Relaxed) {
+        1 => ColorMode::Always,
+        2 => ColorMode::Never,
+        _ => ColorMode::Auto,
+    }
+}
+
+/// Returns `true` if stdout is attached to a terminal.
+#[cfg(feature = "std")]
+pub fn is_stdout_tty() -> bool {io::stdout().is_terminal()}
+
+/// Returns `true` if stderr is attached to a terminal.
+#[cfg(feature = "std")]
+pub fn is_stderr_tty() -> bool {io::stderr().is_terminal()}
+
+/// Detects color support from the environment (`CLICOLOR_FORCE`, `NO_COLOR`) and the TTY state.
+///
+/// This ignores the global [ColorMode]; use [colors_enabled] for the effective value.
+#[cfg(feature = "std")]
+pub fn detect_color_support() -> bool {
+    let var_set = |name: &str| env::var_os(name).filter(|v| !v.is_empty());
+
+    match (var_set("CLICOLOR_FORCE"), var_set("NO_COLOR")) {
+        (Some(force), _) if force != "0" => true,
+        (_, Some(_)) => false,
+        _ => is_stdout_tty(),
+    }
+}
+
+/// Returns `true` if ANSI codes should be emitted according to the global [ColorMode].
+///
+/// In [ColorMode::Auto] the environment is inspected only once and the result is cached
+/// (without the `std` feature, colors are always emitted in this mode).
+pub fn colors_enabled() -> bool {
+    match color_mode() {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        #[cfg(feature = "std")]
+        ColorMode::Auto => *AUTO_DETECTED.get_or_init(detect_color_support),
+        // without `std` there is no terminal to inspect: the output is assumed to be one
+        #[cfg(not(feature = "std"))]
+        ColorMode::Auto => true,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_mode_roundtrip() {
+        [ColorMode::Always, ColorMode::Never, ColorMode::Auto].iter().for_each(|&mode| {
+            set_color_mode(mode);
+            assert_eq!(color_mode(), mode);
+        });
+        set_color_mode(ColorMode::Always);
+        assert!(colors_enabled());
+        set_color_mode(ColorMode::Auto);
+    }
+}
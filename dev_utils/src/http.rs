@@ -0,0 +1,410 @@
+//! A small HTTP/1.1 toolkit to mock backends during development.
+//!
+//! A [DevServer] routes requests to closures, one thread per connection, using only the
+//! standard library. [Middleware]s wrap the routes, like the request [Logger], and a
+//! [Client] calls them. It is meant for local development and tests, not for production traffic.
+//!
+//! # Modules
+//! - [auth] - Basic and Bearer `Authorization` headers, and a middleware that requires them
+//! - [body] - Urlencoded and multipart form bodies (parsing, and encoding for tests)
+//! - [dashboard] - A self-refreshing status page with counters, log rates and health checks
+//! - [sse] - Server-Sent Events for streaming responses ([HttpResponse::stream_sse])
+//! - [static_files] - A handler that serves a directory, with ETags and range requests
+//! - [tls] - A pluggable TLS provider for HTTPS clients and servers (requires the `tls` feature)
+//! - [ws] - WebSockets (RFC 6455): handshake, frames and a message-level connection
+//!
+//! # Examples
+//! ```no_run
+//! use dev_utils::http::{DevServer, HttpResponse, HttpStatus};
+//!
+//! DevServer::new()
+//!     .use_logger()
+//!     .get("/health", |_| HttpResponse::text(HttpStatus::Ok, "up"))
+//!     .post("/echo", |req| HttpResponse::new(HttpStatus::Ok).with_body(req.body.clone()))
+//!     .websocket("/ws", |mut socket| {
+//!         while let Ok(Some(message)) = socket.recv() {
+//!             let _ = socket.send(message);
+//!         }
+//!     })
+//!     .serve("127.0.0.1:8080")
+//!     .unwrap();
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use dev_macros::EnumStr;
+use crate::format::{Color, Stylize, CYAN, GREEN, RED, YELLOW};
+
+pub mod auth;
+pub mod body;
+mod client;
+pub mod dashboard;
+mod middleware;
+mod request;
+mod response;
+mod server;
+pub mod sse;
+pub mod static_files;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod ws;
+
+pub use client::{Client, ClientBuilder, ClientRequest};
+pub use middleware::{Logger, Middleware};
+pub use request::HttpRequest;
+pub use response::HttpResponse;
+pub use server::{DevServer, ServerHandle};
+
+/// The method of an HTTP request.
+///
+/// Parsing is case-insensitive for the standard methods; any other valid token becomes an
+/// [HttpMethod::Extension], kept as written (extension methods are case-sensitive).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::HttpMethod;
+///
+/// assert_eq!("patch".parse::<HttpMethod>(), Ok(HttpMethod::Patch));
+/// assert_eq!("PROPFIND".parse::<HttpMethod>(), Ok(HttpMethod::Extension("PROPFIND".to_string())));
+/// assert!("GET /".parse::<HttpMethod>().is_err());
+/// assert_eq!(HttpMethod::Options.to_string(), "OPTIONS");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HttpMethod {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Connect,
+    Options,
+    Trace,
+    Patch,
+    /// A method outside of the standard ones, like WebDAV's `PROPFIND`.
+    Extension(String),
+}
+
+impl HttpMethod {
+    /// The standard methods (every variant but [HttpMethod::Extension]).
+    pub const STANDARD: [HttpMethod; 9] = [
+        HttpMethod::Get, HttpMethod::Head, HttpMethod::Post, HttpMethod::Put, HttpMethod::Delete,
+        HttpMethod::Connect, HttpMethod::Options, HttpMethod::Trace, HttpMethod::Patch,
+    ];
+
+    /// Returns the name of the method, as sent on the wire.
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Connect => "CONNECT",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Trace => "TRACE",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Extension(name) => name,
+        }
+    }
+
+    /// Returns `true` for the methods that don't change the state of the server
+    /// (GET, HEAD, OPTIONS and TRACE).
+    pub fn is_safe(&self) -> bool {
+        matches!(self, HttpMethod::Get | HttpMethod::Head | HttpMethod::Options | HttpMethod::Trace)
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {f.write_str(self.as_str())}
+}
+
+impl FromStr for HttpMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // a method is an RFC 9110 token
+        let is_tchar = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+        if s.is_empty() || !s.chars().all(is_tchar) {return Err(format!("invalid HttpMethod: {}", s));}
+        Ok(Self::STANDARD.into_iter()
+            .find(|method| method.as_str().eq_ignore_ascii_case(s))
+            .unwrap_or_else(|| HttpMethod::Extension(s.to_string())))
+    }
+}
+
+macro_rules! define_statuses {
+    ($($status:ident => $code:expr, $reason:expr),+ $(,)?) => {
+        /// The status of an HTTP response.
+        ///
+        /// `Display` writes the code and the reason phrase (`404 Not Found`), colored by class.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum HttpStatus {
+            $($status),+
+        }
+
+        impl HttpStatus {
+            /// Returns the numeric code of the status.
+            pub const fn code(&self) -> u16 {
+                match self {
+                    $(HttpStatus::$status => $code),+
+                }
+            }
+
+            /// Returns the standard reason phrase of the status.
+            pub const fn reason(&self) -> &'static str {
+                match self {
+                    $(HttpStatus::$status => $reason),+
+                }
+            }
+
+            /// Returns the status with the given code, if it is known.
+            pub fn from_code(code: u16) -> Option<Self> {
+                match code {
+                    $($code => Some(HttpStatus::$status),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+// The IANA HTTP Status Code Registry (unassigned and unused codes left out, but 418)
+define_statuses! {
+    Continue                      => 100, "Continue",
+    SwitchingProtocols            => 101, "Switching Protocols",
+    Processing                    => 102, "Processing",
+    EarlyHints                    => 103, "Early Hints",
+    Ok                            => 200, "OK",
+    Created                       => 201, "Created",
+    Accepted                      => 202, "Accepted",
+    NonAuthoritativeInformation   => 203, "Non-Authoritative Information",
+    NoContent                     => 204, "No Content",
+    ResetContent                  => 205, "Reset Content",
+    PartialContent                => 206, "Partial Content",
+    MultiStatus                   => 207, "Multi-Status",
+    AlreadyReported               => 208, "Already Reported",
+    ImUsed                        => 226, "IM Used",
+    MultipleChoices               => 300, "Multiple Choices",
+    MovedPermanently              => 301, "Moved Permanently",
+    Found                         => 302, "Found",
+    SeeOther                      => 303, "See Other",
+    NotModified                   => 304, "Not Modified",
+    UseProxy                      => 305, "Use Proxy",
+    TemporaryRedirect             => 307, "Temporary Redirect",
+    PermanentRedirect             => 308, "Permanent Redirect",
+    BadRequest                    => 400, "Bad Request",
+    Unauthorized                  => 401, "Unauthorized",
+    PaymentRequired               => 402, "Payment Required",
+    Forbidden                     => 403, "Forbidden",
+    NotFound                      => 404, "Not Found",
+    MethodNotAllowed              => 405, "Method Not Allowed",
+    NotAcceptable                 => 406, "Not Acceptable",
+    ProxyAuthenticationRequired   => 407, "Proxy Authentication Required",
+    RequestTimeout                => 408, "Request Timeout",
+    Conflict                      => 409, "Conflict",
+    Gone                          => 410, "Gone",
+    LengthRequired                => 411, "Length Required",
+    PreconditionFailed            => 412, "Precondition Failed",
+    ContentTooLarge               => 413, "Content Too Large",
+    UriTooLong                    => 414, "URI Too Long",
+    UnsupportedMediaType          => 415, "Unsupported Media Type",
+    RangeNotSatisfiable           => 416, "Range Not Satisfiable",
+    ExpectationFailed             => 417, "Expectation Failed",
+    ImATeapot                     => 418, "I'm a teapot",
+    MisdirectedRequest            => 421, "Misdirected Request",
+    UnprocessableContent          => 422, "Unprocessable Content",
+    Locked                        => 423, "Locked",
+    FailedDependency              => 424, "Failed Dependency",
+    TooEarly                      => 425, "Too Early",
+    UpgradeRequired               => 426, "Upgrade Required",
+    PreconditionRequired          => 428, "Precondition Required",
+    TooManyRequests               => 429, "Too Many Requests",
+    RequestHeaderFieldsTooLarge   => 431, "Request Header Fields Too Large",
+    UnavailableForLegalReasons    => 451, "Unavailable For Legal Reasons",
+    InternalServerError           => 500, "Internal Server Error",
+    NotImplemented                => 501, "Not Implemented",
+    BadGateway                    => 502, "Bad Gateway",
+    ServiceUnavailable            => 503, "Service Unavailable",
+    GatewayTimeout                => 504, "Gateway Timeout",
+    HttpVersionNotSupported       => 505, "HTTP Version Not Supported",
+    VariantAlsoNegotiates         => 506, "Variant Also Negotiates",
+    InsufficientStorage           => 507, "Insufficient Storage",
+    LoopDetected                  => 508, "Loop Detected",
+    NotExtended                   => 510, "Not Extended",
+    NetworkAuthenticationRequired => 511, "Network Authentication Required",
+}
+
+/// The class of an HTTP status, given by the first digit of its code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumStr)]
+pub enum StatusClass {
+    /// `1xx`: the request was received, the processing continues.
+    Informational,
+    /// `2xx`: the request was accepted.
+    Success,
+    /// `3xx`: the client has to go somewhere else.
+    Redirection,
+    /// `4xx`: the request was wrong.
+    ClientError,
+    /// `5xx`: the server failed to answer a valid request.
+    ServerError,
+}
+
+impl HttpStatus {
+    /// Returns the class of the status.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::http::{HttpStatus, StatusClass};
+    ///
+    /// assert_eq!(HttpStatus::TooManyRequests.class(), StatusClass::ClientError);
+    /// assert!(HttpStatus::PermanentRedirect.is_redirect());
+    /// assert!(!HttpStatus::NotModified.is_success());
+    /// ```
+    pub const fn class(&self) -> StatusClass {
+        match self.code() {
+            100..=199 => StatusClass::Informational,
+            200..=299 => StatusClass::Success,
+            300..=399 => StatusClass::Redirection,
+            400..=499 => StatusClass::ClientError,
+            _ => StatusClass::ServerError,
+        }
+    }
+
+    /// Returns `true` for `1xx` statuses.
+    pub const fn is_informational(&self) -> bool {matches!(self.class(), StatusClass::Informational)}
+
+    /// Returns `true` for `2xx` statuses.
+    pub const fn is_success(&self) -> bool {matches!(self.class(), StatusClass::Success)}
+
+    /// Returns `true` for `3xx` statuses.
+    pub const fn is_redirect(&self) -> bool {matches!(self.class(), StatusClass::Redirection)}
+
+    /// Returns `true` for `4xx` statuses.
+    pub const fn is_client_error(&self) -> bool {matches!(self.class(), StatusClass::ClientError)}
+
+    /// Returns `true` for `5xx` statuses.
+    pub const fn is_server_error(&self) -> bool {matches!(self.class(), StatusClass::ServerError)}
+
+    // Informational and successful statuses are green, redirects cyan, client errors yellow
+    // and server errors red
+    fn color(&self) -> Color {
+        match self.class() {
+            StatusClass::Informational | StatusClass::Success => GREEN,
+            StatusClass::Redirection => CYAN,
+            StatusClass::ClientError => YELLOW,
+            StatusClass::ServerError => RED,
+        }
+    }
+}
+
+impl fmt::Display for HttpStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{} {}", self.code(), self.reason()).color(self.color()))
+    }
+}
+
+// Decodes the `%XX` escapes of a URL component, or `None` if an escape is malformed
+// or the result is not UTF-8
+pub(crate) fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            },
+            b => {
+                out.push(b);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Represents errors that can occur when reading HTTP messages or sending requests.
+#[derive(Debug)]
+pub enum HttpError {
+    /// The connection failed.
+    Io(io::Error),
+    /// The message is not valid HTTP (or uses a feature that is not supported).
+    InvalidRequest(String),
+    /// The response of a server is not valid HTTP (or can't be decoded).
+    InvalidResponse(String),
+    /// A request was redirected more times than the [Client] allows.
+    TooManyRedirects(usize),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Io(err) => write!(f, "IO error: {}", err),
+            HttpError::InvalidRequest(message) => write!(f, "Invalid request: {}", message),
+            HttpError::InvalidResponse(message) => write!(f, "Invalid response: {}", message),
+            HttpError::TooManyRedirects(max) => write!(f, "Too many redirects (more than {})", max),
+        }
+    }
+}
+
+impl Error for HttpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HttpError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HttpError {
+    fn from(err: io::Error) -> Self {HttpError::Io(err)}
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::strip_ansi_codes;
+
+    #[test]
+    fn test_method_and_status() {
+        assert_eq!("DELETE".parse::<HttpMethod>(), Ok(HttpMethod::Delete));
+        assert_eq!(HttpMethod::Get.to_string(), "GET");
+        assert_eq!("Head".parse::<HttpMethod>(), Ok(HttpMethod::Head));
+        assert_eq!("brew".parse::<HttpMethod>().map(|m| m.to_string()), Ok("brew".to_string()));
+        assert_eq!("".parse::<HttpMethod>(), Err("invalid HttpMethod: ".to_string()));
+        assert_eq!("GE:T".parse::<HttpMethod>(), Err("invalid HttpMethod: GE:T".to_string()));
+        assert!(HttpMethod::Options.is_safe() && !HttpMethod::Patch.is_safe());
+        assert_eq!(HttpStatus::from_code(404), Some(HttpStatus::NotFound));
+        assert_eq!(HttpStatus::from_code(299), None);
+        assert_eq!(strip_ansi_codes(&HttpStatus::InternalServerError.to_string()), "500 Internal Server Error");
+        assert_eq!(HttpStatus::Unauthorized.color(), YELLOW);
+    }
+
+    #[test]
+    fn test_status_class() {
+        assert_eq!(HttpStatus::from_code(418), Some(HttpStatus::ImATeapot));
+        assert_eq!(HttpStatus::from_code(511).map(|s| s.reason()), Some("Network Authentication Required"));
+        assert_eq!(HttpStatus::from_code(306), None);
+        assert_eq!(HttpStatus::EarlyHints.class(), StatusClass::Informational);
+        assert_eq!(HttpStatus::ServiceUnavailable.class(), StatusClass::ServerError);
+        assert!(HttpStatus::ImUsed.is_success() && !HttpStatus::ImUsed.is_redirect());
+        assert!(HttpStatus::SeeOther.is_redirect() && HttpStatus::Gone.is_client_error());
+        assert!(HttpStatus::Continue.is_informational() && HttpStatus::LoopDetected.is_server_error());
+        assert_eq!(StatusClass::ClientError.to_string(), "ClientError");
+        assert_eq!(HttpStatus::MovedPermanently.color(), CYAN);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b%2Fc").as_deref(), Some("a b/c"));
+        assert_eq!(percent_decode("%C3%B1").as_deref(), Some("ñ"));
+        assert_eq!(percent_decode("100%"), None);
+        assert_eq!(percent_decode("%zz"), None);
+        assert_eq!(percent_decode("%FF"), None);
+    }
+}
@@ -0,0 +1,300 @@
+//! Form bodies: `application/x-www-form-urlencoded` and `multipart/form-data`.
+//!
+//! Parsers for the requests a [DevServer](super::DevServer) gets from forms and file uploads,
+//! and encoders to build those requests in tests.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::http::body::{encode_multipart, Part};
+//! use dev_utils::http::{DevServer, HttpMethod, HttpRequest, HttpResponse, HttpStatus};
+//!
+//! let server = DevServer::new().post("/upload", |req| match req.multipart() {
+//!     Ok(parts) => {
+//!         let names: Vec<&str> = parts.iter().filter_map(|part| part.filename()).collect();
+//!         HttpResponse::text(HttpStatus::Ok, &names.join(","))
+//!     },
+//!     Err(err) => HttpResponse::text(HttpStatus::BadRequest, &err.to_string()),
+//! });
+//!
+//! let parts = [Part::text("title", "notes"), Part::file("doc", "notes.txt", "text/plain", "hi")];
+//! let request = HttpRequest::new(HttpMethod::Post, "/upload")
+//!     .with_header("Content-Type", "multipart/form-data; boundary=XyZ")
+//!     .with_body(encode_multipart(&parts, "XyZ"));
+//! assert_eq!(server.handle(&request).body, b"notes.txt");
+//! ```
+use std::fs;
+use crate::file::{FileError, TempFile};
+use super::{percent_decode, HttpError, HttpRequest};
+
+/// Parses an `application/x-www-form-urlencoded` string (a form body or a query string)
+/// into its name-value pairs, in order.
+///
+/// `+` stands for a space and `%XX` escapes are decoded; a pair without `=` gets an empty
+/// value, and malformed escapes are kept as written.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::body::parse_urlencoded;
+///
+/// let pairs = parse_urlencoded("q=rust+lang&tag=a%26b&empty&tag=c");
+/// assert_eq!(pairs, [("q", "rust lang"), ("tag", "a&b"), ("empty", ""), ("tag", "c")]
+///     .map(|(n, v)| (n.to_string(), v.to_string())));
+/// ```
+pub fn parse_urlencoded(text: &str) -> Vec<(String, String)> {
+    let decode = |s: &str| {
+        let s = s.replace('+', " ");
+        percent_decode(&s).unwrap_or(s)
+    };
+    text.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+/// Encodes name-value pairs as `application/x-www-form-urlencoded`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::body::encode_urlencoded;
+///
+/// assert_eq!(encode_urlencoded(&[("q", "rust lang"), ("tag", "a&b")]), "q=rust+lang&tag=a%26b");
+/// ```
+pub fn encode_urlencoded(pairs: &[(&str, &str)]) -> String {
+    let encode = |s: &str| s.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => (b as char).to_string(),
+        b' ' => "+".to_string(),
+        b => format!("%{:02X}", b),
+    }).collect::<String>();
+    pairs.iter().map(|(name, value)| format!("{}={}", encode(name), encode(value))).collect::<Vec<_>>().join("&")
+}
+
+/// A part of a `multipart/form-data` body: a form field or an uploaded file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part {
+    /// The headers of the part, like `Content-Disposition` and `Content-Type`.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Part {
+    /// Creates a text field.
+    pub fn text(name: &str, value: &str) -> Self {
+        Part {
+            headers: vec![("Content-Disposition".to_string(), format!("form-data; name=\"{}\"", name))],
+            body: value.as_bytes().to_vec(),
+        }
+    }
+
+    /// Creates a file field.
+    pub fn file(name: &str, filename: &str, content_type: &str, content: impl Into<Vec<u8>>) -> Self {
+        Part {
+            headers: vec![
+                ("Content-Disposition".to_string(), format!("form-data; name=\"{}\"; filename=\"{}\"", name, filename)),
+                ("Content-Type".to_string(), content_type.to_string()),
+            ],
+            body: content.into(),
+        }
+    }
+
+    /// Returns the value of the first header with the given name (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the name of the form field.
+    pub fn name(&self) -> Option<&str> {self.header("Content-Disposition").and_then(|cd| header_param(cd, "name"))}
+
+    /// Returns the name of the uploaded file (`None` for plain fields).
+    pub fn filename(&self) -> Option<&str> {self.header("Content-Disposition").and_then(|cd| header_param(cd, "filename"))}
+
+    /// Returns the content type of the part (`text/plain` if it has none).
+    pub fn content_type(&self) -> &str {self.header("Content-Type").unwrap_or("text/plain")}
+
+    /// Returns the body as text, or `None` if it is not valid UTF-8.
+    pub fn body_text(&self) -> Option<&str> {std::str::from_utf8(&self.body).ok()}
+
+    /// Writes the body to a temporary file (removed when dropped), keeping the extension of
+    /// the uploaded file, to hand it to code that works on paths.
+    pub fn persist(&self) -> Result<TempFile, FileError> {
+        let suffix = self.filename()
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, ext)| format!(".{}", ext))
+            .filter(|ext| ext.chars().all(|c| c == '.' || c.is_ascii_alphanumeric()))
+            .unwrap_or_default();
+        let file = TempFile::with_suffix(&suffix)?;
+        fs::write(file.path(), &self.body)?;
+        Ok(file)
+    }
+}
+
+// The value of a `; name=value` parameter of a header like `Content-Disposition`
+fn header_param<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    header.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| {
+            let value = value.trim();
+            value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value)
+        })
+    })
+}
+
+/// Returns the boundary of a `multipart/...` content type, or `None` for other types.
+pub fn multipart_boundary(content_type: &str) -> Option<&str> {
+    let kind = content_type.split(';').next()?.trim();
+    if !kind.to_ascii_lowercase().starts_with("multipart/") {return None;}
+    header_param(content_type, "boundary").filter(|b| !b.is_empty())
+}
+
+// The first index of `needle` in `haystack` at or after `from`
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack.get(from..)?.windows(needle.len()).position(|w| w == needle).map(|i| i + from)
+}
+
+/// Parses a `multipart/form-data` body with the given boundary.
+///
+/// # Returns
+///
+/// A `Result` containing the parts in order, or an [HttpError::InvalidRequest] if the
+/// body is not delimited by the boundary.
+pub fn parse_multipart(body: &[u8], boundary: &str) -> Result<Vec<Part>, HttpError> {
+    let invalid = |message: &str| HttpError::InvalidRequest(format!("multipart body: {}", message));
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let next_delimiter = [b"\r\n".as_slice(), &delimiter].concat();
+
+    // the first delimiter may follow a preamble
+    let mut pos = match body.starts_with(&delimiter) {
+        true => delimiter.len(),
+        false => find(body, &next_delimiter, 0).ok_or_else(|| invalid("no boundary"))? + next_delimiter.len(),
+    };
+    let mut parts = Vec::new();
+    loop {
+        if body[pos..].starts_with(b"--") {return Ok(parts);}
+        if !body[pos..].starts_with(b"\r\n") {return Err(invalid("malformed boundary line"));}
+        let start = pos + 2;
+        let end = find(body, &next_delimiter, start).ok_or_else(|| invalid("missing closing boundary"))?;
+        parts.push(parse_part(&body[start..end]).ok_or_else(|| invalid("malformed part headers"))?);
+        pos = end + next_delimiter.len();
+    }
+}
+
+// A part: headers, an empty line, and the body
+fn parse_part(raw: &[u8]) -> Option<Part> {
+    let (head, body) = match raw.starts_with(b"\r\n") {
+        true => (&raw[..0], &raw[2..]),
+        false => {
+            let split = find(raw, b"\r\n\r\n", 0)?;
+            (&raw[..split], &raw[split + 4..])
+        },
+    };
+    let headers = std::str::from_utf8(head).ok()?
+        .split("\r\n")
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split_once(':').map(|(n, v)| (n.trim().to_string(), v.trim().to_string())))
+        .collect::<Option<Vec<_>>>()?;
+    Some(Part { headers, body: body.to_vec() })
+}
+
+/// Encodes parts as a `multipart/form-data` body, to send with the content type
+/// `multipart/form-data; boundary=<boundary>`.
+pub fn encode_multipart(parts: &[Part], boundary: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    for part in parts {
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        for (name, value) in &part.headers {
+            body.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+        body.extend_from_slice(b"\r\n");
+        body.extend_from_slice(&part.body);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+impl HttpRequest {
+    /// Returns the fields of a urlencoded form body, or `None` if the request has another
+    /// content type (or a body that is not UTF-8).
+    pub fn form(&self) -> Option<Vec<(String, String)>> {
+        let content_type = self.header("Content-Type")?.split(';').next()?.trim();
+        match content_type.eq_ignore_ascii_case("application/x-www-form-urlencoded") {
+            true => self.body_text().map(parse_urlencoded),
+            false => None,
+        }
+    }
+
+    /// Parses a `multipart/form-data` body.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the parts, or an [HttpError::InvalidRequest] if the request is
+    /// not multipart or its body is malformed.
+    pub fn multipart(&self) -> Result<Vec<Part>, HttpError> {
+        let boundary = self.header("Content-Type")
+            .and_then(multipart_boundary)
+            .ok_or_else(|| HttpError::InvalidRequest("not a multipart request".to_string()))?;
+        parse_multipart(&self.body, boundary)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpMethod;
+
+    #[test]
+    fn test_urlencoded() {
+        let pairs = [("name", "Ana María"), ("math", "1+1=2 & 50%")];
+        let encoded = encode_urlencoded(&pairs);
+        assert_eq!(encoded, "name=Ana+Mar%C3%ADa&math=1%2B1%3D2+%26+50%25");
+        let decoded = parse_urlencoded(&encoded);
+        assert_eq!(decoded.iter().map(|(n, v)| (n.as_str(), v.as_str())).collect::<Vec<_>>(), pairs);
+        assert_eq!(parse_urlencoded("a=100%&&b"), [("a".to_string(), "100%".to_string()), ("b".to_string(), String::new())]);
+
+        let request = HttpRequest::new(HttpMethod::Post, "/")
+            .with_header("Content-Type", "application/x-www-form-urlencoded; charset=UTF-8")
+            .with_body("x=1");
+        assert_eq!(request.form(), Some(vec![("x".to_string(), "1".to_string())]));
+        assert_eq!(HttpRequest::new(HttpMethod::Post, "/").with_body("x=1").form(), None);
+    }
+
+    #[test]
+    fn test_parse_multipart() {
+        // as a browser sends it: a preamble, a field, a file, and an epilogue
+        let body = b"ignored\r\n--b0und\r\nContent-Disposition: form-data; name=\"id\"\r\n\r\n42\r\n\
+            --b0und\r\ncontent-disposition: form-data; name=\"up\"; filename=\"a b.png\"\r\ncontent-type: image/png\r\n\r\n\
+            \x89PNG\r\n\r\n--b0und--\r\nepilogue";
+        let parts = parse_multipart(body, "b0und").unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!((parts[0].name(), parts[0].filename(), parts[0].body_text()), (Some("id"), None, Some("42")));
+        assert_eq!((parts[1].name(), parts[1].filename(), parts[1].content_type()), (Some("up"), Some("a b.png"), "image/png"));
+        assert_eq!(parts[1].body, b"\x89PNG\r\n");
+
+        let file = parts[1].persist().unwrap();
+        assert!(file.path().to_string_lossy().ends_with(".png"));
+        assert_eq!(fs::read(file.path()).unwrap(), b"\x89PNG\r\n");
+
+        let error = |body: &[u8]| parse_multipart(body, "b0und").unwrap_err().to_string();
+        assert_eq!(error(b"--other--"), "Invalid request: multipart body: no boundary");
+        assert_eq!(error(b"--b0und\r\n\r\nno end"), "Invalid request: multipart body: missing closing boundary");
+        assert_eq!(error(b"--b0und\r\nbad header\r\n\r\nx\r\n--b0und--"), "Invalid request: multipart body: malformed part headers");
+    }
+
+    #[test]
+    fn test_multipart_round_trip() {
+        assert_eq!(multipart_boundary("Multipart/Form-Data; boundary=\"abc\""), Some("abc"));
+        assert_eq!(multipart_boundary("text/plain; boundary=abc"), None);
+
+        let parts = vec![Part::text("empty", ""), Part::file("f", "data.bin", "application/octet-stream", vec![0, 13, 10, 45, 45])];
+        let request = HttpRequest::new(HttpMethod::Post, "/")
+            .with_header("Content-Type", "multipart/form-data; boundary=--x--")
+            .with_body(encode_multipart(&parts, "--x--"));
+        assert_eq!(request.multipart().unwrap(), parts);
+        assert!(HttpRequest::new(HttpMethod::Post, "/").multipart().is_err());
+    }
+}
@@ -0,0 +1,607 @@
+//! A blocking HTTP/1.1 client, to call the [DevServer](super::DevServer) (or any plain
+//! `http://` endpoint) from tests and scripts.
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::codex::gzip;
+#[cfg(feature = "tls")]
+use super::tls::{Tls, TlsProvider};
+use super::request::{read_headers, read_line, MAX_BODY};
+use super::{HttpError, HttpMethod, HttpRequest, HttpResponse, HttpStatus};
+
+/// Configures a [Client]: timeouts, redirects, default headers, compression and pooling.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::http::ClientBuilder;
+///
+/// let client = ClientBuilder::new()
+///     .timeout(Duration::from_secs(5))
+///     .max_redirects(3)
+///     .default_header("Authorization", "Bearer dev-token")
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    max_redirects: usize,
+    headers: Vec<(String, String)>,
+    gzip: bool,
+    max_idle_per_host: usize,
+    #[cfg(feature = "tls")]
+    tls: Option<Tls>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        ClientBuilder {
+            timeout: Some(Duration::from_secs(30)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            max_redirects: 10,
+            headers: vec![("User-Agent".to_string(), format!("dev_utils/{}", env!("CARGO_PKG_VERSION")))],
+            gzip: true,
+            max_idle_per_host: 4,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a builder with the default settings: 30 s read/write timeout, 10 s connect
+    /// timeout, up to 10 redirects, gzip decoding and up to 4 idle connections per host.
+    pub fn new() -> Self {Self::default()}
+
+    /// Sets the timeout of each read and write on the connection.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout to establish a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many redirects are followed; with `0` the redirect responses are returned.
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.max_redirects = max;
+        self
+    }
+
+    /// Adds a header sent with every request (unless the request sets it), replacing a
+    /// default header with the same name.
+    pub fn default_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Enables or disables asking for gzip-compressed responses and decoding them.
+    pub fn gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    /// Sets how many idle connections are kept per host; `0` opens a connection per request.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.max_idle_per_host = max;
+        self
+    }
+
+    /// Sets the provider of the TLS sessions for `https://` URLs (requires the `tls` feature).
+    #[cfg(feature = "tls")]
+    pub fn tls<P: TlsProvider + 'static>(mut self, provider: P) -> Self {
+        self.tls = Some(Tls(Arc::new(provider)));
+        self
+    }
+
+    /// Builds the client.
+    pub fn build(self) -> Client {Client { config: self, pool: Mutex::new(HashMap::new()) }}
+}
+
+// A plain or TLS connection
+trait Stream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Stream for T {}
+
+type Connection = BufReader<Box<dyn Stream>>;
+
+/// A blocking HTTP/1.1 client that follows redirects, decodes gzip bodies and keeps the
+/// connections alive to reuse them. `https://` URLs need a [TlsProvider](super::tls::TlsProvider)
+/// (the `tls` feature).
+///
+/// A client can be shared between threads; requests to the same host reuse its idle connections.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::{Client, DevServer, HttpMethod, HttpResponse, HttpStatus};
+///
+/// let server = DevServer::new()
+///     .get("/old", |_| HttpResponse::new(HttpStatus::MovedPermanently).with_header("Location", "/new"))
+///     .get("/new", |_| HttpResponse::text(HttpStatus::Ok, "moved here"))
+///     .post("/echo", |req| HttpResponse::new(HttpStatus::Ok).with_body(req.body.clone()))
+///     .bind("127.0.0.1:0")
+///     .unwrap();
+///
+/// let client = Client::new();
+/// let response = client.get(&format!("{}/old", server.url())).unwrap();
+/// assert_eq!((response.status, response.body.as_slice()), (HttpStatus::Ok, &b"moved here"[..]));
+///
+/// let response = client.request(HttpMethod::Post, &format!("{}/echo", server.url()))
+///     .with_header("Content-Type", "text/plain")
+///     .with_body("ping")
+///     .send()
+///     .unwrap();
+/// assert_eq!(response.body, b"ping");
+/// ```
+pub struct Client {
+    config: ClientBuilder,
+    pool: Mutex<HashMap<String, Vec<Connection>>>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let idle: HashMap<String, usize> = self.pool.lock().unwrap().iter().map(|(origin, idle)| (origin.clone(), idle.len())).collect();
+        f.debug_struct("Client").field("config", &self.config).field("idle", &idle).finish()
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {Self::new()}
+}
+
+impl Client {
+    /// Creates a client with the default settings of [ClientBuilder::new].
+    pub fn new() -> Self {ClientBuilder::new().build()}
+
+    /// Returns a builder to configure a client.
+    pub fn builder() -> ClientBuilder {ClientBuilder::new()}
+
+    /// Sends a `GET` request.
+    pub fn get(&self, url: &str) -> Result<HttpResponse, HttpError> {self.request(HttpMethod::Get, url).send()}
+
+    /// Sends a `POST` request with a body.
+    pub fn post(&self, url: &str, body: impl Into<Vec<u8>>) -> Result<HttpResponse, HttpError> {
+        self.request(HttpMethod::Post, url).with_body(body).send()
+    }
+
+    /// Starts a request, to add headers and a body before sending it.
+    pub fn request(&self, method: HttpMethod, url: &str) -> ClientRequest<'_> {
+        ClientRequest { client: self, url: url.to_string(), request: HttpRequest::new(method, "/") }
+    }
+
+    fn execute(&self, url: &str, mut request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        let mut url = Url::parse(url)?;
+        let mut redirects = 0;
+        loop {
+            request.path.clone_from(&url.path);
+            request.query.clone_from(&url.query);
+            let response = self.exchange(&url, &request)?;
+            let Some(location) = response.header("Location").filter(|_| is_redirect(response.status)) else {return Ok(response);};
+            if self.config.max_redirects == 0 {return Ok(response);}
+            if redirects == self.config.max_redirects {return Err(HttpError::TooManyRedirects(redirects));}
+            redirects += 1;
+
+            let next = url.join(location)?;
+            if next.origin() != url.origin() {
+                // credentials are not sent to other hosts
+                request.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("Authorization") && !name.eq_ignore_ascii_case("Cookie"));
+            }
+            // 303 (and 301/302 after a POST, as browsers do) continue with a GET without body
+            let to_get = (response.status == HttpStatus::SeeOther && request.method != HttpMethod::Head)
+                || (matches!(response.status, HttpStatus::MovedPermanently | HttpStatus::Found) && request.method == HttpMethod::Post);
+            if to_get {
+                request.method = HttpMethod::Get;
+                request.body.clear();
+                request.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("Content-Type"));
+            }
+            url = next;
+        }
+    }
+
+    // Sends a request and reads its response, on a pooled connection if there is one
+    fn exchange(&self, url: &Url, request: &HttpRequest) -> Result<HttpResponse, HttpError> {
+        let raw = self.serialize(url, request);
+        let head = request.method == HttpMethod::Head;
+        let key = url.origin();
+        let idle = self.pool.lock().unwrap().get_mut(&key).and_then(Vec::pop);
+        let (mut response, reusable, connection) = match idle.map(|mut conn| (attempt(&mut conn, &raw, head), conn)) {
+            Some((Ok((response, reusable)), conn)) => (response, reusable, conn),
+            // the server may have closed an idle connection: retry on a new one
+            Some((Err(HttpError::Io(_)), _)) | None => {
+                let mut conn = self.connect(url)?;
+                let (response, reusable) = attempt(&mut conn, &raw, head)?;
+                (response, reusable, conn)
+            },
+            Some((Err(err), _)) => return Err(err),
+        };
+        if reusable && self.config.max_idle_per_host > 0 {
+            let mut pool = self.pool.lock().unwrap();
+            let idle = pool.entry(key).or_default();
+            if idle.len() < self.config.max_idle_per_host {idle.push(connection);}
+        }
+
+        let encoding = response.header("Content-Encoding").map(str::to_ascii_lowercase);
+        if self.config.gzip && encoding.as_deref() == Some("gzip") && !response.body.is_empty() {
+            response.body = gzip::decompress(&response.body).map_err(|err| HttpError::InvalidResponse(err.to_string()))?;
+            response.headers.retain(|(name, _)| !name.eq_ignore_ascii_case("Content-Encoding") && !name.eq_ignore_ascii_case("Content-Length"));
+        }
+        Ok(response)
+    }
+
+    fn connect(&self, url: &Url) -> Result<Connection, HttpError> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, format!("no address for {}", url.host));
+        for addr in (url.host.as_str(), url.port).to_socket_addrs()? {
+            let stream = match self.config.connect_timeout {
+                Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                None => TcpStream::connect(addr),
+            };
+            match stream {
+                Ok(stream) => {
+                    stream.set_read_timeout(self.config.timeout)?;
+                    stream.set_write_timeout(self.config.timeout)?;
+                    return self.secure(url, stream).map(BufReader::new);
+                },
+                Err(err) => last_error = err,
+            }
+        }
+        Err(last_error.into())
+    }
+
+    fn secure(&self, url: &Url, stream: TcpStream) -> Result<Box<dyn Stream>, HttpError> {
+        if !url.tls {return Ok(Box::new(stream));}
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.config.tls {return Ok(Box::new(tls.0.connect(&url.host, stream)?));}
+        Err(HttpError::InvalidRequest(format!("no TLS provider for https://{}", url.authority())))
+    }
+
+    // The request head and body, with the headers of the client (the ones of the request win)
+    fn serialize(&self, url: &Url, request: &HttpRequest) -> Vec<u8> {
+        let mut headers = vec![("Host".to_string(), url.host_header())];
+        headers.extend(self.config.headers.iter().cloned());
+        if self.config.gzip {headers.push(("Accept-Encoding".to_string(), "gzip".to_string()));}
+        if self.config.max_idle_per_host == 0 {headers.push(("Connection".to_string(), "close".to_string()));}
+        headers.retain(|(name, _)| request.header(name).is_none());
+        headers.extend(request.headers.iter().cloned());
+        let needs_length = !request.body.is_empty() || matches!(request.method, HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch);
+        if needs_length && request.header("Content-Length").is_none() {
+            headers.push(("Content-Length".to_string(), request.body.len().to_string()));
+        }
+
+        let target = match request.query.is_empty() {
+            true => request.path.clone(),
+            false => format!("{}?{}", request.path, request.query),
+        };
+        let mut raw = format!("{} {} HTTP/1.1\r\n", request.method, target);
+        headers.iter().for_each(|(name, value)| raw += &format!("{}: {}\r\n", name, value));
+        raw += "\r\n";
+        let mut raw = raw.into_bytes();
+        raw.extend_from_slice(&request.body);
+        raw
+    }
+}
+
+/// A request being built by [Client::request].
+#[derive(Debug)]
+pub struct ClientRequest<'a> {
+    client: &'a Client,
+    url: String,
+    request: HttpRequest,
+}
+
+impl ClientRequest<'_> {
+    /// Adds a header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.request = self.request.with_header(name, value);
+        self
+    }
+
+    /// Sets the body.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.request = self.request.with_body(body);
+        self
+    }
+
+    /// Sends the request, following redirects.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the final response (whatever its status), or an [HttpError] if
+    /// the URL is not supported, the connection fails or the response is not valid HTTP.
+    pub fn send(self) -> Result<HttpResponse, HttpError> {self.client.execute(&self.url, self.request)}
+}
+
+fn is_redirect(status: HttpStatus) -> bool {
+    matches!(status, HttpStatus::MovedPermanently | HttpStatus::Found | HttpStatus::SeeOther
+        | HttpStatus::TemporaryRedirect | HttpStatus::PermanentRedirect)
+}
+
+// Writes a request and reads the response, telling if the connection can be reused
+fn attempt(conn: &mut Connection, raw: &[u8], head: bool) -> Result<(HttpResponse, bool), HttpError> {
+    conn.get_mut().write_all(raw)?;
+    conn.get_mut().flush()?;
+    read_response(conn, head).map_err(|err| match err {
+        HttpError::InvalidRequest(message) => HttpError::InvalidResponse(message),
+        err => err,
+    })
+}
+
+// Reads a response; the body is delimited by its length, chunks, or the end of the stream
+fn read_response<R: BufRead>(reader: &mut R, head: bool) -> Result<(HttpResponse, bool), HttpError> {
+    let invalid = |message: String| HttpError::InvalidResponse(message);
+    // informational responses (like 103 Early Hints) come before the final one
+    let (version, status, headers) = loop {
+        let line = read_line(reader)?.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let mut parts = line.splitn(3, ' ');
+        let (Some(version), Some(code)) = (parts.next(), parts.next()) else {return Err(invalid(format!("malformed status line {:?}", line)));};
+        if !version.starts_with("HTTP/1.") {return Err(invalid(format!("unsupported version {}", version)));}
+        let code = code.parse().ok().and_then(HttpStatus::from_code).ok_or_else(|| invalid(format!("unknown status {}", code)))?;
+        let headers = read_headers(reader)?;
+        if !code.is_informational() || code == HttpStatus::SwitchingProtocols {break (version.to_string(), code, headers);}
+    };
+    let mut response = HttpResponse::new(status);
+    response.headers = headers;
+
+    let connection = response.header("Connection").unwrap_or("").to_ascii_lowercase();
+    let mut reusable = match version.as_str() {
+        "HTTP/1.0" => connection.contains("keep-alive"),
+        _ => !connection.contains("close"),
+    };
+    let chunked = response.header("Transfer-Encoding").is_some_and(|te| te.to_ascii_lowercase().contains("chunked"));
+    if head || status.is_informational() || matches!(status, HttpStatus::NoContent | HttpStatus::NotModified) {
+        // no body
+    } else if chunked {
+        response.body = read_chunked(reader)?;
+    } else if let Some(length) = response.header("Content-Length") {
+        let length: usize = length.trim().parse().map_err(|_| invalid(format!("invalid Content-Length {}", length)))?;
+        if length > MAX_BODY {return Err(invalid("body too large".to_string()));}
+        response.body = vec![0; length];
+        reader.read_exact(&mut response.body)?;
+    } else {
+        reader.take(MAX_BODY as u64).read_to_end(&mut response.body)?;
+        reusable = false;
+    }
+    Ok((response, reusable))
+}
+
+fn read_chunked<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, HttpError> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)?.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        let size = line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| HttpError::InvalidResponse(format!("invalid chunk size {:?}", size)))?;
+        if body.len() + size > MAX_BODY {return Err(HttpError::InvalidResponse("body too large".to_string()));}
+        if size == 0 {
+            // trailer fields are read and dropped
+            read_headers(reader)?;
+            return Ok(body);
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        let mut crlf = [0; 2];
+        reader.read_exact(&mut crlf)?;
+        if &crlf != b"\r\n" {return Err(HttpError::InvalidResponse("malformed chunk".to_string()));}
+    }
+}
+
+// An `http://` URL, split into what the client needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Url {
+    tls: bool,
+    host: String,
+    port: u16,
+    path: String,
+    query: String,
+}
+
+impl Url {
+    fn parse(url: &str) -> Result<Self, HttpError> {
+        let unsupported = || HttpError::InvalidRequest(format!("unsupported URL {}", url));
+        let (scheme, rest) = url.split_once("://").ok_or_else(unsupported)?;
+        let tls = match scheme.to_ascii_lowercase().as_str() {
+            "http" => false,
+            "https" => true,
+            _ => return Err(unsupported()),
+        };
+        let rest = rest.split('#').next().unwrap_or("");
+        let end = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, target) = rest.split_at(end);
+        let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port.parse().map_err(|_| unsupported())?),
+            _ => (authority, if tls {443} else {80}),
+        };
+        if host.is_empty() {return Err(unsupported());}
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        Ok(Url {
+            tls,
+            host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            port,
+            path: if path.is_empty() {"/".to_string()} else {path.to_string()},
+            query: query.to_string(),
+        })
+    }
+
+    fn authority(&self) -> String {format!("{}:{}", self.host, self.port)}
+
+    // The connections to the same origin can be shared
+    fn origin(&self) -> String {format!("{}://{}", if self.tls {"https"} else {"http"}, self.authority())}
+
+    fn host_header(&self) -> String {
+        let host = match self.host.contains(':') {
+            true => format!("[{}]", self.host),
+            false => self.host.clone(),
+        };
+        match (self.tls, self.port) {
+            (false, 80) | (true, 443) => host,
+            (_, port) => format!("{}:{}", host, port),
+        }
+    }
+
+    // Resolves the target of a `Location` header against this URL (RFC 3986, section 5.2)
+    fn join(&self, location: &str) -> Result<Self, HttpError> {
+        if location.contains("://") {return Url::parse(location);}
+        if let Some(rest) = location.strip_prefix("//") {
+            return Url::parse(&format!("{}://{}", if self.tls {"https"} else {"http"}, rest));
+        }
+        let location = location.split('#').next().unwrap_or("");
+        let (path, query) = match location.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (location, None),
+        };
+        let query = match (path.is_empty(), query) {
+            (_, Some(query)) => query.to_string(),
+            (true, None) => self.query.clone(),
+            (false, None) => String::new(),
+        };
+        let path = match path {
+            "" => self.path.clone(),
+            path if path.starts_with('/') => path.to_string(),
+            path => format!("{}{}", &self.path[..=self.path.rfind('/').unwrap_or(0)], path),
+        };
+        // `.` and `..` segments are removed
+        let mut segments: Vec<&str> = Vec::new();
+        let parts: Vec<&str> = path.split('/').skip(1).collect();
+        for (i, segment) in parts.iter().enumerate() {
+            match *segment {
+                "." => {},
+                ".." => {segments.pop();},
+                segment => segments.push(segment),
+            }
+            // a trailing dot segment still names a directory
+            if i == parts.len() - 1 && matches!(*segment, "." | "..") {segments.push("");}
+        }
+        Ok(Url { tls: self.tls, host: self.host.clone(), port: self.port, path: format!("/{}", segments.join("/")), query })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+    use crate::http::DevServer;
+
+    #[test]
+    fn test_url() {
+        let url = Url::parse("http://user@localhost:8080/a/b?x=1#top").unwrap();
+        assert_eq!((url.host.as_str(), url.port, url.path.as_str(), url.query.as_str()), ("localhost", 8080, "/a/b", "x=1"));
+        assert_eq!(Url::parse("HTTP://[::1]?q").unwrap().host_header(), "[::1]");
+        assert_eq!(Url::parse("http://example.com").unwrap().path, "/");
+        assert_eq!(Url::parse("https://example.com").unwrap().origin(), "https://example.com:443");
+        assert!(Url::parse("ftp://example.com").is_err());
+        assert!(Url::parse("localhost:80/").is_err());
+
+        let join = |location: &str| {
+            let url = url.join(location).unwrap();
+            format!("{}{}?{}", url.authority(), url.path, url.query)
+        };
+        assert_eq!(join("/c"), "localhost:8080/c?");
+        assert_eq!(join("c?y=2"), "localhost:8080/a/c?y=2");
+        assert_eq!(join("../c/./d"), "localhost:8080/c/d?");
+        assert_eq!(join(".."), "localhost:8080/?");
+        assert_eq!(join("#frag"), "localhost:8080/a/b?x=1");
+        assert_eq!(join("http://other/"), "other:80/?");
+        assert_eq!(join("//other:81/d"), "other:81/d?");
+    }
+
+    #[test]
+    fn test_read_response() {
+        let raw = "HTTP/1.1 103 Early Hints\r\nLink: </a.css>\r\n\r\n\
+            HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5;ext=1\r\nhello\r\n6\r\n world\r\n0\r\nX-Trailer: 1\r\n\r\n\
+            HTTP/1.0 404 Not Found\r\n\r\nmissing";
+        let mut reader = raw.as_bytes();
+        let (response, reusable) = read_response(&mut reader, false).unwrap();
+        assert_eq!((response.status, response.body.as_slice(), reusable), (HttpStatus::Ok, &b"hello world"[..], true));
+        let (response, reusable) = read_response(&mut reader, false).unwrap();
+        assert_eq!((response.status, response.body.as_slice(), reusable), (HttpStatus::NotFound, &b"missing"[..], false));
+
+        let error = |raw: &str| read_response(&mut raw.as_bytes(), false).unwrap_err().to_string();
+        assert_eq!(error("HTTP/1.1 299 Odd\r\n\r\n"), "Invalid response: unknown status 299");
+        assert_eq!(error("SSH-2.0-OpenSSH\r\n\r\n"), "Invalid response: malformed status line \"SSH-2.0-OpenSSH\"");
+        assert_eq!(error("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n"), "Invalid response: invalid chunk size \"zz\"");
+        let (response, _) = read_response(&mut "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n".as_bytes(), true).unwrap();
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn test_redirects_and_gzip() {
+        // `gzip -n` of "hello hello hello hello"
+        let gzipped = [31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 203, 72, 205, 201, 201, 87, 200, 64, 39, 1, 227, 81, 61, 141, 23, 0, 0, 0];
+        let server = DevServer::new()
+            .get("/loop", |_| HttpResponse::new(HttpStatus::Found).with_header("Location", "/loop"))
+            .post("/form", |_| HttpResponse::new(HttpStatus::SeeOther).with_header("Location", "/result"))
+            .get("/result", |req| HttpResponse::text(HttpStatus::Ok, &format!("{} {}", req.method, req.body.len())))
+            .get("/gzip", move |req| match req.header("Accept-Encoding") {
+                Some("gzip") => HttpResponse::new(HttpStatus::Ok).with_header("Content-Encoding", "gzip").with_body(gzipped),
+                _ => HttpResponse::new(HttpStatus::NotAcceptable),
+            })
+            .bind("127.0.0.1:0")
+            .unwrap();
+
+        let client = Client::builder().max_redirects(3).build();
+        let error = client.get(&format!("{}/loop", server.url())).unwrap_err();
+        assert!(matches!(error, HttpError::TooManyRedirects(3)));
+        let response = client.post(&format!("{}/form", server.url()), "a=1").unwrap();
+        assert_eq!(response.body, b"GET 0");
+        let response = Client::builder().max_redirects(0).build().get(&format!("{}/loop", server.url())).unwrap();
+        assert_eq!(response.status, HttpStatus::Found);
+
+        let response = client.get(&format!("{}/gzip", server.url())).unwrap();
+        assert_eq!((response.body.as_slice(), response.header("Content-Encoding")), (&b"hello hello hello hello"[..], None));
+        let response = Client::builder().gzip(false).build().get(&format!("{}/gzip", server.url())).unwrap();
+        assert_eq!(response.status, HttpStatus::NotAcceptable);
+    }
+
+    #[test]
+    fn test_connection_reuse() {
+        // a server that answers on the connections it accepts, until each one is closed
+        let serve = |connections: usize, requests_per_connection: usize| {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/", listener.local_addr().unwrap());
+            let server = thread::spawn(move || (0..connections).map(|_| {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut served = 0;
+                while served < requests_per_connection {
+                    let Ok(Some(_)) = HttpRequest::read_from(&mut reader) else {break;};
+                    HttpResponse::text(HttpStatus::Ok, "ok").write_to(&mut &stream).unwrap();
+                    served += 1;
+                }
+                served
+            }).collect::<Vec<_>>());
+            (url, server)
+        };
+        let client = || Client::builder().timeout(Duration::from_secs(5)).build();
+
+        let (url, server) = serve(1, usize::MAX);
+        let pooled = client();
+        for _ in 0..3 {assert_eq!(pooled.get(&url).unwrap().body, b"ok");}
+        drop(pooled);
+        assert_eq!(server.join().unwrap(), [3]);
+
+        // the server closes each connection after a response: the idle one is stale
+        let (url, server) = serve(2, 1);
+        let pooled = client();
+        for _ in 0..2 {assert_eq!(pooled.get(&url).unwrap().body, b"ok");}
+        assert_eq!(server.join().unwrap(), [1, 1]);
+
+        let (url, server) = serve(2, usize::MAX);
+        let unpooled = Client::builder().pool_max_idle_per_host(0).build();
+        for _ in 0..2 {assert_eq!(unpooled.get(&url).unwrap().body, b"ok");}
+        assert!(unpooled.pool.lock().unwrap().is_empty());
+        assert_eq!(server.join().unwrap(), [1, 1]);
+    }
+}
@@ -0,0 +1,242 @@
+//! A zero-config status page for long-running dev tools: counters, log rates and health checks.
+//!
+//! Counters ([counter]) and health checks ([health_check]) are registered globally, from
+//! anywhere in the program; the log rates come from [dlog::record_counts]. The dashboard
+//! serves them as a JSON document (`/metrics.json`) and an HTML page (`/`) that refreshes
+//! itself every [REFRESH_INTERVAL].
+//!
+//! # Examples
+//! ```no_run
+//! use dev_utils::http::dashboard;
+//!
+//! let jobs = dashboard::counter("jobs_done");
+//! dashboard::health_check("queue", || Ok(()));
+//! let running = dashboard::bind("127.0.0.1:9000").unwrap();  // in the background
+//! println!("status page on {}", running.url());
+//! loop {
+//!     // some work...
+//!     jobs.inc();
+//! }
+//! ```
+use std::collections::BTreeMap;
+use std::io;
+use std::net::ToSocketAddrs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+use crate::dlog::{self, json_escape, Level};
+use super::{DevServer, HttpResponse, HttpStatus, ServerHandle};
+
+/// Time between two refreshes of the dashboard page.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A named counter shown on the dashboard, shared by every clone (see [counter]).
+#[derive(Debug, Clone)]
+pub struct Counter {
+    value: Arc<AtomicU64>,
+}
+
+impl Counter {
+    /// Adds one to the counter.
+    pub fn inc(&self) {self.add(1);}
+
+    /// Adds `n` to the counter.
+    pub fn add(&self, n: u64) {self.value.fetch_add(n, Ordering::Relaxed);}
+
+    /// Returns the current value.
+    pub fn get(&self) -> u64 {self.value.load(Ordering::Relaxed)}
+}
+
+type HealthCheck = Arc<dyn Fn() -> Result<(), String> + Send + Sync>;
+
+static COUNTERS: Mutex<BTreeMap<String, Counter>> = Mutex::new(BTreeMap::new());
+static HEALTH_CHECKS: Mutex<BTreeMap<String, HealthCheck>> = Mutex::new(BTreeMap::new());
+
+// When the dashboard module was first used and the log counts at that time, for the uptime
+// and the log rates (records logged earlier are not part of the rates)
+fn start() -> &'static (Instant, Vec<(Level, u64)>) {
+    static START: OnceLock<(Instant, Vec<(Level, u64)>)> = OnceLock::new();
+    START.get_or_init(|| (Instant::now(), dlog::record_counts()))
+}
+
+/// Returns the counter named `name`, creating it (at 0) on first use.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::dashboard;
+///
+/// dashboard::counter("example_requests").add(2);
+/// assert_eq!(dashboard::counter("example_requests").get(), 2);
+/// ```
+pub fn counter(name: &str) -> Counter {
+    start();
+    COUNTERS.lock().unwrap_or_else(PoisonError::into_inner)
+        .entry(name.to_string())
+        .or_insert_with(|| Counter { value: Arc::new(AtomicU64::new(0)) })
+        .clone()
+}
+
+/// Registers a health check, run on every dashboard refresh: `Ok(())` when healthy, or the
+/// message explaining the failure. A check registered again under the same name replaces it.
+pub fn health_check<F>(name: &str, check: F)
+where F: Fn() -> Result<(), String> + Send + Sync + 'static {
+    start();
+    HEALTH_CHECKS.lock().unwrap_or_else(PoisonError::into_inner).insert(name.to_string(), Arc::new(check));
+}
+
+/// Returns the current state as JSON: uptime, counters, log counts and rates, and health.
+///
+/// The log counts cover the whole process, while the rates only count the records logged
+/// since the dashboard started (over the uptime).
+///
+/// ```text
+/// {"uptime_secs":12.5,"counters":{"jobs_done":3},
+///  "logs":{"Error":{"count":1,"per_minute":4.8},...},
+///  "health":{"ok":false,"checks":{"queue":{"ok":false,"message":"full"}}}}
+/// ```
+pub fn snapshot() -> String {
+    let (started, baseline) = start();
+    let uptime = started.elapsed().as_secs_f64();
+    let counters = COUNTERS.lock().unwrap_or_else(PoisonError::into_inner).iter()
+        .map(|(name, counter)| format!("\"{}\":{}", json_escape(name), counter.get()))
+        .collect::<Vec<_>>();
+    let logs = log_rates(&dlog::record_counts(), baseline, uptime);
+
+    // cloned, so that the checks run without the lock (they may register counters)
+    let checks: Vec<(String, HealthCheck)> = HEALTH_CHECKS.lock().unwrap_or_else(PoisonError::into_inner).iter()
+        .map(|(name, check)| (name.clone(), Arc::clone(check)))
+        .collect();
+    let results: Vec<(String, Result<(), String>)> = checks.into_iter().map(|(name, check)| (name, check())).collect();
+    let healthy = results.iter().all(|(_, result)| result.is_ok());
+    let checks = results.iter()
+        .map(|(name, result)| match result {
+            Ok(()) => format!("\"{}\":{{\"ok\":true}}", json_escape(name)),
+            Err(message) => format!("\"{}\":{{\"ok\":false,\"message\":\"{}\"}}", json_escape(name), json_escape(message)),
+        })
+        .collect::<Vec<_>>();
+
+    format!("{{\"uptime_secs\":{:.1},\"counters\":{{{}}},\"logs\":{{{}}},\"health\":{{\"ok\":{},\"checks\":{{{}}}}}}}",
+        uptime, counters.join(","), logs.join(","), healthy, checks.join(",")
+    )
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>dev_utils dashboard</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 2em; background: #111; color: #ddd; }
+  h2 { margin-top: 1.5em; }
+  table { border-collapse: collapse; min-width: 20em; }
+  td { padding: 0.2em 1em 0.2em 0; }
+  .ok { color: #4c4; } .fail { color: #e55; } .dim { color: #888; }
+</style>
+</head>
+<body>
+<h1>Status <span id="health"></span></h1>
+<p class="dim">up <span id="uptime"></span>, refreshed every {refresh} seconds</p>
+<h2>Counters</h2><table id="counters"></table>
+<h2>Logs</h2><table id="logs"></table>
+<h2>Health checks</h2><table id="checks"></table>
+<script>
+const rows = (id, entries) => document.getElementById(id).innerHTML =
+  entries.map(cells => "<tr>" + cells.map(c => "<td>" + c + "</td>").join("") + "</tr>").join("");
+const escape = s => s.replace(/[&<>"]/g, c => ({"&": "&amp;", "<": "&lt;", ">": "&gt;", '"': "&quot;"})[c]);
+async function refresh() {
+  try {
+    const m = await (await fetch("metrics.json")).json();
+    document.getElementById("uptime").textContent = m.uptime_secs.toFixed(0) + "s";
+    document.getElementById("health").innerHTML = m.health.ok ? '<span class="ok">ok</span>' : '<span class="fail">failing</span>';
+    rows("counters", Object.entries(m.counters).map(([k, v]) => [escape(k), v]));
+    rows("logs", Object.entries(m.logs).map(([k, v]) => [k, v.count, v.per_minute + "/min"]));
+    rows("checks", Object.entries(m.health.checks).map(([k, v]) =>
+      [escape(k), v.ok ? '<span class="ok">ok</span>' : '<span class="fail">' + escape(v.message) + "</span>"]));
+  } catch (e) {
+    document.getElementById("health").innerHTML = '<span class="fail">unreachable</span>';
+  }
+}
+refresh();
+setInterval(refresh, {refresh} * 1000);
+</script>
+</body>
+</html>
+"#;
+
+// The JSON entries of the log counts, with the rates of the records logged after `baseline`
+fn log_rates(counts: &[(Level, u64)], baseline: &[(Level, u64)], uptime: f64) -> Vec<String> {
+    counts.iter().zip(baseline)
+        .map(|(&(level, count), &(_, before))| {
+            let per_minute = count.saturating_sub(before) as f64 * 60.0 / uptime.max(1.0);
+            format!("\"{}\":{{\"count\":{},\"per_minute\":{:.1}}}", level, count, per_minute)
+        })
+        .collect()
+}
+
+/// Adds the dashboard routes to `server`: the page on `/` and the JSON on `/metrics.json`.
+pub fn routes(server: DevServer) -> DevServer {
+    start();
+    let page = PAGE.replace("{refresh}", &REFRESH_INTERVAL.as_secs().to_string());
+    server
+        .get("/", move |_| HttpResponse::html(HttpStatus::Ok, &page))
+        .get("/metrics.json", |_| HttpResponse::json(HttpStatus::Ok, &snapshot()).with_header("Cache-Control", "no-store"))
+}
+
+/// Serves the dashboard on the current thread, until the process ends.
+///
+/// # Returns
+///
+/// An `io::Error` if the address can't be bound.
+pub fn serve<A: ToSocketAddrs>(addr: A) -> io::Result<()> {routes(DevServer::new()).serve(addr)}
+
+/// Serves the dashboard on a background thread (see [DevServer::bind]).
+///
+/// # Returns
+///
+/// An `io::Result` containing the handle of the running server.
+pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<ServerHandle> {routes(DevServer::new()).bind(addr)}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Client, HttpMethod, HttpRequest};
+
+    #[test]
+    fn test_snapshot() {
+        counter("test_jobs").add(3);
+        health_check("test_disk", || Err("almost \"full\"".to_string()));
+        let json = snapshot();
+        assert!(json.starts_with("{\"uptime_secs\":"));
+        assert!(json.contains("\"test_jobs\":3"));
+        assert!(json.contains("\"logs\":{\"Error\":{\"count\":"));
+        assert!(json.contains("\"health\":{\"ok\":false,") && json.contains("\"test_disk\":{\"ok\":false,\"message\":\"almost \\\"full\\\"\"}"));
+
+        let server = routes(DevServer::new());
+        let page = server.handle(&HttpRequest::new(HttpMethod::Get, "/"));
+        assert_eq!(page.header("Content-Type"), Some("text/html; charset=utf-8"));
+        assert!(String::from_utf8_lossy(&page.body).contains("setInterval(refresh, 2 * 1000)"));
+    }
+
+    #[test]
+    fn test_log_rates_since_start() {
+        // 6000 errors logged before the dashboard started, then 30 in its first minute
+        let baseline = [(Level::Error, 6000), (Level::Warn, 0)];
+        let counts = [(Level::Error, 6030), (Level::Warn, 12)];
+        assert_eq!(log_rates(&counts, &baseline, 60.0), [
+            "\"Error\":{\"count\":6030,\"per_minute\":30.0}",
+            "\"Warn\":{\"count\":12,\"per_minute\":12.0}",
+        ]);
+    }
+
+    #[test]
+    fn test_bind() {
+        counter("test_served").inc();
+        let running = bind("127.0.0.1:0").unwrap();
+        let response = Client::new().get(&format!("{}/metrics.json", running.url())).unwrap();
+        running.stop();
+        assert_eq!(response.status, HttpStatus::Ok);
+        assert!(String::from_utf8_lossy(&response.body).contains("\"test_served\":1"));
+    }
+}
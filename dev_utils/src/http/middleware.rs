@@ -0,0 +1,113 @@
+//! Middlewares: layers around the routes of a [DevServer](super::DevServer).
+use std::time::Instant;
+use crate::dlog;
+use crate::format::human_bytes;
+use super::{HttpRequest, HttpResponse, StatusClass};
+
+/// A layer around the routes of a [DevServer](super::DevServer), added with
+/// [DevServer::middleware](super::DevServer::middleware).
+///
+/// A middleware gets each request with `next`, which passes it on to the next middleware
+/// (and finally to the routes). It can change the request, answer without calling `next`,
+/// or change the response. Closures with the same signature as [Middleware::handle] are middlewares.
+///
+/// WebSocket upgrades don't go through middlewares.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::{DevServer, HttpMethod, HttpRequest, HttpResponse, HttpStatus};
+///
+/// let server = DevServer::new()
+///     .get("/", |_| HttpResponse::text(HttpStatus::Ok, "hi"))
+///     .middleware(|req: &HttpRequest, next: &dyn Fn(&HttpRequest) -> HttpResponse| {
+///         next(req).with_header("X-Served-By", "dev_utils")
+///     });
+/// let response = server.handle(&HttpRequest::new(HttpMethod::Get, "/"));
+/// assert_eq!(response.header("X-Served-By"), Some("dev_utils"));
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Handles a request, calling `next` to get the response of the inner layers.
+    fn handle(&self, request: &HttpRequest, next: &dyn Fn(&HttpRequest) -> HttpResponse) -> HttpResponse;
+}
+
+impl<F> Middleware for F
+where F: Fn(&HttpRequest, &dyn Fn(&HttpRequest) -> HttpResponse) -> HttpResponse + Send + Sync {
+    fn handle(&self, request: &HttpRequest, next: &dyn Fn(&HttpRequest) -> HttpResponse) -> HttpResponse {
+        self(request, next)
+    }
+}
+
+/// Logs each request through [dlog](crate::dlog): method, path, status (colored by class),
+/// duration and body size. Server errors are logged as errors, client errors as warnings.
+///
+/// Added with [DevServer::use_logger](super::DevServer::use_logger).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Logger;
+
+impl Middleware for Logger {
+    fn handle(&self, request: &HttpRequest, next: &dyn Fn(&HttpRequest) -> HttpResponse) -> HttpResponse {
+        let start = Instant::now();
+        let response = next(request);
+        let size = match response.is_stream() {
+            true => "stream".to_string(),
+            false => human_bytes(response.body.len() as u64),
+        };
+        let target = match request.query.is_empty() {
+            true => request.path.clone(),
+            false => format!("{}?{}", request.path, request.query),
+        };
+        let level = match response.status.class() {
+            StatusClass::ServerError => dlog::Level::Error,
+            StatusClass::ClientError => dlog::Level::Warn,
+            _ => dlog::Level::Info,
+        };
+        dlog::emit(level, format_args!("{} {} {} {:.1?} {}", request.method, target, response.status, start.elapsed(), size));
+        response
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dlog::{test_capture, Level};
+    use crate::format::strip_ansi_codes;
+    use crate::http::{DevServer, HttpMethod, HttpStatus};
+
+    #[test]
+    fn test_logger() {
+        let server = DevServer::new()
+            .get("/items", |_| HttpResponse::text(HttpStatus::Ok, "[]"))
+            .use_logger();
+        let logs = test_capture();
+        server.handle(&HttpRequest::new(HttpMethod::Get, "/items?page=2"));
+        server.handle(&HttpRequest::new(HttpMethod::Get, "/nope"));
+        let messages: Vec<(Level, String)> = logs.records().into_iter().map(|r| (r.level, strip_ansi_codes(&r.message))).collect();
+        assert_eq!(messages[0].0, Level::Info);
+        assert!(messages[0].1.starts_with("GET /items?page=2 200 OK ") && messages[0].1.ends_with(" 2 B"));
+        assert_eq!(messages[1].0, Level::Warn);
+        assert!(messages[1].1.starts_with("GET /nope 404 Not Found "));
+    }
+
+    #[test]
+    fn test_order() {
+        // the first middleware added is the outermost one
+        let tag = |name: &'static str| move |req: &HttpRequest, next: &dyn Fn(&HttpRequest) -> HttpResponse| {
+            let response = next(req);
+            let body = format!("{}({})", name, String::from_utf8_lossy(&response.body));
+            response.with_body(body)
+        };
+        let server = DevServer::new()
+            .get("/", |_| HttpResponse::text(HttpStatus::Ok, "route"))
+            .middleware(tag("outer"))
+            .middleware(tag("inner"))
+            .middleware(|req: &HttpRequest, next: &dyn Fn(&HttpRequest) -> HttpResponse| match req.header("Token") {
+                Some(_) => next(req),
+                None => HttpResponse::new(HttpStatus::Unauthorized),
+            });
+        let request = HttpRequest::new(HttpMethod::Get, "/");
+        assert_eq!(server.handle(&request.clone().with_header("Token", "x")).body, b"outer(inner(route))");
+        assert_eq!(server.handle(&request).body, b"outer(inner())");
+    }
+}
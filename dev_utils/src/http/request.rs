@@ -0,0 +1,181 @@
+//! Parsing of HTTP/1.1 requests.
+use std::io::{BufRead, Read};
+use super::{HttpError, HttpMethod};
+
+// Limits that keep a misbehaving client from exhausting the memory of the server
+const MAX_HEAD_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+pub(super) const MAX_BODY: usize = 64 * 1024 * 1024;
+
+/// An HTTP request.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::{HttpMethod, HttpRequest};
+///
+/// let raw = "POST /items?sort=name HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello";
+/// let request = HttpRequest::read_from(&mut raw.as_bytes()).unwrap().unwrap();
+/// assert_eq!((&request.method, request.path.as_str(), request.query.as_str()), (&HttpMethod::Post, "/items", "sort=name"));
+/// assert_eq!(request.header("host"), Some("localhost"));
+/// assert_eq!(request.body, b"hello");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    /// The path of the target, without the query string.
+    pub path: String,
+    /// The query string, without the `?` (empty if there is none).
+    pub query: String,
+    /// The protocol version, like `HTTP/1.1`.
+    pub version: String,
+    /// The headers, in the order they were sent.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Creates an HTTP/1.1 request without headers or body, to call handlers directly.
+    ///
+    /// The target is split into its path and query string.
+    pub fn new(method: HttpMethod, target: &str) -> Self {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        HttpRequest {
+            method,
+            path: path.to_string(),
+            query: query.to_string(),
+            version: "HTTP/1.1".to_string(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Replaces the body.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Returns the value of the first header with the given name (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the body as text, or `None` if it is not valid UTF-8.
+    pub fn body_text(&self) -> Option<&str> {std::str::from_utf8(&self.body).ok()}
+
+    /// Returns `true` if the client asked to keep the connection open after the response
+    /// (the default in HTTP/1.1, opt-in in HTTP/1.0).
+    pub fn keep_alive(&self) -> bool {
+        let connection = self.header("Connection").unwrap_or("").to_ascii_lowercase();
+        match self.version.as_str() {
+            "HTTP/1.0" => connection.contains("keep-alive"),
+            _ => !connection.contains("close"),
+        }
+    }
+
+    /// Reads a request (head and body) from a stream.
+    ///
+    /// The body is read according to `Content-Length`; chunked request bodies are not supported.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the request, `None` if the stream ended before a request started,
+    /// or an [HttpError].
+    pub fn read_from<R: BufRead>(reader: &mut R) -> Result<Option<Self>, HttpError> {
+        let invalid = |message: &str| HttpError::InvalidRequest(message.to_string());
+        // empty lines before the request line are allowed (RFC 9112, section 2.2)
+        let request_line = loop {
+            match read_line(reader)? {
+                None => return Ok(None),
+                Some(line) if line.is_empty() => continue,
+                Some(line) => break line,
+            }
+        };
+        let mut parts = request_line.split(' ');
+        let (Some(method), Some(target), Some(version), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid("malformed request line"));
+        };
+        let method = method.parse().map_err(|_| HttpError::InvalidRequest(format!("invalid method {}", method)))?;
+        if !version.starts_with("HTTP/1.") {return Err(invalid("unsupported HTTP version"));}
+        let mut request = HttpRequest::new(method, target);
+        request.version = version.to_string();
+
+        request.headers = read_headers(reader)?;
+
+        if request.header("Transfer-Encoding").is_some() {return Err(invalid("chunked request bodies are not supported"));}
+        if let Some(length) = request.header("Content-Length") {
+            let length: usize = length.parse().map_err(|_| invalid("invalid Content-Length"))?;
+            if length > MAX_BODY {return Err(invalid("body too large"));}
+            request.body = vec![0; length];
+            reader.read_exact(&mut request.body)?;
+        }
+        Ok(Some(request))
+    }
+}
+
+// Reads header lines up to the empty line that ends the head of a message
+pub(super) fn read_headers<R: BufRead>(reader: &mut R) -> Result<Vec<(String, String)>, HttpError> {
+    let invalid = |message: &str| HttpError::InvalidRequest(message.to_string());
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?.ok_or_else(|| invalid("unexpected end of headers"))?;
+        if line.is_empty() {return Ok(headers);}
+        let (name, value) = line.split_once(':').ok_or_else(|| invalid("malformed header"))?;
+        if headers.len() == MAX_HEADERS {return Err(invalid("too many headers"));}
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+}
+
+// Reads a line ended by CRLF (or LF) without the line ending, or `None` at the end of the stream
+pub(super) fn read_line<R: BufRead>(reader: &mut R) -> Result<Option<String>, HttpError> {
+    let mut line = Vec::new();
+    let n = reader.take(MAX_HEAD_LINE as u64 + 1).read_until(b'\n', &mut line)?;
+    if n == 0 {return Ok(None);}
+    if !line.ends_with(b"\n") {
+        return Err(HttpError::InvalidRequest(match n > MAX_HEAD_LINE {
+            true => "line too long".to_string(),
+            false => "unexpected end of stream".to_string(),
+        }));
+    }
+    let line = String::from_utf8(line).map_err(|_| HttpError::InvalidRequest("head is not valid UTF-8".to_string()))?;
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Result<Option<HttpRequest>, HttpError> {HttpRequest::read_from(&mut raw.as_bytes())}
+
+    #[test]
+    fn test_read_request() {
+        let mut stream = "\r\nGET /a HTTP/1.0\r\nConnection: keep-alive\r\n\r\nGET /b HTTP/1.1\nX-Id:  7 \n\n".as_bytes();
+        let first = HttpRequest::read_from(&mut stream).unwrap().unwrap();
+        assert_eq!((first.path.as_str(), first.keep_alive()), ("/a", true));
+        let second = HttpRequest::read_from(&mut stream).unwrap().unwrap();
+        assert_eq!((second.header("x-id"), second.keep_alive(), second.body.len()), (Some("7"), true, 0));
+        assert!(HttpRequest::read_from(&mut stream).unwrap().is_none());
+        assert!(!HttpRequest::new(HttpMethod::Get, "/").with_header("connection", "Close").keep_alive());
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let error = |raw: &str| parse(raw).unwrap_err().to_string();
+        assert_eq!(error("GET /\r\n\r\n"), "Invalid request: malformed request line");
+        assert_eq!(error("GE\"T / HTTP/1.1\r\n\r\n"), "Invalid request: invalid method GE\"T");
+        assert_eq!(parse("BREW / HTTP/1.1\r\n\r\n").unwrap().unwrap().method, HttpMethod::Extension("BREW".to_string()));
+        assert_eq!(error("GET / HTTP/2\r\n\r\n"), "Invalid request: unsupported HTTP version");
+        assert_eq!(error("GET / HTTP/1.1\r\nHost localhost\r\n\r\n"), "Invalid request: malformed header");
+        assert_eq!(error("GET / HTTP/1.1\r\nHost: x"), "Invalid request: unexpected end of stream");
+        assert_eq!(error(&format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_HEAD_LINE))), "Invalid request: line too long");
+        assert!(matches!(parse("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort"), Err(HttpError::Io(_))));
+    }
+}
@@ -0,0 +1,176 @@
+//! HTTP responses and their serialization.
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Arc;
+use super::HttpStatus;
+use super::sse::SseWriter;
+
+type StreamFn = dyn Fn(&mut dyn Write) -> io::Result<()> + Send + Sync;
+
+// A body written by a callback, for responses that don't fit in memory or never end
+#[derive(Clone)]
+struct BodyStream(Arc<StreamFn>);
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {f.write_str("BodyStream")}
+}
+
+impl PartialEq for BodyStream {
+    fn eq(&self, other: &Self) -> bool {Arc::ptr_eq(&self.0, &other.0)}
+}
+
+impl Eq for BodyStream {}
+
+/// An HTTP response.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::{HttpResponse, HttpStatus};
+///
+/// let response = HttpResponse::json(HttpStatus::Created, r#"{"id": 1}"#).with_header("Location", "/items/1");
+/// let mut raw = Vec::new();
+/// response.write_to(&mut raw).unwrap();
+/// assert_eq!(String::from_utf8(raw).unwrap(), "HTTP/1.1 201 Created\r\n\
+///     Content-Type: application/json\r\nLocation: /items/1\r\nContent-Length: 9\r\n\r\n{\"id\": 1}");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: HttpStatus,
+    /// The headers, in the order they are sent. `Content-Length` is added when writing.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    stream: Option<BodyStream>,
+}
+
+impl HttpResponse {
+    /// Creates a response without headers or body.
+    pub fn new(status: HttpStatus) -> Self {HttpResponse { status, headers: Vec::new(), body: Vec::new(), stream: None }}
+
+    /// Creates a plain text response.
+    pub fn text(status: HttpStatus, body: &str) -> Self {
+        Self::new(status).with_header("Content-Type", "text/plain; charset=utf-8").with_body(body)
+    }
+
+    /// Creates an HTML response.
+    pub fn html(status: HttpStatus, body: &str) -> Self {
+        Self::new(status).with_header("Content-Type", "text/html; charset=utf-8").with_body(body)
+    }
+
+    /// Creates a JSON response from an already serialized body.
+    pub fn json(status: HttpStatus, body: &str) -> Self {
+        Self::new(status).with_header("Content-Type", "application/json").with_body(body)
+    }
+
+    /// Creates a streaming response, whose body is written by `stream` once the head is sent.
+    ///
+    /// The response has no `Content-Length`: the body ends when the connection is closed,
+    /// after `stream` returns.
+    pub fn stream<F>(status: HttpStatus, stream: F) -> Self
+    where F: Fn(&mut dyn Write) -> io::Result<()> + Send + Sync + 'static {
+        HttpResponse { stream: Some(BodyStream(Arc::new(stream))), ..Self::new(status) }
+    }
+
+    /// Creates a `text/event-stream` response that sends Server-Sent Events with `events`,
+    /// until it returns or the client disconnects (which makes the writes fail).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dev_utils::http::{DevServer, HttpResponse};
+    /// use dev_utils::http::sse::SseEvent;
+    ///
+    /// let server = DevServer::new().get("/progress", |_| HttpResponse::stream_sse(|events| {
+    ///     for percent in (0..=100).step_by(25) {
+    ///         events.send(&SseEvent::new(&percent.to_string()).event("progress"))?;
+    ///         std::thread::sleep(Duration::from_millis(100));
+    ///     }
+    ///     Ok(())
+    /// }));
+    /// ```
+    pub fn stream_sse<F>(events: F) -> Self
+    where F: Fn(&mut SseWriter<&mut dyn Write>) -> io::Result<()> + Send + Sync + 'static {
+        Self::stream(HttpStatus::Ok, move |writer| events(&mut SseWriter::new(writer)))
+            .with_header("Content-Type", "text/event-stream")
+            .with_header("Cache-Control", "no-cache")
+            .with_header("Connection", "keep-alive")
+    }
+
+    /// Returns `true` if the body is streamed (see [HttpResponse::stream]).
+    pub fn is_stream(&self) -> bool {self.stream.is_some()}
+
+    /// Adds a header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Replaces the body.
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Returns the value of the first header with the given name (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    /// Writes the response as HTTP/1.1, adding a `Content-Length` header if there is none
+    /// (except for `101`, `204` and `304` responses, which have no body, and streaming responses).
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut head = format!("HTTP/1.1 {} {}\r\n", self.status.code(), self.status.reason());
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        let bodyless = matches!(self.status, HttpStatus::SwitchingProtocols | HttpStatus::NoContent | HttpStatus::NotModified);
+        if !bodyless && self.stream.is_none() && self.header("Content-Length").is_none() {
+            head.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+        head.push_str("\r\n");
+        writer.write_all(head.as_bytes())?;
+        match &self.stream {
+            _ if bodyless => {},
+            Some(stream) => {
+                writer.flush()?;
+                (stream.0)(writer)?;
+            },
+            None => writer.write_all(&self.body)?,
+        }
+        writer.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_response() {
+        let mut raw = Vec::new();
+        HttpResponse::new(HttpStatus::NoContent).with_body("ignored").write_to(&mut raw).unwrap();
+        assert_eq!(raw, b"HTTP/1.1 204 No Content\r\n\r\n");
+
+        let response = HttpResponse::text(HttpStatus::NotFound, "nope");
+        assert_eq!(response.header("content-type"), Some("text/plain; charset=utf-8"));
+        raw.clear();
+        response.write_to(&mut raw).unwrap();
+        assert!(raw.ends_with(b"Content-Length: 4\r\n\r\nnope"));
+    }
+
+    #[test]
+    fn test_stream_sse() {
+        let response = HttpResponse::stream_sse(|events| {
+            events.send_data("one")?;
+            events.keep_alive()
+        });
+        assert!(response.is_stream() && response.clone() == response);
+        let mut raw = Vec::new();
+        response.write_to(&mut raw).unwrap();
+        let raw = String::from_utf8(raw).unwrap();
+        assert!(!raw.contains("Content-Length"));
+        assert!(raw.ends_with("Content-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\ndata: one\n\n: keep-alive\n\n"));
+    }
+}
@@ -0,0 +1,447 @@
+//! A development HTTP server that routes requests to closures.
+use std::cell::Cell;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::dlog;
+use crate::signals::ShutdownToken;
+use super::middleware::{Logger, Middleware};
+use super::ws::{self, WebSocket};
+use super::{HttpError, HttpMethod, HttpRequest, HttpResponse, HttpStatus};
+#[cfg(feature = "tls")]
+use super::tls::{Tls, TlsProvider};
+
+// Idle keep-alive connections are closed after this long
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Handler = Arc<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>;
+type SocketHandler = Arc<dyn Fn(WebSocket<TcpStream>) + Send + Sync>;
+
+struct Route {
+    method: HttpMethod,
+    pattern: String,
+    handler: Handler,
+}
+
+/// An HTTP/1.1 server for mocking backends during development.
+///
+/// Routes match a path exactly, or every path under a prefix when the pattern ends with
+/// `/*` (`/static/*` matches `/static/app.js`). The first matching route handles the request;
+/// unmatched paths get a `404`, and paths matched for other methods a `405`.
+///
+/// Each connection is handled by its own thread, with keep-alive. [Middleware]s wrap the
+/// routes, the first one added being the outermost.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::http::{DevServer, HttpMethod, HttpRequest, HttpResponse, HttpStatus};
+///
+/// let server = DevServer::new()
+///     .get("/users/*", |req| HttpResponse::text(HttpStatus::Ok, &req.path["/users/".len()..]));
+///
+/// // handlers can be called without a socket
+/// let response = server.handle(&HttpRequest::new(HttpMethod::Get, "/users/ada"));
+/// assert_eq!(response.body, b"ada");
+/// assert_eq!(server.handle(&HttpRequest::new(HttpMethod::Post, "/users/ada")).status, HttpStatus::MethodNotAllowed);
+///
+/// // or served on a free port
+/// let running = server.bind("127.0.0.1:0").unwrap();
+/// println!("listening on {}", running.url());
+/// running.stop();
+/// ```
+#[derive(Default)]
+pub struct DevServer {
+    routes: Vec<Route>,
+    sockets: Vec<(String, SocketHandler)>,
+    middlewares: Vec<Box<dyn Middleware>>,
+    #[cfg(feature = "tls")]
+    tls: Option<Tls>,
+}
+
+impl DevServer {
+    /// Creates a server without routes.
+    pub fn new() -> Self {Self::default()}
+
+    /// Adds a route for the method and path pattern.
+    pub fn route<F>(mut self, method: HttpMethod, pattern: &str, handler: F) -> Self
+    where F: Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static {
+        self.routes.push(Route { method, pattern: pattern.to_string(), handler: Arc::new(handler) });
+        self
+    }
+
+    /// Adds a `GET` route.
+    pub fn get<F>(self, pattern: &str, handler: F) -> Self
+    where F: Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static {
+        self.route(HttpMethod::Get, pattern, handler)
+    }
+
+    /// Adds a `POST` route.
+    pub fn post<F>(self, pattern: &str, handler: F) -> Self
+    where F: Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static {
+        self.route(HttpMethod::Post, pattern, handler)
+    }
+
+    /// Accepts WebSocket connections on a path pattern: the server answers the handshake and
+    /// calls `handler` with the open connection, on the thread of the connection.
+    ///
+    /// The handshake goes through the [Middleware]s like any request: the connection is only
+    /// upgraded if they pass it on (so [RequireAuth](super::auth::RequireAuth) guards it too).
+    pub fn websocket<F>(mut self, pattern: &str, handler: F) -> Self
+    where F: Fn(WebSocket<TcpStream>) + Send + Sync + 'static {
+        self.sockets.push((pattern.to_string(), Arc::new(handler)));
+        self
+    }
+
+    /// Adds a middleware around the routes (and the middlewares added before it).
+    pub fn middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    /// Serves HTTPS: every connection goes through a TLS session of `provider` (requires the
+    /// `tls` feature). WebSocket routes answer `501 Not Implemented` over TLS.
+    #[cfg(feature = "tls")]
+    pub fn tls<P: TlsProvider + 'static>(mut self, provider: P) -> Self {
+        self.tls = Some(Tls(std::sync::Arc::new(provider)));
+        self
+    }
+
+    /// Logs every request with the [Logger] middleware.
+    pub fn use_logger(self) -> Self {self.middleware(Logger)}
+
+    /// Returns the response of the middlewares and routes to a request.
+    pub fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        self.handle_from(0, request, &|request| self.route_request(request))
+    }
+
+    // Runs the middlewares from `index` on, then `inner` (the routes, or a WebSocket handshake)
+    fn handle_from(&self, index: usize, request: &HttpRequest, inner: &dyn Fn(&HttpRequest) -> HttpResponse) -> HttpResponse {
+        match self.middlewares.get(index) {
+            Some(middleware) => middleware.handle(request, &|request| self.handle_from(index + 1, request, inner)),
+            None => inner(request),
+        }
+    }
+
+    fn route_request(&self, request: &HttpRequest) -> HttpResponse {
+        let mut matched = self.routes.iter().filter(|route| matches(&route.pattern, &request.path)).peekable();
+        if matched.peek().is_none() {
+            return HttpResponse::text(HttpStatus::NotFound, &format!("no route for {}", request.path));
+        }
+        let mut allowed = Vec::new();
+        for route in matched {
+            if route.method == request.method {return (route.handler)(request);}
+            allowed.push(route.method.as_str());
+        }
+        HttpResponse::text(HttpStatus::MethodNotAllowed, &format!("{} is not allowed", request.method))
+            .with_header("Allow", &allowed.join(", "))
+    }
+
+    /// Starts serving on a background thread.
+    ///
+    /// Bind to port `0` to get a free port, then read it from [ServerHandle::addr].
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the handle of the running server.
+    pub fn bind<A: ToSocketAddrs>(self, addr: A) -> io::Result<ServerHandle> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let scheme = self.scheme();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || self.accept_loop(listener, &stop))
+        };
+        Ok(ServerHandle { addr, scheme, stop, thread })
+    }
+
+    /// Serves on the current thread, until the process ends.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Error` if the address can't be bound.
+    pub fn serve<A: ToSocketAddrs>(self, addr: A) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        dlog::info!("serving on {}://{}", self.scheme(), listener.local_addr()?);
+        self.accept_loop(listener, &AtomicBool::new(false));
+        Ok(())
+    }
+
+    /// Serves on the current thread until `token` is triggered, e.g. by `Ctrl+C`
+    /// (see [signals::ctrl_c_token](crate::signals::ctrl_c_token)).
+    /// Like [ServerHandle::stop], connections already open are served until the client closes them.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Error` if the address can't be bound.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dev_utils::http::{DevServer, HttpResponse, HttpStatus};
+    /// use dev_utils::signals;
+    ///
+    /// let shutdown = signals::ctrl_c_token().unwrap();
+    /// DevServer::new()
+    ///     .get("/", |_| HttpResponse::text(HttpStatus::Ok, "hello"))
+    ///     .serve_until("127.0.0.1:8080", &shutdown)
+    ///     .unwrap();
+    /// println!("server stopped");
+    /// ```
+    pub fn serve_until<A: ToSocketAddrs>(self, addr: A, token: &ShutdownToken) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        dlog::info!("serving on {}://{}", self.scheme(), addr);
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let (stop, token) = (Arc::clone(&stop), token.clone());
+            thread::spawn(move || {
+                token.wait();
+                stop.store(true, Ordering::SeqCst);
+                // wake the blocking `accept` up
+                let _ = TcpStream::connect(addr);
+            });
+        }
+        self.accept_loop(listener, &stop);
+        dlog::info!("stopped serving on {}", addr);
+        Ok(())
+    }
+
+    fn scheme(&self) -> &'static str {
+        #[cfg(feature = "tls")]
+        if self.tls.is_some() {return "https";}
+        "http"
+    }
+
+    fn accept_loop(self, listener: TcpListener, stop: &AtomicBool) {
+        let server = Arc::new(self);
+        for stream in listener.incoming() {
+            if stop.load(Ordering::SeqCst) {break;}
+            let Ok(stream) = stream else {continue;};
+            let server = Arc::clone(&server);
+            thread::spawn(move || {
+                if let Err(err) = server.handle_connection(stream) {
+                    dlog::debug!("connection closed: {}", err);
+                }
+            });
+        }
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> Result<(), HttpError> {
+        stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+        // a handle on the socket to change its timeout, whatever wraps it
+        let socket = stream.try_clone()?;
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.tls {
+            let mut reader = BufReader::new(tls.0.accept(stream)?);
+            return self.exchange_loop(&mut reader, &socket, false).map(drop);
+        }
+        let mut reader = BufReader::new(stream);
+        if let Some(handler) = self.exchange_loop(&mut reader, &socket, true)? {
+            handler(WebSocket::from_reader(reader));
+        }
+        Ok(())
+    }
+
+    // Answers the requests of a connection until it closes, or until a WebSocket handshake
+    // succeeds (returning the handler that takes the connection over)
+    fn exchange_loop<S: Read + Write>(&self, reader: &mut BufReader<S>, socket: &TcpStream, websockets: bool) -> Result<Option<SocketHandler>, HttpError> {
+        loop {
+            let request = match HttpRequest::read_from(reader) {
+                Ok(Some(request)) => request,
+                Ok(None) => return Ok(None),
+                Err(HttpError::InvalidRequest(message)) => {
+                    HttpResponse::text(HttpStatus::BadRequest, &message).with_header("Connection", "close").write_to(reader.get_mut())?;
+                    return Ok(None);
+                },
+                Err(err) => return Err(err),
+            };
+
+            if let Some((_, handler)) = self.sockets.iter().find(|(pattern, _)| matches(pattern, &request.path)) {
+                // upgraded only if the middlewares let the handshake through, and it succeeded
+                let upgraded = Cell::new(false);
+                let response = self.handle_from(0, &request, &|request| {
+                    let response = match websockets {
+                        true => ws::handshake(request),
+                        false => HttpResponse::text(HttpStatus::NotImplemented, "WebSockets are not supported over TLS"),
+                    };
+                    upgraded.set(response.status == HttpStatus::SwitchingProtocols);
+                    response
+                });
+                response.write_to(reader.get_mut())?;
+                if !upgraded.get() || response.status != HttpStatus::SwitchingProtocols {return Ok(None);}
+                socket.set_read_timeout(None)?;
+                return Ok(Some(Arc::clone(handler)));
+            }
+
+            let mut response = self.handle(&request);
+            // a streamed body ends when the connection closes
+            let keep_alive = request.keep_alive() && !response.is_stream();
+            if !keep_alive && response.header("Connection").is_none() {response = response.with_header("Connection", "close");}
+            if response.is_stream() {socket.set_read_timeout(None)?;}
+            response.write_to(reader.get_mut())?;
+            if !keep_alive {return Ok(None);}
+        }
+    }
+}
+
+// Whether a route pattern matches a path: exactly, or under a prefix ending with `/*`
+fn matches(pattern: &str, path: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) if prefix.ends_with('/') => path.starts_with(prefix) || path == &prefix[..prefix.len() - 1],
+        _ => pattern == path,
+    }
+}
+
+/// A [DevServer] running on a background thread.
+#[derive(Debug)]
+pub struct ServerHandle {
+    addr: SocketAddr,
+    scheme: &'static str,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl ServerHandle {
+    /// Returns the address the server listens on.
+    pub fn addr(&self) -> SocketAddr {self.addr}
+
+    /// Returns the base URL of the server, like `http://127.0.0.1:8080`.
+    pub fn url(&self) -> String {format!("{}://{}", self.scheme, self.addr)}
+
+    /// Stops accepting connections and waits for the server thread to end.
+    /// Connections already open are served until the client closes them.
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // wake the blocking `accept` up
+        let _ = TcpStream::connect(self.addr);
+        let _ = self.thread.join();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, Read, Write};
+    use crate::http::sse::SseEvent;
+    use crate::http::ws::{Frame, Message, Opcode};
+
+    fn exchange(stream: &mut TcpStream, request: &str) -> String {
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut head = String::new();
+        while !head.ends_with("\r\n\r\n") {reader.read_line(&mut head).unwrap();}
+        let length = head.lines().find_map(|l| l.strip_prefix("Content-Length: ")).map_or(0, |l| l.parse().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        head + &String::from_utf8(body).unwrap()
+    }
+
+    #[test]
+    fn test_routing() {
+        assert!(matches("/static/*", "/static/css/app.css") && matches("/static/*", "/static"));
+        assert!(!matches("/static/*", "/statics") && !matches("/a", "/a/b"));
+
+        let server = DevServer::new().get("/a", |_| HttpResponse::new(HttpStatus::NoContent));
+        let response = server.handle(&HttpRequest::new(HttpMethod::Delete, "/a"));
+        assert_eq!((response.status, response.header("Allow")), (HttpStatus::MethodNotAllowed, Some("GET")));
+        assert_eq!(server.handle(&HttpRequest::new(HttpMethod::Get, "/b")).status, HttpStatus::NotFound);
+    }
+
+    #[test]
+    fn test_serve() {
+        let running = DevServer::new()
+            .post("/echo", |req| HttpResponse::new(HttpStatus::Ok).with_body(req.body.clone()))
+            .bind("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(running.addr()).unwrap();
+        // two requests on the same connection
+        let first = exchange(&mut stream, "POST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\nping");
+        assert!(first.starts_with("HTTP/1.1 200 OK\r\n") && first.ends_with("\r\n\r\nping"));
+        let second = exchange(&mut stream, "GET /nope HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(second.starts_with("HTTP/1.1 404 Not Found\r\n") && second.contains("Connection: close"));
+        running.stop();
+    }
+
+    #[test]
+    fn test_serve_until() {
+        let token = ShutdownToken::new();
+        let stopper = {
+            let token = token.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                token.shutdown();
+            })
+        };
+        DevServer::new().serve_until("127.0.0.1:0", &token).unwrap();
+        stopper.join().unwrap();
+    }
+
+    #[test]
+    fn test_server_sent_events() {
+        let running = DevServer::new()
+            .get("/events", |_| HttpResponse::stream_sse(|events| {
+                (1..=3).try_for_each(|i| events.send(&SseEvent::new(&i.to_string()).id(&i.to_string())))
+            }))
+            .bind("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(running.addr()).unwrap();
+        stream.write_all(b"GET /events HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n"));
+        assert!(response.ends_with("\r\n\r\nid: 1\ndata: 1\n\nid: 2\ndata: 2\n\nid: 3\ndata: 3\n\n"));
+        running.stop();
+    }
+
+    #[test]
+    fn test_websocket() {
+        let running = DevServer::new()
+            .websocket("/ws", |mut socket| {
+                while let Ok(Some(Message::Text(text))) = socket.recv() {
+                    socket.send_text(&text.to_uppercase()).unwrap();
+                }
+            })
+            .bind("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(running.addr()).unwrap();
+        let head = exchange(&mut stream, "GET /ws HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n");
+        assert!(head.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+
+        stream.write_all(&Frame::new(Opcode::Text, "hello").encode(Some([1, 2, 3, 4]))).unwrap();
+        assert_eq!(Frame::read_from(&mut stream).unwrap(), Frame::new(Opcode::Text, "HELLO"));
+        stream.write_all(&Frame::new(Opcode::Ping, "").encode(Some([0; 4]))).unwrap();
+        assert_eq!(Frame::read_from(&mut stream).unwrap(), Frame::new(Opcode::Pong, ""));
+        running.stop();
+    }
+
+    #[test]
+    fn test_websocket_middlewares() {
+        let running = DevServer::new()
+            .middleware(crate::http::auth::RequireAuth::bearer("secret"))
+            .websocket("/ws", |mut socket| {
+                if let Ok(Some(Message::Text(text))) = socket.recv() {socket.send_text(&text).unwrap();}
+            })
+            .bind("127.0.0.1:0").unwrap();
+        let handshake = |auth: &str| {
+            let mut stream = TcpStream::connect(running.addr()).unwrap();
+            let head = exchange(&mut stream, &format!("GET /ws HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n{}\r\n", auth));
+            (head, stream)
+        };
+
+        let (head, _) = handshake("");
+        assert!(head.starts_with("HTTP/1.1 401 Unauthorized\r\n"), "{}", head);
+        let (head, _) = handshake("Authorization: Bearer wrong\r\n");
+        assert!(head.starts_with("HTTP/1.1 401 Unauthorized\r\n"), "{}", head);
+
+        let (head, mut stream) = handshake("Authorization: Bearer secret\r\n");
+        assert!(head.starts_with("HTTP/1.1 101 Switching Protocols\r\n"), "{}", head);
+        stream.write_all(&Frame::new(Opcode::Text, "hi").encode(Some([1, 2, 3, 4]))).unwrap();
+        assert_eq!(Frame::read_from(&mut stream).unwrap(), Frame::new(Opcode::Text, "hi"));
+        running.stop();
+    }
+}
@@ -0,0 +1,113 @@
+//! Server-Sent Events: the `text/event-stream` format and a writer that flushes each event.
+//!
+//! A route streams events by returning [HttpResponse::stream_sse](super::HttpResponse::stream_sse);
+//! the [DevServer](super::DevServer) sends the head and hands the connection to the callback.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::http::sse::{SseEvent, SseWriter};
+//!
+//! let mut out = Vec::new();
+//! let mut writer = SseWriter::new(&mut out);
+//! writer.send(&SseEvent::new("50%").event("progress").id("1")).unwrap();
+//! writer.keep_alive().unwrap();
+//! assert_eq!(String::from_utf8(out).unwrap(), "event: progress\nid: 1\ndata: 50%\n\n: keep-alive\n\n");
+//! ```
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// An event of an event stream.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SseEvent {
+    /// The type of the event (`message` for the client if `None`).
+    pub event: Option<String>,
+    /// The data of the event; each line is sent as its own `data:` field.
+    pub data: String,
+    /// The id the client sends back in `Last-Event-ID` when it reconnects.
+    pub id: Option<String>,
+    /// How long the client waits before reconnecting.
+    pub retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Creates an event with the given data.
+    pub fn new(data: &str) -> Self {SseEvent { data: data.to_string(), ..Default::default() }}
+
+    /// Sets the type of the event.
+    pub fn event(mut self, event: &str) -> Self {
+        self.event = Some(event.to_string());
+        self
+    }
+
+    /// Sets the id of the event.
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Sets the reconnection delay of the client.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Formats the event as in an event stream, ending with the blank line that dispatches it.
+    ///
+    /// Line breaks in the type and id (which can't hold them) are replaced with spaces.
+    pub fn encode(&self) -> String {
+        let single_line = |s: &str| s.replace(['\r', '\n'], " ");
+        let mut out = String::new();
+        if let Some(event) = &self.event {out.push_str(&format!("event: {}\n", single_line(event)));}
+        if let Some(id) = &self.id {out.push_str(&format!("id: {}\n", single_line(id)));}
+        if let Some(retry) = self.retry {out.push_str(&format!("retry: {}\n", retry.as_millis()));}
+        for line in self.data.split('\n') {
+            out.push_str(&format!("data: {}\n", line.trim_end_matches('\r')));
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Writes events to a stream, flushing after each one so the client sees it immediately.
+#[derive(Debug)]
+pub struct SseWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SseWriter<W> {
+    /// Wraps a stream on which the response head is already sent.
+    pub fn new(writer: W) -> Self {SseWriter { writer }}
+
+    /// Sends an event.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Error` if the client is gone.
+    pub fn send(&mut self, event: &SseEvent) -> io::Result<()> {self.write(&event.encode())}
+
+    /// Sends an event with only data.
+    pub fn send_data(&mut self, data: &str) -> io::Result<()> {self.send(&SseEvent::new(data))}
+
+    /// Sends a comment, which clients ignore; sending one every few seconds keeps proxies
+    /// from closing an idle stream, and detects disconnected clients.
+    pub fn keep_alive(&mut self) -> io::Result<()> {self.write(": keep-alive\n\n")}
+
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        self.writer.write_all(text.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(SseEvent::new("").encode(), "data: \n\n");
+        assert_eq!(SseEvent::new("a\r\nb\nc").encode(), "data: a\ndata: b\ndata: c\n\n");
+        let event = SseEvent::new("x").event("two\nlines").retry(Duration::from_secs(3));
+        assert_eq!(event.encode(), "event: two lines\nretry: 3000\ndata: x\n\n");
+    }
+}
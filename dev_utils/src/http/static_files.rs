@@ -0,0 +1,220 @@
+//! Serving the files of a directory, with content types, ETags and range requests.
+//!
+//! # Examples
+//! ```no_run
+//! use dev_utils::http::{static_files, DevServer};
+//!
+//! // http://127.0.0.1:8080/ serves ./public/index.html
+//! DevServer::new().get("/*", static_files::serve("public")).serve("127.0.0.1:8080").unwrap();
+//! ```
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::crypto::hash::{sha256, to_hex};
+use super::{percent_decode, HttpRequest, HttpResponse, HttpStatus};
+
+/// Returns a handler that serves the files under `root`, mapping the path of the request
+/// to a file path (`/css/app.css` to `root/css/app.css`, `/` to `root/index.html`).
+///
+/// Paths that would leave `root` (`..` segments, or symlinks pointing out of it) get a `404`.
+/// Responses carry an `ETag` (a SHA-256 digest of the content, as in `file::hash`) and answer
+/// `If-None-Match` with `304 Not Modified`; a `Range` header with a single range gets
+/// `206 Partial Content`.
+pub fn serve<P: AsRef<Path>>(root: P) -> impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static {
+    serve_under("", root)
+}
+
+/// Like [serve], for a route mounted under `prefix`: `/static/app.js` maps to `root/app.js`
+/// with a prefix of `/static`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::TempDir;
+/// use dev_utils::http::{static_files, DevServer, HttpMethod, HttpRequest, HttpStatus};
+///
+/// let dir = TempDir::new().unwrap();
+/// std::fs::write(dir.join("app.js"), "console.log(1)").unwrap();
+///
+/// let server = DevServer::new().get("/static/*", static_files::serve_under("/static", dir.path()));
+/// let response = server.handle(&HttpRequest::new(HttpMethod::Get, "/static/app.js"));
+/// assert_eq!(response.header("Content-Type"), Some("text/javascript; charset=utf-8"));
+/// assert_eq!(response.body, b"console.log(1)");
+/// let response = server.handle(&HttpRequest::new(HttpMethod::Get, "/static/../secret"));
+/// assert_eq!(response.status, HttpStatus::NotFound);
+/// ```
+pub fn serve_under<P: AsRef<Path>>(prefix: &str, root: P) -> impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static {
+    let prefix = prefix.trim_end_matches('/').to_string();
+    let root = root.as_ref().to_path_buf();
+    move |request| {
+        let not_found = || HttpResponse::text(HttpStatus::NotFound, &format!("{} not found", request.path));
+        // on a segment boundary: `/staticfoo` is not under `/static`
+        let path = request.path.strip_prefix(&prefix).filter(|path| path.is_empty() || path.starts_with('/'));
+        let Some(path) = path.and_then(|path| resolve(&root, path)) else {return not_found();};
+        match fs::read(&path) {
+            Ok(content) => file_response(request, &path, content),
+            Err(_) => not_found(),
+        }
+    }
+}
+
+// The file of `root` that a URL path points to, if it exists and is inside `root`
+fn resolve(root: &Path, url_path: &str) -> Option<PathBuf> {
+    let mut path = root.to_path_buf();
+    for segment in percent_decode(url_path)?.split('/') {
+        match segment {
+            "" | "." => continue,
+            ".." => return None,
+            s if s.contains(['\\', '\0']) || Path::new(s).has_root() => return None,
+            s => path.push(s),
+        }
+    }
+    if path.is_dir() {path.push("index.html");}
+    // symlinks may still lead out of the root
+    let (root, path) = (root.canonicalize().ok()?, path.canonicalize().ok()?);
+    (path.starts_with(&root) && path.is_file()).then_some(path)
+}
+
+fn file_response(request: &HttpRequest, path: &Path, content: Vec<u8>) -> HttpResponse {
+    let etag = format!("\"{}\"", &to_hex(&sha256(&content))[..16]);
+    let response = |status: HttpStatus| HttpResponse::new(status)
+        .with_header("Content-Type", content_type(path))
+        .with_header("ETag", &etag)
+        .with_header("Accept-Ranges", "bytes");
+
+    if request.header("If-None-Match").is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == "*" || tag.trim() == etag)) {
+        return response(HttpStatus::NotModified);
+    }
+    match request.header("Range").map(|range| parse_range(range, content.len() as u64)) {
+        None | Some(Range::Ignored) => response(HttpStatus::Ok).with_body(content),
+        Some(Range::Unsatisfiable) => HttpResponse::new(HttpStatus::RangeNotSatisfiable)
+            .with_header("Content-Range", &format!("bytes */{}", content.len())),
+        Some(Range::Bytes(start, end)) => response(HttpStatus::PartialContent)
+            .with_header("Content-Range", &format!("bytes {}-{}/{}", start, end, content.len()))
+            .with_body(&content[start as usize..=end as usize]),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Range {
+    /// An inclusive range of bytes.
+    Bytes(u64, u64),
+    /// A valid range outside of the content.
+    Unsatisfiable,
+    /// A header that is malformed or asks for several ranges: the whole content is sent.
+    Ignored,
+}
+
+// Parses a `Range` header (`bytes=0-99`, `bytes=100-` or `bytes=-100`) for content of `len` bytes
+fn parse_range(header: &str, len: u64) -> Range {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {return Range::Ignored;};
+    let Some((start, end)) = spec.split_once('-').filter(|_| !spec.contains(',')) else {return Range::Ignored;};
+    let parse = |s: &str| s.trim().parse::<u64>().ok();
+    let range = match (start.trim(), end.trim()) {
+        ("", suffix) => match parse(suffix) {
+            Some(0) => return Range::Unsatisfiable,
+            Some(n) => (len.saturating_sub(n), len.saturating_sub(1)),
+            None => return Range::Ignored,
+        },
+        (start, "") => match parse(start) {
+            Some(start) => (start, len.saturating_sub(1)),
+            None => return Range::Ignored,
+        },
+        (start, end) => match (parse(start), parse(end)) {
+            (Some(start), Some(end)) if start <= end => (start, end.min(len.saturating_sub(1))),
+            _ => return Range::Ignored,
+        },
+    };
+    match range.0 < len {
+        true => Range::Bytes(range.0, range.1),
+        false => Range::Unsatisfiable,
+    }
+}
+
+/// Returns the `Content-Type` of a file from its extension (`application/octet-stream` if unknown).
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" | "log" => "text/plain; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::TempDir;
+    use crate::http::HttpMethod;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-3", 10), Range::Bytes(0, 3));
+        assert_eq!(parse_range("bytes=4-", 10), Range::Bytes(4, 9));
+        assert_eq!(parse_range("bytes=-3", 10), Range::Bytes(7, 9));
+        assert_eq!(parse_range("bytes=5-100", 10), Range::Bytes(5, 9));
+        assert_eq!(parse_range("bytes=-30", 10), Range::Bytes(0, 9));
+        assert_eq!(parse_range("bytes=10-", 10), Range::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 10), Range::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,4-5", 10), Range::Ignored);
+        assert_eq!(parse_range("items=0-1", 10), Range::Ignored);
+        assert_eq!(parse_range("bytes=3-1", 10), Range::Ignored);
+    }
+
+    #[test]
+    fn test_serve() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.join("docs")).unwrap();
+        fs::write(dir.join("docs/index.html"), "<h1>docs</h1>").unwrap();
+        fs::write(dir.join("my file.txt"), "0123456789").unwrap();
+        let handler = serve(dir.path());
+        let get = |path: &str| HttpRequest::new(HttpMethod::Get, path);
+
+        let index = handler(&get("/docs/"));
+        assert_eq!((index.status, index.header("Content-Type")), (HttpStatus::Ok, Some("text/html; charset=utf-8")));
+        assert_eq!(handler(&get("/docs/%2e%2e/my%20file.txt")).status, HttpStatus::NotFound);
+        assert_eq!(handler(&get("/missing")).status, HttpStatus::NotFound);
+
+        let file = handler(&get("/my%20file.txt"));
+        let etag = file.header("ETag").unwrap();
+        assert_eq!(handler(&get("/my%20file.txt").with_header("If-None-Match", etag)).status, HttpStatus::NotModified);
+        let partial = handler(&get("/my%20file.txt").with_header("Range", "bytes=2-4"));
+        assert_eq!((partial.status, partial.body.as_slice()), (HttpStatus::PartialContent, &b"234"[..]));
+        assert_eq!(partial.header("Content-Range"), Some("bytes 2-4/10"));
+        let outside = handler(&get("/my%20file.txt").with_header("Range", "bytes=20-"));
+        assert_eq!((outside.status, outside.header("Content-Range")), (HttpStatus::RangeNotSatisfiable, Some("bytes */10")));
+    }
+
+    #[test]
+    fn test_serve_under() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.join("app.js"), "1").unwrap();
+        fs::write(dir.join("index.html"), "home").unwrap();
+        let handler = serve_under("/static/", dir.path());
+        let get = |path: &str| handler(&HttpRequest::new(HttpMethod::Get, path)).status;
+
+        assert_eq!(get("/static/app.js"), HttpStatus::Ok);
+        assert_eq!(get("/static"), HttpStatus::Ok);
+        assert_eq!(get("/staticapp.js"), HttpStatus::NotFound);
+        assert_eq!(get("/static.old/app.js"), HttpStatus::NotFound);
+    }
+}
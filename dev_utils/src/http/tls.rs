@@ -0,0 +1,115 @@
+//! TLS for the [Client](super::Client) and the [DevServer](super::DevServer), through a
+//! pluggable [TlsProvider] (requires the `tls` feature).
+//!
+//! dev_utils doesn't implement TLS itself: a provider wraps the TCP connections with the
+//! TLS library of your choice (rustls, native-tls, openssl...), which also owns the
+//! certificates. A client with a provider can call `https://` URLs; a server with one
+//! only accepts TLS connections.
+//!
+//! # Examples
+//! ```no_run
+//! use std::io;
+//! use std::net::TcpStream;
+//! use dev_utils::http::tls::{TlsProvider, TlsStream};
+//! use dev_utils::http::{Client, DevServer, HttpResponse, HttpStatus};
+//!
+//! struct MyTls; // e.g. holding a rustls `ClientConfig` and `ServerConfig`
+//!
+//! impl TlsProvider for MyTls {
+//!     fn connect(&self, host: &str, stream: TcpStream) -> io::Result<Box<dyn TlsStream>> {
+//!         todo!("start a client session for `host` over `stream`")
+//!     }
+//!     fn accept(&self, stream: TcpStream) -> io::Result<Box<dyn TlsStream>> {
+//!         todo!("start a server session over `stream`")
+//!     }
+//! }
+//!
+//! let server = DevServer::new()
+//!     .get("/", |_| HttpResponse::text(HttpStatus::Ok, "secure"))
+//!     .tls(MyTls)
+//!     .bind("127.0.0.1:8443")
+//!     .unwrap();
+//! let client = Client::builder().tls(MyTls).build();
+//! client.get(&server.url()).unwrap();  // https://127.0.0.1:8443
+//! ```
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+/// An established TLS session: reading and writing go through the encryption.
+///
+/// Every `Read + Write + Send` type is a `TlsStream`.
+pub trait TlsStream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> TlsStream for T {}
+
+/// Wraps TCP connections in TLS sessions.
+///
+/// The read and write timeouts of the `TcpStream` are already set when it is handed over.
+pub trait TlsProvider: Send + Sync {
+    /// Starts a client session with `host` (the name its certificate must be valid for).
+    fn connect(&self, host: &str, stream: TcpStream) -> io::Result<Box<dyn TlsStream>>;
+
+    /// Starts a server session on an accepted connection.
+    fn accept(&self, stream: TcpStream) -> io::Result<Box<dyn TlsStream>>;
+}
+
+// A shared provider, for the configurations that derive `Debug` and `Clone`
+#[derive(Clone)]
+pub(crate) struct Tls(pub(crate) Arc<dyn TlsProvider>);
+
+impl fmt::Debug for Tls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {f.write_str("TlsProvider")}
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Client, DevServer, HttpResponse, HttpStatus};
+
+    // Not TLS: XORs the bytes on the wire, enough to tell that both ends go through the provider
+    struct Xor<S>(S);
+
+    impl<S: Read> Read for Xor<S> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.read(buf)?;
+            buf[..n].iter_mut().for_each(|b| *b ^= 0x5A);
+            Ok(n)
+        }
+    }
+
+    impl<S: Write> Write for Xor<S> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write_all(&buf.iter().map(|b| b ^ 0x5A).collect::<Vec<_>>())?;
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {self.0.flush()}
+    }
+
+    struct XorTls;
+
+    impl TlsProvider for XorTls {
+        fn connect(&self, _host: &str, stream: TcpStream) -> io::Result<Box<dyn TlsStream>> {Ok(Box::new(Xor(stream)))}
+        fn accept(&self, stream: TcpStream) -> io::Result<Box<dyn TlsStream>> {Ok(Box::new(Xor(stream)))}
+    }
+
+    #[test]
+    fn test_provider() {
+        let server = DevServer::new()
+            .get("/", |_| HttpResponse::text(HttpStatus::Ok, "secure"))
+            .tls(XorTls)
+            .bind("127.0.0.1:0")
+            .unwrap();
+        assert!(server.url().starts_with("https://"));
+        let client = Client::builder().tls(XorTls).build();
+        for _ in 0..2 {assert_eq!(client.get(&server.url()).unwrap().body, b"secure");}
+
+        // a plain client can't talk to it
+        let plain = server.url().replacen("https", "http", 1);
+        assert!(Client::builder().max_redirects(0).build().get(&plain).is_err());
+        let error = Client::new().get(&server.url()).unwrap_err();
+        assert_eq!(error.to_string(), format!("Invalid request: no TLS provider for {}", server.url()));
+    }
+}
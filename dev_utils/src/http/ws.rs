@@ -0,0 +1,395 @@
+//! WebSockets (RFC 6455): the opening handshake, frame encoding and decoding, and a
+//! message-level [WebSocket] connection that answers pings and close frames.
+//!
+//! The [DevServer](super::DevServer) handles the handshake for the paths registered with
+//! [DevServer::websocket](super::DevServer::websocket) and passes the open connection to
+//! the handler.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::http::ws::{accept_key, Frame, Opcode};
+//!
+//! assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+//!
+//! let frame = Frame::new(Opcode::Text, "Hello");
+//! assert_eq!(frame.encode(None), b"\x81\x05Hello");
+//! assert_eq!(Frame::read_from(&mut &frame.encode(Some([1, 2, 3, 4]))[..]).unwrap(), frame);
+//! ```
+use std::io::{self, BufReader, Read, Write};
+use crate::codex::base64;
+use crate::crypto::hash::sha1;
+use super::{HttpRequest, HttpResponse, HttpStatus};
+
+// Appended to the key of the client to compute the accept key (RFC 6455, section 1.3)
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+// The largest message accepted, as the payload of a single frame or of all the fragments
+const MAX_MESSAGE: usize = 16 * 1024 * 1024;
+
+/// Returns the `Sec-WebSocket-Accept` value for the `Sec-WebSocket-Key` of a client.
+pub fn accept_key(key: &str) -> String {
+    base64::encode(&sha1(format!("{}{}", key.trim(), GUID).as_bytes()))
+}
+
+/// Returns `true` if the request asks to upgrade the connection to a WebSocket.
+pub fn is_upgrade(request: &HttpRequest) -> bool {
+    let has_token = |name: &str, token: &str| request.header(name)
+        .is_some_and(|value| value.split(',').any(|t| t.trim().eq_ignore_ascii_case(token)));
+    has_token("Upgrade", "websocket") && has_token("Connection", "upgrade")
+}
+
+/// Returns the response to the opening handshake of a client: `101 Switching Protocols`,
+/// or `400 Bad Request` if the request is not a valid (version 13) WebSocket upgrade.
+pub fn handshake(request: &HttpRequest) -> HttpResponse {
+    let key = request.header("Sec-WebSocket-Key");
+    match (is_upgrade(request), key, request.header("Sec-WebSocket-Version")) {
+        (true, Some(key), Some("13")) => HttpResponse::new(HttpStatus::SwitchingProtocols)
+            .with_header("Upgrade", "websocket")
+            .with_header("Connection", "Upgrade")
+            .with_header("Sec-WebSocket-Accept", &accept_key(key)),
+        _ => HttpResponse::text(HttpStatus::BadRequest, "invalid WebSocket handshake")
+            .with_header("Sec-WebSocket-Version", "13"),
+    }
+}
+
+/// The type of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    /// A fragment of a message, after its first frame.
+    Continuation = 0x0,
+    Text = 0x1,
+    Binary = 0x2,
+    Close = 0x8,
+    Ping = 0x9,
+    Pong = 0xA,
+}
+
+impl Opcode {
+    /// Returns the opcode with the given value, if it is defined.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0x0 => Some(Opcode::Continuation),
+            0x1 => Some(Opcode::Text),
+            0x2 => Some(Opcode::Binary),
+            0x8 => Some(Opcode::Close),
+            0x9 => Some(Opcode::Ping),
+            0xA => Some(Opcode::Pong),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for the control frames (close, ping and pong).
+    pub fn is_control(&self) -> bool {*self as u8 >= 0x8}
+}
+
+/// A WebSocket frame, unmasked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Whether this is the last frame of its message.
+    pub fin: bool,
+    pub opcode: Opcode,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Creates a final (unfragmented) frame.
+    pub fn new(opcode: Opcode, payload: impl Into<Vec<u8>>) -> Self {
+        Frame { fin: true, opcode, payload: payload.into() }
+    }
+
+    /// Encodes the frame, masking the payload with `mask` if given.
+    ///
+    /// Frames sent by a server are not masked; frames sent by a client must be.
+    pub fn encode(&self, mask: Option<[u8; 4]>) -> Vec<u8> {
+        let mut out = vec![(self.fin as u8) << 7 | self.opcode as u8];
+        let mask_bit = (mask.is_some() as u8) << 7;
+        match self.payload.len() {
+            len @ 0..=125 => out.push(mask_bit | len as u8),
+            len @ 126..=0xFFFF => {
+                out.push(mask_bit | 126);
+                out.extend_from_slice(&(len as u16).to_be_bytes());
+            },
+            len => {
+                out.push(mask_bit | 127);
+                out.extend_from_slice(&(len as u64).to_be_bytes());
+            },
+        }
+        match mask {
+            Some(mask) => {
+                out.extend_from_slice(&mask);
+                out.extend(self.payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+            },
+            None => out.extend_from_slice(&self.payload),
+        }
+        out
+    }
+
+    /// Reads a frame, unmasking its payload.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the frame, or an error of kind `InvalidData` if the frame
+    /// is malformed (unknown opcode, reserved bits, fragmented or oversized control frame).
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Frame> {Self::read_masked(reader).map(|(frame, _)| frame)}
+
+    // Reads a frame, also telling whether it was masked
+    fn read_masked<R: Read>(reader: &mut R) -> io::Result<(Frame, bool)> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut head = [0; 2];
+        reader.read_exact(&mut head)?;
+        if head[0] & 0x70 != 0 {return Err(invalid("reserved bits set"));}
+        let opcode = Opcode::from_u8(head[0] & 0x0F).ok_or_else(|| invalid("unknown opcode"))?;
+        let fin = head[0] & 0x80 != 0;
+        let len = match head[1] & 0x7F {
+            126 => {
+                let mut len = [0; 2];
+                reader.read_exact(&mut len)?;
+                u16::from_be_bytes(len) as u64
+            },
+            127 => {
+                let mut len = [0; 8];
+                reader.read_exact(&mut len)?;
+                u64::from_be_bytes(len)
+            },
+            len => len as u64,
+        };
+        if opcode.is_control() && (!fin || len > 125) {return Err(invalid("invalid control frame"));}
+        if len > MAX_MESSAGE as u64 {return Err(invalid("frame too large"));}
+
+        let mask = match head[1] & 0x80 != 0 {
+            true => {
+                let mut mask = [0; 4];
+                reader.read_exact(&mut mask)?;
+                Some(mask)
+            },
+            false => None,
+        };
+        let mut payload = vec![0; len as usize];
+        reader.read_exact(&mut payload)?;
+        if let Some(mask) = mask {
+            payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+        }
+        Ok((Frame { fin, opcode, payload }, mask.is_some()))
+    }
+}
+
+/// A complete WebSocket message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    /// A close frame, with its status code and reason (if any).
+    Close(Option<(u16, String)>),
+}
+
+impl Message {
+    fn into_frame(self) -> Frame {
+        match self {
+            Message::Text(text) => Frame::new(Opcode::Text, text),
+            Message::Binary(data) => Frame::new(Opcode::Binary, data),
+            Message::Ping(data) => Frame::new(Opcode::Ping, data),
+            Message::Pong(data) => Frame::new(Opcode::Pong, data),
+            Message::Close(None) => Frame::new(Opcode::Close, Vec::new()),
+            Message::Close(Some((code, reason))) => {
+                let mut payload = code.to_be_bytes().to_vec();
+                payload.extend_from_slice(reason.as_bytes());
+                Frame::new(Opcode::Close, payload)
+            },
+        }
+    }
+}
+
+/// The server side of an open WebSocket connection.
+///
+/// [WebSocket::recv] answers pings with pongs and close frames with a close frame, so a
+/// handler only has to deal with the data messages. Every frame of the client must be masked
+/// (RFC 6455, section 5.1).
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use dev_utils::http::ws::{Frame, Message, Opcode, WebSocket};
+///
+/// // what a client sends: a masked ping, then a masked text message
+/// let mut input = Frame::new(Opcode::Ping, "?").encode(Some([9, 9, 9, 9]));
+/// input.extend(Frame::new(Opcode::Text, "hi").encode(Some([1, 2, 3, 4])));
+///
+/// let mut socket = WebSocket::new(Cursor::new(input));
+/// assert_eq!(socket.recv().unwrap(), Some(Message::Text("hi".to_string())));
+/// assert_eq!(socket.recv().unwrap(), None);  // the client is gone
+/// ```
+#[derive(Debug)]
+pub struct WebSocket<S: Read + Write> {
+    stream: BufReader<S>,
+    // whether a close frame was received (or the stream ended), and sent
+    closed: bool,
+    close_sent: bool,
+}
+
+impl<S: Read + Write> WebSocket<S> {
+    /// Wraps a stream on which the handshake is already done.
+    pub fn new(stream: S) -> Self {Self::from_reader(BufReader::new(stream))}
+
+    // Keeps the bytes already buffered while reading the handshake
+    pub(crate) fn from_reader(stream: BufReader<S>) -> Self {WebSocket { stream, closed: false, close_sent: false }}
+
+    /// Returns the underlying stream.
+    pub fn get_ref(&self) -> &S {self.stream.get_ref()}
+
+    /// Sends a message.
+    pub fn send(&mut self, message: Message) -> io::Result<()> {
+        self.close_sent |= matches!(message, Message::Close(_));
+        let stream = self.stream.get_mut();
+        stream.write_all(&message.into_frame().encode(None))?;
+        stream.flush()
+    }
+
+    /// Sends a text message.
+    pub fn send_text(&mut self, text: &str) -> io::Result<()> {self.send(Message::Text(text.to_string()))}
+
+    /// Sends a ping; the answer comes back as a [Message::Pong] from [WebSocket::recv].
+    pub fn ping(&mut self, payload: &[u8]) -> io::Result<()> {self.send(Message::Ping(payload.to_vec()))}
+
+    /// Starts the closing handshake: [WebSocket::recv] then returns the close frame
+    /// of the client, and `None` after it.
+    pub fn close(&mut self, code: u16, reason: &str) -> io::Result<()> {
+        self.send(Message::Close(Some((code, reason.to_string()))))
+    }
+
+    /// Waits for the next message, joining fragmented messages and answering pings and close frames.
+    ///
+    /// # Returns
+    ///
+    /// An `io::Result` containing the message, or `None` once the connection is closed
+    /// (by a close frame or by the client disconnecting). An unmasked frame is an error of
+    /// kind `InvalidData`, after which the connection is closed with status code 1002
+    /// (protocol error).
+    pub fn recv(&mut self) -> io::Result<Option<Message>> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut message: Option<(Opcode, Vec<u8>)> = None;
+        while !self.closed {
+            let frame = match Frame::read_masked(&mut self.stream) {
+                Ok((frame, true)) => frame,
+                Ok((_, false)) => {
+                    self.closed = true;
+                    if !self.close_sent {
+                        let _ = self.close(1002, "unmasked frame");
+                    }
+                    return Err(invalid("unmasked client frame"));
+                },
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {self.closed = true; break;},
+                Err(err) => return Err(err),
+            };
+            match frame.opcode {
+                Opcode::Ping => self.send(Message::Pong(frame.payload))?,
+                Opcode::Pong => return Ok(Some(Message::Pong(frame.payload))),
+                Opcode::Close => {
+                    self.closed = true;
+                    let code = frame.payload.get(..2).map(|code| u16::from_be_bytes([code[0], code[1]]));
+                    // answer with the same status code, unless this answers our own close frame
+                    if !self.close_sent {
+                        let _ = self.send(Message::Close(code.map(|code| (code, String::new()))));
+                    }
+                    let reason = String::from_utf8_lossy(frame.payload.get(2..).unwrap_or_default()).into_owned();
+                    return Ok(Some(Message::Close(code.map(|code| (code, reason)))));
+                },
+                opcode => {
+                    let (kind, data) = match (&mut message, opcode) {
+                        (None, Opcode::Text | Opcode::Binary) => message.insert((opcode, Vec::new())),
+                        (Some(partial), Opcode::Continuation) => partial,
+                        _ => return Err(invalid("unexpected frame in a fragmented message")),
+                    };
+                    if data.len() + frame.payload.len() > MAX_MESSAGE {return Err(invalid("message too large"));}
+                    data.extend_from_slice(&frame.payload);
+                    if frame.fin {
+                        let (kind, data) = (*kind, std::mem::take(data));
+                        return match kind {
+                            Opcode::Text => String::from_utf8(data).map(|text| Some(Message::Text(text)))
+                                .map_err(|_| invalid("text message is not valid UTF-8")),
+                            _ => Ok(Some(Message::Binary(data))),
+                        };
+                    }
+                },
+            }
+        }
+        Ok(None)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::http::HttpMethod;
+
+    #[test]
+    fn test_handshake() {
+        let request = HttpRequest::new(HttpMethod::Get, "/ws")
+            .with_header("Upgrade", "websocket")
+            .with_header("Connection", "keep-alive, Upgrade")
+            .with_header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .with_header("Sec-WebSocket-Version", "13");
+        let response = handshake(&request);
+        assert_eq!(response.status, HttpStatus::SwitchingProtocols);
+        assert_eq!(response.header("Sec-WebSocket-Accept"), Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+        assert_eq!(handshake(&HttpRequest::new(HttpMethod::Get, "/ws")).status, HttpStatus::BadRequest);
+    }
+
+    #[test]
+    fn test_frames() {
+        // examples from RFC 6455, section 5.7
+        let masked = [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+        assert_eq!(Frame::new(Opcode::Text, "Hello").encode(Some([0x37, 0xfa, 0x21, 0x3d])), masked);
+        assert_eq!(Frame::read_from(&mut &masked[..]).unwrap(), Frame::new(Opcode::Text, "Hello"));
+        let long = Frame::new(Opcode::Binary, vec![7; 256]).encode(None);
+        assert_eq!(long[..4], [0x82, 0x7E, 0x01, 0x00]);
+        assert_eq!(Frame::new(Opcode::Binary, vec![0; 70_000]).encode(None)[1], 127);
+
+        let error = |bytes: &[u8]| Frame::read_from(&mut &bytes[..]).unwrap_err().to_string();
+        assert_eq!(error(&[0x83, 0x00]), "unknown opcode");
+        assert_eq!(error(&[0xC1, 0x00]), "reserved bits set");
+        assert_eq!(error(&[0x09, 0x00]), "invalid control frame");
+    }
+
+    #[test]
+    fn test_messages() {
+        let mut input = Frame { fin: false, opcode: Opcode::Text, payload: b"Hel".to_vec() }.encode(Some([1, 1, 1, 1]));
+        input.extend(Frame::new(Opcode::Ping, "p").encode(Some([2, 2, 2, 2])));
+        input.extend(Frame::new(Opcode::Continuation, "lo").encode(Some([3, 3, 3, 3])));
+        input.extend(Message::Close(Some((1000, "bye".to_string()))).into_frame().encode(Some([4, 4, 4, 4])));
+        input.extend(Frame::new(Opcode::Text, "ignored").encode(Some([5, 5, 5, 5])));
+
+        let mut socket = WebSocket::new(Cursor::new(input));
+        assert_eq!(socket.recv().unwrap(), Some(Message::Text("Hello".to_string())));
+        assert_eq!(socket.recv().unwrap(), Some(Message::Close(Some((1000, "bye".to_string())))));
+        assert_eq!(socket.recv().unwrap(), None);
+
+        // the pong to the ping and the echo of the close frame were written after the input
+        let written = socket.get_ref().get_ref();
+        let mut replies = &written[written.len() - 7..];
+        assert_eq!(Frame::read_from(&mut replies).unwrap(), Frame::new(Opcode::Pong, "p"));
+        assert_eq!(Frame::read_from(&mut replies).unwrap(), Frame::new(Opcode::Close, 1000u16.to_be_bytes()));
+    }
+
+    #[test]
+    fn test_unmasked_frame() {
+        let mut input = Frame::new(Opcode::Text, "masked").encode(Some([1, 2, 3, 4]));
+        let sent = input.len();
+        input.extend(Frame::new(Opcode::Text, "unmasked").encode(None));
+
+        let mut socket = WebSocket::new(Cursor::new(input));
+        assert_eq!(socket.recv().unwrap(), Some(Message::Text("masked".to_string())));
+        let err = socket.recv().unwrap_err();
+        assert_eq!((err.kind(), err.to_string()), (io::ErrorKind::InvalidData, "unmasked client frame".to_string()));
+        assert_eq!(socket.recv().unwrap(), None);
+
+        let written = socket.get_ref().get_ref();
+        let mut reply = &written[sent + Frame::new(Opcode::Text, "unmasked").encode(None).len()..];
+        let mut payload = 1002u16.to_be_bytes().to_vec();
+        payload.extend_from_slice(b"unmasked frame");
+        assert_eq!(Frame::read_from(&mut reply).unwrap(), Frame::new(Opcode::Close, payload));
+    }
+}
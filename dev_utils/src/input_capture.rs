@@ -0,0 +1,264 @@
+//! Recording of keyboard input sessions (requires the `input_capture` feature).
+//!
+//! Keys are read with [console::events](crate::console::events), timestamped with the
+//! crate's [datetime](crate::datetime), and collected in a [UserInputKeySequence] that can be
+//! exported with any [ExportFormat] ([Csv] or [Json]), loaded back, and [replay]ed with its
+//! original timing. Sequences also report typing statistics: words per minute, per-key
+//! latencies and a [DigraphMatrix].
+//!
+//! # Examples
+//! ```no_run
+//! use dev_utils::console::events::Key;
+//! use dev_utils::input_capture::{record_until, Csv};
+//!
+//! println!("Type something, then press Esc");
+//! let session = record_until(|key| *key == Key::Esc).unwrap();
+//! println!("You typed {:?} in {:.1?}", session.text(), session.duration());
+//! println!("{}", session.typing_report(std::time::Duration::from_secs(5)));
+//! session.save("session.csv", &Csv).unwrap();
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::console::events::{poll_key, Key};
+use crate::console::term::RawMode;
+use crate::datetime::DateTime;
+use crate::file::{self, FileError};
+use crate::signals;
+
+mod analysis;
+mod export;
+mod replay;
+
+pub use analysis::DigraphMatrix;
+pub use export::{Csv, ExportFormat, Json};
+pub use replay::{replay, Replay};
+
+/// Represents errors that can occur when loading a key sequence.
+#[derive(Debug)]
+pub enum CaptureError {
+    /// The file could not be read.
+    File(FileError),
+    /// The text is not a valid export of a key sequence.
+    InvalidData(String),
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::File(err) => write!(f, "{}", err),
+            CaptureError::InvalidData(message) => write!(f, "invalid key sequence: {}", message),
+        }
+    }
+}
+
+impl Error for CaptureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CaptureError::File(err) => Some(err),
+            CaptureError::InvalidData(_) => None,
+        }
+    }
+}
+
+impl From<FileError> for CaptureError {
+    fn from(err: FileError) -> Self {CaptureError::File(err)}
+}
+
+/// A key press, with the time elapsed since the start of its sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+    /// The time since the start of the sequence.
+    pub offset: Duration,
+}
+
+/// A recorded sequence of key presses.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::console::events::Key;
+/// use dev_utils::datetime::DateTime;
+/// use dev_utils::input_capture::UserInputKeySequence;
+///
+/// let mut seq = UserInputKeySequence::new(DateTime::now());
+/// for (i, key) in [Key::Char('h'), Key::Char('o'), Key::Backspace, Key::Char('i')].into_iter().enumerate() {
+///     seq.push(key, Duration::from_millis(100 * i as u64));
+/// }
+/// assert_eq!(seq.text(), "hi");
+/// assert_eq!(seq.duration(), Duration::from_millis(300));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserInputKeySequence {
+    started: DateTime,
+    events: Vec<KeyEvent>,
+}
+
+impl UserInputKeySequence {
+    /// Creates an empty sequence started at the given time.
+    pub fn new(started: DateTime) -> Self {UserInputKeySequence { started, events: Vec::new() }}
+
+    /// Adds a key pressed `offset` after the start of the sequence.
+    ///
+    /// Offsets earlier than the last event are raised to it, so the events stay in order.
+    pub fn push(&mut self, key: Key, offset: Duration) {
+        let offset = offset.max(self.duration());
+        self.events.push(KeyEvent { key, offset });
+    }
+
+    /// Returns the time the sequence started.
+    pub fn started(&self) -> DateTime {self.started}
+
+    /// Returns the recorded key presses, in order.
+    pub fn events(&self) -> &[KeyEvent] {&self.events}
+
+    /// Returns the number of key presses.
+    pub fn len(&self) -> usize {self.events.len()}
+
+    /// Returns `true` if no key was pressed.
+    pub fn is_empty(&self) -> bool {self.events.is_empty()}
+
+    /// Returns the time from the start of the sequence to its last key press.
+    pub fn duration(&self) -> Duration {self.events.last().map_or(Duration::ZERO, |e| e.offset)}
+
+    /// Returns the date and time of an event of this sequence.
+    pub fn time_of(&self, event: &KeyEvent) -> DateTime {self.started.add_duration(event.offset)}
+
+    /// Returns the time between each key press and the next one.
+    pub fn time_between_keys(&self) -> Vec<Duration> {
+        self.events.windows(2).map(|w| w[1].offset - w[0].offset).collect()
+    }
+
+    /// Returns the text typed during the sequence: characters, `Enter` and `Tab` are added,
+    /// `Backspace` removes the last character, and other keys are ignored.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for event in &self.events {
+            match event.key {
+                Key::Char(c) => text.push(c),
+                Key::Enter => text.push('\n'),
+                Key::Tab => text.push('\t'),
+                Key::Backspace => {text.pop();},
+                _ => {},
+            }
+        }
+        text
+    }
+
+    /// Exports the sequence in the given format.
+    pub fn export<F: ExportFormat + ?Sized>(&self, format: &F) -> String {format.export(self)}
+
+    /// Exports the sequence in the given format and writes it to a file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the path of the file, or a [FileError].
+    pub fn save<P: AsRef<Path>, F: ExportFormat + ?Sized>(&self, path: P, format: &F) -> Result<PathBuf, FileError> {
+        file::create(path, &format.export(self))
+    }
+
+    /// Reads a sequence saved with [UserInputKeySequence::save].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the sequence, or a [CaptureError] if the file can't be read or parsed.
+    pub fn load<P: AsRef<Path>, F: ExportFormat + ?Sized>(path: P, format: &F) -> Result<Self, CaptureError> {
+        format.import(&file::read(path)?)
+    }
+}
+
+/// Records key presses into a [UserInputKeySequence], timing them from its creation.
+///
+/// Use it to timestamp keys read in an existing input loop; [record_until] runs the loop itself.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::console::events::Key;
+/// use dev_utils::input_capture::KeyRecorder;
+///
+/// let mut recorder = KeyRecorder::start();
+/// recorder.record(Key::Char('y'));
+/// recorder.record(Key::Enter);
+/// assert_eq!(recorder.finish().text(), "y\n");
+/// ```
+#[derive(Debug)]
+pub struct KeyRecorder {
+    clock: Instant,
+    sequence: UserInputKeySequence,
+}
+
+impl KeyRecorder {
+    /// Starts a new sequence now.
+    pub fn start() -> Self {
+        KeyRecorder { clock: Instant::now(), sequence: UserInputKeySequence::new(DateTime::now()) }
+    }
+
+    /// Adds a key pressed now.
+    pub fn record(&mut self, key: Key) {self.sequence.push(key, self.clock.elapsed());}
+
+    /// Returns the keys recorded so far.
+    pub fn sequence(&self) -> &UserInputKeySequence {&self.sequence}
+
+    /// Stops recording and returns the sequence.
+    pub fn finish(self) -> UserInputKeySequence {self.sequence}
+}
+
+// How often record_until checks for a shutdown between keys
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Records the keys pressed in the terminal until `stop` returns `true` for one of them
+/// (that key is not recorded).
+///
+/// The terminal stays in raw mode while recording, so keys are not echoed. `Ctrl+C` is
+/// handled as outside raw mode (see [signals::interrupt](crate::signals::interrupt)): it
+/// ends the program, unless a `Ctrl+C` handler is installed, in which case recording stops
+/// and the keys recorded so far are returned. Recording also stops when the
+/// [ctrl_c_token](crate::signals::ctrl_c_token) is triggered some other way.
+///
+/// # Returns
+///
+/// A `Result` containing the recorded sequence, or an `io::Error` if the terminal can't be read.
+pub fn record_until<F: FnMut(&Key) -> bool>(mut stop: F) -> io::Result<UserInputKeySequence> {
+    let _raw = RawMode::enable()?;
+    let mut recorder = KeyRecorder::start();
+    while !signals::shutdown_requested() {
+        let Some(key) = poll_key(SHUTDOWN_CHECK_INTERVAL)? else {continue};
+        if key == Key::Ctrl('c') {
+            signals::interrupt();
+            break;
+        }
+        if stop(&key) {break;}
+        recorder.record(key);
+    }
+    Ok(recorder.finish())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn sample() -> UserInputKeySequence {
+        let mut seq = UserInputKeySequence::new("2024-03-01 12:00:00".parse().unwrap());
+        let keys = [(Key::Char('a'), 0), (Key::Char(','), 150), (Key::Ctrl('w'), 400), (Key::Char('"'), 1250)];
+        for (key, ms) in keys {seq.push(key, Duration::from_millis(ms));}
+        seq
+    }
+
+    #[test]
+    fn test_sequence() {
+        let mut seq = sample();
+        assert_eq!(seq.len(), 4);
+        assert_eq!(seq.text(), "a,\"");
+        assert_eq!(seq.time_between_keys(), [150, 250, 850].map(Duration::from_millis));
+        assert_eq!(seq.time_of(&seq.events()[3]).to_string(), "2024-03-01 12:00:01.250");
+        // out of order offsets are clamped
+        seq.push(Key::Enter, Duration::ZERO);
+        assert_eq!(seq.events()[4].offset, Duration::from_millis(1250));
+    }
+}
@@ -0,0 +1,267 @@
+//! Typing statistics of recorded key sequences.
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use crate::console::events::Key;
+use crate::format::chart::{bar_chart, heat_color, line_chart};
+use crate::format::table::{Alignment, BorderStyle, Table};
+use crate::format::{Stylize, Style};
+use crate::stats::{self, Histogram};
+use super::{KeyEvent, UserInputKeySequence};
+
+// The usual definition of a "word" for typing speeds
+const CHARS_PER_WORD: f64 = 5.0;
+
+// Spaces are invisible as chart labels
+fn label(key: &Key) -> String {
+    match key {
+        Key::Char(' ') => "␣".to_string(),
+        key => key.to_string(),
+    }
+}
+
+fn millis(duration: Duration) -> f64 {duration.as_secs_f64() * 1000.0}
+
+/// Typing statistics.
+///
+/// Terminals report key presses but not releases, so the time a key is held down can't be
+/// measured. The per-key statistics use the time from the previous key press instead
+/// (the *latency* of the key), which is what makes a key slow or fast to type.
+impl UserInputKeySequence {
+    // The events of the characters typed
+    fn typed(&self) -> impl Iterator<Item = &KeyEvent> {
+        self.events.iter().filter(|e| matches!(e.key, Key::Char(_)))
+    }
+
+    /// Returns the typing speed in words (of 5 characters) per minute, from the first key
+    /// press to the last. Only characters count: corrections and other keys are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dev_utils::console::events::Key;
+    /// use dev_utils::datetime::DateTime;
+    /// use dev_utils::input_capture::UserInputKeySequence;
+    ///
+    /// // 11 characters, one every 200ms: 5 characters per second, 60 wpm
+    /// let mut seq = UserInputKeySequence::new(DateTime::now());
+    /// for (i, c) in "hello world".chars().enumerate() {
+    ///     seq.push(Key::Char(c), Duration::from_millis(200 * i as u64));
+    /// }
+    /// assert_eq!(seq.wpm().round(), 60.0);
+    /// ```
+    pub fn wpm(&self) -> f64 {
+        let (Some(first), Some(last)) = (self.events.first(), self.events.last()) else {return 0.0;};
+        let minutes = (last.offset - first.offset).as_secs_f64() / 60.0;
+        // the first key only starts the clock
+        let typed = self.events[1..].iter().filter(|e| matches!(e.key, Key::Char(_))).count();
+        match minutes > 0.0 {
+            true => typed as f64 / CHARS_PER_WORD / minutes,
+            false => 0.0,
+        }
+    }
+
+    /// Returns the typing speed (in words per minute) of each consecutive `window` of time,
+    /// starting at the first key press. The last window may be partially filled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    pub fn wpm_over_time(&self, window: Duration) -> Vec<f64> {
+        assert!(!window.is_zero(), "the window must not be empty");
+        let Some(first) = self.events.first() else {return Vec::new();};
+        let windows = ((self.duration() - first.offset).as_nanos() / window.as_nanos()) as usize + 1;
+        let mut counts = vec![0usize; windows];
+        for event in self.typed() {
+            counts[((event.offset - first.offset).as_nanos() / window.as_nanos()) as usize] += 1;
+        }
+        let minutes = window.as_secs_f64() / 60.0;
+        counts.into_iter().map(|n| n as f64 / CHARS_PER_WORD / minutes).collect()
+    }
+
+    /// Returns the latencies of each key: the times from the previous key press to each of its presses.
+    pub fn key_latencies(&self) -> HashMap<Key, Vec<Duration>> {
+        let mut latencies: HashMap<Key, Vec<Duration>> = HashMap::new();
+        for w in self.events.windows(2) {
+            latencies.entry(w[1].key).or_default().push(w[1].offset - w[0].offset);
+        }
+        latencies
+    }
+
+    /// Returns a histogram of the latencies of a key, in milliseconds,
+    /// or `None` if the key was never pressed after another one.
+    pub fn key_histogram(&self, key: Key, buckets: usize) -> Option<Histogram> {
+        let latencies: Vec<f64> = self.key_latencies().remove(&key)?.into_iter().map(millis).collect();
+        Some(Histogram::new(&latencies, buckets))
+    }
+
+    /// Returns the latencies of every pair of consecutive keys.
+    pub fn digraphs(&self) -> DigraphMatrix {
+        let mut matrix = DigraphMatrix { keys: Vec::new(), latencies: HashMap::new() };
+        for w in self.events.windows(2) {
+            for key in [w[0].key, w[1].key] {
+                if !matrix.keys.contains(&key) {matrix.keys.push(key);}
+            }
+            matrix.latencies.entry((w[0].key, w[1].key)).or_default().push(w[1].offset - w[0].offset);
+        }
+        matrix
+    }
+
+    /// Renders a report of the typing statistics: the overall speed, the speed over time
+    /// (in windows of `window`), the mean latency of each key, and the slowest digraphs.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use dev_utils::input_capture::{Csv, UserInputKeySequence};
+    ///
+    /// let session = UserInputKeySequence::load("session.csv", &Csv).unwrap();
+    /// println!("{}", session.typing_report(Duration::from_secs(5)));
+    /// ```
+    pub fn typing_report(&self, window: Duration) -> String {
+        let heading = |s: &str| s.style(Style::Bold);
+        let corrections = self.events.iter().filter(|e| e.key == Key::Backspace).count();
+        let mut out = format!("{} {:.1} wpm | {} keys | {} corrections | {:.1?}\n",
+            heading("speed"), self.wpm(), self.len(), corrections, self.duration());
+
+        let speeds = self.wpm_over_time(window);
+        if speeds.len() > 1 {
+            out.push_str(&format!("\n{} (wpm per {:?})\n{}\n", heading("speed over time"), window, line_chart(&speeds, 60, 6)));
+        }
+
+        let mut keys: Vec<(String, f64)> = self.key_latencies().into_iter()
+            .filter_map(|(key, latencies)| Some((label(&key), stats::mean(&latencies.into_iter().map(millis).collect::<Vec<_>>())?)))
+            .collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+        if !keys.is_empty() {
+            let (labels, values): (Vec<String>, Vec<f64>) = keys.into_iter().unzip();
+            out.push_str(&format!("\n{}\n{}\n", heading("mean latency per key (ms)"), bar_chart(&labels, &values, 40)));
+        }
+
+        let slowest = self.digraphs().slowest(10);
+        if !slowest.is_empty() {
+            let labels: Vec<String> = slowest.iter().map(|((a, b), _)| format!("{} {}", label(a), label(b))).collect();
+            let values: Vec<f64> = slowest.iter().map(|(_, d)| millis(*d)).collect();
+            out.push_str(&format!("\n{}\n{}\n", heading("slowest digraphs (ms)"), bar_chart(&labels, &values, 40)));
+        }
+        out.trim_end().to_string()
+    }
+}
+
+/// The latencies between pairs of consecutive keys (digraphs), like `t` → `h`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::console::events::Key;
+/// use dev_utils::datetime::DateTime;
+/// use dev_utils::input_capture::UserInputKeySequence;
+///
+/// let mut seq = UserInputKeySequence::new(DateTime::now());
+/// for (c, ms) in [('t', 0), ('h', 100), ('e', 250), ('t', 400), ('h', 600)] {
+///     seq.push(Key::Char(c), Duration::from_millis(ms));
+/// }
+/// let digraphs = seq.digraphs();
+/// assert_eq!(digraphs.count(Key::Char('t'), Key::Char('h')), 2);
+/// assert_eq!(digraphs.mean(Key::Char('t'), Key::Char('h')), Some(Duration::from_millis(150)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigraphMatrix {
+    keys: Vec<Key>,
+    latencies: HashMap<(Key, Key), Vec<Duration>>,
+}
+
+impl DigraphMatrix {
+    /// Returns the keys of the digraphs, in the order they were first pressed.
+    pub fn keys(&self) -> &[Key] {&self.keys}
+
+    /// Returns how many times `second` was pressed right after `first`.
+    pub fn count(&self, first: Key, second: Key) -> usize {
+        self.latencies.get(&(first, second)).map_or(0, Vec::len)
+    }
+
+    /// Returns the mean latency from `first` to `second`, or `None` if they were never consecutive.
+    pub fn mean(&self, first: Key, second: Key) -> Option<Duration> {
+        let latencies = self.latencies.get(&(first, second))?;
+        Some(latencies.iter().sum::<Duration>() / latencies.len() as u32)
+    }
+
+    /// Returns the (at most) `n` digraphs with the highest mean latency, slowest first.
+    pub fn slowest(&self, n: usize) -> Vec<((Key, Key), Duration)> {
+        let mut digraphs: Vec<((Key, Key), Duration)> = self.latencies.keys()
+            .filter_map(|&(a, b)| Some(((a, b), self.mean(a, b)?)))
+            .collect();
+        // ties are ordered by the keys, so that the result doesn't depend on the hashing
+        digraphs.sort_by(|x, y| y.1.cmp(&x.1).then_with(|| self.position(x.0).cmp(&self.position(y.0))));
+        digraphs.truncate(n);
+        digraphs
+    }
+
+    fn position(&self, (a, b): (Key, Key)) -> (usize, usize) {
+        let index = |key| self.keys.iter().position(|&k| k == key).unwrap_or(usize::MAX);
+        (index(a), index(b))
+    }
+
+    /// Renders the matrix as a table with the first key of each digraph in the rows and the
+    /// second one in the columns. Each cell holds the mean latency in milliseconds, colored
+    /// from green (fast) to red (slow).
+    pub fn render(&self) -> String {
+        let peak = self.latencies.keys().filter_map(|&(a, b)| self.mean(a, b)).max().map_or(0.0, millis);
+        let mut table = Table::new(std::iter::once(String::new()).chain(self.keys.iter().map(label)))
+            .border(BorderStyle::Line);
+        for &first in &self.keys {
+            table.add_row(std::iter::once(label(&first).style(Style::Bold)).chain(self.keys.iter().map(|&second| {
+                match self.mean(first, second).map(millis) {
+                    Some(ms) => format!("{:.0}", ms).color(heat_color(if peak > 0.0 {ms / peak} else {0.0})),
+                    None => "·".style(Style::Dim),
+                }
+            })));
+        }
+        (1..=self.keys.len()).fold(table, |table, column| table.align(column, Alignment::Right)).render()
+    }
+}
+
+impl fmt::Display for DigraphMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::strip_ansi_codes;
+    use super::super::tests::sample;
+
+    #[test]
+    fn test_speed() {
+        let seq = sample();
+        // 3 characters (the first one starts the clock) over 1.25s
+        assert!((seq.wpm() - 2.0 / 5.0 / (1.25 / 60.0)).abs() < 1e-9);
+        assert_eq!(seq.wpm_over_time(Duration::from_secs(1)), [2.0 / 5.0 * 60.0, 1.0 / 5.0 * 60.0]);
+        assert_eq!(UserInputKeySequence::new(seq.started()).wpm(), 0.0);
+        assert!(UserInputKeySequence::new(seq.started()).wpm_over_time(Duration::from_secs(1)).is_empty());
+    }
+
+    #[test]
+    fn test_latencies() {
+        let seq = sample();
+        let latencies = seq.key_latencies();
+        assert_eq!(latencies[&Key::Ctrl('w')], [Duration::from_millis(250)]);
+        assert!(!latencies.contains_key(&Key::Char('a')));
+        assert_eq!(seq.key_histogram(Key::Char('"'), 2).unwrap().total(), 1);
+        assert_eq!(seq.key_histogram(Key::Enter, 2), None);
+
+        let digraphs = seq.digraphs();
+        assert_eq!(digraphs.keys().len(), 4);
+        assert_eq!(digraphs.slowest(1), [((Key::Ctrl('w'), Key::Char('"')), Duration::from_millis(850))]);
+        let matrix = strip_ansi_codes(&digraphs.render());
+        assert_eq!(matrix.lines().count(), 4 + 4);
+        assert!(matrix.contains("│ Ctrl+w │ · │   · │      · │ 850 │"));
+        assert!(strip_ansi_codes(&seq.typing_report(Duration::from_secs(1))).starts_with("speed 19.2 wpm | 4 keys | 0 corrections |"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Export formats for recorded key sequences.
+use std::time::Duration;
+use crate::console::events::Key;
+use crate::datetime::DateTime;
+use crate::dlog::json_escape;
+use crate::file::yaml::{self, YamlValue};
+use super::{CaptureError, UserInputKeySequence};
+
+/// A text format that key sequences can be exported to and imported from.
+pub trait ExportFormat {
+    /// Returns the usual file extension of the format, without the dot.
+    fn extension(&self) -> &'static str;
+
+    /// Writes the sequence in this format.
+    fn export(&self, sequence: &UserInputKeySequence) -> String;
+
+    /// Reads a sequence written by [ExportFormat::export].
+    fn import(&self, text: &str) -> Result<UserInputKeySequence, CaptureError>;
+}
+
+fn invalid(message: impl Into<String>) -> CaptureError {CaptureError::InvalidData(message.into())}
+
+/// Comma-separated values, one key press per row: `time,offset_us,key`.
+///
+/// Keys are written as in [Key]'s `Display` (`a`, `Enter`, `Ctrl+c`), quoted when needed.
+/// The start of an imported sequence is the time of its first row minus its offset
+/// (or the Unix epoch, for a sequence without keys).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::console::events::Key;
+/// use dev_utils::input_capture::{Csv, UserInputKeySequence};
+///
+/// let mut seq = UserInputKeySequence::new("2024-03-01 12:00:00".parse().unwrap());
+/// seq.push(Key::Char('y'), Duration::from_millis(20));
+/// seq.push(Key::Enter, Duration::from_millis(180));
+/// assert_eq!(seq.export(&Csv), "\
+/// time,offset_us,key
+/// 2024-03-01 12:00:00.020,20000,y
+/// 2024-03-01 12:00:00.180,180000,Enter
+/// ");
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Csv;
+
+impl ExportFormat for Csv {
+    fn extension(&self) -> &'static str {"csv"}
+
+    fn export(&self, sequence: &UserInputKeySequence) -> String {
+        let mut out = String::from("time,offset_us,key\n");
+        for event in sequence.events() {
+            out.push_str(&format!("{},{},{}\n", sequence.time_of(event), event.offset.as_micros(), csv_field(&event.key.to_string())));
+        }
+        out
+    }
+
+    fn import(&self, text: &str) -> Result<UserInputKeySequence, CaptureError> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        match lines.next() {
+            Some((_, "time,offset_us,key")) => {},
+            _ => return Err(invalid("expected the header `time,offset_us,key`")),
+        }
+        let mut sequence: Option<UserInputKeySequence> = None;
+        for (i, line) in lines {
+            let error = |field: &str| invalid(format!("line {}: invalid {}", i + 1, field));
+            let fields = split_csv(line).ok_or_else(|| error("quoting"))?;
+            let [time, offset, key] = fields.as_slice() else {return Err(error("number of fields"));};
+            let offset = Duration::from_micros(offset.parse().map_err(|_| error("offset"))?);
+            let key: Key = key.parse().map_err(|_| error("key"))?;
+            let time: DateTime = time.parse().map_err(|_| error("time"))?;
+            sequence.get_or_insert_with(|| UserInputKeySequence::new(time.sub_duration(offset))).push(key, offset);
+        }
+        Ok(sequence.unwrap_or_else(|| UserInputKeySequence::new(DateTime::from_timestamp_millis(0))))
+    }
+}
+
+// Splits a CSV row into its fields, unquoting them; `None` if a quote is not closed
+fn split_csv(line: &str) -> Option<Vec<String>> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut()?;
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {chars.next(); field.push('"');},
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(String::new()),
+            (c, _) => field.push(c),
+        }
+    }
+    (!quoted).then_some(fields)
+}
+
+// Quotes a field that contains a separator, a quote or a line break (or is a lone space)
+fn csv_field(s: &str) -> String {
+    match s.contains([',', '"', '\n', '\r']) || s.trim().is_empty() {
+        true => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.to_string(),
+    }
+}
+
+/// A JSON object with the start time of the sequence and its key presses.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::console::events::Key;
+/// use dev_utils::input_capture::{Json, UserInputKeySequence};
+///
+/// let mut seq = UserInputKeySequence::new("2024-03-01 12:00:00".parse().unwrap());
+/// seq.push(Key::Char('"'), Duration::from_millis(20));
+/// assert_eq!(seq.export(&Json),
+///     r#"{"started": "2024-03-01 12:00:00", "events": [{"offset_us": 20000, "key": "\""}]}"#);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+impl ExportFormat for Json {
+    fn extension(&self) -> &'static str {"json"}
+
+    fn export(&self, sequence: &UserInputKeySequence) -> String {
+        let events = sequence.events().iter()
+            .map(|event| format!("{{\"offset_us\": {}, \"key\": \"{}\"}}", event.offset.as_micros(), json_escape(&event.key.to_string())))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{{\"started\": \"{}\", \"events\": [{}]}}", sequence.started(), events)
+    }
+
+    fn import(&self, text: &str) -> Result<UserInputKeySequence, CaptureError> {
+        let doc = yaml::parse(text).map_err(|err| invalid(err.to_string()))?;
+        let started: DateTime = doc.get("started").and_then(YamlValue::as_str)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid("missing or invalid `started`"))?;
+        let events = doc.get("events").and_then(YamlValue::as_sequence).ok_or_else(|| invalid("missing `events`"))?;
+        let mut sequence = UserInputKeySequence::new(started);
+        for (i, event) in events.iter().enumerate() {
+            let offset = event.get("offset_us").and_then(YamlValue::as_int).and_then(|us| u64::try_from(us).ok())
+                .ok_or_else(|| invalid(format!("event {}: invalid `offset_us`", i)))?;
+            let key = event.get("key").and_then(YamlValue::as_str).and_then(|key| key.parse::<Key>().ok())
+                .ok_or_else(|| invalid(format!("event {}: invalid `key`", i)))?;
+            sequence.push(key, Duration::from_micros(offset));
+        }
+        Ok(sequence)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::sample;
+
+    #[test]
+    fn test_export() {
+        let csv = sample().export(&Csv);
+        assert_eq!(csv.lines().nth(2), Some("2024-03-01 12:00:00.150,150000,\",\""));
+        assert_eq!(csv.lines().nth(4), Some("2024-03-01 12:00:01.250,1250000,\"\"\"\""));
+        assert_eq!(csv_field(" "), "\" \"");
+
+        let json = sample().export(&Json);
+        assert!(json.contains(r#"{"offset_us": 400000, "key": "Ctrl+w"}"#));
+        assert_eq!((Csv.extension(), Json.extension()), ("csv", "json"));
+    }
+
+    #[test]
+    fn test_import() {
+        let seq = sample();
+        assert_eq!(Csv.import(&seq.export(&Csv)).unwrap(), seq);
+        assert_eq!(Json.import(&seq.export(&Json)).unwrap(), seq);
+        let empty = UserInputKeySequence::new(DateTime::from_timestamp_millis(0));
+        assert_eq!(Csv.import("time,offset_us,key\n").unwrap(), empty);
+
+        assert_eq!(split_csv(r#"a,"b,""c""",d"#), Some(vec!["a".to_string(), "b,\"c\"".to_string(), "d".to_string()]));
+        assert_eq!(split_csv(r#"a,"b"#), None);
+        let error = |result: Result<UserInputKeySequence, CaptureError>| result.unwrap_err().to_string();
+        assert_eq!(error(Csv.import("nope")), "invalid key sequence: expected the header `time,offset_us,key`");
+        assert_eq!(error(Csv.import("time,offset_us,key\n2024-03-01 12:00:00,10,Nope")), "invalid key sequence: line 2: invalid key");
+        assert_eq!(error(Json.import(r#"{"started": "2024-03-01 12:00:00", "events": [{"offset_us": -1, "key": "a"}]}"#)),
+            "invalid key sequence: event 0: invalid `offset_us`");
+    }
+}
@@ -0,0 +1,109 @@
+//! Replay of recorded key sequences with their original timing.
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use crate::console::events::{inject_keys, Key};
+use super::{KeyEvent, UserInputKeySequence};
+
+/// Replays the keys of a sequence with their original timing, scaled by `speed_factor`
+/// (`2.0` replays twice as fast, `f64::INFINITY` without any delay).
+///
+/// The returned [Replay] is an iterator that waits before yielding each key; use
+/// [Replay::inject] to feed the keys to [read_key](crate::console::events::read_key) instead.
+///
+/// # Panics
+///
+/// Panics if `speed_factor` is not positive.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::console::events::Key;
+/// use dev_utils::datetime::DateTime;
+/// use dev_utils::input_capture::{replay, UserInputKeySequence};
+///
+/// let mut seq = UserInputKeySequence::new(DateTime::now());
+/// seq.push(Key::Char('y'), Duration::from_millis(200));
+/// seq.push(Key::Enter, Duration::from_millis(400));
+///
+/// // 100x faster: the keys come after 2ms and 4ms
+/// let keys: Vec<Key> = replay(&seq, 100.0).collect();
+/// assert_eq!(keys, [Key::Char('y'), Key::Enter]);
+/// ```
+pub fn replay(sequence: &UserInputKeySequence, speed_factor: f64) -> Replay {
+    assert!(speed_factor > 0.0, "the replay speed must be positive, got {}", speed_factor);
+    Replay { events: sequence.events().to_vec().into_iter(), speed: speed_factor, start: None }
+}
+
+/// An iterator over the keys of a sequence, yielding each one at its (scaled) time.
+///
+/// The clock starts with the first call to `next`.
+#[derive(Debug)]
+pub struct Replay {
+    events: std::vec::IntoIter<KeyEvent>,
+    speed: f64,
+    start: Option<Instant>,
+}
+
+impl Replay {
+    /// Returns the scaled time of an event from the start of the replay.
+    fn delay(&self, event: &KeyEvent) -> Duration {
+        match self.speed.is_finite() {
+            true => event.offset.div_f64(self.speed),
+            false => Duration::ZERO,
+        }
+    }
+
+    /// Injects the keys into the terminal input from a background thread, each at its time,
+    /// so they are returned by [read_key](crate::console::events::read_key) and
+    /// [poll_key](crate::console::events::poll_key) as if they were typed.
+    ///
+    /// Keys are only seen by reads made after they are injected: a `read_key` already blocked
+    /// on the terminal waits for real input, so the code under test should poll for keys.
+    ///
+    /// # Returns
+    ///
+    /// The handle of the thread, which ends after the last key.
+    pub fn inject(self) -> JoinHandle<()> {
+        thread::spawn(move || self.for_each(|key| inject_keys([key])))
+    }
+}
+
+impl Iterator for Replay {
+    type Item = Key;
+
+    fn next(&mut self) -> Option<Key> {
+        let event = self.events.next()?;
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let elapsed = start.elapsed();
+        let delay = self.delay(&event);
+        if delay > elapsed {thread::sleep(delay - elapsed);}
+        Some(event.key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {self.events.size_hint()}
+}
+
+impl ExactSizeIterator for Replay {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tests::sample;
+
+    #[test]
+    fn test_replay() {
+        let seq = sample();
+        let start = Instant::now();
+        let keys: Vec<Key> = replay(&seq, 25.0).collect();
+        // the last key is 1250ms into the sequence, 50ms at 25x
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(keys, seq.events().iter().map(|e| e.key).collect::<Vec<_>>());
+        assert_eq!(replay(&seq, f64::INFINITY).len(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive")]
+    fn test_replay_speed() {replay(&sample(), 0.0);}
+}
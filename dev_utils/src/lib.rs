@@ -4,6 +4,16 @@
 //! [dependencies]
 //! dev_utils = "0.*"  # Add the latest version of this crate
 //! ```
+//!
+//! # Features
+//!
+//! The modules are behind cargo features (default: `dlog`, `format`, `file`, `datetime`),
+//! so that a crate using only the logger doesn't compile the HTTP or crypto code:
+//! `format`, `datetime`, `dlog`, `file`, `codex`, `crypto`, `num`, `http` (and `tls`),
+//! `input`, `wasm` and `log-compat`. See the README for the modules of each one.
+//!
+//! Without the `std` feature the crate is `no_std` (it only needs `alloc`): `format`
+//! (ANSI styling, tables, charts), `datetime` and `base_change` are still available.
 //! 
 //! # Usage
 //! 
@@ -16,17 +26,87 @@
 //! );
 //! ```
 #![allow(unused)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+// the test harness needs `std`, even for the `no_std` build
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
 
-pub mod dlog;
+// available without `std` (on `core` + `alloc`)
+pub mod base_change;
+#[cfg(feature = "format")]
 pub mod format;
-pub mod file;
+#[cfg(feature = "datetime")]
 pub mod datetime;
-pub mod base_change;
 
+// always available with `std`: no dependencies on the other modules
+#[cfg(feature = "std")]
+pub mod convert;
+#[cfg(feature = "std")]
+pub mod timing;
+#[cfg(feature = "std")]
+pub mod eval;
+#[cfg(feature = "std")]
+pub mod error;
+
+#[cfg(all(feature = "std", feature = "format"))]
+pub mod text;
+#[cfg(all(feature = "std", feature = "format"))]
+pub mod units;
+#[cfg(all(feature = "std", feature = "format"))]
+pub mod stats;
+
+#[cfg(feature = "dlog")]
+pub mod dlog;
+#[cfg(feature = "dlog")]
+pub mod console;
+#[cfg(feature = "dlog")]
+pub mod signals;
+#[cfg(feature = "dlog")]
+pub mod proc;
+#[cfg(feature = "dlog")]
+pub mod performance;
+#[cfg(feature = "dlog")]
+pub mod retry;
+
+#[cfg(feature = "file")]
+pub mod file;
+#[cfg(feature = "file")]
+pub mod testing;
+#[cfg(feature = "file")]
+pub mod diff;
+#[cfg(feature = "file")]
+pub mod config;
+#[cfg(feature = "file")]
+pub mod env;
+#[cfg(feature = "file")]
+pub mod vcs;
+#[cfg(feature = "file")]
+pub mod template;
+
+#[cfg(feature = "codex")]
+pub mod codex;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+#[cfg(feature = "num")]
+pub mod num;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "input")]
+pub mod input_capture;
+
+#[cfg(feature = "std")]
 use std::io::{self, Write};
-use std::str::FromStr;
-use std::fmt::Display;
+use core::str::FromStr;
+use core::fmt::Display;
+
+#[cfg(feature = "file")]
+pub use helpers::{app_info, AppDataError, AppInfo};
+pub use dev_macros::{extract_app_data, EnumStr};
+
+// lets the code generated by the proc macros (`::dev_utils::...`) be used inside this crate
+extern crate self as dev_utils;
 
 /// Reads input from the console, optionally displaying a prompt message.
 ///
@@ -51,11 +131,14 @@ use std::fmt::Display;
 ///
 /// # Examples
 ///
-/// ```
+/// ```no_run
+/// use dev_utils::read_input;
+///
 /// let number: i32 = read_input(Some("Enter a number: ")).unwrap();
 /// let name: String = read_input(Some("Enter your name: ")).unwrap();
 /// read_input::<String>(None); // Acts as a pause
 /// ```
+#[cfg(feature = "std")]
 pub fn read_input<T>(prompt: Option<&str>) -> Result<T, String>
 where
     T: FromStr + Default,
@@ -76,87 +159,158 @@ where
     trimmed.parse().map_err(|e| format!("Parse error: {}", e))
 }
 
+/// Reads input until it can be parsed and passes `validate`, asking again after each failure.
+///
+/// Parse and validation errors are shown in red below the prompt before asking again,
+/// so the caller doesn't need its own retry loop.
+///
+/// # Arguments
+///
+/// - `prompt`: The prompt message displayed before each attempt.
+/// - `validate`: Checks the parsed value, returning the message to show when it is rejected.
+///
+/// # Returns
+///
+/// - `T`: The first valid value
+/// - `String`: An error message if stdin is closed before a valid value is entered
+///
+/// # Examples
+///
+/// ```no_run
+/// use dev_utils::read_input_validated;
+///
+/// let port: u16 = read_input_validated("Port: ", |port| match *port >= 1024 {
+///     true => Ok(()),
+///     false => Err("ports below 1024 need root".to_string()),
+/// }).unwrap();
+/// ```
+#[cfg(all(feature = "std", feature = "format"))]
+pub fn read_input_validated<T, F>(prompt: &str, validate: F) -> Result<T, String>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+    F: Fn(&T) -> Result<(), String>,
+{
+    read_validated_from(&mut io::stdin().lock(), &mut io::stdout(), prompt, None, validate)
+}
+
+/// Reads input, returning `default` when the answer is empty.
+///
+/// The default value is shown in dim text after the prompt (`Name [guest]: `).
+/// Invalid input shows an error and asks again.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dev_utils::read_input_with_default;
+///
+/// let retries: u32 = read_input_with_default("Retries", 3).unwrap();
+/// ```
+#[cfg(all(feature = "std", feature = "format"))]
+pub fn read_input_with_default<T>(prompt: &str, default: T) -> Result<T, String>
+where
+    T: FromStr + Display,
+    <T as FromStr>::Err: Display,
+{
+    use crate::format::{Style, Stylize};
+
+    let prompt = format!("{}{}: ", prompt, format!(" [{}]", default).style(Style::Dim));
+    read_validated_from(&mut io::stdin().lock(), &mut io::stdout(), &prompt, Some(default), |_| Ok(()))
+}
+
+// The retry loop of `read_input_validated` and `read_input_with_default`, over any input and output
+#[cfg(all(feature = "std", feature = "format"))]
+fn read_validated_from<T, F, R, W>(input: &mut R, output: &mut W, prompt: &str, mut default: Option<T>, validate: F) -> Result<T, String>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+    F: Fn(&T) -> Result<(), String>,
+    R: io::BufRead,
+    W: Write,
+{
+    use crate::format::{Stylize, RED};
+
+    loop {
+        write!(output, "{}", prompt).and_then(|_| output.flush()).map_err(|e| e.to_string())?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            return Err("stdin was closed before a valid value was entered".to_string());
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if let Some(default) = default.take() {return Ok(default);}
+        }
+
+        match trimmed.parse::<T>().map_err(|e| e.to_string()).and_then(|value| validate(&value).map(|_| value)) {
+            Ok(value) => return Ok(value),
+            Err(message) => writeln!(output, "{}", format!("✗ {}", message).color(RED)).map_err(|e| e.to_string())?,
+        }
+    }
+}
+
 /// Delays the program execution for the specified number of milliseconds.
+#[cfg(feature = "std")]
 pub fn __delay_ms(ms: u64) {std::thread::sleep(std::time::Duration::from_millis(ms));}
 
 /// Module containing helper functions for the print_app_data macro
+#[cfg(feature = "file")]
 pub mod helpers {
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use std::fs;
     use std::io;
     use std::env;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
+    use std::fmt;
 
+    use crate::file::toml::{self, TomlError, TomlValue};
     use crate::format::{Color, Style, Stylize};
+    use crate::vcs::{self, GitInfo};
 
-    /// Finds the Cargo.toml file by traversing up the directory tree.
+    /// Finds the Cargo.toml file of the package by traversing up the directory tree.
+    ///
+    /// The parents of `start_path` are searched first, then the current directory and its parents.
+    /// Manifests without a `[package]` table (virtual workspace roots) are skipped.
     pub fn find_cargo_toml(start_path: &str) -> io::Result<PathBuf> {
-        let mut path = PathBuf::from(start_path);
-        loop {
-            path = match path.parent() {
-                Some(parent) => parent.to_path_buf(),
-                None => env::current_dir()?,
-            };
-
-            let cargo_path = path.join("Cargo.toml");
-            if cargo_path.exists() {
-                return Ok(cargo_path);
-            }
-
-            if path.as_os_str().is_empty() {
-                return Err(io::Error::new(io::ErrorKind::NotFound, "Cargo.toml not found in any parent directory"));
-            }
-        }
+        let current_dir = env::current_dir()?;
+        Path::new(start_path).ancestors().skip(1)
+            .chain(current_dir.ancestors())
+            .map(|dir| dir.join("Cargo.toml"))
+            .find(|path| fs::read_to_string(path).ok()
+                .and_then(|content| toml::parse(&content).ok())
+                .is_some_and(|manifest| manifest.contains_key("package")))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Cargo.toml not found in any parent directory"))
     }
 
-
+    /// Extracts the selected keys of some sections (tables) of a Cargo.toml.
+    ///
+    /// Sections can be nested tables (`"package.metadata"`); arrays are joined with `, `
+    /// and missing sections or keys are left out.
     pub fn extract_app_data_with_sections<'a>(
-        data: &'a str,
-        sections: &[(&str, &[&str])]
-    ) -> HashMap<&'a str, HashMap<&'a str, String>> {
-        let mut app_data = HashMap::new();
-        let mut current_section = "";
-        let mut current_key = "";
-        let mut multi_line_value = String::new();
-    
-        for line in data.lines() {
-            let trimmed_line = line.trim();
-            
-            // Skip empty lines and full-line comments
-            if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
-                continue;
-            }
-    
-            // Remove inline comments
-            let line_without_comment = trimmed_line.split('#').next().unwrap().trim();
-    
-            if line_without_comment.starts_with('[') && line_without_comment.ends_with(']') {
-                current_section = line_without_comment.trim_matches(&['[', ']'][..]);
-            } else if let Some((key, value)) = line_without_comment.split_once('=') {
-                let key = key.trim();
-                if sections.iter().any(|&(s, keys)| s == current_section && keys.contains(&key)) {
-                    let value = value.trim().trim_matches('"');
-                    current_key = key;
-                    if value.starts_with('[') && !value.ends_with(']') {
-                        multi_line_value = value.to_string();
-                    } else {
-                        app_data.entry(current_section)
-                            .or_insert_with(HashMap::new)
-                            .insert(key, value.to_string());
-                    }
-                }
-            } else if !line_without_comment.is_empty() && !multi_line_value.is_empty() {
-                multi_line_value.push_str(line_without_comment);
-                if line_without_comment.ends_with(']') {
-                    app_data.entry(current_section)
-                        .or_insert_with(HashMap::new)
-                        .insert(current_key, multi_line_value.trim_matches(&['[', ']'][..]).to_string());
-                    multi_line_value.clear();
+        data: &str,
+        sections: &[(&'a str, &[&'a str])]
+    ) -> Result<HashMap<&'a str, HashMap<&'a str, String>>, TomlError> {
+        let manifest = TomlValue::Table(toml::parse(data)?);
+        let mut app_data: HashMap<&str, HashMap<&str, String>> = HashMap::new();
+
+        for &(section, keys) in sections {
+            let Some(table) = manifest.get_path(section) else {continue};
+            for &key in keys {
+                if let Some(value) = table.get(key) {
+                    app_data.entry(section).or_default().insert(key, display_value(value));
                 }
             }
         }
-    
-        app_data
+        Ok(app_data)
+    }
+
+    // Formats a value for display: strings without quotes, arrays as comma-separated lists
+    fn display_value(value: &TomlValue) -> String {
+        match value {
+            TomlValue::String(s) => s.clone(),
+            TomlValue::Array(items) => items.iter().map(display_value).collect::<Vec<_>>().join(", "),
+            other => other.to_string(),
+        }
     }
 
     pub fn print_extracted_data(app_data: &HashMap<&str, HashMap<&str, String>>, skip_keys: &[&str]) {
@@ -170,46 +324,471 @@ pub mod helpers {
             println!();
         }
     }
-}
 
-#[macro_export]
-macro_rules! app_dt {
-    ($file_path:expr $(, $($section:expr => [$($key:expr),+ $(,)?]),* $(,)?)?) => {{
-        use std::io::Write;
-        use $crate::format::*;
-        use $crate::helpers::{find_cargo_toml, extract_app_data_with_sections, print_extracted_data};
-
-        // Clear the terminal screen
-        print!("\x1B[2J\x1B[1;1H");
-        let _ = std::io::stdout().flush();
-
-        // Find and read Cargo.toml
-        let cargo_toml_path = find_cargo_toml($file_path).expect("Failed to find Cargo.toml");
-        let cargo_toml = std::fs::read_to_string(cargo_toml_path).expect("Failed to read Cargo.toml");
-
-        // Extract all data in a single call
-        let all_data = extract_app_data_with_sections(&cargo_toml, &[
-            ("package", &["name", "version"]),
-            $( $(($section, &[$($key),+])),* )?
-        ]);
+    /// A crate of a cargo workspace.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct WorkspaceMember {
+        pub name: String,
+        pub version: String,
+        /// The directory of the crate.
+        pub path: PathBuf,
+    }
+
+    /// Finds the Cargo.toml of the workspace root by traversing up the directory tree.
+    ///
+    /// Unlike [find_cargo_toml], which returns the manifest of the nearest package (often a
+    /// member), this returns the nearest manifest with a `[workspace]` table.
+    pub fn find_workspace_root(start_path: &str) -> io::Result<PathBuf> {
+        let current_dir = env::current_dir()?;
+        Path::new(start_path).ancestors().skip(1)
+            .chain(current_dir.ancestors())
+            .map(|dir| dir.join("Cargo.toml"))
+            .find(|path| fs::read_to_string(path).ok()
+                .and_then(|content| toml::parse(&content).ok())
+                .is_some_and(|manifest| manifest.contains_key("workspace")))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No workspace Cargo.toml found in any parent directory"))
+    }
 
-        let package_data = all_data.get("package").expect("Failed to extract package data");
+    /// Lists the member crates of a workspace, expanding glob patterns (`crates/*`).
+    ///
+    /// # Arguments
+    ///
+    /// * `root_manifest` - The Cargo.toml of the workspace root (see [find_workspace_root])
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the members sorted by path, or an `io::Error` if a manifest
+    /// cannot be read or parsed. Versions inherited with `version.workspace = true` are resolved.
+    pub fn workspace_members(root_manifest: &Path) -> io::Result<Vec<WorkspaceMember>> {
+        let invalid = |e: TomlError| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+        let root = TomlValue::Table(toml::parse(&fs::read_to_string(root_manifest)?).map_err(invalid)?);
+        let root_dir = root_manifest.parent().unwrap_or(Path::new("."));
+        let inherited_version = root.get_path("workspace.package.version").and_then(TomlValue::as_str).unwrap_or("");
+        let excluded = root.get_path("workspace.exclude").and_then(TomlValue::as_array).unwrap_or(&[])
+            .iter().filter_map(TomlValue::as_str).map(|dir| root_dir.join(dir)).collect::<Vec<_>>();
+
+        let mut members = Vec::new();
+        for pattern in root.get_path("workspace.members").and_then(TomlValue::as_array).unwrap_or(&[]).iter().filter_map(TomlValue::as_str) {
+            let dirs = crate::file::glob(&root_dir.join(pattern).to_string_lossy())
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            for dir in dirs.into_iter().filter(|dir| !excluded.contains(dir)) {
+                let Ok(content) = fs::read_to_string(dir.join("Cargo.toml")) else {continue};
+                let manifest = TomlValue::Table(toml::parse(&content).map_err(invalid)?);
+                let Some(package) = manifest.get("package") else {continue};
+                let version = match package.get("version") {
+                    Some(TomlValue::String(version)) => version.as_str(),
+                    Some(TomlValue::Table(_)) => inherited_version,
+                    _ => "",
+                };
+                members.push(WorkspaceMember {
+                    name: package.get("name").and_then(TomlValue::as_str).unwrap_or_default().to_string(),
+                    version: version.to_string(),
+                    path: dir,
+                });
+            }
+        }
+        members.sort_by(|a, b| a.path.cmp(&b.path));
+        members.dedup();
+        Ok(members)
+    }
 
-        println!("{} v{}\n",
-            package_data.get("name").unwrap().color(Color::new(16, 192, 16)),
-            package_data.get("version").unwrap().color(Color::new(8, 64, 224)).style(Style::Italic),
-        );
+    /// Lists the dependencies of a section of a Cargo.toml (e.g. `dependencies` or `dev-dependencies`).
+    ///
+    /// Each dependency is paired with its version requirement or, when there is none, its
+    /// source (`path ../x`, `git <url>` or `workspace`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::helpers::list_dependencies;
+    ///
+    /// let manifest = "[dependencies]\nlog = \"0.4\"\nutils = { path = \"../utils\" }";
+    /// assert_eq!(list_dependencies(manifest, "dependencies").unwrap(), vec![
+    ///     ("log".to_string(), "0.4".to_string()),
+    ///     ("utils".to_string(), "path ../utils".to_string()),
+    /// ]);
+    /// ```
+    pub fn list_dependencies(data: &str, section: &str) -> Result<Vec<(String, String)>, TomlError> {
+        let manifest = TomlValue::Table(toml::parse(data)?);
+        let Some(table) = manifest.get_path(section).and_then(TomlValue::as_table) else {return Ok(Vec::new())};
+        Ok(table.iter().map(|(name, spec)| (name.clone(), dependency_requirement(spec))).collect())
+    }
 
-        // Only print additional data if any optional fields were provided
-        if all_data.len() > 1 || all_data.get("package").map_or(false, |p| p.len() > 2) {
-            print_extracted_data(&all_data, &["name", "version"]);
+    // Describes where a dependency comes from: its version, path, git repository or the workspace
+    fn dependency_requirement(spec: &TomlValue) -> String {
+        let field = |key| spec.get(key).and_then(TomlValue::as_str);
+        match spec {
+            TomlValue::String(version) => version.clone(),
+            _ => match (field("version"), field("path"), field("git")) {
+                (Some(version), _, _) => version.to_string(),
+                (_, Some(path), _) => format!("path {}", path),
+                (_, _, Some(git)) => format!("git {}", git),
+                _ if spec.get("workspace").and_then(TomlValue::as_bool) == Some(true) => "workspace".to_string(),
+                _ => "*".to_string(),
+            },
         }
+    }
+
+    /// Options of the [app_dt!](crate::app_dt) banner.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct AppDtOptions {
+        /// Clear the screen before printing (on by default).
+        pub clear: bool,
+        /// List the members of the enclosing workspace.
+        pub workspace: bool,
+        /// List the `[dependencies]` of the package with their versions.
+        pub dependencies: bool,
+        /// Show the git commit (with a `-dirty` suffix if there are uncommitted changes) and branch.
+        pub git: bool,
+        /// Show the build profile (`debug` or `release`).
+        pub profile: bool,
+    }
+
+    impl Default for AppDtOptions {
+        fn default() -> Self {
+            AppDtOptions { clear: true, workspace: false, dependencies: false, git: false, profile: false }
+        }
+    }
+
+    /// The package data read from a Cargo.toml by [app_info].
+    ///
+    /// Its `Display` implementation renders the same banner as [app_dt!](crate::app_dt)
+    /// (without clearing the screen), so it can be logged or written to any writer.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct AppInfo {
+        pub name: String,
+        pub version: String,
+        pub authors: Vec<String>,
+        pub description: Option<String>,
+        /// The extra keys selected with [app_info_with_sections], by section.
+        pub sections: BTreeMap<String, BTreeMap<String, String>>,
+        /// The `[dependencies]` of the package (see [list_dependencies]).
+        pub dependencies: Vec<(String, String)>,
+        /// The members of the enclosing workspace, if any.
+        pub workspace_members: Vec<WorkspaceMember>,
+        /// The directory of the enclosing workspace, if any.
+        pub workspace_root: Option<PathBuf>,
+        /// The state of the enclosing git repository, if any.
+        pub git: Option<GitInfo>,
+        /// The build profile (see [build_profile](crate::vcs::build_profile)).
+        pub profile: String,
+    }
+
+    /// Represents errors that can occur when reading the package data of [app_info].
+    #[derive(Debug)]
+    pub enum AppDataError {
+        /// No Cargo.toml was found (e.g. for a binary installed with `cargo install`).
+        NotFound,
+        /// A manifest could not be read.
+        Io(io::Error),
+        /// The Cargo.toml is not valid TOML.
+        Parse(TomlError),
+        /// The Cargo.toml has no `[package]` table.
+        MissingPackage(PathBuf),
+    }
+
+    impl fmt::Display for AppDataError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                AppDataError::NotFound => write!(f, "Cargo.toml not found in any parent directory"),
+                AppDataError::Io(err) => write!(f, "Failed to read Cargo.toml: {}", err),
+                AppDataError::Parse(err) => write!(f, "Failed to parse Cargo.toml: {}", err),
+                AppDataError::MissingPackage(path) => write!(f, "{} has no [package] table", path.display()),
+            }
+        }
+    }
+
+    impl std::error::Error for AppDataError {}
+
+    impl From<io::Error> for AppDataError {
+        fn from(err: io::Error) -> Self {AppDataError::Io(err)}
+    }
+
+    impl From<TomlError> for AppDataError {
+        fn from(err: TomlError) -> Self {AppDataError::Parse(err)}
+    }
+
+    /// Reads the package data of the calling crate from its Cargo.toml.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - A path inside the package, usually `file!()`
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the [AppInfo], or an [AppDataError] if the Cargo.toml
+    /// cannot be found, read or parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::app_info;
+    ///
+    /// let info = app_info(file!()).unwrap();
+    /// let version_flag = format!("{} {}", info.name, info.version);
+    /// assert_eq!(version_flag, concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")));
+    /// ```
+    pub fn app_info(file_path: &str) -> Result<AppInfo, AppDataError> {app_info_with_sections(file_path, &[])}
+
+    /// Reads the package data like [app_info], plus the selected keys of some sections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::helpers::app_info_with_sections;
+    ///
+    /// let info = app_info_with_sections(file!(), &[("package", &["license"])]).unwrap();
+    /// assert_eq!(info.sections["package"]["license"], "MIT");
+    /// ```
+    pub fn app_info_with_sections(file_path: &str, sections: &[(&str, &[&str])]) -> Result<AppInfo, AppDataError> {
+        let cargo_toml_path = find_cargo_toml(file_path).map_err(|_| AppDataError::NotFound)?;
+        let cargo_toml = fs::read_to_string(&cargo_toml_path)?;
+        let manifest = TomlValue::Table(toml::parse(&cargo_toml)?);
+        let package = manifest.get("package").ok_or(AppDataError::MissingPackage(cargo_toml_path))?;
+        let field = |key| package.get(key).map(display_value);
+
+        let workspace_root = find_workspace_root(file_path).ok();
+        Ok(AppInfo {
+            name: field("name").unwrap_or_default(),
+            version: field("version").unwrap_or_default(),
+            authors: package.get("authors").and_then(TomlValue::as_array).unwrap_or(&[])
+                .iter().map(display_value).collect(),
+            description: field("description"),
+            sections: extract_app_data_with_sections(&cargo_toml, sections)?.into_iter()
+                .map(|(section, keys)| (section.to_string(), keys.into_iter().map(|(k, v)| (k.to_string(), v)).collect()))
+                .collect(),
+            dependencies: list_dependencies(&cargo_toml, "dependencies")?,
+            workspace_members: workspace_root.as_deref().map(workspace_members).transpose()?.unwrap_or_default(),
+            workspace_root: workspace_root.and_then(|root| root.parent().map(Path::to_path_buf)),
+            git: vcs::git_info(file_path),
+            profile: vcs::build_profile().to_string(),
+        })
+    }
+
+    impl AppInfo {
+        /// Creates the package data from the values cargo sets at compile time
+        /// (`CARGO_PKG_NAME`, `CARGO_PKG_VERSION`, ...), for when no Cargo.toml is available.
+        ///
+        /// `authors` is the colon-separated list of `CARGO_PKG_AUTHORS`. The git state is read
+        /// from the repository containing `file_path`, if any.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use dev_utils::AppInfo;
+        ///
+        /// let info = AppInfo::compiled(file!(), env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), "Ann:Bob", "");
+        /// assert_eq!(info.authors, ["Ann", "Bob"]);
+        /// assert_eq!(info.description, None);
+        /// ```
+        pub fn compiled(file_path: &str, name: &str, version: &str, authors: &str, description: &str) -> Self {
+            AppInfo {
+                name: name.to_string(),
+                version: version.to_string(),
+                authors: authors.split(':').filter(|a| !a.is_empty()).map(String::from).collect(),
+                description: Some(description.to_string()).filter(|d| !d.is_empty()),
+                git: vcs::git_info(file_path),
+                profile: vcs::build_profile().to_string(),
+                ..Default::default()
+            }
+        }
+
+        /// Adds the sections embedded by [extract_app_data!](crate::extract_app_data).
+        pub fn with_sections(mut self, sections: &[(&str, &[(&str, &str)])]) -> Self {
+            for (section, keys) in sections {
+                self.sections.entry(section.to_string()).or_default()
+                    .extend(keys.iter().map(|(key, value)| (key.to_string(), value.to_string())));
+            }
+            self
+        }
+
+        /// Adds the dependencies embedded by [extract_app_data!](crate::extract_app_data)
+        /// (`"dependencies" => *`), described like in [list_dependencies].
+        pub fn with_dependencies(mut self, dependencies: &[(&str, &str)]) -> Self {
+            self.dependencies = dependencies.iter().map(|(name, spec)| {
+                // tables are embedded as inline TOML, versions as plain strings
+                let requirement = match spec.starts_with('{') {
+                    true => toml::parse(&format!("spec = {}", spec)).ok().and_then(|table| table.get("spec").map(dependency_requirement)),
+                    false => None,
+                };
+                (name.to_string(), requirement.unwrap_or_else(|| spec.to_string()))
+            }).collect();
+            self
+        }
+
+        /// Adds the members of the workspace enclosing `file_path`, found at runtime.
+        pub fn with_workspace(mut self, file_path: &str) -> Self {
+            let root = find_workspace_root(file_path).ok();
+            self.workspace_members = root.as_deref().and_then(|root| workspace_members(root).ok()).unwrap_or_default();
+            self.workspace_root = root.and_then(|root| root.parent().map(Path::to_path_buf));
+            self
+        }
+
+        /// Renders the banner, including the workspace members and dependencies if enabled in `options`.
+        pub fn banner(&self, options: &AppDtOptions) -> String {
+            let mut out = format!("{} v{}",
+                self.name.color(Color::new(16, 192, 16)),
+                self.version.color(Color::new(8, 64, 224)).style(Style::Italic),
+            );
+            // build metadata: (abc1234-dirty, main, debug)
+            let mut build = Vec::new();
+            if let (true, Some(git)) = (options.git, &self.git) {
+                build.push(format!("{}{}", git.short_commit(), if git.dirty == Some(true) {"-dirty"} else {""}));
+                build.extend(git.branch.clone());
+            }
+            if options.profile {build.push(self.profile.clone());}
+            if !build.is_empty() {out += &format!(" {}", format!("({})", build.join(", ")).style(Style::Dim));}
+            out += "\n\n";
+
+            for (section, data) in &self.sections {
+                out += &format!("{}:\n", section.style(Style::Bold));
+                for (key, value) in data {
+                    out += &format!("\t{key}: {}\n", value.style(Style::Italic).style(Style::Dim));
+                }
+                out += "\n";
+            }
+
+            if let (true, Some(root)) = (options.workspace, &self.workspace_root) {
+                out += &format!("{} {}\n", "workspace:".style(Style::Bold), root.display().to_string().style(Style::Dim));
+                for member in &self.workspace_members {
+                    let path = member.path.strip_prefix(root).unwrap_or(&member.path);
+                    out += &format!("\t{} v{} {}\n", member.name, member.version.style(Style::Italic), path.display().to_string().style(Style::Dim));
+                }
+                out += "\n";
+            }
+
+            if options.dependencies {
+                out += &format!("{}\n", "dependencies:".style(Style::Bold));
+                for (name, requirement) in &self.dependencies {
+                    out += &format!("\t{name}: {}\n", requirement.style(Style::Italic).style(Style::Dim));
+                }
+                out += "\n";
+            }
+            out
+        }
+    }
+
+    impl fmt::Display for AppInfo {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.banner(&AppDtOptions::default()))
+        }
+    }
+
+    /// Prints the banner of [try_app_dt!](crate::try_app_dt) (see [AppInfo::banner]), clearing
+    /// the screen first if enabled in `options`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the printed [AppInfo], or an [AppDataError] (nothing is printed).
+    pub fn try_print_app_data(file_path: &str, sections: &[(&str, &[&str])], options: &AppDtOptions) -> Result<AppInfo, AppDataError> {
+        let info = app_info_with_sections(file_path, sections)?;
+        print_app_data(&info, options);
+        Ok(info)
+    }
+
+    /// Prints the banner of [app_dt!](crate::app_dt) (see [AppInfo::banner]), clearing the
+    /// screen first if enabled in `options`.
+    pub fn print_app_data(info: &AppInfo, options: &AppDtOptions) {
+        if options.clear {let _ = crate::console::term::clear_screen();}
+        print!("{}", info.banner(options));
+    }
+}
+
+/// Prints the package name and version from the Cargo.toml of the calling crate, after
+/// clearing the screen.
+///
+/// The data is embedded at compile time (see [extract_app_data!]), so the banner also works
+/// for binaries installed with `cargo install`; only `workspace = true` searches the file
+/// system at runtime.
+///
+/// Extra data can be selected with `"section" => ["key", ...]` pairs, and the options of
+/// [AppDtOptions](helpers::AppDtOptions) can be set with `option = value` pairs:
+/// - `clear = false` keeps the screen content
+/// - `workspace = true` lists the members of the enclosing workspace
+/// - `dependencies = true` lists the dependencies of the package with their versions
+/// - `git = true` shows the git commit, dirty state and branch: `app v0.3.1 (abc1234-dirty, main)`
+/// - `profile = true` shows the build profile: `app v0.3.1 (debug)`
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::app_dt;
+///
+/// app_dt!(file!(), "package" => ["license"], workspace = true, dependencies = true);
+/// app_dt!(file!(), clear = false, git = true, profile = true);
+/// ```
+///
+/// To read the Cargo.toml at runtime instead, use [try_app_dt!]; to log the data or write it
+/// elsewhere, use [app_info].
+#[cfg(feature = "file")]
+#[macro_export]
+macro_rules! app_dt {
+    ($file_path:expr $(, $($args:tt)*)?) => {{
+        #[allow(unused_mut)]
+        let mut options = $crate::helpers::AppDtOptions::default();
+        #[allow(unused_mut)]
+        let mut sections: Vec<(&str, &[&str])> = Vec::new();
+        $crate::__app_dt_args!(options, sections; $($($args)*)?);
+        let dependencies = $crate::extract_app_data!("dependencies" => *);
+        let mut info = $crate::helpers::AppInfo::compiled(
+            $file_path,
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            env!("CARGO_PKG_AUTHORS"),
+            env!("CARGO_PKG_DESCRIPTION"),
+        )
+            .with_sections($crate::extract_app_data!($($($args)*)?))
+            .with_dependencies(dependencies.first().map_or(&[], |(_, dependencies)| dependencies));
+        if options.workspace {info = info.with_workspace($file_path);}
+        $crate::helpers::print_app_data(&info, &options);
     }};
 }
 
+/// Like [app_dt!], but reads the Cargo.toml at runtime (see [app_info]) and returns the
+/// printed data as a `Result<AppInfo, AppDataError>`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::try_app_dt;
+///
+/// match try_app_dt!(file!(), clear = false) {
+///     Ok(info) => assert_eq!(info.name, env!("CARGO_PKG_NAME")),
+///     Err(err) => eprintln!("{}", err),
+/// }
+/// ```
+#[cfg(feature = "file")]
+#[macro_export]
+macro_rules! try_app_dt {
+    ($file_path:expr $(, $($args:tt)*)?) => {{
+        #[allow(unused_mut)]
+        let mut options = $crate::helpers::AppDtOptions::default();
+        #[allow(unused_mut)]
+        let mut sections: Vec<(&str, &[&str])> = Vec::new();
+        $crate::__app_dt_args!(options, sections; $($($args)*)?);
+        $crate::helpers::try_print_app_data($file_path, &sections, &options)
+    }};
+}
+
+// Parses the arguments of `app_dt!`: `option = value` and `"section" => [keys]` pairs, in any order
+#[doc(hidden)]
+#[cfg(feature = "file")]
+#[macro_export]
+macro_rules! __app_dt_args {
+    ($options:ident, $sections:ident; $(,)?) => {};
+    ($options:ident, $sections:ident; $option:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $options.$option = $value;
+        $crate::__app_dt_args!($options, $sections; $($($rest)*)?);
+    };
+    ($options:ident, $sections:ident; $section:expr => [$($key:expr),+ $(,)?] $(, $($rest:tt)*)?) => {
+        $sections.extend([($section, &[$($key),+][..])]);
+        $crate::__app_dt_args!($options, $sections; $($($rest)*)?);
+    };
+}
+
 
 // Example usage and testing
-#[cfg(test)]
+#[cfg(all(test, feature = "file"))]
 mod tests {
     use super::*;
 
@@ -217,4 +796,106 @@ mod tests {
     fn some_useful_test() {
         app_dt!(file!());  // Print package name and version from Cargo.toml
     }
+
+    #[test]
+    fn test_workspace_members() {
+        let root = helpers::find_workspace_root(file!()).unwrap();
+        assert!(file::read(&root).unwrap().contains("[workspace]"));
+        let members = helpers::workspace_members(&root).unwrap();
+        let member = members.iter().find(|m| m.name == "dev_utils").unwrap();
+        assert_eq!(member.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(member.path, root.parent().unwrap().join("dev_utils"));
+
+        app_dt!(file!(), "package" => ["license"], workspace = true, dependencies = true);
+    }
+
+    #[test]
+    fn test_app_info() {
+        let info = helpers::app_info_with_sections(file!(), &[("package", &["license", "keywords"])]).unwrap();
+        assert_eq!(info.name, "dev_utils");
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.authors, vec![env!("CARGO_PKG_AUTHORS").to_string()]);
+        assert_eq!(info.sections["package"]["license"], "MIT");
+        assert!(info.dependencies.iter().any(|(name, _)| name == "log"));
+
+        let banner = format::strip_ansi_codes(&info.to_string());
+        assert!(banner.starts_with("dev_utils v"));
+        assert!(banner.contains("package:\n\tkeywords: development, utils"));
+        assert!(!banner.contains("dependencies:"));
+        assert_eq!(try_app_dt!(file!(), clear = false).unwrap().name, "dev_utils");
+
+        let options = helpers::AppDtOptions { profile: true, git: true, ..Default::default() };
+        let info = helpers::AppInfo { git: None, ..info };
+        assert!(format::strip_ansi_codes(&info.banner(&options)).starts_with(&format!("dev_utils v{} ({})\n", info.version, vcs::build_profile())));
+    }
+
+    #[test]
+    fn test_app_data_errors() {
+        let err = helpers::AppDataError::from(file::toml::parse("[workspace]\nmembers = [").unwrap_err());
+        assert!(err.to_string().starts_with("Failed to parse Cargo.toml: "));
+        assert_eq!(helpers::AppDataError::NotFound.to_string(), "Cargo.toml not found in any parent directory");
+
+        let info = AppInfo::compiled(file!(), "app", "1.0.0", "", "A tool");
+        assert!(info.authors.is_empty());
+        assert!(format::strip_ansi_codes(&info.to_string()).starts_with("app v1.0.0\n"));
+    }
+
+    #[test]
+    fn test_list_dependencies() {
+        let manifest = "
+            [dependencies]
+            serde = { version = \"1\", features = [\"derive\"] }
+            shared = { workspace = true }
+            remote = { git = \"https://example.com/remote\" }
+            [dev-dependencies]
+            tempfile = \"3.8\"
+        ";
+        assert_eq!(helpers::list_dependencies(manifest, "dependencies").unwrap(), vec![
+            ("remote".to_string(), "git https://example.com/remote".to_string()),
+            ("serde".to_string(), "1".to_string()),
+            ("shared".to_string(), "workspace".to_string()),
+        ]);
+        assert_eq!(helpers::list_dependencies(manifest, "dev-dependencies").unwrap().len(), 1);
+        assert!(helpers::list_dependencies(manifest, "build-dependencies").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_embedded_app_data() {
+        let data = extract_app_data!("package" => ["license", "missing"], "no.such.section" => *, "dependencies" => *);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0], ("package", &[("license", "MIT")][..]));
+
+        let info = AppInfo::default()
+            .with_sections(&data[..1])
+            .with_dependencies(data[1].1)
+            .with_dependencies(&[("log", "0.4"), ("local", "{ path = \"../local\", optional = true }")]);
+        assert_eq!(info.sections["package"]["license"], "MIT");
+        assert_eq!(info.dependencies, vec![
+            ("log".to_string(), "0.4".to_string()),
+            ("local".to_string(), "path ../local".to_string()),
+        ]);
+        assert!(data[1].1.iter().any(|(name, spec)| *name == "dev_macros" && spec.contains("path")));
+    }
+
+    #[test]
+    fn test_read_validated() {
+        let mut input = io::Cursor::new("abc\n7\n42\n");
+        let mut output = Vec::new();
+        let even = |n: &i32| if n % 2 == 0 {Ok(())} else {Err(format!("{} is odd", n))};
+        assert_eq!(read_validated_from(&mut input, &mut output, "n: ", None, even), Ok(42));
+
+        let output = format::strip_ansi_codes(&String::from_utf8(output).unwrap());
+        assert_eq!(output.matches("n: ").count(), 3);
+        assert!(output.contains("✗ invalid digit found in string"));
+        assert!(output.contains("✗ 7 is odd"));
+        assert!(read_validated_from::<i32, _, _, _>(&mut io::Cursor::new(""), &mut Vec::new(), "n: ", None, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_read_with_default() {
+        let read = |text: &str| read_validated_from(&mut io::Cursor::new(text.to_string()), &mut Vec::new(), "n", Some(5u8), |_| Ok(()));
+        assert_eq!(read("\n"), Ok(5));
+        assert_eq!(read("300\n9\n"), Ok(9));
+        assert_eq!(read("300\n\n"), Ok(5));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,140 @@
+//! Numerical analysis: root finding, numerical integration, linear algebra and interpolation.
+//!
+//! The iterative methods take [Options] (tolerance and iteration limit) and return a [Solution]
+//! with the trace of their [Iteration]s, which can be logged with [Solution::log].
+//!
+//! # Modules
+//! - [root] - Bisection, Newton-Raphson and secant root finding
+//! - [integrate] - Trapezoid, Simpson and adaptive Simpson integration
+//! - [matrix] - Dense matrices, LU decomposition and linear systems
+//! - [interp] - Lagrange and cubic spline interpolation, least-squares polynomial fitting
+//!
+//! # Examples
+//! ```
+//! use dev_utils::num::{integrate, root, Options};
+//!
+//! // the root of x² - 2 is √2
+//! let sqrt2 = root::newton(|x| x * x - 2.0, |x| 2.0 * x, 1.0, Options::default()).unwrap();
+//! assert!((sqrt2.value - 2f64.sqrt()).abs() < 1e-10);
+//!
+//! // ∫₀^π sin(x) dx = 2
+//! let area = integrate::simpson(f64::sin, 0.0, std::f64::consts::PI, Options::default()).unwrap();
+//! assert!((area.value - 2.0).abs() < 1e-9);
+//! ```
+use std::error::Error;
+use std::fmt;
+use crate::dlog::{self, Level};
+
+pub mod integrate;
+pub mod interp;
+pub mod matrix;
+pub mod root;
+
+/// The stopping criteria of an iterative method.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    /// The method stops once the error estimate is at most this value.
+    pub tolerance: f64,
+    /// The method fails with [NumError::NoConvergence] after this many iterations.
+    pub max_iterations: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {Options { tolerance: 1e-10, max_iterations: 100 }}
+}
+
+impl Options {
+    /// Creates options with the given tolerance and the default iteration limit.
+    pub fn tolerance(tolerance: f64) -> Self {Options { tolerance, ..Self::default() }}
+
+    /// Sets the iteration limit.
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+}
+
+/// One step of an iterative method.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Iteration {
+    /// The number of the step, from 1.
+    pub step: usize,
+    /// The estimate after this step (a root, or the value of an integral).
+    pub value: f64,
+    /// The error estimate after this step.
+    pub error: f64,
+}
+
+impl fmt::Display for Iteration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {:>3}: value = {:<22} error = {:e}", self.step, self.value, self.error)
+    }
+}
+
+/// The result of an iterative method, with the trace of its iterations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    /// The final estimate.
+    pub value: f64,
+    /// The steps taken, in order.
+    pub iterations: Vec<Iteration>,
+}
+
+impl Solution {
+    /// Returns the error estimate of the final step.
+    pub fn error(&self) -> f64 {self.iterations.last().map_or(0.0, |it| it.error)}
+
+    /// Logs every iteration with [dlog], prefixed by `label`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::dlog::Level;
+    /// use dev_utils::num::{root, Options};
+    ///
+    /// let root = root::bisection(|x| x * x * x - x - 2.0, 1.0, 2.0, Options::tolerance(1e-3)).unwrap();
+    /// root.log(Level::Debug, "bisection");  // "bisection step   1: value = 1.5 ..."
+    /// ```
+    pub fn log(&self, level: Level, label: &str) {
+        for iteration in &self.iterations {
+            dlog::emit(level, format_args!("{} {}", label, iteration));
+        }
+    }
+
+    // Adds a step and returns `true` if its error is within the tolerance
+    fn push(&mut self, value: f64, error: f64, options: &Options) -> bool {
+        self.value = value;
+        self.iterations.push(Iteration { step: self.iterations.len() + 1, value, error });
+        error <= options.tolerance
+    }
+}
+
+/// Represents errors that can occur in the numerical methods.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumError {
+    /// The function has the same sign at both ends of the bracket.
+    NoSignChange { a: f64, b: f64 },
+    /// The derivative (or the secant slope) is zero at `x`.
+    ZeroDerivative { x: f64 },
+    /// The function returned NaN or an infinity at `x`.
+    NotFinite { x: f64 },
+    /// The method did not reach the tolerance; holds the last estimate and its trace.
+    NoConvergence(Solution),
+}
+
+impl fmt::Display for NumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumError::NoSignChange { a, b } => write!(f, "the function has the same sign at {} and {}", a, b),
+            NumError::ZeroDerivative { x } => write!(f, "zero derivative at {}", x),
+            NumError::NotFinite { x } => write!(f, "the function is not finite at {}", x),
+            NumError::NoConvergence(solution) => write!(f,
+                "no convergence after {} iterations (last value {}, error {:e})",
+                solution.iterations.len(), solution.value, solution.error()),
+        }
+    }
+}
+
+impl Error for NumError {}
+
+type Result<T> = std::result::Result<T, NumError>;
@@ -0,0 +1,212 @@
+//! Numerical integration of `f` over `[a, b]`.
+//!
+//! - [trapezoid] - The composite trapezoid rule, doubling the number of intervals each step
+//! - [simpson] - The composite Simpson rule, from the same refinements
+//! - [adaptive] - Adaptive Simpson, refining only where the function needs it
+//!
+//! # Examples
+//! ```
+//! use dev_utils::num::{integrate, Options};
+//!
+//! let f = |x: f64| x * x;
+//! let trapezoid = integrate::trapezoid(f, 0.0, 3.0, Options::tolerance(1e-6)).unwrap();
+//! let simpson = integrate::simpson(f, 0.0, 3.0, Options::tolerance(1e-6)).unwrap();
+//! assert!((trapezoid.value - 9.0).abs() < 1e-6);
+//! assert_eq!(simpson.value, 9.0);  // exact for polynomials up to degree 3
+//! ```
+use super::{NumError, Options, Result, Solution};
+
+// The trapezoid rule doubles the evaluations each step: stop before they get out of hand
+const MAX_REFINEMENTS: usize = 25;
+
+// Evaluates `f(x)`, failing on NaN and infinities
+fn eval(f: &impl Fn(f64) -> f64, x: f64) -> Result<f64> {
+    let y = f(x);
+    match y.is_finite() {
+        true => Ok(y),
+        false => Err(NumError::NotFinite { x }),
+    }
+}
+
+// The successive trapezoid estimates with 1, 2, 4, 8, ... intervals, each one reusing
+// the evaluations of the previous one
+struct Trapezoid<F> {
+    f: F,
+    a: f64,
+    b: f64,
+    intervals: usize,
+    value: f64,
+}
+
+impl<F: Fn(f64) -> f64> Trapezoid<F> {
+    fn new(f: F, a: f64, b: f64) -> Result<Self> {
+        let value = (b - a) / 2.0 * (eval(&f, a)? + eval(&f, b)?);
+        Ok(Trapezoid { f, a, b, intervals: 1, value })
+    }
+
+    fn refine(&mut self) -> Result<f64> {
+        let h = (self.b - self.a) / (2 * self.intervals) as f64;
+        let mut midpoints = 0.0;
+        for i in 0..self.intervals {
+            midpoints += eval(&self.f, self.a + (2 * i + 1) as f64 * h)?;
+        }
+        self.value = self.value / 2.0 + h * midpoints;
+        self.intervals *= 2;
+        Ok(self.value)
+    }
+}
+
+/// Integrates `f` over `[a, b]` with the composite trapezoid rule.
+///
+/// Each step doubles the number of intervals; its error is the change from the previous estimate.
+///
+/// # Arguments
+///
+/// * `f` - The function
+/// * `a`, `b` - The bounds of the integral
+/// * `options` - The tolerance and iteration limit (at most 25 refinements, 2²⁵ intervals)
+///
+/// # Returns
+///
+/// A `Result` containing the [Solution], or a [NumError] if it does not converge.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::num::{integrate, Options};
+///
+/// let ln2 = integrate::trapezoid(|x| 1.0 / x, 1.0, 2.0, Options::tolerance(1e-8)).unwrap();
+/// assert!((ln2.value - 2f64.ln()).abs() < 1e-8);
+/// ```
+pub fn trapezoid(f: impl Fn(f64) -> f64, a: f64, b: f64, options: Options) -> Result<Solution> {
+    let mut rule = Trapezoid::new(f, a, b)?;
+    let mut solution = Solution { value: rule.value, iterations: Vec::new() };
+    for _ in 0..options.max_iterations.min(MAX_REFINEMENTS) {
+        let previous = rule.value;
+        let value = rule.refine()?;
+        if solution.push(value, (value - previous).abs(), &options) {return Ok(solution);}
+    }
+    Err(NumError::NoConvergence(solution))
+}
+
+/// Integrates `f` over `[a, b]` with the composite Simpson rule.
+///
+/// Each step doubles the number of intervals; its error is the change from the previous estimate
+/// (or from the trapezoid estimate, for the first step).
+///
+/// # Returns
+///
+/// A `Result` containing the [Solution], or a [NumError] if it does not converge.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::num::{integrate, Options};
+///
+/// let e = integrate::simpson(f64::exp, 0.0, 1.0, Options::default()).unwrap();
+/// assert!((e.value - (std::f64::consts::E - 1.0)).abs() < 1e-10);
+/// ```
+pub fn simpson(f: impl Fn(f64) -> f64, a: f64, b: f64, options: Options) -> Result<Solution> {
+    let mut rule = Trapezoid::new(f, a, b)?;
+    let mut solution = Solution { value: rule.value, iterations: Vec::new() };
+    for _ in 0..options.max_iterations.min(MAX_REFINEMENTS) {
+        let (coarse, previous) = (rule.value, solution.value);
+        let fine = rule.refine()?;
+        // Simpson is the Richardson extrapolation of two trapezoid estimates
+        let value = (4.0 * fine - coarse) / 3.0;
+        if solution.push(value, (value - previous).abs(), &options) {return Ok(solution);}
+    }
+    Err(NumError::NoConvergence(solution))
+}
+
+/// Integrates `f` over `[a, b]` with adaptive Simpson quadrature.
+///
+/// Each step splits in two the intervals whose error is above their share of the tolerance,
+/// so the evaluations concentrate where `f` varies quickly. The error of a step is the sum of
+/// the error estimates of all the intervals.
+///
+/// # Returns
+///
+/// A `Result` containing the [Solution], or a [NumError] if some intervals are still above
+/// the tolerance after `options.max_iterations` steps.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::num::{integrate, Options};
+///
+/// // √x has an infinite slope at 0, where the fixed rules converge slowly
+/// let area = integrate::adaptive(f64::sqrt, 0.0, 1.0, Options::tolerance(1e-9)).unwrap();
+/// assert!((area.value - 2.0 / 3.0).abs() < 1e-9);
+/// ```
+pub fn adaptive(f: impl Fn(f64) -> f64, a: f64, b: f64, options: Options) -> Result<Solution> {
+    let panel = |a: f64, b: f64| -> Result<Panel> {
+        let m = (a + b) / 2.0;
+        let (fa, fm, fb) = (eval(&f, a)?, eval(&f, m)?, eval(&f, b)?);
+        Ok(Panel { a, b, fa, fm, fb, whole: (b - a) / 6.0 * (fa + 4.0 * fm + fb) })
+    };
+    let mut pending = vec![panel(a, b)?];
+    let (mut done, mut done_error) = (0.0, 0.0);
+    let mut solution = Solution { value: pending[0].whole, iterations: Vec::new() };
+    for _ in 0..options.max_iterations {
+        let (mut value, mut error) = (done, done_error);
+        let mut next = Vec::new();
+        for p in pending {
+            let m = (p.a + p.b) / 2.0;
+            let (fl, fr) = (eval(&f, (p.a + m) / 2.0)?, eval(&f, (m + p.b) / 2.0)?);
+            let left = Panel { a: p.a, b: m, fa: p.fa, fm: fl, fb: p.fm, whole: (m - p.a) / 6.0 * (p.fa + 4.0 * fl + p.fm) };
+            let right = Panel { a: m, b: p.b, fa: p.fm, fm: fr, fb: p.fb, whole: (p.b - m) / 6.0 * (p.fm + 4.0 * fr + p.fb) };
+            let delta = left.whole + right.whole - p.whole;
+            let (estimate, panel_error) = (left.whole + right.whole + delta / 15.0, delta.abs() / 15.0);
+            value += estimate;
+            error += panel_error;
+            match panel_error <= options.tolerance * (p.b - p.a) / (b - a).abs() {
+                true => {done += estimate; done_error += panel_error;},
+                false => next.extend([left, right]),
+            }
+        }
+        solution.push(value, error, &options);
+        if next.is_empty() {return Ok(solution);}
+        pending = next;
+    }
+    Err(NumError::NoConvergence(solution))
+}
+
+// An interval with the values of `f` at its ends and midpoint, and its Simpson estimate
+struct Panel {
+    a: f64,
+    b: f64,
+    fa: f64,
+    fm: f64,
+    fb: f64,
+    whole: f64,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_fixed_rules() {
+        let trapezoid = trapezoid(f64::sin, 0.0, PI, Options::tolerance(1e-8)).unwrap();
+        let simpson = simpson(f64::sin, 0.0, PI, Options::tolerance(1e-8)).unwrap();
+        assert!((trapezoid.value - 2.0).abs() < 1e-8);
+        assert!((simpson.value - 2.0).abs() < 1e-8);
+        assert!(simpson.iterations.len() < trapezoid.iterations.len());
+        // reversed bounds flip the sign
+        assert!((super::simpson(|x| x, 1.0, 0.0, Options::default()).unwrap().value + 0.5).abs() < 1e-12);
+        assert_eq!(super::trapezoid(|x| 1.0 / x, 0.0, 1.0, Options::default()), Err(NumError::NotFinite { x: 0.0 }));
+        assert!(matches!(super::trapezoid(f64::sin, 0.0, PI, Options::tolerance(1e-12).max_iterations(3)), Err(NumError::NoConvergence(_))));
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let f = |x: f64| (1.0 - x * x).sqrt();  // a quarter circle, steep near 1
+        let area = adaptive(f, 0.0, 1.0, Options::tolerance(1e-10)).unwrap();
+        assert!((area.value - PI / 4.0).abs() < 1e-9);
+        assert!(area.iterations.windows(2).all(|w| w[1].step == w[0].step + 1));
+        assert_eq!(adaptive(|x| 3.0 * x * x, 0.0, 2.0, Options::default()).unwrap().value, 8.0);
+    }
+}
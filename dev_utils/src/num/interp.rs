@@ -0,0 +1,324 @@
+//! Interpolation and curve fitting through sampled points.
+//!
+//! - [Lagrange] - The polynomial through every point (best for a few, evenly spread points)
+//! - [CubicSpline] - A smooth piecewise cubic through every point
+//! - [Polynomial::fit] - The least-squares polynomial of a given degree (for noisy data)
+//!
+//! Each one implements [Interpolant], to be evaluated at any `x`.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::num::interp::{CubicSpline, Interpolant, Lagrange, Polynomial};
+//!
+//! let (xs, ys) = ([0.0, 1.0, 2.0, 3.0], [1.0, 3.0, 7.0, 13.0]);  // x² + x + 1
+//! assert_eq!(Lagrange::new(&xs, &ys).unwrap().eval(4.0), 21.0);
+//! assert_eq!(CubicSpline::natural(&xs, &ys).unwrap().eval(1.0), 3.0);
+//!
+//! let fit = Polynomial::fit(&xs, &ys, 2).unwrap();
+//! assert_eq!(fit.to_string(), "1 + 1x + 1x^2");
+//! assert!((fit.eval(4.0) - 21.0).abs() < 1e-9);
+//! ```
+use std::error::Error;
+use std::fmt;
+use super::matrix::{Matrix, MatrixError};
+
+/// Represents errors that can occur when building an interpolant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpError {
+    /// `xs` and `ys` have different lengths.
+    LengthMismatch { xs: usize, ys: usize },
+    /// There are not enough points for the method.
+    TooFewPoints { needed: usize, found: usize },
+    /// Two points have the same `x`.
+    DuplicateX(f64),
+    /// The least-squares system could not be solved.
+    Matrix(MatrixError),
+}
+
+impl fmt::Display for InterpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpError::LengthMismatch { xs, ys } => write!(f, "got {} x values and {} y values", xs, ys),
+            InterpError::TooFewPoints { needed, found } => write!(f, "needs at least {} points, got {}", needed, found),
+            InterpError::DuplicateX(x) => write!(f, "duplicate x value: {}", x),
+            InterpError::Matrix(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for InterpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InterpError::Matrix(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<MatrixError> for InterpError {
+    fn from(err: MatrixError) -> Self {InterpError::Matrix(err)}
+}
+
+type Result<T> = std::result::Result<T, InterpError>;
+
+/// A function built from sampled points, which can be evaluated anywhere.
+pub trait Interpolant {
+    /// Evaluates the function at `x`.
+    fn eval(&self, x: f64) -> f64;
+
+    /// Evaluates the function at every `x`.
+    fn eval_many(&self, xs: &[f64]) -> Vec<f64> {xs.iter().map(|&x| self.eval(x)).collect()}
+}
+
+// Checks the lengths and returns the points sorted by x, rejecting duplicates
+fn sorted_points(xs: &[f64], ys: &[f64], needed: usize) -> Result<Vec<(f64, f64)>> {
+    if xs.len() != ys.len() {return Err(InterpError::LengthMismatch { xs: xs.len(), ys: ys.len() });}
+    if xs.len() < needed {return Err(InterpError::TooFewPoints { needed, found: xs.len() });}
+    let mut points: Vec<(f64, f64)> = xs.iter().copied().zip(ys.iter().copied()).collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    match points.windows(2).find(|w| w[0].0 == w[1].0) {
+        Some(w) => Err(InterpError::DuplicateX(w[0].0)),
+        None => Ok(points),
+    }
+}
+
+/// The Lagrange interpolating polynomial, evaluated in the stable barycentric form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lagrange {
+    points: Vec<(f64, f64)>,
+    weights: Vec<f64>,
+}
+
+impl Lagrange {
+    /// Builds the polynomial of degree `n - 1` through the `n` points.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the interpolant, or an [InterpError] if there are no points,
+    /// the lengths differ or an `x` is repeated.
+    pub fn new(xs: &[f64], ys: &[f64]) -> Result<Self> {
+        let points = sorted_points(xs, ys, 1)?;
+        let weights = points.iter().enumerate().map(|(i, (xi, _))| {
+            1.0 / points.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, (xj, _))| xi - xj).product::<f64>()
+        }).collect();
+        Ok(Lagrange { points, weights })
+    }
+}
+
+impl Interpolant for Lagrange {
+    fn eval(&self, x: f64) -> f64 {
+        let (mut numerator, mut denominator) = (0.0, 0.0);
+        for ((xi, yi), w) in self.points.iter().zip(&self.weights) {
+            if x == *xi {return *yi;}
+            let term = w / (x - xi);
+            numerator += term * yi;
+            denominator += term;
+        }
+        numerator / denominator
+    }
+}
+
+/// A natural cubic spline: a cubic between each pair of points, with continuous first and
+/// second derivatives and a zero second derivative at both ends.
+///
+/// Outside the points, the first and last cubics are extended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubicSpline {
+    xs: Vec<f64>,
+    // the cubic `a + b·t + c·t² + d·t³` of each segment, with `t = x - xs[i]`
+    segments: Vec<[f64; 4]>,
+}
+
+impl CubicSpline {
+    /// Builds the natural cubic spline through the points.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the interpolant, or an [InterpError] if there are fewer than
+    /// two points, the lengths differ or an `x` is repeated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::num::interp::{CubicSpline, Interpolant};
+    ///
+    /// let spline = CubicSpline::natural(&[0.0, 1.0, 2.0], &[0.0, 1.0, 0.0]).unwrap();
+    /// assert_eq!(spline.eval(0.5), 0.6875);
+    /// ```
+    pub fn natural(xs: &[f64], ys: &[f64]) -> Result<Self> {
+        let points = sorted_points(xs, ys, 2)?;
+        let n = points.len() - 1;
+        let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        let h: Vec<f64> = (0..n).map(|i| x[i + 1] - x[i]).collect();
+
+        // the tridiagonal system for the second derivatives, solved with the Thomas algorithm
+        let mut m = vec![0.0; n + 1];
+        if n > 1 {
+            let (mut diag, mut rhs) = (vec![0.0; n], vec![0.0; n]);
+            for i in 1..n {
+                diag[i] = 2.0 * (h[i - 1] + h[i]);
+                rhs[i] = 6.0 * ((y[i + 1] - y[i]) / h[i] - (y[i] - y[i - 1]) / h[i - 1]);
+                if i > 1 {
+                    let factor = h[i - 1] / diag[i - 1];
+                    diag[i] -= factor * h[i - 1];
+                    rhs[i] -= factor * rhs[i - 1];
+                }
+            }
+            for i in (1..n).rev() {
+                m[i] = (rhs[i] - if i < n - 1 {h[i] * m[i + 1]} else {0.0}) / diag[i];
+            }
+        }
+
+        let segments = (0..n).map(|i| [
+            y[i],
+            (y[i + 1] - y[i]) / h[i] - h[i] * (2.0 * m[i] + m[i + 1]) / 6.0,
+            m[i] / 2.0,
+            (m[i + 1] - m[i]) / (6.0 * h[i]),
+        ]).collect();
+        Ok(CubicSpline { xs: x, segments })
+    }
+}
+
+impl Interpolant for CubicSpline {
+    fn eval(&self, x: f64) -> f64 {
+        // the segment starting at the last point at or before x (clamped to the ends)
+        let i = self.xs.partition_point(|&xi| xi <= x).saturating_sub(1).min(self.segments.len() - 1);
+        let [a, b, c, d] = self.segments[i];
+        let t = x - self.xs[i];
+        a + t * (b + t * (c + t * d))
+    }
+}
+
+/// A polynomial, stored by its coefficients in increasing powers of `x`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    /// The coefficients of `1, x, x², ...`.
+    pub coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    /// Creates a polynomial from its coefficients, in increasing powers of `x`.
+    pub fn new(coefficients: Vec<f64>) -> Self {Polynomial { coefficients }}
+
+    /// Fits the polynomial of the given degree that minimizes the squared error to the points.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the polynomial, or an [InterpError] if there are fewer than
+    /// `degree + 1` distinct points or the lengths differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::num::interp::Polynomial;
+    ///
+    /// // a line through noisy points
+    /// let line = Polynomial::fit(&[0.0, 1.0, 2.0, 3.0], &[0.1, 0.9, 2.1, 2.9], 1).unwrap();
+    /// assert!((line.coefficients[1] - 0.96).abs() < 1e-9);
+    /// assert!(line.r_squared(&[0.0, 1.0, 2.0, 3.0], &[0.1, 0.9, 2.1, 2.9]) > 0.99);
+    /// ```
+    pub fn fit(xs: &[f64], ys: &[f64], degree: usize) -> Result<Self> {
+        if xs.len() != ys.len() {return Err(InterpError::LengthMismatch { xs: xs.len(), ys: ys.len() });}
+        if xs.len() <= degree {return Err(InterpError::TooFewPoints { needed: degree + 1, found: xs.len() });}
+        // the normal equations (Vᵀ·V)·c = Vᵀ·y, with V the Vandermonde matrix of the xs
+        let vandermonde = Matrix::from_rows(&xs.iter().map(|&x| (0..=degree).map(|p| x.powi(p as i32)).collect::<Vec<_>>()).collect::<Vec<_>>())?;
+        let vt = vandermonde.transpose();
+        let coefficients = (&vt * &vandermonde).solve(&vt.mul_vec(ys)?)?;
+        Ok(Polynomial { coefficients })
+    }
+
+    /// Returns the degree of the polynomial (`0` for a constant).
+    pub fn degree(&self) -> usize {self.coefficients.len().saturating_sub(1)}
+
+    /// Returns the coefficient of determination (R²) of the polynomial for the points:
+    /// `1.0` for a perfect fit, lower for worse ones.
+    pub fn r_squared(&self, xs: &[f64], ys: &[f64]) -> f64 {
+        let mean = ys.iter().sum::<f64>() / ys.len() as f64;
+        let total: f64 = ys.iter().map(|y| (y - mean).powi(2)).sum();
+        let residual: f64 = xs.iter().zip(ys).map(|(&x, y)| (y - self.eval(x)).powi(2)).sum();
+        if total == 0.0 {return if residual == 0.0 {1.0} else {0.0};}
+        1.0 - residual / total
+    }
+}
+
+impl Interpolant for Polynomial {
+    fn eval(&self, x: f64) -> f64 {
+        self.coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+    }
+}
+
+/// Formats the polynomial like `1 + 2x - 0.5x^2`, with the precision of the formatter
+/// applied to the coefficients. Coefficients within rounding noise of an integer are rounded.
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = |c: f64| match f.precision() {
+            Some(precision) => format!("{:.*}", precision, c),
+            None if (c - c.round()).abs() < 1e-9 => format!("{}", c.round()),
+            None => c.to_string(),
+        };
+        let mut out = String::new();
+        for (power, &c) in self.coefficients.iter().enumerate() {
+            let sign = if c < 0.0 {"-"} else {"+"};
+            let term = match power {
+                0 => format(c.abs()),
+                1 => format!("{}x", format(c.abs())),
+                _ => format!("{}x^{}", format(c.abs()), power),
+            };
+            match out.is_empty() {
+                true => out = if c < 0.0 {format!("-{}", term)} else {term},
+                false => out.push_str(&format!(" {} {}", sign, term)),
+            }
+        }
+        f.write_str(if out.is_empty() {"0"} else {&out})
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lagrange() {
+        let xs: Vec<f64> = (0..5).map(|i| i as f64).collect();
+        let ys: Vec<f64> = xs.iter().map(|x| x * x * x - 2.0 * x).collect();
+        let p = Lagrange::new(&xs, &ys).unwrap();
+        assert!((p.eval(2.5) - (2.5f64.powi(3) - 5.0)).abs() < 1e-12);
+        assert_eq!(p.eval(3.0), 21.0);
+        assert_eq!(Lagrange::new(&[1.0], &[5.0]).unwrap().eval(100.0), 5.0);
+        assert_eq!(Lagrange::new(&[1.0, 1.0], &[1.0, 2.0]), Err(InterpError::DuplicateX(1.0)));
+        assert_eq!(Lagrange::new(&[1.0], &[]), Err(InterpError::LengthMismatch { xs: 1, ys: 0 }));
+    }
+
+    #[test]
+    fn test_cubic_spline() {
+        let xs = [3.0, 0.0, 1.0, 2.0];  // unsorted on purpose
+        let ys = [0.0, 0.0, 1.0, 0.5];
+        let spline = CubicSpline::natural(&xs, &ys).unwrap();
+        for (x, y) in xs.iter().zip(&ys) {
+            assert!((spline.eval(*x) - y).abs() < 1e-12);
+        }
+        // continuous across the knots
+        for knot in [1.0, 2.0] {
+            assert!((spline.eval(knot - 1e-9) - spline.eval(knot + 1e-9)).abs() < 1e-6);
+        }
+        // two points make a straight line
+        assert_eq!(CubicSpline::natural(&[0.0, 2.0], &[1.0, 5.0]).unwrap().eval(0.5), 2.0);
+        assert_eq!(CubicSpline::natural(&[0.0], &[1.0]), Err(InterpError::TooFewPoints { needed: 2, found: 1 }));
+    }
+
+    #[test]
+    fn test_polynomial_fit() {
+        let xs = [-2.0, -1.0, 0.0, 1.0, 2.0, 3.0];
+        let ys: Vec<f64> = xs.iter().map(|x| 0.5 * x * x - 3.0 * x + 2.0).collect();
+        let fit = Polynomial::fit(&xs, &ys, 2).unwrap();
+        assert!(fit.coefficients.iter().zip([2.0, -3.0, 0.5]).all(|(a, b)| (a - b).abs() < 1e-9));
+        assert_eq!(fit.degree(), 2);
+        assert!((fit.r_squared(&xs, &ys) - 1.0).abs() < 1e-12);
+        assert_eq!(format!("{:.1}", fit), "2.0 - 3.0x + 0.5x^2");
+        assert_eq!(Polynomial::new(vec![0.0, -1.0]).to_string(), "0 - 1x");
+        assert_eq!(Polynomial::new(vec![]).to_string(), "0");
+        assert_eq!(Polynomial::fit(&[1.0, 2.0], &[1.0, 2.0], 2), Err(InterpError::TooFewPoints { needed: 3, found: 2 }));
+        assert_eq!(Polynomial::fit(&[1.0, 1.0], &[1.0, 2.0], 1), Err(InterpError::Matrix(MatrixError::Singular)));
+    }
+}
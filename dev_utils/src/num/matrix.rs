@@ -0,0 +1,434 @@
+//! Dense matrices of `f64`, with LU decomposition and linear system solving.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::num::matrix::Matrix;
+//!
+//! // 2x + y = 5, x + 3y = 10
+//! let a = Matrix::from_rows(&[[2.0, 1.0], [1.0, 3.0]]).unwrap();
+//! assert_eq!(a.solve(&[5.0, 10.0]).unwrap(), [1.0, 3.0]);
+//! assert_eq!(a.determinant().unwrap(), 5.0);
+//!
+//! let b = &a * &a.transpose();
+//! assert_eq!(b, Matrix::from_rows(&[[5.0, 5.0], [5.0, 10.0]]).unwrap());
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+
+/// Represents errors that can occur in matrix operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The dimensions of the operands don't match; both are `(rows, cols)`.
+    DimensionMismatch { expected: (usize, usize), found: (usize, usize) },
+    /// The operation needs a square matrix.
+    NotSquare { rows: usize, cols: usize },
+    /// The matrix is singular (its determinant is zero).
+    Singular,
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch { expected, found } => write!(f,
+                "dimension mismatch: expected {}x{}, found {}x{}", expected.0, expected.1, found.0, found.1),
+            MatrixError::NotSquare { rows, cols } => write!(f, "matrix is not square: {}x{}", rows, cols),
+            MatrixError::Singular => write!(f, "matrix is singular"),
+        }
+    }
+}
+
+impl Error for MatrixError {}
+
+type Result<T> = std::result::Result<T, MatrixError>;
+
+// Pivots smaller than this (relative to the largest entry) are treated as zero
+const SINGULAR_EPSILON: f64 = 1e-12;
+
+/// A dense matrix of `f64`, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    /// Creates a matrix of zeros.
+    pub fn zeros(rows: usize, cols: usize) -> Self {Matrix { rows, cols, data: vec![0.0; rows * cols] }}
+
+    /// Creates the `n`x`n` identity matrix.
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zeros(n, n);
+        (0..n).for_each(|i| m[(i, i)] = 1.0);
+        m
+    }
+
+    /// Creates a matrix from its rows.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matrix, or [MatrixError::DimensionMismatch] if the rows
+    /// have different lengths.
+    pub fn from_rows<R: AsRef<[f64]>>(rows: &[R]) -> Result<Self> {
+        let cols = rows.first().map_or(0, |row| row.as_ref().len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            let row = row.as_ref();
+            if row.len() != cols {
+                return Err(MatrixError::DimensionMismatch { expected: (1, cols), found: (1, row.len()) });
+            }
+            data.extend_from_slice(row);
+        }
+        Ok(Matrix { rows: rows.len(), cols, data })
+    }
+
+    /// Creates a matrix from its values, row by row.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matrix, or [MatrixError::DimensionMismatch] if `data`
+    /// doesn't have `rows * cols` values.
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<f64>) -> Result<Self> {
+        match data.len() == rows * cols {
+            true => Ok(Matrix { rows, cols, data }),
+            false => Err(MatrixError::DimensionMismatch { expected: (rows, cols), found: (1, data.len()) }),
+        }
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {self.rows}
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {self.cols}
+
+    /// Returns `true` if the matrix has as many rows as columns.
+    pub fn is_square(&self) -> bool {self.rows == self.cols}
+
+    /// Returns a row as a slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is out of bounds.
+    pub fn row(&self, row: usize) -> &[f64] {&self.data[row * self.cols..(row + 1) * self.cols]}
+
+    /// Returns the transpose of the matrix.
+    pub fn transpose(&self) -> Matrix {
+        let mut t = Self::zeros(self.cols, self.rows);
+        for r in 0..self.rows {
+            for c in 0..self.cols {t[(c, r)] = self[(r, c)];}
+        }
+        t
+    }
+
+    /// Multiplies the matrix by a column vector.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the product, or [MatrixError::DimensionMismatch] if the vector
+    /// doesn't have one value per column.
+    pub fn mul_vec(&self, v: &[f64]) -> Result<Vec<f64>> {
+        if v.len() != self.cols {
+            return Err(MatrixError::DimensionMismatch { expected: (self.cols, 1), found: (v.len(), 1) });
+        }
+        Ok((0..self.rows).map(|r| self.row(r).iter().zip(v).map(|(a, b)| a * b).sum()).collect())
+    }
+
+    /// Computes the LU decomposition of the matrix, with partial pivoting.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the decomposition, or [MatrixError::NotSquare] / [MatrixError::Singular].
+    pub fn lu(&self) -> Result<Lu> {
+        if !self.is_square() {return Err(MatrixError::NotSquare { rows: self.rows, cols: self.cols });}
+        let n = self.rows;
+        let mut lu = self.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = 1.0;
+        let scale = self.data.iter().fold(0.0f64, |max, x| max.max(x.abs()));
+
+        for k in 0..n {
+            // the row with the largest pivot keeps the rounding errors small
+            let pivot = (k..n).max_by(|&a, &b| lu[(a, k)].abs().total_cmp(&lu[(b, k)].abs())).unwrap_or(k);
+            if lu[(pivot, k)].abs() <= SINGULAR_EPSILON * scale.max(1.0) {return Err(MatrixError::Singular);}
+            if pivot != k {
+                lu.swap_rows(pivot, k);
+                permutation.swap(pivot, k);
+                sign = -sign;
+            }
+            for r in k + 1..n {
+                let factor = lu[(r, k)] / lu[(k, k)];
+                lu[(r, k)] = factor;
+                for c in k + 1..n {lu[(r, c)] -= factor * lu[(k, c)];}
+            }
+        }
+        Ok(Lu { lu, permutation, sign })
+    }
+
+    /// Computes the determinant of the matrix.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the determinant (`0.0` for a singular matrix),
+    /// or [MatrixError::NotSquare].
+    pub fn determinant(&self) -> Result<f64> {
+        match self.lu() {
+            Ok(lu) => Ok(lu.determinant()),
+            Err(MatrixError::Singular) => Ok(0.0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Solves the linear system `self * x = b` with an LU decomposition.
+    ///
+    /// To solve several systems with the same matrix, decompose it once with [Matrix::lu].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `x`, or a [MatrixError] if the matrix is not square, is singular,
+    /// or doesn't match the length of `b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::num::matrix::{Matrix, MatrixError};
+    ///
+    /// let a = Matrix::from_rows(&[[0.0, 2.0], [3.0, 0.0]]).unwrap();  // needs a row swap
+    /// assert_eq!(a.solve(&[4.0, 9.0]).unwrap(), [3.0, 2.0]);
+    ///
+    /// let singular = Matrix::from_rows(&[[1.0, 2.0], [2.0, 4.0]]).unwrap();
+    /// assert_eq!(singular.solve(&[1.0, 2.0]), Err(MatrixError::Singular));
+    /// ```
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>> {self.lu()?.solve(b)}
+
+    /// Computes the inverse of the matrix.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the inverse, or [MatrixError::NotSquare] / [MatrixError::Singular].
+    pub fn inverse(&self) -> Result<Matrix> {
+        let lu = self.lu()?;
+        let mut inverse = Self::zeros(self.rows, self.cols);
+        for c in 0..self.cols {
+            let mut unit = vec![0.0; self.rows];
+            unit[c] = 1.0;
+            for (r, x) in lu.solve(&unit)?.into_iter().enumerate() {inverse[(r, c)] = x;}
+        }
+        Ok(inverse)
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for c in 0..self.cols {self.data.swap(a * self.cols + c, b * self.cols + c);}
+    }
+
+    // Panics with the dimensions of both operands if they don't match
+    fn assert_same_size(&self, other: &Matrix, op: &str) {
+        assert!(self.rows == other.rows && self.cols == other.cols,
+            "cannot {} a {}x{} and a {}x{} matrix", op, self.rows, self.cols, other.rows, other.cols);
+    }
+}
+
+/// The LU decomposition of a square matrix with partial pivoting: `P * A = L * U`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lu {
+    // L (below the diagonal, with an implicit unit diagonal) and U, in one matrix
+    lu: Matrix,
+    // the original row of each row of `lu`
+    permutation: Vec<usize>,
+    // the sign of the permutation, for the determinant
+    sign: f64,
+}
+
+impl Lu {
+    /// Returns the lower triangular factor `L`, with ones on the diagonal.
+    pub fn l(&self) -> Matrix {
+        let n = self.lu.rows;
+        let mut l = Matrix::identity(n);
+        for r in 0..n {
+            for c in 0..r {l[(r, c)] = self.lu[(r, c)];}
+        }
+        l
+    }
+
+    /// Returns the upper triangular factor `U`.
+    pub fn u(&self) -> Matrix {
+        let n = self.lu.rows;
+        let mut u = Matrix::zeros(n, n);
+        for r in 0..n {
+            for c in r..n {u[(r, c)] = self.lu[(r, c)];}
+        }
+        u
+    }
+
+    /// Returns the row permutation: row `i` of `L * U` is row `permutation()[i]` of the matrix.
+    pub fn permutation(&self) -> &[usize] {&self.permutation}
+
+    /// Returns the determinant of the decomposed matrix.
+    pub fn determinant(&self) -> f64 {
+        (0..self.lu.rows).map(|i| self.lu[(i, i)]).product::<f64>() * self.sign
+    }
+
+    /// Solves `A * x = b` by forward and back substitution.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `x`, or [MatrixError::DimensionMismatch] if `b` doesn't have
+    /// one value per row.
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>> {
+        let n = self.lu.rows;
+        if b.len() != n {return Err(MatrixError::DimensionMismatch { expected: (n, 1), found: (b.len(), 1) });}
+        // L * y = P * b
+        let mut x: Vec<f64> = self.permutation.iter().map(|&i| b[i]).collect();
+        for r in 0..n {
+            for c in 0..r {x[r] -= self.lu[(r, c)] * x[c];}
+        }
+        // U * x = y
+        for r in (0..n).rev() {
+            for c in r + 1..n {x[r] -= self.lu[(r, c)] * x[c];}
+            x[r] /= self.lu[(r, r)];
+        }
+        Ok(x)
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (row, col): (usize, usize)) -> &f64 {
+        assert!(row < self.rows && col < self.cols, "index ({}, {}) out of bounds for a {}x{} matrix", row, col, self.rows, self.cols);
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+        assert!(row < self.rows && col < self.cols, "index ({}, {}) out of bounds for a {}x{} matrix", row, col, self.rows, self.cols);
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+/// Adds two matrices.
+///
+/// # Panics
+///
+/// Panics if the matrices have different dimensions.
+impl Add for &Matrix {
+    type Output = Matrix;
+
+    fn add(self, other: &Matrix) -> Matrix {
+        self.assert_same_size(other, "add");
+        Matrix { rows: self.rows, cols: self.cols, data: self.data.iter().zip(&other.data).map(|(a, b)| a + b).collect() }
+    }
+}
+
+/// Subtracts two matrices.
+///
+/// # Panics
+///
+/// Panics if the matrices have different dimensions.
+impl Sub for &Matrix {
+    type Output = Matrix;
+
+    fn sub(self, other: &Matrix) -> Matrix {
+        self.assert_same_size(other, "subtract");
+        Matrix { rows: self.rows, cols: self.cols, data: self.data.iter().zip(&other.data).map(|(a, b)| a - b).collect() }
+    }
+}
+
+/// Multiplies two matrices.
+///
+/// # Panics
+///
+/// Panics if the number of columns of the left matrix is not the number of rows of the right one.
+impl Mul for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: &Matrix) -> Matrix {
+        assert!(self.cols == other.rows, "cannot multiply a {}x{} and a {}x{} matrix", self.rows, self.cols, other.rows, other.cols);
+        let mut product = Matrix::zeros(self.rows, other.cols);
+        for r in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(r, k)];
+                for c in 0..other.cols {product[(r, c)] += a * other[(k, c)];}
+            }
+        }
+        product
+    }
+}
+
+/// Multiplies every value of the matrix by a scalar.
+impl Mul<f64> for &Matrix {
+    type Output = Matrix;
+
+    fn mul(self, k: f64) -> Matrix {
+        Matrix { rows: self.rows, cols: self.cols, data: self.data.iter().map(|x| x * k).collect() }
+    }
+}
+
+/// Formats the matrix with one row per line and right-aligned columns.
+/// The precision of the formatter applies to every value (`{:.2}`).
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<String> = self.data.iter().map(|x| match f.precision() {
+            Some(precision) => format!("{:.*}", precision, x),
+            None => x.to_string(),
+        }).collect();
+        let width = cells.iter().map(String::len).max().unwrap_or(0);
+        for r in 0..self.rows {
+            if r > 0 {writeln!(f)?;}
+            let row: Vec<String> = cells[r * self.cols..(r + 1) * self.cols].iter().map(|s| format!("{:>width$}", s)).collect();
+            write!(f, "[{}]", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: &Matrix, b: &Matrix) -> bool {
+        a.rows == b.rows && a.cols == b.cols && a.data.iter().zip(&b.data).all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Matrix::from_rows(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]).unwrap();
+        let b = Matrix::from_vec(3, 2, vec![7.0, 8.0, 9.0, 10.0, 11.0, 12.0]).unwrap();
+        assert_eq!(&a * &b, Matrix::from_rows(&[[58.0, 64.0], [139.0, 154.0]]).unwrap());
+        assert_eq!(&(&a + &a) - &a, a);
+        assert_eq!(&a * 2.0, &a + &a);
+        assert_eq!(a.transpose().transpose(), a);
+        assert_eq!(a.transpose().row(2), [3.0, 6.0]);
+        assert_eq!(a.mul_vec(&[1.0, 0.0, -1.0]).unwrap(), [-2.0, -2.0]);
+        assert_eq!(&Matrix::identity(3) * &b, b);
+        assert!(Matrix::from_rows(&[vec![1.0], vec![1.0, 2.0]]).is_err());
+        assert_eq!(a.to_string(), "[1 2 3]\n[4 5 6]");
+        assert_eq!(format!("{:.1}", Matrix::from_rows(&[[0.5, -10.0]]).unwrap()), "[  0.5 -10.0]");
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot multiply a 2x3 and a 2x3 matrix")]
+    fn test_mul_mismatch() {
+        let a = Matrix::zeros(2, 3);
+        let _ = &a * &a;
+    }
+
+    #[test]
+    fn test_lu_and_solve() {
+        let a = Matrix::from_rows(&[[2.0, 1.0, 1.0], [4.0, -6.0, 0.0], [-2.0, 7.0, 2.0]]).unwrap();
+        let lu = a.lu().unwrap();
+        let permuted = Matrix::from_rows(&lu.permutation().iter().map(|&i| a.row(i).to_vec()).collect::<Vec<_>>()).unwrap();
+        assert!(approx_eq(&(&lu.l() * &lu.u()), &permuted));
+        assert!((a.determinant().unwrap() - -16.0).abs() < 1e-9);
+
+        let x = a.solve(&[5.0, -2.0, 9.0]).unwrap();
+        assert!(x.iter().zip([1.0, 1.0, 2.0]).all(|(a, b)| (a - b).abs() < 1e-12));
+        assert!(approx_eq(&(&a * &a.inverse().unwrap()), &Matrix::identity(3)));
+
+        assert_eq!(Matrix::zeros(2, 3).lu(), Err(MatrixError::NotSquare { rows: 2, cols: 3 }));
+        assert_eq!(Matrix::zeros(2, 2).determinant(), Ok(0.0));
+        assert_eq!(a.solve(&[1.0]), Err(MatrixError::DimensionMismatch { expected: (3, 1), found: (1, 1) }));
+    }
+}
@@ -0,0 +1,174 @@
+//! Root finding: the `x` where `f(x) = 0`.
+//!
+//! - [bisection] - Always converges within a bracket where `f` changes sign, but slowly
+//! - [newton] - Converges quadratically near the root, but needs the derivative
+//! - [secant] - Nearly as fast as Newton-Raphson, without the derivative
+//!
+//! # Examples
+//! ```
+//! use dev_utils::num::{root, Options};
+//!
+//! let f = |x: f64| x.cos() - x;
+//! let slow = root::bisection(f, 0.0, 1.0, Options::default()).unwrap();
+//! let fast = root::secant(f, 0.0, 1.0, Options::default()).unwrap();
+//! assert!((slow.value - fast.value).abs() < 1e-9);
+//! assert!(fast.iterations.len() < slow.iterations.len());
+//! ```
+use super::{NumError, Options, Result, Solution};
+
+// Evaluates `f(x)`, failing on NaN and infinities
+fn eval(f: &impl Fn(f64) -> f64, x: f64) -> Result<f64> {
+    let y = f(x);
+    match y.is_finite() {
+        true => Ok(y),
+        false => Err(NumError::NotFinite { x }),
+    }
+}
+
+/// Finds a root of `f` in `[a, b]` by halving the bracket.
+///
+/// The error of each step is half the width of the bracket.
+///
+/// # Arguments
+///
+/// * `f` - The function
+/// * `a`, `b` - The ends of the bracket, where `f` must have opposite signs
+/// * `options` - The tolerance and iteration limit
+///
+/// # Returns
+///
+/// A `Result` containing the [Solution], or [NumError::NoSignChange] if `f(a)` and `f(b)`
+/// have the same sign.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::num::{root, Options};
+///
+/// let root = root::bisection(|x| x * x - 4.0, 0.0, 5.0, Options::tolerance(1e-6)).unwrap();
+/// assert!((root.value - 2.0).abs() < 1e-6);
+/// assert!(root::bisection(|x| x * x + 1.0, -1.0, 1.0, Options::default()).is_err());
+/// ```
+pub fn bisection(f: impl Fn(f64) -> f64, a: f64, b: f64, options: Options) -> Result<Solution> {
+    let (mut a, mut b) = if a <= b {(a, b)} else {(b, a)};
+    let mut fa = eval(&f, a)?;
+    let fb = eval(&f, b)?;
+    if fa * fb > 0.0 {return Err(NumError::NoSignChange { a, b });}
+
+    let mut solution = Solution { value: a, iterations: Vec::new() };
+    for _ in 0..options.max_iterations {
+        let mid = a + (b - a) / 2.0;
+        let fm = eval(&f, mid)?;
+        if solution.push(mid, (b - a) / 2.0, &options) || fm == 0.0 {return Ok(solution);}
+        match fa * fm < 0.0 {
+            true => b = mid,
+            false => (a, fa) = (mid, fm),
+        }
+    }
+    Err(NumError::NoConvergence(solution))
+}
+
+/// Finds a root of `f` with the Newton-Raphson method, starting from `x0`.
+///
+/// The error of each step is the distance to the previous estimate.
+///
+/// # Arguments
+///
+/// * `f` - The function
+/// * `df` - The derivative of `f`
+/// * `x0` - The initial guess
+/// * `options` - The tolerance and iteration limit
+///
+/// # Returns
+///
+/// A `Result` containing the [Solution], or [NumError::ZeroDerivative] if the method hits
+/// a flat point.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::num::{root, Options};
+///
+/// let cube_root = root::newton(|x| x.powi(3) - 27.0, |x| 3.0 * x * x, 1.0, Options::default()).unwrap();
+/// assert!((cube_root.value - 3.0).abs() < 1e-12);
+/// ```
+pub fn newton(f: impl Fn(f64) -> f64, df: impl Fn(f64) -> f64, x0: f64, options: Options) -> Result<Solution> {
+    let mut solution = Solution { value: x0, iterations: Vec::new() };
+    let mut x = x0;
+    for _ in 0..options.max_iterations {
+        let (fx, dfx) = (eval(&f, x)?, eval(&df, x)?);
+        if dfx == 0.0 {return Err(NumError::ZeroDerivative { x });}
+        let next = x - fx / dfx;
+        if solution.push(next, (next - x).abs(), &options) {return Ok(solution);}
+        x = next;
+    }
+    Err(NumError::NoConvergence(solution))
+}
+
+/// Finds a root of `f` with the secant method, starting from `x0` and `x1`.
+///
+/// The error of each step is the distance to the previous estimate.
+///
+/// # Returns
+///
+/// A `Result` containing the [Solution], or [NumError::ZeroDerivative] if two consecutive
+/// estimates have the same value of `f`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::num::{root, Options};
+///
+/// let root = root::secant(|x| x.exp() - 2.0, 0.0, 1.0, Options::default()).unwrap();
+/// assert!((root.value - 2f64.ln()).abs() < 1e-12);
+/// ```
+pub fn secant(f: impl Fn(f64) -> f64, x0: f64, x1: f64, options: Options) -> Result<Solution> {
+    let mut solution = Solution { value: x1, iterations: Vec::new() };
+    let (mut x0, mut x1) = (x0, x1);
+    let mut f0 = eval(&f, x0)?;
+    for _ in 0..options.max_iterations {
+        let f1 = eval(&f, x1)?;
+        if f1 == f0 {return Err(NumError::ZeroDerivative { x: x1 });}
+        let next = x1 - f1 * (x1 - x0) / (f1 - f0);
+        if solution.push(next, (next - x1).abs(), &options) {return Ok(solution);}
+        (x0, f0, x1) = (x1, f1, next);
+    }
+    Err(NumError::NoConvergence(solution))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bisection() {
+        let root = bisection(|x| x * x * x - x - 2.0, 2.0, 1.0, Options::tolerance(1e-12)).unwrap();
+        assert!((root.value - 1.5213797068045676).abs() < 1e-11);
+        assert!(root.error() <= 1e-12);
+        assert_eq!(root.iterations[0].value, 1.5);
+        // an exact hit stops early
+        assert_eq!(bisection(|x| x - 1.0, 0.0, 2.0, Options::default()).unwrap().iterations.len(), 1);
+        assert_eq!(bisection(|x| x, 1.0, 2.0, Options::default()), Err(NumError::NoSignChange { a: 1.0, b: 2.0 }));
+    }
+
+    #[test]
+    fn test_newton_and_secant() {
+        let f = |x: f64| x * x - 2.0;
+        let newton = newton(f, |x| 2.0 * x, 1.0, Options::default()).unwrap();
+        let secant = secant(f, 1.0, 2.0, Options::default()).unwrap();
+        for root in [&newton, &secant] {
+            assert!((root.value - std::f64::consts::SQRT_2).abs() < 1e-12);
+        }
+        assert!(newton.iterations.len() <= 6);
+        // the trace shows the error shrinking
+        assert!(newton.iterations.windows(2).skip(1).all(|w| w[1].error < w[0].error));
+
+        assert_eq!(super::newton(f, |_| 0.0, 1.0, Options::default()), Err(NumError::ZeroDerivative { x: 1.0 }));
+        assert_eq!(super::newton(|x| x.ln(), |x| 1.0 / x, -1.0, Options::default()), Err(NumError::NotFinite { x: -1.0 }));
+        let Err(NumError::NoConvergence(partial)) = super::newton(f, |x| 2.0 * x, 1.0, Options::default().max_iterations(2)) else {
+            panic!("expected no convergence");
+        };
+        assert_eq!(partial.iterations.len(), 2);
+    }
+}
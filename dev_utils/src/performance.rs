@@ -0,0 +1,426 @@
+//! Timing and benchmarking helpers.
+//!
+//! [exec_time] times a single run; [bench] warms up, runs a closure many times and reports
+//! the distribution of the timings as a [BenchResult], which can be compared with others.
+//! [ScopedTimer], [time_it!] and [#\[timed\]](macro@timed) log how long a scope or a function
+//! took through [dlog](crate::dlog), and a [Stopwatch] breaks a multi-phase run down into labeled laps.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::performance::bench;
+//!
+//! let sum = bench("sum", 1_000, || (0..1_000u64).sum::<u64>());
+//! let fold = bench("fold", 1_000, || (0..1_000u64).fold(0, |a, b| a + b));
+//! println!("fold is {:.2}x the speed of sum", sum.speedup(&fold));
+//! assert_eq!(sum.iterations, 1_000);
+//! ```
+use std::cell::Cell;
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use crate::dlog::{self, Level};
+use crate::format::{Stylize, Style, CYAN, GREEN, YELLOW};
+use crate::format::num::{group_digits, si};
+use crate::format::table::{Alignment, BorderStyle, Table};
+use crate::stats::Summary;
+
+pub use crate::time_it;
+pub use dev_macros::timed;
+
+
+/// Runs a closure once and returns its execution time in microseconds.
+///
+/// # Examples
+/// ```
+/// use dev_utils::performance::exec_time;
+///
+/// let micros = exec_time(|| std::thread::sleep(std::time::Duration::from_millis(2)));
+/// assert!(micros >= 2_000);
+/// ```
+pub fn exec_time<T, F: FnOnce() -> T>(f: F) -> u128 {
+    let start = Instant::now();
+    black_box(f());
+    start.elapsed().as_micros()
+}
+
+/// Timing statistics of a benchmark run by [bench].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub name: String,
+    /// Number of measured iterations (warm-up runs excluded).
+    pub iterations: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+    /// 95th percentile: 95% of the iterations were at least this fast.
+    pub p95: Duration,
+    pub std_dev: Duration,
+}
+
+impl BenchResult {
+    /// Computes the statistics of a set of timings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` is empty.
+    pub fn from_samples(name: &str, samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "a benchmark needs at least one sample");
+        let secs = samples.iter().map(Duration::as_secs_f64).collect::<Vec<_>>();
+        let summary = Summary::of(&secs).expect("samples is not empty");
+        let duration = Duration::from_secs_f64;
+
+        BenchResult {
+            name: name.to_string(),
+            iterations: summary.count,
+            min: *samples.iter().min().expect("samples is not empty"),
+            max: *samples.iter().max().expect("samples is not empty"),
+            mean: duration(summary.mean),
+            median: duration(summary.median),
+            p95: duration(summary.p95),
+            std_dev: duration(summary.std_dev),
+        }
+    }
+
+    /// Returns the number of iterations per second, based on the mean time.
+    pub fn ops_per_sec(&self) -> f64 {
+        match self.mean.as_secs_f64() {
+            0.0 => f64::INFINITY,
+            mean => 1.0 / mean,
+        }
+    }
+
+    /// Returns how many times faster `other` is than `self`, comparing medians.
+    ///
+    /// A value above `1.0` means `other` is faster.
+    pub fn speedup(&self, other: &BenchResult) -> f64 {
+        self.median.as_secs_f64() / other.median.as_secs_f64()
+    }
+}
+
+impl fmt::Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stat = |label: &str, value: Duration| format!("{} {}", label.style(Style::Dim), format!("{:.2?}", value).color(CYAN));
+        let median = format!("{} {}", "median".style(Style::Dim), format!("{:.2?}", self.median).color(GREEN));
+        write!(f, "{} ({} iterations): {} {} {} {} {} | {} ops/s",
+            self.name.style(Style::Bold),
+            group_digits(self.iterations),
+            stat("min", self.min),
+            stat("mean", self.mean),
+            median,
+            stat("p95", self.p95),
+            stat("σ", self.std_dev),
+            si(self.ops_per_sec()).color(YELLOW),
+        )
+    }
+}
+
+/// Benchmarks a closure, printing and returning the timing statistics.
+///
+/// The closure first runs a few warm-up iterations (a tenth of `iterations`, at least one)
+/// that are not measured, then each of the `iterations` runs is timed separately.
+/// Its return value is passed through [black_box] so the work is not optimized away.
+///
+/// # Arguments
+///
+/// * `name` - The label used in the report
+/// * `iterations` - The number of measured runs (at least one)
+/// * `f` - The code to measure
+///
+/// # Returns
+///
+/// Returns a [BenchResult] with the statistics of the measured runs.
+pub fn bench<T, F: FnMut() -> T>(name: &str, iterations: usize, f: F) -> BenchResult {
+    let result = run(name, iterations, f);
+    println!("{}", result);
+    result
+}
+
+fn run<T, F: FnMut() -> T>(name: &str, iterations: usize, mut f: F) -> BenchResult {
+    let iterations = iterations.max(1);
+    (0..(iterations / 10).max(1)).for_each(|_| {black_box(f());});
+
+    let samples = (0..iterations).map(|_| {
+        let start = Instant::now();
+        black_box(f());
+        start.elapsed()
+    }).collect();
+    BenchResult::from_samples(name, samples)
+}
+
+
+thread_local! {
+    // Number of live `ScopedTimer`s on this thread, used to indent nested timings
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Logs the time elapsed since its creation when dropped.
+///
+/// Timers created while another one is alive on the same thread are nested: their
+/// messages are indented, so the log shows the breakdown of the outer scope.
+///
+/// # Examples
+/// ```
+/// use dev_utils::{dlog::*, performance::ScopedTimer};
+///
+/// set_max_level(Level::Debug);
+/// {
+///     let _timer = ScopedTimer::new("load config");
+///     // ...
+/// }  // DEBUG load config took 1.20µs
+/// ```
+#[must_use = "the time is logged as soon as the timer is dropped"]
+pub struct ScopedTimer {
+    label: String,
+    level: Level,
+    depth: usize,
+    start: Instant,
+}
+
+impl ScopedTimer {
+    /// Starts a timer that logs at the `Debug` level.
+    pub fn new(label: &str) -> Self {Self::with_level(label, Level::Debug)}
+
+    /// Starts a timer that logs at the given level.
+    pub fn with_level(label: &str, level: Level) -> Self {
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        ScopedTimer { label: label.to_string(), level, depth, start: Instant::now() }
+    }
+
+    /// Returns the time elapsed since the timer was started.
+    pub fn elapsed(&self) -> Duration {self.start.elapsed()}
+}
+
+impl Drop for ScopedTimer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        DEPTH.with(|depth| depth.set(self.depth));
+        dlog::emit(self.level, format_args!("{}{} took {:.2?}", "  ".repeat(self.depth), self.label, elapsed));
+    }
+}
+
+/// Times a block with a [ScopedTimer], evaluating to the block's value.
+///
+/// The elapsed time is logged at the `Debug` level, unless another level is given first.
+///
+/// # Examples
+/// ```
+/// use dev_utils::{time_it, dlog::Level};
+///
+/// let total = time_it!("sum", {
+///     let squares = time_it!(Level::Trace, "squares", {
+///         (1..=10).map(|n| n * n).collect::<Vec<u32>>()
+///     });
+///     squares.iter().sum::<u32>()
+/// });
+/// assert_eq!(total, 385);
+/// ```
+#[macro_export]
+macro_rules! time_it {
+    ($level:expr, $label:expr, $body:block $(,)?) => {{
+        let _timer = $crate::performance::ScopedTimer::with_level($label, $level);
+        $body
+    }};
+    ($label:expr, $body:block $(,)?) => {
+        $crate::time_it!($crate::dlog::Level::Debug, $label, $body)
+    };
+}
+
+/// A labeled lap recorded by a [Stopwatch].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lap {
+    pub label: String,
+    /// The time since the previous lap (or the start).
+    pub duration: Duration,
+    /// The total running time of the stopwatch when the lap was recorded.
+    pub total: Duration,
+}
+
+/// A stopwatch that can be paused and split into labeled laps.
+///
+/// Its `Display` implementation renders the laps as a table, with the share of the total
+/// time spent in each of them.
+///
+/// # Examples
+/// ```
+/// use dev_utils::performance::Stopwatch;
+///
+/// let mut watch = Stopwatch::start_new();
+/// let config = "debug = true".to_string();  // load config
+/// watch.lap("config");
+/// let lines = config.lines().count();        // process
+/// watch.lap("process");
+/// watch.stop();
+///
+/// assert_eq!(watch.laps().len(), 2);
+/// println!("{}", watch);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Stopwatch {
+    started: Option<Instant>,
+    accumulated: Duration,
+    last_lap: Duration,
+    laps: Vec<Lap>,
+}
+
+impl Stopwatch {
+    /// Creates a stopped stopwatch at zero.
+    pub fn new() -> Self {Self::default()}
+
+    /// Creates a stopwatch and starts it.
+    pub fn start_new() -> Self {
+        let mut watch = Self::new();
+        watch.start();
+        watch
+    }
+
+    /// Starts (or resumes) the stopwatch. Does nothing if it is already running.
+    pub fn start(&mut self) {self.start_at(Instant::now())}
+
+    /// Pauses the stopwatch, keeping the elapsed time and the laps.
+    pub fn stop(&mut self) {self.stop_at(Instant::now())}
+
+    /// Records a lap with the time since the previous one, and returns that time.
+    pub fn lap(&mut self, label: &str) -> Duration {self.lap_at(label, Instant::now())}
+
+    /// Stops the stopwatch and clears the elapsed time and the laps.
+    pub fn reset(&mut self) {*self = Self::new()}
+
+    pub fn is_running(&self) -> bool {self.started.is_some()}
+
+    /// Returns the total running time (pauses excluded).
+    pub fn elapsed(&self) -> Duration {self.elapsed_at(Instant::now())}
+
+    pub fn laps(&self) -> &[Lap] {&self.laps}
+
+    fn start_at(&mut self, now: Instant) {
+        self.started.get_or_insert(now);
+    }
+
+    fn stop_at(&mut self, now: Instant) {
+        self.accumulated = self.elapsed_at(now);
+        self.started = None;
+    }
+
+    fn lap_at(&mut self, label: &str, now: Instant) -> Duration {
+        let total = self.elapsed_at(now);
+        let duration = total - self.last_lap;
+        self.last_lap = total;
+        self.laps.push(Lap { label: label.to_string(), duration, total });
+        duration
+    }
+
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        self.accumulated + self.started.map_or(Duration::ZERO, |start| now.saturating_duration_since(start))
+    }
+
+    fn table(&self, total: Duration) -> Table {
+        let share = |d: Duration| match total.is_zero() {
+            true => "-".to_string(),
+            false => format!("{:.1}%", d.as_secs_f64() * 100.0 / total.as_secs_f64()),
+        };
+        let mut table = Table::new(["#", "Lap", "Time", "Total", "%"].map(|h| h.style(Style::Bold)))
+            .border(BorderStyle::Rounded);
+        for (i, lap) in self.laps.iter().enumerate() {
+            table.add_row([(i + 1).to_string(), lap.label.clone(), format!("{:.2?}", lap.duration).color(CYAN), format!("{:.2?}", lap.total), share(lap.duration)]);
+        }
+        table.add_row([String::new(), "total".style(Style::Bold), format!("{:.2?}", total).color(GREEN), String::new(), String::new()]);
+        [0, 2, 3, 4].into_iter().fold(table, |table, column| table.align(column, Alignment::Right))
+    }
+}
+
+impl fmt::Display for Stopwatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.table(self.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics() {
+        let samples = (1..=20).map(Duration::from_millis).collect();
+        let result = BenchResult::from_samples("ms", samples);
+        assert_eq!(result.min, Duration::from_millis(1));
+        assert_eq!(result.max, Duration::from_millis(20));
+        assert_eq!(result.mean, Duration::from_micros(10_500));
+        assert_eq!(result.median, Duration::from_micros(10_500));
+        assert_eq!(result.p95, Duration::from_millis(19));
+        assert_eq!(result.std_dev.as_micros(), 5_766);
+    }
+
+    #[test]
+    fn test_run() {
+        let mut calls = 0;
+        let result = run("count", 50, || calls += 1);
+        assert_eq!(result.iterations, 50);
+        assert_eq!(calls, 55);  // 5 warm-up runs
+        assert!(result.min <= result.median && result.median <= result.p95 && result.p95 <= result.max);
+
+        let slow = BenchResult::from_samples("slow", vec![Duration::from_millis(4)]);
+        let fast = BenchResult::from_samples("fast", vec![Duration::from_millis(1)]);
+        assert_eq!(slow.speedup(&fast), 4.0);
+        assert_eq!(fast.ops_per_sec(), 1000.0);
+    }
+
+    #[test]
+    fn test_scoped_timer() {
+        let logs = dlog::test_capture();
+        let value = time_it!("outer", {
+            time_it!(Level::Info, "inner", {});
+            let _second = ScopedTimer::new("second");
+            7
+        });
+        assert_eq!(value, 7);
+
+        let messages = logs.records().into_iter().map(|r| (r.level, r.message)).collect::<Vec<_>>();
+        assert!(messages[0].1.starts_with("  inner took "));
+        assert_eq!(messages[0].0, Level::Info);
+        assert!(messages[1].1.starts_with("  second took "));
+        assert!(messages[2].1.starts_with("outer took "));
+        assert_eq!(DEPTH.with(Cell::get), 0);
+    }
+
+    #[timed]
+    fn timed_sum(n: u32) -> u32 {(1..=n).map(timed_square).sum()}
+
+    #[timed(level = "TRACE")]
+    fn timed_square(n: u32) -> u32 {n * n}
+
+    #[test]
+    fn test_timed() {
+        let logs = dlog::test_capture();
+        assert_eq!(timed_sum(2), 5);
+
+        let messages = logs.records().into_iter().map(|r| (r.level, r.message)).collect::<Vec<_>>();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].0, Level::Trace);
+        assert!(messages[0].1.starts_with("  timed_square took "));
+        assert_eq!(messages[2].0, Level::Debug);
+        assert!(messages[2].1.starts_with("timed_sum took "));
+    }
+
+    #[test]
+    fn test_stopwatch() {
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+        let mut watch = Stopwatch::new();
+        watch.start_at(t0);
+        assert_eq!(watch.lap_at("load", t0 + ms(10)), ms(10));
+        watch.stop_at(t0 + ms(15));
+        assert_eq!(watch.elapsed_at(t0 + ms(100)), ms(15));  // paused
+        watch.start_at(t0 + ms(100));
+        assert_eq!(watch.lap_at("process", t0 + ms(125)), ms(30));
+        watch.stop_at(t0 + ms(125));
+
+        assert_eq!(watch.laps()[1], Lap { label: "process".into(), duration: ms(30), total: ms(40) });
+        let table = crate::format::strip_ansi_codes(&watch.to_string());
+        assert!(table.contains("│ 1 │ load    │ 10.00ms │ 10.00ms │ 25.0% │"), "{}", table);
+        assert!(table.contains("│ 2 │ process │ 30.00ms │ 40.00ms │ 75.0% │"), "{}", table);
+
+        watch.reset();
+        assert!(watch.laps().is_empty() && !watch.is_running() && watch.elapsed().is_zero());
+    }
+}
@@ -0,0 +1,144 @@
+//! Process-level utilities.
+//!
+//! # Features
+//! - [single_instance] to prevent running the same application twice (watchers, daemons, dashboards)
+//! - [run] and [Cmd] to run other tools: captured or streamed output (through dlog),
+//!   timeouts and environment overrides
+//! - [cmd] and [Pipeline] to chain commands with `|` and redirect their ends to files
+//!
+//! # Examples
+//! ```
+//! use dev_utils::proc::{single_instance, InstanceError};
+//!
+//! let _lock = match single_instance("my_daemon_doc") {
+//!     Ok(lock) => lock,  // held until dropped (or until the process exits)
+//!     Err(InstanceError::AlreadyRunning { pid }) => panic!("already running (PID {:?})", pid),
+//!     Err(err) => panic!("{}", err),
+//! };
+//! ```
+use std::fmt;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+mod command;
+pub use command::{run, run_streamed, Cmd, Output, ProcError};
+mod pipeline;
+pub use pipeline::{cmd, Pipeline};
+
+/// Represents errors that can occur when acquiring a [single_instance] lock.
+#[derive(Debug)]
+pub enum InstanceError {
+    /// Another instance holds the lock (its PID, if it could be read).
+    AlreadyRunning { pid: Option<u32> },
+    /// The application name is empty or contains path separators.
+    InvalidName(String),
+    /// Represents an IO error from the standard library.
+    Io(io::Error),
+}
+
+impl fmt::Display for InstanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstanceError::AlreadyRunning { pid: Some(pid) } => write!(f, "Another instance is already running (PID {})", pid),
+            InstanceError::AlreadyRunning { pid: None } => write!(f, "Another instance is already running"),
+            InstanceError::InvalidName(name) => write!(f, "Invalid application name: {:?}", name),
+            InstanceError::Io(err) => write!(f, "IO error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for InstanceError {}
+
+impl From<io::Error> for InstanceError {
+    fn from(err: io::Error) -> Self {InstanceError::Io(err)}
+}
+
+/// Guard of a [single_instance] lock.
+///
+/// The lock is released when the guard is dropped, and by the OS if the process dies,
+/// so a crashed instance never leaves a stale lock behind.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Returns the path of the lock file.
+    pub fn path(&self) -> &Path {&self.path}
+}
+
+/// Returns the directory for runtime files such as lock files.
+///
+/// This is `$XDG_RUNTIME_DIR` when set, or the system temporary directory otherwise.
+pub fn runtime_dir() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => std::env::temp_dir(),
+    }
+}
+
+/// Ensures that only one instance of an application runs at a time.
+///
+/// An exclusive lock is taken on `<runtime_dir>/<app_name>.lock` (see [runtime_dir]), which
+/// then stores the PID of the owner.
+///
+/// # Arguments
+///
+/// * `app_name` - The name identifying the application (used as the lock file name)
+///
+/// # Returns
+///
+/// Returns a `Result` containing the [InstanceLock] guard, or
+/// [InstanceError::AlreadyRunning] with the PID of the running instance.
+pub fn single_instance(app_name: &str) -> Result<InstanceLock, InstanceError> {
+    if app_name.is_empty() || app_name.contains(['/', '\\']) || app_name == "." || app_name == ".." {
+        return Err(InstanceError::InvalidName(app_name.to_string()));
+    }
+    let path = runtime_dir().join(format!("{}.lock", app_name));
+    let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+
+    match file.try_lock() {
+        Ok(()) => {
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            write!(file, "{}", std::process::id())?;
+            file.flush()?;
+            Ok(InstanceLock { _file: file, path })
+        },
+        Err(TryLockError::WouldBlock) => {
+            let mut content = String::new();
+            let pid = file.read_to_string(&mut content).ok().and_then(|_| content.trim().parse().ok());
+            Err(InstanceError::AlreadyRunning { pid })
+        },
+        Err(TryLockError::Error(err)) => Err(InstanceError::Io(err)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_instance() {
+        let name = format!("dev_utils_test_{}", std::process::id());
+        let lock = single_instance(&name).unwrap();
+        assert!(lock.path().exists());
+
+        match single_instance(&name) {
+            Err(InstanceError::AlreadyRunning { pid }) => assert_eq!(pid, Some(std::process::id())),
+            other => panic!("expected AlreadyRunning, got {:?}", other),
+        }
+        drop(lock);
+        let lock = single_instance(&name).unwrap();
+        std::fs::remove_file(lock.path()).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_name() {
+        assert!(matches!(single_instance(""), Err(InstanceError::InvalidName(_))));
+        assert!(matches!(single_instance("../etc"), Err(InstanceError::InvalidName(_))));
+    }
+}
@@ -0,0 +1,354 @@
+// Running subprocesses: captured or streamed output, timeouts and environment overrides.
+use std::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::dlog::{self, Level};
+use crate::format::{Stylize, CYAN};
+
+/// The result of a finished command.
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// The command line, for messages (`cargo build --release`).
+    pub command: String,
+    pub status: ExitStatus,
+    /// Everything the command wrote to its standard output (invalid UTF-8 is replaced).
+    pub stdout: String,
+    /// Everything the command wrote to its standard error.
+    pub stderr: String,
+    /// How long the command ran.
+    pub duration: Duration,
+}
+
+impl Output {
+    /// Returns `true` if the command exited with a success status.
+    pub fn success(&self) -> bool {self.status.success()}
+
+    /// Returns the exit code, or `None` if the command was killed by a signal.
+    pub fn code(&self) -> Option<i32> {self.status.code()}
+}
+
+/// Represents errors that can occur when running a command.
+#[derive(Debug)]
+pub enum ProcError {
+    /// The program could not be started (not found, not executable...).
+    Spawn { command: String, source: io::Error },
+    /// The command exited with a failure status. Its output is kept, to show what went wrong.
+    Failed(Box<Output>),
+    /// The command ran longer than its timeout and was killed.
+    Timeout { command: String, timeout: Duration },
+    /// Represents an IO error from the standard library.
+    Io(io::Error),
+}
+
+impl fmt::Display for ProcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcError::Spawn { command, source } => write!(f, "Failed to start `{}`: {}", command, source),
+            ProcError::Failed(output) => {
+                match output.code() {
+                    Some(code) => write!(f, "`{}` failed with exit code {}", output.command, code)?,
+                    None => write!(f, "`{}` was terminated by a signal", output.command)?,
+                }
+                match output.stderr.trim_end().lines().last() {
+                    Some(last) => write!(f, ": {}", last.trim()),
+                    None => Ok(()),
+                }
+            },
+            ProcError::Timeout { command, timeout } => write!(f, "`{}` timed out after {:?}", command, timeout),
+            ProcError::Io(err) => write!(f, "IO error: {}", err),
+        }
+    }
+}
+
+impl Error for ProcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProcError::Spawn { source, .. } => Some(source),
+            ProcError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ProcError {
+    fn from(err: io::Error) -> Self {ProcError::Io(err)}
+}
+
+/// A command to run, configured with a builder.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use dev_utils::proc::Cmd;
+///
+/// let output = Cmd::new("cargo")
+///     .args(["test", "--workspace"])
+///     .env("RUST_BACKTRACE", "1")
+///     .timeout(Duration::from_secs(600))
+///     .run_streamed()  // logs every line as `[cargo] ...`
+///     .unwrap();
+/// println!("tests took {:?}", output.duration);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cmd {
+    program: OsString,
+    args: Vec<OsString>,
+    env: Vec<(OsString, Option<OsString>)>,
+    dir: Option<PathBuf>,
+    timeout: Option<Duration>,
+    prefix: Option<String>,
+}
+
+impl Cmd {
+    /// Creates a command that runs `program` (looked up in the `PATH`), without arguments.
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Self {
+        Cmd { program: program.as_ref().to_os_string(), args: Vec::new(), env: Vec::new(), dir: None, timeout: None, prefix: None }
+    }
+
+    /// Adds an argument (passed as is: no shell quoting or expansion).
+    pub fn arg<S: AsRef<OsStr>>(mut self, arg: S) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds several arguments.
+    pub fn args<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(mut self, args: I) -> Self {
+        self.args.extend(args.into_iter().map(|arg| arg.as_ref().to_os_string()));
+        self
+    }
+
+    /// Sets an environment variable for the command (the others are inherited).
+    pub fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(mut self, key: K, value: V) -> Self {
+        self.env.push((key.as_ref().to_os_string(), Some(value.as_ref().to_os_string())));
+        self
+    }
+
+    /// Removes an inherited environment variable.
+    pub fn env_remove<K: AsRef<OsStr>>(mut self, key: K) -> Self {
+        self.env.push((key.as_ref().to_os_string(), None));
+        self
+    }
+
+    /// Sets the working directory of the command.
+    pub fn current_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Kills the command if it runs longer than `timeout` ([ProcError::Timeout]).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the prefix of the lines logged by [Cmd::run_streamed] (the program name by default).
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Runs the command and waits for it, capturing its output.
+    ///
+    /// # Returns
+    /// A `Result` containing the [Output], or a [ProcError] if the command could not start,
+    /// timed out or exited with a failure status.
+    pub fn run(&self) -> Result<Output, ProcError> {self.execute(false)}
+
+    /// Like [Cmd::run], also logging each line of output through [dlog](crate::dlog) as it
+    /// arrives: standard output at the `Info` level, standard error at the `Warn` level,
+    /// after a `[prefix]`.
+    pub fn run_streamed(&self) -> Result<Output, ProcError> {self.execute(true)}
+
+    // The std command with the program, arguments, environment and directory (not the pipes)
+    pub(super) fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        for (key, value) in &self.env {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        if let Some(dir) = &self.dir {command.current_dir(dir);}
+        command
+    }
+
+    fn execute(&self, stream: bool) -> Result<Output, ProcError> {
+        let start = Instant::now();
+        let mut command = self.command();
+        command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut child = command.spawn().map_err(|source| ProcError::Spawn { command: self.to_string(), source })?;
+
+        // the pipes are read on their own threads, and the chunks handled here (where the
+        // logs go to the caller's `dlog::test_capture`, and the timeout is checked)
+        let (sender, receiver) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {forward(stdout, 0, sender.clone());}
+        if let Some(stderr) = child.stderr.take() {forward(stderr, 1, sender);}
+        let deadline = self.timeout.map(|timeout| start + timeout);
+        let (mut captured, mut partial) = ([Vec::new(), Vec::new()], [Vec::new(), Vec::new()]);
+        loop {
+            let message = match deadline {
+                Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match message {
+                Ok((pipe, chunk)) => {
+                    captured[pipe].extend_from_slice(&chunk);
+                    if stream {
+                        partial[pipe].extend_from_slice(&chunk);
+                        while let Some(end) = partial[pipe].iter().position(|&b| b == b'\n') {
+                            let line: Vec<u8> = partial[pipe].drain(..=end).collect();
+                            self.log_line(pipe, &line);
+                        }
+                    }
+                },
+                Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => return Err(self.kill(&mut child)),
+            }
+        }
+        for (pipe, rest) in partial.iter().enumerate().filter(|(_, rest)| !rest.is_empty()) {self.log_line(pipe, rest);}
+
+        // the pipes may close before the process exits
+        let status = loop {
+            match (child.try_wait()?, deadline) {
+                (Some(status), _) => break status,
+                (None, Some(deadline)) if Instant::now() >= deadline => return Err(self.kill(&mut child)),
+                (None, _) => thread::sleep(Duration::from_millis(5)),
+            }
+        };
+        let [stdout, stderr] = captured.map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+        let output = Output { command: self.to_string(), status, stdout, stderr, duration: start.elapsed() };
+        match output.success() {
+            true => Ok(output),
+            false => Err(ProcError::Failed(Box::new(output))),
+        }
+    }
+
+    fn log_line(&self, pipe: usize, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let program = Path::new(&self.program).file_stem().unwrap_or(&self.program).to_string_lossy();
+        let prefix = format!("[{}]", self.prefix.as_deref().unwrap_or(&program));
+        let level = if pipe == 0 {Level::Info} else {Level::Warn};
+        dlog::emit(level, format_args!("{} {}", prefix.color(CYAN), line.trim_end_matches(['\n', '\r'])));
+    }
+
+    fn kill(&self, child: &mut Child) -> ProcError {
+        let _ = child.kill();
+        let _ = child.wait();
+        ProcError::Timeout { command: self.to_string(), timeout: self.timeout.unwrap_or_default() }
+    }
+}
+
+// Sends what `pipe` yields to `sender`, from a new thread, until the end of the stream
+pub(super) fn forward<R: Read + Send + 'static>(mut pipe: R, index: usize, sender: Sender<(usize, Vec<u8>)>) {
+    thread::spawn(move || {
+        let mut buffer = [0; 8192];
+        loop {
+            match pipe.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => if sender.send((index, buffer[..n].to_vec())).is_err() {break;},
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+impl fmt::Display for Cmd {
+    // The command line, quoting the arguments with spaces or quotes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program.to_string_lossy())?;
+        for arg in &self.args {
+            let arg = arg.to_string_lossy();
+            match arg.is_empty() || arg.contains([' ', '\t', '\n', '"', '\'']) {
+                true => write!(f, " '{}'", arg.replace('\'', r"'\''"))?,
+                false => write!(f, " {}", arg)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs a program with arguments and captures its output (see [Cmd::run]).
+///
+/// # Examples
+/// ```no_run
+/// use dev_utils::proc::run;
+///
+/// let output = run("git", ["rev-parse", "--short", "HEAD"]).unwrap();
+/// println!("commit {}", output.stdout.trim());
+/// ```
+pub fn run<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(program: &str, args: I) -> Result<Output, ProcError> {
+    Cmd::new(program).args(args).run()
+}
+
+/// Runs a program with arguments, logging its output line by line (see [Cmd::run_streamed]).
+pub fn run_streamed<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(program: &str, args: I) -> Result<Output, ProcError> {
+    Cmd::new(program).args(args).run_streamed()
+}
+
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::format::strip_ansi_codes;
+
+    fn sh(script: &str) -> Cmd {Cmd::new("sh").args(["-c", script])}
+
+    #[test]
+    fn test_run() {
+        let output = run("sh", ["-c", "echo out; echo err >&2"]).unwrap();
+        assert_eq!((output.stdout.as_str(), output.stderr.as_str()), ("out\n", "err\n"));
+        assert_eq!(output.code(), Some(0));
+        assert_eq!(output.command, "sh -c 'echo out; echo err >&2'");
+
+        match run("sh", ["-c", "echo partial; echo 'no such file' >&2; exit 3"]) {
+            Err(ProcError::Failed(output)) => {
+                assert_eq!((output.code(), output.stdout.as_str()), (Some(3), "partial\n"));
+                assert_eq!(ProcError::Failed(output).to_string(), "`sh -c 'echo partial; echo '\\''no such file'\\'' >&2; exit 3'` failed with exit code 3: no such file");
+            },
+            other => panic!("expected a failure, got {:?}", other),
+        }
+        let error = run("dev-utils-no-such-program", [""; 0]).unwrap_err();
+        assert!(matches!(error, ProcError::Spawn { .. }) && error.source().is_some());
+    }
+
+    #[test]
+    fn test_env_and_dir() {
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let output = sh("echo \"$GREETING $HOME\"; pwd").env("GREETING", "hi").env_remove("HOME").current_dir(&dir).run().unwrap();
+        assert_eq!(output.stdout, format!("hi \n{}\n", dir.display()));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let start = Instant::now();
+        let error = Cmd::new("sleep").arg("5").timeout(Duration::from_millis(100)).run().unwrap_err();
+        assert!(matches!(error, ProcError::Timeout { timeout, .. } if timeout == Duration::from_millis(100)));
+        assert!(start.elapsed() < Duration::from_secs(3));
+        assert_eq!(error.to_string(), "`sleep 5` timed out after 100ms");
+        assert!(sh("exit 0").timeout(Duration::from_secs(5)).run().is_ok());
+    }
+
+    #[test]
+    fn test_run_streamed() {
+        let logs = dlog::test_capture();
+        let output = sh("echo one; echo two; echo oops >&2; printf last").prefix("build").run_streamed().unwrap();
+        assert_eq!(output.stdout, "one\ntwo\nlast");
+        let messages: Vec<(Level, String)> = logs.records().into_iter().map(|r| (r.level, strip_ansi_codes(&r.message))).collect();
+        assert_eq!(messages.iter().filter(|(level, _)| *level == Level::Info).count(), 3);
+        assert!(messages.contains(&(Level::Info, "[build] two".to_string())));
+        assert!(messages.contains(&(Level::Info, "[build] last".to_string())));
+        assert!(messages.contains(&(Level::Warn, "[build] oops".to_string())));
+
+        logs.clear();
+        run_streamed("sh", ["-c", "echo default"]).unwrap();
+        assert_eq!(strip_ansi_codes(&logs.records()[0].message), "[sh] default");
+    }
+}
@@ -0,0 +1,272 @@
+// Shell-style pipelines of commands (`cmd("a") | cmd("b")`), with redirections.
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::ops::BitOr;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::time::Instant;
+use super::command::{forward, Cmd, Output, ProcError};
+
+/// Creates a [Cmd] from a command line, split into words like a shell does.
+///
+/// Words are separated by whitespace; single quotes keep their content as is, double
+/// quotes too except for `\"`, `\\` and `\$`, and a backslash outside quotes escapes the
+/// next character. There is no expansion (variables, globs, `~`) and no operators:
+/// use [Cmd]'s methods and `|` (see [Pipeline]) instead.
+///
+/// # Examples
+/// ```
+/// use dev_utils::proc::cmd;
+///
+/// assert_eq!(cmd("git commit -m 'first commit'").to_string(), "git commit -m 'first commit'");
+/// ```
+pub fn cmd(command_line: &str) -> Cmd {
+    let words = split_words(command_line);
+    let (program, args) = words.split_first().map(|(p, a)| (p.as_str(), a)).unwrap_or(("", &[]));
+    Cmd::new(program).args(args)
+}
+
+// The words of a command line (an unterminated quote runs to the end)
+fn split_words(line: &str) -> Vec<String> {
+    let (mut words, mut word, mut in_word) = (Vec::new(), String::new(), false);
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {words.push(std::mem::take(&mut word));}
+                in_word = false;
+                continue;
+            },
+            '\'' => chars.by_ref().take_while(|&c| c != '\'').for_each(|c| word.push(c)),
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '$')) => word.push(escaped),
+                            Some(other) => word.extend(['\\', other]),
+                            None => word.push('\\'),
+                        },
+                        c => word.push(c),
+                    }
+                }
+            },
+            '\\' => word.extend(chars.next()),
+            c => word.push(c),
+        }
+        in_word = true;
+    }
+    if in_word {words.push(word);}
+    words
+}
+
+/// Commands connected by pipes: the output of each stage is the input of the next one.
+///
+/// Build one with `|` between [Cmd]s, then redirect its ends with [Pipeline::read_from],
+/// [Pipeline::write_to] and [Pipeline::append_to] (Rust has no `<` and `>` operators to
+/// overload for this). All the stages run at the same time.
+///
+/// Like `set -o pipefail`, the pipeline fails if any stage fails: the error is the one of
+/// the first failing stage, with its own output. A stage killed by `SIGPIPE` because a
+/// later one stopped reading (`yes | head -1`) doesn't count as a failure.
+///
+/// # Examples
+/// ```no_run
+/// use dev_utils::proc::cmd;
+///
+/// // cat Cargo.toml | grep version | wc -l > count.txt
+/// (cmd("cat Cargo.toml") | cmd("grep version") | cmd("wc -l")).write_to("count.txt").run().unwrap();
+///
+/// let todos = (cmd("git ls-files") | cmd("xargs grep -n TODO")).run().unwrap();
+/// println!("{}", todos.stdout);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    stages: Vec<Cmd>,
+    input: Option<PathBuf>,
+    output: Option<(PathBuf, bool)>,
+}
+
+impl Pipeline {
+    /// Reads the standard input of the first stage from a file (`< path`).
+    pub fn read_from<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.input = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Writes the standard output of the last stage to a file, truncating it (`> path`).
+    pub fn write_to<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.output = Some((path.as_ref().to_path_buf(), false));
+        self
+    }
+
+    /// Appends the standard output of the last stage to a file (`>> path`).
+    pub fn append_to<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.output = Some((path.as_ref().to_path_buf(), true));
+        self
+    }
+
+    /// Runs every stage and waits for all of them.
+    ///
+    /// # Returns
+    /// A `Result` containing the [Output] of the pipeline (the status and standard output
+    /// of the last stage, empty if redirected, and the standard error of all stages), or the
+    /// [ProcError] of the first stage that could not start or failed.
+    pub fn run(&self) -> Result<Output, ProcError> {
+        let start = Instant::now();
+        let (sender, receiver) = mpsc::channel();
+        let mut children: Vec<Child> = Vec::with_capacity(self.stages.len());
+        let mut previous = None;
+        for (i, stage) in self.stages.iter().enumerate() {
+            let last = i + 1 == self.stages.len();
+            let stdin = match (previous.take(), &self.input) {
+                (Some(stdout), _) => Stdio::from(stdout),
+                (None, Some(path)) => Stdio::from(File::open(path).inspect_err(|_| kill_all(&mut children))?),
+                (None, None) => Stdio::null(),
+            };
+            let stdout = match (&self.output, last) {
+                (Some((path, append)), true) => {
+                    let file = OpenOptions::new().create(true).write(true).append(*append).truncate(!*append).open(path);
+                    Stdio::from(file.inspect_err(|_| kill_all(&mut children))?)
+                },
+                _ => Stdio::piped(),
+            };
+            let mut command = stage.command();
+            command.stdin(stdin).stdout(stdout).stderr(Stdio::piped());
+            let mut child = command.spawn().map_err(|source| {
+                kill_all(&mut children);
+                ProcError::Spawn { command: stage.to_string(), source }
+            })?;
+            match last {
+                true => if let Some(stdout) = child.stdout.take() {forward(stdout, 0, sender.clone());},
+                false => previous = child.stdout.take(),
+            }
+            // the standard error of stage `i` goes to slot `i + 1`
+            if let Some(stderr) = child.stderr.take() {forward(stderr, i + 1, sender.clone());}
+            children.push(child);
+        }
+        drop(sender);
+
+        let mut captured = vec![Vec::new(); self.stages.len() + 1];
+        for (slot, chunk) in receiver {captured[slot].extend_from_slice(&chunk);}
+        let statuses = children.iter_mut().map(|child| child.wait()).collect::<Result<Vec<_>, _>>()?;
+        let text = |slot: usize| String::from_utf8_lossy(&captured[slot]).into_owned();
+
+        let last = self.stages.len() - 1;
+        let ok = |i: usize, status: &ExitStatus| status.success() || (i < last && killed_by_sigpipe(status));
+        let failed = statuses.iter().enumerate().find(|&(i, status)| !ok(i, status));
+        if let Some((i, &status)) = failed {
+            let stdout = if i == last {text(0)} else {String::new()};
+            let output = Output { command: self.stages[i].to_string(), status, stdout, stderr: text(i + 1), duration: start.elapsed() };
+            return Err(ProcError::Failed(Box::new(output)));
+        }
+        Ok(Output {
+            command: self.to_string(),
+            status: statuses[last],
+            stdout: text(0),
+            stderr: (1..captured.len()).map(text).collect(),
+            duration: start.elapsed(),
+        })
+    }
+}
+
+fn kill_all(children: &mut [Child]) {
+    for child in children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+#[cfg(unix)]
+fn killed_by_sigpipe(status: &ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal() == Some(13)
+}
+
+#[cfg(not(unix))]
+fn killed_by_sigpipe(_status: &ExitStatus) -> bool {false}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stages: Vec<String> = self.stages.iter().map(Cmd::to_string).collect();
+        write!(f, "{}", stages.join(" | "))?;
+        if let Some(input) = &self.input {write!(f, " < {}", input.display())?;}
+        match &self.output {
+            Some((path, false)) => write!(f, " > {}", path.display()),
+            Some((path, true)) => write!(f, " >> {}", path.display()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<Cmd> for Pipeline {
+    fn from(cmd: Cmd) -> Self {Pipeline { stages: vec![cmd], input: None, output: None }}
+}
+
+impl BitOr<Cmd> for Cmd {
+    type Output = Pipeline;
+    fn bitor(self, next: Cmd) -> Pipeline {Pipeline::from(self) | next}
+}
+
+impl BitOr<Cmd> for Pipeline {
+    type Output = Pipeline;
+    fn bitor(mut self, next: Cmd) -> Pipeline {
+        self.stages.push(next);
+        self
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(split_words("  grep -rn 'fn main' src  "), ["grep", "-rn", "fn main", "src"]);
+        assert_eq!(split_words(r#"echo "a \"quoted\" \$HOME \n" it\'s '' x"#), ["echo", r#"a "quoted" $HOME \n"#, "it's", "", "x"]);
+        assert_eq!(split_words("a'b'\"c\"d"), ["abcd"]);
+        assert_eq!(split_words("unterminated 'quote here"), ["unterminated", "quote here"]);
+        assert!(split_words("   ").is_empty());
+    }
+
+    #[cfg(all(unix, feature = "file"))]
+    #[test]
+    fn test_pipeline() {
+        use crate::file::{read, TempDir};
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.join("in.txt"), "banana\napple\ncherry\napple pie\n").unwrap();
+
+        let output = (cmd("grep apple") | cmd("sort -r")).read_from(tmp.join("in.txt")).run().unwrap();
+        assert_eq!(output.stdout, "apple pie\napple\n");
+        assert_eq!(output.command, format!("grep apple | sort -r < {}", tmp.join("in.txt").display()));
+
+        let to_file = (cmd("printf 'b\\na\\n'") | cmd("sort")).write_to(tmp.join("out.txt"));
+        to_file.run().unwrap();
+        to_file.clone().append_to(tmp.join("out.txt")).run().unwrap();
+        assert_eq!(read(tmp.join("out.txt")).unwrap(), "a\nb\na\nb\n");
+
+        // a later stage that stops reading early doesn't fail the pipeline
+        assert_eq!((cmd("yes") | cmd("head -n 2")).run().unwrap().stdout, "y\ny\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipeline_errors() {
+        let error = (cmd("sh -c 'echo broken >&2; exit 2'") | cmd("cat") | cmd("wc -l")).run().unwrap_err();
+        match &error {
+            ProcError::Failed(output) => assert_eq!((output.code(), output.stderr.as_str()), (Some(2), "broken\n")),
+            other => panic!("expected a failure, got {:?}", other),
+        }
+        assert_eq!(error.to_string(), "`sh -c 'echo broken >&2; exit 2'` failed with exit code 2: broken");
+
+        // grep exits with 1 when nothing matches
+        let error = (cmd("echo nothing") | cmd("grep something")).run().unwrap_err();
+        assert!(error.to_string().starts_with("`grep something` failed with exit code 1"));
+
+        let error = (cmd("echo a") | cmd("dev-utils-no-such-program")).run().unwrap_err();
+        assert!(matches!(error, ProcError::Spawn { command, .. } if command == "dev-utils-no-such-program"));
+        assert!(matches!(Pipeline::from(cmd("cat")).read_from("/no/such/file").run(), Err(ProcError::Io(_))));
+    }
+}
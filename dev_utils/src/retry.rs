@@ -0,0 +1,177 @@
+//! Retrying fallible operations with a backoff between attempts.
+//!
+//! A [RetryPolicy] sets the number of attempts and how long to wait between them
+//! (fixed, linear or exponential [Backoff], optionally randomized with jitter).
+//! Failed attempts are logged through [dlog](crate::dlog).
+//!
+//! # Examples
+//! ```
+//! use std::time::Duration;
+//! use dev_utils::retry::{retry, RetryPolicy};
+//!
+//! let policy = RetryPolicy::exponential(Duration::from_millis(1)).max_attempts(4).jitter(0.2);
+//! let mut calls = 0;
+//! let result = retry(&policy, || {
+//!     calls += 1;
+//!     if calls < 3 {Err("service unavailable")} else {Ok(calls)}
+//! });
+//! assert_eq!(result, Ok(3));
+//! ```
+use std::collections::hash_map::RandomState;
+use std::fmt::Display;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+use crate::dlog::{self, Level};
+
+/// How the delay grows between attempts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    /// The same delay after every attempt.
+    Fixed(Duration),
+    /// `initial`, then `initial + step`, `initial + 2 * step`, ...
+    Linear { initial: Duration, step: Duration },
+    /// `initial`, then multiplied by `factor` after every attempt.
+    Exponential { initial: Duration, factor: f64 },
+}
+
+/// The number of attempts and the delays used by [retry].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    backoff: Backoff,
+    max_attempts: usize,
+    max_delay: Duration,
+    jitter: f64,
+}
+
+impl RetryPolicy {
+    /// Creates a policy from a [Backoff], with 3 attempts, no jitter and delays capped at 1 minute.
+    pub fn new(backoff: Backoff) -> Self {
+        RetryPolicy { backoff, max_attempts: 3, max_delay: Duration::from_secs(60), jitter: 0.0 }
+    }
+
+    /// Waits `delay` between attempts.
+    pub fn fixed(delay: Duration) -> Self {Self::new(Backoff::Fixed(delay))}
+
+    /// Waits `initial`, then `step` longer after every attempt.
+    pub fn linear(initial: Duration, step: Duration) -> Self {Self::new(Backoff::Linear { initial, step })}
+
+    /// Waits `initial`, then twice as long after every attempt.
+    pub fn exponential(initial: Duration) -> Self {Self::new(Backoff::Exponential { initial, factor: 2.0 })}
+
+    /// Sets the total number of attempts (including the first one, at least 1).
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Caps the delay between attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Randomizes every delay by up to `fraction` of it (e.g. `0.1` for ±10%), so that
+    /// clients failing together don't retry in lockstep.
+    pub fn jitter(mut self, fraction: f64) -> Self {
+        self.jitter = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the delay (without jitter) to wait after the given failed attempt (1-based).
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let n = attempt.saturating_sub(1) as u32;
+        let delay = match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Linear { initial, step } => initial.saturating_add(step.saturating_mul(n)),
+            Backoff::Exponential { initial, factor } => {
+                let secs = initial.as_secs_f64() * factor.powi(n.min(i32::MAX as u32) as i32);
+                Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+            },
+        };
+        delay.min(self.max_delay)
+    }
+
+    fn jittered_delay(&self, attempt: usize) -> Duration {
+        let delay = self.delay(attempt);
+        match self.jitter {
+            0.0 => delay,
+            jitter => delay.mul_f64((1.0 + jitter * (2.0 * random_unit() - 1.0)).max(0.0)),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts with an exponential backoff starting at 100ms.
+    fn default() -> Self {Self::exponential(Duration::from_millis(100))}
+}
+
+// A random number in [0, 1), from the randomly seeded hasher of the standard library
+fn random_unit() -> f64 {
+    (RandomState::new().build_hasher().finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Calls `f` until it succeeds or the policy runs out of attempts, sleeping between attempts.
+///
+/// Every failed attempt is logged as a warning, and the final failure as an error.
+///
+/// # Arguments
+///
+/// * `policy` - The number of attempts and the delays between them
+/// * `f` - The operation to retry
+///
+/// # Returns
+///
+/// Returns the first `Ok` value, or the error of the last attempt.
+pub fn retry<T, E: Display, F: FnMut() -> Result<T, E>>(policy: &RetryPolicy, mut f: F) -> Result<T, E> {
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= policy.max_attempts => {
+                dlog::emit(Level::Error, format_args!("Attempt {}/{} failed: {}; giving up", attempt, policy.max_attempts, err));
+                return Err(err);
+            },
+            Err(err) => {
+                let delay = policy.jittered_delay(attempt);
+                dlog::emit(Level::Warn, format_args!("Attempt {}/{} failed: {}; retrying in {:.2?}", attempt, policy.max_attempts, err, delay));
+                thread::sleep(delay);
+                attempt += 1;
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays() {
+        let ms = Duration::from_millis;
+        let linear = RetryPolicy::linear(ms(100), ms(50));
+        assert_eq!((1..=3).map(|n| linear.delay(n)).collect::<Vec<_>>(), vec![ms(100), ms(150), ms(200)]);
+
+        let exponential = RetryPolicy::exponential(ms(100)).max_delay(ms(500));
+        assert_eq!((1..=4).map(|n| exponential.delay(n)).collect::<Vec<_>>(), vec![ms(100), ms(200), ms(400), ms(500)]);
+        assert_eq!(exponential.delay(10_000), ms(500));
+
+        let jittered = RetryPolicy::fixed(ms(100)).jitter(0.5);
+        assert!((0..20).map(|_| jittered.jittered_delay(1)).all(|d| d >= ms(50) && d <= ms(150)));
+    }
+
+    #[test]
+    fn test_retry() {
+        let logs = dlog::test_capture();
+        let policy = RetryPolicy::fixed(Duration::ZERO).max_attempts(3);
+
+        let mut calls = 0;
+        assert_eq!(retry(&policy, || {calls += 1; if calls < 2 {Err("flaky")} else {Ok(calls)}}), Ok(2));
+        logs.assert_logged(Level::Warn, "Attempt 1/3 failed: flaky");
+
+        let mut calls = 0;
+        assert_eq!(retry(&policy, || {calls += 1; Err::<(), _>(format!("down {}", calls))}), Err("down 3".to_string()));
+        logs.assert_logged(Level::Error, "Attempt 3/3 failed: down 3; giving up");
+    }
+}
@@ -0,0 +1,313 @@
+//! Graceful shutdown on `Ctrl+C`.
+//!
+//! [on_ctrl_c] registers a handler for `Ctrl+C` (`SIGINT`, and `SIGTERM` on unix; the console
+//! close events on Windows), and [ctrl_c_token] returns a [ShutdownToken] that is triggered by
+//! it. Long-running loops check a token to stop cleanly, dropping their guards (raw mode,
+//! hidden cursor, alternate screen) instead of leaving the terminal broken:
+//! - [DevServer::serve_until](crate::http::DevServer::serve_until)
+//! - [file::watch_until](crate::file::watch_until)
+//! - `input_capture::record_until`, where `Ctrl+C` is read as a key in raw mode (see [interrupt])
+//!
+//! Handlers run on a dedicated thread, not in the signal handler, so they can lock, log
+//! and allocate. The first `Ctrl+C` triggers the token and calls the handlers; a second one,
+//! while the program is still shutting down, [resets](crate::console::term::reset) the
+//! terminal and exits at once with code 130.
+//!
+//! # Examples
+//! ```no_run
+//! use std::time::Duration;
+//! use dev_utils::signals;
+//!
+//! signals::on_ctrl_c(|| println!("\nshutting down...")).unwrap();
+//! let shutdown = signals::ctrl_c_token().unwrap();
+//! while !shutdown.is_shutdown() {
+//!     // some work...
+//!     shutdown.wait_timeout(Duration::from_secs(1));  // returns early on Ctrl+C
+//! }
+//! println!("bye!");
+//! ```
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::console::term;
+
+/// A flag to ask loops and threads to stop, shared by its clones.
+///
+/// Get the one triggered by `Ctrl+C` from [ctrl_c_token], or create independent ones with
+/// [ShutdownToken::new] (e.g. to stop a server from a test).
+///
+/// # Examples
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use dev_utils::signals::ShutdownToken;
+///
+/// let token = ShutdownToken::new();
+/// let worker = {
+///     let token = token.clone();
+///     thread::spawn(move || {
+///         let mut ticks = 0;
+///         while !token.wait_timeout(Duration::from_millis(5)) {ticks += 1;}
+///         ticks
+///     })
+/// };
+/// thread::sleep(Duration::from_millis(20));
+/// token.shutdown();
+/// assert!(worker.join().unwrap() > 0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownToken {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl ShutdownToken {
+    /// Creates a token that is not triggered.
+    pub fn new() -> Self {Self::default()}
+
+    /// Triggers the token, waking every thread waiting on it.
+    pub fn shutdown(&self) {
+        let (flag, condvar) = &*self.inner;
+        *flag.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        condvar.notify_all();
+    }
+
+    /// Returns whether the token was triggered.
+    pub fn is_shutdown(&self) -> bool {*self.inner.0.lock().unwrap_or_else(|e| e.into_inner())}
+
+    /// Blocks until the token is triggered.
+    pub fn wait(&self) {
+        let (flag, condvar) = &*self.inner;
+        let guard = flag.lock().unwrap_or_else(|e| e.into_inner());
+        drop(condvar.wait_while(guard, |shutdown| !*shutdown).unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Sleeps for `timeout`, or less if the token is triggered in the meantime.
+    ///
+    /// # Returns
+    /// `true` if the token was triggered.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (flag, condvar) = &*self.inner;
+        let deadline = Instant::now() + timeout;
+        let mut shutdown = flag.lock().unwrap_or_else(|e| e.into_inner());
+        while !*shutdown {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {break;}
+            shutdown = condvar.wait_timeout(shutdown, left).unwrap_or_else(|e| e.into_inner()).0;
+        }
+        *shutdown
+    }
+}
+
+type Handler = Arc<dyn Fn() + Send + Sync>;
+
+static HANDLERS: Mutex<Vec<Handler>> = Mutex::new(Vec::new());
+// Whether the OS handler is installed (set once it is)
+static INSTALLED: Mutex<bool> = Mutex::new(false);
+
+fn global_token() -> &'static ShutdownToken {
+    static TOKEN: OnceLock<ShutdownToken> = OnceLock::new();
+    TOKEN.get_or_init(ShutdownToken::new)
+}
+
+fn install() -> io::Result<()> {
+    let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+    if !*installed {
+        sys::install()?;
+        *installed = true;
+    }
+    Ok(())
+}
+
+/// Calls `handler` on every `Ctrl+C`, instead of terminating the process.
+///
+/// The handler runs on a dedicated thread, after the [ctrl_c_token] is triggered.
+/// Handlers are called in the order they were registered.
+///
+/// # Returns
+/// An `io::Error` if the signal handler can't be installed (or on platforms without one).
+pub fn on_ctrl_c<F: Fn() + Send + Sync + 'static>(handler: F) -> io::Result<()> {
+    install()?;
+    HANDLERS.lock().unwrap_or_else(|e| e.into_inner()).push(Arc::new(handler));
+    Ok(())
+}
+
+/// Returns the token triggered by the first `Ctrl+C` (installing the signal handler).
+///
+/// # Returns
+/// An `io::Error` if the signal handler can't be installed (or on platforms without one).
+pub fn ctrl_c_token() -> io::Result<ShutdownToken> {
+    install()?;
+    Ok(global_token().clone())
+}
+
+// Whether the Ctrl+C token was triggered (without installing the signal handler)
+pub(crate) fn shutdown_requested() -> bool {global_token().is_shutdown()}
+
+/// Handles a `Ctrl+C` as if the signal was received.
+///
+/// In [raw mode](crate::console::term::RawMode), `Ctrl+C` is read as a key
+/// (`Key::Ctrl('c')`) instead of sending a signal: key loops call this to keep its meaning.
+/// Without a handler ([on_ctrl_c], [ctrl_c_token]), this does what the signal would do:
+/// the terminal is [reset](term::reset) and the process exits with code 130.
+pub fn interrupt() {
+    let installed = *INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+    match installed {
+        true => dispatch(),
+        false => {
+            term::reset();
+            std::process::exit(130);
+        },
+    }
+}
+
+// What a Ctrl+C does once handled: trigger the token and call the handlers,
+// or exit right away if a shutdown was already requested
+fn dispatch() {
+    let token = global_token();
+    if token.is_shutdown() {
+        term::reset();
+        std::process::exit(130);
+    }
+    token.shutdown();
+    // cloned, so that handlers can register other handlers
+    let handlers = HANDLERS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    for handler in handlers {handler();}
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{c_int, c_void};
+    use std::io::{self, Read};
+    use std::os::unix::io::IntoRawFd;
+    use std::os::unix::net::UnixStream;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::thread;
+
+    const SIGINT: c_int = 2;
+    const SIGTERM: c_int = 15;
+    const SIG_ERR: usize = usize::MAX;
+
+    extern "C" {
+        fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+        fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+    }
+
+    // The end of the socket pair written by the signal handler
+    static NOTIFY_FD: AtomicI32 = AtomicI32::new(-1);
+
+    // Only async-signal-safe calls are allowed here: the signal thread does the rest
+    extern "C" fn on_signal(_: c_int) {
+        let byte = 1u8;
+        // SAFETY: write(2) is async-signal-safe, and the buffer is a valid byte
+        unsafe {write(NOTIFY_FD.load(Ordering::SeqCst), &byte as *const u8 as *const c_void, 1);}
+    }
+
+    pub fn install() -> io::Result<()> {
+        let (mut receiver, notifier) = UnixStream::pair()?;
+        // a full buffer drops the notification instead of blocking the handler
+        notifier.set_nonblocking(true)?;
+        NOTIFY_FD.store(notifier.into_raw_fd(), Ordering::SeqCst);
+        thread::Builder::new().name("signals".to_string()).spawn(move || {
+            let mut buffer = [0; 16];
+            loop {
+                match receiver.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => (0..n).for_each(|_| super::dispatch()),
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                }
+            }
+        })?;
+        for signum in [SIGINT, SIGTERM] {
+            // SAFETY: `on_signal` only calls async-signal-safe functions
+            if unsafe {signal(signum, on_signal)} == SIG_ERR {return Err(io::Error::last_os_error());}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::io;
+
+    const CTRL_C_EVENT: u32 = 0;
+    const CTRL_BREAK_EVENT: u32 = 1;
+    const CTRL_CLOSE_EVENT: u32 = 2;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+
+    // Windows calls console handlers on a new thread: the handlers can run right there
+    unsafe extern "system" fn on_event(event: u32) -> i32 {
+        match event {
+            CTRL_C_EVENT | CTRL_BREAK_EVENT | CTRL_CLOSE_EVENT => {
+                super::dispatch();
+                1
+            },
+            _ => 0,
+        }
+    }
+
+    pub fn install() -> io::Result<()> {
+        // SAFETY: registers a handler with the expected signature
+        match unsafe {SetConsoleCtrlHandler(Some(on_event), 1)} {
+            0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use std::io;
+
+    pub fn install() -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "signals are not supported on this platform"))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_token() {
+        let token = ShutdownToken::new();
+        let start = Instant::now();
+        assert!(!token.wait_timeout(Duration::from_millis(30)));
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        let waiter = {
+            let token = token.clone();
+            thread::spawn(move || token.wait_timeout(Duration::from_secs(10)))
+        };
+        thread::sleep(Duration::from_millis(20));
+        token.shutdown();
+        assert!(waiter.join().unwrap() && token.is_shutdown());
+        token.wait();  // returns at once
+        assert!(!ShutdownToken::new().is_shutdown());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ctrl_c() {
+        extern "C" {
+            fn raise(signum: std::ffi::c_int) -> std::ffi::c_int;
+        }
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let sender = Mutex::new(sender);
+        on_ctrl_c(move || sender.lock().unwrap().send(thread::current().name().map(String::from)).unwrap()).unwrap();
+        let token = ctrl_c_token().unwrap();
+        assert!(!token.is_shutdown());
+
+        // SAFETY: sends SIGINT to this process, which now handles it
+        assert_eq!(unsafe {raise(2)}, 0);
+        let thread = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(thread.as_deref(), Some("signals"));
+        assert!(token.is_shutdown());
+    }
+}
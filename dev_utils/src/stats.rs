@@ -0,0 +1,243 @@
+//! Descriptive statistics and histograms, for benchmark timings and other samples.
+//!
+//! The functions take a slice of samples in any order and return `None` if it is empty.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::stats::{self, Histogram, Summary};
+//!
+//! let latencies = [12.0, 15.0, 11.0, 14.0, 90.0, 13.0, 12.0, 16.0];
+//! assert_eq!(stats::mean(&latencies), Some(22.875));
+//! assert_eq!(stats::median(&latencies), Some(13.5));
+//! assert_eq!(stats::percentile(&latencies, 90.0), Some(90.0));
+//!
+//! let summary = Summary::of(&latencies).unwrap();
+//! assert_eq!((summary.min, summary.max), (11.0, 90.0));
+//!
+//! let histogram = Histogram::new(&latencies, 4);
+//! assert_eq!(histogram.counts(), [7, 0, 0, 1]);
+//! println!("{}", histogram.render(30));
+//! ```
+use std::fmt;
+use crate::format::{Stylize, Style};
+use crate::format::chart::bar_chart;
+use crate::format::num::group_digits;
+
+/// Returns the arithmetic mean of the samples.
+pub fn mean(data: &[f64]) -> Option<f64> {
+    match data.is_empty() {
+        true => None,
+        false => Some(data.iter().sum::<f64>() / data.len() as f64),
+    }
+}
+
+/// Returns the median of the samples: the middle value, or the mean of the two middle values.
+pub fn median(data: &[f64]) -> Option<f64> {
+    let sorted = sorted(data);
+    let n = sorted.len();
+    match n {
+        0 => None,
+        _ if n.is_multiple_of(2) => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+        _ => Some(sorted[n / 2]),
+    }
+}
+
+/// Returns the `p`th percentile (`0.0` to `100.0`) of the samples, by the nearest-rank method:
+/// the smallest sample that is at least as large as `p`% of the samples.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::stats::percentile;
+///
+/// let data: Vec<f64> = (1..=20).map(f64::from).collect();
+/// assert_eq!(percentile(&data, 95.0), Some(19.0));
+/// assert_eq!(percentile(&data, 0.0), Some(1.0));
+/// assert_eq!(percentile(&data, 100.0), Some(20.0));
+/// ```
+pub fn percentile(data: &[f64], p: f64) -> Option<f64> {
+    let sorted = sorted(data);
+    let n = sorted.len();
+    if n == 0 {return None;}
+    let rank = (n as f64 * p.clamp(0.0, 100.0) / 100.0).ceil() as usize;
+    Some(sorted[rank.clamp(1, n) - 1])
+}
+
+/// Returns the population variance of the samples.
+pub fn variance(data: &[f64]) -> Option<f64> {
+    let mean = mean(data)?;
+    Some(data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / data.len() as f64)
+}
+
+/// Returns the population standard deviation of the samples.
+pub fn std_dev(data: &[f64]) -> Option<f64> {variance(data).map(f64::sqrt)}
+
+fn sorted(data: &[f64]) -> Vec<f64> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+/// The usual statistics of a set of samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// 95th percentile (nearest rank).
+    pub p95: f64,
+    /// 99th percentile (nearest rank).
+    pub p99: f64,
+    pub std_dev: f64,
+}
+
+impl Summary {
+    /// Computes the statistics of the samples, or `None` if there are none.
+    pub fn of(data: &[f64]) -> Option<Summary> {
+        let sorted = sorted(data);
+        Some(Summary {
+            count: sorted.len(),
+            min: *sorted.first()?,
+            max: *sorted.last()?,
+            mean: mean(&sorted)?,
+            median: median(&sorted)?,
+            p95: percentile(&sorted, 95.0)?,
+            p99: percentile(&sorted, 99.0)?,
+            std_dev: std_dev(&sorted)?,
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stat = |label: &str, value: f64| format!("{} {:.3}", label.style(Style::Dim), value);
+        write!(f, "{} {} | {} {} {} {} {} {}",
+            "n".style(Style::Dim), group_digits(self.count),
+            stat("min", self.min), stat("mean", self.mean), stat("median", self.median),
+            stat("p95", self.p95), stat("max", self.max), stat("σ", self.std_dev),
+        )
+    }
+}
+
+/// A range of values and the number of samples that fall in it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bucket {
+    /// The start of the range (inclusive).
+    pub start: f64,
+    /// The end of the range (exclusive, except for the last bucket).
+    pub end: f64,
+    pub count: usize,
+}
+
+/// Samples grouped into buckets of equal width, between the smallest and the largest sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub buckets: Vec<Bucket>,
+}
+
+impl Histogram {
+    /// Groups the samples into `buckets` buckets (at least one). NaN samples are ignored.
+    ///
+    /// If every sample has the same value, all of them go in the first bucket.
+    pub fn new(data: &[f64], buckets: usize) -> Self {
+        let data: Vec<f64> = data.iter().copied().filter(|x| !x.is_nan()).collect();
+        let buckets = buckets.max(1);
+        let (min, max) = data.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+        if data.is_empty() {return Histogram { buckets: Vec::new() };}
+
+        let width = (max - min) / buckets as f64;
+        let mut histogram = Histogram {
+            buckets: (0..buckets).map(|i| Bucket {
+                start: min + width * i as f64,
+                end: if i + 1 == buckets {max} else {min + width * (i + 1) as f64},
+                count: 0,
+            }).collect(),
+        };
+        for x in data {
+            let i = match width > 0.0 {
+                true => (((x - min) / width) as usize).min(buckets - 1),
+                false => 0,
+            };
+            histogram.buckets[i].count += 1;
+        }
+        histogram
+    }
+
+    /// Returns the number of samples in each bucket.
+    pub fn counts(&self) -> Vec<usize> {self.buckets.iter().map(|b| b.count).collect()}
+
+    /// Returns the total number of samples.
+    pub fn total(&self) -> usize {self.buckets.iter().map(|b| b.count).sum()}
+
+    /// Renders the histogram with one horizontal bar per bucket, the longest one `width`
+    /// characters wide, colored from green (few samples) to red (most samples).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dev_utils::format::strip_ansi_codes;
+    /// use dev_utils::stats::Histogram;
+    ///
+    /// let histogram = Histogram::new(&[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 3.0], 3);
+    /// assert_eq!(strip_ansi_codes(&histogram.render(8)), "\
+    /// 1.00 - 1.67 │██ 1
+    /// 1.67 - 2.33 │████ 2
+    /// 2.33 - 3.00 │████████ 4");
+    /// ```
+    pub fn render(&self, width: usize) -> String {
+        let labels: Vec<(String, String)> = self.buckets.iter().map(|b| (format!("{:.2}", b.start), format!("{:.2}", b.end))).collect();
+        let start_width = labels.iter().map(|(start, _)| start.len()).max().unwrap_or(0);
+        let end_width = labels.iter().map(|(_, end)| end.len()).max().unwrap_or(0);
+        let labels: Vec<String> = labels.iter().map(|(start, end)| format!("{:>sw$} - {:>ew$}", start, end, sw = start_width, ew = end_width)).collect();
+        let counts: Vec<f64> = self.buckets.iter().map(|b| b.count as f64).collect();
+        bar_chart(&labels, &counts, width)
+    }
+}
+
+/// Renders the histogram with bars up to 40 characters wide.
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(40))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::strip_ansi_codes;
+
+    #[test]
+    fn test_statistics() {
+        let data = [4.0, 1.0, 3.0, 2.0];
+        assert_eq!(mean(&data), Some(2.5));
+        assert_eq!(median(&data), Some(2.5));
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(variance(&data), Some(1.25));
+        assert_eq!(std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]), Some(2.0));
+        assert_eq!(percentile(&data, 50.0), Some(2.0));
+        assert_eq!(percentile(&data, 51.0), Some(3.0));
+        assert_eq!(percentile(&data, 250.0), Some(4.0));
+        for f in [mean, median, variance, std_dev] {
+            assert_eq!(f(&[]), None);
+        }
+        assert_eq!(Summary::of(&[]), None);
+        assert_eq!(Summary::of(&[5.0]).unwrap().p99, 5.0);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let data: Vec<f64> = (0..100).map(f64::from).collect();
+        let histogram = Histogram::new(&data, 10);
+        assert_eq!(histogram.counts(), [10; 10]);
+        assert_eq!(histogram.buckets[9].end, 99.0);
+        assert_eq!(Histogram::new(&[2.0, 2.0], 3).counts(), [2, 0, 0]);
+        assert_eq!(Histogram::new(&[f64::NAN], 3).total(), 0);
+        assert_eq!(Histogram::new(&[], 3).render(10), "");
+
+        let rendered = strip_ansi_codes(&Histogram::new(&[0.0, 0.0, 0.0, 10.0, 10.0, 10.0, 10.0, 10.0], 2).render(10));
+        assert_eq!(rendered, "0.00 -  5.00 │██████ 3\n5.00 - 10.00 │██████████ 5");
+    }
+}
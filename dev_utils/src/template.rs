@@ -0,0 +1,554 @@
+//! A small template engine for code generation and scaffolding.
+//!
+//! Templates interpolate values of a context ([YamlValue], which also holds parsed JSON,
+//! [TomlValue], or plain `(key, value)` pairs):
+//! - `{{ path }}` inserts a value (`name`, `package.version`, `items[0].id`), optionally
+//!   through filters: `{{ name | snake_case }}`
+//! - `{% if path %}`, `{% elif not path %}`, `{% else %}`, `{% endif %}`, where a condition
+//!   can also compare a value: `{% if lang == "rust" %}` (or `!=`)
+//! - `{% for item in path %}` ... `{% endfor %}` over a sequence (or the keys of a mapping),
+//!   and `{% for key, value in path %}` over a mapping (or the indices and items of a
+//!   sequence), with an optional `{% else %}` for empty collections; `loop.index`,
+//!   `loop.index0`, `loop.first` and `loop.last` are set
+//! - `{# comments #}`
+//!
+//! A block tag or comment alone on its line doesn't leave an empty line in the output.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::template::render;
+//! use dev_utils::file::toml;
+//!
+//! let ctx = toml::parse(r#"
+//! name = "UserAccount"
+//! fields = [{ name = "id", type = "u64" }, { name = "email", type = "String" }]
+//! "#).unwrap();
+//!
+//! let code = render("\
+//! pub struct {{ name }} {
+//! {% for field in fields %}
+//!     pub {{ field.name }}: {{ field.type }},
+//! {% endfor %}
+//! }
+//! ", &ctx).unwrap();
+//! assert_eq!(code, "pub struct UserAccount {\n    pub id: u64,\n    pub email: String,\n}\n");
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use crate::file::{self, FileError};
+use crate::file::toml::{Table, TomlValue};
+use crate::file::yaml::{Mapping, YamlValue};
+use crate::text;
+
+/// Error returned when a template cannot be parsed or rendered.
+#[derive(Debug)]
+pub enum TemplateError {
+    /// The template is malformed (unclosed tag, unknown filter, missing `endif`, ...).
+    Syntax { line: usize, message: String },
+    /// A value cannot be rendered (undefined variable, loop over a scalar, ...).
+    Render { line: usize, message: String },
+    /// The template file cannot be read.
+    File(FileError),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Syntax { line, message } => write!(f, "template syntax error at line {}: {}", line, message),
+            TemplateError::Render { line, message } => write!(f, "template error at line {}: {}", line, message),
+            TemplateError::File(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+impl From<FileError> for TemplateError {
+    fn from(err: FileError) -> Self {TemplateError::File(err)}
+}
+
+fn syntax(line: usize, message: impl Into<String>) -> TemplateError {
+    TemplateError::Syntax { line, message: message.into() }
+}
+
+/// A value that can be used as the context of a template.
+///
+/// The context is converted into a [YamlValue] tree once per render.
+pub trait Context {
+    /// Returns the context as a value tree (usually a [YamlValue::Mapping]).
+    fn to_value(&self) -> YamlValue;
+}
+
+impl Context for YamlValue {
+    fn to_value(&self) -> YamlValue {self.clone()}
+}
+
+impl Context for TomlValue {
+    fn to_value(&self) -> YamlValue {self.clone().into()}
+}
+
+impl Context for Table {
+    fn to_value(&self) -> YamlValue {TomlValue::Table(self.clone()).into()}
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> Context for [(K, V)] {
+    fn to_value(&self) -> YamlValue {
+        YamlValue::Mapping(self.iter().map(|(k, v)| (k.as_ref().to_string(), YamlValue::String(v.as_ref().to_string()))).collect())
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>, const N: usize> Context for [(K, V); N] {
+    fn to_value(&self) -> YamlValue {self.as_slice().to_value()}
+}
+
+/// The filters that can be applied to a value with `{{ value | filter }}`.
+const FILTERS: [&str; 9] = ["upper", "lower", "trim", "snake_case", "kebab_case", "camel_case", "title_case", "pluralize", "length"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Truthy(String),
+    Not(String),
+    Eq(String, String),
+    Ne(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Var { path: String, filters: Vec<String>, line: usize },
+    If { branches: Vec<(Condition, Vec<Node>)>, otherwise: Vec<Node> },
+    For { key: Option<String>, value: String, path: String, body: Vec<Node>, otherwise: Vec<Node>, line: usize },
+}
+
+enum Token {
+    Text(String),
+    Var(String, usize),
+    Block(String, usize),
+}
+
+/// A parsed template, which can be rendered any number of times.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::template::Template;
+///
+/// let template = Template::parse("{{ greeting | upper }}, {{ name }}!").unwrap();
+/// assert_eq!(template.render(&[("greeting", "hello"), ("name", "Ann")]).unwrap(), "HELLO, Ann!");
+/// assert_eq!(template.render(&[("greeting", "hi"), ("name", "Bob")]).unwrap(), "HI, Bob!");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Parses a template.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [Template], or a [TemplateError::Syntax] with the line of the first error.
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let tokens = tokenize(source)?;
+        let mut pos = 0;
+        let (nodes, _) = parse_nodes(&tokens, &mut pos, &[])?;
+        Ok(Template { nodes })
+    }
+
+    /// Renders the template with a context.
+    ///
+    /// # Returns
+    ///
+    /// Returns the rendered text, or a [TemplateError::Render] if a value cannot be rendered.
+    pub fn render<C: Context + ?Sized>(&self, ctx: &C) -> Result<String, TemplateError> {
+        let root = ctx.to_value();
+        let mut renderer = Renderer { root: &root, scopes: Vec::new() };
+        let mut out = String::new();
+        renderer.render(&self.nodes, &mut out)?;
+        Ok(out)
+    }
+}
+
+/// Renders a template with a context.
+///
+/// # Arguments
+///
+/// * `template` - The template source
+/// * `ctx` - The values available to the template
+///
+/// # Returns
+///
+/// Returns the rendered text, or a [TemplateError] if the template is malformed or uses
+/// values that cannot be rendered.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::template::render;
+///
+/// let ctx = [("module", "http_client"), ("async", "")];
+/// let out = render("mod {{ module }};{% if async %} // async{% endif %}", &ctx).unwrap();
+/// assert_eq!(out, "mod http_client;");
+/// assert!(render("{{ missing }}", &ctx).is_err());
+/// ```
+pub fn render<C: Context + ?Sized>(template: &str, ctx: &C) -> Result<String, TemplateError> {
+    Template::parse(template)?.render(ctx)
+}
+
+/// Reads a template file and renders it with a context.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::file::{create, TempDir};
+/// use dev_utils::template::render_file;
+///
+/// let dir = TempDir::new().unwrap();
+/// let path = create(dir.join("main.rs.tpl"), "fn main() { println!(\"{{ name }}\"); }").unwrap();
+/// assert_eq!(render_file(&path, &[("name", "app")]).unwrap(), "fn main() { println!(\"app\"); }");
+/// ```
+pub fn render_file<P: AsRef<Path>, C: Context + ?Sized>(path: P, ctx: &C) -> Result<String, TemplateError> {
+    render(&file::read(path)?, ctx)
+}
+
+// Splits a template into text, `{{ }}` and `{% %}` tokens (comments are dropped)
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let line_at = |pos: usize| source[..pos].matches('\n').count() + 1;
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut pos = 0;
+    // whether only whitespace has been written since the last line break
+    let mut clean_line = true;
+
+    loop {
+        let next = ["{{", "{%", "{#"].into_iter()
+            .filter_map(|open| source[pos..].find(open).map(|i| (pos + i, open)))
+            .min();
+        let Some((start, open)) = next else {
+            text.push_str(&source[pos..]);
+            break;
+        };
+        let segment = &source[pos..start];
+        clean_line = match segment.rfind('\n') {
+            Some(i) => segment[i + 1..].trim().is_empty(),
+            None => clean_line && segment.trim().is_empty(),
+        };
+        text.push_str(segment);
+
+        let line = line_at(start);
+        let close = match open {"{{" => "}}", "{%" => "%}", _ => "#}"};
+        let end = source[start + 2..].find(close).map(|i| start + 2 + i)
+            .ok_or_else(|| syntax(line, format!("unclosed `{}`", open)))?;
+        let inner = source[start + 2..end].trim();
+        pos = end + 2;
+
+        if open == "{{" {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+            tokens.push(Token::Var(inner.to_string(), line));
+            clean_line = false;
+            continue;
+        }
+        // a block tag or comment alone on its line is removed with the line
+        let line_end = source[pos..].find('\n').map_or(source.len(), |i| pos + i + 1);
+        if clean_line && source[pos..line_end].trim().is_empty() {
+            text.truncate(text.trim_end_matches([' ', '\t']).len());
+            pos = line_end;
+        } else {
+            clean_line = false;
+        }
+        if open == "{%" {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+            tokens.push(Token::Block(inner.to_string(), line));
+        }
+    }
+    tokens.push(Token::Text(text));
+    Ok(tokens)
+}
+
+// A block tag and its line
+type Tag = (String, usize);
+
+// Parses nodes until a block tag starting with one of `ends`, which is returned with them
+fn parse_nodes(tokens: &[Token], pos: &mut usize, ends: &[&str]) -> Result<(Vec<Node>, Option<Tag>), TemplateError> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.get(*pos) {
+        *pos += 1;
+        match token {
+            Token::Text(text) if text.is_empty() => {},
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Var(expr, line) => {
+                let mut parts = expr.split('|').map(str::trim);
+                let path = parts.next().unwrap_or_default().to_string();
+                if path.is_empty() {return Err(syntax(*line, "empty `{{ }}`"));}
+                let filters: Vec<String> = parts.map(String::from).collect();
+                if let Some(filter) = filters.iter().find(|f| !FILTERS.contains(&f.as_str())) {
+                    return Err(syntax(*line, format!("unknown filter `{}` (expected one of: {})", filter, FILTERS.join(", "))));
+                }
+                nodes.push(Node::Var { path, filters, line: *line });
+            },
+            Token::Block(tag, line) => {
+                let keyword = tag.split_whitespace().next().unwrap_or_default();
+                if ends.contains(&keyword) {return Ok((nodes, Some((tag.clone(), *line))));}
+                let args = tag[keyword.len()..].trim();
+                match keyword {
+                    "if" => nodes.push(parse_if(tokens, pos, args, *line)?),
+                    "for" => nodes.push(parse_for(tokens, pos, args, *line)?),
+                    _ => return Err(syntax(*line, format!("unexpected `{{% {} %}}`", tag))),
+                }
+            },
+        }
+    }
+    match ends.last() {
+        Some(end) => Err(syntax(line_count(tokens), format!("missing `{{% {} %}}`", end))),
+        None => Ok((nodes, None)),
+    }
+}
+
+// The last line of the template (where a missing end tag is reported)
+fn line_count(tokens: &[Token]) -> usize {
+    tokens.iter().rev().find_map(|token| match token {
+        Token::Var(_, line) | Token::Block(_, line) => Some(*line),
+        Token::Text(_) => None,
+    }).unwrap_or(1)
+}
+
+fn parse_if(tokens: &[Token], pos: &mut usize, args: &str, line: usize) -> Result<Node, TemplateError> {
+    let mut branches = Vec::new();
+    let mut condition = parse_condition(args, line)?;
+    loop {
+        let (body, end) = parse_nodes(tokens, pos, &["elif", "else", "endif"])?;
+        let (tag, line) = end.expect("`parse_nodes` returns the end tag");
+        branches.push((condition, body));
+        match tag.split_whitespace().next() {
+            Some("elif") => condition = parse_condition(tag["elif".len()..].trim(), line)?,
+            Some("else") => {
+                let (otherwise, _) = parse_nodes(tokens, pos, &["endif"])?;
+                return Ok(Node::If { branches, otherwise });
+            },
+            _ => return Ok(Node::If { branches, otherwise: Vec::new() }),
+        }
+    }
+}
+
+fn parse_condition(args: &str, line: usize) -> Result<Condition, TemplateError> {
+    let literal = |s: &str| {
+        let s = s.trim();
+        ['"', '\''].iter()
+            .find_map(|&quote| s.strip_prefix(quote).and_then(|s| s.strip_suffix(quote)))
+            .unwrap_or(s)
+            .to_string()
+    };
+    let condition = if let Some((path, value)) = args.split_once("==") {
+        Condition::Eq(path.trim().to_string(), literal(value))
+    } else if let Some((path, value)) = args.split_once("!=") {
+        Condition::Ne(path.trim().to_string(), literal(value))
+    } else if let Some(path) = args.strip_prefix("not ") {
+        Condition::Not(path.trim().to_string())
+    } else {
+        Condition::Truthy(args.to_string())
+    };
+    match condition {
+        Condition::Truthy(ref path) | Condition::Not(ref path) | Condition::Eq(ref path, _) | Condition::Ne(ref path, _)
+            if path.is_empty() || path.contains(char::is_whitespace) => Err(syntax(line, format!("invalid condition `{}`", args))),
+        _ => Ok(condition),
+    }
+}
+
+fn parse_for(tokens: &[Token], pos: &mut usize, args: &str, line: usize) -> Result<Node, TemplateError> {
+    let invalid = || syntax(line, format!("invalid loop `for {}` (expected `for item in path` or `for key, value in path`)", args));
+    let (vars, path) = args.split_once(" in ").ok_or_else(invalid)?;
+    let vars: Vec<String> = vars.split(',').map(|v| v.trim().to_string()).collect();
+    if vars.iter().any(|v| v.is_empty() || v.contains(char::is_whitespace)) || path.trim().is_empty() {return Err(invalid());}
+    let (key, value) = match vars.as_slice() {
+        [value] => (None, value.clone()),
+        [key, value] => (Some(key.clone()), value.clone()),
+        _ => return Err(invalid()),
+    };
+
+    let (body, end) = parse_nodes(tokens, pos, &["else", "endfor"])?;
+    let otherwise = match end.is_some_and(|(tag, _)| tag == "else") {
+        true => parse_nodes(tokens, pos, &["endfor"])?.0,
+        false => Vec::new(),
+    };
+    Ok(Node::For { key, value, path: path.trim().to_string(), body, otherwise, line })
+}
+
+struct Renderer<'a> {
+    root: &'a YamlValue,
+    // the loop variables, innermost last
+    scopes: Vec<(String, YamlValue)>,
+}
+
+impl Renderer<'_> {
+    fn lookup(&self, path: &str) -> Option<&YamlValue> {
+        let (name, rest) = path.split_at(path.find(['.', '[']).unwrap_or(path.len()));
+        let base = match self.scopes.iter().rev().find(|(var, _)| var == name) {
+            Some((_, value)) => value,
+            None => self.root.get(name)?,
+        };
+        base.get_path(rest.strip_prefix('.').unwrap_or(rest))
+    }
+
+    fn check(&self, condition: &Condition) -> bool {
+        let text = |path: &str| self.lookup(path).map(display).unwrap_or_default();
+        match condition {
+            Condition::Truthy(path) => self.lookup(path).is_some_and(is_truthy),
+            Condition::Not(path) => !self.lookup(path).is_some_and(is_truthy),
+            Condition::Eq(path, value) => text(path) == *value,
+            Condition::Ne(path, value) => text(path) != *value,
+        }
+    }
+
+    fn render(&mut self, nodes: &[Node], out: &mut String) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Var { path, filters, line } => {
+                    let value = self.lookup(path).ok_or_else(|| TemplateError::Render {
+                        line: *line, message: format!("undefined variable `{}`", path),
+                    })?;
+                    let rendered = filters.iter().fold(display(value), |s, filter| apply_filter(filter, s, value));
+                    out.push_str(&rendered);
+                },
+                Node::If { branches, otherwise } => {
+                    let body = branches.iter().find(|(condition, _)| self.check(condition)).map_or(otherwise, |(_, body)| body);
+                    self.render(body, out)?;
+                },
+                Node::For { key, value, path, body, otherwise, line } => {
+                    let error = |message: String| TemplateError::Render { line: *line, message };
+                    let items: Vec<(YamlValue, YamlValue)> = match self.lookup(path) {
+                        None => return Err(error(format!("undefined variable `{}`", path))),
+                        Some(YamlValue::Null) => Vec::new(),
+                        Some(YamlValue::Sequence(items)) => items.iter().enumerate().map(|(i, v)| (YamlValue::Int(i as i64), v.clone())).collect(),
+                        Some(YamlValue::Mapping(map)) => map.iter().map(|(k, v)| (YamlValue::String(k.clone()), v.clone())).collect(),
+                        Some(other) => return Err(error(format!("cannot loop over `{}`, a {}", path, other.type_name()))),
+                    };
+                    if items.is_empty() {
+                        self.render(otherwise, out)?;
+                        continue;
+                    }
+                    let len = items.len();
+                    for (i, (k, v)) in items.into_iter().enumerate() {
+                        let scope_len = self.scopes.len();
+                        let loop_info = Mapping::from([
+                            ("index".to_string(), YamlValue::Int(i as i64 + 1)),
+                            ("index0".to_string(), YamlValue::Int(i as i64)),
+                            ("first".to_string(), YamlValue::Bool(i == 0)),
+                            ("last".to_string(), YamlValue::Bool(i == len - 1)),
+                        ]);
+                        self.scopes.push(("loop".to_string(), YamlValue::Mapping(loop_info)));
+                        match key {
+                            Some(key) => self.scopes.extend([(key.clone(), k), (value.clone(), v)]),
+                            // a mapping iterated with a single variable gives its keys
+                            None if matches!(k, YamlValue::String(_)) => self.scopes.push((value.clone(), k)),
+                            None => self.scopes.push((value.clone(), v)),
+                        }
+                        let result = self.render(body, out);
+                        self.scopes.truncate(scope_len);
+                        result?;
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+// Renders a value as text: strings without quotes, null as nothing
+fn display(value: &YamlValue) -> String {
+    match value {
+        YamlValue::Null => String::new(),
+        YamlValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn is_truthy(value: &YamlValue) -> bool {
+    match value {
+        YamlValue::Null => false,
+        YamlValue::Bool(b) => *b,
+        YamlValue::Int(i) => *i != 0,
+        YamlValue::Float(f) => *f != 0.0,
+        YamlValue::String(s) => !s.is_empty(),
+        YamlValue::Sequence(items) => !items.is_empty(),
+        YamlValue::Mapping(map) => !map.is_empty(),
+    }
+}
+
+fn apply_filter(filter: &str, s: String, value: &YamlValue) -> String {
+    match filter {
+        "upper" => s.to_uppercase(),
+        "lower" => s.to_lowercase(),
+        "trim" => s.trim().to_string(),
+        "snake_case" => text::to_snake_case(&s),
+        "kebab_case" => text::to_kebab_case(&s),
+        "camel_case" => text::to_camel_case(&s),
+        "title_case" => text::to_title_case(&s),
+        "pluralize" => text::pluralize(&s),
+        "length" => match value {
+            YamlValue::Sequence(items) => items.len(),
+            YamlValue::Mapping(map) => map.len(),
+            _ => s.chars().count(),
+        }.to_string(),
+        _ => s,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::yaml;
+
+    fn ctx() -> YamlValue {
+        yaml::parse("
+name: order item
+tags: [a, b, c]
+empty: []
+deps: {log: '0.4', syn: '2'}
+count: 0
+lang: rust
+").unwrap()
+    }
+
+    #[test]
+    fn test_variables_and_filters() {
+        let ctx = ctx();
+        assert_eq!(render("{{ name | pluralize | camel_case }}", &ctx).unwrap(), "orderItems");
+        assert_eq!(render("{{name|title_case}} {{ tags[1] }} {{ tags | length }} {{ count }}", &ctx).unwrap(), "Order Item b 3 0");
+        assert_eq!(render("{{ deps.log }}", &ctx).unwrap(), "0.4");
+        assert!(matches!(render("{{ nope }}", &ctx), Err(TemplateError::Render { line: 1, .. })));
+        assert!(matches!(render("\n{{ name | reverse }}", &ctx), Err(TemplateError::Syntax { line: 2, .. })));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let ctx = ctx();
+        let template = "{% if count %}a{% elif lang == 'rust' %}b{% else %}c{% endif %}";
+        assert_eq!(render(template, &ctx).unwrap(), "b");
+        assert_eq!(render("{% if not empty %}empty{% endif %}{% if missing %}!{% endif %}", &ctx).unwrap(), "empty");
+        assert_eq!(render("{% if lang != \"go\" %}ok{% endif %}", &ctx).unwrap(), "ok");
+        assert!(render("{% if name %}unclosed", &ctx).is_err());
+        assert!(render("{% endif %}", &ctx).is_err());
+    }
+
+    #[test]
+    fn test_loops() {
+        let ctx = ctx();
+        let template = "{% for t in tags %}{{ loop.index }}{{ t }}{% if not loop.last %},{% endif %}{% endfor %}";
+        assert_eq!(render(template, &ctx).unwrap(), "1a,2b,3c");
+        assert_eq!(render("{% for k, v in deps %}{{ k }}={{ v }};{% endfor %}", &ctx).unwrap(), "log=0.4;syn=2;");
+        assert_eq!(render("{% for k in deps %}{{ k }} {% endfor %}", &ctx).unwrap(), "log syn ");
+        assert_eq!(render("{% for i, t in tags %}{{ i }}{{ t }}{% endfor %}", &ctx).unwrap(), "0a1b2c");
+        assert_eq!(render("{% for t in empty %}x{% else %}none{% endfor %}", &ctx).unwrap(), "none");
+        assert!(render("{% for c in count %}{% endfor %}", &ctx).is_err());
+        assert!(render("{% for in tags %}{% endfor %}", &ctx).is_err());
+    }
+
+    #[test]
+    fn test_standalone_lines() {
+        let template = "start\n  {% if lang %}\n  {# comment #}\n  {{ lang }}\n  {% endif %}\nend {% if lang %}x{% endif %}\n";
+        assert_eq!(render(template, &ctx()).unwrap(), "start\n  rust\nend x\n");
+    }
+}
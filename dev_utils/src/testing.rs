@@ -0,0 +1,501 @@
+//! Helpers and assertion macros for writing tests.
+//!
+//! # Features
+//! - [assert_completes_within!] to encode performance budgets as ordinary unit tests
+//! - [with_timeout] to run a closure on a separate thread and give up after a deadline
+//! - [assert_bytes_eq!] to compare binary data, printing a side-by-side hexdump diff on mismatch
+//! - [assert_eq_pretty!] and [assert_str_eq!] to compare values or text, printing a colored line diff on mismatch
+//! - [Fixture] to give every test its own copy of a directory of test data
+//! - [with_env_vars] to run a closure with some environment variables, restoring them afterwards
+//!
+//! # Examples
+//! ```
+//! use std::time::Duration;
+//! use dev_utils::assert_completes_within;
+//!
+//! let sum = assert_completes_within!(Duration::from_millis(500), {
+//!     (0..1_000u64).sum::<u64>()
+//! });
+//! assert_eq!(sum, 499_500);
+//! ```
+use std::env;
+use std::fmt;
+use std::error::Error;
+use std::ffi::OsString;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub use crate::{assert_completes_within, assert_bytes_eq, assert_eq_pretty, assert_str_eq};
+use crate::diff::Diff;
+use crate::file::{self, FileError, TempDir};
+use crate::format::{hexdump_diff, Stylize, Style, GREEN, RED};
+
+
+/// Error returned by [with_timeout] when the closure does not finish in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutError {
+    /// The time limit that was exceeded.
+    pub limit: Duration,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operation timed out after {:?}", self.limit)
+    }
+}
+
+impl Error for TimeoutError {}
+
+/// Runs a closure and returns its value together with the elapsed time.
+///
+/// # Examples
+/// ```
+/// use dev_utils::testing::measure;
+///
+/// let (value, elapsed) = measure(|| 2 + 2);
+/// assert_eq!(value, 4);
+/// assert!(elapsed.as_secs() < 1);
+/// ```
+pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+/// Runs a closure on a separate thread, waiting at most `limit` for its result.
+///
+/// If the deadline passes, the thread is left running in the background (it cannot be
+/// killed safely) and a [TimeoutError] is returned. Panics inside the closure are
+/// propagated to the caller.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::testing::with_timeout;
+///
+/// assert_eq!(with_timeout(Duration::from_secs(1), || 42).unwrap(), 42);
+/// assert!(with_timeout(Duration::from_millis(10), || std::thread::sleep(Duration::from_secs(1))).is_err());
+/// ```
+pub fn with_timeout<T, F>(limit: Duration, f: F) -> Result<T, TimeoutError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    let handle = thread::spawn(move || {let _ = tx.send(f());});
+
+    match rx.recv_timeout(limit) {
+        Ok(value) => Ok(value),
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(TimeoutError { limit }),
+        // the sender was dropped without sending: the closure panicked
+        Err(mpsc::RecvTimeoutError::Disconnected) => match handle.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => unreachable!("the closure always sends its result"),
+        },
+    }
+}
+
+/// Asserts that a block finishes within the given [Duration], evaluating to the block's value.
+///
+/// The block runs through [with_timeout], on a separate thread: it captures the variables
+/// it uses by move, and its value must be `Send + 'static`. When the limit passes the test
+/// fails right away, even if the block never finishes (it is left running in the background).
+/// Panics inside the block are propagated.
+///
+/// # Examples
+/// ```should_panic
+/// use std::time::Duration;
+/// use dev_utils::assert_completes_within;
+///
+/// assert_completes_within!(Duration::from_millis(1), {
+///     std::thread::sleep(Duration::from_millis(20));
+/// });
+/// ```
+#[macro_export]
+macro_rules! assert_completes_within {
+    ($limit:expr, $body:block $(,)?) => {{
+        let limit: std::time::Duration = $limit;
+        match $crate::testing::with_timeout(limit, move || $body) {
+            Ok(value) => value,
+            Err(_) => panic!("assertion failed: block at {}:{} is still running, exceeding the limit of {:?}",
+                file!(), line!(), limit
+            ),
+        }
+    }};
+}
+
+
+/// Renders a side-by-side hexdump of two buffers, highlighting the bytes that differ.
+///
+/// Only the rows containing differences (plus a few rows of context) are shown, see
+/// [hexdump_diff](crate::format::hexdump_diff). The header reports the lengths and the
+/// offset of the first difference.
+///
+/// # Examples
+/// ```
+/// use dev_utils::testing::bytes_diff;
+///
+/// let diff = bytes_diff(b"Hello, World!", b"Hello, Wor1d!");
+/// assert!(diff.contains("first difference at offset 0x0000000a"));
+/// ```
+pub fn bytes_diff(left: &[u8], right: &[u8]) -> String {
+    let first_diff = (0..left.len().max(right.len())).find(|&i| left.get(i) != right.get(i));
+    let mut out = format!("byte slices differ (left: {} bytes, right: {} bytes)", left.len(), right.len());
+    let Some(first_diff) = first_diff else {return out};
+    out.push_str(&format!(", first difference at offset {:#010x}\n", first_diff));
+    out.push_str(&hexdump_diff(left, right));
+    out
+}
+
+#[doc(hidden)]
+pub fn __as_bytes<T: AsRef<[u8]> + ?Sized>(data: &T) -> &[u8] {data.as_ref()}
+
+/// Asserts that two byte buffers are equal, printing a side-by-side hexdump diff on mismatch.
+///
+/// Accepts anything that implements `AsRef<[u8]>` (`Vec<u8>`, `&[u8]`, byte strings, `String`, ...).
+///
+/// # Examples
+/// ```
+/// use dev_utils::assert_bytes_eq;
+///
+/// assert_bytes_eq!(vec![0xCA, 0xFE], b"\xCA\xFE");
+/// ```
+///
+/// ```should_panic
+/// use dev_utils::assert_bytes_eq;
+///
+/// assert_bytes_eq!(b"gzip\x00", b"gzip\x01");  // panics showing the offset 0x00000004
+/// ```
+#[macro_export]
+macro_rules! assert_bytes_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        match (&$left, &$right) {
+            (left, right) => {
+                let (left, right) = ($crate::testing::__as_bytes(left), $crate::testing::__as_bytes(right));
+                if left != right {
+                    panic!("assertion `left == right` failed at {}:{}\n{}", file!(), line!(), $crate::testing::bytes_diff(left, right));
+                }
+            }
+        }
+    }};
+}
+
+/// Renders a colored line diff of two texts (see [Diff]), with `-` for the left and `+` for the right.
+///
+/// # Examples
+/// ```
+/// use dev_utils::{format::strip_ansi_codes, testing::text_diff};
+///
+/// let diff = strip_ansi_codes(&text_diff("a\nb", "a\nc"));
+/// assert!(diff.contains("-b\n+c"));
+/// ```
+pub fn text_diff(left: &str, right: &str) -> String {
+    // a missing final new line on both sides is not worth reporting
+    let (left, right) = match left.ends_with('\n') || right.ends_with('\n') {
+        true => (left.to_string(), right.to_string()),
+        false => (format!("{}\n", left), format!("{}\n", right)),
+    };
+    Diff::new(&left, &right).labels("left", "right").to_string().trim_end().to_string()
+}
+
+#[doc(hidden)]
+pub fn __eq_failure(file: &str, line: u32, left: &str, right: &str, message: Option<String>) -> String {
+    let message = message.map_or(String::new(), |message| format!(": {}", message));
+    format!("assertion `left == right` failed at {}:{}{}\n{}", file, line, message, text_diff(left, right))
+}
+
+#[doc(hidden)]
+pub fn __as_str<T: AsRef<str> + ?Sized>(text: &T) -> &str {text.as_ref()}
+
+/// Asserts that two values are equal, printing a colored line diff of their pretty `Debug`
+/// output (`{:#?}`) on mismatch, so the differing fields of large structures stand out.
+///
+/// Like `assert_eq!`, a custom message can be given after the values.
+///
+/// # Examples
+/// ```
+/// use dev_utils::assert_eq_pretty;
+///
+/// assert_eq_pretty!(vec![(1, "one"), (2, "two")], [(1, "one"), (2, "two")]);
+/// ```
+///
+/// ```should_panic
+/// use dev_utils::assert_eq_pretty;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Config { name: &'static str, port: u16 }
+///
+/// // panics showing `-    port: 80,` and `+    port: 8080,`
+/// assert_eq_pretty!(Config { name: "api", port: 80 }, Config { name: "api", port: 8080 }, "loading {}", "api.toml");
+/// ```
+#[macro_export]
+macro_rules! assert_eq_pretty {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_eq_pretty!(@impl $left, $right, None)
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::assert_eq_pretty!(@impl $left, $right, Some(format!($($arg)+)))
+    };
+    (@impl $left:expr, $right:expr, $message:expr) => {{
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    panic!("{}", $crate::testing::__eq_failure(file!(), line!(), &format!("{:#?}", left), &format!("{:#?}", right), $message));
+                }
+            }
+        }
+    }};
+}
+
+/// Asserts that two texts are equal, printing a colored line diff on mismatch.
+///
+/// Accepts anything that implements `AsRef<str>` (`&str`, `String`, ...); a custom message
+/// can be given after the texts.
+///
+/// # Examples
+/// ```
+/// use dev_utils::assert_str_eq;
+///
+/// let rendered = format!("{}\n{}\n", "header", "body");
+/// assert_str_eq!(rendered, "header\nbody\n");
+/// ```
+///
+/// ```should_panic
+/// use dev_utils::assert_str_eq;
+///
+/// assert_str_eq!("line 1\nline 2\n", "line 1\nline two\n");  // panics showing `-line 2` and `+line two`
+/// ```
+#[macro_export]
+macro_rules! assert_str_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_str_eq!(@impl $left, $right, None)
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        $crate::assert_str_eq!(@impl $left, $right, Some(format!($($arg)+)))
+    };
+    (@impl $left:expr, $right:expr, $message:expr) => {{
+        match (&$left, &$right) {
+            (left, right) => {
+                let (left, right) = ($crate::testing::__as_str(left), $crate::testing::__as_str(right));
+                if left != right {
+                    panic!("{}", $crate::testing::__eq_failure(file!(), line!(), left, right, $message));
+                }
+            }
+        }
+    }};
+}
+
+/// A private copy of a directory of test data, removed when dropped.
+///
+/// Every test gets its own directory (see [TempDir]), so tests that modify their files can
+/// run in parallel without colliding. Paths given to the helpers are relative to the fixture.
+///
+/// # Examples
+/// ```
+/// use dev_utils::testing::Fixture;
+///
+/// let fixture = Fixture::new().unwrap();
+/// fixture.write("config/app.toml", "port = 80").unwrap();
+///
+/// let copy = Fixture::from_template(fixture.path()).unwrap();  // e.g. "tests/data/project"
+/// copy.write("config/app.toml", "port = 8080").unwrap();
+/// assert_eq!(fixture.read("config/app.toml").unwrap(), "port = 80");
+/// assert_eq!(copy.files(), ["config/app.toml"]);
+/// ```
+#[derive(Debug)]
+pub struct Fixture {
+    dir: TempDir,
+}
+
+impl Fixture {
+    /// Creates an empty fixture directory.
+    pub fn new() -> Result<Self, FileError> {
+        Ok(Fixture { dir: TempDir::with_prefix("dev_utils_fixture")? })
+    }
+
+    /// Creates a fixture with a copy of the contents of the `template` directory.
+    pub fn from_template<P: AsRef<Path>>(template: P) -> Result<Self, FileError> {
+        let fixture = Self::new()?;
+        file::recursive_copy(template, fixture.path())?;
+        Ok(fixture)
+    }
+
+    /// Returns the path of the fixture directory.
+    pub fn path(&self) -> &Path {self.dir.path()}
+
+    /// Returns the absolute path of a file of the fixture.
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {self.dir.join(path)}
+
+    /// Writes a file of the fixture, creating its parent directories.
+    pub fn write<P: AsRef<Path>>(&self, path: P, content: &str) -> Result<PathBuf, FileError> {
+        file::create(self.join(path), content)
+    }
+
+    /// Reads a file of the fixture.
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> Result<String, FileError> {file::read(self.join(path))}
+
+    /// Returns whether a file or directory of the fixture exists.
+    pub fn exists<P: AsRef<Path>>(&self, path: P) -> bool {self.join(path).exists()}
+
+    /// Returns the paths of all the files of the fixture, relative to it, sorted and with `/` separators.
+    pub fn files(&self) -> Vec<String> {
+        let mut files = file::find(self.path(), |entry| entry.path().is_file()).unwrap_or_default().into_iter()
+            .filter_map(|path| path.strip_prefix(self.path()).ok().map(|path| path.to_string_lossy().replace('\\', "/")))
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+}
+
+impl AsRef<Path> for Fixture {
+    fn as_ref(&self) -> &Path {self.path()}
+}
+
+// Serializes `with_env_vars` calls, as the environment is shared by all the test threads
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Runs a closure with some environment variables set, restoring their previous values
+/// (or removing them) afterwards, even if the closure panics.
+///
+/// Calls are serialized, so tests using it can run in parallel.
+///
+/// # Examples
+/// ```
+/// use std::env;
+/// use dev_utils::testing::with_env_vars;
+///
+/// let port = with_env_vars(&[("APP_PORT", "8080")], || env::var("APP_PORT").unwrap());
+/// assert_eq!(port, "8080");
+/// assert!(env::var("APP_PORT").is_err());
+/// ```
+pub fn with_env_vars<T, F: FnOnce() -> T>(vars: &[(&str, &str)], f: F) -> T {
+    let _lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let previous: Vec<(&str, Option<OsString>)> = vars.iter().map(|(key, _)| (*key, env::var_os(key))).collect();
+    vars.iter().for_each(|(key, value)| env::set_var(key, value));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    for (key, value) in previous.into_iter().rev() {
+        match value {
+            Some(value) => env::set_var(key, value),
+            None => env::remove_var(key),
+        }
+    }
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completes_within() {
+        let data = [1, 2, 3];
+        let total = assert_completes_within!(Duration::from_secs(1), {data.iter().sum::<i32>()});
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeding the limit")]
+    fn test_overrun_fails() {
+        assert_completes_within!(Duration::from_millis(1), {
+            thread::sleep(Duration::from_millis(10));
+        });
+    }
+
+    #[test]
+    fn test_hanging_block_fails() {
+        let start = Instant::now();
+        let result = panic::catch_unwind(|| assert_completes_within!(Duration::from_millis(20), {
+            thread::sleep(Duration::from_secs(60));
+        }));
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_bytes_diff() {
+        let left: Vec<u8> = (0..64).collect();
+        let mut right = left.clone();
+        right[40] = 0xFF;
+        let diff = crate::format::strip_ansi_codes(&bytes_diff(&left, &right));
+        assert!(diff.contains("first difference at offset 0x00000028"));
+        assert!(diff.contains("...\n00000018"));  // rows far from the difference are skipped
+        assert!(diff.contains("28 29 2a") && diff.contains("ff 29 2a"));
+        assert!(!bytes_diff(b"same", b"same").contains("offset"));
+    }
+
+    #[test]
+    #[should_panic(expected = "left: 3 bytes, right: 2 bytes")]
+    fn test_assert_bytes_eq_length() {
+        assert_bytes_eq!(vec![1u8, 2, 3], [1u8, 2]);
+    }
+
+    #[test]
+    fn test_text_diff() {
+        let diff = crate::format::strip_ansi_codes(&text_diff("a\nb\nc", "a\nB\nc"));
+        assert_eq!(diff, "--- left\n+++ right\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c");
+        assert!(text_diff("x\n", "x").contains("No newline at end of file"));
+
+        #[derive(Debug, PartialEq)]
+        struct Point { x: i32, y: i32 }
+        let failure = std::panic::catch_unwind(|| assert_eq_pretty!(Point { x: 1, y: 2 }, Point { x: 1, y: 3 }, "point {}", 7));
+        let message = failure.unwrap_err().downcast::<String>().unwrap();
+        let message = crate::format::strip_ansi_codes(&message);
+        assert!(message.contains("src/testing.rs:") && message.contains(": point 7\n"));
+        assert!(message.contains("     x: 1,\n-    y: 2,\n+    y: 3,\n"), "{}", message);
+
+        assert_eq_pretty!(Point { x: 1, y: 2 }, Point { x: 1, y: 2 });
+        assert_str_eq!(String::from("same"), "same");
+    }
+
+    #[test]
+    #[should_panic(expected = "+two")]
+    fn test_assert_str_eq() {
+        assert_str_eq!("one\n", "two\n");
+    }
+
+    #[test]
+    fn test_fixture() {
+        let template = Fixture::new().unwrap();
+        template.write("a.txt", "a").unwrap();
+        template.write("sub/b.txt", "b").unwrap();
+
+        let fixture = Fixture::from_template(&template).unwrap();
+        assert_eq!(fixture.files(), ["a.txt", "sub/b.txt"]);
+        fixture.write("sub/b.txt", "changed").unwrap();
+        assert_eq!(template.read("sub/b.txt").unwrap(), "b");
+        assert!(fixture.exists("sub") && !fixture.exists("c.txt"));
+
+        let path = fixture.path().to_path_buf();
+        drop(fixture);
+        assert!(!path.exists());
+        assert!(Fixture::from_template(path).is_err());
+    }
+
+    #[test]
+    fn test_with_env_vars() {
+        env::set_var("DEVUTILS_TESTING_KEPT", "before");
+        let values = with_env_vars(&[("DEVUTILS_TESTING_KEPT", "during"), ("DEVUTILS_TESTING_NEW", "1")], || {
+            (env::var("DEVUTILS_TESTING_KEPT").unwrap(), env::var("DEVUTILS_TESTING_NEW").unwrap())
+        });
+        assert_eq!(values, ("during".to_string(), "1".to_string()));
+        assert_eq!(env::var("DEVUTILS_TESTING_KEPT").unwrap(), "before");
+        assert!(env::var("DEVUTILS_TESTING_NEW").is_err());
+
+        let panicked = panic::catch_unwind(|| with_env_vars(&[("DEVUTILS_TESTING_NEW", "1")], || panic!("boom")));
+        assert!(panicked.is_err());
+        assert!(env::var("DEVUTILS_TESTING_NEW").is_err());
+        with_env_vars(&[], || ());  // the lock is not poisoned
+    }
+
+    #[test]
+    fn test_with_timeout() {
+        assert_eq!(with_timeout(Duration::from_secs(1), || "done"), Ok("done"));
+        let err = with_timeout(Duration::from_millis(5), || thread::sleep(Duration::from_millis(200)));
+        assert_eq!(err, Err(TimeoutError { limit: Duration::from_millis(5) }));
+    }
+}
@@ -0,0 +1,423 @@
+//! Case conversion, inflection and fuzzy matching of identifiers and words.
+//!
+//! The case conversions split their input into words at spaces, `_`, `-`, and case changes
+//! (`parseHTTPResponse` is `parse`, `HTTP`, `Response`), so they can convert between any of the
+//! common cases. [truncate_with_ellipsis] shortens text to a number of visible characters,
+//! ignoring and keeping its ANSI escape codes. [levenshtein], [similarity] and [fuzzy_find]
+//! compare strings, e.g. to suggest the closest command to a mistyped one.
+//!
+//! # Examples
+//! ```
+//! use dev_utils::text::*;
+//!
+//! assert_eq!(to_snake_case("parseHTTPResponse"), "parse_http_response");
+//! assert_eq!(to_camel_case("user-id"), "userId");
+//! assert_eq!(pluralize("category"), "categories");
+//! assert_eq!(pluralize_count(1, "file"), "1 file");
+//! assert_eq!(truncate_with_ellipsis("a long message", 7), "a long…");
+//!
+//! let commands = ["build", "bench", "check", "clean"];
+//! let suggestions = fuzzy_find("biuld", &commands);
+//! assert_eq!(commands[suggestions[0].0], "build");  // did you mean `build`?
+//! ```
+use crate::format::visual_length;
+
+/// Splits an identifier or a sentence into its words.
+///
+/// Words are separated by whitespace, `_`, `-`, `.` and `/`, by a lowercase letter or digit
+/// followed by an uppercase letter, and at the end of an acronym (`HTTPServer`).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::words;
+///
+/// assert_eq!(words("parseHTTPResponse"), ["parse", "HTTP", "Response"]);
+/// assert_eq!(words("  user_id-v2 "), ["user", "id", "v2"]);
+/// ```
+pub fn words(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_whitespace() || matches!(c, '_' | '-' | '.' | '/') {
+            if !word.is_empty() {words.push(std::mem::take(&mut word));}
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            // `aB` starts a word, and so does the last capital of an acronym (`HTTPServer`)
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {words.push(word);}
+    words
+}
+
+// Capitalizes the first letter of a word and lowercases the rest
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+fn join_lowercase(s: &str, separator: &str) -> String {
+    words(s).iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join(separator)
+}
+
+/// Converts a string to `snake_case`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::to_snake_case;
+///
+/// assert_eq!(to_snake_case("HttpVersion"), "http_version");
+/// assert_eq!(to_snake_case("max retries"), "max_retries");
+/// ```
+pub fn to_snake_case(s: &str) -> String {join_lowercase(s, "_")}
+
+/// Converts a string to `kebab-case`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::to_kebab_case;
+///
+/// assert_eq!(to_kebab_case("HttpVersion"), "http-version");
+/// ```
+pub fn to_kebab_case(s: &str) -> String {join_lowercase(s, "-")}
+
+/// Converts a string to `camelCase`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::to_camel_case;
+///
+/// assert_eq!(to_camel_case("http_version"), "httpVersion");
+/// assert_eq!(to_camel_case("XMLParser"), "xmlParser");
+/// ```
+pub fn to_camel_case(s: &str) -> String {
+    words(s).iter().enumerate()
+        .map(|(i, word)| match i {
+            0 => word.to_lowercase(),
+            _ => capitalize(word),
+        })
+        .collect()
+}
+
+/// Converts a string to `Title Case`.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::to_title_case;
+///
+/// assert_eq!(to_title_case("http_version"), "Http Version");
+/// assert_eq!(to_title_case("the QUICK fox"), "The Quick Fox");
+/// ```
+pub fn to_title_case(s: &str) -> String {
+    words(s).iter().map(|word| capitalize(word)).collect::<Vec<_>>().join(" ")
+}
+
+// (singular, plural) pairs that don't follow the rules
+const IRREGULAR: [(&str, &str); 14] = [
+    ("person", "people"), ("child", "children"), ("man", "men"), ("woman", "women"),
+    ("mouse", "mice"), ("goose", "geese"), ("foot", "feet"), ("tooth", "teeth"),
+    ("index", "indices"), ("matrix", "matrices"), ("vertex", "vertices"), ("criterion", "criteria"),
+    ("analysis", "analyses"), ("datum", "data"),
+];
+
+// Words with the same singular and plural form
+const UNCOUNTABLE: [&str; 10] = [
+    "sheep", "fish", "series", "species", "deer", "information", "equipment", "metadata", "news", "software",
+];
+
+// Words ending in `f` or `fe` whose plural ends in `ves`
+const VES: [&str; 12] = ["knife", "life", "wife", "leaf", "half", "wolf", "shelf", "calf", "elf", "loaf", "thief", "self"];
+
+// Words ending in `o` whose plural ends in `oes` (most, like `repo` or `photo`, just take an `s`)
+const OES: [&str; 10] = ["hero", "potato", "tomato", "echo", "veto", "torpedo", "volcano", "mosquito", "embargo", "domino"];
+
+/// Returns the plural of an English noun.
+///
+/// Common irregular and uncountable nouns are known; other nouns follow the regular rules
+/// (`box` → `boxes`, `quiz` → `quizzes`, `city` → `cities`, `day` → `days`). The case of the word is kept.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::pluralize;
+///
+/// assert_eq!(pluralize("file"), "files");
+/// assert_eq!(pluralize("Match"), "Matches");
+/// assert_eq!(pluralize("child"), "children");
+/// assert_eq!(pluralize("KEY"), "KEYS");
+/// ```
+pub fn pluralize(word: &str) -> String {
+    let lower = word.to_lowercase();
+    let plural = if UNCOUNTABLE.contains(&lower.as_str()) {
+        lower.clone()
+    } else if let Some((_, plural)) = IRREGULAR.iter().find(|(singular, _)| *singular == lower) {
+        plural.to_string()
+    } else if VES.contains(&lower.as_str()) {
+        format!("{}ves", lower.trim_end_matches("fe").trim_end_matches('f'))
+    } else if OES.contains(&lower.as_str()) {
+        format!("{}es", lower)
+    } else if lower.ends_with('z') && lower[..lower.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']) {
+        format!("{}zes", lower)  // a single final `z` is doubled: `quiz` → `quizzes`
+    } else if ["s", "x", "z", "ch", "sh"].iter().any(|end| lower.ends_with(end)) {
+        format!("{}es", lower)
+    } else if lower.len() > 1 && lower.ends_with('y') && !lower[..lower.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']) {
+        format!("{}ies", &lower[..lower.len() - 1])
+    } else {
+        format!("{}s", lower)
+    };
+
+    // keeps the case of the word: `KEY` → `KEYS`, `Child` → `Children`
+    let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+    match word.chars().next() {
+        _ if letters > 1 && word.chars().all(|c| !c.is_lowercase()) => plural.to_uppercase(),
+        Some(first) if first.is_uppercase() => capitalize(&plural),
+        _ => plural,
+    }
+}
+
+/// Formats a count followed by a noun, pluralized unless the count is 1.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::pluralize_count;
+///
+/// assert_eq!(pluralize_count(0, "entry"), "0 entries");
+/// assert_eq!(pluralize_count(1, "entry"), "1 entry");
+/// ```
+pub fn pluralize_count(count: usize, word: &str) -> String {
+    match count {
+        1 => format!("1 {}", word),
+        _ => format!("{} {}", count, pluralize(word)),
+    }
+}
+
+/// Shortens a string to at most `width` visible characters, ending it with `…` when it's cut.
+///
+/// ANSI escape codes don't count towards the width and are kept, so styled text stays styled;
+/// a reset code is added after the ellipsis if the string had any.
+///
+/// # Arguments
+///
+/// * `s` - The string, which may contain ANSI escape codes
+/// * `width` - The maximum number of visible characters, including the ellipsis
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::truncate_with_ellipsis;
+/// use dev_utils::format::visual_length;
+///
+/// assert_eq!(truncate_with_ellipsis("short", 10), "short");
+/// assert_eq!(truncate_with_ellipsis("\x1b[31mred text\x1b[0m", 4), "\x1b[31mred…\x1b[0m");
+/// assert_eq!(visual_length(&truncate_with_ellipsis("\x1b[1mbold and long\x1b[0m", 6)), 6);
+/// ```
+pub fn truncate_with_ellipsis(s: &str, width: usize) -> String {
+    if visual_length(s) <= width {return s.to_string();}
+    if width == 0 {return String::new();}
+
+    let mut out = String::new();
+    let (mut visible, mut styled) = (0, false);
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            // copies the whole escape sequence (`ESC [ params m`)
+            out.push(c);
+            for c in chars.by_ref() {
+                out.push(c);
+                if !(c == '[' || c == ';' || c.is_ascii_digit()) {break;}
+            }
+            styled = true;
+            continue;
+        }
+        if visible == width - 1 {break;}
+        out.push(c);
+        visible += 1;
+    }
+    out.push('…');
+    if styled {out.push_str("\x1b[0m");}
+    out
+}
+
+/// Returns the Levenshtein distance between two strings: the number of characters to
+/// insert, delete or substitute to turn one into the other.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::levenshtein;
+///
+/// assert_eq!(levenshtein("kitten", "sitting"), 3);
+/// assert_eq!(levenshtein("", "abc"), 3);
+/// ```
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // the distances from the previous prefix of `a` to every prefix of `b`
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Returns how similar two strings are, from `0.0` (nothing in common) to `1.0` (equal).
+///
+/// This is the Levenshtein distance relative to the length of the longest string.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::similarity;
+///
+/// assert_eq!(similarity("test", "test"), 1.0);
+/// assert_eq!(similarity("test", "text"), 0.75);
+/// assert_eq!(similarity("abc", "xyz"), 0.0);
+/// ```
+pub fn similarity(a: &str, b: &str) -> f64 {
+    match a.chars().count().max(b.chars().count()) {
+        0 => 1.0,
+        len => 1.0 - levenshtein(a, b) as f64 / len as f64,
+    }
+}
+
+/// The score a match of [fuzzy_find] must exceed.
+const FUZZY_THRESHOLD: f64 = 0.5;
+
+/// Finds the strings of `haystack` that look like `needle`, best matches first.
+///
+/// Matching ignores case. A string scores its [similarity] to the needle, at least `0.8`
+/// if it starts with the needle and `0.7` if it contains it, so abbreviations are found too.
+/// Strings scoring `0.5` or less are left out.
+///
+/// # Arguments
+///
+/// * `needle` - The string to look for, e.g. a mistyped command
+/// * `haystack` - The candidates
+///
+/// # Returns
+///
+/// The `(index, score)` pairs of the matching candidates, sorted by decreasing score
+/// (and by index for equal scores).
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::text::fuzzy_find;
+///
+/// let options = ["--verbose", "--version", "--quiet"];
+/// assert_eq!(fuzzy_find("--verison", &options)[0].0, 1);
+/// assert_eq!(fuzzy_find("--verb", &options)[0].0, 0);
+/// assert!(fuzzy_find("--color", &options).is_empty());
+/// ```
+pub fn fuzzy_find(needle: &str, haystack: &[&str]) -> Vec<(usize, f64)> {
+    let needle = needle.to_lowercase();
+    let mut matches: Vec<(usize, f64)> = haystack.iter().enumerate()
+        .map(|(i, candidate)| {
+            let candidate = candidate.to_lowercase();
+            let bonus = match () {
+                _ if needle.is_empty() => 0.0,
+                _ if candidate.starts_with(&needle) => 0.8,
+                _ if candidate.contains(&needle) => 0.7,
+                _ => 0.0,
+            };
+            (i, similarity(&needle, &candidate).max(bonus))
+        })
+        .filter(|&(_, score)| score > FUZZY_THRESHOLD)
+        .collect();
+    matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    matches
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words("XMLHttpRequest"), ["XML", "Http", "Request"]);
+        assert_eq!(words("version2Beta"), ["version2", "Beta"]);
+        assert_eq!(words("SCREAMING_SNAKE"), ["SCREAMING", "SNAKE"]);
+        assert!(words(" _- ").is_empty());
+    }
+
+    #[test]
+    fn test_case_conversion() {
+        for input in ["user account id", "UserAccountId", "user_account_id", "user-account-id", "userAccountID"] {
+            assert_eq!(to_snake_case(input), "user_account_id");
+            assert_eq!(to_kebab_case(input), "user-account-id");
+            assert_eq!(to_camel_case(input), "userAccountId");
+            assert_eq!(to_title_case(input), "User Account Id");
+        }
+        assert_eq!(to_camel_case(""), "");
+    }
+
+    #[test]
+    fn test_pluralize() {
+        let cases = [
+            ("day", "days"), ("city", "cities"), ("bus", "buses"), ("dish", "dishes"), ("box", "boxes"),
+            ("leaf", "leaves"), ("knife", "knives"), ("roof", "roofs"), ("person", "people"), ("sheep", "sheep"),
+            ("Person", "People"), ("BOX", "BOXES"), ("y", "ys"),
+            ("quiz", "quizzes"), ("buzz", "buzzes"), ("waltz", "waltzes"), ("Quiz", "Quizzes"),
+            ("hero", "heroes"), ("potato", "potatoes"), ("echo", "echoes"), ("repo", "repos"), ("photo", "photos"),
+        ];
+        for (singular, plural) in cases {
+            assert_eq!(pluralize(singular), plural, "plural of {}", singular);
+        }
+        assert_eq!(pluralize_count(2, "test"), "2 tests");
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+        assert_eq!(levenshtein("saturday", "sunday"), 3);
+        assert_eq!(levenshtein("héllo", "hello"), 1);
+        assert_eq!(levenshtein("abc", "abc"), 0);
+        assert_eq!(similarity("", ""), 1.0);
+    }
+
+    #[test]
+    fn test_fuzzy_find() {
+        let commands = ["install", "uninstall", "init", "info"];
+        let found = fuzzy_find("INSTAL", &commands);
+        assert_eq!(found.iter().map(|&(i, _)| i).collect::<Vec<_>>(), [0, 1]);
+        assert!(found[0].1 > found[1].1);
+        assert_eq!(fuzzy_find("inf", &commands)[0], (3, 0.8));
+        assert!(fuzzy_find("xyz", &commands).is_empty());
+        assert!(fuzzy_find("a", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate_with_ellipsis("hello world", 5), "hell…");
+        assert_eq!(truncate_with_ellipsis("hello", 5), "hello");
+        assert_eq!(truncate_with_ellipsis("hello", 1), "…");
+        assert_eq!(truncate_with_ellipsis("hello", 0), "");
+        assert_eq!(truncate_with_ellipsis("héllo wörld", 4), "hél…");
+
+        let styled = "\x1b[1mab\x1b[0m\x1b[32mcdef\x1b[0m";
+        assert_eq!(truncate_with_ellipsis(styled, 4), "\x1b[1mab\x1b[0m\x1b[32mc…\x1b[0m");
+    }
+}
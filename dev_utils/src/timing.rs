@@ -0,0 +1,228 @@
+//! Rate limiting of closures: debounce and throttle.
+//!
+//! - [throttle] runs a closure at most once per interval (extra calls are dropped)
+//! - [debounce] runs a closure once calls stop for a while, with the last argument
+//!
+//! Neither spawns threads: a [Debounced] closure runs from [Debounced::poll], which fits
+//! event loops such as one built on [poll_key](crate::console::events::poll_key).
+//! Time is read from a [Clock], so tests can use a [ManualClock] instead of sleeping.
+//!
+//! # Examples
+//! ```
+//! use std::time::Duration;
+//! use dev_utils::timing::{ManualClock, Throttled};
+//!
+//! let clock = ManualClock::new();
+//! let mut save = Throttled::with_clock(Duration::from_secs(1), |n: u32| n * 2, clock.clone());
+//! assert_eq!(save.call(1), Some(2));
+//! assert_eq!(save.call(2), None);  // too soon
+//! clock.advance(Duration::from_secs(1));
+//! assert_eq!(save.call(3), Some(6));
+//! ```
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A monotonic source of time.
+pub trait Clock {
+    /// Returns the time elapsed since an arbitrary (but fixed) origin.
+    fn now(&self) -> Duration;
+}
+
+/// The system's monotonic clock (based on [Instant]).
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    origin: Instant,
+}
+
+impl MonotonicClock {
+    pub fn new() -> Self {MonotonicClock { origin: Instant::now() }}
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {Self::new()}
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> Duration {self.origin.elapsed()}
+}
+
+/// A clock that only moves when told to, for tests.
+///
+/// Clones share the same time, so a test can keep a clone to advance the clock
+/// given to a [Throttled] or [Debounced] closure.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// Creates a clock at time zero.
+    pub fn new() -> Self {Self::default()}
+
+    /// Moves the clock forward.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {*self.now.lock().unwrap_or_else(PoisonError::into_inner)}
+}
+
+/// A closure that runs at most once per interval, created by [throttle].
+pub struct Throttled<F, C = MonotonicClock> {
+    f: F,
+    interval: Duration,
+    last_run: Option<Duration>,
+    clock: C,
+}
+
+/// Wraps a closure so that it runs at most once per `interval`.
+///
+/// The first call runs immediately; calls made before `interval` has passed since the
+/// last run are dropped.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::timing::throttle;
+///
+/// let mut redraw = throttle(Duration::from_secs(60), |frame: u32| println!("frame {frame}"));
+/// assert!(redraw.call(1).is_some());
+/// assert!(redraw.call(2).is_none());
+/// ```
+pub fn throttle<A, R, F: FnMut(A) -> R>(interval: Duration, f: F) -> Throttled<F> {
+    Throttled::with_clock(interval, f, MonotonicClock::new())
+}
+
+impl<F, C: Clock> Throttled<F, C> {
+    /// Creates a throttled closure reading time from the given [Clock].
+    pub fn with_clock(interval: Duration, f: F, clock: C) -> Self {
+        Throttled { f, interval, last_run: None, clock }
+    }
+
+    /// Runs the closure if the interval has passed since its last run.
+    ///
+    /// # Returns
+    ///
+    /// Returns the value of the closure, or `None` if the call was dropped.
+    pub fn call<A, R>(&mut self, arg: A) -> Option<R> where F: FnMut(A) -> R {
+        let now = self.clock.now();
+        match self.last_run {
+            Some(last) if now.saturating_sub(last) < self.interval => None,
+            _ => {
+                self.last_run = Some(now);
+                Some((self.f)(arg))
+            },
+        }
+    }
+
+    /// Returns the time left until a call would run (zero if it would run now).
+    pub fn time_until_ready(&self) -> Duration {
+        self.last_run.map_or(Duration::ZERO, |last| (last + self.interval).saturating_sub(self.clock.now()))
+    }
+}
+
+/// A closure that runs once calls stop for a while, created by [debounce].
+pub struct Debounced<A, F, C = MonotonicClock> {
+    f: F,
+    wait: Duration,
+    pending: Option<(A, Duration)>,
+    clock: C,
+}
+
+/// Wraps a closure so that it only runs once `wait` has passed without new calls.
+///
+/// [Debounced::call] only records the argument; the closure runs with the last recorded
+/// argument from [Debounced::poll] (or [Debounced::flush]), which should be called regularly.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use dev_utils::{console::events::poll_key, console::events::Key, timing::debounce};
+///
+/// let mut search = debounce(Duration::from_millis(300), |query: String| println!("searching {query}"));
+/// let mut query = String::new();
+/// loop {
+///     if let Some(Key::Char(c)) = poll_key(Duration::from_millis(50)).unwrap() {
+///         query.push(c);
+///         search.call(query.clone());
+///     }
+///     search.poll();
+/// }
+/// ```
+pub fn debounce<A, R, F: FnMut(A) -> R>(wait: Duration, f: F) -> Debounced<A, F> {
+    Debounced::with_clock(wait, f, MonotonicClock::new())
+}
+
+impl<A, F, C: Clock> Debounced<A, F, C> {
+    /// Creates a debounced closure reading time from the given [Clock].
+    pub fn with_clock(wait: Duration, f: F, clock: C) -> Self {
+        Debounced { f, wait, pending: None, clock }
+    }
+
+    /// Records a call, replacing the pending argument and restarting the wait.
+    pub fn call(&mut self, arg: A) {
+        self.pending = Some((arg, self.clock.now() + self.wait));
+    }
+
+    /// Returns whether a call is waiting to run.
+    pub fn is_pending(&self) -> bool {self.pending.is_some()}
+
+    /// Runs the closure if a call is pending and the wait is over.
+    ///
+    /// # Returns
+    ///
+    /// Returns the value of the closure, or `None` if it didn't run.
+    pub fn poll<R>(&mut self) -> Option<R> where F: FnMut(A) -> R {
+        match &self.pending {
+            Some((_, deadline)) if self.clock.now() >= *deadline => self.flush(),
+            _ => None,
+        }
+    }
+
+    /// Runs the closure now if a call is pending, without waiting.
+    pub fn flush<R>(&mut self) -> Option<R> where F: FnMut(A) -> R {
+        self.pending.take().map(|(arg, _)| (self.f)(arg))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle() {
+        let clock = ManualClock::new();
+        let mut runs = Vec::new();
+        let mut throttled = Throttled::with_clock(Duration::from_millis(100), |n| runs.push(n), clock.clone());
+        for n in 0..10 {
+            throttled.call(n);
+            clock.advance(Duration::from_millis(30));
+        }
+        assert_eq!(throttled.time_until_ready(), Duration::from_millis(40));
+        drop(throttled);
+        assert_eq!(runs, vec![0, 4, 8]);
+    }
+
+    #[test]
+    fn test_debounce() {
+        let clock = ManualClock::new();
+        let mut debounced = Debounced::with_clock(Duration::from_millis(100), |s: &str| s.len(), clock.clone());
+        assert_eq!(debounced.poll(), None);
+
+        debounced.call("a");
+        clock.advance(Duration::from_millis(60));
+        debounced.call("ab");
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(debounced.poll(), None);  // the second call restarted the wait
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(debounced.poll(), Some(2));
+        assert!(!debounced.is_pending());
+
+        debounced.call("abc");
+        assert_eq!(debounced.flush(), Some(3));
+        assert_eq!(debounced.flush(), None);
+    }
+}
@@ -0,0 +1,367 @@
+//! Typed unit conversions: data sizes, temperatures, lengths and durations.
+//!
+//! - [Bytes] - Data sizes with decimal (`kB`, `MB`, ...) and binary (`KiB`, `MiB`, ...) units,
+//!   parsed from strings like `"1.5 GiB"`
+//! - [Temperature] - Celsius, Fahrenheit and Kelvin
+//! - [Length] - Metric and imperial lengths
+//! - [parse_duration] and [format_duration] - Durations written like `"1h30m"` or `"250ms"`
+//!
+//! # Examples
+//! ```
+//! use std::time::Duration;
+//! use dev_utils::units::*;
+//! use dev_utils::retry::RetryPolicy;
+//!
+//! assert_eq!(Bytes::from_mib(4).as_bytes(), 4 * 1024 * 1024);
+//! assert_eq!("1.5 KiB".parse::<Bytes>().unwrap().as_bytes(), 1536);
+//! assert_eq!(Temperature::celsius(30).to_fahrenheit(), 86.0);
+//! assert_eq!(Length::miles(1).to_kilometers(), 1.609344);
+//!
+//! let timeout = parse_duration("1h30m").unwrap();
+//! assert_eq!(timeout, Duration::from_secs(90 * 60));
+//! let policy = RetryPolicy::fixed(parse_duration("250ms").unwrap());  // e.g. from a config file
+//! assert_eq!(policy.delay(1), Duration::from_millis(250));
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use crate::format::human_bytes;
+
+/// Represents errors that can occur when parsing a value with a unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnitError {
+    /// The input is empty or a number is missing or malformed.
+    InvalidNumber(String),
+    /// The unit is not known.
+    UnknownUnit(String),
+    /// The value is negative or too large.
+    OutOfRange(String),
+}
+
+impl fmt::Display for UnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitError::InvalidNumber(s) => write!(f, "invalid number: {:?}", s),
+            UnitError::UnknownUnit(s) => write!(f, "unknown unit: {:?}", s),
+            UnitError::OutOfRange(s) => write!(f, "value out of range: {:?}", s),
+        }
+    }
+}
+
+impl Error for UnitError {}
+
+// Splits `"1.5GiB"` or `"1.5 GiB"` into its number and unit
+fn split_number(s: &str) -> Result<(f64, &str), UnitError> {
+    let s = s.trim();
+    let end = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let number = s[..end].parse::<f64>().map_err(|_| UnitError::InvalidNumber(s.to_string()))?;
+    Ok((number, s[end..].trim_start()))
+}
+
+/// A data size, in bytes.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::units::Bytes;
+///
+/// let size = Bytes::from_gb(2);
+/// assert_eq!(size.as_mb(), 2000.0);
+/// assert_eq!(size.to_string(), "1.9 GiB");
+/// assert_eq!("512".parse::<Bytes>(), Ok(Bytes::from_bytes(512)));
+/// assert_eq!("4mb".parse::<Bytes>(), Ok(Bytes::from_mb(4)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Bytes(u64);
+
+// The units of `Bytes` (lowercase), with the number of bytes they stand for
+const BYTE_UNITS: [(&str, u64); 13] = [
+    ("b", 1),
+    ("kb", 1_000), ("mb", 1_000_000), ("gb", 1_000_000_000), ("tb", 1_000_000_000_000),
+    ("kib", 1 << 10), ("mib", 1 << 20), ("gib", 1 << 30), ("tib", 1 << 40),
+    ("k", 1 << 10), ("m", 1 << 20), ("g", 1 << 30), ("t", 1 << 40),
+];
+
+macro_rules! byte_units {
+    ($($from:ident $as:ident => $factor:expr, $unit:literal;)*) => {$(
+        #[doc = concat!("Creates a size from a number of ", $unit, " (saturating at `u64::MAX` bytes).")]
+        pub const fn $from(n: u64) -> Self {Bytes(n.saturating_mul($factor))}
+
+        #[doc = concat!("Returns the size in ", $unit, ".")]
+        pub fn $as(&self) -> f64 {
+            const FACTOR: u64 = $factor;
+            self.0 as f64 / FACTOR as f64
+        }
+    )*};
+}
+
+impl Bytes {
+    /// Creates a size from a number of bytes.
+    pub const fn from_bytes(n: u64) -> Self {Bytes(n)}
+
+    /// Returns the size in bytes.
+    pub const fn as_bytes(&self) -> u64 {self.0}
+
+    byte_units! {
+        from_kb as_kb => 1_000, "kilobytes (1000 bytes)";
+        from_mb as_mb => 1_000_000, "megabytes (1000² bytes)";
+        from_gb as_gb => 1_000_000_000, "gigabytes (1000³ bytes)";
+        from_tb as_tb => 1_000_000_000_000, "terabytes (1000⁴ bytes)";
+        from_kib as_kib => 1 << 10, "kibibytes (1024 bytes)";
+        from_mib as_mib => 1 << 20, "mebibytes (1024² bytes)";
+        from_gib as_gib => 1 << 30, "gibibytes (1024³ bytes)";
+        from_tib as_tib => 1 << 40, "tebibytes (1024⁴ bytes)";
+    }
+}
+
+/// Formats the size with binary units, like [human_bytes].
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&human_bytes(self.0))
+    }
+}
+
+/// Parses a size like `"512"`, `"4 MB"` or `"1.5GiB"`.
+///
+/// Units are case-insensitive; `kB`, `MB`, ... are powers of 1000, and `KiB`, `MiB`, ...
+/// (or the short `K`, `M`, `G`, `T`) powers of 1024. Fractions are rounded down to a byte.
+impl FromStr for Bytes {
+    type Err = UnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (number, unit) = split_number(s)?;
+        let unit = unit.to_lowercase();
+        let factor = match unit.as_str() {
+            "" => 1,
+            unit => BYTE_UNITS.iter().find(|(name, _)| *name == unit).map(|(_, factor)| *factor)
+                .ok_or_else(|| UnitError::UnknownUnit(unit.to_string()))?,
+        };
+        let bytes = number * factor as f64;
+        match bytes < u64::MAX as f64 {
+            true => Ok(Bytes(bytes as u64)),
+            false => Err(UnitError::OutOfRange(s.to_string())),
+        }
+    }
+}
+
+/// A temperature, convertible between Celsius, Fahrenheit and Kelvin.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::units::Temperature;
+///
+/// assert_eq!(Temperature::fahrenheit(212).to_celsius(), 100.0);
+/// assert_eq!(Temperature::kelvin(0).to_celsius(), -273.15);
+/// assert!(Temperature::celsius(20) < Temperature::fahrenheit(70));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Temperature {
+    kelvin: f64,
+}
+
+impl Temperature {
+    /// Creates a temperature in degrees Celsius.
+    pub fn celsius<T: Into<f64>>(degrees: T) -> Self {Temperature { kelvin: degrees.into() + 273.15 }}
+
+    /// Creates a temperature in degrees Fahrenheit.
+    pub fn fahrenheit<T: Into<f64>>(degrees: T) -> Self {Self::celsius((degrees.into() - 32.0) * 5.0 / 9.0)}
+
+    /// Creates a temperature in Kelvin.
+    pub fn kelvin<T: Into<f64>>(kelvin: T) -> Self {Temperature { kelvin: kelvin.into() }}
+
+    /// Returns the temperature in degrees Celsius.
+    pub fn to_celsius(&self) -> f64 {round(self.kelvin - 273.15)}
+
+    /// Returns the temperature in degrees Fahrenheit.
+    pub fn to_fahrenheit(&self) -> f64 {round((self.kelvin - 273.15) * 9.0 / 5.0 + 32.0)}
+
+    /// Returns the temperature in Kelvin.
+    pub fn to_kelvin(&self) -> f64 {round(self.kelvin)}
+}
+
+// Rounds away the floating point noise of the conversions (`86.00000000000001`)
+fn round(x: f64) -> f64 {(x * 1e9).round() / 1e9}
+
+/// A length, convertible between metric and imperial units.
+///
+/// # Examples
+///
+/// ```
+/// use dev_utils::units::Length;
+///
+/// assert_eq!(Length::feet(3).to_yards(), 1.0);
+/// assert_eq!(Length::inches(1).to_millimeters(), 25.4);
+/// assert_eq!(Length::kilometers(1.5).to_meters(), 1500.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Length {
+    meters: f64,
+}
+
+macro_rules! length_units {
+    ($($new:ident $to:ident => $meters:expr, $unit:literal;)*) => {$(
+        #[doc = concat!("Creates a length in ", $unit, ".")]
+        pub fn $new<T: Into<f64>>(n: T) -> Self {Length { meters: n.into() * $meters }}
+
+        #[doc = concat!("Returns the length in ", $unit, ".")]
+        pub fn $to(&self) -> f64 {round(self.meters / $meters)}
+    )*};
+}
+
+impl Length {
+    length_units! {
+        millimeters to_millimeters => 0.001, "millimeters";
+        centimeters to_centimeters => 0.01, "centimeters";
+        meters to_meters => 1.0, "meters";
+        kilometers to_kilometers => 1000.0, "kilometers";
+        inches to_inches => 0.0254, "inches";
+        feet to_feet => 0.3048, "feet";
+        yards to_yards => 0.9144, "yards";
+        miles to_miles => 1609.344, "miles";
+    }
+}
+
+// The units of a duration, with their length in nanoseconds
+const DURATION_UNITS: [(&str, u128); 12] = [
+    ("ns", 1), ("us", 1_000), ("µs", 1_000), ("ms", 1_000_000),
+    ("s", 1_000_000_000), ("sec", 1_000_000_000), ("m", 60_000_000_000), ("min", 60_000_000_000),
+    ("h", 3_600_000_000_000), ("hr", 3_600_000_000_000), ("d", 86_400_000_000_000), ("w", 604_800_000_000_000),
+];
+
+/// Parses a duration made of one or more `<number><unit>` parts, like `"1h30m"`,
+/// `"2d 4h"`, `"1.5s"` or `"250ms"`.
+///
+/// The units are `ns`, `us` (or `µs`), `ms`, `s` (or `sec`), `m` (or `min`), `h` (or `hr`),
+/// `d` and `w`. A plain `"0"` is also accepted.
+///
+/// # Returns
+///
+/// A `Result` containing the [Duration], or a [UnitError] describing the invalid part.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::units::parse_duration;
+///
+/// assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+/// assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+/// assert_eq!(parse_duration("2m 10s").unwrap(), Duration::from_secs(130));
+/// assert!(parse_duration("10").is_err());  // the unit is required
+/// assert!(parse_duration("5 parsecs").is_err());
+/// ```
+pub fn parse_duration(s: &str) -> Result<Duration, UnitError> {
+    if s.trim() == "0" {return Ok(Duration::ZERO);}
+    if s.trim().is_empty() {return Err(UnitError::InvalidNumber(s.to_string()));}
+    let mut rest = s.trim();
+    let mut nanos: f64 = 0.0;
+    while !rest.is_empty() {
+        let (number, tail) = split_number(rest)?;
+        let unit_len = tail.find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace()).unwrap_or(tail.len());
+        let unit = &tail[..unit_len];
+        let factor = DURATION_UNITS.iter().find(|(name, _)| *name == unit).map(|(_, factor)| *factor)
+            .ok_or_else(|| match unit {
+                "" => UnitError::InvalidNumber(s.to_string()),
+                unit => UnitError::UnknownUnit(unit.to_string()),
+            })?;
+        nanos += number * factor as f64;
+        rest = tail[unit_len..].trim_start();
+    }
+    match nanos < u64::MAX as f64 {
+        true => Ok(Duration::from_nanos(nanos.round() as u64)),
+        false => Err(UnitError::OutOfRange(s.to_string())),
+    }
+}
+
+/// Formats a duration in the format read by [parse_duration], with the largest units first.
+///
+/// Durations under a second use a single unit (`ms`, `µs` or `ns`); longer ones are written
+/// in days, hours, minutes and seconds, keeping milliseconds as a fraction of the seconds.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dev_utils::units::format_duration;
+///
+/// assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+/// assert_eq!(format_duration(Duration::from_millis(90_500)), "1m30.5s");
+/// assert_eq!(format_duration(Duration::from_micros(250)), "250µs");
+/// assert_eq!(format_duration(Duration::ZERO), "0s");
+/// ```
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    match nanos {
+        0 => return "0s".to_string(),
+        n if n < 1_000 => return format!("{}ns", n),
+        n if n < 1_000_000 => return format!("{}µs", n / 1_000),
+        n if n < 1_000_000_000 => return format!("{}ms", n / 1_000_000),
+        _ => {},
+    }
+    let secs = duration.as_secs();
+    let (days, hours, minutes, seconds) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60);
+    let millis = duration.subsec_millis();
+    let mut out = String::new();
+    for (value, unit) in [(days, "d"), (hours, "h"), (minutes, "m")] {
+        if value > 0 {out.push_str(&format!("{}{}", value, unit));}
+    }
+    match (seconds, millis) {
+        (0, 0) => {},
+        (s, 0) => out.push_str(&format!("{}s", s)),
+        (s, ms) => out.push_str(&format!("{}s", format!("{}.{:03}", s, ms).trim_end_matches('0'))),
+    }
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(Bytes::from_kib(1).as_bytes(), 1024);
+        assert_eq!(Bytes::from_mib(1).as_kib(), 1024.0);
+        assert_eq!(Bytes::from_tib(u64::MAX).as_bytes(), u64::MAX);
+        assert_eq!("1.5GiB".parse::<Bytes>(), Ok(Bytes::from_mib(1536)));
+        assert_eq!(" 10 KB ".parse::<Bytes>(), Ok(Bytes::from_bytes(10_000)));
+        assert_eq!("2G".parse::<Bytes>(), Ok(Bytes::from_gib(2)));
+        assert_eq!("1 parsec".parse::<Bytes>(), Err(UnitError::UnknownUnit("parsec".to_string())));
+        assert_eq!("MB".parse::<Bytes>(), Err(UnitError::InvalidNumber("MB".to_string())));
+        assert!("-1MB".parse::<Bytes>().is_err());
+        assert!("99999999TB".parse::<Bytes>().is_err());
+    }
+
+    #[test]
+    fn test_temperature_and_length() {
+        assert_eq!(Temperature::celsius(-40).to_fahrenheit(), -40.0);
+        assert_eq!(Temperature::celsius(0).to_kelvin(), 273.15);
+        assert_eq!(Temperature::fahrenheit(98.6).to_celsius(), 37.0);
+        assert_eq!(Length::miles(1).to_feet(), 5280.0);
+        assert_eq!(Length::centimeters(254).to_inches(), 100.0);
+    }
+
+    #[test]
+    fn test_durations() {
+        let cases = [
+            ("1w", 604_800_000), ("1d 2h", 93_600_000), ("1.5h", 5_400_000), ("90s", 90_000), ("1m30.5s", 90_500),
+            ("0.5ms", 0), ("2min 3sec", 123_000), ("0", 0),
+        ];
+        for (input, millis) in cases {
+            assert_eq!(parse_duration(input).unwrap().as_millis(), millis, "{}", input);
+        }
+        assert_eq!(parse_duration("1500ns").unwrap(), Duration::from_nanos(1500));
+        assert_eq!(parse_duration("3µs").unwrap(), parse_duration("3us").unwrap());
+        assert_eq!(parse_duration(""), Err(UnitError::InvalidNumber(String::new())));
+        assert_eq!(parse_duration("1h 5"), Err(UnitError::InvalidNumber("1h 5".to_string())));
+        assert_eq!(parse_duration("5y"), Err(UnitError::UnknownUnit("y".to_string())));
+
+        for millis in [1, 999, 1_000, 61_001, 3_600_000, 90_061_250, 8 * 86_400_000] {
+            let duration = Duration::from_millis(millis);
+            assert_eq!(parse_duration(&format_duration(duration)).unwrap(), duration, "{}", format_duration(duration));
+        }
+        assert_eq!(format_duration(Duration::from_secs(86_400 + 5)), "1d5s");
+    }
+}
@@ -0,0 +1,170 @@
+//! Version control metadata: the git commit, branch and dirty state of a working tree.
+//!
+//! The commit and branch are read directly from the `.git` directory (`HEAD`, loose refs
+//! and `packed-refs`), so they are available even without a `git` executable. Only the
+//! dirty state needs one (`git status`).
+//!
+//! # Examples
+//! ```
+//! use dev_utils::vcs::{build_profile, git_info};
+//!
+//! if let Some(git) = git_info(file!()) {
+//!     println!("built from {} ({})", git, build_profile());
+//! }
+//! ```
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The state of a git working tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitInfo {
+    /// The full hash of the checked out commit.
+    pub commit: String,
+    /// The checked out branch (`None` when the HEAD is detached).
+    pub branch: Option<String>,
+    /// Whether there are uncommitted changes (`None` if `git` could not be run).
+    pub dirty: Option<bool>,
+}
+
+impl GitInfo {
+    /// Returns the abbreviated (7 characters) commit hash.
+    pub fn short_commit(&self) -> &str {&self.commit[..self.commit.len().min(7)]}
+}
+
+impl fmt::Display for GitInfo {
+    /// Formats as `abc1234`, with a `-dirty` suffix and the branch when known: `abc1234-dirty (main)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.short_commit())?;
+        if self.dirty == Some(true) {write!(f, "-dirty")?;}
+        match &self.branch {
+            Some(branch) => write!(f, " ({})", branch),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Finds the `.git` directory of the repository containing a path.
+///
+/// The parents of `start_path` are searched first, then the current directory and its
+/// parents. A `.git` file (used by worktrees and submodules) is followed to the directory
+/// it points to.
+pub fn find_git_dir(start_path: &str) -> Option<PathBuf> {
+    find_repository(start_path).map(|(_, git_dir)| git_dir)
+}
+
+// Returns the working tree and the git directory of the repository containing a path
+fn find_repository(start_path: &str) -> Option<(PathBuf, PathBuf)> {
+    let current_dir = env::current_dir().ok()?;
+    Path::new(start_path).ancestors().skip(1)
+        .chain(current_dir.ancestors())
+        .find_map(|dir| {
+            let git = dir.join(".git");
+            let git_dir = match git.is_file() {
+                true => dir.join(fs::read_to_string(&git).ok()?.strip_prefix("gitdir:")?.trim()),
+                false => git,
+            };
+            git_dir.join("HEAD").is_file().then(|| (dir.to_path_buf(), git_dir))
+        })
+}
+
+/// Reads the git state of the repository containing a path (see [find_git_dir]).
+///
+/// # Arguments
+///
+/// * `start_path` - A path inside the repository, usually `file!()`
+///
+/// # Returns
+///
+/// Returns the [GitInfo], or `None` if the path is not inside a git repository (or it has no commits).
+pub fn git_info(start_path: &str) -> Option<GitInfo> {
+    let (work_tree, git_dir) = find_repository(start_path)?;
+    read_git_dir(&git_dir, &work_tree)
+}
+
+fn read_git_dir(git_dir: &Path, work_tree: &Path) -> Option<GitInfo> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let (commit, branch) = match head.strip_prefix("ref:") {
+        Some(reference) => {
+            let reference = reference.trim();
+            let branch = reference.strip_prefix("refs/heads/").unwrap_or(reference).to_string();
+            (resolve_ref(git_dir, reference)?, Some(branch))
+        },
+        None => (head.to_string(), None),
+    };
+    Some(GitInfo { commit, branch, dirty: is_dirty(work_tree) })
+}
+
+// Resolves a reference from its loose file or from `packed-refs`
+fn resolve_ref(git_dir: &Path, reference: &str) -> Option<String> {
+    if let Ok(hash) = fs::read_to_string(git_dir.join(reference)) {return Some(hash.trim().to_string());}
+    // worktrees keep the shared refs in the main repository
+    let common_dir = fs::read_to_string(git_dir.join("commondir")).ok()
+        .map_or(git_dir.to_path_buf(), |dir| git_dir.join(dir.trim()));
+    if common_dir != git_dir {
+        if let Ok(hash) = fs::read_to_string(common_dir.join(reference)) {return Some(hash.trim().to_string());}
+    }
+    fs::read_to_string(common_dir.join("packed-refs")).ok()?.lines()
+        .filter(|line| !line.starts_with(['#', '^']))
+        .find_map(|line| line.split_once(' ').filter(|(_, name)| *name == reference).map(|(hash, _)| hash.to_string()))
+}
+
+// Asks git whether the working tree has uncommitted changes
+fn is_dirty(work_tree: &Path) -> Option<bool> {
+    let output = Command::new("git")
+        .arg("-C").arg(work_tree)
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .stderr(Stdio::null())
+        .output().ok()?;
+    output.status.success().then_some(!output.stdout.is_empty())
+}
+
+/// Returns the build profile, `"debug"` or `"release"` (based on `debug_assertions`).
+pub fn build_profile() -> &'static str {
+    match cfg!(debug_assertions) {
+        true => "debug",
+        false => "release",
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::{self, TempDir};
+
+    const HASH: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    #[test]
+    fn test_read_git_dir() {
+        let dir = TempDir::new().unwrap();
+        let git = dir.join(".git");
+        file::create(git.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        file::create(git.join("refs/heads/main"), &format!("{}\n", HASH)).unwrap();
+
+        let info = read_git_dir(&git, dir.path()).unwrap();
+        assert_eq!(info.commit, HASH);
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert_eq!(info.short_commit(), "0123456");
+        assert!(info.to_string().starts_with("0123456"));
+
+        // packed refs
+        file::delete(git.join("refs/heads/main")).unwrap();
+        file::create(git.join("packed-refs"), &format!("# pack-refs\n{} refs/heads/main\n", HASH)).unwrap();
+        assert_eq!(read_git_dir(&git, dir.path()).unwrap().commit, HASH);
+
+        // detached HEAD
+        file::update(git.join("HEAD"), HASH).unwrap();
+        assert_eq!(read_git_dir(&git, dir.path()).unwrap().branch, None);
+    }
+
+    #[test]
+    fn test_display() {
+        let info = GitInfo { commit: HASH.into(), branch: Some("dev".into()), dirty: Some(true) };
+        assert_eq!(info.to_string(), "0123456-dirty (dev)");
+        assert_eq!(GitInfo { branch: None, dirty: Some(false), ..info }.to_string(), "0123456");
+    }
+}